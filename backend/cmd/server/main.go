@@ -28,6 +28,12 @@ import (
 )
 
 func getWorkDir() string {
+	// Flatpak/Snap 等受限环境由桌面端指定数据目录，保证与壳层一致且能持久化
+	if dir := strings.TrimSpace(os.Getenv("APP_DATA_DIR")); dir != "" {
+		if err := os.MkdirAll(dir, 0755); err == nil {
+			return dir
+		}
+	}
 	// 如果是作为 Tauri 边车运行，使用用户目录下的应用支持目录
 	if platform.IsTauriSidecar() {
 		configDir, err := os.UserConfigDir()
@@ -184,6 +190,7 @@ func main() {
 	config.InitConfig()
 
 	// 2. 初始化数据库
+	model.ReadOnly = config.GlobalConfig.Database.ReadOnly
 	model.InitDB(config.GlobalConfig.Database.Path)
 
 	// 3. 初始化存储
@@ -198,6 +205,7 @@ func main() {
 		}
 	}
 	storage.InitStorage(config.GlobalConfig.Storage.LocalDir, ossConfig)
+	storage.SetMinFreeMB(config.GlobalConfig.Storage.MinFreeMB)
 
 	// 3.5 初始化模板市场
 	templates.InitStore(templates.Options{
@@ -273,11 +281,27 @@ func main() {
 		c.Next()
 	})
 
+	// 只读安全模式下拒绝所有写请求，返回明确提示而不是 SQLite 的底层报错
+	if model.ReadOnly {
+		r.Use(func(c *gin.Context) {
+			if c.Request.Method == http.MethodGet || c.Request.Method == http.MethodHead {
+				c.Next()
+				return
+			}
+			c.AbortWithStatusJSON(http.StatusServiceUnavailable, gin.H{
+				"code":    503,
+				"message": "database is read-only (safe mode)",
+				"data":    nil,
+			})
+		})
+	}
+
 	v1 := r.Group("/api/v1")
 	{
 		v1.GET("/health", func(c *gin.Context) {
 			api.Success(c, gin.H{"status": "ok", "message": "ok"})
 		})
+		v1.GET("/version", api.VersionHandler)
 		v1.GET("/templates", api.ListTemplatesHandler)
 		v1.GET("/template-image", api.TemplateImageProxyHandler)
 		v1.GET("/providers", api.ListProvidersHandler)
@@ -289,8 +313,13 @@ func main() {
 		v1.POST("/tasks/generate-with-images", api.GenerateWithImagesHandler)
 		v1.GET("/tasks/:task_id", api.GetTaskHandler)
 		v1.GET("/tasks/:task_id/stream", api.StreamTaskHandler)
+		v1.GET("/queue", api.GetQueueStatusHandler)
+		v1.POST("/queue/pause", api.PauseQueueHandler)
+		v1.POST("/queue/resume", api.ResumeQueueHandler)
 		v1.GET("/images", api.ListImagesHandler)
 		v1.POST("/images/export", api.ExportImagesHandler)
+		v1.POST("/images/import", api.ImportImageHandler)
+		v1.POST("/images/relocate", api.RelocateImagesHandler)
 		v1.DELETE("/images/:id", api.DeleteImageHandler)
 		v1.GET("/images/:id/download", api.DownloadImageHandler)
 		// 文件夹管理 API
@@ -403,6 +403,7 @@ func OptimizePromptHandler(c *gin.Context) {
 		Provider: req.Provider,
 		Model:    req.Model,
 		Prompt:   req.Prompt,
+		APIKey:   sessionOptimizeAPIKey(c, req.Provider),
 		Mode: func() string {
 			if strings.TrimSpace(req.ResponseFormat) == "" {
 				return promptopt.ModeText
@@ -427,7 +428,11 @@ func GenerateHandler(c *gin.Context) {
 	}
 
 	// 1. 获取并校验 Provider
-	p := provider.GetProvider(req.Provider)
+	p, err := resolveProvider(c, req.Provider)
+	if err != nil {
+		Error(c, http.StatusBadRequest, 400, err.Error())
+		return
+	}
 	if p == nil {
 		Error(c, http.StatusBadRequest, 400, "未找到指定的 Provider: "+req.Provider)
 		return
@@ -511,8 +516,10 @@ func GenerateHandler(c *gin.Context) {
 
 	// 提交到 Worker 池
 	task := &worker.Task{
-		TaskModel: taskModel,
-		Params:    req.Params,
+		TaskModel:      taskModel,
+		Params:         req.Params,
+		APIKey:         sessionAPIKey(c, req.Provider),
+		OptimizeAPIKey: sessionOptimizeAPIKey(c, promptopt.ExtractProvider(req.Params)),
 	}
 	diagnostic.AttachTaskID(task.Params, taskModel.TaskID)
 
@@ -552,7 +559,11 @@ func GenerateWithImagesHandler(c *gin.Context) {
 	log.Printf("[API] 请求解析成功: Prompt=%s, Provider=%s, Images=%d\n", req.Prompt, req.Provider, len(req.RefImages))
 
 	// 2. 校验 Provider
-	p := provider.GetProvider(req.Provider)
+	p, err := resolveProvider(c, req.Provider)
+	if err != nil {
+		Error(c, http.StatusBadRequest, 400, err.Error())
+		return
+	}
 	if p == nil {
 		Error(c, http.StatusBadRequest, 400, "未找到指定的 Provider: "+req.Provider)
 		return
@@ -707,8 +718,10 @@ func GenerateWithImagesHandler(c *gin.Context) {
 
 	// 4. 提交到 Worker 池
 	task := &worker.Task{
-		TaskModel: taskModel,
-		Params:    taskParams,
+		TaskModel:      taskModel,
+		Params:         taskParams,
+		APIKey:         sessionAPIKey(c, req.Provider),
+		OptimizeAPIKey: sessionOptimizeAPIKey(c, promptopt.ExtractProvider(taskParams)),
 	}
 	diagnostic.AttachTaskID(task.Params, taskModel.TaskID)
 
@@ -814,7 +827,15 @@ func DeleteImageHandler(c *gin.Context) {
 
 	// 删除物理文件/OSS 文件
 	// 优先使用数据库中存储的实际路径，兼容旧数据则尝试各种格式
-	if task.LocalPath != "" {
+	if task.LocalPath != "" && inStorageSubdir(task.LocalPath) {
+		// 整理到子目录（年/月、文件夹）后的文件按实际路径删除
+		if err := os.Remove(task.LocalPath); err != nil {
+			fmt.Printf("警告: 删除物理文件失败 %s: %v\n", task.LocalPath, err)
+		}
+		if task.ThumbnailPath != "" && inStorageSubdir(task.ThumbnailPath) {
+			_ = os.Remove(task.ThumbnailPath)
+		}
+	} else if task.LocalPath != "" {
 		// 使用实际存储的文件名
 		fileName := filepath.Base(task.LocalPath)
 		if err := storage.GlobalStorage.Delete(fileName); err != nil {
@@ -946,6 +967,9 @@ func ImageToPromptHandler(c *gin.Context) {
 		Error(c, http.StatusBadRequest, 400, "未找到指定的 Provider: "+providerName)
 		return
 	}
+	if key := sessionAPIKey(c, providerName); key != "" {
+		cfg.APIKey = key
+	}
 	if strings.TrimSpace(cfg.APIKey) == "" {
 		Error(c, http.StatusBadRequest, 400, "Provider API Key 未配置")
 		return
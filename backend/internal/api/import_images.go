@@ -0,0 +1,119 @@
+package api
+
+import (
+	"log"
+	"net/http"
+	"os"
+	"path/filepath"
+	"strconv"
+	"strings"
+	"time"
+
+	"image-gen-service/internal/config"
+	"image-gen-service/internal/model"
+	"image-gen-service/internal/storage"
+
+	"github.com/gin-gonic/gin"
+	"github.com/google/uuid"
+)
+
+// ImportImageRequest 导入外部图片请求（桌面端已把文件复制到存储目录）
+type ImportImageRequest struct {
+	LocalPath    string `json:"local_path" binding:"required"` // 相对工作目录的路径，如 storage/import_xxx.png
+	Prompt       string `json:"prompt"`
+	ProviderName string `json:"provider_name"` // 来源，如 midjourney / dalle
+	ModelID      string `json:"model_id"`
+	CreatedAt    string `json:"created_at"` // 原始生成时间：RFC3339、本地时间字符串或 Unix 时间戳，缺省为当前时间
+}
+
+// ImportImageHandler 把存储目录中已有的图片登记为一条已完成任务，并补生成缩略图
+func ImportImageHandler(c *gin.Context) {
+	var req ImportImageRequest
+	if err := c.ShouldBindJSON(&req); err != nil {
+		Error(c, http.StatusBadRequest, 400, err.Error())
+		return
+	}
+
+	localPath := filepath.Clean(strings.TrimSpace(req.LocalPath))
+	if filepath.IsAbs(localPath) || !pathWithinRoot(localPath, config.GlobalConfig.Storage.LocalDir) {
+		Error(c, http.StatusBadRequest, 400, "local_path 必须位于存储目录内")
+		return
+	}
+	info, err := os.Stat(localPath)
+	if err != nil || !info.Mode().IsRegular() {
+		Error(c, http.StatusBadRequest, 400, "图片文件不存在")
+		return
+	}
+
+	thumbPath, width, height, err := storage.GenerateThumbnail(localPath)
+	if err != nil {
+		// 解码失败（如 webp）时仍然登记，前端会回退显示原图
+		log.Printf("[API] 导入图片生成缩略图失败: %v\n", err)
+	}
+
+	createdAt, ok := parseImportTime(req.CreatedAt)
+	if !ok {
+		createdAt = time.Now()
+	}
+	prompt := strings.TrimSpace(req.Prompt)
+	providerName := strings.TrimSpace(req.ProviderName)
+	if providerName == "" {
+		providerName = "import"
+	}
+
+	taskModel := &model.Task{
+		TaskID:         uuid.New().String(),
+		Prompt:         prompt,
+		PromptOriginal: prompt,
+		ProviderName:   providerName,
+		ModelID:        strings.TrimSpace(req.ModelID),
+		Status:         "completed",
+		LocalPath:      localPath,
+		ThumbnailPath:  thumbPath,
+		Width:          width,
+		Height:         height,
+		TotalCount:     1,
+		ConfigSnapshot: "Imported from " + providerName,
+		CreatedAt:      createdAt,
+		CompletedAt:    &createdAt,
+	}
+
+	// 按原始生成时间归入对应月份文件夹
+	if monthFolder, err := getOrCreateMonthFolder(model.DB, createdAt); err == nil {
+		taskModel.FolderID = strconv.FormatUint(uint64(monthFolder.ID), 10)
+	} else {
+		log.Printf("[API] 警告: 导入图片获取月份文件夹失败: %v\n", err)
+	}
+
+	if err := model.DB.Create(taskModel).Error; err != nil {
+		Error(c, http.StatusInternalServerError, 500, "登记导入图片失败")
+		return
+	}
+
+	sanitizeTaskImagePaths(taskModel)
+	Success(c, taskModel)
+}
+
+// parseImportTime 兼容导出包里常见的几种时间写法，不带时区的按本地时间处理
+func parseImportTime(raw string) (time.Time, bool) {
+	raw = strings.TrimSpace(raw)
+	if raw == "" {
+		return time.Time{}, false
+	}
+	if n, err := strconv.ParseInt(raw, 10, 64); err == nil && n > 0 {
+		// 超过 1e12 视为毫秒
+		if n > 1e12 {
+			return time.UnixMilli(n), true
+		}
+		return time.Unix(n, 0), true
+	}
+	if t, err := time.Parse(time.RFC3339, raw); err == nil {
+		return t, true
+	}
+	for _, layout := range []string{"2006-01-02T15:04:05", "2006-01-02 15:04:05", "2006-01-02 15.04.05", "2006-01-02"} {
+		if t, err := time.ParseInLocation(layout, raw, time.Local); err == nil {
+			return t, true
+		}
+	}
+	return time.Time{}, false
+}
@@ -0,0 +1,37 @@
+package api
+
+import (
+	"image-gen-service/internal/worker"
+
+	"github.com/gin-gonic/gin"
+)
+
+// QueueStatus 任务队列状态
+type QueueStatus struct {
+	Paused  bool `json:"paused"`
+	Pending int  `json:"pending"`
+}
+
+func currentQueueStatus() QueueStatus {
+	return QueueStatus{
+		Paused:  worker.Pool.Paused(),
+		Pending: worker.Pool.QueueLength(),
+	}
+}
+
+// GetQueueStatusHandler 查询任务队列是否暂停
+func GetQueueStatusHandler(c *gin.Context) {
+	Success(c, currentQueueStatus())
+}
+
+// PauseQueueHandler 暂停任务队列，用于“立即停止消耗 API 额度”
+func PauseQueueHandler(c *gin.Context) {
+	worker.Pool.Pause()
+	Success(c, currentQueueStatus())
+}
+
+// ResumeQueueHandler 恢复任务队列
+func ResumeQueueHandler(c *gin.Context) {
+	worker.Pool.Resume()
+	Success(c, currentQueueStatus())
+}
@@ -0,0 +1,94 @@
+package api
+
+import (
+	"net/http"
+	"os"
+	"path/filepath"
+	"strings"
+
+	"image-gen-service/internal/config"
+	"image-gen-service/internal/model"
+
+	"github.com/gin-gonic/gin"
+	"gorm.io/gorm"
+)
+
+// RelocateImageItem 单条路径更新：桌面端已把文件移动到新位置
+type RelocateImageItem struct {
+	TaskID        string `json:"task_id" binding:"required"`
+	LocalPath     string `json:"local_path" binding:"required"`
+	ThumbnailPath string `json:"thumbnail_path"`
+}
+
+// RelocateImagesRequest 批量更新图片本地路径请求
+type RelocateImagesRequest struct {
+	Items []RelocateImageItem `json:"items" binding:"required"`
+}
+
+func validateRelocatedPath(raw string) (string, bool) {
+	cleaned := filepath.Clean(strings.TrimSpace(raw))
+	if !pathWithinRoot(cleaned, config.GlobalConfig.Storage.LocalDir) {
+		return "", false
+	}
+	info, err := os.Stat(cleaned)
+	if err != nil || !info.Mode().IsRegular() {
+		return "", false
+	}
+	return cleaned, true
+}
+
+// inStorageSubdir 判断路径是否位于存储目录的子目录中（经过整理的文件）
+func inStorageSubdir(raw string) bool {
+	root := config.GlobalConfig.Storage.LocalDir
+	dir := filepath.Dir(filepath.Clean(strings.TrimSpace(raw)))
+	return pathWithinRoot(dir, root) && !pathWithinRoot(root, dir)
+}
+
+// RelocateImagesHandler 在一个事务里更新一批任务的本地路径，任何一条失败都整体回滚
+func RelocateImagesHandler(c *gin.Context) {
+	var req RelocateImagesRequest
+	if err := c.ShouldBindJSON(&req); err != nil {
+		Error(c, http.StatusBadRequest, 400, err.Error())
+		return
+	}
+
+	for i := range req.Items {
+		item := &req.Items[i]
+		localPath, ok := validateRelocatedPath(item.LocalPath)
+		if !ok {
+			Error(c, http.StatusBadRequest, 400, "无效的图片路径: "+item.LocalPath)
+			return
+		}
+		item.LocalPath = localPath
+		if strings.TrimSpace(item.ThumbnailPath) != "" {
+			thumbPath, ok := validateRelocatedPath(item.ThumbnailPath)
+			if !ok {
+				Error(c, http.StatusBadRequest, 400, "无效的缩略图路径: "+item.ThumbnailPath)
+				return
+			}
+			item.ThumbnailPath = thumbPath
+		}
+	}
+
+	var updated int64
+	err := model.DB.Transaction(func(tx *gorm.DB) error {
+		for _, item := range req.Items {
+			updates := map[string]interface{}{"local_path": item.LocalPath}
+			if item.ThumbnailPath != "" {
+				updates["thumbnail_path"] = item.ThumbnailPath
+			}
+			result := tx.Model(&model.Task{}).Where("task_id = ?", item.TaskID).Updates(updates)
+			if result.Error != nil {
+				return result.Error
+			}
+			updated += result.RowsAffected
+		}
+		return nil
+	})
+	if err != nil {
+		Error(c, http.StatusInternalServerError, 500, "更新图片路径失败")
+		return
+	}
+
+	Success(c, gin.H{"updated": updated})
+}
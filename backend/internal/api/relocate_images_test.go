@@ -0,0 +1,92 @@
+package api
+
+import (
+	"os"
+	"path/filepath"
+	"testing"
+
+	"image-gen-service/internal/config"
+)
+
+// setupRelocateStorage 在临时工作目录下构造 storage/ 与同级的 data.db，和后端实际运行时的相对路径布局一致
+func setupRelocateStorage(t *testing.T) {
+	t.Helper()
+	t.Chdir(t.TempDir())
+
+	originalDir := config.GlobalConfig.Storage.LocalDir
+	config.GlobalConfig.Storage.LocalDir = "storage"
+	t.Cleanup(func() {
+		config.GlobalConfig.Storage.LocalDir = originalDir
+	})
+
+	files := []string{
+		"data.db",
+		filepath.Join("storage", "root.png"),
+		filepath.Join("storage", "2026", "10", "nested.png"),
+	}
+	for _, file := range files {
+		if err := os.MkdirAll(filepath.Dir(file), 0755); err != nil {
+			t.Fatalf("create dir for %s: %v", file, err)
+		}
+		if err := os.WriteFile(file, []byte("x"), 0644); err != nil {
+			t.Fatalf("write %s: %v", file, err)
+		}
+	}
+}
+
+func TestValidateRelocatedPath(t *testing.T) {
+	setupRelocateStorage(t)
+
+	tests := []struct {
+		name   string
+		raw    string
+		want   string
+		wantOK bool
+	}{
+		{name: "nested year/month file", raw: "storage/2026/10/nested.png", want: filepath.Join("storage", "2026", "10", "nested.png"), wantOK: true},
+		{name: "file at storage root", raw: " storage/root.png ", want: filepath.Join("storage", "root.png"), wantOK: true},
+		{name: "redundant segments are cleaned", raw: "storage/2026/./10/../10/nested.png", want: filepath.Join("storage", "2026", "10", "nested.png"), wantOK: true},
+		{name: "traversal to database", raw: "storage/../data.db", wantOK: false},
+		{name: "sibling outside storage", raw: "data.db", wantOK: false},
+		{name: "storage root itself", raw: "storage", wantOK: false},
+		{name: "nested directory", raw: "storage/2026/10", wantOK: false},
+		{name: "missing file", raw: "storage/2026/10/missing.png", wantOK: false},
+		{name: "empty path", raw: "", wantOK: false},
+	}
+
+	for _, tc := range tests {
+		t.Run(tc.name, func(t *testing.T) {
+			got, ok := validateRelocatedPath(tc.raw)
+			if ok != tc.wantOK || got != tc.want {
+				t.Fatalf("validateRelocatedPath(%q) = (%q, %t), want (%q, %t)", tc.raw, got, ok, tc.want, tc.wantOK)
+			}
+		})
+	}
+}
+
+func TestInStorageSubdir(t *testing.T) {
+	setupRelocateStorage(t)
+
+	tests := []struct {
+		name string
+		raw  string
+		want bool
+	}{
+		{name: "nested year/month file", raw: "storage/2026/10/nested.png", want: true},
+		{name: "year directory file", raw: "storage/2026/nested.png", want: true},
+		{name: "file at storage root", raw: "storage/root.png", want: false},
+		{name: "nested path resolving to root", raw: "storage/2026/../root.png", want: false},
+		{name: "traversal to database", raw: "storage/../data.db", want: false},
+		{name: "deep traversal out of nested dir", raw: "storage/2026/10/../../../data.db", want: false},
+		{name: "storage root itself", raw: "storage", want: false},
+		{name: "sibling outside storage", raw: "data.db", want: false},
+	}
+
+	for _, tc := range tests {
+		t.Run(tc.name, func(t *testing.T) {
+			if got := inStorageSubdir(tc.raw); got != tc.want {
+				t.Fatalf("inStorageSubdir(%q) = %t, want %t", tc.raw, got, tc.want)
+			}
+		})
+	}
+}
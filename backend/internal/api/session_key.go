@@ -0,0 +1,50 @@
+package api
+
+import (
+	"fmt"
+	"strings"
+
+	"image-gen-service/internal/promptopt"
+	"image-gen-service/internal/provider"
+
+	"github.com/gin-gonic/gin"
+)
+
+// 桌面壳层代理注入的本次会话临时 API Key（只在内存里，不落库），前端发来的同名请求头会被代理去掉
+const (
+	sessionAPIKeyHeader      = "X-Session-Api-Key"
+	sessionAPIProviderHeader = "X-Session-Api-Provider"
+)
+
+// sessionAPIKey 返回对指定 Provider 生效的临时 Key；Provider 头为空时对所有 Provider 生效，
+// "gemini" 同时匹配 "gemini-chat"，"openai" 同时匹配 "openai-image"、"openai-chat"
+func sessionAPIKey(c *gin.Context, providerName string) string {
+	key := strings.TrimSpace(c.GetHeader(sessionAPIKeyHeader))
+	if key == "" {
+		return ""
+	}
+	scope := strings.TrimSpace(strings.ToLower(c.GetHeader(sessionAPIProviderHeader)))
+	name := strings.TrimSpace(strings.ToLower(providerName))
+	if scope == "" || name == scope || strings.HasPrefix(name, scope+"-") {
+		return key
+	}
+	return ""
+}
+
+// sessionOptimizeAPIKey 提示词优化使用的 Provider 名称与生图不同，先按优化服务的规则归一化
+func sessionOptimizeAPIKey(c *gin.Context, providerName string) string {
+	return sessionAPIKey(c, promptopt.NormalizeProviderName(providerName))
+}
+
+// resolveProvider 有临时 Key 时用它创建一个临时 Provider，否则使用 Registry 里的实例；
+// 临时 Key 生效但创建失败时直接返回错误，不能悄悄退回到已保存的 Key
+func resolveProvider(c *gin.Context, providerName string) (provider.Provider, error) {
+	if key := sessionAPIKey(c, providerName); key != "" {
+		p, err := provider.NewWithAPIKey(providerName, key)
+		if err != nil {
+			return nil, fmt.Errorf("使用会话临时 API Key 创建 Provider 失败: %w", err)
+		}
+		return p, nil
+	}
+	return provider.GetProvider(providerName), nil
+}
@@ -0,0 +1,104 @@
+package api
+
+import (
+	"net/http"
+	"net/http/httptest"
+	"testing"
+
+	"image-gen-service/internal/model"
+
+	"github.com/gin-gonic/gin"
+)
+
+func newSessionKeyContext(key, scope string) *gin.Context {
+	request := httptest.NewRequest(http.MethodPost, "/api/generate", nil)
+	if key != "" {
+		request.Header.Set(sessionAPIKeyHeader, key)
+	}
+	if scope != "" {
+		request.Header.Set(sessionAPIProviderHeader, scope)
+	}
+	c, _ := gin.CreateTestContext(httptest.NewRecorder())
+	c.Request = request
+	return c
+}
+
+func TestSessionAPIKeyMatchesProviderScope(t *testing.T) {
+	gin.SetMode(gin.TestMode)
+
+	tests := []struct {
+		name     string
+		key      string
+		scope    string
+		provider string
+		want     string
+	}{
+		{name: "no key", key: "", scope: "", provider: "gemini", want: ""},
+		{name: "blank key", key: "   ", scope: "", provider: "gemini", want: ""},
+		{name: "empty scope matches any provider", key: "sk-session", scope: "", provider: "openai-image", want: "sk-session"},
+		{name: "exact match", key: "sk-session", scope: "gemini", provider: "gemini", want: "sk-session"},
+		{name: "match ignores case and spaces", key: " sk-session ", scope: " Gemini ", provider: "GEMINI", want: "sk-session"},
+		{name: "gemini scope covers gemini-chat", key: "sk-session", scope: "gemini", provider: "gemini-chat", want: "sk-session"},
+		{name: "openai scope covers openai-image", key: "sk-session", scope: "openai", provider: "openai-image", want: "sk-session"},
+		{name: "specific scope does not cover base provider", key: "sk-session", scope: "gemini-chat", provider: "gemini", want: ""},
+		{name: "prefix without dash does not match", key: "sk-session", scope: "gem", provider: "gemini", want: ""},
+		{name: "other provider does not match", key: "sk-session", scope: "openai", provider: "gemini", want: ""},
+	}
+
+	for _, tc := range tests {
+		t.Run(tc.name, func(t *testing.T) {
+			c := newSessionKeyContext(tc.key, tc.scope)
+			if got := sessionAPIKey(c, tc.provider); got != tc.want {
+				t.Fatalf("sessionAPIKey(scope=%q, provider=%q) = %q, want %q", tc.scope, tc.provider, got, tc.want)
+			}
+		})
+	}
+}
+
+func TestSessionOptimizeAPIKeyNormalizesProvider(t *testing.T) {
+	gin.SetMode(gin.TestMode)
+
+	tests := []struct {
+		name     string
+		scope    string
+		provider string
+		want     string
+	}{
+		{name: "empty provider is openai-chat", scope: "openai-chat", provider: "", want: "sk-session"},
+		{name: "gemini becomes gemini-chat", scope: "gemini-chat", provider: "gemini", want: "sk-session"},
+		{name: "openai scope covers openai-chat", scope: "openai", provider: "openai", want: "sk-session"},
+		{name: "non-matching scope", scope: "gemini", provider: "openai", want: ""},
+	}
+
+	for _, tc := range tests {
+		t.Run(tc.name, func(t *testing.T) {
+			c := newSessionKeyContext("sk-session", tc.scope)
+			if got := sessionOptimizeAPIKey(c, tc.provider); got != tc.want {
+				t.Fatalf("sessionOptimizeAPIKey(scope=%q, provider=%q) = %q, want %q", tc.scope, tc.provider, got, tc.want)
+			}
+		})
+	}
+}
+
+func TestResolveProviderFailsWhenSessionKeyCannotBeUsed(t *testing.T) {
+	gin.SetMode(gin.TestMode)
+
+	// 数据库未初始化时 NewWithAPIKey 必然失败，用来模拟临时 Provider 创建失败
+	originalDB := model.DB
+	model.DB = nil
+	defer func() { model.DB = originalDB }()
+
+	c := newSessionKeyContext("sk-session", "gemini")
+	p, err := resolveProvider(c, "gemini")
+	if err == nil {
+		t.Fatalf("resolveProvider with failing session key returned nil error")
+	}
+	if p != nil {
+		t.Fatalf("resolveProvider with failing session key returned provider %v, want nil", p)
+	}
+
+	c = newSessionKeyContext("sk-session", "openai")
+	if _, err := resolveProvider(c, "gemini"); err != nil {
+		t.Fatalf("resolveProvider without matching session key returned error: %v", err)
+	}
+}
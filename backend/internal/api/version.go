@@ -0,0 +1,19 @@
+package api
+
+import (
+	"github.com/gin-gonic/gin"
+)
+
+// Version 构建时可通过 -ldflags "-X image-gen-service/internal/api.Version=x.y.z" 注入
+var Version = "dev"
+
+// APIVersion 接口出现不兼容变更时递增，桌面端据此判断 sidecar 与界面是否匹配
+const APIVersion = 1
+
+// VersionHandler 返回后端版本与接口版本
+func VersionHandler(c *gin.Context) {
+	Success(c, gin.H{
+		"version":     Version,
+		"api_version": APIVersion,
+	})
+}
@@ -13,11 +13,13 @@ type Config struct {
 		Port int    `mapstructure:"port"`
 	} `mapstructure:"server"`
 	Database struct {
-		Path string `mapstructure:"path"`
+		Path     string `mapstructure:"path"`
+		ReadOnly bool   `mapstructure:"read_only"` // 只读安全模式：数据库被其他进程占用时由桌面壳层开启
 	} `mapstructure:"database"`
 	Storage struct {
-		LocalDir string `mapstructure:"local_dir"`
-		OSS      struct {
+		LocalDir  string `mapstructure:"local_dir"`
+		MinFreeMB int    `mapstructure:"min_free_mb"` // 保存生成结果前要求保留的剩余空间，0 表示不检查
+		OSS       struct {
 			Enabled         bool   `mapstructure:"enabled"`
 			Endpoint        string `mapstructure:"endpoint"`
 			AccessKeyID     string `mapstructure:"access_key_id"`
@@ -246,7 +248,9 @@ func InitConfig() {
 
 	// 设置默认值
 	viper.SetDefault("database.path", "data.db")
+	viper.SetDefault("database.read_only", false)
 	viper.SetDefault("storage.local_dir", "storage")
+	viper.SetDefault("storage.min_free_mb", 0)
 	viper.SetDefault("server.host", "127.0.0.1")
 	viper.SetDefault("server.port", 8080)
 	viper.SetDefault("prompts.optimize_system", DefaultOptimizeSystemPrompt)
@@ -13,6 +13,9 @@ import (
 
 var DB *gorm.DB
 
+// ReadOnly 为 true 时以只读方式打开数据库，跳过迁移与所有启动期写操作
+var ReadOnly bool
+
 const STALE_TASK_ERROR_MESSAGE = "任务因应用重启中断，请重新生成"
 const ZOMBIE_TASK_ERROR_MESSAGE = "任务超时未完成，请重试"
 
@@ -26,7 +29,11 @@ var zombieReconciler struct {
 // InitDB 初始化 SQLite 数据库
 func InitDB(dbPath string) {
 	var err error
-	DB, err = gorm.Open(sqlite.Open(dbPath+"?_busy_timeout=5000"), &gorm.Config{
+	dsn := dbPath + "?_busy_timeout=5000"
+	if ReadOnly {
+		dsn = "file:" + dbPath + "?mode=ro&_busy_timeout=5000"
+	}
+	DB, err = gorm.Open(sqlite.Open(dsn), &gorm.Config{
 		Logger: logger.Default.LogMode(logger.Info),
 	})
 	if err != nil {
@@ -41,6 +48,11 @@ func InitDB(dbPath string) {
 		sqlDB.SetConnMaxLifetime(time.Hour)
 	}
 
+	if ReadOnly {
+		log.Println("数据库以只读安全模式打开，跳过迁移与任务状态修复")
+		return
+	}
+
 	// 自动迁移表结构
 	err = DB.AutoMigrate(&ProviderConfig{}, &Task{}, &Folder{})
 	if err != nil {
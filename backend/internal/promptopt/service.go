@@ -27,6 +27,9 @@ type Request struct {
 	Model    string
 	Prompt   string
 	Mode     string
+
+	// 本次会话的临时 API Key，非空时覆盖数据库里的配置
+	APIKey string
 }
 
 type Result struct {
@@ -102,6 +105,9 @@ func OptimizePrompt(ctx context.Context, req Request) (*Result, error) {
 	if err := model.DB.Where("provider_name = ?", providerName).First(&cfg).Error; err != nil {
 		return nil, fmt.Errorf("未找到指定的 Provider: %s", providerName)
 	}
+	if apiKey := strings.TrimSpace(req.APIKey); apiKey != "" {
+		cfg.APIKey = apiKey
+	}
 	if strings.TrimSpace(cfg.APIKey) == "" {
 		return nil, fmt.Errorf("Provider API Key 未配置")
 	}
@@ -2,6 +2,7 @@ package provider
 
 import (
 	"context"
+	"fmt"
 	"image-gen-service/internal/config"
 	"image-gen-service/internal/model"
 	"log"
@@ -60,6 +61,38 @@ func GetProvider(name string) Provider {
 	return Registry[name]
 }
 
+func newProvider(cfg *model.ProviderConfig) (Provider, error) {
+	switch cfg.ProviderName {
+	case "gemini":
+		return NewGeminiProvider(cfg)
+	case "openai":
+		return NewOpenAIProvider(cfg)
+	case "openai-image":
+		return NewOpenAIImageProvider(cfg)
+	default:
+		return nil, fmt.Errorf("未知的 Provider 类型: %s", cfg.ProviderName)
+	}
+}
+
+// NewWithAPIKey 用数据库里的配置和指定的 API Key 创建一个临时 Provider，不写回数据库、不进入 Registry
+func NewWithAPIKey(name, apiKey string) (Provider, error) {
+	if model.DB == nil {
+		return nil, fmt.Errorf("数据库未初始化")
+	}
+	var cfg model.ProviderConfig
+	if err := model.DB.Where("provider_name = ?", name).First(&cfg).Error; err != nil {
+		return nil, fmt.Errorf("未找到指定的 Provider: %s", name)
+	}
+	cfg.APIKey = apiKey
+	if cfg.TimeoutSeconds <= 0 {
+		cfg.TimeoutSeconds = defaultTimeoutSeconds(name)
+	}
+	if cfg.MaxRetries < 0 {
+		cfg.MaxRetries = defaultMaxRetries(name)
+	}
+	return newProvider(&cfg)
+}
+
 // InitProviders 从数据库初始化所有已启用的 Provider
 func InitProviders() error {
 	initMu.Lock()
@@ -127,21 +160,7 @@ func InitProviders() error {
 			}
 		}
 
-		var p Provider
-		var err error
-
-		switch cfg.ProviderName {
-		case "gemini":
-			p, err = NewGeminiProvider(&cfg)
-		case "openai":
-			p, err = NewOpenAIProvider(&cfg)
-		case "openai-image":
-			p, err = NewOpenAIImageProvider(&cfg)
-		default:
-			log.Printf("未知的 Provider 类型: %s", cfg.ProviderName)
-			continue
-		}
-
+		p, err := newProvider(&cfg)
 		if err != nil {
 			log.Printf("初始化 Provider %s 失败: %v", cfg.ProviderName, err)
 			// 这里我们选择记录错误并继续，但也可以选择返回错误
@@ -0,0 +1,48 @@
+package storage
+
+import (
+	"errors"
+	"fmt"
+	"log"
+)
+
+// ErrInsufficientSpace 存储目录所在磁盘剩余空间低于下限
+var ErrInsufficientSpace = errors.New("磁盘剩余空间不足")
+
+// minFreeBytes 保存图片前要求保留的剩余空间，0 表示不检查
+var minFreeBytes uint64
+
+// SetMinFreeMB 设置剩余空间下限，桌面端通过 STORAGE_MIN_FREE_MB 传入磁盘保护阈值
+func SetMinFreeMB(mb int) {
+	if mb <= 0 {
+		minFreeBytes = 0
+		return
+	}
+	minFreeBytes = uint64(mb) * 1024 * 1024
+}
+
+// EnsureFreeSpace 写入 needed 字节前检查本地存储目录的剩余空间，查询失败时不拦截
+func EnsureFreeSpace(needed int) error {
+	if minFreeBytes == 0 {
+		return nil
+	}
+	composite, ok := GlobalStorage.(*CompositeStorage)
+	if !ok || composite.Local == nil {
+		return nil
+	}
+	dir := composite.Local.BaseDir
+	if err := ensureDir(dir); err != nil {
+		return nil
+	}
+	free, err := freeSpace(dir)
+	if err != nil {
+		log.Printf("[Storage] 查询剩余空间失败: %v", err)
+		return nil
+	}
+	// 缩略图和临时文件按原图大小再预留一份
+	required := minFreeBytes + uint64(needed)*2
+	if free < required {
+		return fmt.Errorf("%w: 剩余 %d MB，至少需要 %d MB", ErrInsufficientSpace, free/1024/1024, required/1024/1024)
+	}
+	return nil
+}
@@ -275,6 +275,20 @@ func (l *LocalStorage) SaveWithThumbnail(name string, reader io.Reader) (string,
 	return localPath, "", thumbPath, "", width, height, nil
 }
 
+// GenerateThumbnail 为已落盘的本地图片补生成缩略图（用于导入外部图片），返回缩略图路径与原图尺寸
+func GenerateThumbnail(localPath string) (string, int, int, error) {
+	srcImg, width, height, err := decodeImageFile(localPath)
+	if err != nil {
+		return "", 0, 0, err
+	}
+	thumbPath := filepath.Join(filepath.Dir(localPath), "thumb_"+filepath.Base(localPath))
+	dstImg := imaging.Thumbnail(srcImg, 256, 256, imaging.Lanczos)
+	if err := imaging.Save(dstImg, thumbPath); err != nil {
+		return "", width, height, err
+	}
+	return thumbPath, width, height, nil
+}
+
 func (l *LocalStorage) Delete(name string) error {
 	// 使用 filepath.Base 防止路径遍历攻击
 	safeName := filepath.Base(name)
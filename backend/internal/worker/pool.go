@@ -23,6 +23,10 @@ import (
 type Task struct {
 	TaskModel *model.Task
 	Params    map[string]interface{}
+
+	// 桌面端本次会话的临时 API Key，只保存在内存里，非空时代替数据库里的配置
+	APIKey         string
+	OptimizeAPIKey string
 }
 
 // WorkerPool 任务池结构
@@ -33,6 +37,10 @@ type WorkerPool struct {
 	ctx         context.Context
 	cancel      context.CancelFunc
 	stopping    int32
+
+	// 暂停时非 nil，恢复时关闭以唤醒等待中的 Worker
+	pauseMu  sync.Mutex
+	resumeCh chan struct{}
 }
 
 var Pool *WorkerPool
@@ -102,11 +110,66 @@ func (wp *WorkerPool) worker(id int) {
 			if !ok {
 				return
 			}
+			// 暂停期间取到的任务先拿在手里，恢复后再执行
+			if !wp.waitResume() {
+				wp.failTask(task, errors.New(model.STALE_TASK_ERROR_MESSAGE))
+				wp.drainPendingTasks(id)
+				return
+			}
 			wp.processTask(task)
 		}
 	}
 }
 
+// Pause 暂停任务执行：进行中的任务继续完成，排队中的任务等 Resume 后再执行
+func (wp *WorkerPool) Pause() {
+	wp.pauseMu.Lock()
+	defer wp.pauseMu.Unlock()
+	if wp.resumeCh == nil {
+		wp.resumeCh = make(chan struct{})
+		log.Println("Worker 池已暂停")
+	}
+}
+
+// Resume 恢复任务执行
+func (wp *WorkerPool) Resume() {
+	wp.pauseMu.Lock()
+	defer wp.pauseMu.Unlock()
+	if wp.resumeCh != nil {
+		close(wp.resumeCh)
+		wp.resumeCh = nil
+		log.Println("Worker 池已恢复")
+	}
+}
+
+// Paused 是否处于暂停状态
+func (wp *WorkerPool) Paused() bool {
+	wp.pauseMu.Lock()
+	defer wp.pauseMu.Unlock()
+	return wp.resumeCh != nil
+}
+
+// QueueLength 排队中（尚未被 Worker 取走）的任务数
+func (wp *WorkerPool) QueueLength() int {
+	return len(wp.taskQueue)
+}
+
+// waitResume 暂停时阻塞到恢复；Worker 池停止时返回 false
+func (wp *WorkerPool) waitResume() bool {
+	wp.pauseMu.Lock()
+	ch := wp.resumeCh
+	wp.pauseMu.Unlock()
+	if ch == nil {
+		return true
+	}
+	select {
+	case <-ch:
+		return true
+	case <-wp.ctx.Done():
+		return false
+	}
+}
+
 func (wp *WorkerPool) drainPendingTasks(workerID int) {
 	drained := 0
 	for {
@@ -173,6 +236,14 @@ func (wp *WorkerPool) processTask(task *Task) {
 
 	// 2. 获取 Provider
 	p := provider.GetProvider(task.TaskModel.ProviderName)
+	if task.APIKey != "" {
+		sessionProvider, err := provider.NewWithAPIKey(task.TaskModel.ProviderName, task.APIKey)
+		if err != nil {
+			wp.failTask(task, err)
+			return
+		}
+		p = sessionProvider
+	}
 	if p == nil {
 		wp.failTask(task, fmt.Errorf("Provider %s 不存在", task.TaskModel.ProviderName))
 		return
@@ -289,6 +360,11 @@ func (wp *WorkerPool) processTask(task *Task) {
 			len(result.Images),
 			len(result.Images[0]),
 		)
+		// 写入前检查剩余空间，避免磁盘写满时留下半截文件
+		if err := storage.EnsureFreeSpace(len(result.Images[0])); err != nil {
+			wp.failTask(task, err)
+			return
+		}
 		reader := bytes.NewReader(result.Images[0])
 		localPath, remoteURL, thumbLocalPath, thumbRemoteURL, width, height, err := storage.GlobalStorage.SaveWithThumbnail(baseFileName, reader)
 		if err != nil {
@@ -387,6 +463,7 @@ func (wp *WorkerPool) optimizePromptForTask(ctx context.Context, task *Task) err
 		Model:    optModel,
 		Prompt:   rawPrompt,
 		Mode:     mode,
+		APIKey:   task.OptimizeAPIKey,
 	})
 	if err != nil {
 		return err
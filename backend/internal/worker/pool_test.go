@@ -213,3 +213,152 @@ func TestProcessTaskRecordsProviderPanicAsFailure(t *testing.T) {
 		t.Fatalf("task error = %q, want provider panic failure", saved.ErrorMessage)
 	}
 }
+
+func setupPauseTestTask(t *testing.T, providerName, taskID string) (*gorm.DB, model.Task) {
+	t.Helper()
+	originalDB := model.DB
+	t.Cleanup(func() {
+		model.DB = originalDB
+	})
+
+	db, err := gorm.Open(sqlite.Open(":memory:"), &gorm.Config{})
+	if err != nil {
+		t.Fatalf("open test database: %v", err)
+	}
+	if err := db.AutoMigrate(&model.ProviderConfig{}, &model.Task{}); err != nil {
+		t.Fatalf("migrate test database: %v", err)
+	}
+	model.DB = db
+
+	if err := db.Create(&model.ProviderConfig{ProviderName: providerName, TimeoutSeconds: 5}).Error; err != nil {
+		t.Fatalf("create provider config: %v", err)
+	}
+
+	taskModel := model.Task{
+		TaskID:       taskID,
+		Prompt:       "draw a banana",
+		ProviderName: providerName,
+		ModelID:      "test-model",
+		Status:       "pending",
+		TotalCount:   1,
+	}
+	if err := db.Create(&taskModel).Error; err != nil {
+		t.Fatalf("create task: %v", err)
+	}
+	return db, taskModel
+}
+
+func newTestPool() *WorkerPool {
+	ctx, cancel := context.WithCancel(context.Background())
+	return &WorkerPool{
+		workerCount: 1,
+		taskQueue:   make(chan *Task, 1),
+		ctx:         ctx,
+		cancel:      cancel,
+	}
+}
+
+func TestPausedTaskRunsAfterResume(t *testing.T) {
+	providerName := "pause-resume-test-provider"
+	_, taskModel := setupPauseTestTask(t, providerName, "pause-resume-task")
+
+	fakeProvider := &blockingProvider{
+		name:     providerName,
+		started:  make(chan struct{}),
+		release:  make(chan struct{}),
+		finished: make(chan struct{}),
+	}
+	provider.Register(fakeProvider)
+
+	wp := newTestPool()
+	wp.Pause()
+	wp.Start()
+	t.Cleanup(wp.Stop)
+
+	if !wp.Submit(&Task{TaskModel: &taskModel, Params: map[string]interface{}{}}) {
+		t.Fatal("submit task failed")
+	}
+
+	select {
+	case <-fakeProvider.started:
+		t.Fatal("provider Generate started while the pool was paused")
+	case <-time.After(200 * time.Millisecond):
+	}
+	if !wp.Paused() {
+		t.Fatal("pool should still be paused")
+	}
+
+	wp.Resume()
+
+	select {
+	case <-fakeProvider.started:
+	case <-time.After(500 * time.Millisecond):
+		t.Fatal("provider Generate did not start after Resume")
+	}
+	close(fakeProvider.release)
+
+	select {
+	case <-fakeProvider.finished:
+	case <-time.After(500 * time.Millisecond):
+		t.Fatal("provider Generate did not finish")
+	}
+}
+
+func TestStopWhilePausedFailsHeldTask(t *testing.T) {
+	providerName := "pause-stop-test-provider"
+	db, taskModel := setupPauseTestTask(t, providerName, "pause-stop-task")
+
+	fakeProvider := &blockingProvider{
+		name:     providerName,
+		started:  make(chan struct{}),
+		release:  make(chan struct{}),
+		finished: make(chan struct{}),
+	}
+	provider.Register(fakeProvider)
+
+	wp := newTestPool()
+	wp.Pause()
+	wp.Start()
+
+	if !wp.Submit(&Task{TaskModel: &taskModel, Params: map[string]interface{}{}}) {
+		t.Fatal("submit task failed")
+	}
+
+	// 等 Worker 把任务从队列里取走并阻塞在 waitResume
+	deadline := time.Now().Add(500 * time.Millisecond)
+	for wp.QueueLength() > 0 {
+		if time.Now().After(deadline) {
+			t.Fatal("worker did not pick up the task")
+		}
+		time.Sleep(10 * time.Millisecond)
+	}
+
+	stopped := make(chan struct{})
+	go func() {
+		wp.Stop()
+		close(stopped)
+	}()
+
+	select {
+	case <-stopped:
+	case <-time.After(time.Second):
+		t.Fatal("Stop did not return while the pool was paused")
+	}
+
+	select {
+	case <-fakeProvider.started:
+		t.Fatal("provider Generate should not run after Stop")
+	default:
+	}
+
+	var saved model.Task
+	if err := db.Where("task_id = ?", taskModel.TaskID).First(&saved).Error; err != nil {
+		t.Fatalf("reload task: %v", err)
+	}
+	if saved.Status != "failed" {
+		t.Fatalf("task status = %q, want failed", saved.Status)
+	}
+	if saved.ErrorMessage != model.STALE_TASK_ERROR_MESSAGE {
+		t.Fatalf("task error = %q, want %q", saved.ErrorMessage, model.STALE_TASK_ERROR_MESSAGE)
+	}
+}
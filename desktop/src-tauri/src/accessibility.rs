@@ -0,0 +1,404 @@
+use std::sync::Mutex;
+
+use tauri::{Emitter, Manager};
+
+use crate::LogState;
+
+// 上次发给前端的系统辅助功能设置，窗口重新获得焦点时比较，变化了才发事件
+static LAST_PREFS: Mutex<Option<AccessibilityPrefs>> = Mutex::new(None);
+
+// 系统辅助功能设置；部分 WebView（尤其 Linux 的 WebKitGTK）不反映 prefers-reduced-motion 等媒体查询，由壳层读取后转发
+#[derive(Clone, PartialEq, serde::Serialize)]
+pub struct AccessibilityPrefs {
+    reduce_motion: bool,
+    increase_contrast: bool,
+    reduce_transparency: bool,
+    // 光标放大倍数，1.0 为系统默认大小
+    cursor_scale: f64,
+    // 系统文字缩放（Windows“使文本更大”、GNOME 文字缩放），1.0 为不缩放
+    text_scale: f64,
+}
+
+impl Default for AccessibilityPrefs {
+    fn default() -> Self {
+        Self {
+            reduce_motion: false,
+            increase_contrast: false,
+            reduce_transparency: false,
+            cursor_scale: 1.0,
+            text_scale: 1.0,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum TaskEvent {
+    Started,
+    Completed,
+    Failed,
+}
+
+impl TaskEvent {
+    fn parse(raw: &str) -> Result<Self, String> {
+        match raw.trim().to_lowercase().as_str() {
+            "started" | "start" => Ok(Self::Started),
+            "completed" | "complete" | "success" => Ok(Self::Completed),
+            "failed" | "fail" | "error" => Ok(Self::Failed),
+            other => Err(format!("unknown task event: {}", other)),
+        }
+    }
+
+    // 前端未传文案时的兜底播报
+    fn default_message(self) -> &'static str {
+        match self {
+            Self::Started => "开始生成图片",
+            Self::Completed => "图片生成完成",
+            Self::Failed => "图片生成失败",
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn post_announcement(
+    app: &tauri::AppHandle,
+    message: String,
+    event: TaskEvent,
+) -> Result<bool, String> {
+    use std::sync::mpsc;
+
+    use objc2::runtime::AnyObject;
+    use objc2_app_kit::{
+        NSAccessibilityAnnouncementKey, NSAccessibilityAnnouncementRequestedNotification,
+        NSAccessibilityPostNotificationWithUserInfo, NSAccessibilityPriorityKey,
+        NSAccessibilityPriorityLevel, NSApplication,
+    };
+    use objc2_foundation::{NSDictionary, NSNumber, NSString};
+
+    // 失败需要打断 VoiceOver 当前朗读，开始/完成按普通优先级排队
+    let priority = match event {
+        TaskEvent::Failed => NSAccessibilityPriorityLevel::High,
+        TaskEvent::Completed => NSAccessibilityPriorityLevel::Medium,
+        TaskEvent::Started => NSAccessibilityPriorityLevel::Low,
+    };
+
+    // AppKit 调用必须在主线程
+    let (tx, rx) = mpsc::channel::<Result<bool, String>>();
+    app.run_on_main_thread(move || {
+        let result = match objc2::MainThreadMarker::new() {
+            Some(mtm) => {
+                let ns_app = NSApplication::sharedApplication(mtm);
+                let text = NSString::from_str(&message);
+                let level = NSNumber::new_isize(priority.0);
+                let text_obj: &AnyObject = &text;
+                let level_obj: &AnyObject = &level;
+                unsafe {
+                    let info = NSDictionary::from_slices(
+                        &[NSAccessibilityAnnouncementKey, NSAccessibilityPriorityKey],
+                        &[text_obj, level_obj],
+                    );
+                    // 以 NSApp 为元素发布，焦点在应用内任意位置都会播报
+                    NSAccessibilityPostNotificationWithUserInfo(
+                        &ns_app,
+                        NSAccessibilityAnnouncementRequestedNotification,
+                        Some(&info),
+                    );
+                }
+                Ok(true)
+            }
+            None => Err("not on main thread".to_string()),
+        };
+        let _ = tx.send(result);
+    })
+    .map_err(|e| format!("run_on_main_thread failed: {}", e))?;
+
+    rx.recv()
+        .map_err(|_| "accessibility announcement aborted".to_string())?
+}
+
+#[cfg(target_os = "windows")]
+fn post_announcement(
+    app: &tauri::AppHandle,
+    message: String,
+    event: TaskEvent,
+) -> Result<bool, String> {
+    use windows_sys::Win32::Foundation::{SysAllocString, SysFreeString};
+    use windows_sys::Win32::UI::Accessibility::{
+        NotificationKind_ActionCompleted, NotificationKind_Other,
+        NotificationProcessing_ImportantAll, NotificationProcessing_MostRecent,
+        UiaHostProviderFromHwnd, UiaRaiseNotificationEvent,
+    };
+
+    let window = app
+        .get_webview_window("main")
+        .ok_or_else(|| "main window not found".to_string())?;
+    let hwnd = window
+        .hwnd()
+        .map_err(|e| format!("get window handle failed: {}", e))?
+        .0;
+
+    // 开始生成只保留最新一条，完成/失败必须全部播报
+    let (kind, processing) = match event {
+        TaskEvent::Started => (NotificationKind_Other, NotificationProcessing_MostRecent),
+        TaskEvent::Completed | TaskEvent::Failed => (
+            NotificationKind_ActionCompleted,
+            NotificationProcessing_ImportantAll,
+        ),
+    };
+    let to_wide = |s: &str| s.encode_utf16().chain(Some(0)).collect::<Vec<u16>>();
+    let text = to_wide(&message);
+    let activity = to_wide("banana.task");
+
+    unsafe {
+        let mut provider: *mut core::ffi::c_void = std::ptr::null_mut();
+        let hr = UiaHostProviderFromHwnd(hwnd, &mut provider);
+        if hr < 0 || provider.is_null() {
+            return Err(format!("get uia provider failed: 0x{:08x}", hr));
+        }
+        let display = SysAllocString(text.as_ptr());
+        let activity_id = SysAllocString(activity.as_ptr());
+        let hr = UiaRaiseNotificationEvent(provider, kind, processing, display, activity_id);
+        SysFreeString(display);
+        SysFreeString(activity_id);
+        // UiaHostProviderFromHwnd 返回的 COM 对象需要手动 Release
+        let vtbl = *(provider as *const *const windows_sys::core::IUnknown_Vtbl);
+        ((*vtbl).Release)(provider);
+        if hr < 0 {
+            return Err(format!("raise uia notification failed: 0x{:08x}", hr));
+        }
+    }
+    Ok(true)
+}
+
+// Linux 上 AT-SPI 没有独立于控件的播报接口，由前端 aria-live 区域兜底
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn post_announcement(
+    _app: &tauri::AppHandle,
+    _message: String,
+    _event: TaskEvent,
+) -> Result<bool, String> {
+    Ok(false)
+}
+
+// 生成任务开始/完成/失败时向读屏软件（VoiceOver / Narrator / NVDA）发送播报
+// 返回 false 表示当前平台不支持原生播报
+#[tauri::command]
+pub fn announce_task_event(
+    app: tauri::AppHandle,
+    event: String,
+    message: Option<String>,
+) -> Result<bool, String> {
+    let event = TaskEvent::parse(&event)?;
+    let message = message
+        .map(|m| m.trim().to_string())
+        .filter(|m| !m.is_empty())
+        .unwrap_or_else(|| event.default_message().to_string());
+
+    let result = post_announcement(&app, message, event);
+    if let Err(err) = &result {
+        app.state::<LogState>().log_app(
+            "WARN",
+            &format!("accessibility announcement failed: {}", err),
+        );
+    }
+    result
+}
+
+#[cfg(target_os = "macos")]
+fn read_prefs(app: &tauri::AppHandle) -> Result<AccessibilityPrefs, String> {
+    use std::sync::mpsc;
+
+    use objc2::rc::{Allocated, Retained};
+    use objc2::runtime::{AnyObject, Bool};
+    use objc2::{class, msg_send};
+    use objc2_foundation::NSString;
+
+    // NSWorkspace 的辅助功能属性需要在主线程读取
+    let (tx, rx) = mpsc::channel::<AccessibilityPrefs>();
+    app.run_on_main_thread(move || {
+        let prefs = unsafe {
+            let workspace: Retained<AnyObject> = msg_send![class!(NSWorkspace), sharedWorkspace];
+            let reduce_motion: Bool = msg_send![&workspace, accessibilityDisplayShouldReduceMotion];
+            let increase_contrast: Bool =
+                msg_send![&workspace, accessibilityDisplayShouldIncreaseContrast];
+            let reduce_transparency: Bool =
+                msg_send![&workspace, accessibilityDisplayShouldReduceTransparency];
+            // 光标大小只存在辅助功能的偏好域里，沙盒下读不到时按默认大小
+            let allocated: Allocated<AnyObject> = msg_send![class!(NSUserDefaults), alloc];
+            let defaults: Option<Retained<AnyObject>> = msg_send![
+                allocated,
+                initWithSuiteName: &*NSString::from_str("com.apple.universalaccess")
+            ];
+            let cursor_scale: f64 = match &defaults {
+                Some(defaults) => msg_send![
+                    defaults,
+                    doubleForKey: &*NSString::from_str("mouseDriverCursorSize")
+                ],
+                None => 0.0,
+            };
+            AccessibilityPrefs {
+                reduce_motion: reduce_motion.as_bool(),
+                increase_contrast: increase_contrast.as_bool(),
+                reduce_transparency: reduce_transparency.as_bool(),
+                cursor_scale: if cursor_scale >= 1.0 {
+                    cursor_scale
+                } else {
+                    1.0
+                },
+                text_scale: 1.0,
+            }
+        };
+        let _ = tx.send(prefs);
+    })
+    .map_err(|e| format!("run_on_main_thread failed: {}", e))?;
+    rx.recv()
+        .map_err(|_| "read accessibility preferences aborted".to_string())
+}
+
+#[cfg(target_os = "windows")]
+fn read_prefs(_app: &tauri::AppHandle) -> Result<AccessibilityPrefs, String> {
+    use windows_sys::Win32::System::Registry::{RegGetValueW, HKEY_CURRENT_USER, RRF_RT_REG_DWORD};
+    use windows_sys::Win32::UI::Accessibility::{HCF_HIGHCONTRASTON, HIGHCONTRASTW};
+    use windows_sys::Win32::UI::WindowsAndMessaging::{
+        SystemParametersInfoW, SPI_GETCLIENTAREAANIMATION, SPI_GETHIGHCONTRAST,
+    };
+
+    // 未公开但稳定的 SPI_GETCURSORBASESIZE，默认 32 像素
+    const SPI_GETCURSORBASESIZE: u32 = 0x2028;
+
+    let to_wide = |s: &str| s.encode_utf16().chain(Some(0)).collect::<Vec<u16>>();
+    let read_dword = |key: &str, name: &str| -> Option<u32> {
+        let (key, name) = (to_wide(key), to_wide(name));
+        let mut value = 0u32;
+        let mut size = std::mem::size_of::<u32>() as u32;
+        let status = unsafe {
+            RegGetValueW(
+                HKEY_CURRENT_USER,
+                key.as_ptr(),
+                name.as_ptr(),
+                RRF_RT_REG_DWORD,
+                std::ptr::null_mut(),
+                &mut value as *mut u32 as *mut core::ffi::c_void,
+                &mut size,
+            )
+        };
+        (status == 0).then_some(value)
+    };
+
+    let mut animations = 1i32;
+    let mut contrast: HIGHCONTRASTW = unsafe { std::mem::zeroed() };
+    contrast.cbSize = std::mem::size_of::<HIGHCONTRASTW>() as u32;
+    let mut cursor_size = 0u32;
+    unsafe {
+        SystemParametersInfoW(
+            SPI_GETCLIENTAREAANIMATION,
+            0,
+            &mut animations as *mut i32 as *mut core::ffi::c_void,
+            0,
+        );
+        SystemParametersInfoW(
+            SPI_GETHIGHCONTRAST,
+            contrast.cbSize,
+            &mut contrast as *mut HIGHCONTRASTW as *mut core::ffi::c_void,
+            0,
+        );
+        SystemParametersInfoW(
+            SPI_GETCURSORBASESIZE,
+            0,
+            &mut cursor_size as *mut u32 as *mut core::ffi::c_void,
+            0,
+        );
+    }
+    let text_scale = read_dword("Software\\Microsoft\\Accessibility", "TextScaleFactor")
+        .filter(|v| *v >= 100)
+        .map_or(1.0, |v| v as f64 / 100.0);
+    let transparency = read_dword(
+        "Software\\Microsoft\\Windows\\CurrentVersion\\Themes\\Personalize",
+        "EnableTransparency",
+    );
+    Ok(AccessibilityPrefs {
+        reduce_motion: animations == 0,
+        increase_contrast: contrast.dwFlags & HCF_HIGHCONTRASTON != 0,
+        reduce_transparency: transparency == Some(0),
+        cursor_scale: if cursor_size > 32 {
+            cursor_size as f64 / 32.0
+        } else {
+            1.0
+        },
+        text_scale,
+    })
+}
+
+// Linux 读 GNOME 的 gsettings；其他桌面环境没有时按默认值
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn read_prefs(_app: &tauri::AppHandle) -> Result<AccessibilityPrefs, String> {
+    fn gsetting(schema: &str, key: &str) -> Option<String> {
+        let output = std::process::Command::new("gsettings")
+            .args(["get", schema, key])
+            .output()
+            .ok()?;
+        output
+            .status
+            .success()
+            .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+
+    let interface = "org.gnome.desktop.interface";
+    let cursor_size = gsetting(interface, "cursor-size").and_then(|v| v.parse::<f64>().ok());
+    let text_scale = gsetting(interface, "text-scaling-factor").and_then(|v| v.parse::<f64>().ok());
+    Ok(AccessibilityPrefs {
+        reduce_motion: gsetting(interface, "enable-animations").as_deref() == Some("false"),
+        increase_contrast: gsetting("org.gnome.desktop.a11y.interface", "high-contrast").as_deref()
+            == Some("true"),
+        reduce_transparency: false,
+        // GNOME 默认光标 24 像素
+        cursor_scale: cursor_size.filter(|v| *v > 24.0).map_or(1.0, |v| v / 24.0),
+        text_scale: text_scale.filter(|v| *v > 0.0).unwrap_or(1.0),
+    })
+}
+
+// 主窗口重新获得焦点时调用：用户多半是刚从系统设置切回来，重新读取并在变化时发出 accessibility-prefs-changed
+pub fn on_window_event(app: &tauri::AppHandle, label: &str, event: &tauri::WindowEvent) {
+    if label != "main" || !matches!(event, tauri::WindowEvent::Focused(true)) {
+        return;
+    }
+    // 还没有前端读取过就不需要比较
+    if LAST_PREFS.lock().unwrap().is_none() {
+        return;
+    }
+    let app = app.clone();
+    // macOS 上读取需要回到主线程执行，这里不能阻塞事件循环
+    std::thread::spawn(move || match read_prefs(&app) {
+        Ok(prefs) => {
+            let mut last = LAST_PREFS.lock().unwrap();
+            if last.as_ref() == Some(&prefs) {
+                return;
+            }
+            *last = Some(prefs.clone());
+            drop(last);
+            let _ = app.emit("accessibility-prefs-changed", prefs);
+        }
+        Err(err) => app.state::<LogState>().log_app(
+            "WARN",
+            &format!("read accessibility preferences failed: {}", err),
+        ),
+    });
+}
+
+// 系统的减弱动态效果、增强对比度、光标大小、文字缩放设置；读取失败时返回默认值，不影响界面
+#[tauri::command]
+pub async fn get_accessibility_prefs(app: tauri::AppHandle) -> AccessibilityPrefs {
+    let app_for_task = app.clone();
+    let result = tauri::async_runtime::spawn_blocking(move || read_prefs(&app_for_task))
+        .await
+        .map_err(|e| format!("read accessibility preferences failed: {}", e))
+        .and_then(|r| r);
+    let prefs = match result {
+        Ok(prefs) => prefs,
+        Err(err) => {
+            app.state::<LogState>().log_app("WARN", &err);
+            AccessibilityPrefs::default()
+        }
+    };
+    *LAST_PREFS.lock().unwrap() = Some(prefs.clone());
+    prefs
+}
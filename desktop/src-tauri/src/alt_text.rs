@@ -0,0 +1,240 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use sha2::{Digest, Sha256};
+use tauri::Manager;
+
+use crate::{BackendPort, LogState};
+
+const IMAGE_TO_PROMPT_PATH: &str = "/api/v1/prompts/image-to-prompt";
+// 屏幕阅读器建议的替代文本长度，完整描述另存一份
+const MAX_ALT_CHARS: usize = 250;
+const MAX_ENTRIES: usize = 5000;
+
+// 串行化 alt_text.json 的读写
+static CACHE_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+struct AltTextEntry {
+    alt_text: String,
+    description: String,
+    language: String,
+    created_at: u128,
+}
+
+#[derive(Default, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+struct AltTextCache {
+    // 键为图片内容的 sha256，同一张图复制、导出到别处后仍能命中
+    entries: BTreeMap<String, AltTextEntry>,
+}
+
+#[derive(serde::Serialize)]
+pub struct AltTextResult {
+    hash: String,
+    alt_text: String,
+    description: String,
+    cached: bool,
+}
+
+fn cache_path(app: &tauri::AppHandle) -> PathBuf {
+    crate::app_data_base(app).join("alt_text.json")
+}
+
+fn image_hash(path: &std::path::Path) -> Result<String, String> {
+    let bytes =
+        std::fs::read(path).map_err(|e| format!("read file failed: {} ({})", e, path.display()))?;
+    Ok(Sha256::digest(&bytes)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect())
+}
+
+fn lookup(app: &tauri::AppHandle, hash: &str) -> Option<AltTextEntry> {
+    let _guard = CACHE_LOCK.lock().unwrap();
+    crate::settings::read_json_or_default::<AltTextCache>(&cache_path(app))
+        .entries
+        .get(hash)
+        .cloned()
+}
+
+fn store(app: &tauri::AppHandle, hash: &str, entry: AltTextEntry) -> Result<(), String> {
+    let _guard = CACHE_LOCK.lock().unwrap();
+    let path = cache_path(app);
+    let mut cache: AltTextCache = crate::settings::read_json_or_default(&path);
+    cache.entries.insert(hash.to_string(), entry);
+    // 超出上限时丢掉最早生成的
+    while cache.entries.len() > MAX_ENTRIES {
+        let Some(oldest) = cache
+            .entries
+            .iter()
+            .min_by_key(|(_, e)| e.created_at)
+            .map(|(k, _)| k.clone())
+        else {
+            break;
+        };
+        cache.entries.remove(&oldest);
+    }
+    crate::settings::write_json_atomic(&path, &cache)
+}
+
+// 反推提示词的结果偏长，替代文本取首段并在句末截断
+fn condense(description: &str) -> String {
+    let first = description
+        .split("\n\n")
+        .map(|p| p.split_whitespace().collect::<Vec<_>>().join(" "))
+        .find(|p| !p.is_empty())
+        .unwrap_or_default();
+    if first.chars().count() <= MAX_ALT_CHARS {
+        return first;
+    }
+    let head: String = first.chars().take(MAX_ALT_CHARS).collect();
+    let end = head
+        .char_indices()
+        .filter(|(_, c)| ['.', '。', '!', '！', '?', '？', ';', '；'].contains(c))
+        .map(|(i, c)| i + c.len_utf8())
+        .last();
+    match end {
+        // 句子太短时宁可硬截断，避免只剩半句开头
+        Some(end) if end > head.len() / 2 => head[..end].to_string(),
+        _ => format!("{}…", head.trim_end()),
+    }
+}
+
+// 导出、分享时按文件内容取已缓存的替代文本，没有就返回 None，不触发生成
+pub fn cached_for_file(app: &tauri::AppHandle, path: &std::path::Path) -> Option<String> {
+    let hash = image_hash(path).ok()?;
+    lookup(app, &hash)
+        .map(|entry| entry.alt_text)
+        .filter(|alt| !alt.is_empty())
+}
+
+// 经过壳层代理调用反推接口，暂停开关和费用记账同样生效
+async fn describe(
+    app: &tauri::AppHandle,
+    image_path: &std::path::Path,
+    language: &str,
+    provider: Option<String>,
+    model: Option<String>,
+) -> Result<String, String> {
+    let backend_port = *app.state::<BackendPort>().0.lock().unwrap();
+    let port = crate::proxy::frontend_port(app, backend_port);
+    if port == 0 {
+        return Err("backend is not running".to_string());
+    }
+    let mut form = vec![
+        ("image_path", image_path.to_string_lossy().to_string()),
+        ("language", language.to_string()),
+    ];
+    if let Some(provider) = provider.filter(|p| !p.trim().is_empty()) {
+        form.push(("provider", provider));
+    }
+    if let Some(model) = model.filter(|m| !m.trim().is_empty()) {
+        form.push(("model", model));
+    }
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(120))
+        .build()
+        .map_err(|e| format!("build alt text client failed: {}", e))?;
+    let response = client
+        .post(format!("http://127.0.0.1:{}{}", port, IMAGE_TO_PROMPT_PATH))
+        .header(reqwest::header::ORIGIN, "tauri://localhost")
+        .form(&form)
+        .send()
+        .await
+        .map_err(|e| format!("alt text request failed: {}", e))?;
+    let status = response.status();
+    let body: serde_json::Value = response
+        .json()
+        .await
+        .map_err(|e| format!("parse alt text response failed: {}", e))?;
+    if !status.is_success() {
+        let message = body
+            .get("message")
+            .and_then(|v| v.as_str())
+            .unwrap_or("request failed");
+        return Err(format!("alt text request failed: {} {}", status, message));
+    }
+    body.pointer("/data/prompt")
+        .and_then(|v| v.as_str())
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .ok_or_else(|| "alt text response is empty".to_string())
+}
+
+// 为图片生成无障碍替代文本；按内容哈希缓存，refresh=true 时重新生成
+#[tauri::command]
+pub async fn suggest_alt_text(
+    app: tauri::AppHandle,
+    path: String,
+    language: Option<String>,
+    provider: Option<String>,
+    model: Option<String>,
+    refresh: Option<bool>,
+) -> Result<AltTextResult, String> {
+    let file_path = crate::resolve_local_path(&app, &path)?;
+    let hash_path = file_path.clone();
+    let hash = tauri::async_runtime::spawn_blocking(move || image_hash(&hash_path))
+        .await
+        .map_err(|e| format!("hash image failed: {}", e))??;
+    let language = language
+        .map(|l| l.trim().to_lowercase())
+        .filter(|l| !l.is_empty())
+        .unwrap_or_else(|| "zh".to_string());
+
+    if !refresh.unwrap_or(false) {
+        if let Some(entry) = lookup(&app, &hash).filter(|e| e.language == language) {
+            return Ok(AltTextResult {
+                hash,
+                alt_text: entry.alt_text,
+                description: entry.description,
+                cached: true,
+            });
+        }
+    }
+
+    let description = describe(&app, &file_path, &language, provider, model).await?;
+    let entry = AltTextEntry {
+        alt_text: condense(&description),
+        description,
+        language,
+        created_at: crate::now_ms(),
+    };
+    if let Err(err) = store(&app, &hash, entry.clone()) {
+        app.state::<LogState>()
+            .log_app("WARN", &format!("save alt text cache failed: {}", err));
+    }
+    app.state::<LogState>().log_app(
+        "INFO",
+        &format!(
+            "Alt text generated chars={}",
+            entry.alt_text.chars().count()
+        ),
+    );
+    Ok(AltTextResult {
+        hash,
+        alt_text: entry.alt_text,
+        description: entry.description,
+        cached: false,
+    })
+}
+
+// 只查缓存，不调用后端；导出、分享时用来默认带上已有的替代文本
+#[tauri::command]
+pub async fn get_cached_alt_text(
+    app: tauri::AppHandle,
+    path: String,
+) -> Result<Option<AltTextResult>, String> {
+    let file_path = crate::resolve_local_path(&app, &path)?;
+    let hash = tauri::async_runtime::spawn_blocking(move || image_hash(&file_path))
+        .await
+        .map_err(|e| format!("hash image failed: {}", e))??;
+    Ok(lookup(&app, &hash).map(|entry| AltTextResult {
+        hash,
+        alt_text: entry.alt_text,
+        description: entry.description,
+        cached: true,
+    }))
+}
@@ -0,0 +1,397 @@
+use std::path::PathBuf;
+
+use ab_glyph::{Font, FontVec, PxScale, ScaleFont};
+use image::{DynamicImage, Rgba, RgbaImage};
+use tauri::Manager;
+
+use crate::export::OutputFormat;
+use crate::fonts::FontState;
+use crate::LogState;
+
+const DEFAULT_COLOR: [u8; 4] = [255, 59, 48, 255];
+const DEFAULT_STROKE_WIDTH: f32 = 4.0;
+const DEFAULT_TEXT_SIZE: f32 = 32.0;
+
+// 坐标均为原图像素；颜色为 #rrggbb / #rrggbbaa
+#[derive(serde::Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Annotation {
+    Rect {
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+        color: Option<String>,
+        stroke_width: Option<f32>,
+        // 半透明填充，用于高亮区域
+        fill: Option<String>,
+    },
+    Arrow {
+        x1: f32,
+        y1: f32,
+        x2: f32,
+        y2: f32,
+        color: Option<String>,
+        stroke_width: Option<f32>,
+    },
+    Text {
+        x: f32,
+        y: f32,
+        text: String,
+        color: Option<String>,
+        size: Option<f32>,
+        font_family: Option<String>,
+        // 文字底色，浅色背景上的文字更易读
+        background: Option<String>,
+    },
+}
+
+#[derive(serde::Serialize)]
+pub struct AnnotateResult {
+    dest_path: String,
+    width: u32,
+    height: u32,
+    annotations: usize,
+}
+
+pub fn parse_color(value: Option<&str>, default: [u8; 4]) -> Result<[u8; 4], String> {
+    let Some(raw) = value.map(str::trim).filter(|v| !v.is_empty()) else {
+        return Ok(default);
+    };
+    let hex = raw.trim_start_matches('#');
+    if !hex.is_ascii() {
+        return Err(format!("invalid color: {}", raw));
+    }
+    let channel = |i: usize| {
+        u8::from_str_radix(&hex[i..i + 2], 16).map_err(|_| format!("invalid color: {}", raw))
+    };
+    match hex.len() {
+        6 => Ok([channel(0)?, channel(2)?, channel(4)?, 255]),
+        8 => Ok([channel(0)?, channel(2)?, channel(4)?, channel(6)?]),
+        _ => Err(format!("invalid color: {}", raw)),
+    }
+}
+
+// 按覆盖率做 alpha 混合，抗锯齿边缘和半透明颜色共用
+fn blend(img: &mut RgbaImage, x: i64, y: i64, color: [u8; 4], coverage: f32) {
+    if x < 0 || y < 0 || x >= img.width() as i64 || y >= img.height() as i64 {
+        return;
+    }
+    let alpha = color[3] as f32 / 255.0 * coverage.clamp(0.0, 1.0);
+    if alpha <= 0.0 {
+        return;
+    }
+    let Rgba(dst) = img.get_pixel_mut(x as u32, y as u32);
+    let dst_alpha = dst[3] as f32 / 255.0;
+    let out_alpha = alpha + dst_alpha * (1.0 - alpha);
+    for (d, c) in dst.iter_mut().zip(color).take(3) {
+        let mixed = c as f32 * alpha + *d as f32 * dst_alpha * (1.0 - alpha);
+        *d = (mixed / out_alpha.max(f32::EPSILON))
+            .round()
+            .clamp(0.0, 255.0) as u8;
+    }
+    dst[3] = (out_alpha * 255.0).round() as u8;
+}
+
+pub fn fill_rect(img: &mut RgbaImage, x: f32, y: f32, width: f32, height: f32, color: [u8; 4]) {
+    let (x0, y0) = (x.round() as i64, y.round() as i64);
+    let (x1, y1) = ((x + width).round() as i64, (y + height).round() as i64);
+    for py in y0.max(0)..y1.min(img.height() as i64) {
+        for px in x0.max(0)..x1.min(img.width() as i64) {
+            blend(img, px, py, color, 1.0);
+        }
+    }
+}
+
+fn distance_to_segment(p: (f32, f32), a: (f32, f32), b: (f32, f32)) -> f32 {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let len_sq = dx * dx + dy * dy;
+    let t = if len_sq <= f32::EPSILON {
+        0.0
+    } else {
+        (((p.0 - a.0) * dx + (p.1 - a.1) * dy) / len_sq).clamp(0.0, 1.0)
+    };
+    let (cx, cy) = (a.0 + t * dx, a.1 + t * dy);
+    ((p.0 - cx).powi(2) + (p.1 - cy).powi(2)).sqrt()
+}
+
+// 圆头粗线：只扫描线段包围盒，按像素中心到线段的距离算覆盖率
+fn draw_line(img: &mut RgbaImage, a: (f32, f32), b: (f32, f32), width: f32, color: [u8; 4]) {
+    let half = width.max(1.0) / 2.0;
+    let min_x = (a.0.min(b.0) - half - 1.0).floor() as i64;
+    let max_x = (a.0.max(b.0) + half + 1.0).ceil() as i64;
+    let min_y = (a.1.min(b.1) - half - 1.0).floor() as i64;
+    let max_y = (a.1.max(b.1) + half + 1.0).ceil() as i64;
+    for py in min_y.max(0)..=max_y.min(img.height() as i64 - 1) {
+        for px in min_x.max(0)..=max_x.min(img.width() as i64 - 1) {
+            let d = distance_to_segment((px as f32 + 0.5, py as f32 + 0.5), a, b);
+            blend(img, px, py, color, half + 0.5 - d);
+        }
+    }
+}
+
+fn fill_triangle(img: &mut RgbaImage, pts: [(f32, f32); 3], color: [u8; 4]) {
+    let edge = |a: (f32, f32), b: (f32, f32), p: (f32, f32)| {
+        (b.0 - a.0) * (p.1 - a.1) - (b.1 - a.1) * (p.0 - a.0)
+    };
+    let min_x = pts.iter().map(|p| p.0).fold(f32::MAX, f32::min).floor() as i64;
+    let max_x = pts.iter().map(|p| p.0).fold(f32::MIN, f32::max).ceil() as i64;
+    let min_y = pts.iter().map(|p| p.1).fold(f32::MAX, f32::min).floor() as i64;
+    let max_y = pts.iter().map(|p| p.1).fold(f32::MIN, f32::max).ceil() as i64;
+    for py in min_y.max(0)..=max_y.min(img.height() as i64 - 1) {
+        for px in min_x.max(0)..=max_x.min(img.width() as i64 - 1) {
+            let p = (px as f32 + 0.5, py as f32 + 0.5);
+            let w0 = edge(pts[1], pts[2], p);
+            let w1 = edge(pts[2], pts[0], p);
+            let w2 = edge(pts[0], pts[1], p);
+            // 顶点顺序不固定，三个符号一致即在内部
+            if (w0 >= 0.0 && w1 >= 0.0 && w2 >= 0.0) || (w0 <= 0.0 && w1 <= 0.0 && w2 <= 0.0) {
+                blend(img, px, py, color, 1.0);
+            }
+        }
+    }
+}
+
+fn draw_arrow(img: &mut RgbaImage, from: (f32, f32), to: (f32, f32), width: f32, color: [u8; 4]) {
+    let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+    let len = (dx * dx + dy * dy).sqrt();
+    if len <= f32::EPSILON {
+        return;
+    }
+    let (ux, uy) = (dx / len, dy / len);
+    // 箭头大小随线宽变化，但不超过线长的一半
+    let head_len = (width * 4.0).max(12.0).min(len / 2.0);
+    let head_half = head_len * 0.6;
+    let base = (to.0 - ux * head_len, to.1 - uy * head_len);
+    draw_line(img, from, base, width, color);
+    fill_triangle(
+        img,
+        [
+            to,
+            (base.0 - uy * head_half, base.1 + ux * head_half),
+            (base.0 + uy * head_half, base.1 - ux * head_half),
+        ],
+        color,
+    );
+}
+
+// 按字体族名查找系统字体，找不到时退回默认无衬线字体；text 中主字体缺的字（中文、emoji）自动追加回退字体
+pub fn with_font<T>(
+    db: &fontdb::Database,
+    family: Option<&str>,
+    text: &str,
+    f: impl FnOnce(&[FontVec]) -> T,
+) -> Result<T, String> {
+    let chain = crate::fonts::font_chain(db, family, text)?;
+    Ok(f(&chain))
+}
+
+// 回退链里最高的字体决定行高和基线，中文字体的上下伸部通常比西文字体大
+fn metrics<F: Font>(fonts: &[F], size: f32) -> (f32, f32) {
+    fonts.iter().fold((0.0, 0.0), |(ascent, height), font| {
+        let scaled = font.as_scaled(PxScale::from(size));
+        (
+            f32::max(ascent, scaled.ascent()),
+            f32::max(height, scaled.height() + scaled.line_gap()),
+        )
+    })
+}
+
+// 多行文本的像素宽高
+pub fn measure_text<F: Font>(fonts: &[F], size: f32, text: &str) -> (f32, f32) {
+    let width = text
+        .lines()
+        .map(|line| {
+            let mut prev = None;
+            line.chars()
+                .map(|c| {
+                    let index = crate::fonts::pick(fonts, c);
+                    let scaled = fonts[index].as_scaled(PxScale::from(size));
+                    let id = scaled.glyph_id(c);
+                    // 只在同一字体的相邻字形之间做字距调整
+                    let kern = match prev {
+                        Some((p_index, p)) if p_index == index => scaled.kern(p, id),
+                        _ => 0.0,
+                    };
+                    prev = Some((index, id));
+                    kern + scaled.h_advance(id)
+                })
+                .sum::<f32>()
+        })
+        .fold(0.0, f32::max);
+    let lines = text.lines().count().max(1);
+    (width, metrics(fonts, size).1 * lines as f32)
+}
+
+// (x, y) 为文本框左上角，支持换行
+pub fn draw_text<F: Font>(
+    img: &mut RgbaImage,
+    fonts: &[F],
+    size: f32,
+    x: f32,
+    y: f32,
+    text: &str,
+    color: [u8; 4],
+) {
+    let (ascent, step) = metrics(fonts, size);
+    for (row, line) in text.lines().enumerate() {
+        let baseline = y + ascent + step * row as f32;
+        let mut caret = x;
+        let mut prev = None;
+        for c in line.chars() {
+            let index = crate::fonts::pick(fonts, c);
+            let font = &fonts[index];
+            let scaled = font.as_scaled(PxScale::from(size));
+            let id = scaled.glyph_id(c);
+            if let Some((p_index, p)) = prev {
+                if p_index == index {
+                    caret += scaled.kern(p, id);
+                }
+            }
+            prev = Some((index, id));
+            let glyph =
+                id.with_scale_and_position(scaled.scale(), ab_glyph::point(caret, baseline));
+            caret += scaled.h_advance(id);
+            let Some(outlined) = font.outline_glyph(glyph) else {
+                continue;
+            };
+            let bounds = outlined.px_bounds();
+            outlined.draw(|gx, gy, coverage| {
+                blend(
+                    img,
+                    bounds.min.x as i64 + gx as i64,
+                    bounds.min.y as i64 + gy as i64,
+                    color,
+                    coverage,
+                );
+            });
+        }
+    }
+}
+
+fn render(
+    img: &mut RgbaImage,
+    annotations: &[Annotation],
+    fonts: &FontState,
+) -> Result<(), String> {
+    for annotation in annotations {
+        match annotation {
+            Annotation::Rect {
+                x,
+                y,
+                width,
+                height,
+                color,
+                stroke_width,
+                fill,
+            } => {
+                if fill.is_some() {
+                    let fill = parse_color(fill.as_deref(), DEFAULT_COLOR)?;
+                    fill_rect(img, *x, *y, *width, *height, fill);
+                }
+                let color = parse_color(color.as_deref(), DEFAULT_COLOR)?;
+                let w = stroke_width.unwrap_or(DEFAULT_STROKE_WIDTH);
+                let (x1, y1) = (x + width, y + height);
+                for (a, b) in [
+                    ((*x, *y), (x1, *y)),
+                    ((x1, *y), (x1, y1)),
+                    ((x1, y1), (*x, y1)),
+                    ((*x, y1), (*x, *y)),
+                ] {
+                    draw_line(img, a, b, w, color);
+                }
+            }
+            Annotation::Arrow {
+                x1,
+                y1,
+                x2,
+                y2,
+                color,
+                stroke_width,
+            } => {
+                let color = parse_color(color.as_deref(), DEFAULT_COLOR)?;
+                let w = stroke_width.unwrap_or(DEFAULT_STROKE_WIDTH);
+                draw_arrow(img, (*x1, *y1), (*x2, *y2), w, color);
+            }
+            Annotation::Text {
+                x,
+                y,
+                text,
+                color,
+                size,
+                font_family,
+                background,
+            } => {
+                if text.trim().is_empty() {
+                    continue;
+                }
+                let color = parse_color(color.as_deref(), DEFAULT_COLOR)?;
+                let background = background
+                    .as_deref()
+                    .map(|b| parse_color(Some(b), [0, 0, 0, 160]))
+                    .transpose()?;
+                let size = size.unwrap_or(DEFAULT_TEXT_SIZE).max(1.0);
+                let db = fonts.database();
+                with_font(&db, font_family.as_deref(), text, |fonts| {
+                    if let Some(bg) = background {
+                        let (w, h) = measure_text(fonts, size, text);
+                        let pad = size * 0.25;
+                        fill_rect(img, x - pad, y - pad, w + pad * 2.0, h + pad * 2.0, bg);
+                    }
+                    draw_text(img, fonts, size, *x, *y, text, color);
+                })?;
+            }
+        }
+    }
+    Ok(())
+}
+
+// 把箭头/矩形/文字标注烧录到图片副本上导出，原图不改动
+#[tauri::command]
+pub async fn export_annotated_image(
+    app: tauri::AppHandle,
+    path: String,
+    dest_path: String,
+    annotations: Vec<Annotation>,
+    quality: Option<u8>,
+) -> Result<AnnotateResult, String> {
+    let src_path = crate::resolve_local_path(&app, &path)?;
+    let dest = PathBuf::from(dest_path.trim());
+    if dest.as_os_str().is_empty() {
+        return Err("dest_path is empty".to_string());
+    }
+    let format = OutputFormat::from_path(&dest)?;
+    let fonts = FontState(app.state::<FontState>().0.clone());
+    let log_state = app.state::<LogState>().inner().clone();
+
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        let mut img = crate::export::load_image(&src_path)?.to_rgba8();
+        render(&mut img, &annotations, &fonts)?;
+        let (width, height) = img.dimensions();
+        crate::disk::ensure_free_space(&dest, width as u64 * height as u64 * 4)?;
+        crate::export::write_image(
+            &DynamicImage::ImageRgba8(img),
+            &dest,
+            format,
+            quality.unwrap_or(92),
+            None,
+        )?;
+        Ok::<_, String>(AnnotateResult {
+            dest_path: dest.to_string_lossy().to_string(),
+            width,
+            height,
+            annotations: annotations.len(),
+        })
+    })
+    .await
+    .map_err(|e| format!("annotate task failed: {}", e))??;
+
+    log_state.log_app(
+        "INFO",
+        &format!(
+            "Annotated export finished annotations={} dest={}",
+            result.annotations, result.dest_path
+        ),
+    );
+    Ok(result)
+}
@@ -0,0 +1,98 @@
+use std::path::PathBuf;
+
+use tauri::Manager;
+use tauri_plugin_dialog::{DialogExt, MessageDialogKind};
+use tauri_plugin_shell::process::Command;
+use tauri_plugin_shell::ShellExt;
+
+use crate::LogState;
+
+// 当前进程是否运行在 Rosetta 2 转译下（x86_64 版本跑在 Apple Silicon 上）
+#[cfg(target_os = "macos")]
+pub fn is_translated() -> bool {
+    let mut value: libc::c_int = 0;
+    let mut size = std::mem::size_of::<libc::c_int>();
+    let ret = unsafe {
+        libc::sysctlbyname(
+            c"sysctl.proc_translated".as_ptr(),
+            &mut value as *mut libc::c_int as *mut libc::c_void,
+            &mut size,
+            std::ptr::null_mut(),
+            0,
+        )
+    };
+    // 老系统 / Intel 机器上没有这个键，返回 ENOENT
+    ret == 0 && value == 1
+}
+
+#[cfg(not(target_os = "macos"))]
+pub fn is_translated() -> bool {
+    false
+}
+
+// 机器的真实架构：主程序被转译时，sidecar 仍应选原生版本
+pub fn host_arch() -> &'static str {
+    if is_translated() {
+        "aarch64"
+    } else {
+        std::env::consts::ARCH
+    }
+}
+
+// universal 包里按架构附带的 sidecar（见 tauri.universal.conf.json），和主程序放在同一目录
+fn arch_sidecar_path(arch: &str) -> Option<PathBuf> {
+    let dir = std::env::current_exe().ok()?.parent()?.to_path_buf();
+    let name = if cfg!(windows) {
+        format!("server-{}.exe", arch)
+    } else {
+        format!("server-{}", arch)
+    };
+    let path = dir.join(name);
+    path.is_file().then_some(path)
+}
+
+// 不经过 tauri 直接拉起后端时用（见 batch.rs）：打包后 sidecar 与主程序同目录，文件名去掉了 target triple
+pub fn sidecar_path() -> Option<PathBuf> {
+    if let Some(path) = arch_sidecar_path(host_arch()) {
+        return Some(path);
+    }
+    let dir = std::env::current_exe().ok()?.parent()?.to_path_buf();
+    let name = if cfg!(windows) {
+        "server.exe"
+    } else {
+        "server"
+    };
+    let path = dir.join(name);
+    path.is_file().then_some(path)
+}
+
+// 优先使用与机器架构一致的 sidecar，找不到时回退到按 target triple 打包的默认 sidecar
+pub fn sidecar_command(app: &tauri::AppHandle) -> Result<Command, String> {
+    let arch = host_arch();
+    if let Some(path) = arch_sidecar_path(arch) {
+        app.state::<LogState>().log_app(
+            "INFO",
+            &format!("Using {} sidecar at {}", arch, path.display()),
+        );
+        return Ok(app.shell().command(path));
+    }
+    app.shell()
+        .sidecar("server")
+        .map_err(|err| format!("create sidecar command failed: {}", err))
+}
+
+// 通过 Rosetta 运行时提醒用户下载原生版本：图片编解码和界面渲染都会明显变慢
+pub fn warn_if_translated(app: &tauri::AppHandle) {
+    if !is_translated() {
+        return;
+    }
+    app.state::<LogState>()
+        .log_app("WARN", "Running under Rosetta translation on Apple Silicon");
+    app.dialog()
+        .message(
+            "当前运行的是 Intel 版本，正在通过 Rosetta 转译运行，性能会明显下降。\n\n建议前往官网下载 Apple 芯片版本或通用版本。",
+        )
+        .title("建议使用 Apple 芯片版本")
+        .kind(MessageDialogKind::Warning)
+        .show(|_| {});
+}
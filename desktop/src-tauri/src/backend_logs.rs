@@ -0,0 +1,121 @@
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+use tauri::{Manager, State};
+
+// 打包后看不到控制台输出，内存里保留最近的 sidecar 输出供前端日志面板查看
+const CAPACITY: usize = 2000;
+const DEFAULT_LIMIT: usize = 200;
+
+#[derive(Default)]
+pub struct BackendLogBuffer(Mutex<VecDeque<BackendLogLine>>);
+
+#[derive(Clone, serde::Serialize)]
+pub struct BackendLogLine {
+    ts: u128,
+    // stdout / stderr
+    stream: &'static str,
+    // debug / info / warn / error，按行内容推断
+    level: &'static str,
+    message: String,
+}
+
+fn severity(level: &str) -> u8 {
+    match level {
+        "debug" => 0,
+        "info" => 1,
+        "warn" => 2,
+        _ => 3,
+    }
+}
+
+// Go 的日志没有统一格式：先看显式级别，再看 gin 访问日志里的状态码（如 | 502 |）
+fn infer_level(line: &str) -> &'static str {
+    let upper = line.to_ascii_uppercase();
+    if ["ERROR", "FATAL", "PANIC"]
+        .iter()
+        .any(|k| upper.contains(k))
+    {
+        return "error";
+    }
+    if upper.contains("WARN") {
+        return "warn";
+    }
+    if upper.contains("[GIN]") {
+        let status = line
+            .split('|')
+            .map(str::trim)
+            .find(|part| part.len() == 3 && part.bytes().all(|b| b.is_ascii_digit()));
+        match status.and_then(|s| s.chars().next()) {
+            Some('5') => return "error",
+            Some('4') => return "warn",
+            _ => {}
+        }
+    }
+    if upper.contains("DEBUG") {
+        return "debug";
+    }
+    "info"
+}
+
+pub fn push(app: &tauri::AppHandle, stream: &'static str, message: &str) {
+    let message = message.trim_end();
+    if message.is_empty() {
+        return;
+    }
+    let Some(buffer) = app.try_state::<BackendLogBuffer>() else {
+        return;
+    };
+    let mut lines = buffer.0.lock().unwrap();
+    if lines.len() >= CAPACITY {
+        lines.pop_front();
+    }
+    lines.push_back(BackendLogLine {
+        ts: crate::now_ms(),
+        stream,
+        level: infer_level(message),
+        message: message.to_string(),
+    });
+}
+
+// 指定输出流最近 n 行（时间正序），用于启动失败时展示
+pub fn recent(app: &tauri::AppHandle, stream: &str, n: usize) -> Vec<String> {
+    let Some(buffer) = app.try_state::<BackendLogBuffer>() else {
+        return Vec::new();
+    };
+    let lines = buffer.0.lock().unwrap();
+    let mut out: Vec<String> = lines
+        .iter()
+        .rev()
+        .filter(|l| l.stream == stream)
+        .take(n)
+        .map(|l| l.message.clone())
+        .collect();
+    out.reverse();
+    out
+}
+
+// 返回最近 limit 行（时间正序），level 为最低级别，如 warn 返回 warn 和 error
+#[tauri::command]
+pub fn get_backend_logs(
+    buffer: State<'_, BackendLogBuffer>,
+    limit: Option<usize>,
+    level: Option<String>,
+) -> Result<Vec<BackendLogLine>, String> {
+    let min = match level.as_deref().map(str::trim) {
+        None | Some("") => 0,
+        Some(level @ ("debug" | "info" | "warn" | "error")) => severity(level),
+        Some(other) => return Err(format!("unknown log level: {}", other)),
+    };
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(CAPACITY);
+    let lines = buffer.0.lock().unwrap();
+    let mut out: Vec<BackendLogLine> = lines
+        .iter()
+        .rev()
+        .filter(|l| severity(l.level) >= min)
+        .take(limit)
+        .cloned()
+        .collect();
+    out.reverse();
+    Ok(out)
+}
@@ -0,0 +1,93 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, System};
+use tauri::Emitter;
+
+// 事件推送的最小间隔，再短没有意义（CPU 占用本身按两次采样间的差值计算）
+const MIN_INTERVAL_SECS: u64 = 1;
+
+// 复用同一个 System，CPU 占用才能按上一次采样计算增量
+static SYSTEM: Mutex<Option<System>> = Mutex::new(None);
+// 每次修改推送间隔都换一代，旧的推送线程发现代数变化后退出
+static MONITOR_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Clone, serde::Serialize)]
+pub struct BackendResources {
+    pid: u32,
+    // 多核下可能超过 100
+    cpu_percent: f32,
+    rss_bytes: u64,
+    // Windows 上是句柄数；平台不支持时为 None
+    open_files: Option<usize>,
+    uptime_secs: u64,
+}
+
+fn refresh(system: &mut System, pid: Pid) -> bool {
+    system.refresh_processes_specifics(
+        ProcessesToUpdate::Some(&[pid]),
+        true,
+        ProcessRefreshKind::nothing().with_cpu().with_memory(),
+    ) > 0
+}
+
+// 阻塞执行：首次采样需要间隔一段时间再取一次才能得到 CPU 占用
+fn sample(pid: u32) -> Result<BackendResources, String> {
+    let pid = Pid::from_u32(pid);
+    let mut guard = SYSTEM.lock().unwrap();
+    let first = guard.is_none();
+    let system = guard.get_or_insert_with(System::new);
+    if !refresh(system, pid) {
+        return Err(format!("backend process {} not found", pid));
+    }
+    if first {
+        std::thread::sleep(sysinfo::MINIMUM_CPU_UPDATE_INTERVAL);
+        refresh(system, pid);
+    }
+    let process = system
+        .process(pid)
+        .ok_or_else(|| format!("backend process {} not found", pid))?;
+    Ok(BackendResources {
+        pid: pid.as_u32(),
+        cpu_percent: process.cpu_usage(),
+        rss_bytes: process.memory(),
+        open_files: process.open_files(),
+        uptime_secs: process.run_time(),
+    })
+}
+
+// 外部后端模式或 sidecar 未运行时没有本地进程
+fn sidecar_pid(app: &tauri::AppHandle) -> Result<u32, String> {
+    crate::backend_status(app)
+        .pid
+        .ok_or_else(|| "backend is not running locally".to_string())
+}
+
+#[tauri::command]
+pub async fn get_backend_resources(app: tauri::AppHandle) -> Result<BackendResources, String> {
+    let pid = sidecar_pid(&app)?;
+    tauri::async_runtime::spawn_blocking(move || sample(pid))
+        .await
+        .map_err(|e| format!("sample backend resources failed: {}", e))?
+}
+
+// 状态栏组件挂载时开启，按间隔推送 backend-resources 事件；传 0 停止推送
+#[tauri::command]
+pub fn set_backend_resources_interval(app: tauri::AppHandle, interval_secs: u64) {
+    let generation = MONITOR_GENERATION.fetch_add(1, Ordering::SeqCst) + 1;
+    if interval_secs == 0 {
+        return;
+    }
+    let interval = Duration::from_secs(interval_secs.max(MIN_INTERVAL_SECS));
+    std::thread::spawn(move || loop {
+        if MONITOR_GENERATION.load(Ordering::SeqCst) != generation {
+            return;
+        }
+        // 后端重启的间隙取不到 PID，跳过这一轮
+        if let Ok(resources) = sidecar_pid(&app).and_then(sample) {
+            let _ = app.emit("backend-resources", resources);
+        }
+        std::thread::sleep(interval);
+    });
+}
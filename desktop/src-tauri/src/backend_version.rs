@@ -0,0 +1,127 @@
+use std::sync::Mutex;
+use std::time::Duration;
+
+use tauri::{Emitter, Manager};
+
+use crate::LogState;
+
+// 当前界面支持的后端接口版本范围（含两端），后端 internal/api/version.go 里的 APIVersion
+const MIN_API_VERSION: u32 = 1;
+const MAX_API_VERSION: u32 = 1;
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+// 最近一次就绪时读取到的后端版本；后端重启后会重新读取
+static CURRENT: Mutex<Option<BackendVersion>> = Mutex::new(None);
+
+#[derive(Clone, serde::Serialize)]
+pub struct BackendVersion {
+    version: String,
+    // 没有 /version 接口的旧版后端记为 0
+    api_version: u32,
+    compatible: bool,
+}
+
+#[derive(Clone, serde::Serialize)]
+struct VersionMismatchPayload {
+    backend: BackendVersion,
+    app_version: String,
+    min_api_version: u32,
+    max_api_version: u32,
+}
+
+#[derive(Default, serde::Deserialize)]
+#[serde(default)]
+struct VersionData {
+    version: String,
+    api_version: u32,
+}
+
+#[derive(Default, serde::Deserialize)]
+#[serde(default)]
+struct VersionResponse {
+    data: VersionData,
+}
+
+async fn fetch(port: u16) -> Result<VersionData, String> {
+    let client = reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .map_err(|e| format!("build version client failed: {}", e))?;
+    let response = client
+        .get(format!("http://127.0.0.1:{}/api/v1/version", port))
+        .header(reqwest::header::ORIGIN, "tauri://localhost")
+        .send()
+        .await
+        .map_err(|e| format!("request backend version failed: {}", e))?;
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        return Ok(VersionData {
+            version: "unknown".to_string(),
+            api_version: 0,
+        });
+    }
+    if !response.status().is_success() {
+        return Err(format!(
+            "request backend version failed: {}",
+            response.status()
+        ));
+    }
+    response
+        .json::<VersionResponse>()
+        .await
+        .map(|r| r.data)
+        .map_err(|e| format!("parse backend version failed: {}", e))
+}
+
+// 后端健康检查通过后调用；不匹配时发出 backend-version-mismatch，由前端提示重新安装或更新
+pub fn check(app: &tauri::AppHandle, port: u16) {
+    CURRENT.lock().unwrap().take();
+    let app = app.clone();
+    std::thread::spawn(move || {
+        let data = match tauri::async_runtime::block_on(fetch(port)) {
+            Ok(data) => data,
+            Err(err) => {
+                app.state::<LogState>().log_app("WARN", &err);
+                return;
+            }
+        };
+        let backend = BackendVersion {
+            compatible: (MIN_API_VERSION..=MAX_API_VERSION).contains(&data.api_version),
+            version: data.version,
+            api_version: data.api_version,
+        };
+        *CURRENT.lock().unwrap() = Some(backend.clone());
+        let app_version = app.package_info().version.to_string();
+        if backend.compatible {
+            app.state::<LogState>().log_app(
+                "INFO",
+                &format!(
+                    "Backend version {} api={}",
+                    backend.version, backend.api_version
+                ),
+            );
+            return;
+        }
+        app.state::<LogState>().log_app(
+            "WARN",
+            &format!(
+                "Backend api version {} not in {}..={} (backend {}, app {})",
+                backend.api_version, MIN_API_VERSION, MAX_API_VERSION, backend.version, app_version
+            ),
+        );
+        let _ = app.emit(
+            "backend-version-mismatch",
+            VersionMismatchPayload {
+                backend,
+                app_version,
+                min_api_version: MIN_API_VERSION,
+                max_api_version: MAX_API_VERSION,
+            },
+        );
+    });
+}
+
+// 尚未读取到（后端未就绪或请求失败）时返回 None
+#[tauri::command]
+pub fn get_backend_version() -> Option<BackendVersion> {
+    CURRENT.lock().unwrap().clone()
+}
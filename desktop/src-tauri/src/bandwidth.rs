@@ -0,0 +1,240 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use tauri::{Emitter, Manager, State};
+use tauri_plugin_notification::NotificationExt;
+
+use crate::settings::SettingsState;
+use crate::LogState;
+
+// 按天保留的记录数，设置页只展示最近几周
+const KEEP_DAYS: usize = 90;
+// 代理每个请求都会记账（含缩略图），攒一会儿再落盘
+const FLUSH_INTERVAL: Duration = Duration::from_secs(10);
+
+static METER: Mutex<Option<Meter>> = Mutex::new(None);
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct BandwidthSettings {
+    // 每日流量软上限（MB），0 表示不提醒；只提醒不拦截
+    pub daily_cap_mb: u64,
+    pub warn_ratio: f64,
+}
+
+impl Default for BandwidthSettings {
+    fn default() -> Self {
+        Self {
+            daily_cap_mb: 0,
+            warn_ratio: 0.8,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Default, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct Traffic {
+    sent: u64,
+    received: u64,
+}
+
+#[derive(Clone, Default, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct DayUsage {
+    date: String,
+    sent: u64,
+    received: u64,
+    // proxy / download / reference / feed / redownload
+    by_source: BTreeMap<String, Traffic>,
+}
+
+impl DayUsage {
+    fn total(&self) -> u64 {
+        self.sent + self.received
+    }
+}
+
+#[derive(Default, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+struct BandwidthStore {
+    days: Vec<DayUsage>,
+}
+
+struct Meter {
+    store: BandwidthStore,
+    last_flush: Instant,
+    dirty: bool,
+}
+
+#[derive(serde::Serialize)]
+pub struct BandwidthReport {
+    days: Vec<DayUsage>,
+    today: DayUsage,
+    daily_cap_mb: u64,
+}
+
+#[derive(Clone, serde::Serialize)]
+struct BandwidthWarning {
+    used_bytes: u64,
+    cap_bytes: u64,
+    exceeded: bool,
+}
+
+fn store_path(app: &tauri::AppHandle) -> PathBuf {
+    crate::app_data_base(app).join("bandwidth.json")
+}
+
+fn with_meter<T>(app: &tauri::AppHandle, f: impl FnOnce(&mut Meter) -> T) -> T {
+    let mut guard = METER.lock().unwrap();
+    let meter = guard.get_or_insert_with(|| Meter {
+        store: crate::settings::read_json_or_default(&store_path(app)),
+        last_flush: Instant::now(),
+        dirty: false,
+    });
+    f(meter)
+}
+
+fn write_store(app: &tauri::AppHandle, meter: &mut Meter) {
+    if !meter.dirty {
+        return;
+    }
+    if let Err(err) = crate::settings::write_json_atomic(&store_path(app), &meter.store) {
+        app.state::<LogState>()
+            .log_app("WARN", &format!("save bandwidth usage failed: {}", err));
+    }
+    meter.dirty = false;
+    meter.last_flush = Instant::now();
+}
+
+// 只在本次记账跨过阈值时提醒，与费用预算的做法一致
+fn check_cap(app: &tauri::AppHandle, before: u64, after: u64) {
+    let settings = app.state::<SettingsState>().get().bandwidth;
+    if settings.daily_cap_mb == 0 {
+        return;
+    }
+    let cap = settings.daily_cap_mb * 1024 * 1024;
+    let warn = (cap as f64 * settings.warn_ratio.clamp(0.0, 1.0)) as u64;
+    let (exceeded, body) = if before < cap && after >= cap {
+        (
+            true,
+            format!(
+                "今日流量 {:.1} MB 已超过上限 {} MB",
+                after as f64 / 1048576.0,
+                settings.daily_cap_mb
+            ),
+        )
+    } else if warn > 0 && before < warn && after >= warn {
+        (
+            false,
+            format!(
+                "今日流量 {:.1} MB 已达到上限的 {:.0}%",
+                after as f64 / 1048576.0,
+                settings.warn_ratio * 100.0
+            ),
+        )
+    } else {
+        return;
+    };
+    app.state::<LogState>()
+        .log_app("WARN", &format!("Bandwidth cap alert: {}", body));
+    let _ = app.emit(
+        "bandwidth-warning",
+        BandwidthWarning {
+            used_bytes: after,
+            cap_bytes: cap,
+            exceeded,
+        },
+    );
+    if let Err(err) = app
+        .notification()
+        .builder()
+        .title("流量提醒")
+        .body(body)
+        .show()
+    {
+        app.state::<LogState>().log_app(
+            "WARN",
+            &format!("show bandwidth notification failed: {}", err),
+        );
+    }
+}
+
+// 代理和各个下载入口在传输结束后调用
+pub fn record(app: &tauri::AppHandle, source: &str, sent: u64, received: u64) {
+    if sent == 0 && received == 0 {
+        return;
+    }
+    let date = crate::clock::today();
+    let (before, after) = with_meter(app, |meter| {
+        let days = &mut meter.store.days;
+        if days.last().is_none_or(|d| d.date != date) {
+            days.push(DayUsage {
+                date: date.clone(),
+                ..Default::default()
+            });
+            if days.len() > KEEP_DAYS {
+                days.drain(..days.len() - KEEP_DAYS);
+            }
+        }
+        let day = days.last_mut().unwrap();
+        let before = day.total();
+        day.sent += sent;
+        day.received += received;
+        let traffic = day.by_source.entry(source.to_string()).or_default();
+        traffic.sent += sent;
+        traffic.received += received;
+        let after = day.total();
+        meter.dirty = true;
+        if meter.last_flush.elapsed() >= FLUSH_INTERVAL {
+            write_store(app, meter);
+        }
+        (before, after)
+    });
+    check_cap(app, before, after);
+}
+
+// 退出时把还没落盘的计数写掉
+pub fn flush(app: &tauri::AppHandle) {
+    with_meter(app, |meter| write_store(app, meter));
+}
+
+// 最近 days 天（默认 30）的每日统计，按日期倒序
+#[tauri::command]
+pub fn get_bandwidth_usage(
+    app: tauri::AppHandle,
+    state: State<'_, SettingsState>,
+    days: Option<u32>,
+) -> BandwidthReport {
+    let limit = days.unwrap_or(30).clamp(1, KEEP_DAYS as u32) as usize;
+    let date = crate::clock::today();
+    let list: Vec<DayUsage> = with_meter(&app, |meter| {
+        meter.store.days.iter().rev().take(limit).cloned().collect()
+    });
+    let today = match list.first().filter(|d| d.date == date) {
+        Some(day) => day.clone(),
+        None => DayUsage {
+            date,
+            ..Default::default()
+        },
+    };
+    BandwidthReport {
+        days: list,
+        today,
+        daily_cap_mb: state.get().bandwidth.daily_cap_mb,
+    }
+}
+
+#[tauri::command]
+pub fn get_bandwidth_settings(state: State<'_, SettingsState>) -> BandwidthSettings {
+    state.get().bandwidth
+}
+
+#[tauri::command]
+pub fn set_bandwidth_settings(
+    state: State<'_, SettingsState>,
+    settings: BandwidthSettings,
+) -> Result<BandwidthSettings, String> {
+    Ok(state.update(|s| s.bandwidth = settings)?.bandwidth)
+}
@@ -0,0 +1,510 @@
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Stdio};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+// 退出码：全部成功 / 启动或运行出错 / 参数或提示词文件有误 / 部分任务失败
+const EXIT_OK: i32 = 0;
+const EXIT_ERROR: i32 = 1;
+const EXIT_USAGE: i32 = 2;
+const EXIT_PARTIAL: i32 = 3;
+
+const BACKEND_START_TIMEOUT: Duration = Duration::from_secs(60);
+const HEALTH_POLL_INTERVAL: Duration = Duration::from_millis(200);
+const TASK_POLL_INTERVAL: Duration = Duration::from_secs(2);
+const DEFAULT_TASK_TIMEOUT_SECS: u64 = 600;
+const STOP_GRACE: Duration = Duration::from_secs(5);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+
+pub const USAGE: &str = "  nanobanana batch <prompts.jsonl> --out <dir> [--provider <name>] [--model <id>]
+                   [--timeout <secs>]
+                               generate every prompt in the file and save results with manifest.json;
+                               each line is a prompt string or {\"prompt\", \"provider\", \"model_id\", \"params\", \"name\"}";
+
+struct Options {
+    input: PathBuf,
+    out: PathBuf,
+    provider: Option<String>,
+    model: Option<String>,
+    task_timeout: Duration,
+}
+
+#[derive(Default, serde::Deserialize)]
+#[serde(default)]
+struct JobLine {
+    prompt: String,
+    provider: Option<String>,
+    model_id: Option<String>,
+    // 原样并入请求的 params（aspectRatio、imageSize、count 等）
+    params: serde_json::Map<String, serde_json::Value>,
+    // 输出文件名前缀，缺省用行号
+    name: Option<String>,
+}
+
+struct Job {
+    line: usize,
+    name: String,
+    prompt: String,
+    provider: String,
+    model_id: Option<String>,
+    params: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(serde::Serialize)]
+struct ManifestItem {
+    line: usize,
+    name: String,
+    prompt: String,
+    provider: String,
+    model_id: Option<String>,
+    task_id: Option<String>,
+    // completed / failed / timeout
+    status: String,
+    // 相对输出目录的文件名
+    file: Option<String>,
+    width: Option<u64>,
+    height: Option<u64>,
+    error: Option<String>,
+    elapsed_ms: u128,
+}
+
+#[derive(serde::Serialize)]
+struct Manifest {
+    input: String,
+    started_at: String,
+    finished_at: String,
+    total: usize,
+    succeeded: usize,
+    failed: usize,
+    items: Vec<ManifestItem>,
+}
+
+fn parse_options(args: &[String]) -> Result<Options, String> {
+    let mut input = None;
+    let mut out = None;
+    let mut provider = None;
+    let mut model = None;
+    let mut timeout = DEFAULT_TASK_TIMEOUT_SECS;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let mut value = |flag: &str| {
+            iter.next()
+                .cloned()
+                .ok_or_else(|| format!("missing value for {}", flag))
+        };
+        match arg.as_str() {
+            "--out" | "-o" => out = Some(PathBuf::from(value(arg)?)),
+            "--provider" => provider = Some(value(arg)?),
+            "--model" => model = Some(value(arg)?),
+            "--timeout" => {
+                timeout = value(arg)?
+                    .parse()
+                    .map_err(|_| "invalid --timeout".to_string())?
+            }
+            _ if arg.starts_with('-') => return Err(format!("unknown option: {}", arg)),
+            _ if input.is_none() => input = Some(PathBuf::from(arg)),
+            _ => return Err(format!("unexpected argument: {}", arg)),
+        }
+    }
+    Ok(Options {
+        input: input.ok_or_else(|| "missing prompts file".to_string())?,
+        out: out.ok_or_else(|| "missing --out".to_string())?,
+        provider,
+        model,
+        task_timeout: Duration::from_secs(timeout.max(1)),
+    })
+}
+
+// 文件名里只保留字母数字和 - _，其余替换掉
+fn sanitize(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .take(64)
+        .collect();
+    name.trim_matches('_').to_string()
+}
+
+// 整个文件先校验一遍，有错时一张都不生成，避免跑到一半才发现格式问题
+fn load_jobs(options: &Options) -> Result<Vec<Job>, String> {
+    let file = std::fs::File::open(&options.input)
+        .map_err(|e| format!("open prompts file failed: {}", e))?;
+    let mut jobs = Vec::new();
+    for (index, line) in BufReader::new(file).lines().enumerate() {
+        let line_no = index + 1;
+        let line = line.map_err(|e| format!("read prompts file failed: {}", e))?;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with("//") {
+            continue;
+        }
+        let job: JobLine = match serde_json::from_str::<serde_json::Value>(line) {
+            Ok(serde_json::Value::String(prompt)) => JobLine {
+                prompt,
+                ..Default::default()
+            },
+            Ok(value @ serde_json::Value::Object(_)) => {
+                serde_json::from_value(value).map_err(|e| format!("line {}: {}", line_no, e))?
+            }
+            Ok(_) => return Err(format!("line {}: expected string or object", line_no)),
+            Err(e) => return Err(format!("line {}: {}", line_no, e)),
+        };
+        let prompt = job.prompt.trim().to_string();
+        if prompt.is_empty() {
+            return Err(format!("line {}: prompt is empty", line_no));
+        }
+        let provider = job
+            .provider
+            .or_else(|| options.provider.clone())
+            .ok_or_else(|| format!("line {}: no provider (use --provider)", line_no))?;
+        let name = job
+            .name
+            .map(|n| sanitize(&n))
+            .filter(|n| !n.is_empty())
+            .unwrap_or_else(|| format!("{:04}", line_no));
+        jobs.push(Job {
+            line: line_no,
+            name,
+            prompt,
+            provider,
+            model_id: job.model_id.or_else(|| options.model.clone()),
+            params: job.params,
+        });
+    }
+    if jobs.is_empty() {
+        return Err("prompts file has no prompts".to_string());
+    }
+    Ok(jobs)
+}
+
+// 由批处理拉起的后端；已有实例在运行时直接复用它的本机端口，不另起进程
+struct Backend {
+    port: u16,
+    child: Option<Child>,
+}
+
+impl Drop for Backend {
+    fn drop(&mut self) {
+        let Some(mut child) = self.child.take() else {
+            return;
+        };
+        // 先请求后端自行退出（落盘、关闭数据库），超时再强制结束
+        let _ = crate::shutdown::request_stop(child.id());
+        let started = Instant::now();
+        while started.elapsed() < STOP_GRACE {
+            if matches!(child.try_wait(), Ok(Some(_))) {
+                return;
+            }
+            std::thread::sleep(HEALTH_POLL_INTERVAL);
+        }
+        let _ = child.kill();
+        let _ = child.wait();
+    }
+}
+
+async fn healthy(client: &reqwest::Client, port: u16) -> bool {
+    client
+        .get(format!("http://127.0.0.1:{}/api/v1/health", port))
+        .header(reqwest::header::ORIGIN, "tauri://localhost")
+        .send()
+        .await
+        .is_ok_and(|r| r.status().is_success())
+}
+
+// 与 sidecar.rs 一样预分配端口并通过 SERVER_PORT 传入；端口被抢时以 stdout 里的 SERVER_PORT= 为准
+fn start_backend(client: &reqwest::Client) -> Result<Backend, String> {
+    if let Some(port) = crate::instance::running_port() {
+        if tauri::async_runtime::block_on(healthy(client, port)) {
+            eprintln!("using running instance on port {}", port);
+            return Ok(Backend { port, child: None });
+        }
+    }
+    let path =
+        crate::arch::sidecar_path().ok_or_else(|| "backend executable not found".to_string())?;
+    let reserved = std::net::TcpListener::bind((std::net::Ipv4Addr::LOCALHOST, 0))
+        .and_then(|l| l.local_addr())
+        .map(|a| a.port())
+        .map_err(|e| format!("reserve port failed: {}", e))?;
+    let mut child = std::process::Command::new(&path)
+        .env("TAURI_PLATFORM", std::env::consts::OS)
+        .env("TAURI_FAMILY", std::env::consts::FAMILY)
+        .env("SERVER_PORT", reserved.to_string())
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("spawn backend failed: {}", e))?;
+    let (tx, rx) = mpsc::channel();
+    if let Some(stdout) = child.stdout.take() {
+        std::thread::spawn(move || {
+            for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                if let Some(port) = crate::parse_port_line(&line, "SERVER_PORT=") {
+                    let _ = tx.send(port);
+                }
+            }
+        });
+    }
+    let mut backend = Backend {
+        port: reserved,
+        child: Some(child),
+    };
+    let started = Instant::now();
+    while started.elapsed() < BACKEND_START_TIMEOUT {
+        if let Ok(port) = rx.try_recv() {
+            backend.port = port;
+        }
+        if let Some(child) = backend.child.as_mut() {
+            if let Ok(Some(status)) = child.try_wait() {
+                return Err(format!("backend exited during startup: {}", status));
+            }
+        }
+        if tauri::async_runtime::block_on(healthy(client, backend.port)) {
+            return Ok(backend);
+        }
+        std::thread::sleep(HEALTH_POLL_INTERVAL);
+    }
+    Err(format!(
+        "backend not ready within {}s",
+        BACKEND_START_TIMEOUT.as_secs()
+    ))
+}
+
+async fn api_data(response: reqwest::Response) -> Result<serde_json::Value, String> {
+    let status = response.status();
+    let body: serde_json::Value = response
+        .json()
+        .await
+        .map_err(|e| format!("parse response failed: {}", e))?;
+    if !status.is_success() {
+        let message = body["message"].as_str().unwrap_or("request failed");
+        return Err(format!("{} {}", status.as_u16(), message));
+    }
+    Ok(body["data"].clone())
+}
+
+async fn submit(client: &reqwest::Client, port: u16, job: &Job) -> Result<String, String> {
+    let mut params = job.params.clone();
+    params.insert("prompt".to_string(), job.prompt.clone().into());
+    let mut body = serde_json::json!({
+        "provider": job.provider,
+        "params": params,
+    });
+    if let Some(model_id) = &job.model_id {
+        body["model_id"] = model_id.clone().into();
+    }
+    let response = client
+        .post(format!("http://127.0.0.1:{}/api/v1/tasks/generate", port))
+        .header(reqwest::header::ORIGIN, "tauri://localhost")
+        .json(&body)
+        .send()
+        .await
+        .map_err(|e| format!("submit failed: {}", e))?;
+    let data = api_data(response)
+        .await
+        .map_err(|e| format!("submit failed: {}", e))?;
+    data["task_id"]
+        .as_str()
+        .map(String::from)
+        .ok_or_else(|| "submit failed: no task_id in response".to_string())
+}
+
+async fn fetch_task(
+    client: &reqwest::Client,
+    port: u16,
+    task_id: &str,
+) -> Result<serde_json::Value, String> {
+    let response = client
+        .get(format!(
+            "http://127.0.0.1:{}/api/v1/tasks/{}",
+            port, task_id
+        ))
+        .header(reqwest::header::ORIGIN, "tauri://localhost")
+        .send()
+        .await
+        .map_err(|e| format!("query task failed: {}", e))?;
+    api_data(response)
+        .await
+        .map_err(|e| format!("query task failed: {}", e))
+}
+
+async fn download(
+    client: &reqwest::Client,
+    port: u16,
+    task_id: &str,
+    dest: &Path,
+) -> Result<(), String> {
+    let response = client
+        .get(format!(
+            "http://127.0.0.1:{}/api/v1/images/{}/download",
+            port, task_id
+        ))
+        .header(reqwest::header::ORIGIN, "tauri://localhost")
+        .send()
+        .await
+        .map_err(|e| format!("download failed: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("download failed: {}", response.status()));
+    }
+    let bytes = response
+        .bytes()
+        .await
+        .map_err(|e| format!("download failed: {}", e))?;
+    std::fs::write(dest, &bytes).map_err(|e| format!("write image failed: {}", e))
+}
+
+fn run_job(
+    client: &reqwest::Client,
+    port: u16,
+    job: &Job,
+    out: &Path,
+    timeout: Duration,
+) -> ManifestItem {
+    let started = Instant::now();
+    let mut item = ManifestItem {
+        line: job.line,
+        name: job.name.clone(),
+        prompt: job.prompt.clone(),
+        provider: job.provider.clone(),
+        model_id: job.model_id.clone(),
+        task_id: None,
+        status: "failed".to_string(),
+        file: None,
+        width: None,
+        height: None,
+        error: None,
+        elapsed_ms: 0,
+    };
+    let mut wait = || -> Result<(), String> {
+        let task_id = tauri::async_runtime::block_on(submit(client, port, job))?;
+        item.task_id = Some(task_id.clone());
+        loop {
+            if started.elapsed() >= timeout {
+                item.status = "timeout".to_string();
+                return Err(format!("not finished within {}s", timeout.as_secs()));
+            }
+            std::thread::sleep(TASK_POLL_INTERVAL);
+            let task = tauri::async_runtime::block_on(fetch_task(client, port, &task_id))?;
+            match task["status"].as_str().unwrap_or_default() {
+                "completed" => {
+                    let ext = Path::new(task["local_path"].as_str().unwrap_or_default())
+                        .extension()
+                        .and_then(|e| e.to_str())
+                        .unwrap_or("png")
+                        .to_string();
+                    let file = format!("{}.{}", job.name, ext);
+                    tauri::async_runtime::block_on(download(
+                        client,
+                        port,
+                        &task_id,
+                        &out.join(&file),
+                    ))?;
+                    item.file = Some(file);
+                    item.width = task["width"].as_u64();
+                    item.height = task["height"].as_u64();
+                    return Ok(());
+                }
+                "failed" => {
+                    return Err(task["error_message"]
+                        .as_str()
+                        .unwrap_or("generation failed")
+                        .to_string())
+                }
+                _ => {}
+            }
+        }
+    };
+    match wait() {
+        Ok(()) => item.status = "completed".to_string(),
+        Err(err) => item.error = Some(err),
+    }
+    item.elapsed_ms = started.elapsed().as_millis();
+    item
+}
+
+fn now() -> String {
+    crate::clock::now(false).to_rfc3339()
+}
+
+// 入口：cli::run 识别到 batch 子命令后调用，返回进程退出码
+pub fn run(args: &[String]) -> i32 {
+    let options = match parse_options(args) {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("nanobanana batch: {}\nusage:\n{}", err, USAGE);
+            return EXIT_USAGE;
+        }
+    };
+    let jobs = match load_jobs(&options) {
+        Ok(jobs) => jobs,
+        Err(err) => {
+            eprintln!("nanobanana batch: {}", err);
+            return EXIT_USAGE;
+        }
+    };
+    if let Err(err) = std::fs::create_dir_all(&options.out) {
+        eprintln!("nanobanana batch: create output dir failed: {}", err);
+        return EXIT_ERROR;
+    }
+    let Ok(client) = reqwest::Client::builder().timeout(REQUEST_TIMEOUT).build() else {
+        eprintln!("nanobanana batch: build http client failed");
+        return EXIT_ERROR;
+    };
+    let started_at = now();
+    let backend = match start_backend(&client) {
+        Ok(backend) => backend,
+        Err(err) => {
+            eprintln!("nanobanana batch: {}", err);
+            return EXIT_ERROR;
+        }
+    };
+
+    let mut items = Vec::with_capacity(jobs.len());
+    for (index, job) in jobs.iter().enumerate() {
+        eprint!("[{}/{}] {} ... ", index + 1, jobs.len(), job.name);
+        let item = run_job(
+            &client,
+            backend.port,
+            job,
+            &options.out,
+            options.task_timeout,
+        );
+        match &item.error {
+            None => eprintln!("ok ({}s)", item.elapsed_ms / 1000),
+            Some(err) => eprintln!("{}: {}", item.status, err),
+        }
+        items.push(item);
+    }
+    drop(backend);
+
+    let succeeded = items.iter().filter(|i| i.status == "completed").count();
+    let manifest = Manifest {
+        input: options.input.to_string_lossy().to_string(),
+        started_at,
+        finished_at: now(),
+        total: items.len(),
+        succeeded,
+        failed: items.len() - succeeded,
+        items,
+    };
+    let manifest_path = options.out.join("manifest.json");
+    if let Err(err) = crate::settings::write_json_atomic(&manifest_path, &manifest) {
+        eprintln!("nanobanana batch: {}", err);
+        return EXIT_ERROR;
+    }
+    println!("{}", manifest_path.display());
+    eprintln!(
+        "{} succeeded, {} failed",
+        manifest.succeeded, manifest.failed
+    );
+    if manifest.failed > 0 {
+        EXIT_PARTIAL
+    } else {
+        EXIT_OK
+    }
+}
@@ -0,0 +1,235 @@
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tauri::Manager;
+use tauri_plugin_opener::OpenerExt;
+
+use crate::{BackendPort, LogState};
+
+const SESSION_COOKIE: &str = "banana_ui";
+const MAX_HEADER_LINES: usize = 64;
+
+#[derive(Clone)]
+pub struct BrowserServer {
+    port: u16,
+    token: String,
+}
+
+// 首次在浏览器打开时才启动本地 UI 服务，端口与 token 在本次运行期间保持不变
+pub struct BrowserUiState(pub Arc<Mutex<Option<BrowserServer>>>);
+
+fn query_param<'a>(query: &'a str, key: &str) -> Option<&'a str> {
+    query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(k, _)| *k == key)
+        .map(|(_, v)| v)
+}
+
+fn write_response(
+    stream: &mut TcpStream,
+    status: &str,
+    headers: &[(&str, String)],
+    body: &[u8],
+    head_only: bool,
+) {
+    let mut head = format!(
+        "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\nX-Content-Type-Options: nosniff\r\n",
+        status,
+        body.len()
+    );
+    for (name, value) in headers {
+        head.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    head.push_str("\r\n");
+    let _ = stream.write_all(head.as_bytes());
+    if !head_only {
+        let _ = stream.write_all(body);
+    }
+    let _ = stream.flush();
+}
+
+// 极简静态服务：只读地提供打包进应用的前端资源，API 请求仍然直连 sidecar
+fn handle_connection(app: &tauri::AppHandle, token: &str, mut stream: TcpStream) {
+    let _ = stream.set_read_timeout(Some(Duration::from_secs(10)));
+    let Ok(clone) = stream.try_clone() else {
+        return;
+    };
+    let mut reader = BufReader::new(clone);
+    let mut request_line = String::new();
+    if reader.read_line(&mut request_line).is_err() {
+        return;
+    }
+    let mut cookie = String::new();
+    for _ in 0..MAX_HEADER_LINES {
+        let mut line = String::new();
+        if reader.read_line(&mut line).unwrap_or(0) == 0 || line.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("cookie") {
+                cookie = value.trim().to_string();
+            }
+        }
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or("");
+    let target = parts.next().unwrap_or("/");
+    if method != "GET" && method != "HEAD" {
+        write_response(&mut stream, "405 Method Not Allowed", &[], b"", false);
+        return;
+    }
+    let head_only = method == "HEAD";
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+
+    // 带 token 的入口链接：换成 HttpOnly cookie 后重定向，避免 token 留在地址栏和历史记录里
+    if query_param(query, "token")
+        .is_some_and(|t| crate::instance::constant_time_eq(t.as_bytes(), token.as_bytes()))
+    {
+        let rest: Vec<&str> = query
+            .split('&')
+            .filter(|pair| !pair.starts_with("token="))
+            .collect();
+        let location = if rest.is_empty() {
+            path.to_string()
+        } else {
+            format!("{}?{}", path, rest.join("&"))
+        };
+        write_response(
+            &mut stream,
+            "302 Found",
+            &[
+                ("Location", location),
+                (
+                    "Set-Cookie",
+                    format!(
+                        "{}={}; Path=/; HttpOnly; SameSite=Strict",
+                        SESSION_COOKIE, token
+                    ),
+                ),
+            ],
+            b"",
+            head_only,
+        );
+        return;
+    }
+    let authorized = cookie
+        .split(';')
+        .filter_map(|c| c.trim().split_once('='))
+        .any(|(k, v)| {
+            k == SESSION_COOKIE && crate::instance::constant_time_eq(v.as_bytes(), token.as_bytes())
+        });
+    if !authorized {
+        write_response(&mut stream, "403 Forbidden", &[], b"forbidden", head_only);
+        return;
+    }
+
+    let asset_path = path.trim_start_matches('/');
+    if asset_path.split('/').any(|seg| seg == "..") {
+        write_response(&mut stream, "400 Bad Request", &[], b"", head_only);
+        return;
+    }
+    let resolver = app.asset_resolver();
+    // 前端是单页应用，找不到的路径统一回退到 index.html
+    let asset = (!asset_path.is_empty())
+        .then(|| resolver.get(asset_path.to_string()))
+        .flatten()
+        .or_else(|| resolver.get("index.html".to_string()));
+    let Some(asset) = asset else {
+        write_response(&mut stream, "404 Not Found", &[], b"not found", head_only);
+        return;
+    };
+    let mut headers = vec![
+        ("Content-Type", asset.mime_type.clone()),
+        ("Cache-Control", "no-store".to_string()),
+    ];
+    if let Some(csp) = asset.csp_header.clone() {
+        headers.push(("Content-Security-Policy", csp));
+    }
+    write_response(&mut stream, "200 OK", &headers, &asset.bytes, head_only);
+}
+
+fn start_server(app: &tauri::AppHandle) -> Result<BrowserServer, String> {
+    let listener =
+        TcpListener::bind("127.0.0.1:0").map_err(|e| format!("bind ui server failed: {}", e))?;
+    let port = listener
+        .local_addr()
+        .map_err(|e| format!("bind ui server failed: {}", e))?
+        .port();
+    let server = BrowserServer {
+        port,
+        token: crate::instance::random_token()?,
+    };
+
+    let app_handle = app.clone();
+    let token = server.token.clone();
+    std::thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let app_handle = app_handle.clone();
+            let token = token.clone();
+            std::thread::spawn(move || handle_connection(&app_handle, &token, stream));
+        }
+    });
+    app.state::<LogState>().log_app(
+        "INFO",
+        &format!("Browser UI server listening port={}", port),
+    );
+    Ok(server)
+}
+
+// route 只接受站内路径（可带 #hash），防止拼出指向外部站点的链接
+fn normalize_route(route: Option<&str>) -> Result<(String, String), String> {
+    let route = route.unwrap_or("").trim();
+    if route.contains("://") || route.starts_with("//") || route.contains('\\') {
+        return Err(format!("invalid route: {}", route));
+    }
+    let (path, fragment) = route.split_once('#').unwrap_or((route, ""));
+    let (path, _) = path.split_once('?').unwrap_or((path, ""));
+    let fragment = if fragment.is_empty() {
+        String::new()
+    } else {
+        format!("#{}", fragment)
+    };
+    Ok((format!("/{}", path.trim_start_matches('/')), fragment))
+}
+
+// 在系统浏览器中打开当前会话：本地 UI 服务端口 + 本次运行的会话 token + sidecar 端口 + 可选路由
+// sidecar 仍由桌面端管理，浏览器页面和桌面窗口一样经过壳层代理访问同一个后端
+#[tauri::command]
+pub fn open_in_browser(app: tauri::AppHandle, route: Option<String>) -> Result<String, String> {
+    let backend_port =
+        crate::proxy::frontend_port(&app, *app.state::<BackendPort>().0.lock().unwrap());
+    if backend_port == 0 {
+        return Err("backend is not running".to_string());
+    }
+    let (path, fragment) = normalize_route(route.as_deref())?;
+
+    let server = {
+        let state = app.state::<BrowserUiState>();
+        let mut guard = state.0.lock().unwrap();
+        match guard.as_ref() {
+            Some(server) => server.clone(),
+            None => {
+                let server = start_server(&app)?;
+                *guard = Some(server.clone());
+                server
+            }
+        }
+    };
+
+    let url = format!(
+        "http://127.0.0.1:{}{}?token={}&backend_port={}{}",
+        server.port, path, server.token, backend_port, fragment
+    );
+    app.opener()
+        .open_url(&url, None::<String>)
+        .map_err(|e| format!("open browser failed: {}", e))?;
+    app.state::<LogState>().log_app(
+        "INFO",
+        &format!("Opened session in browser route={}{}", path, fragment),
+    );
+    Ok(url)
+}
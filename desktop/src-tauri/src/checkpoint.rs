@@ -0,0 +1,522 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{BufWriter, Write};
+use std::path::{Component, Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use rusqlite::limits::Limit;
+use rusqlite::types::ValueRef;
+use rusqlite::Connection;
+use sha2::{Digest, Sha256};
+use tauri::{Emitter, Manager};
+
+use crate::LogState;
+
+const MANIFEST_NAME: &str = "checkpoint.json";
+const MANIFEST_FORMAT: &str = "nano-banana-checkpoint";
+const MANIFEST_VERSION: u32 = 1;
+const DB_DIR: &str = "db";
+const STORAGE_DIR: &str = "storage";
+// 图片交给 Git LFS，数据库导出为按行排序的 SQL 文本，git diff 能看出具体改了哪些记录
+const GITATTRIBUTES: &str = "\
+storage/** filter=lfs diff=lfs merge=lfs -text
+db/*.sql text eol=lf
+checkpoint.json text eol=lf
+";
+
+// 目录布局：checkpoint.json + db/<表名>.sql + storage/<与应用存储目录相同的相对路径>
+#[derive(Default, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+struct Manifest {
+    format: String,
+    version: u32,
+    label: String,
+    created_at: String,
+    app_version: String,
+    tables: Vec<String>,
+    files: BTreeMap<String, ManifestFile>,
+}
+
+#[derive(Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+struct ManifestFile {
+    sha256: String,
+    size: u64,
+    // 与上次检查点的大小、修改时间一致时直接复用哈希，不必重新读取大图
+    modified_ms: u64,
+}
+
+#[derive(serde::Serialize)]
+pub struct CheckpointResult {
+    dir: String,
+    tables: usize,
+    files: usize,
+    added: usize,
+    updated: usize,
+    removed: usize,
+}
+
+#[derive(serde::Serialize)]
+pub struct CheckpointRestoreResult {
+    tables: usize,
+    restored_files: usize,
+    unchanged_files: usize,
+}
+
+#[derive(Clone, serde::Serialize)]
+struct CheckpointProgress {
+    phase: &'static str,
+    done: usize,
+    total: usize,
+}
+
+fn emit_progress(app: &tauri::AppHandle, phase: &'static str, done: usize, total: usize) {
+    let _ = app.emit(
+        "checkpoint-progress",
+        CheckpointProgress { phase, done, total },
+    );
+}
+
+fn sha256_file(path: &Path) -> Result<String, String> {
+    let mut file =
+        fs::File::open(path).map_err(|e| format!("open {} failed: {}", path.display(), e))?;
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut file, &mut hasher)
+        .map_err(|e| format!("read {} failed: {}", path.display(), e))?;
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect())
+}
+
+fn modified_ms(meta: &fs::Metadata) -> u64 {
+    meta.modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+fn collect_files(root: &Path, dir: &Path, out: &mut Vec<String>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        if file_type.is_dir() {
+            collect_files(root, &path, out);
+        } else if file_type.is_file() {
+            let Ok(rel) = path.strip_prefix(root) else {
+                continue;
+            };
+            // 清单里统一用 /，Windows 上导出的检查点在 macOS 上也能恢复
+            let rel: Vec<String> = rel
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy().to_string())
+                .collect();
+            out.push(rel.join("/"));
+        }
+    }
+}
+
+fn read_manifest(dir: &Path) -> Option<Manifest> {
+    let bytes = fs::read(dir.join(MANIFEST_NAME)).ok()?;
+    let manifest: Manifest = serde_json::from_slice(&bytes).ok()?;
+    (manifest.format == MANIFEST_FORMAT).then_some(manifest)
+}
+
+fn sql_literal(value: ValueRef<'_>) -> String {
+    match value {
+        ValueRef::Null => "NULL".to_string(),
+        ValueRef::Integer(i) => i.to_string(),
+        ValueRef::Real(f) => format!("{:?}", f),
+        ValueRef::Text(t) => format!("'{}'", String::from_utf8_lossy(t).replace('\'', "''")),
+        ValueRef::Blob(b) => format!(
+            "X'{}'",
+            b.iter()
+                .map(|byte| format!("{:02x}", byte))
+                .collect::<String>()
+        ),
+    }
+}
+
+fn quote_ident(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+// 每张表一个文件：建表语句 + 按 rowid 排序的 INSERT，每行一条记录，改动时 diff 只涉及对应行
+fn dump_database(conn: &Connection, dir: &Path) -> Result<Vec<String>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT name, sql FROM sqlite_master \
+             WHERE type = 'table' AND name NOT LIKE 'sqlite_%' AND sql IS NOT NULL \
+             AND sql NOT LIKE 'CREATE VIRTUAL%' ORDER BY name",
+        )
+        .map_err(|e| format!("read schema failed: {}", e))?;
+    let tables: Vec<(String, String)> = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+        .and_then(|rows| rows.collect())
+        .map_err(|e| format!("read schema failed: {}", e))?;
+    let mut indexes: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    let mut stmt = conn
+        .prepare(
+            "SELECT tbl_name, sql FROM sqlite_master \
+             WHERE type IN ('index', 'trigger') AND sql IS NOT NULL ORDER BY name",
+        )
+        .map_err(|e| format!("read schema failed: {}", e))?;
+    let rows: Vec<(String, String)> = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+        .and_then(|rows| rows.collect())
+        .map_err(|e| format!("read schema failed: {}", e))?;
+    for (table, sql) in rows {
+        indexes.entry(table).or_default().push(sql);
+    }
+
+    let _ = fs::remove_dir_all(dir);
+    fs::create_dir_all(dir).map_err(|e| format!("create db dir failed: {}", e))?;
+    for (table, create_sql) in &tables {
+        let file = fs::File::create(dir.join(format!("{}.sql", table)))
+            .map_err(|e| format!("create dump for {} failed: {}", table, e))?;
+        let mut out = BufWriter::new(file);
+        let write_err = |e: std::io::Error| format!("write dump for {} failed: {}", table, e);
+        writeln!(out, "{};", create_sql).map_err(write_err)?;
+        for sql in indexes.get(table).into_iter().flatten() {
+            writeln!(out, "{};", sql).map_err(write_err)?;
+        }
+        // WITHOUT ROWID 表没有 rowid，退回按全部列排序
+        let order = if create_sql.to_ascii_uppercase().contains("WITHOUT ROWID") {
+            "1"
+        } else {
+            "rowid"
+        };
+        let mut stmt = conn
+            .prepare(&format!(
+                "SELECT * FROM {} ORDER BY {}",
+                quote_ident(table),
+                order
+            ))
+            .map_err(|e| format!("read {} failed: {}", table, e))?;
+        let columns = stmt.column_count();
+        let mut rows = stmt
+            .query([])
+            .map_err(|e| format!("read {} failed: {}", table, e))?;
+        while let Some(row) = rows
+            .next()
+            .map_err(|e| format!("read {} failed: {}", table, e))?
+        {
+            let values: Vec<String> = (0..columns)
+                .map(|i| row.get_ref(i).map(sql_literal))
+                .collect::<Result<_, _>>()
+                .map_err(|e| format!("read {} failed: {}", table, e))?;
+            writeln!(
+                out,
+                "INSERT INTO {} VALUES({});",
+                quote_ident(table),
+                values.join(",")
+            )
+            .map_err(write_err)?;
+        }
+        out.flush().map_err(write_err)?;
+    }
+    Ok(tables.into_iter().map(|(name, _)| name).collect())
+}
+
+fn export(app: &tauri::AppHandle, dir: &Path, label: &str) -> Result<CheckpointResult, String> {
+    fs::create_dir_all(dir).map_err(|e| format!("create checkpoint dir failed: {}", e))?;
+    let previous = read_manifest(dir).unwrap_or_default();
+
+    emit_progress(app, "database", 0, 1);
+    let conn = crate::db::open_readonly(app)?;
+    let tables = dump_database(&conn, &dir.join(DB_DIR))?;
+    drop(conn);
+    emit_progress(app, "database", 1, 1);
+
+    // 只复制相对上次检查点新增或变化的文件，删除已不存在的文件，git 里即为增量
+    let storage = crate::paths::storage_dir(app);
+    let mut names = Vec::new();
+    collect_files(&storage, &storage, &mut names);
+    names.sort();
+    let dest_root = dir.join(STORAGE_DIR);
+    let mut files = BTreeMap::new();
+    let (mut added, mut updated) = (0, 0);
+    for (done, name) in names.iter().enumerate() {
+        let source = storage.join(name);
+        let Ok(meta) = fs::metadata(&source) else {
+            continue;
+        };
+        let dest = dest_root.join(name);
+        let mut entry = ManifestFile {
+            sha256: String::new(),
+            size: meta.len(),
+            modified_ms: modified_ms(&meta),
+        };
+        let old = previous.files.get(name);
+        entry.sha256 = match old {
+            Some(old) if old.size == entry.size && old.modified_ms == entry.modified_ms => {
+                old.sha256.clone()
+            }
+            _ => sha256_file(&source)?,
+        };
+        let unchanged = old.is_some_and(|old| old.sha256 == entry.sha256) && dest.is_file();
+        if !unchanged {
+            if let Some(parent) = dest.parent() {
+                fs::create_dir_all(parent).map_err(|e| format!("create dir failed: {}", e))?;
+            }
+            fs::copy(&source, &dest).map_err(|e| format!("copy {} failed: {}", name, e))?;
+            if old.is_some() {
+                updated += 1;
+            } else {
+                added += 1;
+            }
+        }
+        files.insert(name.clone(), entry);
+        emit_progress(app, "storage", done + 1, names.len());
+    }
+    let mut removed = 0;
+    for name in previous.files.keys().filter(|n| !files.contains_key(*n)) {
+        if fs::remove_file(dest_root.join(name)).is_ok() {
+            removed += 1;
+        }
+    }
+
+    if !dir.join(".gitattributes").exists() {
+        fs::write(dir.join(".gitattributes"), GITATTRIBUTES)
+            .map_err(|e| format!("write .gitattributes failed: {}", e))?;
+    }
+    let manifest = Manifest {
+        format: MANIFEST_FORMAT.to_string(),
+        version: MANIFEST_VERSION,
+        label: label.to_string(),
+        created_at: crate::clock::rfc3339(app),
+        app_version: app.package_info().version.to_string(),
+        tables: tables.clone(),
+        files,
+    };
+    crate::settings::write_json_atomic(&dir.join(MANIFEST_NAME), &manifest)?;
+
+    Ok(CheckpointResult {
+        dir: dir.to_string_lossy().to_string(),
+        tables: tables.len(),
+        files: manifest.files.len(),
+        added,
+        updated,
+        removed,
+    })
+}
+
+// 检查点来自 git 仓库，清单内容不可信：只接受由普通路径段组成的相对路径
+fn checked_relative(name: &str) -> Result<PathBuf, String> {
+    let path = Path::new(name);
+    let valid = !name.is_empty() && path.components().all(|c| matches!(c, Component::Normal(_)));
+    if !valid {
+        return Err(format!("invalid checkpoint file name: {}", name));
+    }
+    Ok(path.to_path_buf())
+}
+
+// 解析符号链接后必须仍在 root 之内，防止仓库里的链接把读写引到别处
+fn resolve_inside(root: &Path, path: &Path) -> Result<PathBuf, String> {
+    let root = root
+        .canonicalize()
+        .map_err(|e| format!("resolve {} failed: {}", root.display(), e))?;
+    let resolved = path
+        .canonicalize()
+        .map_err(|e| format!("resolve {} failed: {}", path.display(), e))?;
+    if !resolved.starts_with(&root) {
+        return Err(format!("path outside checkpoint: {}", path.display()));
+    }
+    Ok(resolved)
+}
+
+// 写入目标可能还不存在，只校验父目录，文件名取自已校验过的相对路径
+fn storage_dest(storage: &Path, rel: &Path) -> Result<PathBuf, String> {
+    let dest = storage.join(rel);
+    let parent = dest
+        .parent()
+        .ok_or_else(|| format!("invalid path: {}", dest.display()))?;
+    fs::create_dir_all(parent).map_err(|e| format!("create dir failed: {}", e))?;
+    let parent = resolve_inside(storage, parent)
+        .map_err(|_| format!("path outside storage: {}", dest.display()))?;
+    let file_name = dest
+        .file_name()
+        .ok_or_else(|| format!("invalid path: {}", dest.display()))?;
+    let dest = parent.join(file_name);
+    // 已存在的目标是符号链接时不跟随写入
+    if fs::symlink_metadata(&dest).is_ok_and(|m| m.file_type().is_symlink()) {
+        return Err(format!("path outside storage: {}", dest.display()));
+    }
+    Ok(dest)
+}
+
+fn valid_table_name(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+// 只恢复当前数据库里已有的表，表名同时用来拼 db/<表名>.sql
+fn check_tables(app: &tauri::AppHandle, tables: &[String]) -> Result<(), String> {
+    let conn = crate::db::open_readonly(app)?;
+    let mut stmt = conn
+        .prepare("SELECT name FROM sqlite_master WHERE type = 'table'")
+        .map_err(|e| format!("read schema failed: {}", e))?;
+    let live: Vec<String> = stmt
+        .query_map([], |row| row.get(0))
+        .and_then(|rows| rows.collect())
+        .map_err(|e| format!("read schema failed: {}", e))?;
+    for table in tables {
+        if !valid_table_name(table) || !live.contains(table) {
+            return Err(format!("unexpected checkpoint table: {}", table));
+        }
+    }
+    Ok(())
+}
+
+// 把 SQL 文本重建到临时库，再用在线备份 API 整体覆盖当前数据库
+fn restore_database(app: &tauri::AppHandle, dir: &Path, tables: &[String]) -> Result<(), String> {
+    let temp = crate::app_data_base(app).join("checkpoint_restore.db");
+    let _ = fs::remove_file(&temp);
+    let result = (|| {
+        let staging =
+            Connection::open(&temp).map_err(|e| format!("open staging db failed: {}", e))?;
+        // 导出文件可以被随意改写：禁止 ATTACH 到其他文件，也不信任其中的函数调用
+        staging.set_limit(Limit::SQLITE_LIMIT_ATTACHED, 0);
+        staging
+            .execute_batch("PRAGMA trusted_schema = OFF; BEGIN")
+            .map_err(|e| format!("restore database failed: {}", e))?;
+        let db_dir = dir.join(DB_DIR);
+        for table in tables {
+            let dump = resolve_inside(&db_dir, &db_dir.join(format!("{}.sql", table)))?;
+            let sql = fs::read_to_string(dump)
+                .map_err(|e| format!("read dump for {} failed: {}", table, e))?;
+            staging
+                .execute_batch(&sql)
+                .map_err(|e| format!("restore table {} failed: {}", table, e))?;
+        }
+        staging
+            .execute_batch("COMMIT")
+            .map_err(|e| format!("restore database failed: {}", e))?;
+        let mut live = crate::db::open_for_maintenance(app)?;
+        rusqlite::backup::Backup::new(&staging, &mut live)
+            .and_then(|backup| {
+                backup.run_to_completion(256, std::time::Duration::from_millis(20), None)
+            })
+            .map_err(|e| format!("restore database failed: {}", e))
+    })();
+    let _ = fs::remove_file(&temp);
+    result
+}
+
+fn restore(app: &tauri::AppHandle, dir: &Path) -> Result<CheckpointRestoreResult, String> {
+    let manifest = read_manifest(dir).ok_or_else(|| "not a checkpoint directory".to_string())?;
+    if manifest.version > MANIFEST_VERSION {
+        return Err(format!(
+            "checkpoint version {} is newer than supported",
+            manifest.version
+        ));
+    }
+    check_tables(app, &manifest.tables)?;
+    // 检查点里缺文件（如 LFS 对象还没拉取，只有指针文件）时先报错，不动当前数据
+    let source_root = dir.join(STORAGE_DIR);
+    let mut sources = Vec::with_capacity(manifest.files.len());
+    for (name, file) in &manifest.files {
+        let rel = checked_relative(name)?;
+        let source = resolve_inside(&source_root, &source_root.join(&rel));
+        let size = source
+            .as_ref()
+            .ok()
+            .and_then(|p| fs::metadata(p).ok())
+            .map(|m| m.len());
+        if size != Some(file.size) {
+            return Err(format!(
+                "checkpoint file {} is missing or incomplete (run git lfs pull)",
+                name
+            ));
+        }
+        sources.push((rel, source?));
+    }
+
+    crate::db::create_snapshot(app, "pre-checkpoint")?;
+    emit_progress(app, "database", 0, 1);
+    restore_database(app, dir, &manifest.tables)?;
+    emit_progress(app, "database", 1, 1);
+
+    // 只写回内容不同的文件；本地多出来的文件保留，避免误删检查点之后的新图
+    let storage = crate::paths::storage_dir(app);
+    fs::create_dir_all(&storage).map_err(|e| format!("create dir failed: {}", e))?;
+    let (mut restored, mut unchanged) = (0, 0);
+    let total = manifest.files.len();
+    for (done, ((name, file), (rel, source))) in manifest.files.iter().zip(&sources).enumerate() {
+        let dest = storage_dest(&storage, rel)?;
+        let same = fs::metadata(&dest).is_ok_and(|m| m.len() == file.size)
+            && sha256_file(&dest).is_ok_and(|hash| hash == file.sha256);
+        if same {
+            unchanged += 1;
+        } else {
+            fs::copy(source, &dest).map_err(|e| format!("restore {} failed: {}", name, e))?;
+            restored += 1;
+        }
+        emit_progress(app, "storage", done + 1, total);
+    }
+    Ok(CheckpointRestoreResult {
+        tables: manifest.tables.len(),
+        restored_files: restored,
+        unchanged_files: unchanged,
+    })
+}
+
+// 导出到用户选定的目录（通常是一个 git 仓库），重复导出到同一目录只更新变化的部分
+#[tauri::command]
+pub async fn create_checkpoint(
+    app: tauri::AppHandle,
+    dir: String,
+    label: Option<String>,
+) -> Result<CheckpointResult, String> {
+    let dir = PathBuf::from(dir.trim());
+    if dir.as_os_str().is_empty() {
+        return Err("dir is empty".to_string());
+    }
+    let label = label.unwrap_or_default().trim().to_string();
+    let app_for_task = app.clone();
+    let result = tauri::async_runtime::spawn_blocking(move || export(&app_for_task, &dir, &label))
+        .await
+        .map_err(|e| format!("checkpoint task failed: {}", e))??;
+    app.state::<LogState>().log_app(
+        "INFO",
+        &format!(
+            "Checkpoint written dir={} files={} added={} updated={} removed={}",
+            result.dir, result.files, result.added, result.updated, result.removed
+        ),
+    );
+    Ok(result)
+}
+
+// 从检查点目录恢复：停掉 sidecar，覆盖数据库并写回图片，完成后重新拉起
+#[tauri::command]
+pub async fn restore_checkpoint(
+    app: tauri::AppHandle,
+    dir: String,
+) -> Result<CheckpointRestoreResult, String> {
+    let dir = PathBuf::from(dir.trim());
+    crate::db::ensure_writable(&app)?;
+    let app_for_task = app.clone();
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        let app = app_for_task;
+        crate::with_sidecar_stopped(&app, crate::SidecarRestart::Maintenance, || {
+            restore(&app, &dir)
+        })
+    })
+    .await
+    .map_err(|e| format!("restore task failed: {}", e))??;
+
+    app.state::<LogState>().log_app(
+        "INFO",
+        &format!(
+            "Checkpoint restored tables={} files={}",
+            result.tables, result.restored_files
+        ),
+    );
+    Ok(result)
+}
@@ -0,0 +1,200 @@
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::time::Duration;
+
+use tauri::Manager;
+
+use crate::LogState;
+
+// 不带 offset 时先回放末尾这么多字节，相当于 tail
+const TAIL_BYTES: u64 = 16 * 1024;
+// 单次最多返回的字节数，落后太多时分几次追上
+const MAX_CHUNK_BYTES: u64 = 256 * 1024;
+const FOLLOW_INTERVAL: Duration = Duration::from_secs(1);
+
+const USAGE: &str = "usage:
+  nanobanana status            print backend and queue status of the running instance
+  nanobanana logs [-f] [--server]
+                               print recent logs (app.log, or server.log with --server);
+                               -f keeps streaming new lines
+";
+
+// 以下两个由运行中的实例在 instance::handle 里调用
+pub fn status_json(app: &tauri::AppHandle) -> serde_json::Value {
+    serde_json::json!({
+        "version": app.package_info().version.to_string(),
+        "backend": crate::backend_status(app),
+        "queue": crate::queue::get_queue_status(app.clone()),
+        "generating": *app.state::<crate::GenerationState>().0.lock().unwrap(),
+    })
+}
+
+// 按字节偏移增量读取日志；只返回完整的行，文件被轮转（变短）时从头读
+pub fn log_chunk(
+    app: &tauri::AppHandle,
+    source: &str,
+    offset: Option<u64>,
+) -> Result<serde_json::Value, String> {
+    let name = match source {
+        "app" => "app.log",
+        "server" => "server.log",
+        _ => return Err(format!("invalid log source: {}", source)),
+    };
+    let path = app.state::<LogState>().dir.join(name);
+    let mut file = File::open(&path).map_err(|e| format!("open log failed: {}", e))?;
+    let len = file
+        .metadata()
+        .map_err(|e| format!("read log metadata failed: {}", e))?
+        .len();
+    let start = match offset {
+        Some(offset) if offset <= len => offset,
+        Some(_) => 0,
+        None => len.saturating_sub(TAIL_BYTES),
+    };
+    file.seek(SeekFrom::Start(start))
+        .map_err(|e| format!("seek log failed: {}", e))?;
+    let mut bytes = Vec::new();
+    file.take(MAX_CHUNK_BYTES)
+        .read_to_end(&mut bytes)
+        .map_err(|e| format!("read log failed: {}", e))?;
+    let complete = bytes.iter().rposition(|b| *b == b'\n').map_or(0, |i| i + 1);
+    bytes.truncate(complete);
+    let next = start + complete as u64;
+    // tail 的起点多半落在某一行中间，丢掉这半行
+    if offset.is_none() && start > 0 {
+        let skip = bytes
+            .iter()
+            .position(|b| *b == b'\n')
+            .map_or(bytes.len(), |i| i + 1);
+        bytes.drain(..skip);
+    }
+    Ok(serde_json::json!({
+        "offset": next,
+        "text": String::from_utf8_lossy(&bytes),
+    }))
+}
+
+// Windows 发布版是 GUI 子系统，没有控制台；从终端启动时挂到父进程的控制台上才能输出
+#[cfg(windows)]
+fn attach_console() {
+    use windows_sys::Win32::System::Console::{AttachConsole, ATTACH_PARENT_PROCESS};
+    unsafe {
+        AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
+
+#[cfg(not(windows))]
+fn attach_console() {}
+
+fn text<'a>(value: &'a serde_json::Value, key: &str) -> &'a str {
+    value[key].as_str().unwrap_or("-")
+}
+
+fn number(value: &serde_json::Value, key: &str) -> u64 {
+    value[key].as_u64().unwrap_or(0)
+}
+
+fn print_status() -> Result<(), String> {
+    let status = crate::instance::call("GET", "status")?;
+    let backend = &status["backend"];
+    let queue = &status["queue"];
+    println!("version:    {}", text(&status, "version"));
+    println!(
+        "backend:    {} (port {}, pid {}, uptime {}s)",
+        text(backend, "state"),
+        number(backend, "port"),
+        backend["pid"]
+            .as_u64()
+            .map_or_else(|| "-".to_string(), |p| p.to_string()),
+        number(backend, "uptime_secs"),
+    );
+    if let Some(endpoint) = backend["endpoint"].as_str() {
+        println!("endpoint:   {}", endpoint);
+    }
+    if let Some(error) = backend["last_error"].as_str() {
+        println!("last error: {}", error);
+    }
+    println!(
+        "queue:      {}active {}/{}, waiting {}",
+        if queue["paused"].as_bool() == Some(true) {
+            "paused, "
+        } else {
+            ""
+        },
+        number(queue, "active"),
+        number(queue, "max_concurrent"),
+        number(queue, "waiting"),
+    );
+    println!(
+        "generating: {}",
+        if status["generating"].as_bool() == Some(true) {
+            "yes"
+        } else {
+            "no"
+        }
+    );
+    Ok(())
+}
+
+fn print_logs(source: &str, follow: bool) -> Result<(), String> {
+    let mut offset: Option<u64> = None;
+    loop {
+        let target = match offset {
+            Some(offset) => format!("logs?source={}&offset={}", source, offset),
+            None => format!("logs?source={}", source),
+        };
+        let chunk = crate::instance::call("GET", &target)?;
+        let text = chunk["text"].as_str().unwrap_or_default();
+        if !text.is_empty() {
+            let mut stdout = std::io::stdout().lock();
+            let _ = stdout.write_all(text.as_bytes());
+            let _ = stdout.flush();
+        }
+        let next = number(&chunk, "offset");
+        if !follow {
+            return Ok(());
+        }
+        // 还有没读完的内容时立刻接着读
+        if offset != Some(next) {
+            offset = Some(next);
+            continue;
+        }
+        std::thread::sleep(FOLLOW_INTERVAL);
+    }
+}
+
+// 在 Builder 之前调用：识别到子命令时执行并返回退出码，调用方直接退出，不启动界面
+pub fn run() -> Option<i32> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let command = args.first()?.as_str();
+    if !matches!(command, "status" | "logs" | "batch") {
+        return None;
+    }
+    attach_console();
+    let result = match command {
+        "status" => print_status(),
+        "batch" => return Some(crate::batch::run(&args[1..])),
+        _ => {
+            let mut follow = false;
+            let mut source = "app";
+            for arg in &args[1..] {
+                match arg.as_str() {
+                    "-f" | "--follow" => follow = true,
+                    "--server" => source = "server",
+                    _ => {
+                        eprintln!("{}{}", USAGE, crate::batch::USAGE);
+                        return Some(2);
+                    }
+                }
+            }
+            print_logs(source, follow)
+        }
+    };
+    match result {
+        Ok(()) => Some(0),
+        Err(err) => {
+            eprintln!("nanobanana: {}", err);
+            Some(1)
+        }
+    }
+}
@@ -0,0 +1,823 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::BTreeMap;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+
+use base64::Engine;
+use image::imageops::FilterType;
+use image::DynamicImage;
+use tauri::{Emitter, Manager};
+
+use crate::LogState;
+
+// 内嵌 data URI 会让富文本编辑器里的文档变大，长边超过这个尺寸时先缩小
+const MAX_EDGE: u32 = 1600;
+const JPEG_QUALITY: u8 = 90;
+const CANCELLED: &str = "cancelled";
+
+static COPY_SEQ: AtomicU64 = AtomicU64::new(0);
+// 进行中的复制任务 -> 取消标记
+static COPY_TASKS: Mutex<BTreeMap<String, Arc<AtomicBool>>> = Mutex::new(BTreeMap::new());
+
+#[derive(Clone, serde::Serialize)]
+struct CopyEvent {
+    task_id: String,
+    path: String,
+    error: Option<String>,
+    cancelled: bool,
+}
+
+fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            '\n' => out.push_str("<br>"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+// 有透明通道时保留 PNG，否则转 JPEG 控制体积
+fn data_uri(img: &DynamicImage) -> Result<String, String> {
+    let mut bytes = Vec::new();
+    let mime = if img.color().has_alpha() {
+        crate::export::write_png(img, &mut bytes, None)?;
+        "image/png"
+    } else {
+        crate::export::write_jpeg(img, &mut bytes, JPEG_QUALITY, None)?;
+        "image/jpeg"
+    };
+    Ok(format!(
+        "data:{};base64,{}",
+        mime,
+        base64::engine::general_purpose::STANDARD.encode(bytes)
+    ))
+}
+
+fn build_html(
+    uri: &str,
+    width: u32,
+    height: u32,
+    alt: Option<&str>,
+    caption: &str,
+    prompt: &str,
+) -> String {
+    // 优先用已生成的无障碍替代文本
+    let alt = alt.unwrap_or(if caption.is_empty() { prompt } else { caption });
+    let mut html = format!(
+        "<figure style=\"margin:0\"><img src=\"{}\" alt=\"{}\" width=\"{}\" height=\"{}\" style=\"max-width:100%;height:auto\">",
+        uri,
+        escape_html(alt),
+        width,
+        height
+    );
+    if !caption.is_empty() || !prompt.is_empty() {
+        html.push_str(
+            "<figcaption style=\"margin-top:4px;font-size:12px;line-height:1.5;color:#666\">",
+        );
+        if !caption.is_empty() {
+            html.push_str(&format!("<strong>{}</strong>", escape_html(caption)));
+        }
+        if !prompt.is_empty() {
+            if !caption.is_empty() {
+                html.push_str("<br>");
+            }
+            html.push_str(&format!("Prompt: {}", escape_html(prompt)));
+        }
+        html.push_str("</figcaption>");
+    }
+    html.push_str("</figure>");
+    html
+}
+
+// 以 HTML 格式复制结果图（img 内嵌 data URI + 说明/提示词），粘贴到 Confluence、Gmail 等富文本编辑器时图文一起带上
+// arboard 一次只能写入一组格式：这里写 HTML 和纯文本（说明 + 提示词），不同时写位图
+#[tauri::command]
+pub async fn copy_image_as_html(
+    app: tauri::AppHandle,
+    path: String,
+    caption: Option<String>,
+    prompt: Option<String>,
+) -> Result<(), String> {
+    let file_path = crate::resolve_local_path(&app, &path)?;
+    let caption = caption.unwrap_or_default().trim().to_string();
+    let prompt = prompt.unwrap_or_default().trim().to_string();
+    let app_for_task = app.clone();
+    let (html, text) = tauri::async_runtime::spawn_blocking(move || {
+        let alt = crate::alt_text::cached_for_file(&app_for_task, &file_path);
+        let mut img = crate::export::load_image(&file_path)?;
+        if img.width().max(img.height()) > MAX_EDGE {
+            img = img.resize(MAX_EDGE, MAX_EDGE, FilterType::Lanczos3);
+        }
+        let uri = data_uri(&img)?;
+        let html = build_html(
+            &uri,
+            img.width(),
+            img.height(),
+            alt.as_deref(),
+            &caption,
+            &prompt,
+        );
+        let text = [caption, prompt]
+            .into_iter()
+            .filter(|s| !s.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n");
+        Ok::<_, String>((html, text))
+    })
+    .await
+    .map_err(|e| format!("build clipboard html failed: {}", e))??;
+
+    let html_len = html.len();
+    // 与 copy_image 一样切到主线程写剪贴板
+    let (tx, rx) = mpsc::channel::<Result<(), String>>();
+    app.run_on_main_thread(move || {
+        let result = (|| {
+            let mut clipboard =
+                arboard::Clipboard::new().map_err(|e| format!("clipboard init failed: {}", e))?;
+            clipboard
+                .set_html(html, (!text.is_empty()).then_some(text))
+                .map_err(|e| format!("clipboard set html failed: {}", e))?;
+            Ok(())
+        })();
+        let _ = tx.send(result);
+    })
+    .map_err(|e| format!("run_on_main_thread failed: {}", e))?;
+    rx.recv()
+        .map_err(|_| "clipboard task aborted".to_string())??;
+
+    app.state::<LogState>()
+        .log_app("INFO", &format!("Copied image as HTML bytes={}", html_len));
+    Ok(())
+}
+
+// copy_image_as_base64 允许的缩放范围
+const MIN_DATA_URL_EDGE: u32 = 64;
+const MAX_DATA_URL_EDGE: u32 = 8192;
+
+#[derive(serde::Serialize)]
+pub struct DataUrlImage {
+    data_url: String,
+    width: u32,
+    height: u32,
+    // data URL 的字符数，部分网页编辑器对粘贴内容有长度限制，前端可据此提示
+    length: usize,
+}
+
+// 把结果图编码成 data URL（有透明通道为 PNG，否则 JPEG），给只认 data URL 的网页编辑器使用；
+// max_edge 指定长边上限时先缩小，copy 为 true 时同时以纯文本写入剪贴板
+#[tauri::command]
+pub async fn copy_image_as_base64(
+    app: tauri::AppHandle,
+    path: String,
+    max_edge: Option<u32>,
+    copy: Option<bool>,
+) -> Result<DataUrlImage, String> {
+    let file_path = crate::resolve_local_path(&app, &path)?;
+    let max_edge = max_edge.map(|e| e.clamp(MIN_DATA_URL_EDGE, MAX_DATA_URL_EDGE));
+    let image = tauri::async_runtime::spawn_blocking(move || {
+        let mut img = crate::export::load_image(&file_path)?;
+        if let Some(edge) = max_edge {
+            if img.width().max(img.height()) > edge {
+                img = img.resize(edge, edge, FilterType::Lanczos3);
+            }
+        }
+        let data_url = data_uri(&img)?;
+        Ok::<_, String>(DataUrlImage {
+            length: data_url.len(),
+            data_url,
+            width: img.width(),
+            height: img.height(),
+        })
+    })
+    .await
+    .map_err(|e| format!("encode data url failed: {}", e))??;
+
+    if copy.unwrap_or(false) {
+        crate::copy_text_to_clipboard(app.clone(), image.data_url.clone())?;
+    }
+    app.state::<LogState>().log_app(
+        "INFO",
+        &format!(
+            "Encoded image as data url {}x{} length={}",
+            image.width, image.height, image.length
+        ),
+    );
+    Ok(image)
+}
+
+#[derive(Clone, serde::Serialize)]
+pub struct PastedImage {
+    path: String,
+    width: u32,
+    height: u32,
+    // 像素内容的哈希，也是文件名的一部分：同一张截图重复粘贴时得到同一个文件，前端据此去重
+    hash: String,
+    // inline 为 true 时附带 PNG 的 data URI，前端可直接预览，不必再走 asset 协议
+    data_url: Option<String>,
+}
+
+pub fn content_hash(img: &image::RgbaImage) -> String {
+    let mut hasher = DefaultHasher::new();
+    img.dimensions().hash(&mut hasher);
+    img.as_raw().hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
+
+// 按像素内容命名保存为 PNG，同一张图只写一次；返回图片信息和 PNG 字节
+pub fn store_png(dir: &Path, buffer: image::RgbaImage) -> Result<(PastedImage, Vec<u8>), String> {
+    let (width, height) = buffer.dimensions();
+    let hash = content_hash(&buffer);
+    std::fs::create_dir_all(dir).map_err(|e| format!("create clipboard dir failed: {}", e))?;
+    let path = dir.join(format!("clipboard-{}.png", hash));
+    let png = if path.exists() {
+        std::fs::read(&path).map_err(|e| format!("read clipboard image failed: {}", e))?
+    } else {
+        let mut png = Vec::new();
+        crate::export::write_png(&DynamicImage::ImageRgba8(buffer), &mut png, None)?;
+        crate::disk::ensure_free_space(dir, png.len() as u64)?;
+        let tmp = path.with_extension("png.tmp");
+        std::fs::write(&tmp, &png).map_err(|e| format!("save clipboard image failed: {}", e))?;
+        std::fs::rename(&tmp, &path).map_err(|e| format!("save clipboard image failed: {}", e))?;
+        png
+    };
+    let image = PastedImage {
+        path: path.to_string_lossy().to_string(),
+        width,
+        height,
+        hash,
+        data_url: None,
+    };
+    Ok((image, png))
+}
+
+// 从系统剪贴板读取图片（截图等），编码为 PNG 存到 AppData/clipboard 并返回路径，供图生图作为参考图；
+// 剪贴板里没有图片时返回 None。与 read_image_from_clipboard 相比按内容命名，重复粘贴不会堆积文件
+#[tauri::command]
+pub async fn paste_image_from_clipboard(
+    app: tauri::AppHandle,
+    inline: Option<bool>,
+) -> Result<Option<PastedImage>, String> {
+    let app_for_task = app.clone();
+    let pasted = tauri::async_runtime::spawn_blocking(move || {
+        let Some(buffer) = crate::clipboard_image(&app_for_task)? else {
+            return Ok(None);
+        };
+        let dir = crate::app_data_base(&app_for_task).join("clipboard");
+        let (mut image, png) = store_png(&dir, buffer)?;
+        image.data_url = inline.unwrap_or(false).then(|| {
+            format!(
+                "data:image/png;base64,{}",
+                base64::engine::general_purpose::STANDARD.encode(&png)
+            )
+        });
+        Ok::<_, String>(Some(image))
+    })
+    .await
+    .map_err(|e| format!("paste clipboard image failed: {}", e))??;
+
+    if let Some(image) = &pasted {
+        app.state::<LogState>().log_app(
+            "INFO",
+            &format!(
+                "Pasted clipboard image {}x{} hash={}",
+                image.width, image.height, image.hash
+            ),
+        );
+    }
+    Ok(pasted)
+}
+
+// copy_image_to_clipboard 的 mode 参数：只放位图（默认，兼容旧调用）、只放文件引用、两者都放
+#[derive(Clone, Copy, Default, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClipboardImageMode {
+    #[default]
+    Bitmap,
+    File,
+    Both,
+}
+
+// arboard 每次写入都会清空剪贴板，没法和位图写在一起，文件引用直接调用系统接口；
+// 位图也走这里，额外带一份 PNG，透明图粘贴到设计工具时不丢 alpha
+#[cfg(target_os = "macos")]
+mod file_ref {
+    use std::path::PathBuf;
+
+    use image::DynamicImage;
+    use objc2::rc::{Allocated, Retained};
+    use objc2::runtime::{AnyObject, Bool};
+    use objc2::{class, msg_send};
+    use objc2_foundation::{NSArray, NSData, NSString, NSURL};
+
+    pub type Payload = Option<Vec<u8>>;
+
+    pub fn encode(img: &DynamicImage) -> Result<Payload, String> {
+        let mut png = Vec::new();
+        crate::export::write_png(img, &mut png, None)?;
+        Ok(Some(png))
+    }
+
+    // 每个文件一个 NSPasteboardItem，带 public.file-url；位图（public.png + public.tiff）放在第一项上：
+    // Finder、Slack 等按文件粘贴，预览、Photoshop 等按图片粘贴。paths 为空时只写位图
+    pub fn write(paths: &[PathBuf], payload: Payload) -> Result<(), String> {
+        unsafe {
+            let mut items = Vec::with_capacity(paths.len());
+            for path in paths {
+                let url = NSURL::fileURLWithPath(&NSString::from_str(&path.to_string_lossy()));
+                let url_string: Option<Retained<NSString>> = msg_send![&url, absoluteString];
+                let url_string = url_string.ok_or_else(|| "build file url failed".to_string())?;
+                let item: Retained<AnyObject> = msg_send![class!(NSPasteboardItem), new];
+                let ok: Bool = msg_send![&item, setString: &*url_string, forType: &*NSString::from_str("public.file-url")];
+                if !ok.as_bool() {
+                    return Err("clipboard set file url failed".to_string());
+                }
+                items.push(item);
+            }
+            if let Some(png) = payload {
+                if items.is_empty() {
+                    items.push(msg_send![class!(NSPasteboardItem), new]);
+                }
+                let item = &items[0];
+                let data = NSData::with_bytes(&png);
+                let _: Bool =
+                    msg_send![item, setData: &*data, forType: &*NSString::from_str("public.png")];
+                // 只认 TIFF 的老程序（以及 arboard 原来写的格式），由 NSImage 从 PNG 转换，保留透明度
+                let allocated: Allocated<AnyObject> = msg_send![class!(NSImage), alloc];
+                let image: Option<Retained<AnyObject>> = msg_send![allocated, initWithData: &*data];
+                let tiff: Option<Retained<NSData>> =
+                    image.and_then(|image| msg_send![&image, TIFFRepresentation]);
+                if let Some(tiff) = tiff {
+                    let _: Bool = msg_send![item, setData: &*tiff, forType: &*NSString::from_str("public.tiff")];
+                }
+            }
+            let pasteboard: Retained<AnyObject> =
+                msg_send![class!(NSPasteboard), generalPasteboard];
+            let _: isize = msg_send![&pasteboard, clearContents];
+            let items = NSArray::from_retained_slice(&items);
+            let ok: Bool = msg_send![&pasteboard, writeObjects: &*items];
+            if !ok.as_bool() {
+                return Err("clipboard write file reference failed".to_string());
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(windows)]
+mod file_ref {
+    use std::os::windows::ffi::OsStrExt;
+    use std::path::PathBuf;
+
+    use image::DynamicImage;
+    use windows_sys::Win32::System::DataExchange::{
+        CloseClipboard, EmptyClipboard, OpenClipboard, RegisterClipboardFormatW, SetClipboardData,
+    };
+    use windows_sys::Win32::System::Memory::{
+        GlobalAlloc, GlobalFree, GlobalLock, GlobalUnlock, GMEM_MOVEABLE,
+    };
+
+    // 标准剪贴板格式编号（winuser.h）
+    const CF_HDROP: u32 = 15;
+    const CF_DIBV5: u32 = 17;
+    // sizeof(BITMAPV5HEADER)
+    const V5_HEADER_SIZE: u32 = 124;
+    // sizeof(DROPFILES)：pFiles、pt.x、pt.y、fNC、fWide 各 4 字节
+    const DROPFILES_SIZE: u32 = 20;
+
+    // CF_DIBV5（带 alpha 通道掩码；系统会据此自动合成 CF_DIB 给只认它的程序）
+    // 和注册格式 PNG（Chrome、Office、Figma 等读它以保留透明度）
+    pub type Payload = Option<(Vec<u8>, Vec<u8>)>;
+
+    // 32 位 BI_BITFIELDS 的 BITMAPV5HEADER 位图，行从下往上、像素按 BGRA 排列
+    fn dib(img: &DynamicImage) -> Vec<u8> {
+        const BI_BITFIELDS: u32 = 3;
+        const LCS_SRGB: u32 = 0x7352_4742;
+        const LCS_GM_IMAGES: u32 = 4;
+
+        let rgba = img.to_rgba8();
+        let (width, height) = rgba.dimensions();
+        let mut bytes = Vec::with_capacity(V5_HEADER_SIZE as usize + rgba.len());
+        bytes.extend_from_slice(&V5_HEADER_SIZE.to_le_bytes());
+        bytes.extend_from_slice(&(width as i32).to_le_bytes());
+        bytes.extend_from_slice(&(height as i32).to_le_bytes());
+        bytes.extend_from_slice(&1u16.to_le_bytes());
+        bytes.extend_from_slice(&32u16.to_le_bytes());
+        bytes.extend_from_slice(&BI_BITFIELDS.to_le_bytes());
+        bytes.extend_from_slice(&(rgba.len() as u32).to_le_bytes());
+        // 分辨率、调色板字段
+        bytes.extend_from_slice(&[0u8; 16]);
+        // R、G、B、A 掩码
+        for mask in [0x00FF_0000u32, 0x0000_FF00, 0x0000_00FF, 0xFF00_0000] {
+            bytes.extend_from_slice(&mask.to_le_bytes());
+        }
+        bytes.extend_from_slice(&LCS_SRGB.to_le_bytes());
+        // 端点（36 字节）和 gamma（12 字节），sRGB 时忽略
+        bytes.extend_from_slice(&[0u8; 48]);
+        bytes.extend_from_slice(&LCS_GM_IMAGES.to_le_bytes());
+        // 配置文件偏移、大小和保留字段
+        bytes.extend_from_slice(&[0u8; 12]);
+        for row in rgba.rows().rev() {
+            for px in row {
+                bytes.extend_from_slice(&[px[2], px[1], px[0], px[3]]);
+            }
+        }
+        bytes
+    }
+
+    pub fn encode(img: &DynamicImage) -> Result<Payload, String> {
+        let mut png = Vec::new();
+        crate::export::write_png(img, &mut png, None)?;
+        Ok(Some((dib(img), png)))
+    }
+
+    // 每个路径以空字符结尾，整个列表再多一个空字符
+    fn drop_files(paths: &[PathBuf]) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&DROPFILES_SIZE.to_le_bytes());
+        bytes.extend_from_slice(&[0u8; 12]);
+        bytes.extend_from_slice(&1i32.to_le_bytes());
+        for path in paths {
+            for unit in path.as_os_str().encode_wide().chain([0]) {
+                bytes.extend_from_slice(&unit.to_le_bytes());
+            }
+        }
+        bytes.extend_from_slice(&0u16.to_le_bytes());
+        bytes
+    }
+
+    unsafe fn set_data(format: u32, bytes: &[u8]) -> Result<(), String> {
+        let handle = GlobalAlloc(GMEM_MOVEABLE, bytes.len());
+        if handle.is_null() {
+            return Err("clipboard alloc failed".to_string());
+        }
+        let ptr = GlobalLock(handle) as *mut u8;
+        if ptr.is_null() {
+            GlobalFree(handle);
+            return Err("clipboard lock failed".to_string());
+        }
+        std::ptr::copy_nonoverlapping(bytes.as_ptr(), ptr, bytes.len());
+        GlobalUnlock(handle);
+        // 成功后内存归系统所有，失败时才需要自己释放
+        if SetClipboardData(format, handle).is_null() {
+            GlobalFree(handle);
+            return Err(format!(
+                "clipboard set data failed: {}",
+                std::io::Error::last_os_error()
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn write(paths: &[PathBuf], payload: Payload) -> Result<(), String> {
+        unsafe {
+            if OpenClipboard(std::ptr::null_mut()) == 0 {
+                return Err(format!(
+                    "open clipboard failed: {}",
+                    std::io::Error::last_os_error()
+                ));
+            }
+            let result = (|| {
+                EmptyClipboard();
+                if !paths.is_empty() {
+                    set_data(CF_HDROP, &drop_files(paths))?;
+                }
+                if let Some((dib, png)) = payload {
+                    set_data(CF_DIBV5, &dib)?;
+                    let name: Vec<u16> = "PNG".encode_utf16().chain([0]).collect();
+                    let png_format = RegisterClipboardFormatW(name.as_ptr());
+                    if png_format != 0 {
+                        set_data(png_format, &png)?;
+                    }
+                }
+                Ok(())
+            })();
+            CloseClipboard();
+            result
+        }
+    }
+}
+
+pub fn supports_file_reference() -> bool {
+    cfg!(any(target_os = "macos", windows))
+}
+
+// 以文件引用的形式复制原图，粘贴到 Finder / 资源管理器时得到原文件（保留文件名），
+// with_bitmap 为 true 时同一次写入里再带上位图，粘贴到只认图片的程序也能用
+#[cfg(any(target_os = "macos", windows))]
+pub fn copy_file_reference(
+    app: &tauri::AppHandle,
+    path: &Path,
+    with_bitmap: bool,
+) -> Result<(), String> {
+    let payload = if with_bitmap {
+        file_ref::encode(&crate::export::load_image(path)?)?
+    } else {
+        None
+    };
+    write_references(app, vec![path.to_path_buf()], payload)
+}
+
+#[cfg(any(target_os = "macos", windows))]
+fn write_references(
+    app: &tauri::AppHandle,
+    paths: Vec<PathBuf>,
+    payload: file_ref::Payload,
+) -> Result<(), String> {
+    let (tx, rx) = mpsc::channel::<Result<(), String>>();
+    app.run_on_main_thread(move || {
+        let _ = tx.send(file_ref::write(&paths, payload));
+    })
+    .map_err(|e| format!("run_on_main_thread failed: {}", e))?;
+    rx.recv()
+        .map_err(|_| "clipboard task aborted".to_string())??;
+    crate::clipboard_watch::note_own_write();
+    Ok(())
+}
+
+#[cfg(not(any(target_os = "macos", windows)))]
+pub fn copy_file_reference(
+    _app: &tauri::AppHandle,
+    _path: &Path,
+    _with_bitmap: bool,
+) -> Result<(), String> {
+    Err("copy as file is not supported on this platform".to_string())
+}
+
+// 不支持多文件剪贴板的平台（Linux）上把多张图按网格拼成一张位图
+#[cfg(not(any(target_os = "macos", windows)))]
+fn composite(paths: &[PathBuf]) -> Result<image::RgbaImage, String> {
+    const CELL: u32 = 1024;
+    const GAP: u32 = 16;
+
+    let cols = (paths.len() as f64).sqrt().ceil() as u32;
+    let rows = (paths.len() as u32).div_ceil(cols);
+    let mut canvas = image::RgbaImage::from_pixel(
+        cols * CELL + (cols - 1) * GAP,
+        rows * CELL + (rows - 1) * GAP,
+        image::Rgba([255, 255, 255, 255]),
+    );
+    for (index, path) in paths.iter().enumerate() {
+        let img = crate::export::load_image(path)?;
+        let img = if img.width() > CELL || img.height() > CELL {
+            img.resize(CELL, CELL, FilterType::Lanczos3)
+        } else {
+            img
+        };
+        let (col, row) = (index as u32 % cols, index as u32 / cols);
+        let x = col * (CELL + GAP) + (CELL - img.width()) / 2;
+        let y = row * (CELL + GAP) + (CELL - img.height()) / 2;
+        image::imageops::overlay(&mut canvas, &img.to_rgba8(), x as i64, y as i64);
+    }
+    Ok(canvas)
+}
+
+// macOS / Windows 直接写系统剪贴板：位图之外再带一份 PNG，透明图粘贴到 Figma、Photoshop 等不会被压平
+#[cfg(any(target_os = "macos", windows))]
+fn set_bitmap(
+    app: &tauri::AppHandle,
+    img: DynamicImage,
+    cancel: Option<&AtomicBool>,
+) -> Result<(), String> {
+    let payload = file_ref::encode(&img)?;
+    drop(img);
+    check_cancelled(cancel)?;
+    write_references(app, Vec::new(), payload)
+}
+
+// Wayland 下剪贴板内容由写入方进程自己提供，没有剪贴板管理器时 arboard 的 Clipboard 一释放内容就丢了：
+// 优先交给 wl-copy（它会在后台常驻直到剪贴板被别人替换，应用退出后也能粘贴），
+// 没装 wl-copy 或在 X11 下时用后台线程持有内容，直到被其他程序替换
+#[cfg(not(any(target_os = "macos", windows)))]
+fn set_bitmap(
+    app: &tauri::AppHandle,
+    img: DynamicImage,
+    cancel: Option<&AtomicBool>,
+) -> Result<(), String> {
+    if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+        let mut png = Vec::new();
+        crate::export::write_png(&img, &mut png, None)?;
+        check_cancelled(cancel)?;
+        match holder::wl_copy(&png) {
+            Ok(true) => {
+                crate::clipboard_watch::note_own_write();
+                return Ok(());
+            }
+            Ok(false) => {}
+            Err(err) => app
+                .state::<LogState>()
+                .log_app("WARN", &format!("{}, falling back to arboard", err)),
+        }
+    }
+    let img = img.into_rgba8();
+    check_cancelled(cancel)?;
+    holder::hold_image(img)?;
+    crate::clipboard_watch::note_own_write();
+    Ok(())
+}
+
+#[cfg(not(any(target_os = "macos", windows)))]
+mod holder {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    use arboard::SetExtLinux;
+
+    // wait() 写入成功后会一直阻塞到剪贴板被替换，这段时间内没有返回就认为已经在持有
+    const SET_TIMEOUT: Duration = Duration::from_millis(500);
+
+    // 返回 false 表示系统里没有 wl-copy；wl-copy 读完 stdin 后 fork 到后台，前台进程随即退出
+    pub fn wl_copy(png: &[u8]) -> Result<bool, String> {
+        let mut child = match Command::new("wl-copy")
+            .args(["--type", "image/png"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+        {
+            Ok(child) => child,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(false),
+            Err(e) => return Err(format!("wl-copy spawn failed: {}", e)),
+        };
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin
+                .write_all(png)
+                .map_err(|e| format!("wl-copy write failed: {}", e))?;
+        }
+        let status = child
+            .wait()
+            .map_err(|e| format!("wl-copy wait failed: {}", e))?;
+        if !status.success() {
+            return Err(format!("wl-copy failed: {}", status));
+        }
+        Ok(true)
+    }
+
+    // 每次写入起一个持有线程；下一次复制（本应用或其他程序）接管剪贴板后旧线程自然退出
+    pub fn hold_image(img: image::RgbaImage) -> Result<(), String> {
+        let (width, height) = img.dimensions();
+        let (tx, rx) = mpsc::channel::<Result<(), String>>();
+        std::thread::Builder::new()
+            .name("clipboard-holder".to_string())
+            .spawn(move || {
+                let result = (|| {
+                    let mut clipboard = arboard::Clipboard::new()
+                        .map_err(|e| format!("clipboard init failed: {}", e))?;
+                    clipboard
+                        .set()
+                        .wait()
+                        .image(arboard::ImageData {
+                            width: width as usize,
+                            height: height as usize,
+                            bytes: std::borrow::Cow::Owned(img.into_raw()),
+                        })
+                        .map_err(|e| format!("clipboard set image failed: {}", e))
+                })();
+                let _ = tx.send(result);
+            })
+            .map_err(|e| format!("spawn clipboard holder failed: {}", e))?;
+        match rx.recv_timeout(SET_TIMEOUT) {
+            Ok(result) => result,
+            // 仍在阻塞说明内容已经写入并由线程持有
+            Err(mpsc::RecvTimeoutError::Timeout) => Ok(()),
+            Err(mpsc::RecvTimeoutError::Disconnected) => Err("clipboard task aborted".to_string()),
+        }
+    }
+}
+
+#[cfg(any(target_os = "macos", windows))]
+fn write_multiple(app: &tauri::AppHandle, paths: Vec<PathBuf>) -> Result<(), String> {
+    write_references(app, paths, None)
+}
+
+#[cfg(not(any(target_os = "macos", windows)))]
+fn write_multiple(app: &tauri::AppHandle, paths: Vec<PathBuf>) -> Result<(), String> {
+    set_bitmap(app, DynamicImage::ImageRgba8(composite(&paths)?), None)
+}
+
+// 一次复制多张图（如一批生成结果）：macOS / Windows 放多个文件引用，粘贴到聊天软件时是多张图片；
+// 其他平台拼成一张网格图
+#[tauri::command]
+pub async fn copy_images_to_clipboard(
+    app: tauri::AppHandle,
+    paths: Vec<String>,
+) -> Result<(), String> {
+    const MAX_IMAGES: usize = 20;
+
+    if paths.is_empty() {
+        return Err("paths is empty".to_string());
+    }
+    if paths.len() > MAX_IMAGES {
+        return Err(format!("too many images: max {}", MAX_IMAGES));
+    }
+    let files = paths
+        .iter()
+        .map(|p| crate::resolve_local_path(&app, p))
+        .collect::<Result<Vec<_>, _>>()?;
+    let count = files.len();
+    let app_for_task = app.clone();
+    tauri::async_runtime::spawn_blocking(move || write_multiple(&app_for_task, files))
+        .await
+        .map_err(|e| format!("copy images failed: {}", e))??;
+
+    app.state::<LogState>()
+        .log_app("INFO", &format!("Copied {} images to clipboard", count));
+    Ok(())
+}
+
+fn check_cancelled(cancel: Option<&AtomicBool>) -> Result<(), String> {
+    if cancel.is_some_and(|c| c.load(Ordering::SeqCst)) {
+        return Err(CANCELLED.to_string());
+    }
+    Ok(())
+}
+
+// 读文件、解码并写入剪贴板，耗时的部分都在调用方线程里（需在后台线程调用）；
+// cancel 在每一步之间检查，解码一旦开始只能等它结束
+pub fn copy_image(
+    app: &tauri::AppHandle,
+    file_path: &Path,
+    mode: ClipboardImageMode,
+    cancel: Option<&AtomicBool>,
+) -> Result<(), String> {
+    // 不支持文件引用的平台（Linux）上 both 退化为只放位图
+    if mode == ClipboardImageMode::File
+        || (mode == ClipboardImageMode::Both && supports_file_reference())
+    {
+        return copy_file_reference(app, file_path, mode == ClipboardImageMode::Both);
+    }
+
+    let bytes = std::fs::read(file_path)
+        .map_err(|e| format!("read file failed: {} ({})", e, file_path.display()))?;
+    check_cancelled(cancel)?;
+    let img = image::load_from_memory(&bytes).map_err(|e| format!("decode image failed: {}", e))?;
+    drop(bytes);
+    check_cancelled(cancel)?;
+    set_bitmap(app, img, cancel)
+}
+
+// 将本地图片写入系统剪贴板（用于 macOS 打包环境下 Web Clipboard API 不可用/不稳定的兜底）；
+// mode 为 file / both 时同时放原文件的引用，见 copy_file_reference。
+// 4K 图解码要好几秒，这里立即返回任务 id，结果通过 clipboard-copy-done / clipboard-copy-failed 通知
+#[tauri::command]
+pub fn copy_image_to_clipboard(
+    app: tauri::AppHandle,
+    path: String,
+    mode: Option<ClipboardImageMode>,
+) -> Result<String, String> {
+    let file_path = crate::resolve_local_path(&app, &path)?;
+    let task_id = format!("copy-{}", COPY_SEQ.fetch_add(1, Ordering::SeqCst) + 1);
+    let cancel = Arc::new(AtomicBool::new(false));
+    COPY_TASKS
+        .lock()
+        .unwrap()
+        .insert(task_id.clone(), cancel.clone());
+    let id = task_id.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let result = copy_image(&app, &file_path, mode.unwrap_or_default(), Some(&cancel));
+        COPY_TASKS.lock().unwrap().remove(&id);
+        let cancelled = cancel.load(Ordering::SeqCst);
+        let mut event = CopyEvent {
+            task_id: id,
+            path,
+            error: None,
+            cancelled,
+        };
+        match result {
+            Ok(()) => {
+                let _ = app.emit("clipboard-copy-done", event);
+            }
+            Err(err) => {
+                if !cancelled {
+                    app.state::<LogState>()
+                        .log_app("WARN", &format!("copy image to clipboard failed: {}", err));
+                }
+                event.error = Some(err);
+                let _ = app.emit("clipboard-copy-failed", event);
+            }
+        }
+    });
+    Ok(task_id)
+}
+
+// 已写入剪贴板或任务不存在时返回 false
+#[tauri::command]
+pub fn cancel_clipboard_copy(task_id: String) -> bool {
+    match COPY_TASKS.lock().unwrap().get(&task_id) {
+        Some(cancel) => {
+            cancel.store(true, Ordering::SeqCst);
+            true
+        }
+        None => false,
+    }
+}
@@ -0,0 +1,190 @@
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use tauri::{Emitter, Manager, State};
+
+use crate::settings::SettingsState;
+use crate::LogState;
+
+const MIN_INTERVAL_MS: u64 = 500;
+const MAX_INTERVAL_MS: u64 = 10_000;
+// 暂存目录只留最近这么多张，用户没采用的图不会一直堆积
+const MAX_STAGED: usize = 20;
+// 图标、表情之类的小图不提示
+const MIN_EDGE: u32 = 32;
+
+// 应用自己刚写过剪贴板（复制结果图），下一次变化不当作外部新图
+static OWN_WRITE: AtomicBool = AtomicBool::new(false);
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct ClipboardWatchSettings {
+    // 默认关闭：轮询剪贴板涉及隐私，由用户在设置里主动开启
+    pub enabled: bool,
+    pub interval_ms: u64,
+}
+
+impl Default for ClipboardWatchSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_ms: 1000,
+        }
+    }
+}
+
+pub fn note_own_write() {
+    OWN_WRITE.store(true, Ordering::SeqCst);
+}
+
+// 剪贴板变化计数：macOS 的 changeCount、Windows 的序列号，读取开销很小，变化了才去取图片；
+// Linux 没有对应接口，返回 None 时每次都取图片比较内容哈希
+#[cfg(target_os = "macos")]
+fn change_count(app: &tauri::AppHandle) -> Option<u64> {
+    use objc2::rc::Retained;
+    use objc2::runtime::AnyObject;
+    use objc2::{class, msg_send};
+
+    let (tx, rx) = std::sync::mpsc::channel();
+    app.run_on_main_thread(move || {
+        let count: isize = unsafe {
+            let pasteboard: Retained<AnyObject> =
+                msg_send![class!(NSPasteboard), generalPasteboard];
+            msg_send![&pasteboard, changeCount]
+        };
+        let _ = tx.send(count as u64);
+    })
+    .ok()?;
+    rx.recv().ok()
+}
+
+#[cfg(windows)]
+fn change_count(_app: &tauri::AppHandle) -> Option<u64> {
+    use windows_sys::Win32::System::DataExchange::GetClipboardSequenceNumber;
+    Some(unsafe { GetClipboardSequenceNumber() } as u64)
+}
+
+#[cfg(not(any(target_os = "macos", windows)))]
+fn change_count(_app: &tauri::AppHandle) -> Option<u64> {
+    None
+}
+
+// 按修改时间保留最新的 MAX_STAGED 个文件
+fn prune(dir: &Path) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    let mut files: Vec<_> = entries
+        .flatten()
+        .filter_map(|e| {
+            let modified = e.metadata().ok()?.modified().ok()?;
+            Some((modified, e.path()))
+        })
+        .collect();
+    if files.len() <= MAX_STAGED {
+        return;
+    }
+    files.sort_by(|a, b| b.0.cmp(&a.0));
+    for (_, path) in files.into_iter().skip(MAX_STAGED) {
+        let _ = fs::remove_file(path);
+    }
+}
+
+// 取到新图片时保存到 AppData/clipboard/staging 并发出 clipboard-image-available；
+// 返回本次图片的哈希，内容没变时不重复提示
+fn check(app: &tauri::AppHandle, last_hash: &Option<String>) -> Result<Option<String>, String> {
+    let Some(buffer) = crate::clipboard_image(app)? else {
+        return Ok(None);
+    };
+    let dir = crate::app_data_base(app).join("clipboard").join("staging");
+    let hash = crate::clipboard::content_hash(&buffer);
+    if last_hash.as_deref() == Some(hash.as_str()) {
+        return Ok(Some(hash));
+    }
+    let (width, height) = buffer.dimensions();
+    if width.min(height) < MIN_EDGE {
+        return Ok(Some(hash));
+    }
+    let (image, _) = crate::clipboard::store_png(&dir, buffer)?;
+    prune(&dir);
+    app.state::<LogState>().log_app(
+        "INFO",
+        &format!(
+            "Clipboard image available {}x{} hash={}",
+            width, height, image.hash
+        ),
+    );
+    let _ = app.emit("clipboard-image-available", image);
+    Ok(Some(hash))
+}
+
+// 开启后轮询系统剪贴板，检测到外部复制的新图片时通知前端，可一键作为参考图
+pub fn start(app: &tauri::AppHandle) {
+    let app = app.clone();
+    std::thread::spawn(move || {
+        let mut last_count: Option<u64> = None;
+        let mut last_hash: Option<String> = None;
+        let mut watching = false;
+        loop {
+            let settings = app.state::<SettingsState>().get().clipboard_watch;
+            std::thread::sleep(Duration::from_millis(
+                settings.interval_ms.clamp(MIN_INTERVAL_MS, MAX_INTERVAL_MS),
+            ));
+            if !settings.enabled {
+                watching = false;
+                continue;
+            }
+            let count = change_count(&app);
+            // 刚开启时只记录当前内容作为基线，开启前就在剪贴板里的图片不提示
+            if !watching {
+                watching = true;
+                OWN_WRITE.store(false, Ordering::SeqCst);
+                last_count = count;
+                last_hash = crate::clipboard_image(&app)
+                    .ok()
+                    .flatten()
+                    .map(|buffer| crate::clipboard::content_hash(&buffer));
+                continue;
+            }
+            if count.is_some() && count == last_count {
+                continue;
+            }
+            last_count = count;
+            // 自己写入的图片只更新基线
+            let own = OWN_WRITE.swap(false, Ordering::SeqCst);
+            if own && count.is_some() {
+                continue;
+            }
+            let result = if own {
+                crate::clipboard_image(&app)
+                    .map(|b| b.map(|buffer| crate::clipboard::content_hash(&buffer)))
+            } else {
+                check(&app, &last_hash)
+            };
+            match result {
+                Ok(hash) => last_hash = hash,
+                Err(err) => app
+                    .state::<LogState>()
+                    .log_app("WARN", &format!("clipboard watch failed: {}", err)),
+            }
+        }
+    });
+}
+
+#[tauri::command]
+pub fn get_clipboard_watch_settings(state: State<'_, SettingsState>) -> ClipboardWatchSettings {
+    state.get().clipboard_watch
+}
+
+#[tauri::command]
+pub fn set_clipboard_watch_settings(
+    state: State<'_, SettingsState>,
+    mut settings: ClipboardWatchSettings,
+) -> Result<ClipboardWatchSettings, String> {
+    settings.interval_ms = settings.interval_ms.clamp(MIN_INTERVAL_MS, MAX_INTERVAL_MS);
+    Ok(state
+        .update(|s| s.clipboard_watch = settings)?
+        .clipboard_watch)
+}
@@ -0,0 +1,155 @@
+use std::time::SystemTime;
+
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, Duration, FixedOffset, Local, LocalResult, NaiveDate, TimeZone, Utc};
+use tauri::{Manager, State};
+
+use crate::settings::SettingsState;
+
+// 文件名里 Windows 不允许的字符
+const FILENAME_FORBIDDEN: &[char] = &['<', '>', ':', '"', '/', '\\', '|', '?', '*'];
+const MAX_FORMAT_LEN: usize = 64;
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct TimeSettings {
+    // 导出、诊断文件名里的时间戳（strftime 语法）
+    pub filename_format: String,
+    // 导出文档里给人看的时间
+    pub display_format: String,
+    // 清单（manifest）、钩子等机器读取的时间统一 RFC 3339；开启后写 UTC 而不是带偏移的本地时间
+    pub use_utc: bool,
+}
+
+impl Default for TimeSettings {
+    fn default() -> Self {
+        Self {
+            filename_format: "%Y%m%d-%H%M%S".to_string(),
+            display_format: "%Y-%m-%d %H:%M:%S".to_string(),
+            use_utc: false,
+        }
+    }
+}
+
+#[derive(serde::Serialize)]
+pub struct TimePreview {
+    filename: String,
+    display: String,
+    rfc3339: String,
+    // 当前时区相对 UTC 的偏移，如 +08:00；夏令时切换前后会变
+    utc_offset: String,
+}
+
+fn validate_format(format: &str) -> Result<(), String> {
+    if format.trim().is_empty() {
+        return Err("time format is empty".to_string());
+    }
+    if format.len() > MAX_FORMAT_LEN {
+        return Err(format!("time format too long: {}", format));
+    }
+    // 非法占位符在 to_string 时会 panic，保存前先校验
+    if StrftimeItems::new(format).any(|item| matches!(item, Item::Error)) {
+        return Err(format!("invalid time format: {}", format));
+    }
+    Ok(())
+}
+
+pub fn settings(app: &tauri::AppHandle) -> TimeSettings {
+    app.state::<SettingsState>().get().time
+}
+
+// 本地时间带上当时的偏移；夏令时期间偏移随之变化，序列化后仍能还原到同一时刻
+pub fn now(use_utc: bool) -> DateTime<FixedOffset> {
+    if use_utc {
+        Utc::now().fixed_offset()
+    } else {
+        Local::now().fixed_offset()
+    }
+}
+
+pub fn rfc3339(app: &tauri::AppHandle) -> String {
+    now(settings(app).use_utc).to_rfc3339()
+}
+
+pub fn display(app: &tauri::AppHandle) -> String {
+    let settings = settings(app);
+    now(settings.use_utc)
+        .format(&settings.display_format)
+        .to_string()
+}
+
+fn filename_from(settings: &TimeSettings, time: DateTime<Local>) -> String {
+    let mut stamp = if settings.use_utc {
+        time.with_timezone(&Utc).format(&settings.filename_format)
+    } else {
+        time.format(&settings.filename_format)
+    }
+    .to_string();
+    // 夏令时结束时同一段本地时间会出现两次，格式里没有偏移时补上，避免文件名重复、排序错乱
+    let ambiguous = matches!(
+        Local.from_local_datetime(&time.naive_local()),
+        LocalResult::Ambiguous(_, _)
+    );
+    if !settings.use_utc && ambiguous && !settings.filename_format.contains("%z") {
+        stamp.push_str(&time.format("%z").to_string());
+    }
+    stamp.replace(FILENAME_FORBIDDEN, "-")
+}
+
+// 用于文件名的时间戳，已去掉文件系统不允许的字符
+pub fn filename_stamp(app: &tauri::AppHandle) -> String {
+    filename_from(&settings(app), Local::now())
+}
+
+// 按本地自然日分桶（日志轮转、流量统计）始终用本地日期，不受 use_utc 影响
+pub fn today() -> String {
+    Local::now().format("%Y-%m-%d").to_string()
+}
+
+pub fn local_day(time: SystemTime) -> String {
+    DateTime::<Local>::from(time).format("%Y-%m-%d").to_string()
+}
+
+// 本地某天 0 点的毫秒时间戳；部分时区的夏令时在 0 点切换，0 点不存在时取当天第一个有效时刻
+pub fn start_of_day(date: NaiveDate) -> i64 {
+    let midnight = date.and_hms_opt(0, 0, 0).unwrap_or_default();
+    (0..=8)
+        .map(|step| midnight + Duration::minutes(step * 15))
+        .find_map(|t| Local.from_local_datetime(&t).earliest())
+        .map(|t| t.timestamp_millis())
+        .unwrap_or_else(|| midnight.and_utc().timestamp_millis())
+}
+
+#[tauri::command]
+pub fn get_time_settings(state: State<'_, SettingsState>) -> TimeSettings {
+    state.get().time
+}
+
+#[tauri::command]
+pub fn set_time_settings(
+    state: State<'_, SettingsState>,
+    settings: TimeSettings,
+) -> Result<TimeSettings, String> {
+    validate_format(&settings.filename_format)?;
+    validate_format(&settings.display_format)?;
+    Ok(state.update(|s| s.time = settings)?.time)
+}
+
+// 设置页实时预览；settings 为空时预览当前生效的配置
+#[tauri::command]
+pub fn preview_time_format(
+    state: State<'_, SettingsState>,
+    settings: Option<TimeSettings>,
+) -> Result<TimePreview, String> {
+    let settings = settings.unwrap_or_else(|| state.get().time);
+    validate_format(&settings.filename_format)?;
+    validate_format(&settings.display_format)?;
+    let time = Local::now();
+    let current = now(settings.use_utc);
+    Ok(TimePreview {
+        filename: filename_from(&settings, time),
+        display: current.format(&settings.display_format).to_string(),
+        rfc3339: current.to_rfc3339(),
+        utc_offset: time.format("%:z").to_string(),
+    })
+}
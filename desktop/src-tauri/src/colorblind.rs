@@ -0,0 +1,189 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use image::{DynamicImage, RgbaImage};
+use tauri::Manager;
+
+use crate::export::OutputFormat;
+use crate::LogState;
+
+// Machado, Oliveira & Fernandes (2009) 在线性 RGB 空间的完全色盲（severity 1.0）模拟矩阵
+const PROTANOPIA: [[f32; 3]; 3] = [
+    [0.152286, 1.052583, -0.204868],
+    [0.114503, 0.786281, 0.099216],
+    [-0.003882, -0.048116, 1.051998],
+];
+const DEUTERANOPIA: [[f32; 3]; 3] = [
+    [0.367322, 0.860646, -0.227968],
+    [0.280085, 0.672501, 0.047413],
+    [-0.011820, 0.042940, 0.968881],
+];
+const TRITANOPIA: [[f32; 3]; 3] = [
+    [1.255528, -0.076749, -0.178779],
+    [-0.078411, 0.930809, 0.147602],
+    [0.004733, 0.691367, 0.303900],
+];
+const KINDS: [&str; 3] = ["deuteranopia", "protanopia", "tritanopia"];
+// 线性值回 sRGB 的查表精度
+const ENCODE_STEPS: usize = 4096;
+
+#[derive(serde::Serialize)]
+pub struct ColorVisionRender {
+    kind: String,
+    path: String,
+}
+
+fn matrix(kind: &str) -> Option<[[f32; 3]; 3]> {
+    match kind {
+        "protanopia" => Some(PROTANOPIA),
+        "deuteranopia" => Some(DEUTERANOPIA),
+        "tritanopia" => Some(TRITANOPIA),
+        _ => None,
+    }
+}
+
+// severity < 1 时与单位矩阵线性插值，近似轻度色弱
+fn blend(m: [[f32; 3]; 3], severity: f32) -> [[f32; 3]; 3] {
+    let mut out = [[0.0; 3]; 3];
+    for (r, row) in m.iter().enumerate() {
+        for (c, v) in row.iter().enumerate() {
+            let identity = if r == c { 1.0 } else { 0.0 };
+            out[r][c] = identity + (v - identity) * severity;
+        }
+    }
+    out
+}
+
+fn srgb_to_linear(v: f32) -> f32 {
+    if v <= 0.04045 {
+        v / 12.92
+    } else {
+        ((v + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(v: f32) -> f32 {
+    if v <= 0.0031308 {
+        v * 12.92
+    } else {
+        1.055 * v.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+struct Tables {
+    decode: [f32; 256],
+    encode: Vec<u8>,
+}
+
+impl Tables {
+    fn new() -> Self {
+        let mut decode = [0.0; 256];
+        for (i, v) in decode.iter_mut().enumerate() {
+            *v = srgb_to_linear(i as f32 / 255.0);
+        }
+        let encode = (0..=ENCODE_STEPS)
+            .map(|i| (linear_to_srgb(i as f32 / ENCODE_STEPS as f32) * 255.0).round() as u8)
+            .collect();
+        Self { decode, encode }
+    }
+
+    fn encode(&self, v: f32) -> u8 {
+        self.encode[(v.clamp(0.0, 1.0) * ENCODE_STEPS as f32).round() as usize]
+    }
+}
+
+fn simulate(src: &RgbaImage, m: [[f32; 3]; 3], tables: &Tables) -> RgbaImage {
+    let mut out = src.clone();
+    for px in out.pixels_mut() {
+        let [r, g, b, a] = px.0;
+        let (r, g, b) = (
+            tables.decode[r as usize],
+            tables.decode[g as usize],
+            tables.decode[b as usize],
+        );
+        px.0 = [
+            tables.encode(m[0][0] * r + m[0][1] * g + m[0][2] * b),
+            tables.encode(m[1][0] * r + m[1][1] * g + m[1][2] * b),
+            tables.encode(m[2][0] * r + m[2][1] * g + m[2][2] * b),
+            a,
+        ];
+    }
+    out
+}
+
+fn render_all(
+    src_path: &Path,
+    dest_dir: &Path,
+    kinds: &[String],
+    severity: f32,
+) -> Result<Vec<ColorVisionRender>, String> {
+    let img = crate::export::load_image(src_path)?.to_rgba8();
+    let stem = src_path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| "image".to_string());
+    fs::create_dir_all(dest_dir).map_err(|e| format!("create destination dir failed: {}", e))?;
+    crate::disk::ensure_free_space(
+        dest_dir,
+        img.width() as u64 * img.height() as u64 * 4 * kinds.len() as u64,
+    )?;
+    let tables = Tables::new();
+    let mut renders = Vec::new();
+    for kind in kinds {
+        let m = matrix(kind).ok_or_else(|| format!("unknown color vision type: {}", kind))?;
+        let simulated = simulate(&img, blend(m, severity), &tables);
+        // 输出 PNG，避免 JPEG 压缩再引入色偏
+        let dest = dest_dir.join(format!("{}_{}.png", stem, kind));
+        crate::export::write_image(
+            &DynamicImage::ImageRgba8(simulated),
+            &dest,
+            OutputFormat::Png,
+            100,
+            None,
+        )?;
+        renders.push(ColorVisionRender {
+            kind: kind.clone(),
+            path: dest.to_string_lossy().to_string(),
+        });
+    }
+    Ok(renders)
+}
+
+// 生成色盲模拟图（deuteranopia / protanopia / tritanopia），kinds 缺省时三种都生成
+// dest_dir 缺省写到应用数据目录的 color_vision/ 下，只用于预览
+#[tauri::command]
+pub async fn simulate_color_vision(
+    app: tauri::AppHandle,
+    path: String,
+    kinds: Option<Vec<String>>,
+    severity: Option<f32>,
+    dest_dir: Option<String>,
+) -> Result<Vec<ColorVisionRender>, String> {
+    let src_path = crate::resolve_local_path(&app, &path)?;
+    let kinds: Vec<String> = match kinds {
+        Some(kinds) if !kinds.is_empty() => kinds
+            .into_iter()
+            .map(|k| k.trim().to_ascii_lowercase())
+            .collect(),
+        _ => KINDS.iter().map(|k| k.to_string()).collect(),
+    };
+    let severity = severity.unwrap_or(1.0).clamp(0.0, 1.0);
+    let dest_dir = match dest_dir.map(|d| d.trim().to_string()) {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => crate::app_data_base(&app).join("color_vision"),
+    };
+    let renders = tauri::async_runtime::spawn_blocking(move || {
+        render_all(&src_path, &dest_dir, &kinds, severity)
+    })
+    .await
+    .map_err(|e| format!("color vision task failed: {}", e))??;
+    app.state::<LogState>().log_app(
+        "INFO",
+        &format!(
+            "Color vision simulation finished renders={} severity={}",
+            renders.len(),
+            severity
+        ),
+    );
+    Ok(renders)
+}
@@ -0,0 +1,311 @@
+use std::path::PathBuf;
+
+use ab_glyph::Font;
+use image::imageops::FilterType;
+use image::{DynamicImage, GenericImageView, RgbaImage};
+use tauri::Manager;
+
+use crate::annotate::{draw_text, measure_text, parse_color, with_font};
+use crate::export::OutputFormat;
+use crate::fonts::FontState;
+use crate::LogState;
+
+const MAX_ITEMS: usize = 16;
+const DEFAULT_CELL_SIZE: u32 = 768;
+// 说明文字最多显示几行，超出部分用省略号截断
+const MAX_CAPTION_LINES: usize = 3;
+const MAX_COMPARE_PIXELS: u64 = 200_000_000;
+
+#[derive(serde::Deserialize)]
+pub struct CompareItem {
+    path: String,
+    // 标题行，如 “Before” / “Seed 42”
+    label: Option<String>,
+    // 正文说明，通常是提示词
+    caption: Option<String>,
+}
+
+#[derive(Default, serde::Deserialize)]
+#[serde(default)]
+pub struct CompareOptions {
+    // strip（默认，横向一排）/ grid
+    layout: Option<String>,
+    // grid 的列数，默认取接近正方形的列数
+    columns: Option<u32>,
+    // strip 为统一高度，grid 为单元格边长
+    cell_size: Option<u32>,
+    gap: Option<u32>,
+    background: Option<String>,
+    text_color: Option<String>,
+    caption_size: Option<f32>,
+    font_family: Option<String>,
+    quality: Option<u8>,
+}
+
+#[derive(serde::Serialize)]
+pub struct CompareResult {
+    dest_path: String,
+    width: u32,
+    height: u32,
+    items: usize,
+}
+
+// 按字符折行，中日韩文本没有空格也能断开
+fn wrap_text<F: Font>(fonts: &[F], size: f32, text: &str, max_width: f32) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for paragraph in text.lines() {
+        let mut line = String::new();
+        for c in paragraph.chars() {
+            let mut next = line.clone();
+            next.push(c);
+            if !line.is_empty() && measure_text(fonts, size, &next).0 > max_width {
+                lines.push(line.trim_end().to_string());
+                line = c.to_string().trim_start().to_string();
+            } else {
+                line = next;
+            }
+        }
+        lines.push(line);
+    }
+    if lines.len() > MAX_CAPTION_LINES {
+        lines.truncate(MAX_CAPTION_LINES);
+        if let Some(last) = lines.last_mut() {
+            while !last.is_empty() && measure_text(fonts, size, &format!("{}…", last)).0 > max_width
+            {
+                last.pop();
+            }
+            last.push('…');
+        }
+    }
+    lines
+}
+
+struct Cell {
+    img: DynamicImage,
+    label: Vec<String>,
+    caption: Vec<String>,
+}
+
+fn fit_size(src: (u32, u32), bound: (u32, u32)) -> (u32, u32) {
+    let scale = (bound.0 as f64 / src.0 as f64).min(bound.1 as f64 / src.1 as f64);
+    (
+        ((src.0 as f64 * scale).round() as u32).max(1),
+        ((src.1 as f64 * scale).round() as u32).max(1),
+    )
+}
+
+fn compose(
+    items: &[CompareItem],
+    sources: Vec<DynamicImage>,
+    options: &CompareOptions,
+    fonts: &FontState,
+) -> Result<RgbaImage, String> {
+    let cell_size = options
+        .cell_size
+        .unwrap_or(DEFAULT_CELL_SIZE)
+        .clamp(64, 4096);
+    let gap = options.gap.unwrap_or(16).min(256);
+    let background = parse_color(options.background.as_deref(), [255, 255, 255, 255])?;
+    let text_color = parse_color(options.text_color.as_deref(), [34, 34, 34, 255])?;
+    let caption_size = options
+        .caption_size
+        .unwrap_or(cell_size as f32 / 28.0)
+        .max(8.0);
+    let label_size = caption_size * 1.25;
+    let grid = options.layout.as_deref() == Some("grid");
+    let count = sources.len() as u32;
+    let columns = if grid {
+        options
+            .columns
+            .unwrap_or_else(|| (count as f64).sqrt().ceil() as u32)
+            .clamp(1, count)
+    } else {
+        count
+    };
+    let rows = count.div_ceil(columns);
+
+    // strip 统一高度、宽度按比例；grid 每格等大，图片居中放入
+    let boxes: Vec<(u32, u32)> = sources
+        .iter()
+        .map(|img| {
+            if grid {
+                (cell_size, cell_size)
+            } else {
+                fit_size(img.dimensions(), (u32::MAX, cell_size))
+            }
+        })
+        .collect();
+
+    // 所有标签和说明（以及截断用的省略号）一起检测缺字，整张对比图共用一条回退链
+    let mut text: String = items
+        .iter()
+        .flat_map(|item| [item.label.as_deref(), item.caption.as_deref()])
+        .flatten()
+        .collect();
+    text.push('…');
+    let db = fonts.database();
+    with_font(&db, options.font_family.as_deref(), &text, |font| {
+        let cells: Vec<Cell> = sources
+            .into_iter()
+            .zip(items)
+            .zip(&boxes)
+            .map(|((img, item), (w, _))| {
+                let max_width = *w as f32 - gap as f32;
+                let wrap = |text: &Option<String>, size: f32| {
+                    text.as_deref()
+                        .map(str::trim)
+                        .filter(|t| !t.is_empty())
+                        .map(|t| wrap_text(font, size, t, max_width))
+                        .unwrap_or_default()
+                };
+                Cell {
+                    label: wrap(&item.label, label_size),
+                    caption: wrap(&item.caption, caption_size),
+                    img,
+                }
+            })
+            .collect();
+
+        let line = |size: f32| measure_text(font, size, "Ag").1;
+        // 说明区取所有格子的最大高度，保证同一行图片顶端对齐
+        let caption_height = cells
+            .iter()
+            .map(|c| {
+                let text = c.label.len() as f32 * line(label_size)
+                    + c.caption.len() as f32 * line(caption_size);
+                if text > 0.0 {
+                    text + gap as f32
+                } else {
+                    0.0
+                }
+            })
+            .fold(0.0, f32::max)
+            .ceil() as u32;
+
+        let col_width = |col: u32| -> u32 {
+            (col..count)
+                .step_by(columns as usize)
+                .map(|i| boxes[i as usize].0)
+                .max()
+                .unwrap_or(0)
+        };
+        let widths: Vec<u32> = (0..columns).map(col_width).collect();
+        let row_height = cell_size + caption_height;
+        let width = widths.iter().sum::<u32>() + gap * (columns + 1);
+        let height = row_height * rows + gap * (rows + 1);
+        if width as u64 * height as u64 > MAX_COMPARE_PIXELS {
+            return Err(format!("comparison image too large: {}x{}", width, height));
+        }
+
+        let mut canvas = RgbaImage::from_pixel(width, height, image::Rgba(background));
+        for (i, cell) in cells.iter().enumerate() {
+            let (col, row) = (i as u32 % columns, i as u32 / columns);
+            let x = gap + widths[..col as usize].iter().sum::<u32>() + col * gap;
+            let y = gap + row * (row_height + gap);
+            let (bw, bh) = (widths[col as usize], cell_size);
+            let (tw, th) = fit_size(cell.img.dimensions(), (bw, bh));
+            let scaled = cell
+                .img
+                .resize_exact(tw, th, FilterType::Lanczos3)
+                .to_rgba8();
+            image::imageops::overlay(
+                &mut canvas,
+                &scaled,
+                (x + (bw - tw) / 2) as i64,
+                (y + (bh - th) / 2) as i64,
+            );
+
+            let mut text_y = (y + cell_size) as f32 + gap as f32 / 2.0;
+            for line_text in &cell.label {
+                draw_text(
+                    &mut canvas,
+                    font,
+                    label_size,
+                    x as f32,
+                    text_y,
+                    line_text,
+                    text_color,
+                );
+                text_y += line(label_size);
+            }
+            for line_text in &cell.caption {
+                draw_text(
+                    &mut canvas,
+                    font,
+                    caption_size,
+                    x as f32,
+                    text_y,
+                    line_text,
+                    text_color,
+                );
+                text_y += line(caption_size);
+            }
+        }
+        Ok(canvas)
+    })?
+}
+
+// 把多张结果拼成一张带标题/提示词说明的对比图（横排或网格）
+#[tauri::command]
+pub async fn export_comparison_image(
+    app: tauri::AppHandle,
+    items: Vec<CompareItem>,
+    dest_path: String,
+    options: CompareOptions,
+) -> Result<CompareResult, String> {
+    if items.len() < 2 {
+        return Err("at least two images are required".to_string());
+    }
+    if items.len() > MAX_ITEMS {
+        return Err(format!(
+            "too many images: {} (max {})",
+            items.len(),
+            MAX_ITEMS
+        ));
+    }
+    let dest = PathBuf::from(dest_path.trim());
+    if dest.as_os_str().is_empty() {
+        return Err("dest_path is empty".to_string());
+    }
+    let format = OutputFormat::from_path(&dest)?;
+    let paths = items
+        .iter()
+        .map(|item| crate::resolve_local_path(&app, &item.path))
+        .collect::<Result<Vec<_>, _>>()?;
+    let fonts = FontState(app.state::<FontState>().0.clone());
+    let log_state = app.state::<LogState>().inner().clone();
+
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        let sources = paths
+            .iter()
+            .map(|p| crate::export::load_image(p))
+            .collect::<Result<Vec<_>, _>>()?;
+        let canvas = compose(&items, sources, &options, &fonts)?;
+        let (width, height) = canvas.dimensions();
+        crate::disk::ensure_free_space(&dest, width as u64 * height as u64 * 4)?;
+        crate::export::write_image(
+            &DynamicImage::ImageRgba8(canvas),
+            &dest,
+            format,
+            options.quality.unwrap_or(92),
+            None,
+        )?;
+        Ok::<_, String>(CompareResult {
+            dest_path: dest.to_string_lossy().to_string(),
+            width,
+            height,
+            items: items.len(),
+        })
+    })
+    .await
+    .map_err(|e| format!("comparison export failed: {}", e))??;
+
+    log_state.log_app(
+        "INFO",
+        &format!(
+            "Comparison export finished items={} size={}x{} dest={}",
+            result.items, result.width, result.height, result.dest_path
+        ),
+    );
+    Ok(result)
+}
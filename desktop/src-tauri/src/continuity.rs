@@ -0,0 +1,219 @@
+use std::sync::Mutex;
+
+use tauri::{Emitter, Manager};
+
+use crate::LogState;
+
+// 本次导入要附加到参考图上的标签；照片在用户按下快门后才异步送达
+static PENDING_TAGS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+#[derive(Clone, serde::Serialize)]
+struct ContinuityFailed {
+    error: String,
+}
+
+// 连续互通相机送来的图片入参考图库，结果通过事件告知前端
+fn ingest(app: &tauri::AppHandle, png: Vec<u8>) {
+    let tags = PENDING_TAGS.lock().unwrap().clone();
+    let app = app.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        match crate::references::add_bytes(&app, &png, "iphone.png", "continuity_camera", "", &tags)
+        {
+            Ok(reference) => {
+                app.state::<LogState>()
+                    .log_app("INFO", "Continuity camera image added to references");
+                let _ = app.emit("continuity-import", reference);
+            }
+            Err(err) => {
+                app.state::<LogState>()
+                    .log_app("WARN", &format!("Continuity camera import failed: {}", err));
+                let _ = app.emit("continuity-import-failed", ContinuityFailed { error: err });
+            }
+        }
+    });
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use std::cell::RefCell;
+    use std::sync::OnceLock;
+
+    use objc2::rc::{Allocated, Retained};
+    use objc2::runtime::{AnyObject, Bool, NSObjectProtocol, Sel};
+    use objc2::{class, define_class, msg_send, MainThreadMarker, MainThreadOnly};
+    use objc2_app_kit::NSResponder;
+    use objc2_foundation::{NSArray, NSDictionary, NSObject, NSPoint, NSString};
+
+    // NSBitmapImageFileTypePNG
+    const FILE_TYPE_PNG: usize = 4;
+    // AppKit 识别这个标识，自动把菜单项替换为附近设备的“拍照 / 扫描文稿 / 添加速绘”
+    const IMPORT_FROM_DEVICE_IDENTIFIER: &str = "NSMenuItemImportFromDeviceIdentifier";
+    const RETURN_TYPES: &[&str] = &["public.png", "public.jpeg", "public.tiff", "com.adobe.pdf"];
+
+    static APP: OnceLock<tauri::AppHandle> = OnceLock::new();
+
+    thread_local! {
+        // 只在主线程访问，保持 requestor 存活直到应用退出
+        static REQUESTOR: RefCell<Option<Retained<ContinuityRequestor>>> =
+            const { RefCell::new(None) };
+    }
+
+    define_class!(
+        // 插进主窗口的响应链，AppKit 通过它询问“能否接收图片”并送回拍摄结果
+        #[unsafe(super(NSResponder, NSObject))]
+        #[thread_kind = MainThreadOnly]
+        #[name = "NanoBananaContinuityRequestor"]
+        struct ContinuityRequestor;
+
+        impl ContinuityRequestor {
+            #[unsafe(method(validRequestorForSendType:returnType:))]
+            fn valid_requestor(
+                &self,
+                send_type: *mut AnyObject,
+                return_type: *mut AnyObject,
+            ) -> *mut AnyObject {
+                // 只接收、不发送：没有 sendType 且 returnType 是图片时由自己处理
+                if send_type.is_null() && !return_type.is_null() {
+                    return self as *const Self as *mut AnyObject;
+                }
+                unsafe {
+                    msg_send![
+                        super(self),
+                        validRequestorForSendType: send_type,
+                        returnType: return_type
+                    ]
+                }
+            }
+
+            #[unsafe(method(readSelectionFromPasteboard:))]
+            fn read_selection(&self, pasteboard: &AnyObject) -> Bool {
+                let Some(app) = APP.get() else {
+                    return Bool::NO;
+                };
+                match unsafe { pasteboard_png(pasteboard) } {
+                    Some(png) => {
+                        super::ingest(app, png);
+                        Bool::YES
+                    }
+                    None => Bool::NO,
+                }
+            }
+        }
+
+        unsafe impl NSObjectProtocol for ContinuityRequestor {}
+    );
+
+    impl ContinuityRequestor {
+        fn new(mtm: MainThreadMarker) -> Retained<Self> {
+            let this = Self::alloc(mtm).set_ivars(());
+            unsafe { msg_send![super(this), init] }
+        }
+    }
+
+    // 照片、扫描件（PDF）、速绘统一经 NSImage 转成 PNG，参考图库只处理常见位图格式
+    unsafe fn pasteboard_png(pasteboard: &AnyObject) -> Option<Vec<u8>> {
+        let image: Allocated<AnyObject> = msg_send![class!(NSImage), alloc];
+        let image: Option<Retained<AnyObject>> = msg_send![image, initWithPasteboard: pasteboard];
+        let image = image?;
+        let tiff: Option<Retained<AnyObject>> = msg_send![&image, TIFFRepresentation];
+        let rep: Option<Retained<AnyObject>> =
+            msg_send![class!(NSBitmapImageRep), imageRepWithData: &*tiff?];
+        let properties = NSDictionary::<NSString, AnyObject>::new();
+        let png: Option<Retained<AnyObject>> =
+            msg_send![&rep?, representationUsingType: FILE_TYPE_PNG, properties: &*properties];
+        let png = png?;
+        let len: usize = msg_send![&png, length];
+        let bytes: *const u8 = msg_send![&png, bytes];
+        if bytes.is_null() || len == 0 {
+            return None;
+        }
+        Some(std::slice::from_raw_parts(bytes, len).to_vec())
+    }
+
+    // 只安装一次：插在窗口和它原来的下一个响应者之间
+    unsafe fn install(window: *mut AnyObject, mtm: MainThreadMarker) {
+        if REQUESTOR.with(|r| r.borrow().is_some()) {
+            return;
+        }
+        let requestor = ContinuityRequestor::new(mtm);
+        let next: *mut AnyObject = msg_send![window, nextResponder];
+        let _: () = msg_send![&requestor, setNextResponder: next];
+        let _: () = msg_send![window, setNextResponder: &*requestor];
+
+        let types: Vec<Retained<NSString>> =
+            RETURN_TYPES.iter().map(|t| NSString::from_str(t)).collect();
+        let types = NSArray::from_retained_slice(&types);
+        let empty = NSArray::<NSString>::new();
+        let ns_app: *mut AnyObject = msg_send![class!(NSApplication), sharedApplication];
+        let _: () = msg_send![ns_app, registerServicesMenuSendTypes: &*empty, returnTypes: &*types];
+
+        REQUESTOR.with(|r| *r.borrow_mut() = Some(requestor));
+    }
+
+    // 在鼠标位置弹出只含“从 iPhone 导入”的菜单，菜单关闭后返回；照片稍后经 requestor 送达
+    pub fn show_import_menu(app: &tauri::AppHandle) -> Result<(), String> {
+        use std::sync::mpsc;
+
+        let _ = APP.set(app.clone());
+        let window = app
+            .get_webview_window("main")
+            .ok_or_else(|| "main window not found".to_string())?;
+        let ns_window = window
+            .ns_window()
+            .map_err(|e| format!("get native window failed: {}", e))?
+            as usize;
+        let (tx, rx) = mpsc::channel::<Result<(), String>>();
+        app.run_on_main_thread(move || {
+            let Some(mtm) = MainThreadMarker::new() else {
+                let _ = tx.send(Err("not on main thread".to_string()));
+                return;
+            };
+            unsafe {
+                install(ns_window as *mut AnyObject, mtm);
+                let menu: Retained<AnyObject> = msg_send![class!(NSMenu), new];
+                let item: Allocated<AnyObject> = msg_send![class!(NSMenuItem), alloc];
+                let title = NSString::from_str("从 iPhone 导入");
+                let empty = NSString::from_str("");
+                let item: Retained<AnyObject> = msg_send![
+                    item,
+                    initWithTitle: &*title,
+                    action: None::<Sel>,
+                    keyEquivalent: &*empty
+                ];
+                let identifier = NSString::from_str(IMPORT_FROM_DEVICE_IDENTIFIER);
+                let _: () = msg_send![&item, setIdentifier: &*identifier];
+                let _: () = msg_send![&menu, addItem: &*item];
+                let location: NSPoint = msg_send![class!(NSEvent), mouseLocation];
+                let _: Bool = msg_send![
+                    &menu,
+                    popUpMenuPositioningItem: None::<&AnyObject>,
+                    atLocation: location,
+                    inView: None::<&AnyObject>
+                ];
+            }
+            let _ = tx.send(Ok(()));
+        })
+        .map_err(|e| format!("run_on_main_thread failed: {}", e))?;
+        rx.recv()
+            .map_err(|_| "continuity camera menu aborted".to_string())?
+    }
+}
+
+// macOS 连续互通相机：弹出“从 iPhone 导入”菜单（拍照 / 扫描文稿 / 添加速绘），
+// 拍摄结果直接进入参考图库，完成后发出 continuity-import 事件
+#[tauri::command]
+pub async fn import_from_iphone(
+    app: tauri::AppHandle,
+    tags: Option<Vec<String>>,
+) -> Result<(), String> {
+    *PENDING_TAGS.lock().unwrap() = tags.unwrap_or_default();
+    #[cfg(target_os = "macos")]
+    {
+        platform::show_import_menu(&app)
+    }
+    #[cfg(not(target_os = "macos"))]
+    {
+        let _ = app;
+        Err("continuity camera is only available on macOS".to_string())
+    }
+}
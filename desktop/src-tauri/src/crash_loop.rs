@@ -0,0 +1,212 @@
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use tauri::{Emitter, Manager};
+use tauri_plugin_dialog::{
+    DialogExt, MessageDialogButtons, MessageDialogKind, MessageDialogResult,
+};
+use tauri_plugin_opener::OpenerExt;
+
+use crate::{BackendLifecycle, BackendPort, LogState};
+
+// 一分钟内自动重启超过这么多次视为崩溃循环，不再自动拉起
+const MAX_RESTARTS: usize = 5;
+const WINDOW: Duration = Duration::from_secs(60);
+const STDERR_LINES: usize = 100;
+const STDOUT_LINES: usize = 50;
+
+#[derive(Default)]
+struct Supervisor {
+    // 窗口内每次自动重启的时刻
+    recent: VecDeque<Instant>,
+    total: u64,
+    last_restart_ms: Option<u128>,
+    // 已判定为崩溃循环，等待用户处理期间不再自动重启
+    tripped: bool,
+}
+
+static SUPERVISOR: Mutex<Supervisor> = Mutex::new(Supervisor {
+    recent: VecDeque::new(),
+    total: 0,
+    last_restart_ms: None,
+    tripped: false,
+});
+
+#[derive(Clone, serde::Serialize)]
+pub struct RestartStats {
+    total_restarts: u64,
+    restarts_last_minute: usize,
+    last_restart_ms: Option<u128>,
+    crash_looping: bool,
+}
+
+#[derive(Clone, serde::Serialize)]
+struct CrashLoopPayload {
+    restarts: usize,
+    window_secs: u64,
+    last_error: Option<String>,
+    // 诊断信息落盘的位置，前端可提供“打开”
+    diagnostics_path: Option<String>,
+}
+
+fn stats(supervisor: &mut Supervisor) -> RestartStats {
+    while supervisor
+        .recent
+        .front()
+        .is_some_and(|t| t.elapsed() > WINDOW)
+    {
+        supervisor.recent.pop_front();
+    }
+    RestartStats {
+        total_restarts: supervisor.total,
+        restarts_last_minute: supervisor.recent.len(),
+        last_restart_ms: supervisor.last_restart_ms,
+        crash_looping: supervisor.tripped,
+    }
+}
+
+// 每次自动重启前调用（崩溃退避重启、健康巡检重启）；返回 false 表示已进入崩溃循环，调用方不应再拉起
+pub fn allow_restart(app: &tauri::AppHandle) -> bool {
+    let restarts = {
+        let mut supervisor = SUPERVISOR.lock().unwrap();
+        if supervisor.tripped {
+            return false;
+        }
+        let current = stats(&mut supervisor);
+        if current.restarts_last_minute < MAX_RESTARTS {
+            supervisor.recent.push_back(Instant::now());
+            supervisor.total += 1;
+            supervisor.last_restart_ms = Some(crate::now_ms());
+            return true;
+        }
+        supervisor.tripped = true;
+        current.restarts_last_minute
+    };
+    on_crash_loop(app, restarts);
+    false
+}
+
+// 用户手动重启后端时调用，重新开始计数
+pub fn reset() {
+    let mut supervisor = SUPERVISOR.lock().unwrap();
+    supervisor.recent.clear();
+    supervisor.tripped = false;
+}
+
+// 把最近的 sidecar 输出写到日志目录，反馈问题时直接附上这个文件
+fn write_diagnostics(app: &tauri::AppHandle, restarts: usize) -> Result<String, String> {
+    let status = crate::backend_status(app);
+    let mut text = format!(
+        "crash loop detected at {}\nrestarts in last {}s: {}\napp version: {}\nos: {} {}\nlast error: {}\n",
+        crate::clock::rfc3339(app),
+        WINDOW.as_secs(),
+        restarts,
+        app.package_info().version,
+        std::env::consts::OS,
+        std::env::consts::ARCH,
+        status.last_error.as_deref().unwrap_or("-"),
+    );
+    text.push_str("\n--- stderr ---\n");
+    for line in crate::backend_logs::recent(app, "stderr", STDERR_LINES) {
+        text.push_str(&line);
+        text.push('\n');
+    }
+    text.push_str("\n--- stdout ---\n");
+    for line in crate::backend_logs::recent(app, "stdout", STDOUT_LINES) {
+        text.push_str(&line);
+        text.push('\n');
+    }
+    let path = app.state::<LogState>().dir.join(format!(
+        "crash-loop-{}.log",
+        crate::clock::filename_stamp(app)
+    ));
+    std::fs::write(&path, text)
+        .map_err(|e| format!("write crash loop diagnostics failed: {}", e))?;
+    Ok(path.to_string_lossy().to_string())
+}
+
+fn restart_now(app: &tauri::AppHandle) {
+    reset();
+    app.state::<LogState>().log_app(
+        "INFO",
+        "Restarting backend after crash loop on user request",
+    );
+    let port_state = app.state::<BackendPort>().0.clone();
+    if let Err(err) = crate::spawn_sidecar(app, port_state) {
+        app.state::<LogState>()
+            .log_app("ERROR", &format!("Sidecar restart failed: {}", err));
+    }
+}
+
+fn on_crash_loop(app: &tauri::AppHandle, restarts: usize) {
+    app.state::<LogState>().log_app(
+        "ERROR",
+        &format!(
+            "Sidecar restarted {} times within {}s, auto restart stopped",
+            restarts,
+            WINDOW.as_secs()
+        ),
+    );
+    let diagnostics_path = match write_diagnostics(app, restarts) {
+        Ok(path) => Some(path),
+        Err(err) => {
+            app.state::<LogState>().log_app("WARN", &err);
+            None
+        }
+    };
+    crate::update_backend_status(app, |s| s.state = BackendLifecycle::Crashed);
+    let last_error = crate::backend_status(app).last_error;
+    let _ = app.emit(
+        "backend-crash-loop",
+        CrashLoopPayload {
+            restarts,
+            window_secs: WINDOW.as_secs(),
+            last_error: last_error.clone(),
+            diagnostics_path: diagnostics_path.clone(),
+        },
+    );
+
+    let mut message = format!(
+        "后台服务在 {} 秒内异常退出了 {} 次，已停止自动重启。",
+        WINDOW.as_secs(),
+        restarts
+    );
+    if let Some(error) = &last_error {
+        message.push_str(&format!("\n\n最后的错误：{}", error));
+    }
+    if let Some(path) = &diagnostics_path {
+        message.push_str(&format!("\n\n诊断信息已保存到：{}", path));
+    }
+    let app_handle = app.clone();
+    app.dialog()
+        .message(message)
+        .title("后台服务反复崩溃")
+        .kind(MessageDialogKind::Error)
+        .buttons(MessageDialogButtons::YesNoCancelCustom(
+            "重新启动".to_string(),
+            "打开日志目录".to_string(),
+            "关闭".to_string(),
+        ))
+        .show_with_result(move |result| {
+            let choice = match result {
+                MessageDialogResult::Custom(label) => label,
+                _ => String::new(),
+            };
+            match choice.as_str() {
+                "重新启动" => restart_now(&app_handle),
+                "打开日志目录" => {
+                    let dir = app_handle.state::<LogState>().dir.clone();
+                    let _ = app_handle
+                        .opener()
+                        .open_path(dir.to_string_lossy().to_string(), None::<String>);
+                }
+                _ => {}
+            }
+        });
+}
+
+#[tauri::command]
+pub fn get_restart_stats() -> RestartStats {
+    stats(&mut SUPERVISOR.lock().unwrap())
+}
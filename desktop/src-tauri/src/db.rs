@@ -0,0 +1,283 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use rusqlite::backup::Backup;
+use rusqlite::{Connection, OpenFlags};
+use tauri::Manager;
+
+use crate::settings::SettingsState;
+use crate::LogState;
+
+// 快照只保留最近几份，单个 data.db 可能有几百 MB
+const MAX_SNAPSHOTS: usize = 5;
+
+#[derive(serde::Serialize)]
+pub struct OptimizeDatabaseResult {
+    before_bytes: u64,
+    after_bytes: u64,
+    reclaimed_bytes: u64,
+    duration_ms: u64,
+}
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+pub struct DbSnapshot {
+    id: String,
+    created_at_ms: u64,
+    // pre-update / pre-restore / manual
+    reason: String,
+    app_version: String,
+    size_bytes: u64,
+}
+
+// 与 sidecar 共用同一份 data.db（位于 sidecar 工作目录）
+pub fn db_path(app: &tauri::AppHandle) -> PathBuf {
+    crate::paths::backend_data_dir(app).join("data.db")
+}
+
+fn snapshots_dir(app: &tauri::AppHandle) -> PathBuf {
+    crate::app_data_base(app).join("db_snapshots")
+}
+
+// 壳层只读访问数据库：sidecar 持有写连接，这里不参与写锁竞争
+pub fn open_readonly(app: &tauri::AppHandle) -> Result<Connection, String> {
+    let path = db_path(app);
+    let conn = Connection::open_with_flags(
+        &path,
+        OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+    )
+    .map_err(|e| format!("open database failed: {} ({})", e, path.display()))?;
+    conn.busy_timeout(Duration::from_secs(5))
+        .map_err(|e| format!("set busy timeout failed: {}", e))?;
+    Ok(conn)
+}
+
+// 走 SQLite 在线备份 API，sidecar 运行中也能拿到一致的副本（直接复制文件会漏掉 WAL）
+fn backup_to(src: &Connection, dest: &Path) -> Result<(), String> {
+    let mut dst = Connection::open(dest).map_err(|e| format!("open snapshot failed: {}", e))?;
+    Backup::new(src, &mut dst)
+        .and_then(|backup| backup.run_to_completion(256, Duration::from_millis(20), None))
+        .map_err(|e| format!("backup database failed: {}", e))
+}
+
+fn read_snapshot_meta(dir: &Path) -> Option<DbSnapshot> {
+    let bytes = fs::read(dir.join("snapshot.json")).ok()?;
+    let meta: DbSnapshot = serde_json::from_slice(&bytes).ok()?;
+    dir.join("data.db").is_file().then_some(meta)
+}
+
+fn list_snapshots(app: &tauri::AppHandle) -> Vec<DbSnapshot> {
+    let Ok(entries) = fs::read_dir(snapshots_dir(app)) else {
+        return Vec::new();
+    };
+    let mut snapshots: Vec<DbSnapshot> = entries
+        .flatten()
+        .filter_map(|entry| read_snapshot_meta(&entry.path()))
+        .collect();
+    snapshots.sort_by_key(|s| std::cmp::Reverse(s.created_at_ms));
+    snapshots
+}
+
+fn prune_snapshots(app: &tauri::AppHandle) {
+    let dir = snapshots_dir(app);
+    for stale in list_snapshots(app).into_iter().skip(MAX_SNAPSHOTS) {
+        let _ = fs::remove_dir_all(dir.join(&stale.id));
+    }
+}
+
+fn take_snapshot(app: &tauri::AppHandle, reason: &str) -> Result<DbSnapshot, String> {
+    let created_at_ms = crate::now_ms() as u64;
+    let reason: String = reason
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '-')
+        .collect();
+    let reason = if reason.is_empty() {
+        "manual".to_string()
+    } else {
+        reason
+    };
+    let id = format!("{}-{}", created_at_ms, reason);
+    let dir = snapshots_dir(app).join(&id);
+    fs::create_dir_all(&dir).map_err(|e| format!("create snapshot dir failed: {}", e))?;
+
+    let dest = dir.join("data.db");
+    let result = open_readonly(app).and_then(|src| backup_to(&src, &dest));
+    if let Err(err) = result {
+        let _ = fs::remove_dir_all(&dir);
+        return Err(err);
+    }
+
+    let meta = DbSnapshot {
+        id,
+        created_at_ms,
+        reason,
+        app_version: app.package_info().version.to_string(),
+        size_bytes: fs::metadata(&dest).map(|m| m.len()).unwrap_or(0),
+    };
+    crate::settings::write_json_atomic(&dir.join("snapshot.json"), &meta)?;
+
+    app.state::<LogState>().log_app(
+        "INFO",
+        &format!(
+            "Database snapshot created id={} size={}",
+            meta.id, meta.size_bytes
+        ),
+    );
+    Ok(meta)
+}
+
+pub fn create_snapshot(app: &tauri::AppHandle, reason: &str) -> Result<DbSnapshot, String> {
+    let meta = take_snapshot(app, reason)?;
+    prune_snapshots(app);
+    Ok(meta)
+}
+
+// 版本号变化说明刚完成更新：在新版 sidecar 迁移表结构之前先留一份旧库
+pub fn snapshot_on_version_change(app: &tauri::AppHandle) {
+    let settings = app.state::<SettingsState>();
+    let current = app.package_info().version.to_string();
+    let previous = settings.get().last_app_version;
+    if previous.as_deref() == Some(current.as_str()) {
+        return;
+    }
+    // 没有记录过版本的老用户升级上来同样需要快照；全新安装还没有 data.db
+    if db_path(app).exists() {
+        if let Err(err) = create_snapshot(app, "pre-update") {
+            app.state::<LogState>()
+                .log_app("ERROR", &format!("pre-update snapshot failed: {}", err));
+        }
+    }
+    let _ = settings.update(|s| s.last_app_version = Some(current));
+}
+
+// 手动或更新安装前调用（前端在 install 前触发），reason 只保留字母数字和连字符
+#[tauri::command]
+pub async fn snapshot_database(
+    app: tauri::AppHandle,
+    reason: Option<String>,
+) -> Result<DbSnapshot, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        create_snapshot(&app, reason.as_deref().unwrap_or("manual"))
+    })
+    .await
+    .map_err(|e| format!("snapshot task failed: {}", e))?
+}
+
+#[tauri::command]
+pub fn list_db_snapshots(app: tauri::AppHandle) -> Vec<DbSnapshot> {
+    list_snapshots(&app)
+}
+
+// 数据库 + WAL/SHM 的总占用
+fn db_files_size(app: &tauri::AppHandle) -> u64 {
+    let path = db_path(app);
+    ["", "-wal", "-shm"]
+        .iter()
+        .map(|suffix| {
+            let mut name = path.as_os_str().to_owned();
+            name.push(suffix);
+            fs::metadata(PathBuf::from(name))
+                .map(|m| m.len())
+                .unwrap_or(0)
+        })
+        .sum()
+}
+
+// 维护操作需要独占写连接，在 crate::with_sidecar_stopped 里执行；只读安全模式下不允许写库
+pub fn ensure_writable(app: &tauri::AppHandle) -> Result<(), String> {
+    if crate::safe_mode::is_read_only(app) {
+        return Err("database is in read-only safe mode".to_string());
+    }
+    Ok(())
+}
+
+pub fn open_for_maintenance(app: &tauri::AppHandle) -> Result<Connection, String> {
+    let conn =
+        Connection::open(db_path(app)).map_err(|e| format!("open database failed: {}", e))?;
+    // sidecar 进程退出可能稍慢，给锁释放留出时间
+    conn.busy_timeout(Duration::from_secs(10))
+        .map_err(|e| format!("set busy timeout failed: {}", e))?;
+    Ok(conn)
+}
+
+// 恢复快照：先停 sidecar 释放写连接，并把当前库再备份一份，恢复后重新拉起 sidecar
+#[tauri::command]
+pub async fn restore_db_snapshot(app: tauri::AppHandle, id: String) -> Result<DbSnapshot, String> {
+    let snapshot = list_snapshots(&app)
+        .into_iter()
+        .find(|s| s.id == id)
+        .ok_or_else(|| format!("snapshot not found: {}", id))?;
+    let source = snapshots_dir(&app).join(&snapshot.id).join("data.db");
+    ensure_writable(&app)?;
+
+    let app_for_task = app.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let app = app_for_task;
+        crate::with_sidecar_stopped(&app, crate::SidecarRestart::Maintenance, || {
+            // 这里先不清理旧快照，避免恢复源本身正好是最旧的那份被删掉
+            if db_path(&app).exists() {
+                take_snapshot(&app, "pre-restore")?;
+            }
+            let mut dst = open_for_maintenance(&app)?;
+            let src = Connection::open_with_flags(&source, OpenFlags::SQLITE_OPEN_READ_ONLY)
+                .map_err(|e| format!("open snapshot failed: {}", e))?;
+            Backup::new(&src, &mut dst)
+                .and_then(|backup| backup.run_to_completion(256, Duration::from_millis(20), None))
+                .map_err(|e| format!("restore database failed: {}", e))?;
+            prune_snapshots(&app);
+            Ok(())
+        })
+    })
+    .await
+    .map_err(|e| format!("restore task failed: {}", e))??;
+
+    app.state::<LogState>().log_app(
+        "INFO",
+        &format!("Database restored from snapshot id={}", snapshot.id),
+    );
+    Ok(snapshot)
+}
+
+// 停掉 sidecar 后执行 WAL checkpoint + VACUUM + ANALYZE，完成后重新拉起；返回回收的空间
+#[tauri::command]
+pub async fn optimize_database(app: tauri::AppHandle) -> Result<OptimizeDatabaseResult, String> {
+    let path = db_path(&app);
+    if !path.exists() {
+        return Err("database not found".to_string());
+    }
+    let before_bytes = db_files_size(&app);
+    // VACUUM 会先写一份完整的临时副本，最坏需要与原库同样大的空间
+    crate::disk::ensure_free_space(&path, before_bytes)?;
+
+    ensure_writable(&app)?;
+    let started = std::time::Instant::now();
+    let app_for_task = app.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let app = app_for_task;
+        crate::with_sidecar_stopped(&app, crate::SidecarRestart::Maintenance, || {
+            let conn = open_for_maintenance(&app)?;
+            conn.execute_batch(
+                "PRAGMA wal_checkpoint(TRUNCATE); VACUUM; ANALYZE; PRAGMA wal_checkpoint(TRUNCATE);",
+            )
+            .map_err(|e| format!("optimize database failed: {}", e))
+        })
+    })
+    .await
+    .map_err(|e| format!("optimize task failed: {}", e))??;
+
+    let after_bytes = db_files_size(&app);
+    let result = OptimizeDatabaseResult {
+        before_bytes,
+        after_bytes,
+        reclaimed_bytes: before_bytes.saturating_sub(after_bytes),
+        duration_ms: started.elapsed().as_millis() as u64,
+    };
+    app.state::<LogState>().log_app(
+        "INFO",
+        &format!(
+            "Database optimized before={} after={} duration_ms={}",
+            result.before_bytes, result.after_bytes, result.duration_ms
+        ),
+    );
+    Ok(result)
+}
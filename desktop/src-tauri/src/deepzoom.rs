@@ -0,0 +1,282 @@
+use std::fs;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+
+use image::imageops::FilterType;
+use image::DynamicImage;
+use sha2::{Digest, Sha256};
+use tauri::{Emitter, Manager};
+
+use crate::LogState;
+
+const DZI_NAME: &str = "image.dzi";
+const TILES_NAME: &str = "image_files";
+const DEFAULT_TILE_SIZE: u32 = 254;
+const TILE_OVERLAP: u32 = 1;
+const JPEG_QUALITY: u8 = 90;
+const STAGING_SUFFIX: &str = ".partial";
+// 切片缓存只保留最近用到的若干张，超大图的金字塔动辄几百 MB
+const MAX_CACHED_PYRAMIDS: usize = 12;
+
+#[derive(Clone, serde::Serialize)]
+pub struct DeepZoomResult {
+    // image.dzi 和切片目录都在应用数据目录下，前端用 convertFileSrc 经 asset 协议读取
+    dzi_path: String,
+    tiles_dir: String,
+    width: u32,
+    height: u32,
+    levels: u32,
+    tile_size: u32,
+    overlap: u32,
+    format: String,
+    tiles: usize,
+    // true 表示直接复用了已有的金字塔
+    cached: bool,
+}
+
+#[derive(Clone, serde::Serialize)]
+struct DeepZoomProgress {
+    key: String,
+    level: u32,
+    levels: u32,
+}
+
+// 路径 + 大小 + 修改时间 + 参数决定缓存键，原图被覆盖后会重新切片
+fn cache_key(path: &Path, tile_size: u32, format: &str) -> Result<String, String> {
+    let meta = fs::metadata(path).map_err(|e| format!("read file metadata failed: {}", e))?;
+    let modified = meta
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_millis())
+        .unwrap_or(0);
+    let seed = format!(
+        "{}|{}|{}|{}|{}",
+        path.display(),
+        meta.len(),
+        modified,
+        tile_size,
+        format
+    );
+    Ok(Sha256::digest(seed.as_bytes())
+        .iter()
+        .take(12)
+        .map(|b| format!("{:02x}", b))
+        .collect())
+}
+
+fn level_count(width: u32, height: u32) -> u32 {
+    // DZI 约定：第 0 层为 1x1，最高层为原图
+    32 - width.max(height).max(1).saturating_sub(1).leading_zeros() + 1
+}
+
+fn dzi_xml(width: u32, height: u32, tile_size: u32, format: &str) -> String {
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<Image xmlns=\"http://schemas.microsoft.com/deepzoom/2008\" Format=\"{}\" Overlap=\"{}\" TileSize=\"{}\">\n  <Size Width=\"{}\" Height=\"{}\"/>\n</Image>\n",
+        format, TILE_OVERLAP, tile_size, width, height
+    )
+}
+
+fn write_tile(img: &DynamicImage, dest: &Path, format: &str) -> Result<(), String> {
+    let file = fs::File::create(dest).map_err(|e| format!("create tile failed: {}", e))?;
+    let writer = BufWriter::new(file);
+    if format == "png" {
+        crate::export::write_png(img, writer, None)
+    } else {
+        crate::export::write_jpeg(img, writer, JPEG_QUALITY, None)
+    }
+}
+
+fn build_pyramid(
+    app: &tauri::AppHandle,
+    key: &str,
+    img: DynamicImage,
+    dir: &Path,
+    tile_size: u32,
+    format: &str,
+) -> Result<usize, String> {
+    let (width, height) = (img.width(), img.height());
+    let levels = level_count(width, height);
+    let tiles_dir = dir.join(TILES_NAME);
+    let mut tiles = 0;
+    // 从最高层开始逐层减半，每层都由上一层缩小得到，避免每层都从原图重采样
+    let mut current = img;
+    for level in (0..levels).rev() {
+        let scale = 1u64 << (levels - 1 - level);
+        let level_w = ((width as u64).div_ceil(scale) as u32).max(1);
+        let level_h = ((height as u64).div_ceil(scale) as u32).max(1);
+        if current.width() != level_w || current.height() != level_h {
+            current = current.resize_exact(level_w, level_h, FilterType::Triangle);
+        }
+        let level_dir = tiles_dir.join(level.to_string());
+        fs::create_dir_all(&level_dir).map_err(|e| format!("create tile dir failed: {}", e))?;
+        let cols = level_w.div_ceil(tile_size);
+        let rows = level_h.div_ceil(tile_size);
+        for col in 0..cols {
+            for row in 0..rows {
+                let x = (col * tile_size).saturating_sub(if col > 0 { TILE_OVERLAP } else { 0 });
+                let y = (row * tile_size).saturating_sub(if row > 0 { TILE_OVERLAP } else { 0 });
+                let right = ((col + 1) * tile_size + TILE_OVERLAP).min(level_w);
+                let bottom = ((row + 1) * tile_size + TILE_OVERLAP).min(level_h);
+                let tile = current.crop_imm(x, y, right - x, bottom - y);
+                write_tile(
+                    &tile,
+                    &level_dir.join(format!("{}_{}.{}", col, row, format)),
+                    format,
+                )?;
+                tiles += 1;
+            }
+        }
+        let _ = app.emit(
+            "deep-zoom-progress",
+            DeepZoomProgress {
+                key: key.to_string(),
+                level: levels - level,
+                levels,
+            },
+        );
+    }
+    fs::write(
+        dir.join(DZI_NAME),
+        dzi_xml(width, height, tile_size, format),
+    )
+    .map_err(|e| format!("write dzi failed: {}", e))?;
+    Ok(tiles)
+}
+
+fn count_tiles(dir: &Path) -> usize {
+    fs::read_dir(dir.join(TILES_NAME))
+        .map(|levels| {
+            levels
+                .flatten()
+                .filter_map(|l| fs::read_dir(l.path()).ok())
+                .map(|tiles| tiles.count())
+                .sum()
+        })
+        .unwrap_or(0)
+}
+
+// 按描述文件的修改时间保留最近的 MAX_CACHED_PYRAMIDS 个金字塔，其余删除；正在生成的临时目录不动
+fn prune_cache(root: &Path, keep: &Path) {
+    let Ok(entries) = fs::read_dir(root) else {
+        return;
+    };
+    let mut dirs: Vec<(std::time::SystemTime, PathBuf)> = entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.is_dir() && p != keep)
+        .filter(|p| !p.to_string_lossy().ends_with(STAGING_SUFFIX))
+        .map(|p| {
+            let modified = fs::metadata(p.join(DZI_NAME))
+                .and_then(|m| m.modified())
+                .unwrap_or(std::time::UNIX_EPOCH);
+            (modified, p)
+        })
+        .collect();
+    dirs.sort_by(|a, b| b.0.cmp(&a.0));
+    for (_, dir) in dirs.into_iter().skip(MAX_CACHED_PYRAMIDS.saturating_sub(1)) {
+        let _ = fs::remove_dir_all(dir);
+    }
+}
+
+// 为超大图生成 Deep Zoom（DZI）切片金字塔，前端可用 OpenSeadragon 按需加载切片平滑缩放
+// format 缺省时带透明通道用 png，否则 jpg；同一张图重复调用直接复用已有切片
+#[tauri::command]
+pub async fn build_deep_zoom(
+    app: tauri::AppHandle,
+    path: String,
+    tile_size: Option<u32>,
+    format: Option<String>,
+) -> Result<DeepZoomResult, String> {
+    let src_path = crate::resolve_local_path(&app, &path)?;
+    let tile_size = tile_size.unwrap_or(DEFAULT_TILE_SIZE).clamp(64, 1024);
+    let format = match format.map(|f| f.trim().to_ascii_lowercase()).as_deref() {
+        None | Some("") => None,
+        Some("png") => Some("png"),
+        Some("jpg") | Some("jpeg") => Some("jpg"),
+        Some(other) => return Err(format!("unsupported tile format: {}", other)),
+    };
+    let root = crate::app_data_base(&app).join("deep_zoom");
+    let app_for_task = app.clone();
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        let (width, height) = image::image_dimensions(&src_path)
+            .map_err(|e| format!("read image size failed: {}", e))?;
+        // 未指定格式时先看两种格式是否已有缓存，命中就不必解码原图
+        let mut hit = None;
+        for candidate in format
+            .map(|f| vec![f])
+            .unwrap_or_else(|| vec!["jpg", "png"])
+        {
+            let key = cache_key(&src_path, tile_size, candidate)?;
+            if root.join(&key).join(DZI_NAME).exists() {
+                hit = Some((candidate, key));
+                break;
+            }
+        }
+        let cached = hit.is_some();
+        let (format, key, tiles) = match hit {
+            Some((format, key)) => {
+                let dir = root.join(&key);
+                // 重写描述文件刷新修改时间，清理缓存时按它判断最近使用
+                fs::write(
+                    dir.join(DZI_NAME),
+                    dzi_xml(width, height, tile_size, format),
+                )
+                .map_err(|e| format!("write dzi failed: {}", e))?;
+                (format, key, count_tiles(&dir))
+            }
+            None => {
+                let img = crate::export::load_image(&src_path)?;
+                let format = format.unwrap_or(if img.color().has_alpha() {
+                    "png"
+                } else {
+                    "jpg"
+                });
+                let key = cache_key(&src_path, tile_size, format)?;
+                // 先写到临时目录，全部成功后再改名，中途失败不会留下残缺的金字塔
+                let staging = root.join(format!("{}{}", key, STAGING_SUFFIX));
+                let _ = fs::remove_dir_all(&staging);
+                fs::create_dir_all(&staging)
+                    .map_err(|e| format!("create deep zoom dir failed: {}", e))?;
+                // 各层面积之和约为原图的 4/3
+                crate::disk::ensure_free_space(&staging, width as u64 * height as u64 * 2)?;
+                let built = build_pyramid(&app_for_task, &key, img, &staging, tile_size, format)
+                    .and_then(|tiles| {
+                        let dir = root.join(&key);
+                        let _ = fs::remove_dir_all(&dir);
+                        fs::rename(&staging, &dir)
+                            .map_err(|e| format!("finalize deep zoom dir failed: {}", e))?;
+                        Ok(tiles)
+                    });
+                if built.is_err() {
+                    let _ = fs::remove_dir_all(&staging);
+                }
+                (format, key, built?)
+            }
+        };
+        let dir = root.join(&key);
+        prune_cache(&root, &dir);
+        Ok::<_, String>(DeepZoomResult {
+            dzi_path: dir.join(DZI_NAME).to_string_lossy().to_string(),
+            tiles_dir: dir.join(TILES_NAME).to_string_lossy().to_string(),
+            width,
+            height,
+            levels: level_count(width, height),
+            tile_size,
+            overlap: TILE_OVERLAP,
+            format: format.to_string(),
+            tiles,
+            cached,
+        })
+    })
+    .await
+    .map_err(|e| format!("deep zoom task failed: {}", e))??;
+    app.state::<LogState>().log_app(
+        "INFO",
+        &format!(
+            "Deep zoom pyramid ready size={}x{} levels={} tiles={} cached={}",
+            result.width, result.height, result.levels, result.tiles, result.cached
+        ),
+    );
+    Ok(result)
+}
@@ -0,0 +1,323 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tauri::{Emitter, Manager, State};
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+
+use crate::settings::SettingsState;
+use crate::LogState;
+
+// 两级阈值：warning 提醒清理，critical 时写入前置检查直接拒绝
+const WARNING_FREE_BYTES: u64 = 2 * 1024 * 1024 * 1024;
+const CRITICAL_FREE_BYTES: u64 = 500 * 1024 * 1024;
+// 写入前至少保留的余量，防止“刚好写满”导致数据库/日志一起写坏
+const WRITE_RESERVE_BYTES: u64 = 100 * 1024 * 1024;
+const MONITOR_INTERVAL: Duration = Duration::from_secs(60);
+// 恢复时多留一段余量，避免在阈值附近反复暂停/恢复
+const RESUME_MARGIN_BYTES: u64 = 256 * 1024 * 1024;
+
+#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiskLevel {
+    Ok,
+    Warning,
+    Critical,
+}
+
+impl DiskLevel {
+    fn from_free(free: u64) -> Self {
+        if free < CRITICAL_FREE_BYTES {
+            Self::Critical
+        } else if free < WARNING_FREE_BYTES {
+            Self::Warning
+        } else {
+            Self::Ok
+        }
+    }
+}
+
+#[derive(Clone, serde::Serialize)]
+pub struct DiskStatus {
+    // storage（图片目录所在卷）/ temp（系统临时目录所在卷）
+    kind: String,
+    path: String,
+    free_bytes: u64,
+    total_bytes: u64,
+    level: DiskLevel,
+}
+
+// 记录每个卷上次上报的级别，只在级别变化时发 disk-low，避免每分钟刷屏
+pub struct DiskState(pub Arc<Mutex<HashMap<String, DiskLevel>>>);
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct DiskGuardSettings {
+    pub enabled: bool,
+    // 存储卷剩余空间低于该值时暂停接收新的生成结果
+    pub min_free_mb: u64,
+}
+
+impl Default for DiskGuardSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            min_free_mb: 1024,
+        }
+    }
+}
+
+// 磁盘保护触发后置为 true，空间恢复或关闭保护后自动解除
+pub struct DiskGuardState(pub Arc<AtomicBool>);
+
+#[derive(Clone, serde::Serialize)]
+pub struct DiskGuardStatus {
+    paused: bool,
+    free_bytes: Option<u64>,
+    floor_bytes: u64,
+}
+
+// 目标文件可能还不存在，向上找到第一个存在的目录再查询所在卷
+fn existing_ancestor(path: &Path) -> Option<PathBuf> {
+    path.ancestors().find(|p| p.exists()).map(Path::to_path_buf)
+}
+
+#[cfg(unix)]
+fn query_space(path: &Path) -> Option<(u64, u64)> {
+    use std::os::unix::ffi::OsStrExt;
+
+    let c_path = std::ffi::CString::new(path.as_os_str().as_bytes()).ok()?;
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(c_path.as_ptr(), &mut stat) } != 0 {
+        return None;
+    }
+    // f_bavail 是普通用户可用的块数，比 f_bfree 更接近实际可写空间
+    let block = stat.f_frsize as u64;
+    Some((stat.f_bavail as u64 * block, stat.f_blocks as u64 * block))
+}
+
+#[cfg(windows)]
+fn query_space(path: &Path) -> Option<(u64, u64)> {
+    use std::os::windows::ffi::OsStrExt;
+    use windows_sys::Win32::Storage::FileSystem::GetDiskFreeSpaceExW;
+
+    let wide: Vec<u16> = path.as_os_str().encode_wide().chain(Some(0)).collect();
+    let mut free = 0u64;
+    let mut total = 0u64;
+    let ok =
+        unsafe { GetDiskFreeSpaceExW(wide.as_ptr(), &mut free, &mut total, std::ptr::null_mut()) };
+    (ok != 0).then_some((free, total))
+}
+
+// 返回 (可用字节, 总字节)，查询失败时返回 None（调用方按“不拦截”处理）
+pub fn free_space(path: &Path) -> Option<(u64, u64)> {
+    query_space(&existing_ancestor(path)?)
+}
+
+// 写入前置检查：剩余空间不足时提前报错，而不是写到一半留下损坏文件
+pub fn ensure_free_space(path: &Path, needed_bytes: u64) -> Result<(), String> {
+    let Some((free, _)) = free_space(path) else {
+        return Ok(());
+    };
+    if free < needed_bytes.saturating_add(WRITE_RESERVE_BYTES) {
+        return Err(format!(
+            "insufficient disk space: {} MB free at {}, need {} MB",
+            free / 1024 / 1024,
+            path.display(),
+            needed_bytes.div_ceil(1024 * 1024)
+        ));
+    }
+    Ok(())
+}
+
+fn watched_paths(app: &tauri::AppHandle) -> Vec<(&'static str, PathBuf)> {
+    vec![
+        ("storage", crate::paths::storage_dir(app)),
+        ("temp", crate::paths::temp_dir(app)),
+    ]
+}
+
+fn collect_status(app: &tauri::AppHandle) -> Vec<DiskStatus> {
+    watched_paths(app)
+        .into_iter()
+        .filter_map(|(kind, path)| {
+            let (free, total) = free_space(&path)?;
+            Some(DiskStatus {
+                kind: kind.to_string(),
+                path: path.to_string_lossy().to_string(),
+                free_bytes: free,
+                total_bytes: total,
+                level: DiskLevel::from_free(free),
+            })
+        })
+        .collect()
+}
+
+fn storage_free(app: &tauri::AppHandle) -> Option<u64> {
+    free_space(&crate::paths::storage_dir(app)).map(|(free, _)| free)
+}
+
+pub fn generation_paused(app: &tauri::AppHandle) -> bool {
+    app.state::<DiskGuardState>().0.load(Ordering::SeqCst)
+}
+
+fn guard_status(app: &tauri::AppHandle) -> DiskGuardStatus {
+    let settings = app.state::<SettingsState>().get().disk_guard;
+    DiskGuardStatus {
+        paused: generation_paused(app),
+        free_bytes: storage_free(app),
+        floor_bytes: settings.min_free_mb * 1024 * 1024,
+    }
+}
+
+// 提示用户清理空间，“释放空间”直接跳到前端的存储统计页
+fn prompt_free_space(app: &tauri::AppHandle, free: u64) {
+    let app_handle = app.clone();
+    app.dialog()
+        .message(format!(
+            "磁盘剩余空间仅 {} MB，已暂停接收新的生成结果。\n请清理空间后继续。",
+            free / 1024 / 1024
+        ))
+        .title("磁盘空间不足")
+        .kind(MessageDialogKind::Warning)
+        .buttons(MessageDialogButtons::OkCancelCustom(
+            "释放空间".to_string(),
+            "稍后".to_string(),
+        ))
+        .show(move |confirmed| {
+            if !confirmed {
+                return;
+            }
+            if let Some(window) = app_handle.get_webview_window("main") {
+                let _ = window.show();
+                let _ = window.set_focus();
+            }
+            let _ = app_handle.emit("open-storage-stats", ());
+        });
+}
+
+fn update_guard(app: &tauri::AppHandle) {
+    let settings = app.state::<SettingsState>().get().disk_guard;
+    let guard = app.state::<DiskGuardState>();
+    let was_paused = guard.0.load(Ordering::SeqCst);
+    let floor = settings.min_free_mb * 1024 * 1024;
+    let free = storage_free(app);
+
+    let paused = match free {
+        Some(_) if !settings.enabled => false,
+        Some(free) if was_paused => free < floor.saturating_add(RESUME_MARGIN_BYTES),
+        Some(free) => free < floor,
+        // 查询失败时保持原状态
+        None => was_paused && settings.enabled,
+    };
+    if paused == was_paused {
+        return;
+    }
+    guard.0.store(paused, Ordering::SeqCst);
+
+    let status = guard_status(app);
+    app.state::<LogState>().log_app(
+        if paused { "WARN" } else { "INFO" },
+        &format!(
+            "Disk guard {} free_mb={:?} floor_mb={}",
+            if paused {
+                "paused generation"
+            } else {
+                "resumed generation"
+            },
+            status.free_bytes.map(|b| b / 1024 / 1024),
+            settings.min_free_mb
+        ),
+    );
+    if paused {
+        let _ = app.emit("generation-paused", status.clone());
+        prompt_free_space(app, status.free_bytes.unwrap_or(0));
+    } else {
+        let _ = app.emit("generation-resumed", status);
+    }
+}
+
+fn check_once(app: &tauri::AppHandle) {
+    update_guard(app);
+    let state = app.state::<DiskState>();
+    for status in collect_status(app) {
+        let changed = {
+            let mut levels = state.0.lock().unwrap();
+            let previous = levels.insert(status.kind.clone(), status.level);
+            // 首次检查时空间充足就不用通知
+            previous.unwrap_or(DiskLevel::Ok) != status.level
+        };
+        if !changed {
+            continue;
+        }
+        app.state::<LogState>().log_app(
+            if status.level == DiskLevel::Ok {
+                "INFO"
+            } else {
+                "WARN"
+            },
+            &format!(
+                "Disk level changed kind={} level={:?} free_mb={} path={}",
+                status.kind,
+                status.level,
+                status.free_bytes / 1024 / 1024,
+                status.path
+            ),
+        );
+        let _ = app.emit("disk-low", status);
+    }
+}
+
+// 后台定时检查存储卷与临时目录卷，级别变化时向前端发送 disk-low（恢复时 level=ok）
+pub fn start_monitor(app: &tauri::AppHandle) {
+    let app = app.clone();
+    std::thread::spawn(move || loop {
+        check_once(&app);
+        std::thread::sleep(MONITOR_INTERVAL);
+    });
+}
+
+// 获取存储卷与临时目录卷的剩余空间
+#[tauri::command]
+pub async fn get_disk_space(app: tauri::AppHandle) -> Result<Vec<DiskStatus>, String> {
+    tauri::async_runtime::spawn_blocking(move || collect_status(&app))
+        .await
+        .map_err(|e| format!("query disk space failed: {}", e))
+}
+
+#[tauri::command]
+pub fn get_disk_guard_status(app: tauri::AppHandle) -> DiskGuardStatus {
+    guard_status(&app)
+}
+
+// 提交生成前由前端调用：磁盘保护已触发时直接拒绝，避免结果写到一半
+#[tauri::command]
+pub fn check_generation_allowed(app: tauri::AppHandle) -> Result<(), String> {
+    if generation_paused(&app) {
+        return Err("generation paused: disk space is below the configured floor".to_string());
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_disk_guard_settings(state: State<'_, SettingsState>) -> DiskGuardSettings {
+    state.get().disk_guard
+}
+
+// 保存后立即重新评估，调低阈值或关闭保护时不用等下一轮定时检查
+#[tauri::command]
+pub async fn set_disk_guard_settings(
+    app: tauri::AppHandle,
+    settings: DiskGuardSettings,
+) -> Result<DiskGuardStatus, String> {
+    app.state::<SettingsState>()
+        .update(|s| s.disk_guard = settings)?;
+    tauri::async_runtime::spawn_blocking(move || {
+        update_guard(&app);
+        guard_status(&app)
+    })
+    .await
+    .map_err(|e| format!("update disk guard failed: {}", e))
+}
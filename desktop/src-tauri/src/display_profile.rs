@@ -0,0 +1,294 @@
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use sha2::{Digest, Sha256};
+use tauri::{Emitter, Manager};
+
+use crate::LogState;
+
+// 上次上报时主窗口所在的显示器，窗口移动后只有换了显示器才重新检测
+static LAST_MONITOR: Mutex<Option<String>> = Mutex::new(None);
+
+// sRGB 三原色经 D50 适配后的 XYZ（ICC 配置文件里的 rXYZ/gXYZ/bXYZ 同样是 D50）
+const SRGB_PRIMARIES: [[f64; 3]; 3] = [
+    [0.4361, 0.2225, 0.0139],
+    [0.3851, 0.7169, 0.0971],
+    [0.1431, 0.0606, 0.7141],
+];
+// 色域面积超过 sRGB 这么多倍视为广色域
+const WIDE_GAMUT_RATIO: f64 = 1.1;
+
+#[derive(Clone, serde::Serialize)]
+pub struct DisplayProfile {
+    monitor: Option<String>,
+    // ICC 配置文件里的描述，如 "Display P3"、"Color LCD"
+    description: Option<String>,
+    // 复制到应用数据目录下的 .icc，前端可经 asset 协议读取
+    icc_path: Option<String>,
+    // 原色三角形面积与 sRGB 的比值，无法解析时为空
+    gamut_ratio: Option<f64>,
+    wide_gamut: bool,
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    data.get(offset..offset + 4)
+        .map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+fn find_tag<'a>(icc: &'a [u8], signature: &[u8; 4]) -> Option<&'a [u8]> {
+    let count = read_u32(icc, 128)? as usize;
+    (0..count.min(256)).find_map(|i| {
+        let entry = 132 + i * 12;
+        if icc.get(entry..entry + 4)? != signature {
+            return None;
+        }
+        let offset = read_u32(icc, entry + 4)? as usize;
+        let size = read_u32(icc, entry + 8)? as usize;
+        icc.get(offset..offset.checked_add(size)?)
+    })
+}
+
+// v2 的 desc 是 ASCII，v4 的 mluc 是 UTF-16BE，取第一条记录
+fn description(icc: &[u8]) -> Option<String> {
+    let tag = find_tag(icc, b"desc")?;
+    let text = match tag.get(0..4)? {
+        b"desc" => {
+            let len = read_u32(tag, 8)? as usize;
+            String::from_utf8_lossy(tag.get(12..12 + len)?).to_string()
+        }
+        b"mluc" => {
+            let len = read_u32(tag, 20)? as usize;
+            let offset = read_u32(tag, 24)? as usize;
+            let units: Vec<u16> = tag
+                .get(offset..offset + len)?
+                .chunks_exact(2)
+                .map(|c| u16::from_be_bytes([c[0], c[1]]))
+                .collect();
+            String::from_utf16_lossy(&units)
+        }
+        _ => return None,
+    };
+    let text = text.trim_matches(char::from(0)).trim().to_string();
+    (!text.is_empty()).then_some(text)
+}
+
+fn xyz_tag(icc: &[u8], signature: &[u8; 4]) -> Option<[f64; 3]> {
+    let tag = find_tag(icc, signature)?;
+    if tag.get(0..4)? != b"XYZ " {
+        return None;
+    }
+    let fixed = |offset| read_u32(tag, offset).map(|v| v as i32 as f64 / 65536.0);
+    Some([fixed(8)?, fixed(12)?, fixed(16)?])
+}
+
+fn triangle_area(primaries: &[[f64; 3]; 3]) -> f64 {
+    let xy: Vec<(f64, f64)> = primaries
+        .iter()
+        .map(|[x, y, z]| {
+            let sum = (x + y + z).max(f64::EPSILON);
+            (x / sum, y / sum)
+        })
+        .collect();
+    ((xy[1].0 - xy[0].0) * (xy[2].1 - xy[0].1) - (xy[2].0 - xy[0].0) * (xy[1].1 - xy[0].1)).abs()
+        / 2.0
+}
+
+fn gamut_ratio(icc: &[u8]) -> Option<f64> {
+    let primaries = [
+        xyz_tag(icc, b"rXYZ")?,
+        xyz_tag(icc, b"gXYZ")?,
+        xyz_tag(icc, b"bXYZ")?,
+    ];
+    let ratio = triangle_area(&primaries) / triangle_area(&SRGB_PRIMARIES);
+    Some((ratio * 100.0).round() / 100.0)
+}
+
+// 按内容哈希缓存，同一个配置文件只写一次
+fn cache_icc(app: &tauri::AppHandle, icc: &[u8]) -> Result<PathBuf, String> {
+    let hash: String = Sha256::digest(icc)
+        .iter()
+        .take(8)
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    let dir = crate::app_data_base(app).join("display_profiles");
+    let path = dir.join(format!("{}.icc", hash));
+    if !path.exists() {
+        fs::create_dir_all(&dir).map_err(|e| format!("create profile dir failed: {}", e))?;
+        fs::write(&path, icc).map_err(|e| format!("write profile failed: {}", e))?;
+    }
+    Ok(path)
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use std::sync::mpsc;
+
+    use objc2::msg_send;
+    use objc2::rc::Retained;
+    use objc2::runtime::AnyObject;
+
+    // NSWindow.screen.colorSpace.ICCProfileData，AppKit 调用必须在主线程；调用方不能在主线程等待
+    pub fn icc_profile(window: &tauri::WebviewWindow) -> Result<Option<Vec<u8>>, String> {
+        let ns_window = window
+            .ns_window()
+            .map_err(|e| format!("get native window failed: {}", e))?
+            as usize;
+        let (tx, rx) = mpsc::channel::<Option<Vec<u8>>>();
+        window
+            .run_on_main_thread(move || {
+                let icc = unsafe {
+                    let window = ns_window as *mut AnyObject;
+                    let screen: Option<Retained<AnyObject>> = msg_send![window, screen];
+                    screen.and_then(|screen| {
+                        let space: Option<Retained<AnyObject>> = msg_send![&screen, colorSpace];
+                        let data: Option<Retained<AnyObject>> = msg_send![&space?, ICCProfileData];
+                        let data = data?;
+                        let len: usize = msg_send![&data, length];
+                        let bytes: *const u8 = msg_send![&data, bytes];
+                        (!bytes.is_null() && len > 0)
+                            .then(|| std::slice::from_raw_parts(bytes, len).to_vec())
+                    })
+                };
+                let _ = tx.send(icc);
+            })
+            .map_err(|e| format!("run_on_main_thread failed: {}", e))?;
+        rx.recv()
+            .map_err(|_| "display profile task aborted".to_string())
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use windows_sys::Win32::Graphics::Gdi::{
+        CreateDCW, DeleteDC, GetMonitorInfoW, MonitorFromWindow, MONITORINFO, MONITORINFOEXW,
+        MONITOR_DEFAULTTONEAREST,
+    };
+    use windows_sys::Win32::UI::ColorSystem::GetICMProfileW;
+
+    // 窗口所在显示器的设备上下文关联的 ICC 文件（显示设置里为该显示器指定的配置文件）
+    pub fn icc_profile(window: &tauri::WebviewWindow) -> Result<Option<Vec<u8>>, String> {
+        let hwnd = window
+            .hwnd()
+            .map_err(|e| format!("get native window failed: {}", e))?;
+        let path = unsafe {
+            let monitor = MonitorFromWindow(hwnd.0 as _, MONITOR_DEFAULTTONEAREST);
+            let mut info: MONITORINFOEXW = std::mem::zeroed();
+            info.monitorInfo.cbSize = std::mem::size_of::<MONITORINFOEXW>() as u32;
+            if GetMonitorInfoW(
+                monitor,
+                &mut info as *mut MONITORINFOEXW as *mut MONITORINFO,
+            ) == 0
+            {
+                return Err("get monitor info failed".to_string());
+            }
+            let hdc = CreateDCW(
+                std::ptr::null(),
+                info.szDevice.as_ptr(),
+                std::ptr::null(),
+                std::ptr::null(),
+            );
+            if hdc.is_null() {
+                return Err("create monitor dc failed".to_string());
+            }
+            let mut buf = [0u16; 260];
+            let mut len = buf.len() as u32;
+            let ok = GetICMProfileW(hdc, &mut len, buf.as_mut_ptr()) != 0;
+            DeleteDC(hdc);
+            if !ok {
+                return Ok(None);
+            }
+            let end = buf.iter().position(|c| *c == 0).unwrap_or(buf.len());
+            String::from_utf16_lossy(&buf[..end])
+        };
+        std::fs::read(&path)
+            .map(Some)
+            .map_err(|e| format!("read display profile failed: {} ({})", e, path))
+    }
+}
+
+// Linux 各桌面环境获取方式不一（colord、_ICC_PROFILE 属性），暂按 sRGB 处理
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+mod platform {
+    pub fn icc_profile(_window: &tauri::WebviewWindow) -> Result<Option<Vec<u8>>, String> {
+        Ok(None)
+    }
+}
+
+fn detect(app: &tauri::AppHandle) -> Result<DisplayProfile, String> {
+    let window = app
+        .get_webview_window("main")
+        .ok_or_else(|| "main window not found".to_string())?;
+    let monitor = window
+        .current_monitor()
+        .ok()
+        .flatten()
+        .and_then(|m| m.name().cloned());
+    *LAST_MONITOR.lock().unwrap() = monitor.clone();
+    let Some(icc) = platform::icc_profile(&window)? else {
+        return Ok(DisplayProfile {
+            monitor,
+            description: None,
+            icc_path: None,
+            gamut_ratio: None,
+            wide_gamut: false,
+        });
+    };
+    let ratio = gamut_ratio(&icc);
+    Ok(DisplayProfile {
+        monitor,
+        description: description(&icc),
+        icc_path: Some(cache_icc(app, &icc)?.to_string_lossy().to_string()),
+        gamut_ratio: ratio,
+        wide_gamut: ratio.is_some_and(|r| r >= WIDE_GAMUT_RATIO),
+    })
+}
+
+// 主窗口移动或缩放比例变化时调用（在主线程上）；换到另一台显示器才重新检测并发出 display-profile-changed
+pub fn on_window_event(app: &tauri::AppHandle, label: &str, event: &tauri::WindowEvent) {
+    if label != "main"
+        || !matches!(
+            event,
+            tauri::WindowEvent::Moved(_) | tauri::WindowEvent::ScaleFactorChanged { .. }
+        )
+    {
+        return;
+    }
+    let Some(window) = app.get_webview_window("main") else {
+        return;
+    };
+    let monitor = window
+        .current_monitor()
+        .ok()
+        .flatten()
+        .and_then(|m| m.name().cloned());
+    if *LAST_MONITOR.lock().unwrap() == monitor {
+        return;
+    }
+    let app = app.clone();
+    // macOS 上检测需要回到主线程执行，这里不能阻塞事件循环
+    std::thread::spawn(move || match detect(&app) {
+        Ok(profile) => {
+            app.state::<LogState>().log_app(
+                "INFO",
+                &format!(
+                    "Display changed monitor={} profile={}",
+                    profile.monitor.as_deref().unwrap_or("unknown"),
+                    profile.description.as_deref().unwrap_or("none")
+                ),
+            );
+            let _ = app.emit("display-profile-changed", profile);
+        }
+        Err(err) => app
+            .state::<LogState>()
+            .log_app("WARN", &format!("detect display profile failed: {}", err)),
+    });
+}
+
+// 返回主窗口当前所在显示器的 ICC 配置文件信息，前端据此决定是否做色彩管理预览
+#[tauri::command]
+pub async fn get_display_profile(app: tauri::AppHandle) -> Result<DisplayProfile, String> {
+    tauri::async_runtime::spawn_blocking(move || detect(&app))
+        .await
+        .map_err(|e| format!("display profile task failed: {}", e))?
+}
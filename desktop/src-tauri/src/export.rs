@@ -0,0 +1,503 @@
+use std::fs;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use image::imageops::FilterType;
+use image::{DynamicImage, GenericImageView};
+
+use crate::LogState;
+use tauri::Manager;
+
+const MM_PER_INCH: f64 = 25.4;
+const DEFAULT_PRINT_DPI: u32 = 300;
+// 防御：避免误填尺寸导致一次性申请几十 GB 内存
+const MAX_EXPORT_PIXELS: u64 = 400_000_000;
+
+#[derive(Default, serde::Deserialize)]
+#[serde(default)]
+pub struct ExportScaleOptions {
+    // 精确像素尺寸，只给一边时按原图比例推算另一边
+    width: Option<u32>,
+    height: Option<u32>,
+    // 打印尺寸：纸张名或毫米宽高，与 dpi 一起换算像素
+    paper: Option<String>,
+    print_width_mm: Option<f64>,
+    print_height_mm: Option<f64>,
+    dpi: Option<u32>,
+    // contain（默认，完整放入目标框）/ cover（裁切铺满）/ stretch（拉伸）
+    fit: Option<String>,
+    quality: Option<u8>,
+    // web：去掉 DPI 等元数据并做体积优化，报告节省的字节数
+    profile: Option<String>,
+    // web 档下 PNG 是否允许调色板量化（默认允许）
+    lossy: Option<bool>,
+    // 缩放后依次应用的后期滤镜（颗粒 / 锐化 / 暗角）
+    filters: Option<Vec<crate::filters::Filter>>,
+}
+
+#[derive(serde::Serialize)]
+pub struct ExportScaleResult {
+    dest_path: String,
+    width: u32,
+    height: u32,
+    dpi: Option<u32>,
+    size: u64,
+    optimization: Option<crate::optimize::WebOptimization>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum OutputFormat {
+    Png,
+    Jpeg,
+    Webp,
+}
+
+impl OutputFormat {
+    pub fn from_path(path: &Path) -> Result<Self, String> {
+        let ext = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_lowercase())
+            .unwrap_or_default();
+        match ext.as_str() {
+            "png" => Ok(Self::Png),
+            "jpg" | "jpeg" => Ok(Self::Jpeg),
+            "webp" => Ok(Self::Webp),
+            _ => Err(format!("unsupported export format: {}", ext)),
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Png => "png",
+            Self::Jpeg => "jpg",
+            Self::Webp => "webp",
+        }
+    }
+}
+
+fn paper_size_mm(name: &str) -> Option<(f64, f64)> {
+    match name.trim().to_lowercase().as_str() {
+        "a3" => Some((297.0, 420.0)),
+        "a4" => Some((210.0, 297.0)),
+        "a5" => Some((148.0, 210.0)),
+        "a6" => Some((105.0, 148.0)),
+        "letter" => Some((215.9, 279.4)),
+        "legal" => Some((215.9, 355.6)),
+        _ => None,
+    }
+}
+
+fn mm_to_px(mm: f64, dpi: u32) -> u32 {
+    (mm / MM_PER_INCH * dpi as f64).round().max(1.0) as u32
+}
+
+// 计算目标框尺寸；返回 None 表示保持原像素只写 DPI
+fn target_box(options: &ExportScaleOptions, src: (u32, u32)) -> Result<Option<(u32, u32)>, String> {
+    let (src_w, src_h) = src;
+    let ratio = src_w as f64 / src_h as f64;
+
+    if options.width.is_some() || options.height.is_some() {
+        return Ok(Some(match (options.width, options.height) {
+            (Some(w), Some(h)) => (w, h),
+            (Some(w), None) => (w, (w as f64 / ratio).round().max(1.0) as u32),
+            (None, Some(h)) => ((h as f64 * ratio).round().max(1.0) as u32, h),
+            (None, None) => unreachable!(),
+        }));
+    }
+
+    let dpi = options.dpi.unwrap_or(DEFAULT_PRINT_DPI);
+    let print_mm = if let Some(paper) = options.paper.as_deref() {
+        let (short, long) =
+            paper_size_mm(paper).ok_or_else(|| format!("unknown paper size: {}", paper))?;
+        // 纸张方向跟随原图方向，横图自动按横向纸张计算
+        Some(if src_w >= src_h {
+            (long, short)
+        } else {
+            (short, long)
+        })
+    } else {
+        match (options.print_width_mm, options.print_height_mm) {
+            (Some(w), Some(h)) => Some((w, h)),
+            (Some(w), None) => Some((w, w / ratio)),
+            (None, Some(h)) => Some((h * ratio, h)),
+            (None, None) => None,
+        }
+    };
+
+    Ok(print_mm.map(|(w_mm, h_mm)| (mm_to_px(w_mm, dpi), mm_to_px(h_mm, dpi))))
+}
+
+fn resample(img: DynamicImage, target: (u32, u32), fit: &str) -> Result<DynamicImage, String> {
+    let (w, h) = target;
+    if w == 0 || h == 0 {
+        return Err("target size must be positive".to_string());
+    }
+    if w as u64 * h as u64 > MAX_EXPORT_PIXELS {
+        return Err(format!("target size too large: {}x{}", w, h));
+    }
+    if img.dimensions() == (w, h) {
+        return Ok(img);
+    }
+    // Lanczos3 在放大与缩小两个方向上的锐度/振铃折中最好，适合打印交付
+    Ok(match fit {
+        "cover" => img.resize_to_fill(w, h, FilterType::Lanczos3),
+        "stretch" => img.resize_exact(w, h, FilterType::Lanczos3),
+        _ => img.resize(w, h, FilterType::Lanczos3),
+    })
+}
+
+// 先写 .part 临时文件再改名，中途失败不会留下半截文件
+fn write_atomic(
+    dest: &Path,
+    write: impl FnOnce(BufWriter<fs::File>) -> Result<(), String>,
+) -> Result<(), String> {
+    if let Some(parent) = dest.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("create destination dir failed: {}", e))?;
+    }
+    let file_name = dest
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("export");
+    let temp_path = dest.with_file_name(format!("{}.part", file_name));
+    let file =
+        fs::File::create(&temp_path).map_err(|e| format!("create temp file failed: {}", e))?;
+
+    if let Err(err) = write(BufWriter::new(file)) {
+        let _ = fs::remove_file(&temp_path);
+        return Err(err);
+    }
+
+    fs::rename(&temp_path, dest).map_err(|e| {
+        let _ = fs::remove_file(&temp_path);
+        format!("finalize export failed: {}", e)
+    })
+}
+
+// 编码并写入文件，带上 DPI 元数据（PNG pHYs / JPEG JFIF density）
+pub fn write_image(
+    img: &DynamicImage,
+    dest: &Path,
+    format: OutputFormat,
+    quality: u8,
+    dpi: Option<u32>,
+) -> Result<(), String> {
+    write_atomic(dest, |writer| match format {
+        OutputFormat::Png => write_png(img, writer, dpi),
+        OutputFormat::Jpeg => write_jpeg(img, writer, quality, dpi),
+        OutputFormat::Webp => write_webp(img, writer),
+    })
+}
+
+pub fn write_png<W: std::io::Write>(
+    img: &DynamicImage,
+    writer: W,
+    dpi: Option<u32>,
+) -> Result<(), String> {
+    let (color, bytes) = if img.color().has_alpha() {
+        (png::ColorType::Rgba, img.to_rgba8().into_raw())
+    } else {
+        (png::ColorType::Rgb, img.to_rgb8().into_raw())
+    };
+    let mut encoder = png::Encoder::new(writer, img.width(), img.height());
+    encoder.set_color(color);
+    encoder.set_depth(png::BitDepth::Eight);
+    if let Some(dpi) = dpi {
+        // pHYs 只支持“每米像素数”
+        let ppm = (dpi as f64 / 0.0254).round() as u32;
+        encoder.set_pixel_dims(Some(png::PixelDimensions {
+            xppu: ppm,
+            yppu: ppm,
+            unit: png::Unit::Meter,
+        }));
+    }
+    let mut png_writer = encoder
+        .write_header()
+        .map_err(|e| format!("encode png failed: {}", e))?;
+    png_writer
+        .write_image_data(&bytes)
+        .map_err(|e| format!("encode png failed: {}", e))?;
+    png_writer
+        .finish()
+        .map_err(|e| format!("encode png failed: {}", e))
+}
+
+pub fn write_jpeg<W: std::io::Write>(
+    img: &DynamicImage,
+    writer: W,
+    quality: u8,
+    dpi: Option<u32>,
+) -> Result<(), String> {
+    use image::codecs::jpeg::{JpegEncoder, PixelDensity};
+
+    let mut encoder = JpegEncoder::new_with_quality(writer, quality.clamp(1, 100));
+    if let Some(dpi) = dpi {
+        encoder.set_pixel_density(PixelDensity::dpi(dpi.min(u16::MAX as u32) as u16));
+    }
+    // JPEG 不支持透明通道，统一转 RGB
+    encoder
+        .encode_image(&DynamicImage::ImageRgb8(img.to_rgb8()))
+        .map_err(|e| format!("encode jpeg failed: {}", e))
+}
+
+// image 自带的 WebP 编码器只有无损模式，quality 不生效，也不写 DPI
+pub fn write_webp<W: std::io::Write>(img: &DynamicImage, writer: W) -> Result<(), String> {
+    use image::codecs::webp::WebPEncoder;
+    use image::ExtendedColorType;
+
+    let encoder = WebPEncoder::new_lossless(writer);
+    let result = if img.color().has_alpha() {
+        encoder.encode(
+            img.to_rgba8().as_raw(),
+            img.width(),
+            img.height(),
+            ExtendedColorType::Rgba8,
+        )
+    } else {
+        encoder.encode(
+            img.to_rgb8().as_raw(),
+            img.width(),
+            img.height(),
+            ExtendedColorType::Rgb8,
+        )
+    };
+    result.map_err(|e| format!("encode webp failed: {}", e))
+}
+
+pub fn load_image(path: &Path) -> Result<DynamicImage, String> {
+    let bytes =
+        fs::read(path).map_err(|e| format!("read file failed: {} ({})", e, path.display()))?;
+    image::load_from_memory(&bytes).map_err(|e| format!("decode image failed: {}", e))
+}
+
+// 按精确像素或 DPI/纸张尺寸重采样导出，用于打印交付；profile=web 时改为网页用图的体积优化
+#[tauri::command]
+pub async fn export_image_scaled(
+    app: tauri::AppHandle,
+    path: String,
+    dest_path: String,
+    options: ExportScaleOptions,
+) -> Result<ExportScaleResult, String> {
+    let src_path = crate::resolve_local_path(&app, &path)?;
+    let dest = PathBuf::from(dest_path.trim());
+    if dest.as_os_str().is_empty() {
+        return Err("dest_path is empty".to_string());
+    }
+    let format = OutputFormat::from_path(&dest)?;
+    let log_state = app.state::<LogState>().inner().clone();
+
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        let img = load_image(&src_path)?;
+        let fit = options.fit.as_deref().unwrap_or("contain");
+        let img = match target_box(&options, img.dimensions())? {
+            Some(target) => resample(img, target, fit)?,
+            None => img,
+        };
+        let img = crate::filters::apply(img, options.filters.as_deref().unwrap_or_default());
+        let uses_print_size = options.paper.is_some()
+            || options.print_width_mm.is_some()
+            || options.print_height_mm.is_some();
+        let dpi = options.dpi.or(uses_print_size.then_some(DEFAULT_PRINT_DPI));
+        // 按未压缩 RGBA 估算上限，PNG/JPEG 实际体积只会更小
+        crate::disk::ensure_free_space(&dest, img.width() as u64 * img.height() as u64 * 4)?;
+        let (dpi, optimization) = if options.profile.as_deref() == Some("web") {
+            // 网页用图不写 DPI；JPEG 默认质量略低，肉眼差别不大但体积明显更小
+            let quality = options.quality.unwrap_or(82);
+            let (bytes, report) =
+                crate::optimize::encode_web(&img, format, quality, options.lossy.unwrap_or(true))?;
+            write_atomic(&dest, |mut writer| {
+                writer
+                    .write_all(&bytes)
+                    .and_then(|_| writer.flush())
+                    .map_err(|e| format!("write export failed: {}", e))
+            })?;
+            (None, Some(report))
+        } else {
+            write_image(&img, &dest, format, options.quality.unwrap_or(92), dpi)?;
+            (dpi, None)
+        };
+        let size = fs::metadata(&dest).map(|m| m.len()).unwrap_or(0);
+        Ok::<_, String>(ExportScaleResult {
+            dest_path: dest.to_string_lossy().to_string(),
+            width: img.width(),
+            height: img.height(),
+            dpi,
+            size,
+            optimization,
+        })
+    })
+    .await
+    .map_err(|e| format!("export task failed: {}", e))??;
+
+    log_state.log_app(
+        "INFO",
+        &format!(
+            "Scaled export finished size={}x{} dpi={:?} bytes={} saved={} dest={}",
+            result.width,
+            result.height,
+            result.dpi,
+            result.size,
+            result.optimization.as_ref().map_or(0, |o| o.saved_bytes),
+            result.dest_path
+        ),
+    );
+    crate::feedback::notify(&app, "export_done");
+    crate::hooks::fire(
+        &app,
+        "export_finished",
+        serde_json::json!({
+            "path": result.dest_path,
+            "source_path": path,
+            "width": result.width,
+            "height": result.height,
+            "size": result.size,
+        }),
+    );
+    Ok(result)
+}
+
+#[derive(Default, serde::Deserialize)]
+#[serde(default)]
+pub struct SaveAsOptions {
+    // 对话框里预填的文件名（不含扩展名）
+    file_name: Option<String>,
+    // png / jpeg / webp，默认沿用原图格式；用户在对话框里改了扩展名时以扩展名为准
+    format: Option<String>,
+    // 仅 JPEG 生效
+    quality: Option<u8>,
+}
+
+#[derive(serde::Serialize)]
+pub struct SaveAsResult {
+    dest_path: String,
+    format: &'static str,
+    width: u32,
+    height: u32,
+    size: u64,
+}
+
+fn parse_format(name: &str) -> Result<OutputFormat, String> {
+    OutputFormat::from_path(Path::new(&format!("x.{}", name.trim().to_lowercase())))
+}
+
+// 打包后的 WebView 里浏览器下载流程不可用：用系统“另存为”对话框选位置，按选择的格式转换后写入。
+// 用户取消时返回 None
+#[tauri::command]
+pub async fn save_image_as(
+    app: tauri::AppHandle,
+    path: String,
+    options: Option<SaveAsOptions>,
+) -> Result<Option<SaveAsResult>, String> {
+    use tauri_plugin_dialog::DialogExt;
+
+    let options = options.unwrap_or_default();
+    let src_path = crate::resolve_local_path(&app, &path)?;
+    let source_format = OutputFormat::from_path(&src_path).ok();
+    let preferred = match options.format.as_deref() {
+        Some(name) => parse_format(name)?,
+        None => source_format.unwrap_or(OutputFormat::Png),
+    };
+    let stem = options
+        .file_name
+        .as_deref()
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(str::to_string)
+        .or_else(|| {
+            src_path
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .map(str::to_string)
+        })
+        .unwrap_or_else(|| "image".to_string());
+
+    // 选中的格式排在第一个，作为对话框的默认筛选项
+    let mut formats = vec![OutputFormat::Png, OutputFormat::Jpeg, OutputFormat::Webp];
+    formats.sort_by_key(|f| *f != preferred);
+    let mut dialog =
+        app.dialog()
+            .file()
+            .set_file_name(format!("{}.{}", stem, preferred.extension()));
+    if let Ok(dir) = app.path().download_dir() {
+        dialog = dialog.set_directory(dir);
+    }
+    for format in formats {
+        dialog = match format {
+            OutputFormat::Png => dialog.add_filter("PNG", &["png"]),
+            OutputFormat::Jpeg => dialog.add_filter("JPEG", &["jpg", "jpeg"]),
+            OutputFormat::Webp => dialog.add_filter("WebP", &["webp"]),
+        };
+    }
+    // 对话框会阻塞直到用户选择，不能占住主线程
+    let picked = tauri::async_runtime::spawn_blocking(move || dialog.blocking_save_file())
+        .await
+        .map_err(|e| format!("save dialog failed: {}", e))?;
+    let Some(picked) = picked else {
+        return Ok(None);
+    };
+    let mut dest = picked
+        .into_path()
+        .map_err(|e| format!("invalid save path: {}", e))?;
+    // 部分平台的对话框不会自动补扩展名
+    let format = match OutputFormat::from_path(&dest) {
+        Ok(format) => format,
+        Err(_) => {
+            let name = dest
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_else(|| stem.clone());
+            dest.set_file_name(format!("{}.{}", name, preferred.extension()));
+            preferred
+        }
+    };
+    let quality = options.quality;
+    let log_state = app.state::<LogState>().inner().clone();
+
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        let img = load_image(&src_path)?;
+        crate::disk::ensure_free_space(&dest, img.width() as u64 * img.height() as u64 * 4)?;
+        // 格式相同且不要求调整质量时直接复制原文件，保留原始编码和元数据
+        if source_format == Some(format) && quality.is_none() && src_path != dest {
+            write_atomic(&dest, |mut writer| {
+                let mut src = fs::File::open(&src_path)
+                    .map_err(|e| format!("read file failed: {} ({})", e, src_path.display()))?;
+                std::io::copy(&mut src, &mut writer)
+                    .and_then(|_| writer.flush())
+                    .map_err(|e| format!("write export failed: {}", e))
+            })?;
+        } else {
+            write_image(&img, &dest, format, quality.unwrap_or(92), None)?;
+        }
+        let size = fs::metadata(&dest).map(|m| m.len()).unwrap_or(0);
+        Ok::<_, String>(SaveAsResult {
+            dest_path: dest.to_string_lossy().to_string(),
+            format: format.extension(),
+            width: img.width(),
+            height: img.height(),
+            size,
+        })
+    })
+    .await
+    .map_err(|e| format!("export task failed: {}", e))??;
+
+    log_state.log_app(
+        "INFO",
+        &format!(
+            "Save as finished format={} size={}x{} bytes={} dest={}",
+            result.format, result.width, result.height, result.size, result.dest_path
+        ),
+    );
+    crate::feedback::notify(&app, "export_done");
+    crate::hooks::fire(
+        &app,
+        "export_finished",
+        serde_json::json!({
+            "path": result.dest_path,
+            "source_path": path,
+            "width": result.width,
+            "height": result.height,
+            "size": result.size,
+        }),
+    );
+    Ok(Some(result))
+}
@@ -0,0 +1,177 @@
+use std::sync::Mutex;
+use std::time::Duration;
+
+use tauri::Manager;
+
+use crate::proxy::ProxyRequest;
+use crate::LogState;
+
+const MAX_LATENCY_MS: u64 = 120_000;
+
+// 注入配置只保存在内存里，重启应用即恢复正常，避免忘记关掉
+static FAULTS: Mutex<Option<FaultState>> = Mutex::new(None);
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct FaultConfig {
+    // 每个请求转发前额外等待的时间，再加上 0..jitter_ms 的随机抖动
+    pub latency_ms: u64,
+    pub jitter_ms: u64,
+    // 直接断开连接（不返回任何响应）的概率，0~1
+    pub drop_rate: f64,
+    // 返回合成错误响应的概率，0~1
+    pub error_rate: f64,
+    pub error_status: u16,
+    // 只对以此开头的路径生效，为空时对所有后端请求生效
+    pub path_prefix: String,
+    // 只对接下来的 N 个匹配请求生效，用完自动关闭；None 表示一直生效
+    pub remaining: Option<u32>,
+}
+
+impl Default for FaultConfig {
+    fn default() -> Self {
+        Self {
+            latency_ms: 0,
+            jitter_ms: 0,
+            drop_rate: 0.0,
+            error_rate: 0.0,
+            error_status: 502,
+            path_prefix: String::new(),
+            remaining: None,
+        }
+    }
+}
+
+#[derive(Clone, serde::Serialize)]
+pub struct FaultStatus {
+    config: FaultConfig,
+    // 已注入的次数
+    delayed: u64,
+    dropped: u64,
+    errored: u64,
+}
+
+struct FaultState {
+    config: FaultConfig,
+    delayed: u64,
+    dropped: u64,
+    errored: u64,
+}
+
+pub enum Fault {
+    Drop,
+    Error(u16),
+}
+
+// 0~1 的均匀随机数
+fn random() -> f64 {
+    let mut bytes = [0u8; 8];
+    let _ = getrandom::fill(&mut bytes);
+    (u64::from_le_bytes(bytes) >> 11) as f64 / (1u64 << 53) as f64
+}
+
+// 代理转发前调用：按配置等待，返回 Some 时由代理断开连接或返回错误，不再转发给后端
+pub fn inject(request: &ProxyRequest) -> Option<Fault> {
+    let (delay, fault) = {
+        let mut guard = FAULTS.lock().unwrap();
+        let state = guard.as_mut()?;
+        if !request.path.starts_with(&state.config.path_prefix) {
+            return None;
+        }
+        if let Some(remaining) = state.config.remaining.as_mut() {
+            *remaining = remaining.saturating_sub(1);
+            if *remaining == 0 {
+                *guard = None;
+                return None;
+            }
+        }
+        let config = &state.config;
+        let jitter = if config.jitter_ms > 0 {
+            (random() * config.jitter_ms as f64) as u64
+        } else {
+            0
+        };
+        let delay = Duration::from_millis(config.latency_ms + jitter);
+        let roll = random();
+        let fault = if roll < config.drop_rate {
+            Some(Fault::Drop)
+        } else if roll < config.drop_rate + config.error_rate {
+            Some(Fault::Error(config.error_status))
+        } else {
+            None
+        };
+        if !delay.is_zero() {
+            state.delayed += 1;
+        }
+        match fault {
+            Some(Fault::Drop) => state.dropped += 1,
+            Some(Fault::Error(_)) => state.errored += 1,
+            None => {}
+        }
+        (delay, fault)
+    };
+    if !delay.is_zero() {
+        std::thread::sleep(delay);
+    }
+    fault
+}
+
+fn status() -> Option<FaultStatus> {
+    FAULTS.lock().unwrap().as_ref().map(|state| FaultStatus {
+        config: state.config.clone(),
+        delayed: state.delayed,
+        dropped: state.dropped,
+        errored: state.errored,
+    })
+}
+
+// 隐藏的 QA 开关：给代理注入延迟、断连或合成错误，用来稳定复现前端的重试和报错界面；
+// 与调试器同一个门槛（开发构建、--debug 启动或打开了隐藏的调试开关），config 为空时关闭
+#[tauri::command]
+pub fn set_fault_injection(
+    app: tauri::AppHandle,
+    config: Option<FaultConfig>,
+) -> Result<Option<FaultStatus>, String> {
+    if !crate::webview::devtools_allowed(&app) {
+        return Err("fault injection disabled".to_string());
+    }
+    let Some(mut config) = config else {
+        *FAULTS.lock().unwrap() = None;
+        app.state::<LogState>()
+            .log_app("INFO", "Proxy fault injection disabled");
+        return Ok(None);
+    };
+    if !(400..=599).contains(&config.error_status) {
+        return Err(format!("invalid error status: {}", config.error_status));
+    }
+    config.latency_ms = config.latency_ms.min(MAX_LATENCY_MS);
+    config.jitter_ms = config.jitter_ms.min(MAX_LATENCY_MS);
+    config.drop_rate = config.drop_rate.clamp(0.0, 1.0);
+    config.error_rate = config.error_rate.clamp(0.0, 1.0 - config.drop_rate);
+    // remaining 在每次匹配时先减一，为 0 时关闭，这里多算一次
+    config.remaining = config.remaining.map(|n| n.saturating_add(1));
+    app.state::<LogState>().log_app(
+        "WARN",
+        &format!(
+            "Proxy fault injection enabled latency={}ms jitter={}ms drop={} error={}({}) prefix={}",
+            config.latency_ms,
+            config.jitter_ms,
+            config.drop_rate,
+            config.error_rate,
+            config.error_status,
+            config.path_prefix
+        ),
+    );
+    *FAULTS.lock().unwrap() = Some(FaultState {
+        config,
+        delayed: 0,
+        dropped: 0,
+        errored: 0,
+    });
+    Ok(status())
+}
+
+#[tauri::command]
+pub fn get_fault_injection() -> Option<FaultStatus> {
+    status()
+}
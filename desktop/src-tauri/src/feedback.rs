@@ -0,0 +1,117 @@
+use tauri::{Manager, State};
+
+use crate::settings::SettingsState;
+use crate::LogState;
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct FeedbackSettings {
+    // Force Touch 触控板的触感反馈，仅 macOS 有效
+    pub haptics: bool,
+    // 拖放接受 / 拒绝、导出完成时的轻提示音
+    pub sounds: bool,
+}
+
+impl Default for FeedbackSettings {
+    fn default() -> Self {
+        Self {
+            haptics: true,
+            sounds: true,
+        }
+    }
+}
+
+// 交互事件对应的提示音和触感；触感只在用户手指正放在触控板上时才能感知
+fn event_feedback(event: &str) -> Result<(&'static str, Option<&'static str>), String> {
+    match event {
+        "drop_accept" => Ok(("pop", Some("alignment"))),
+        "drop_reject" => Ok(("funk", Some("generic"))),
+        "export_done" => Ok(("tink", None)),
+        _ => Err(format!("unknown feedback event: {}", event)),
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn perform_haptic(app: &tauri::AppHandle, kind: &str) -> Result<bool, String> {
+    use objc2::runtime::AnyObject;
+    use objc2::{class, msg_send};
+
+    // NSHapticFeedbackPattern
+    let pattern: isize = match kind {
+        "generic" => 0,
+        "alignment" => 1,
+        "level_change" => 2,
+        _ => return Err(format!("unknown haptic kind: {}", kind)),
+    };
+    // NSHapticFeedbackPerformanceTimeNow
+    const PERFORM_NOW: usize = 1;
+    app.run_on_main_thread(move || unsafe {
+        let performer: *mut AnyObject =
+            msg_send![class!(NSHapticFeedbackManager), defaultPerformer];
+        if !performer.is_null() {
+            let _: () =
+                msg_send![performer, performFeedbackPattern: pattern, performanceTime: PERFORM_NOW];
+        }
+    })
+    .map_err(|e| format!("run_on_main_thread failed: {}", e))?;
+    Ok(true)
+}
+
+// 其他平台没有可用的触感 API，返回 false 由前端忽略
+#[cfg(not(target_os = "macos"))]
+fn perform_haptic(_app: &tauri::AppHandle, kind: &str) -> Result<bool, String> {
+    match kind {
+        "generic" | "alignment" | "level_change" => Ok(false),
+        _ => Err(format!("unknown haptic kind: {}", kind)),
+    }
+}
+
+// 供 Rust 侧的长任务（如导出）完成时直接调用，失败只写日志
+pub fn notify(app: &tauri::AppHandle, event: &str) {
+    if let Err(err) = trigger(app, event) {
+        app.state::<LogState>()
+            .log_app("WARN", &format!("feedback {} failed: {}", event, err));
+    }
+}
+
+fn trigger(app: &tauri::AppHandle, event: &str) -> Result<bool, String> {
+    let (sound, haptic) = event_feedback(event)?;
+    let settings = app.state::<SettingsState>().get().feedback;
+    let mut performed = false;
+    if settings.sounds {
+        crate::sound::play_builtin(app, sound)?;
+        performed = true;
+    }
+    if let Some(haptic) = haptic.filter(|_| settings.haptics) {
+        performed |= perform_haptic(app, haptic)?;
+    }
+    Ok(performed)
+}
+
+// kind: generic / alignment / level_change；返回 false 表示已关闭或当前平台不支持
+#[tauri::command]
+pub fn trigger_haptic(app: tauri::AppHandle, kind: String) -> Result<bool, String> {
+    if !app.state::<SettingsState>().get().feedback.haptics {
+        return Ok(false);
+    }
+    perform_haptic(&app, kind.trim())
+}
+
+// event: drop_accept / drop_reject / export_done，按设置播放提示音并触发触感
+#[tauri::command]
+pub fn trigger_feedback(app: tauri::AppHandle, event: String) -> Result<bool, String> {
+    trigger(&app, event.trim())
+}
+
+#[tauri::command]
+pub fn get_feedback_settings(state: State<'_, SettingsState>) -> FeedbackSettings {
+    state.get().feedback
+}
+
+#[tauri::command]
+pub fn set_feedback_settings(
+    state: State<'_, SettingsState>,
+    settings: FeedbackSettings,
+) -> Result<FeedbackSettings, String> {
+    Ok(state.update(|s| s.feedback = settings)?.feedback)
+}
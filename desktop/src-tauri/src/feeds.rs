@@ -0,0 +1,348 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use tauri::{Emitter, Manager};
+
+use crate::LogState;
+
+// 串行化 reference_feeds.json 的读写
+static FEEDS_LOCK: Mutex<()> = Mutex::new(());
+
+const POLL_INTERVAL: Duration = Duration::from_secs(60);
+const MIN_INTERVAL_MINUTES: u64 = 15;
+const DEFAULT_INTERVAL_MINUTES: u64 = 60;
+// 单次抓取最多入库的新图片，第一次订阅一个大源时不会一下灌进几百张
+const MAX_NEW_PER_CHECK: usize = 20;
+// 每个源记住的已处理链接数量，超出后丢弃最早的
+const MAX_SEEN_PER_FEED: usize = 1000;
+const IMAGE_EXTENSIONS: [&str; 6] = [".jpg", ".jpeg", ".png", ".webp", ".gif", ".bmp"];
+
+// 定时抓取的参考图来源：RSS/Atom、JSON Feed 或任意包含图片链接的 JSON/HTML
+#[derive(Clone, Default, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct ReferenceFeed {
+    pub id: String,
+    pub url: String,
+    // 入库时附加到每张图上的标签
+    pub tags: Vec<String>,
+    pub interval_minutes: u64,
+    pub enabled: bool,
+    pub last_checked: u128,
+    pub last_error: String,
+    pub total_added: usize,
+}
+
+#[derive(Default, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+struct FeedStore {
+    feeds: Vec<ReferenceFeed>,
+    // 已处理过的图片链接，按源 id 分开记录；下载失败的链接下次还会重试
+    seen: BTreeMap<String, Vec<String>>,
+}
+
+#[derive(Clone, serde::Serialize)]
+pub struct FeedCheckResult {
+    id: String,
+    found: usize,
+    added: usize,
+    failed: usize,
+}
+
+fn store_path(app: &tauri::AppHandle) -> PathBuf {
+    crate::app_data_base(app).join("reference_feeds.json")
+}
+
+fn load(app: &tauri::AppHandle) -> FeedStore {
+    crate::settings::read_json_or_default(&store_path(app))
+}
+
+fn save(app: &tauri::AppHandle, store: &FeedStore) -> Result<(), String> {
+    crate::settings::write_json_atomic(&store_path(app), store)
+}
+
+fn new_id() -> String {
+    let mut bytes = [0u8; 4];
+    let _ = getrandom::fill(&mut bytes);
+    format!(
+        "{:x}{}",
+        crate::now_ms(),
+        bytes
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect::<String>()
+    )
+}
+
+fn looks_like_image(url: &str) -> bool {
+    let path = url
+        .split(['?', '#'])
+        .next()
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    IMAGE_EXTENSIONS.iter().any(|ext| path.ends_with(ext))
+}
+
+fn push_url(urls: &mut Vec<String>, raw: &str) {
+    let url = raw.trim().replace("&amp;", "&");
+    if (url.starts_with("http://") || url.starts_with("https://")) && !urls.contains(&url) {
+        urls.push(url);
+    }
+}
+
+// JSON：image/banner_image/thumbnail 字段直接认，其余字符串按扩展名判断
+fn collect_json(value: &serde_json::Value, key: &str, urls: &mut Vec<String>) {
+    match value {
+        serde_json::Value::String(s)
+            if matches!(key, "image" | "banner_image" | "thumbnail") || looks_like_image(s) =>
+        {
+            push_url(urls, s);
+        }
+        serde_json::Value::Array(items) => {
+            for item in items {
+                collect_json(item, key, urls);
+            }
+        }
+        serde_json::Value::Object(map) => {
+            for (k, v) in map {
+                collect_json(v, k, urls);
+            }
+        }
+        _ => {}
+    }
+}
+
+// XML/HTML 不做完整解析：enclosure/media:content 的 url 属性直接认，src/href 按扩展名判断
+fn collect_markup(text: &str, urls: &mut Vec<String>) {
+    for attr in ["url=", "src=", "href="] {
+        let mut rest = text;
+        while let Some(pos) = rest.find(attr) {
+            rest = &rest[pos + attr.len()..];
+            let Some(quote) = rest.chars().next().filter(|c| *c == '"' || *c == '\'') else {
+                continue;
+            };
+            let Some(end) = rest[1..].find(quote) else {
+                break;
+            };
+            let value = &rest[1..1 + end];
+            if attr == "url=" || looks_like_image(value) {
+                push_url(urls, value);
+            }
+            rest = &rest[1 + end..];
+        }
+    }
+}
+
+fn extract_image_urls(body: &[u8]) -> Vec<String> {
+    let mut urls = Vec::new();
+    match serde_json::from_slice::<serde_json::Value>(body) {
+        Ok(json) => collect_json(&json, "", &mut urls),
+        Err(_) => collect_markup(&String::from_utf8_lossy(body), &mut urls),
+    }
+    urls
+}
+
+pub async fn check(app: &tauri::AppHandle, id: &str) -> Result<FeedCheckResult, String> {
+    let (feed, seen) = {
+        let _guard = FEEDS_LOCK.lock().unwrap();
+        let store = load(app);
+        let feed = store
+            .feeds
+            .iter()
+            .find(|f| f.id == id)
+            .cloned()
+            .ok_or_else(|| format!("reference feed not found: {}", id))?;
+        (feed, store.seen.get(id).cloned().unwrap_or_default())
+    };
+
+    let fetched = crate::references::download(app, "feed", &feed.url).await;
+    let mut result = FeedCheckResult {
+        id: feed.id.clone(),
+        found: 0,
+        added: 0,
+        failed: 0,
+    };
+    let mut processed = Vec::new();
+    let error = match fetched {
+        Ok((body, _)) => {
+            let urls: Vec<String> = extract_image_urls(&body)
+                .into_iter()
+                .filter(|u| !seen.contains(u))
+                .collect();
+            result.found = urls.len();
+            for url in urls.into_iter().take(MAX_NEW_PER_CHECK) {
+                let added = match crate::references::download(app, "feed", &url).await {
+                    Ok((bytes, name)) => {
+                        // 下载成功就算处理过，不是图片的链接下次不再重复下载
+                        processed.push(url.clone());
+                        let app = app.clone();
+                        let tags = feed.tags.clone();
+                        let source_url = url.clone();
+                        tauri::async_runtime::spawn_blocking(move || {
+                            crate::references::add_bytes(
+                                &app,
+                                &bytes,
+                                &name,
+                                "feed",
+                                &source_url,
+                                &tags,
+                            )
+                        })
+                        .await
+                        .map_err(|e| format!("add reference failed: {}", e))
+                        .and_then(|r| r)
+                    }
+                    Err(err) => Err(err),
+                };
+                match added {
+                    Ok(_) => result.added += 1,
+                    Err(err) => {
+                        result.failed += 1;
+                        app.state::<LogState>().log_app(
+                            "WARN",
+                            &format!("Feed image skipped feed={} url={}: {}", feed.id, url, err),
+                        );
+                    }
+                }
+            }
+            String::new()
+        }
+        Err(err) => err,
+    };
+
+    {
+        let _guard = FEEDS_LOCK.lock().unwrap();
+        let mut store = load(app);
+        // 抓取期间源被删掉了，就不再写回记录
+        let Some(stored) = store.feeds.iter_mut().find(|f| f.id == feed.id) else {
+            return Ok(result);
+        };
+        stored.last_checked = crate::now_ms();
+        stored.last_error = error.clone();
+        stored.total_added += result.added;
+        let seen = store.seen.entry(feed.id.clone()).or_default();
+        seen.extend(processed);
+        if seen.len() > MAX_SEEN_PER_FEED {
+            let excess = seen.len() - MAX_SEEN_PER_FEED;
+            seen.drain(..excess);
+        }
+        save(app, &store)?;
+    }
+
+    app.state::<LogState>().log_app(
+        if error.is_empty() { "INFO" } else { "WARN" },
+        &format!(
+            "Reference feed checked id={} found={} added={} failed={} error={}",
+            result.id, result.found, result.added, result.failed, error
+        ),
+    );
+    let _ = app.emit("reference-feed-checked", result.clone());
+    if !error.is_empty() {
+        return Err(error);
+    }
+    Ok(result)
+}
+
+fn due_feeds(app: &tauri::AppHandle) -> Vec<String> {
+    let now = crate::now_ms();
+    load(app)
+        .feeds
+        .into_iter()
+        .filter(|f| f.enabled)
+        .filter(|f| {
+            let interval = f.interval_minutes.max(MIN_INTERVAL_MINUTES) as u128 * 60_000;
+            now.saturating_sub(f.last_checked) >= interval
+        })
+        .map(|f| f.id)
+        .collect()
+}
+
+// 后台每分钟看一次哪些源到期，逐个抓取
+pub fn start(app: &tauri::AppHandle) {
+    let app = app.clone();
+    std::thread::spawn(move || loop {
+        std::thread::sleep(POLL_INTERVAL);
+        // 内存紧张时暂停后台抓取，到期的订阅源等下一轮
+        if crate::memory::under_pressure() {
+            continue;
+        }
+        for id in due_feeds(&app) {
+            let _ = tauri::async_runtime::block_on(check(&app, &id));
+        }
+    });
+}
+
+#[tauri::command]
+pub fn list_reference_feeds(app: tauri::AppHandle) -> Vec<ReferenceFeed> {
+    load(&app).feeds
+}
+
+#[tauri::command]
+pub fn add_reference_feed(
+    app: tauri::AppHandle,
+    url: String,
+    tags: Vec<String>,
+    interval_minutes: Option<u64>,
+) -> Result<ReferenceFeed, String> {
+    let url = url.trim().to_string();
+    if !url.starts_with("http://") && !url.starts_with("https://") {
+        return Err(format!("unsupported url: {}", url));
+    }
+    let _guard = FEEDS_LOCK.lock().unwrap();
+    let mut store = load(&app);
+    if store.feeds.iter().any(|f| f.url == url) {
+        return Err(format!("reference feed already exists: {}", url));
+    }
+    let feed = ReferenceFeed {
+        id: new_id(),
+        url,
+        tags,
+        interval_minutes: interval_minutes
+            .unwrap_or(DEFAULT_INTERVAL_MINUTES)
+            .max(MIN_INTERVAL_MINUTES),
+        enabled: true,
+        ..Default::default()
+    };
+    store.feeds.push(feed.clone());
+    save(&app, &store)?;
+    Ok(feed)
+}
+
+// 只更新标签、间隔和启用状态，抓取记录保持不变
+#[tauri::command]
+pub fn update_reference_feed(
+    app: tauri::AppHandle,
+    feed: ReferenceFeed,
+) -> Result<ReferenceFeed, String> {
+    let _guard = FEEDS_LOCK.lock().unwrap();
+    let mut store = load(&app);
+    let stored = store
+        .feeds
+        .iter_mut()
+        .find(|f| f.id == feed.id)
+        .ok_or_else(|| format!("reference feed not found: {}", feed.id))?;
+    stored.tags = feed.tags;
+    stored.interval_minutes = feed.interval_minutes.max(MIN_INTERVAL_MINUTES);
+    stored.enabled = feed.enabled;
+    let updated = stored.clone();
+    save(&app, &store)?;
+    Ok(updated)
+}
+
+#[tauri::command]
+pub fn remove_reference_feed(app: tauri::AppHandle, id: String) -> Result<(), String> {
+    let _guard = FEEDS_LOCK.lock().unwrap();
+    let mut store = load(&app);
+    store.feeds.retain(|f| f.id != id);
+    store.seen.remove(&id);
+    save(&app, &store)
+}
+
+#[tauri::command]
+pub async fn check_reference_feed(
+    app: tauri::AppHandle,
+    id: String,
+) -> Result<FeedCheckResult, String> {
+    check(&app, &id).await
+}
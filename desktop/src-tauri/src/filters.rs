@@ -0,0 +1,253 @@
+use std::path::PathBuf;
+
+use image::{DynamicImage, GenericImageView, RgbaImage};
+use tauri::Manager;
+
+use crate::export::OutputFormat;
+use crate::LogState;
+
+// 后期滤镜，按数组顺序依次应用；参数都有缺省值，前端只需传 type
+#[derive(Clone, serde::Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Filter {
+    Grain {
+        // 0~1，1 约等于 ±25% 亮度的噪点
+        #[serde(default = "default_grain_amount")]
+        amount: f32,
+        // 颗粒大小（像素），大于 1 时噪点成团，更接近胶片
+        #[serde(default = "default_grain_size")]
+        size: f32,
+        #[serde(default = "default_true")]
+        monochrome: bool,
+        // 固定种子，同一组参数多次导出结果一致
+        #[serde(default)]
+        seed: u64,
+    },
+    UnsharpMask {
+        #[serde(default = "default_unsharp_radius")]
+        radius: f32,
+        // 0~5，锐化强度
+        #[serde(default = "default_unsharp_amount")]
+        amount: f32,
+        // 0~255，差值低于阈值的平坦区域不锐化，避免放大噪点
+        #[serde(default)]
+        threshold: u8,
+    },
+    Vignette {
+        // 0~1，四角最暗时的压暗比例
+        #[serde(default = "default_vignette_strength")]
+        strength: f32,
+        // 0~1，从中心到角落的归一化距离，小于它的区域不受影响
+        #[serde(default = "default_vignette_radius")]
+        radius: f32,
+        #[serde(default = "default_vignette_softness")]
+        softness: f32,
+    },
+}
+
+fn default_grain_amount() -> f32 {
+    0.15
+}
+fn default_grain_size() -> f32 {
+    1.0
+}
+fn default_true() -> bool {
+    true
+}
+fn default_unsharp_radius() -> f32 {
+    1.2
+}
+fn default_unsharp_amount() -> f32 {
+    0.8
+}
+fn default_vignette_strength() -> f32 {
+    0.35
+}
+fn default_vignette_radius() -> f32 {
+    0.55
+}
+fn default_vignette_softness() -> f32 {
+    0.45
+}
+
+#[derive(serde::Serialize)]
+pub struct FilterResult {
+    dest_path: String,
+    width: u32,
+    height: u32,
+    filters: usize,
+}
+
+// splitmix64：不引入 rand，结果只需要“看起来随机”且可复现
+struct Noise(u64);
+
+impl Noise {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    // 三个均匀分布相加近似高斯，范围约 [-1, 1]
+    fn gaussian(&mut self) -> f32 {
+        let sum: f32 = (0..3)
+            .map(|_| (self.next() >> 40) as f32 / (1u64 << 24) as f32)
+            .sum();
+        (sum - 1.5) / 1.5
+    }
+}
+
+fn clamp_u8(v: f32) -> u8 {
+    v.round().clamp(0.0, 255.0) as u8
+}
+
+fn grain(img: &mut RgbaImage, amount: f32, size: f32, monochrome: bool, seed: u64) {
+    let (w, h) = img.dimensions();
+    let size = size.clamp(1.0, 8.0);
+    let scale = amount.clamp(0.0, 1.0) * 64.0;
+    // 先在缩小的网格上生成噪点再放大，得到成团的颗粒
+    let gw = ((w as f32 / size).ceil() as u32).max(1);
+    let gh = ((h as f32 / size).ceil() as u32).max(1);
+    let mut noise = Noise(seed ^ 0x5EED);
+    let channels = if monochrome { 1 } else { 3 };
+    let mut field = RgbaImage::new(gw, gh);
+    for px in field.pixels_mut() {
+        let mut sample = [0u8; 3];
+        for value in sample.iter_mut().take(channels) {
+            *value = clamp_u8(128.0 + noise.gaussian() * 127.0);
+        }
+        if monochrome {
+            sample = [sample[0]; 3];
+        }
+        px.0 = [sample[0], sample[1], sample[2], 255];
+    }
+    let field = if size > 1.0 {
+        image::imageops::resize(&field, w, h, image::imageops::FilterType::Triangle)
+    } else {
+        field
+    };
+    for (x, y, px) in img.enumerate_pixels_mut() {
+        let n = field
+            .get_pixel(x.min(field.width() - 1), y.min(field.height() - 1))
+            .0;
+        // 中间调颗粒最明显，高光和暗部减弱，避免纯黑纯白出现杂点
+        let luma = (0.299 * px[0] as f32 + 0.587 * px[1] as f32 + 0.114 * px[2] as f32) / 255.0;
+        let weight = 1.0 - (2.0 * luma - 1.0).powi(2) * 0.6;
+        for (value, noise) in px.0.iter_mut().zip(n).take(3) {
+            let delta = (noise as f32 - 128.0) / 127.0 * scale * weight;
+            *value = clamp_u8(*value as f32 + delta);
+        }
+    }
+}
+
+fn unsharp_mask(img: &mut RgbaImage, radius: f32, amount: f32, threshold: u8) {
+    let blurred = image::imageops::blur(img, radius.clamp(0.1, 20.0));
+    let amount = amount.clamp(0.0, 5.0);
+    for (px, blur) in img.pixels_mut().zip(blurred.pixels()) {
+        for (value, blur) in px.0.iter_mut().zip(blur.0).take(3) {
+            let diff = *value as f32 - blur as f32;
+            if diff.abs() >= threshold as f32 {
+                *value = clamp_u8(*value as f32 + diff * amount);
+            }
+        }
+    }
+}
+
+fn vignette(img: &mut RgbaImage, strength: f32, radius: f32, softness: f32) {
+    let (w, h) = img.dimensions();
+    let (cx, cy) = (w as f32 / 2.0, h as f32 / 2.0);
+    let strength = strength.clamp(0.0, 1.0);
+    let radius = radius.clamp(0.0, 1.0);
+    let softness = softness.max(0.01);
+    for (x, y, px) in img.enumerate_pixels_mut() {
+        // 按画面比例归一化，四角距离为 1，横图竖图都是椭圆形暗角
+        let dx = (x as f32 + 0.5 - cx) / cx;
+        let dy = (y as f32 + 0.5 - cy) / cy;
+        let d = ((dx * dx + dy * dy) / 2.0).sqrt();
+        let t = ((d - radius) / softness).clamp(0.0, 1.0);
+        let factor = 1.0 - strength * t * t * (3.0 - 2.0 * t);
+        for value in px.0.iter_mut().take(3) {
+            *value = clamp_u8(*value as f32 * factor);
+        }
+    }
+}
+
+pub fn apply(img: DynamicImage, filters: &[Filter]) -> DynamicImage {
+    if filters.is_empty() {
+        return img;
+    }
+    let has_alpha = img.color().has_alpha();
+    let mut rgba = img.to_rgba8();
+    for filter in filters {
+        match *filter {
+            Filter::Grain {
+                amount,
+                size,
+                monochrome,
+                seed,
+            } => grain(&mut rgba, amount, size, monochrome, seed),
+            Filter::UnsharpMask {
+                radius,
+                amount,
+                threshold,
+            } => unsharp_mask(&mut rgba, radius, amount, threshold),
+            Filter::Vignette {
+                strength,
+                radius,
+                softness,
+            } => vignette(&mut rgba, strength, radius, softness),
+        }
+    }
+    let out = DynamicImage::ImageRgba8(rgba);
+    // 原图不带透明通道时保持 RGB，PNG 体积不会因此变大
+    if has_alpha {
+        out
+    } else {
+        DynamicImage::ImageRgb8(out.to_rgb8())
+    }
+}
+
+// 对图片应用颗粒 / USM 锐化 / 暗角并导出到 dest_path（PNG 或 JPEG）
+#[tauri::command]
+pub async fn apply_filters(
+    app: tauri::AppHandle,
+    path: String,
+    filters: Vec<Filter>,
+    dest_path: String,
+    quality: Option<u8>,
+) -> Result<FilterResult, String> {
+    let src_path = crate::resolve_local_path(&app, &path)?;
+    let dest = PathBuf::from(dest_path.trim());
+    if dest.as_os_str().is_empty() {
+        return Err("dest_path is empty".to_string());
+    }
+    if filters.is_empty() {
+        return Err("no filters given".to_string());
+    }
+    let format = OutputFormat::from_path(&dest)?;
+    let count = filters.len();
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        let img = apply(crate::export::load_image(&src_path)?, &filters);
+        let (width, height) = img.dimensions();
+        crate::disk::ensure_free_space(&dest, width as u64 * height as u64 * 4)?;
+        crate::export::write_image(&img, &dest, format, quality.unwrap_or(92), None)?;
+        Ok::<_, String>(FilterResult {
+            dest_path: dest.to_string_lossy().to_string(),
+            width,
+            height,
+            filters: count,
+        })
+    })
+    .await
+    .map_err(|e| format!("apply filters failed: {}", e))??;
+    app.state::<LogState>().log_app(
+        "INFO",
+        &format!(
+            "Filters applied count={} size={}x{} dest={}",
+            result.filters, result.width, result.height, result.dest_path
+        ),
+    );
+    Ok(result)
+}
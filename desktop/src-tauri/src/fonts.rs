@@ -0,0 +1,254 @@
+use std::sync::{Arc, Mutex};
+
+use ab_glyph::{Font, FontRef, FontVec};
+use tauri::State;
+
+// 主字体缺字时按文字类别依次尝试的字体族，覆盖 macOS / Windows / 常见 Linux 发行版
+const CJK_FAMILIES: &[&str] = &[
+    "PingFang SC",
+    "Hiragino Sans GB",
+    "Heiti SC",
+    "Microsoft YaHei",
+    "DengXian",
+    "SimHei",
+    "Noto Sans CJK SC",
+    "Source Han Sans SC",
+    "Noto Sans SC",
+    "WenQuanYi Micro Hei",
+    "Droid Sans Fallback",
+    "Hiragino Sans",
+    "Yu Gothic",
+    "Apple SD Gothic Neo",
+    "Malgun Gothic",
+];
+// ab_glyph 只能画轮廓，Apple Color Emoji、Noto Color Emoji 这类位图字体画不出来，只用带轮廓的字体
+const EMOJI_FAMILIES: &[&str] = &[
+    "Segoe UI Emoji",
+    "Noto Emoji",
+    "Segoe UI Symbol",
+    "Apple Symbols",
+    "Symbola",
+];
+const SYMBOL_FAMILIES: &[&str] = &[
+    "Segoe UI Symbol",
+    "Apple Symbols",
+    "Noto Sans Symbols",
+    "Noto Sans Symbols 2",
+    "DejaVu Sans",
+    "Arial Unicode MS",
+];
+
+#[derive(Clone, serde::Serialize)]
+pub struct SystemFont {
+    family: String,
+    // 可读样式名，如 "Bold Italic"
+    style: String,
+    weight: u16,
+    italic: bool,
+    monospaced: bool,
+    post_script_name: String,
+    path: Option<String>,
+    // TTC 字体集合中的序号，渲染时需要一起传回
+    index: u32,
+}
+
+// 系统字体库：首次使用时扫描，后续水印/文字叠加渲染共用同一份
+pub struct FontState(pub Arc<Mutex<Option<Arc<fontdb::Database>>>>);
+
+impl FontState {
+    pub fn database(&self) -> Arc<fontdb::Database> {
+        let mut guard = self.0.lock().unwrap();
+        if let Some(db) = guard.as_ref() {
+            return db.clone();
+        }
+        let mut db = fontdb::Database::new();
+        db.load_system_fonts();
+        let db = Arc::new(db);
+        *guard = Some(db.clone());
+        db
+    }
+}
+
+fn weight_name(weight: u16) -> &'static str {
+    match weight {
+        0..=149 => "Thin",
+        150..=249 => "ExtraLight",
+        250..=349 => "Light",
+        350..=449 => "Regular",
+        450..=549 => "Medium",
+        550..=649 => "SemiBold",
+        650..=749 => "Bold",
+        750..=849 => "ExtraBold",
+        _ => "Black",
+    }
+}
+
+fn style_name(face: &fontdb::FaceInfo) -> String {
+    let weight = weight_name(face.weight.0);
+    match face.style {
+        fontdb::Style::Normal => weight.to_string(),
+        fontdb::Style::Italic => format!("{} Italic", weight),
+        fontdb::Style::Oblique => format!("{} Oblique", weight),
+    }
+}
+
+fn face_path(source: &fontdb::Source) -> Option<String> {
+    match source {
+        fontdb::Source::File(path) | fontdb::Source::SharedFile(path, _) => {
+            Some(path.to_string_lossy().to_string())
+        }
+        fontdb::Source::Binary(_) => None,
+    }
+}
+
+// 枚举系统已安装字体（family/style/path），供水印、文字叠加选择字体
+#[tauri::command]
+pub async fn list_system_fonts(state: State<'_, FontState>) -> Result<Vec<SystemFont>, String> {
+    let state = FontState(state.0.clone());
+    tauri::async_runtime::spawn_blocking(move || {
+        let db = state.database();
+        let mut fonts: Vec<SystemFont> = db
+            .faces()
+            .filter_map(|face| {
+                let family = face.families.first()?.0.clone();
+                Some(SystemFont {
+                    family,
+                    style: style_name(face),
+                    weight: face.weight.0,
+                    italic: face.style != fontdb::Style::Normal,
+                    monospaced: face.monospaced,
+                    post_script_name: face.post_script_name.clone(),
+                    path: face_path(&face.source),
+                    index: face.index,
+                })
+            })
+            .collect();
+        fonts.sort_by(|a, b| {
+            a.family
+                .to_lowercase()
+                .cmp(&b.family.to_lowercase())
+                .then(a.weight.cmp(&b.weight))
+                .then(a.italic.cmp(&b.italic))
+        });
+        fonts
+    })
+    .await
+    .map_err(|e| format!("list system fonts failed: {}", e))
+}
+
+fn is_cjk(c: char) -> bool {
+    // 部首、假名、注音、统一汉字，韩文音节，兼容汉字，全角标点，扩展区汉字
+    matches!(
+        c as u32,
+        0x2E80..=0x9FFF | 0xAC00..=0xD7AF | 0xF900..=0xFAFF | 0xFF00..=0xFFEF | 0x20000..=0x3FFFF
+    )
+}
+
+fn is_emoji(c: char) -> bool {
+    matches!(c as u32, 0x2600..=0x27BF | 0x1F000..=0x1FAFF)
+}
+
+// 有字形且有轮廓才算能画；空白字符没有轮廓，只看有没有字形
+pub fn covers<F: Font>(font: &F, c: char) -> bool {
+    let id = font.glyph_id(c);
+    id.0 != 0 && (c.is_whitespace() || font.outline(id).is_some())
+}
+
+fn load_face(db: &fontdb::Database, id: fontdb::ID) -> Option<FontVec> {
+    db.with_face_data(id, |data, index| {
+        FontVec::try_from_vec_and_index(data.to_vec(), index).ok()
+    })
+    .flatten()
+}
+
+fn query(db: &fontdb::Database, families: &[fontdb::Family]) -> Option<fontdb::ID> {
+    db.query(&fontdb::Query {
+        families,
+        weight: fontdb::Weight::NORMAL,
+        stretch: fontdb::Stretch::Normal,
+        style: fontdb::Style::Normal,
+    })
+}
+
+// 按字体族名查找主字体（找不到时用默认无衬线字体），再为 text 里主字体画不出的字符
+// （中文提示词、emoji 等）按类别追加回退字体；主字体能覆盖时不会加载额外字体
+pub fn font_chain(
+    db: &fontdb::Database,
+    family: Option<&str>,
+    text: &str,
+) -> Result<Vec<FontVec>, String> {
+    let mut families = Vec::new();
+    if let Some(name) = family.map(str::trim).filter(|n| !n.is_empty()) {
+        families.push(fontdb::Family::Name(name));
+    }
+    families.push(fontdb::Family::SansSerif);
+    let primary_id = query(db, &families)
+        .or_else(|| db.faces().next().map(|face| face.id))
+        .ok_or_else(|| "no system font available".to_string())?;
+    let primary = load_face(db, primary_id).ok_or_else(|| "load font failed".to_string())?;
+
+    let mut missing: Vec<char> = text
+        .chars()
+        .filter(|c| !c.is_control() && !c.is_whitespace() && !covers(&primary, *c))
+        .collect();
+    missing.sort_unstable();
+    missing.dedup();
+    let mut chain = vec![primary];
+    let mut tried = vec![primary_id];
+    while let Some(&c) = missing.first() {
+        let candidates: Vec<&str> = if is_emoji(c) {
+            EMOJI_FAMILIES
+                .iter()
+                .chain(SYMBOL_FAMILIES)
+                .copied()
+                .collect()
+        } else if is_cjk(c) {
+            CJK_FAMILIES.to_vec()
+        } else {
+            SYMBOL_FAMILIES
+                .iter()
+                .chain(CJK_FAMILIES)
+                .copied()
+                .collect()
+        };
+        let mut found = None;
+        for name in candidates {
+            let Some(id) = query(db, &[fontdb::Family::Name(name)]) else {
+                continue;
+            };
+            if tried.contains(&id) {
+                continue;
+            }
+            tried.push(id);
+            // 先用借用的数据检查覆盖，确认需要再复制整份字体（CJK 字体动辄几十 MB）
+            let useful = db
+                .with_face_data(id, |data, index| {
+                    FontRef::try_from_slice_and_index(data, index)
+                        .is_ok_and(|font| covers(&font, c))
+                })
+                .unwrap_or(false);
+            if useful {
+                found = load_face(db, id);
+                if found.is_some() {
+                    break;
+                }
+            }
+        }
+        match found {
+            Some(font) => {
+                missing.retain(|m| !covers(&font, *m));
+                chain.push(font);
+            }
+            // 系统里没有能画这个字的字体，只能显示主字体的缺字框
+            None => {
+                missing.remove(0);
+            }
+        }
+    }
+    Ok(chain)
+}
+
+// 渲染单个字符时用回退链里第一个能画它的字体
+pub fn pick<F: Font>(fonts: &[F], c: char) -> usize {
+    fonts.iter().position(|f| covers(f, c)).unwrap_or(0)
+}
@@ -0,0 +1,345 @@
+use std::process::Command;
+use std::sync::{Arc, Mutex};
+use tauri::{Manager, State};
+
+use crate::LogState;
+
+#[derive(Clone, serde::Serialize)]
+pub struct GpuAdapter {
+    name: String,
+    vendor: String,
+    vram_bytes: Option<u64>,
+    driver_version: Option<String>,
+}
+
+#[derive(Clone, serde::Serialize)]
+pub struct GpuCapabilities {
+    adapters: Vec<GpuAdapter>,
+    // 视频硬解能力：用于决定预览是否走硬件解码
+    hardware_video_decode: bool,
+    decode_api: Option<String>,
+    // 本地 ONNX 超分：显存不足或无可用执行后端时前端应隐藏入口
+    onnx_execution_provider: Option<String>,
+    onnx_upscale_recommended: bool,
+}
+
+pub struct GpuState(pub Arc<Mutex<Option<GpuCapabilities>>>);
+
+// 本地超分模型（Real-ESRGAN 级别）在 2GB 以下显存上基本不可用
+const ONNX_MIN_VRAM_BYTES: u64 = 2 * 1024 * 1024 * 1024;
+
+// 先判断 intel/nvidia：“Intel Corporation” 里含有 "ati"，不能用裸的 "ati" 匹配 AMD
+fn normalize_vendor(raw: &str) -> String {
+    let lower = raw.to_lowercase();
+    if lower.contains("intel") || lower.contains("0x8086") {
+        "intel".to_string()
+    } else if lower.contains("nvidia") || lower.contains("0x10de") {
+        "nvidia".to_string()
+    } else if lower.contains("amd")
+        || lower.contains("advanced micro devices")
+        || lower.contains("ati technologies")
+        || lower.contains("radeon")
+        || lower.contains("0x1002")
+    {
+        "amd".to_string()
+    } else if lower.contains("apple") {
+        "apple".to_string()
+    } else if raw.trim().is_empty() {
+        "unknown".to_string()
+    } else {
+        raw.trim().to_string()
+    }
+}
+
+fn run_command_stdout(program: &str, args: &[&str]) -> Option<String> {
+    // 启动时在后台探测，Windows 上不要弹出控制台窗口
+    let output = crate::shutdown::hide_console(Command::new(program).args(args))
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+#[cfg(target_os = "macos")]
+fn parse_vram_text(raw: &str) -> Option<u64> {
+    // system_profiler 输出形如 "8 GB" / "1536 MB"
+    let mut parts = raw.split_whitespace();
+    let value = parts.next()?.parse::<u64>().ok()?;
+    match parts.next().map(|u| u.to_uppercase()) {
+        Some(unit) if unit.starts_with("GB") => Some(value * 1024 * 1024 * 1024),
+        Some(unit) if unit.starts_with("MB") => Some(value * 1024 * 1024),
+        _ => None,
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn detect_adapters() -> Vec<GpuAdapter> {
+    let Some(raw) = run_command_stdout("system_profiler", &["SPDisplaysDataType", "-json"]) else {
+        return Vec::new();
+    };
+    let Ok(json) = serde_json::from_str::<serde_json::Value>(&raw) else {
+        return Vec::new();
+    };
+    let items = json
+        .get("SPDisplaysDataType")
+        .and_then(|v| v.as_array())
+        .cloned()
+        .unwrap_or_default();
+
+    items
+        .iter()
+        .map(|item| {
+            let text = |key: &str| item.get(key).and_then(|v| v.as_str()).unwrap_or("");
+            let name = if text("sppci_model").is_empty() {
+                text("_name").to_string()
+            } else {
+                text("sppci_model").to_string()
+            };
+            let vendor_raw = if text("spdisplays_vendor").is_empty() {
+                name.clone()
+            } else {
+                text("spdisplays_vendor").to_string()
+            };
+            let vram = parse_vram_text(text("spdisplays_vram"))
+                .or_else(|| parse_vram_text(text("spdisplays_vram_shared")));
+            GpuAdapter {
+                name,
+                vendor: normalize_vendor(&vendor_raw),
+                vram_bytes: vram,
+                driver_version: None,
+            }
+        })
+        .collect()
+}
+
+#[cfg(target_os = "windows")]
+fn detect_adapters() -> Vec<GpuAdapter> {
+    let script = "Get-CimInstance Win32_VideoController | \
+        Select-Object Name,AdapterCompatibility,AdapterRAM,DriverVersion | \
+        ConvertTo-Json -Compress";
+    let Some(raw) = run_command_stdout("powershell", &["-NoProfile", "-Command", script]) else {
+        return Vec::new();
+    };
+    let Ok(json) = serde_json::from_str::<serde_json::Value>(raw.trim()) else {
+        return Vec::new();
+    };
+    // 只有一块显卡时 ConvertTo-Json 输出的是对象而不是数组
+    let items = match json {
+        serde_json::Value::Array(items) => items,
+        other => vec![other],
+    };
+
+    items
+        .iter()
+        .map(|item| {
+            let text = |key: &str| item.get(key).and_then(|v| v.as_str()).unwrap_or("");
+            GpuAdapter {
+                name: text("Name").to_string(),
+                vendor: normalize_vendor(text("AdapterCompatibility")),
+                // AdapterRAM 是 uint32，4GB 以上显卡会被截断，仅作参考
+                vram_bytes: item.get("AdapterRAM").and_then(|v| v.as_u64()),
+                driver_version: Some(text("DriverVersion").to_string()).filter(|v| !v.is_empty()),
+            }
+        })
+        .collect()
+}
+
+#[cfg(target_os = "linux")]
+fn detect_adapters() -> Vec<GpuAdapter> {
+    use std::fs;
+
+    let mut adapters = Vec::new();
+    let Ok(entries) = fs::read_dir("/sys/class/drm") else {
+        return adapters;
+    };
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        // 只看 card0/card1，忽略 card0-HDMI-A-1 这类连接器节点
+        if !name.starts_with("card") || name.contains('-') {
+            continue;
+        }
+        let device = entry.path().join("device");
+        let read = |file: &str| {
+            fs::read_to_string(device.join(file))
+                .map(|s| s.trim().to_string())
+                .unwrap_or_default()
+        };
+        let vendor_id = read("vendor");
+        if vendor_id.is_empty() {
+            continue;
+        }
+        let vram = read("mem_info_vram_total").parse::<u64>().ok();
+        let driver = fs::read_link(device.join("driver"))
+            .ok()
+            .and_then(|p| p.file_name().map(|n| n.to_string_lossy().to_string()));
+        adapters.push(GpuAdapter {
+            name: format!("{} {}", vendor_id, read("device")),
+            vendor: normalize_vendor(&vendor_id),
+            vram_bytes: vram,
+            driver_version: driver,
+        });
+    }
+
+    // lspci 能给出可读的型号名称，取不到时保留 PCI ID
+    if let Some(raw) = run_command_stdout("lspci", &["-mm"]) {
+        let names: Vec<String> = raw
+            .lines()
+            .filter(|l| l.contains("VGA") || l.contains("3D controller"))
+            .map(|l| {
+                l.split('"')
+                    .filter(|s| !s.trim().is_empty())
+                    .skip(2)
+                    .take(2)
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            })
+            .collect();
+        for (adapter, name) in adapters.iter_mut().zip(names) {
+            if !name.is_empty() {
+                adapter.name = name;
+            }
+        }
+    }
+
+    adapters
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+fn detect_adapters() -> Vec<GpuAdapter> {
+    Vec::new()
+}
+
+fn detect_decode_api(adapters: &[GpuAdapter]) -> Option<String> {
+    if cfg!(target_os = "macos") {
+        // VideoToolbox 在所有受支持的 macOS 版本上都可用
+        return Some("VideoToolbox".to_string());
+    }
+    if cfg!(target_os = "windows") {
+        return (!adapters.is_empty()).then(|| "D3D11VA".to_string());
+    }
+    if cfg!(target_os = "linux") {
+        if adapters.iter().any(|a| a.vendor == "nvidia") {
+            return Some("NVDEC".to_string());
+        }
+        if std::path::Path::new("/dev/dri/renderD128").exists() {
+            return Some("VA-API".to_string());
+        }
+    }
+    None
+}
+
+fn detect_onnx_provider(adapters: &[GpuAdapter]) -> Option<String> {
+    if cfg!(target_os = "macos") {
+        return Some("CoreML".to_string());
+    }
+    if adapters.iter().any(|a| a.vendor == "nvidia") {
+        return Some("CUDA".to_string());
+    }
+    if cfg!(target_os = "windows") && !adapters.is_empty() {
+        return Some("DirectML".to_string());
+    }
+    None
+}
+
+fn detect_capabilities() -> GpuCapabilities {
+    let adapters = detect_adapters();
+    let decode_api = detect_decode_api(&adapters);
+    let onnx_execution_provider = detect_onnx_provider(&adapters);
+
+    // Apple Silicon 是统一内存，没有独立显存数值，按芯片本身判断
+    let apple_silicon = cfg!(target_os = "macos") && std::env::consts::ARCH == "aarch64";
+    let max_vram = adapters
+        .iter()
+        .filter_map(|a| a.vram_bytes)
+        .max()
+        .unwrap_or(0);
+    let onnx_upscale_recommended =
+        onnx_execution_provider.is_some() && (apple_silicon || max_vram >= ONNX_MIN_VRAM_BYTES);
+
+    GpuCapabilities {
+        hardware_video_decode: decode_api.is_some(),
+        decode_api,
+        onnx_execution_provider,
+        onnx_upscale_recommended,
+        adapters,
+    }
+}
+
+fn summarize(caps: &GpuCapabilities) -> String {
+    let adapters = caps
+        .adapters
+        .iter()
+        .map(|a| {
+            format!(
+                "{} ({}, vram={})",
+                a.name,
+                a.vendor,
+                a.vram_bytes
+                    .map(|v| format!("{}MB", v / 1024 / 1024))
+                    .unwrap_or_else(|| "n/a".to_string())
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("; ");
+    format!(
+        "GPU capabilities adapters=[{}] decode={} onnx={} upscale_recommended={}",
+        adapters,
+        caps.decode_api.as_deref().unwrap_or("none"),
+        caps.onnx_execution_provider.as_deref().unwrap_or("none"),
+        caps.onnx_upscale_recommended
+    )
+}
+
+// 启动时在后台探测一次并写入 app.log，渲染异常反馈时日志里就带上显卡信息
+pub fn detect_in_background(app: &tauri::AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let caps = detect_capabilities();
+        app.state::<LogState>().log_app("INFO", &summarize(&caps));
+        if let Ok(mut guard) = app.state::<GpuState>().0.lock() {
+            *guard = Some(caps);
+        }
+    });
+}
+
+// 获取显卡与硬解能力（结果缓存，system_profiler/PowerShell 调用较慢）
+#[tauri::command]
+pub async fn get_gpu_capabilities(state: State<'_, GpuState>) -> Result<GpuCapabilities, String> {
+    if let Some(caps) = state.0.lock().unwrap().clone() {
+        return Ok(caps);
+    }
+
+    let caps = tauri::async_runtime::spawn_blocking(detect_capabilities)
+        .await
+        .map_err(|e| format!("detect gpu failed: {}", e))?;
+    *state.0.lock().unwrap() = Some(caps.clone());
+    Ok(caps)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::normalize_vendor;
+
+    #[test]
+    fn normalize_vendor_matches_known_vendors() {
+        let cases = [
+            ("Intel Corporation", "intel"),
+            ("Intel(R) UHD Graphics 630", "intel"),
+            ("0x8086", "intel"),
+            ("NVIDIA", "nvidia"),
+            ("0x10de", "nvidia"),
+            ("Advanced Micro Devices, Inc.", "amd"),
+            ("ATI Technologies Inc.", "amd"),
+            ("AMD Radeon Pro 5500M", "amd"),
+            ("0x1002", "amd"),
+            ("Apple", "apple"),
+            ("  ", "unknown"),
+            (" Matrox ", "Matrox"),
+        ];
+        for (raw, expected) in cases {
+            assert_eq!(normalize_vendor(raw), expected, "raw={:?}", raw);
+        }
+    }
+}
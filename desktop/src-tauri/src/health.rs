@@ -0,0 +1,169 @@
+use std::time::Duration;
+
+use tauri::{Emitter, Manager, State};
+
+use crate::settings::SettingsState;
+use crate::{GenerationState, LogState};
+
+const MIN_INTERVAL_SECS: u64 = 5;
+const MAX_INTERVAL_SECS: u64 = 300;
+const MAX_FAILURE_THRESHOLD: u32 = 20;
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct HealthSettings {
+    pub enabled: bool,
+    pub interval_secs: u64,
+    // 连续失败这么多次才算异常，偶发的超时（如生成时 CPU 占满）不打扰用户
+    pub failure_threshold: u32,
+    // 判定异常后自动重启 sidecar；有生成任务进行中时不重启，只提示
+    pub auto_restart: bool,
+}
+
+impl Default for HealthSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval_secs: 15,
+            failure_threshold: 3,
+            auto_restart: false,
+        }
+    }
+}
+
+#[derive(Clone, serde::Serialize)]
+struct UnhealthyPayload {
+    port: u16,
+    consecutive_failures: u32,
+    last_error: String,
+    // 本次是否已触发自动重启
+    restarting: bool,
+}
+
+#[derive(Clone, serde::Serialize)]
+struct HealthyPayload {
+    port: u16,
+}
+
+async fn ping(client: &reqwest::Client, port: u16) -> Result<(), String> {
+    let response = client
+        .get(format!("http://127.0.0.1:{}/api/v1/health", port))
+        .header(reqwest::header::ORIGIN, "tauri://localhost")
+        .send()
+        .await
+        .map_err(|e| format!("request backend health failed: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!(
+            "request backend health failed: {}",
+            response.status()
+        ));
+    }
+    Ok(())
+}
+
+// 与 restart_sidecar 相同：后端已经不响应，不走平滑停止，直接结束后重新拉起
+fn restart(app: &tauri::AppHandle) -> Result<(), String> {
+    crate::with_sidecar_stopped(app, crate::SidecarRestart::Unresponsive, || Ok(()))
+}
+
+fn can_restart(app: &tauri::AppHandle) -> bool {
+    let generating = app
+        .state::<GenerationState>()
+        .0
+        .lock()
+        .map(|g| *g)
+        .unwrap_or(false);
+    !generating && crate::remote_backend::endpoint(app).is_none()
+}
+
+// 就绪探测（readiness.rs）只管启动阶段；这里在后端就绪后持续巡检，
+// 进程还在但接口不再响应（死锁、端口被占）时发出 backend-unhealthy，前端据此显示横幅而不是每个请求都 502
+pub fn start(app: &tauri::AppHandle) {
+    let app = app.clone();
+    std::thread::spawn(move || {
+        let Ok(client) = reqwest::Client::builder().timeout(REQUEST_TIMEOUT).build() else {
+            return;
+        };
+        let mut watched_port = 0;
+        let mut failures = 0;
+        loop {
+            let settings = app.state::<SettingsState>().get().health;
+            std::thread::sleep(Duration::from_secs(
+                settings
+                    .interval_secs
+                    .clamp(MIN_INTERVAL_SECS, MAX_INTERVAL_SECS),
+            ));
+            // 未就绪（启动中、崩溃重启中）由 sidecar 生命周期处理，不重复计数
+            let Some(port) = crate::readiness::ready_port().filter(|_| settings.enabled) else {
+                watched_port = 0;
+                failures = 0;
+                continue;
+            };
+            if port != watched_port {
+                watched_port = port;
+                failures = 0;
+            }
+            let error = match tauri::async_runtime::block_on(ping(&client, port)) {
+                Ok(()) => {
+                    if failures >= settings.failure_threshold.max(1) {
+                        app.state::<LogState>()
+                            .log_app("INFO", &format!("Backend healthy again port={}", port));
+                        let _ = app.emit("backend-healthy", HealthyPayload { port });
+                    }
+                    failures = 0;
+                    continue;
+                }
+                Err(err) => err,
+            };
+            failures += 1;
+            // 只在达到阈值的那一次发事件，之后持续失败不再刷屏
+            if failures != settings.failure_threshold.max(1) {
+                continue;
+            }
+            // 自动重启同样计入崩溃循环统计
+            let restarting = settings.auto_restart
+                && can_restart(&app)
+                && crate::crash_loop::allow_restart(&app);
+            app.state::<LogState>().log_app(
+                "WARN",
+                &format!(
+                    "Backend unhealthy port={} failures={} restarting={}: {}",
+                    port, failures, restarting, error
+                ),
+            );
+            let _ = app.emit(
+                "backend-unhealthy",
+                UnhealthyPayload {
+                    port,
+                    consecutive_failures: failures,
+                    last_error: error,
+                    restarting,
+                },
+            );
+            if restarting {
+                if let Err(err) = restart(&app) {
+                    app.state::<LogState>()
+                        .log_app("ERROR", &format!("Backend auto restart failed: {}", err));
+                }
+            }
+        }
+    });
+}
+
+#[tauri::command]
+pub fn get_health_settings(state: State<'_, SettingsState>) -> HealthSettings {
+    state.get().health
+}
+
+#[tauri::command]
+pub fn set_health_settings(
+    state: State<'_, SettingsState>,
+    mut settings: HealthSettings,
+) -> Result<HealthSettings, String> {
+    settings.interval_secs = settings
+        .interval_secs
+        .clamp(MIN_INTERVAL_SECS, MAX_INTERVAL_SECS);
+    settings.failure_threshold = settings.failure_threshold.clamp(1, MAX_FAILURE_THRESHOLD);
+    Ok(state.update(|s| s.health = settings)?.health)
+}
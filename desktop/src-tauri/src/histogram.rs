@@ -0,0 +1,131 @@
+use tauri::Manager;
+
+use crate::LogState;
+
+#[derive(serde::Serialize)]
+pub struct ChannelStats {
+    // 256 档计数
+    bins: Vec<u64>,
+    mean: f64,
+    median: u8,
+    // 值为 0 / 255 的像素占比（0~1）
+    clipped_shadows: f64,
+    clipped_highlights: f64,
+}
+
+#[derive(serde::Serialize)]
+pub struct ImageHistogram {
+    width: u32,
+    height: u32,
+    // 参与统计的像素数，完全透明的像素不计入
+    pixels: u64,
+    red: ChannelStats,
+    green: ChannelStats,
+    blue: ChannelStats,
+    // Rec.709 亮度
+    luminance: ChannelStats,
+    // 任一通道为 255 / 0 的像素占比，打印前判断是否过曝、死黑
+    any_clipped_highlights: f64,
+    any_clipped_shadows: f64,
+}
+
+fn channel_stats(bins: [u64; 256], total: u64) -> ChannelStats {
+    let sum: u64 = bins.iter().enumerate().map(|(v, n)| v as u64 * n).sum();
+    let half = total.div_ceil(2);
+    let mut seen = 0;
+    let mut median = 0u8;
+    for (v, n) in bins.iter().enumerate() {
+        seen += n;
+        if seen >= half {
+            median = v as u8;
+            break;
+        }
+    }
+    let ratio = |n: u64| {
+        if total == 0 {
+            0.0
+        } else {
+            n as f64 / total as f64
+        }
+    };
+    ChannelStats {
+        mean: if total == 0 {
+            0.0
+        } else {
+            sum as f64 / total as f64
+        },
+        median,
+        clipped_shadows: ratio(bins[0]),
+        clipped_highlights: ratio(bins[255]),
+        bins: bins.to_vec(),
+    }
+}
+
+fn compute(img: &image::RgbaImage) -> ImageHistogram {
+    let mut rgb = [[0u64; 256]; 3];
+    let mut luma = [0u64; 256];
+    let (mut total, mut high, mut low) = (0u64, 0u64, 0u64);
+    for px in img.pixels() {
+        let [r, g, b, a] = px.0;
+        if a == 0 {
+            continue;
+        }
+        total += 1;
+        rgb[0][r as usize] += 1;
+        rgb[1][g as usize] += 1;
+        rgb[2][b as usize] += 1;
+        let y = 0.2126 * r as f32 + 0.7152 * g as f32 + 0.0722 * b as f32;
+        luma[y.round().clamp(0.0, 255.0) as usize] += 1;
+        if r == 255 || g == 255 || b == 255 {
+            high += 1;
+        }
+        if r == 0 || g == 0 || b == 0 {
+            low += 1;
+        }
+    }
+    let ratio = |n: u64| {
+        if total == 0 {
+            0.0
+        } else {
+            n as f64 / total as f64
+        }
+    };
+    let [red, green, blue] = rgb;
+    ImageHistogram {
+        width: img.width(),
+        height: img.height(),
+        pixels: total,
+        red: channel_stats(red, total),
+        green: channel_stats(green, total),
+        blue: channel_stats(blue, total),
+        luminance: channel_stats(luma, total),
+        any_clipped_highlights: ratio(high),
+        any_clipped_shadows: ratio(low),
+    }
+}
+
+// 计算各通道直方图和裁切（过曝 / 死黑）统计，供信息面板展示
+#[tauri::command]
+pub async fn get_image_histogram(
+    app: tauri::AppHandle,
+    path: String,
+) -> Result<ImageHistogram, String> {
+    let src_path = crate::resolve_local_path(&app, &path)?;
+    let histogram = tauri::async_runtime::spawn_blocking(move || {
+        let img = crate::export::load_image(&src_path)?.to_rgba8();
+        Ok::<_, String>(compute(&img))
+    })
+    .await
+    .map_err(|e| format!("histogram task failed: {}", e))??;
+    app.state::<LogState>().log_app(
+        "INFO",
+        &format!(
+            "Histogram computed size={}x{} highlights={:.4} shadows={:.4}",
+            histogram.width,
+            histogram.height,
+            histogram.any_clipped_highlights,
+            histogram.any_clipped_shadows
+        ),
+    );
+    Ok(histogram)
+}
@@ -0,0 +1,234 @@
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use sha2::{Digest, Sha256};
+use tauri::{Emitter, Manager};
+use tauri_plugin_notification::NotificationExt;
+
+use crate::proxy::ProxyRequest;
+use crate::{BackendPort, LogState};
+
+// 托盘和跳转列表里放不下太多，完整的生成历史仍在后端
+const MAX_RECENT_PROMPTS: usize = 20;
+const TEXT_TO_IMAGE_PATH: &str = "/api/v1/tasks/generate";
+
+// 串行化 recent_prompts.json 的读写
+static HISTORY_LOCK: Mutex<()> = Mutex::new(());
+// 冷启动时从跳转列表带进来的重跑请求，等后端端口就绪后执行
+static PENDING_RERUN: Mutex<Option<String>> = Mutex::new(None);
+
+#[derive(Clone, Default, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct RecentPrompt {
+    pub id: String,
+    pub prompt: String,
+    pub provider: String,
+    pub model_id: String,
+    // 文生图请求的原始 JSON，可在壳层直接重新提交；图生图带参考图，只能回填到界面
+    request: Option<serde_json::Value>,
+    pub rerunnable: bool,
+    pub last_used: u128,
+}
+
+#[derive(Default, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+struct RecentStore {
+    prompts: Vec<RecentPrompt>,
+}
+
+fn store_path(app: &tauri::AppHandle) -> PathBuf {
+    crate::app_data_base(app).join("recent_prompts.json")
+}
+
+// 取 sha256 前 8 字节：id 会落盘并用于托盘菜单，需跨版本稳定
+fn prompt_id(prompt: &str) -> String {
+    Sha256::digest(prompt.as_bytes())[..8]
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+pub fn list(app: &tauri::AppHandle) -> Vec<RecentPrompt> {
+    crate::settings::read_json_or_default::<RecentStore>(&store_path(app)).prompts
+}
+
+fn changed(app: &tauri::AppHandle) {
+    crate::tray::refresh(app);
+    crate::jumplist::update(app);
+    let _ = app.emit("recent-prompts-changed", ());
+}
+
+// 由代理在生成请求提交成功后调用；同一提示词只保留最近一次
+pub fn record(app: &tauri::AppHandle, request: &ProxyRequest) {
+    if !request.path.starts_with("/api/v1/tasks/generate") {
+        return;
+    }
+    let json: Option<serde_json::Value> = serde_json::from_slice(&request.body).ok();
+    let field = |name: &str| {
+        json.as_ref()
+            .and_then(|j| j.get(name))
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string())
+            .or_else(|| request.form_field(name))
+            .unwrap_or_default()
+    };
+    let prompt = json
+        .as_ref()
+        .and_then(|j| j.pointer("/params/prompt"))
+        .and_then(|v| v.as_str())
+        .map(|s| s.to_string())
+        .or_else(|| request.form_field("prompt"))
+        .unwrap_or_default()
+        .trim()
+        .to_string();
+    if prompt.is_empty() {
+        return;
+    }
+    let rerunnable = request.path == TEXT_TO_IMAGE_PATH && json.is_some();
+    let entry = RecentPrompt {
+        id: prompt_id(&prompt),
+        provider: field("provider"),
+        model_id: field("model_id"),
+        request: if rerunnable { json.clone() } else { None },
+        rerunnable,
+        last_used: crate::now_ms(),
+        prompt,
+    };
+
+    {
+        let _guard = HISTORY_LOCK.lock().unwrap();
+        let path = store_path(app);
+        let mut store: RecentStore = crate::settings::read_json_or_default(&path);
+        // 旧版本的 id 由 DefaultHasher 生成，同时按文本去重
+        store
+            .prompts
+            .retain(|p| p.id != entry.id && p.prompt != entry.prompt);
+        store.prompts.insert(0, entry);
+        store.prompts.truncate(MAX_RECENT_PROMPTS);
+        if let Err(err) = crate::settings::write_json_atomic(&path, &store) {
+            app.state::<LogState>()
+                .log_app("WARN", &format!("save recent prompts failed: {}", err));
+            return;
+        }
+    }
+    changed(app);
+}
+
+fn show_main_window(app: &tauri::AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+}
+
+// 最近一次可重跑的文生图请求，快速生成窗口沿用其中的服务商、模型和尺寸
+pub fn latest_request(app: &tauri::AppHandle) -> Option<serde_json::Value> {
+    list(app)
+        .into_iter()
+        .find(|p| p.rerunnable)
+        .and_then(|p| p.request)
+}
+
+// 经过壳层代理提交，暂停开关和费用记账同样生效
+pub async fn submit(app: &tauri::AppHandle, body: &serde_json::Value) -> Result<(), String> {
+    let backend_port = *app.state::<BackendPort>().0.lock().unwrap();
+    let port = crate::proxy::frontend_port(app, backend_port);
+    if port == 0 {
+        return Err("backend is not running".to_string());
+    }
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(30))
+        .build()
+        .map_err(|e| format!("build rerun client failed: {}", e))?;
+    let response = client
+        .post(format!("http://127.0.0.1:{}{}", port, TEXT_TO_IMAGE_PATH))
+        .header(reqwest::header::ORIGIN, "tauri://localhost")
+        .json(body)
+        .send()
+        .await
+        .map_err(|e| format!("rerun request failed: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("rerun request failed: {}", response.status()));
+    }
+    Ok(())
+}
+
+// 可重跑的直接在后台重新提交，不需要打开主窗口；其余的打开窗口交给前端回填
+pub async fn rerun(app: &tauri::AppHandle, id: &str) -> Result<(), String> {
+    let entry = list(app)
+        .into_iter()
+        .find(|p| p.id == id)
+        .ok_or_else(|| format!("recent prompt not found: {}", id))?;
+    let Some(body) = entry.request.as_ref().filter(|_| entry.rerunnable) else {
+        show_main_window(app);
+        return app
+            .emit("apply-prompt", entry.clone())
+            .map_err(|e| format!("emit apply-prompt failed: {}", e));
+    };
+    submit(app, body).await?;
+    app.state::<LogState>()
+        .log_app("INFO", &format!("Recent prompt resubmitted id={}", id));
+    let _ = app
+        .notification()
+        .builder()
+        .title("已重新提交生成")
+        .body(entry.prompt.chars().take(80).collect::<String>())
+        .show();
+    Ok(())
+}
+
+pub fn spawn_rerun(app: &tauri::AppHandle, id: String) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(err) = rerun(&app, &id).await {
+            app.state::<LogState>()
+                .log_app("WARN", &format!("rerun recent prompt failed: {}", err));
+        }
+    });
+}
+
+pub fn set_pending_rerun(id: String) {
+    *PENDING_RERUN.lock().unwrap() = Some(id);
+}
+
+// 后端端口就绪时调用
+pub fn run_pending(app: &tauri::AppHandle) {
+    if let Some(id) = PENDING_RERUN.lock().unwrap().take() {
+        spawn_rerun(app, id);
+    }
+}
+
+#[tauri::command]
+pub fn list_recent_prompts(app: tauri::AppHandle) -> Vec<RecentPrompt> {
+    list(&app)
+}
+
+#[tauri::command]
+pub async fn rerun_recent_prompt(app: tauri::AppHandle, id: String) -> Result<(), String> {
+    rerun(&app, &id).await
+}
+
+#[tauri::command]
+pub fn remove_recent_prompt(app: tauri::AppHandle, id: String) -> Result<(), String> {
+    {
+        let _guard = HISTORY_LOCK.lock().unwrap();
+        let path = store_path(&app);
+        let mut store: RecentStore = crate::settings::read_json_or_default(&path);
+        store.prompts.retain(|p| p.id != id);
+        crate::settings::write_json_atomic(&path, &store)?;
+    }
+    changed(&app);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn clear_recent_prompts(app: tauri::AppHandle) -> Result<(), String> {
+    {
+        let _guard = HISTORY_LOCK.lock().unwrap();
+        crate::settings::write_json_atomic(&store_path(&app), &RecentStore::default())?;
+    }
+    changed(&app);
+    Ok(())
+}
@@ -0,0 +1,297 @@
+use std::collections::HashSet;
+use std::fs;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use tauri::Manager;
+
+use crate::{BackendPort, LogState};
+
+// 后端单页上限
+const PAGE_SIZE: usize = 100;
+
+// CSV 列；JSONL / JSON 保留后端返回的完整记录
+const CSV_COLUMNS: &[&str] = &[
+    "task_id",
+    "created_at",
+    "completed_at",
+    "status",
+    "provider_name",
+    "model_id",
+    "prompt",
+    "prompt_original",
+    "prompt_optimized",
+    "prompt_optimize_mode",
+    "width",
+    "height",
+    "total_count",
+    "folder_id",
+    "error_message",
+    "local_path",
+    "image_url",
+    "config_snapshot",
+];
+
+#[derive(Clone, Copy, PartialEq)]
+enum ExportFormat {
+    Csv,
+    Jsonl,
+    Json,
+}
+
+impl ExportFormat {
+    fn parse(format: Option<&str>, path: &Path) -> Result<Self, String> {
+        let name = match format.map(|f| f.trim().to_ascii_lowercase()) {
+            Some(f) if !f.is_empty() => f,
+            _ => path
+                .extension()
+                .and_then(|e| e.to_str())
+                .map(|e| e.to_ascii_lowercase())
+                .unwrap_or_default(),
+        };
+        match name.as_str() {
+            "csv" => Ok(Self::Csv),
+            "jsonl" | "ndjson" => Ok(Self::Jsonl),
+            "json" => Ok(Self::Json),
+            _ => Err(format!("unsupported history format: {}", name)),
+        }
+    }
+}
+
+#[derive(serde::Serialize)]
+pub struct HistoryExportResult {
+    path: String,
+    count: usize,
+}
+
+// 日期参数为 YYYY-MM-DD（含首尾两天），按记录自身时区的日期比较
+fn parse_date(value: Option<String>) -> Result<Option<String>, String> {
+    let Some(value) = value
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+    else {
+        return Ok(None);
+    };
+    let bytes = value.as_bytes();
+    let valid = bytes.len() == 10
+        && bytes[4] == b'-'
+        && bytes[7] == b'-'
+        && bytes
+            .iter()
+            .enumerate()
+            .all(|(i, b)| i == 4 || i == 7 || b.is_ascii_digit());
+    if !valid {
+        return Err(format!("invalid date: {}", value));
+    }
+    Ok(Some(value))
+}
+
+fn csv_field(value: Option<&serde_json::Value>) -> String {
+    let text = match value {
+        None | Some(serde_json::Value::Null) => String::new(),
+        Some(serde_json::Value::String(s)) => s.clone(),
+        Some(other) => other.to_string(),
+    };
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text
+    }
+}
+
+struct RecordWriter {
+    writer: BufWriter<fs::File>,
+    format: ExportFormat,
+    count: usize,
+}
+
+impl RecordWriter {
+    fn create(path: &Path, format: ExportFormat) -> Result<Self, String> {
+        let file =
+            fs::File::create(path).map_err(|e| format!("create export file failed: {}", e))?;
+        let mut writer = BufWriter::new(file);
+        let header = match format {
+            // 带 BOM，Excel 打开中文提示词不乱码
+            ExportFormat::Csv => format!("\u{feff}{}\n", CSV_COLUMNS.join(",")),
+            ExportFormat::Json => "[\n".to_string(),
+            ExportFormat::Jsonl => String::new(),
+        };
+        writer
+            .write_all(header.as_bytes())
+            .map_err(|e| format!("write export failed: {}", e))?;
+        Ok(Self {
+            writer,
+            format,
+            count: 0,
+        })
+    }
+
+    fn write(&mut self, task: &serde_json::Value) -> Result<(), String> {
+        let result = match self.format {
+            ExportFormat::Csv => {
+                let line: Vec<String> = CSV_COLUMNS
+                    .iter()
+                    .map(|c| csv_field(task.get(*c)))
+                    .collect();
+                writeln!(self.writer, "{}", line.join(","))
+            }
+            ExportFormat::Jsonl => serde_json::to_writer(&mut self.writer, task)
+                .map_err(std::io::Error::from)
+                .and_then(|_| self.writer.write_all(b"\n")),
+            ExportFormat::Json => {
+                let separator: &[u8] = if self.count == 0 { b"  " } else { b",\n  " };
+                self.writer
+                    .write_all(separator)
+                    .and_then(|_| serde_json::to_writer(&mut self.writer, task).map_err(Into::into))
+            }
+        };
+        result.map_err(|e| format!("write export failed: {}", e))?;
+        self.count += 1;
+        Ok(())
+    }
+
+    fn finish(mut self) -> Result<usize, String> {
+        if self.format == ExportFormat::Json {
+            self.writer
+                .write_all(b"\n]\n")
+                .map_err(|e| format!("write export failed: {}", e))?;
+        }
+        self.writer
+            .flush()
+            .map_err(|e| format!("write export failed: {}", e))?;
+        Ok(self.count)
+    }
+}
+
+async fn fetch_page(
+    client: &reqwest::Client,
+    port: u16,
+    page: usize,
+) -> Result<Vec<serde_json::Value>, String> {
+    let response = client
+        .get(format!(
+            "http://127.0.0.1:{}/api/v1/images?page={}&page_size={}",
+            port, page, PAGE_SIZE
+        ))
+        .header(reqwest::header::ORIGIN, "tauri://localhost")
+        .send()
+        .await
+        .map_err(|e| format!("history request failed: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("history request failed: {}", response.status()));
+    }
+    let body: serde_json::Value = response
+        .json()
+        .await
+        .map_err(|e| format!("parse history failed: {}", e))?;
+    Ok(body
+        .pointer("/data/list")
+        .and_then(|v| v.as_array())
+        .cloned()
+        .unwrap_or_default())
+}
+
+// 逐页拉取并立即写出，不在内存里攒全部历史
+async fn export(
+    port: u16,
+    dest: &Path,
+    format: ExportFormat,
+    from: Option<String>,
+    to: Option<String>,
+) -> Result<usize, String> {
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(30))
+        .build()
+        .map_err(|e| format!("build history client failed: {}", e))?;
+    let mut writer = RecordWriter::create(dest, format)?;
+    // 分页期间有新任务插入时偏移会错位，按 task_id 去重
+    let mut seen = HashSet::new();
+    let mut page = 1;
+    loop {
+        let tasks = fetch_page(&client, port, page).await?;
+        if tasks.is_empty() {
+            break;
+        }
+        let mut reached_start = false;
+        for task in &tasks {
+            let status = task.get("status").and_then(|v| v.as_str()).unwrap_or("");
+            let date: String = task
+                .get("created_at")
+                .and_then(|v| v.as_str())
+                .unwrap_or("")
+                .chars()
+                .take(10)
+                .collect();
+            if from.as_ref().is_some_and(|from| date < *from) {
+                // 排队中和进行中的任务排在最前面，其余按创建时间倒序，越过起始日期即可停止
+                if status != "pending" && status != "processing" {
+                    reached_start = true;
+                }
+                continue;
+            }
+            if to.as_ref().is_some_and(|to| date > *to) {
+                continue;
+            }
+            let id = task.get("task_id").and_then(|v| v.as_str()).unwrap_or("");
+            if !seen.insert(id.to_string()) {
+                continue;
+            }
+            writer.write(task)?;
+        }
+        if reached_start || tasks.len() < PAGE_SIZE {
+            break;
+        }
+        page += 1;
+    }
+    writer.finish()
+}
+
+// 导出生成历史到 CSV / JSONL / JSON，format 缺省时按扩展名判断；from/to 为 YYYY-MM-DD
+#[tauri::command]
+pub async fn export_history(
+    app: tauri::AppHandle,
+    path: String,
+    format: Option<String>,
+    from: Option<String>,
+    to: Option<String>,
+) -> Result<HistoryExportResult, String> {
+    let dest = PathBuf::from(path.trim());
+    if dest.as_os_str().is_empty() {
+        return Err("path is empty".to_string());
+    }
+    let format = ExportFormat::parse(format.as_deref(), &dest)?;
+    let (from, to) = (parse_date(from)?, parse_date(to)?);
+    let port = *app.state::<BackendPort>().0.lock().unwrap();
+    if port == 0 {
+        return Err("backend is not running".to_string());
+    }
+    if let Some(parent) = dest.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent).map_err(|e| format!("create destination dir failed: {}", e))?;
+    }
+    // 先写临时文件，中途失败不会留下半截的导出
+    let file_name = dest
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("history");
+    let temp = dest.with_file_name(format!("{}.part", file_name));
+    let count = match export(port, &temp, format, from, to).await {
+        Ok(count) => count,
+        Err(err) => {
+            let _ = fs::remove_file(&temp);
+            return Err(err);
+        }
+    };
+    fs::rename(&temp, &dest).map_err(|e| {
+        let _ = fs::remove_file(&temp);
+        format!("finalize export failed: {}", e)
+    })?;
+    app.state::<LogState>().log_app(
+        "INFO",
+        &format!("History exported count={} dest={}", count, dest.display()),
+    );
+    Ok(HistoryExportResult {
+        path: dest.to_string_lossy().to_string(),
+        count,
+    })
+}
@@ -0,0 +1,374 @@
+use std::io::{Read, Write};
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+use rusqlite::OptionalExtension;
+use tauri::{Emitter, Manager, State};
+
+use crate::settings::SettingsState;
+use crate::LogState;
+
+const EVENTS: [&str; 2] = ["generation_completed", "export_finished"];
+const DEFAULT_TIMEOUT_SECS: u64 = 30;
+const MAX_TIMEOUT_SECS: u64 = 600;
+// 钩子退出后，它拉起的后台进程可能仍占着输出管道，最多再等这么久
+const OUTPUT_GRACE: Duration = Duration::from_secs(2);
+// 只保留输出的开头，写日志和发事件用
+const MAX_OUTPUT_BYTES: usize = 4000;
+// 清空环境变量后仍透传的少量变量，保证脚本能找到解释器和临时目录
+const PASSTHROUGH_ENV: [&str; 8] = [
+    "PATH",
+    "HOME",
+    "USERPROFILE",
+    "SYSTEMROOT",
+    "TMPDIR",
+    "TEMP",
+    "TMP",
+    "LANG",
+];
+
+#[derive(Clone, Default, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct HookSettings {
+    pub hooks: Vec<Hook>,
+}
+
+#[derive(Clone, Default, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct Hook {
+    pub id: String,
+    pub name: String,
+    pub enabled: bool,
+    // generation_completed / export_finished
+    pub event: String,
+    // 可执行文件，不经过 shell 解析
+    pub program: String,
+    // 支持占位符 {path} {task_id} {event}
+    pub args: Vec<String>,
+    // 打开后把完整的事件 JSON 写入 stdin
+    pub stdin_json: bool,
+    pub timeout_secs: u64,
+}
+
+#[derive(Clone, serde::Serialize)]
+pub struct HookRun {
+    hook_id: String,
+    event: String,
+    success: bool,
+    exit_code: Option<i32>,
+    timed_out: bool,
+    duration_ms: u64,
+    output: String,
+    error: Option<String>,
+}
+
+fn expand(arg: &str, payload: &serde_json::Value) -> String {
+    let field = |key: &str| payload.get(key).and_then(|v| v.as_str()).unwrap_or("");
+    arg.replace("{path}", field("path"))
+        .replace("{task_id}", field("task_id"))
+        .replace("{event}", field("event"))
+}
+
+// 钩子放进独立的进程组，超时时连同它拉起的子进程一起结束
+#[cfg(unix)]
+fn isolate(command: &mut Command) {
+    use std::os::unix::process::CommandExt;
+    command.process_group(0);
+}
+
+#[cfg(windows)]
+fn isolate(command: &mut Command) {
+    crate::shutdown::hide_console(command);
+}
+
+#[cfg(unix)]
+fn kill_tree(child: &mut Child) {
+    unsafe {
+        libc::kill(-(child.id() as libc::pid_t), libc::SIGKILL);
+    }
+    let _ = child.kill();
+}
+
+// Windows 上用 taskkill /T 结束整棵进程树
+#[cfg(windows)]
+fn kill_tree(child: &mut Child) {
+    let _ = crate::shutdown::hide_console(Command::new("taskkill").args([
+        "/F",
+        "/T",
+        "/PID",
+        &child.id().to_string(),
+    ]))
+    .status();
+    let _ = child.kill();
+}
+
+fn truncate(mut text: String) -> String {
+    if text.len() > MAX_OUTPUT_BYTES {
+        let mut end = MAX_OUTPUT_BYTES;
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        text.truncate(end);
+        text.push_str("…(truncated)");
+    }
+    text
+}
+
+// 清空环境、工作目录设为临时目录后执行，超时强制结束；stdout/stderr 合并截断后返回
+fn run(hook: &Hook, payload: &serde_json::Value) -> HookRun {
+    let started = Instant::now();
+    let timeout = Duration::from_secs(
+        match hook.timeout_secs {
+            0 => DEFAULT_TIMEOUT_SECS,
+            secs => secs,
+        }
+        .min(MAX_TIMEOUT_SECS),
+    );
+    let mut command = Command::new(hook.program.trim());
+    command
+        .args(hook.args.iter().map(|a| expand(a, payload)))
+        .env_clear()
+        .envs(
+            PASSTHROUGH_ENV
+                .iter()
+                .filter_map(|k| std::env::var_os(k).map(|v| (k, v))),
+        )
+        .env("NANO_BANANA_EVENT", payload["event"].as_str().unwrap_or(""))
+        .current_dir(std::env::temp_dir())
+        .stdin(if hook.stdin_json {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    isolate(&mut command);
+    let mut result = HookRun {
+        hook_id: hook.id.clone(),
+        event: payload["event"].as_str().unwrap_or("").to_string(),
+        success: false,
+        exit_code: None,
+        timed_out: false,
+        duration_ms: 0,
+        output: String::new(),
+        error: None,
+    };
+    let mut child = match command.spawn() {
+        Ok(child) => child,
+        Err(e) => {
+            result.error = Some(format!("spawn hook failed: {}", e));
+            return result;
+        }
+    };
+    if let Some(mut stdin) = child.stdin.take() {
+        let body = serde_json::to_vec(payload).unwrap_or_default();
+        // 脚本不读 stdin 时写入会 EPIPE，忽略即可
+        std::thread::spawn(move || {
+            let _ = stdin.write_all(&body);
+        });
+    }
+    // 输出在独立线程读取，避免管道写满导致子进程阻塞；读完后按 stdout、stderr 的顺序拼接
+    let (tx, rx) = mpsc::channel();
+    let pipes: Vec<_> = [
+        child
+            .stdout
+            .take()
+            .map(|s| Box::new(s) as Box<dyn Read + Send>),
+        child
+            .stderr
+            .take()
+            .map(|s| Box::new(s) as Box<dyn Read + Send>),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+    let pipe_count = pipes.len();
+    for (index, mut pipe) in pipes.into_iter().enumerate() {
+        let tx = tx.clone();
+        std::thread::spawn(move || {
+            let mut buf = Vec::new();
+            let _ = pipe.read_to_end(&mut buf);
+            let _ = tx.send((index, buf));
+        });
+    }
+    drop(tx);
+    loop {
+        match child.try_wait() {
+            Ok(Some(status)) => {
+                result.exit_code = status.code();
+                result.success = status.success();
+                break;
+            }
+            Ok(None) if started.elapsed() >= timeout => {
+                kill_tree(&mut child);
+                let _ = child.wait();
+                result.timed_out = true;
+                result.error = Some(format!("hook timed out after {}s", timeout.as_secs()));
+                break;
+            }
+            Ok(None) => std::thread::sleep(Duration::from_millis(50)),
+            Err(e) => {
+                result.error = Some(format!("wait hook failed: {}", e));
+                break;
+            }
+        }
+    }
+    let deadline = Instant::now() + OUTPUT_GRACE;
+    let mut chunks = Vec::with_capacity(pipe_count);
+    while chunks.len() < pipe_count {
+        match rx.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+            Ok(chunk) => chunks.push(chunk),
+            Err(_) => {
+                // 管道仍被残留的后台进程占用：结束整个进程组，已读到的输出照常返回
+                kill_tree(&mut child);
+                break;
+            }
+        }
+    }
+    chunks.sort_by_key(|(index, _)| *index);
+    let output: Vec<u8> = chunks.into_iter().flat_map(|(_, buf)| buf).collect();
+    result.output = truncate(String::from_utf8_lossy(&output).trim().to_string());
+    result.duration_ms = started.elapsed().as_millis() as u64;
+    result
+}
+
+fn run_and_report(app: &tauri::AppHandle, hook: &Hook, payload: &serde_json::Value) -> HookRun {
+    let result = run(hook, payload);
+    app.state::<LogState>().log_app(
+        if result.success { "INFO" } else { "WARN" },
+        &format!(
+            "Hook finished name={} event={} success={} exit={:?} duration_ms={} error={}",
+            hook.name,
+            result.event,
+            result.success,
+            result.exit_code,
+            result.duration_ms,
+            result.error.as_deref().unwrap_or("")
+        ),
+    );
+    let _ = app.emit("hook-finished", result.clone());
+    result
+}
+
+// 触发某个事件的全部已启用钩子，每个钩子在独立线程里执行，互不阻塞
+pub fn fire(app: &tauri::AppHandle, event: &str, mut payload: serde_json::Value) {
+    let hooks: Vec<Hook> = app
+        .state::<SettingsState>()
+        .get()
+        .hooks
+        .hooks
+        .into_iter()
+        .filter(|h| h.enabled && h.event == event)
+        .collect();
+    if hooks.is_empty() {
+        return;
+    }
+    payload["event"] = serde_json::Value::from(event);
+    payload["fired_at"] = serde_json::Value::from(crate::clock::rfc3339(app));
+    for hook in hooks {
+        let app = app.clone();
+        let payload = payload.clone();
+        std::thread::spawn(move || {
+            run_and_report(&app, &hook, &payload);
+        });
+    }
+}
+
+// 生成完成时由 timings 的轮询调用，补上图片路径和任务元数据
+pub fn fire_generation_completed(app: &tauri::AppHandle, task_id: &str, total_ms: u64) {
+    if !app
+        .state::<SettingsState>()
+        .get()
+        .hooks
+        .hooks
+        .iter()
+        .any(|h| h.enabled && h.event == "generation_completed")
+    {
+        return;
+    }
+    let row = crate::db::open_readonly(app).ok().and_then(|conn| {
+        conn.query_row(
+            "SELECT local_path, prompt, provider_name, model_id FROM tasks WHERE task_id = ?1",
+            [task_id],
+            |row| {
+                Ok((
+                    row.get::<_, Option<String>>(0)?.unwrap_or_default(),
+                    row.get::<_, Option<String>>(1)?.unwrap_or_default(),
+                    row.get::<_, Option<String>>(2)?.unwrap_or_default(),
+                    row.get::<_, Option<String>>(3)?.unwrap_or_default(),
+                ))
+            },
+        )
+        .optional()
+        .ok()
+        .flatten()
+    });
+    let Some((local_path, prompt, provider, model_id)) = row else {
+        return;
+    };
+    let path = crate::storage::resolve_db_path(&crate::app_data_base(app), &local_path)
+        .map(|p| p.to_string_lossy().to_string())
+        .unwrap_or_default();
+    fire(
+        app,
+        "generation_completed",
+        serde_json::json!({
+            "task_id": task_id,
+            "path": path,
+            "prompt": prompt,
+            "provider": provider,
+            "model_id": model_id,
+            "total_ms": total_ms,
+        }),
+    );
+}
+
+fn validate(hook: &Hook) -> Result<(), String> {
+    if hook.id.trim().is_empty() {
+        return Err("hook id is empty".to_string());
+    }
+    if hook.program.trim().is_empty() {
+        return Err(format!("hook {} has no program", hook.name));
+    }
+    if !EVENTS.contains(&hook.event.as_str()) {
+        return Err(format!("unknown hook event: {}", hook.event));
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_hook_settings(state: State<'_, SettingsState>) -> HookSettings {
+    state.get().hooks
+}
+
+#[tauri::command]
+pub fn set_hook_settings(
+    state: State<'_, SettingsState>,
+    settings: HookSettings,
+) -> Result<HookSettings, String> {
+    for hook in &settings.hooks {
+        validate(hook)?;
+    }
+    Ok(state.update(|s| s.hooks = settings)?.hooks)
+}
+
+// 设置页“试运行”：用给定图片路径构造示例事件，同步返回执行结果
+#[tauri::command]
+pub async fn test_hook(
+    app: tauri::AppHandle,
+    hook: Hook,
+    path: Option<String>,
+) -> Result<HookRun, String> {
+    validate(&hook)?;
+    let payload = serde_json::json!({
+        "event": hook.event,
+        "task_id": "test",
+        "path": path.unwrap_or_default(),
+        "fired_at": crate::clock::rfc3339(&app),
+        "test": true,
+    });
+    tauri::async_runtime::spawn_blocking(move || run_and_report(&app, &hook, &payload))
+        .await
+        .map_err(|e| format!("test hook failed: {}", e))
+}
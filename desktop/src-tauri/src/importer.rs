@@ -0,0 +1,433 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::{Cursor, Read};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use tauri::{Emitter, Manager};
+
+use crate::{BackendPort, LogState};
+
+const IMAGE_EXTENSIONS: [&str; 5] = ["png", "jpg", "jpeg", "webp", "gif"];
+// 单张图片上限，防止异常条目把内存撑爆
+const MAX_ENTRY_BYTES: u64 = 64 * 1024 * 1024;
+// 元数据 JSON 里可能出现的提示词字段（Midjourney 导出工具常用 full_command，DALL·E 用 prompt）
+const PROMPT_KEYS: [&str; 4] = ["full_command", "prompt", "revised_prompt", "caption"];
+const TIME_KEYS: [&str; 4] = ["created_at", "created", "enqueue_time", "timestamp"];
+
+#[derive(Clone, serde::Serialize)]
+pub struct ImportFailure {
+    name: String,
+    reason: String,
+}
+
+#[derive(serde::Serialize)]
+pub struct ImportResult {
+    imported: usize,
+    skipped: usize,
+    failed: Vec<ImportFailure>,
+}
+
+#[derive(Clone, serde::Serialize)]
+struct ImportProgress {
+    done: usize,
+    total: usize,
+}
+
+#[derive(Clone, Default)]
+struct ImageMeta {
+    prompt: String,
+    created_at: Option<String>,
+}
+
+// 已复制进存储目录、等待登记到后端的图片
+struct Candidate {
+    name: String,
+    dest: PathBuf,
+    local_path: String,
+    provider: String,
+    meta: ImageMeta,
+}
+
+fn is_uuid_like(s: &str) -> bool {
+    s.len() == 36
+        && s.chars().enumerate().all(|(i, c)| match i {
+            8 | 13 | 18 | 23 => c == '-',
+            _ => c.is_ascii_hexdigit(),
+        })
+}
+
+fn json_time(value: &serde_json::Value) -> Option<String> {
+    match value {
+        serde_json::Value::String(s) if !s.trim().is_empty() => Some(s.trim().to_string()),
+        serde_json::Value::Number(n) => n
+            .as_i64()
+            .or_else(|| n.as_f64().map(|f| f as i64))
+            .map(|n| n.to_string()),
+        _ => None,
+    }
+}
+
+// 递归扫描 JSON：带提示词字段的对象，把其中所有字符串值（文件名、id、路径）都作为关联键
+fn collect_json_meta(value: &serde_json::Value, out: &mut HashMap<String, ImageMeta>) {
+    match value {
+        serde_json::Value::Array(items) => {
+            for item in items {
+                collect_json_meta(item, out);
+            }
+        }
+        serde_json::Value::Object(map) => {
+            let prompt = PROMPT_KEYS
+                .iter()
+                .find_map(|k| map.get(*k).and_then(|v| v.as_str()))
+                .map(str::trim)
+                .filter(|p| !p.is_empty());
+            if let Some(prompt) = prompt {
+                let meta = ImageMeta {
+                    prompt: prompt.to_string(),
+                    created_at: TIME_KEYS
+                        .iter()
+                        .find_map(|k| map.get(*k).and_then(json_time)),
+                };
+                let mut keys = Vec::new();
+                for v in map.values() {
+                    match v {
+                        serde_json::Value::String(s) => keys.push(s.as_str()),
+                        serde_json::Value::Array(items) => {
+                            keys.extend(items.iter().filter_map(|i| i.as_str()))
+                        }
+                        _ => {}
+                    }
+                }
+                for key in keys {
+                    let stem = file_stem(key).to_lowercase();
+                    // 太短的值（如 "png"、版本号）容易误匹配
+                    if stem.len() >= 8 && stem != prompt.to_lowercase() {
+                        out.entry(stem).or_insert_with(|| meta.clone());
+                    }
+                }
+            }
+            for v in map.values() {
+                collect_json_meta(v, out);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn file_stem(name: &str) -> &str {
+    let base = name.rsplit(['/', '\\']).next().unwrap_or(name);
+    match base.rfind('.') {
+        Some(i) if i > 0 => &base[..i],
+        _ => base,
+    }
+}
+
+fn lookup_json_meta(stem: &str, metas: &HashMap<String, ImageMeta>) -> Option<ImageMeta> {
+    let stem = stem.to_lowercase();
+    if let Some(meta) = metas.get(&stem) {
+        return Some(meta.clone());
+    }
+    // Midjourney 文件名里带 job id，元数据里通常只有 id
+    metas
+        .iter()
+        .filter(|(k, _)| stem.contains(k.as_str()))
+        .max_by_key(|(k, _)| k.len())
+        .map(|(_, m)| m.clone())
+}
+
+// Midjourney 下载的 PNG 在 tEXt 里写了 Description（提示词 + Job ID）
+fn png_text_prompt(bytes: &[u8]) -> Option<String> {
+    let decoder = png::Decoder::new(Cursor::new(bytes));
+    let reader = decoder.read_info().ok()?;
+    let info = reader.info();
+    let texts = info
+        .uncompressed_latin1_text
+        .iter()
+        .map(|t| (t.keyword.as_str(), t.text.clone()))
+        .chain(
+            info.utf8_text
+                .iter()
+                .filter_map(|t| t.get_text().ok().map(|text| (t.keyword.as_str(), text))),
+        );
+    for (keyword, text) in texts {
+        if !matches!(keyword, "Description" | "prompt" | "parameters" | "Comment") {
+            continue;
+        }
+        let prompt = text.split("Job ID:").next().unwrap_or("").trim();
+        if !prompt.is_empty() {
+            return Some(prompt.to_string());
+        }
+    }
+    None
+}
+
+// 文件名兜底：DALL·E 2023-10-12 14.33.21 - prompt.png / user_prompt_words_<uuid>.png
+fn parse_filename(stem: &str) -> (Option<&'static str>, ImageMeta) {
+    for prefix in ["DALL·E ", "DALL-E ", "DALLE "] {
+        if let Some(rest) = stem.strip_prefix(prefix) {
+            let (time, prompt) = rest.split_once(" - ").unwrap_or(("", rest));
+            return (
+                Some("dalle"),
+                ImageMeta {
+                    prompt: prompt.trim().to_string(),
+                    created_at: (!time.trim().is_empty()).then(|| time.trim().to_string()),
+                },
+            );
+        }
+    }
+    let parts: Vec<&str> = stem.split('_').collect();
+    if parts.len() >= 3 && is_uuid_like(parts[parts.len() - 1]) {
+        return (
+            Some("midjourney"),
+            ImageMeta {
+                prompt: parts[1..parts.len() - 1].join(" "),
+                created_at: None,
+            },
+        );
+    }
+    (None, ImageMeta::default())
+}
+
+fn skip_entry(name: &str) -> bool {
+    name.starts_with("__MACOSX/")
+        || name
+            .rsplit('/')
+            .next()
+            .map(|base| base.starts_with('.'))
+            .unwrap_or(true)
+}
+
+// 解压阶段：读取 JSON 元数据，把图片复制进存储目录，返回待登记列表
+fn extract_archive(
+    app: &tauri::AppHandle,
+    archive_path: &Path,
+    source: Option<&str>,
+    skipped: &mut usize,
+    failed: &mut Vec<ImportFailure>,
+) -> Result<Vec<Candidate>, String> {
+    let file = fs::File::open(archive_path).map_err(|e| format!("open archive failed: {}", e))?;
+    let mut archive =
+        zip::ZipArchive::new(file).map_err(|e| format!("read archive failed: {}", e))?;
+
+    let mut metas = HashMap::new();
+    let mut images = Vec::new();
+    for i in 0..archive.len() {
+        let Ok(mut entry) = archive.by_index(i) else {
+            continue;
+        };
+        if entry.is_dir() || skip_entry(entry.name()) {
+            continue;
+        }
+        let name = entry.name().to_string();
+        let ext = Path::new(&name)
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_ascii_lowercase())
+            .unwrap_or_default();
+        if ext == "json" {
+            let mut text = String::new();
+            if entry.read_to_string(&mut text).is_ok() {
+                if let Ok(value) = serde_json::from_str::<serde_json::Value>(&text) {
+                    collect_json_meta(&value, &mut metas);
+                }
+            }
+        } else if IMAGE_EXTENSIONS.contains(&ext.as_str()) {
+            images.push((i, name, ext));
+        } else {
+            *skipped += 1;
+        }
+    }
+
+    // 后端按自己的工作目录解析 storage/<file>，这里必须写到同一个目录（Linux 下不是 app_data_dir）
+    let storage_dir = crate::paths::storage_dir(app);
+    debug_assert!(storage_dir.starts_with(crate::paths::backend_data_dir(app)));
+    fs::create_dir_all(&storage_dir).map_err(|e| format!("create storage dir failed: {}", e))?;
+    let batch = crate::now_ms();
+
+    let mut candidates = Vec::new();
+    for (seq, (index, name, ext)) in images.into_iter().enumerate() {
+        let mut entry = match archive.by_index(index) {
+            Ok(entry) => entry,
+            Err(e) => {
+                failed.push(ImportFailure {
+                    name,
+                    reason: format!("read entry failed: {}", e),
+                });
+                continue;
+            }
+        };
+        if entry.size() > MAX_ENTRY_BYTES {
+            failed.push(ImportFailure {
+                name,
+                reason: "file too large".to_string(),
+            });
+            continue;
+        }
+        let mut bytes = Vec::with_capacity(entry.size() as usize);
+        if let Err(e) = entry.read_to_end(&mut bytes) {
+            failed.push(ImportFailure {
+                name,
+                reason: format!("read entry failed: {}", e),
+            });
+            continue;
+        }
+        drop(entry);
+
+        let stem = file_stem(&name);
+        let (detected, from_name) = parse_filename(stem);
+        let meta = lookup_json_meta(stem, &metas)
+            .or_else(|| {
+                (ext == "png")
+                    .then(|| png_text_prompt(&bytes))
+                    .flatten()
+                    .map(|prompt| ImageMeta {
+                        prompt,
+                        created_at: from_name.created_at.clone(),
+                    })
+            })
+            .unwrap_or_else(|| ImageMeta {
+                prompt: if from_name.prompt.is_empty() {
+                    stem.to_string()
+                } else {
+                    from_name.prompt.clone()
+                },
+                created_at: from_name.created_at.clone(),
+            });
+        let provider = source.or(detected).unwrap_or("import").to_string();
+
+        let file_name = format!("import_{}_{}.{}", batch, seq, ext);
+        let dest = storage_dir.join(&file_name);
+        let written = crate::disk::ensure_free_space(&dest, bytes.len() as u64).and_then(|_| {
+            fs::write(&dest, &bytes).map_err(|e| format!("write image failed: {}", e))
+        });
+        if let Err(reason) = written {
+            let _ = fs::remove_file(&dest);
+            failed.push(ImportFailure { name, reason });
+            continue;
+        }
+        candidates.push(Candidate {
+            name,
+            dest,
+            // 相对 sidecar 工作目录（paths::backend_data_dir），保持和生成结果一样的路径形式
+            local_path: format!("storage/{}", file_name),
+            provider,
+            meta,
+        });
+    }
+    Ok(candidates)
+}
+
+async fn register_image(
+    client: &reqwest::Client,
+    port: u16,
+    candidate: &Candidate,
+) -> Result<(), String> {
+    let response = client
+        .post(format!("http://127.0.0.1:{}/api/v1/images/import", port))
+        .header(reqwest::header::ORIGIN, "tauri://localhost")
+        .json(&serde_json::json!({
+            "local_path": candidate.local_path,
+            "prompt": candidate.meta.prompt,
+            "provider_name": candidate.provider,
+            "created_at": candidate.meta.created_at.clone().unwrap_or_default(),
+        }))
+        .send()
+        .await
+        .map_err(|e| format!("register request failed: {}", e))?;
+    if response.status().is_success() {
+        return Ok(());
+    }
+    let status = response.status();
+    let message = response
+        .json::<serde_json::Value>()
+        .await
+        .ok()
+        .and_then(|v| {
+            v.get("message")
+                .and_then(|m| m.as_str())
+                .map(str::to_string)
+        })
+        .unwrap_or_default();
+    Err(format!("register request failed: {} {}", status, message))
+}
+
+// 导入 Midjourney / DALL·E 导出的 ZIP：提示词优先取元数据 JSON，其次 PNG 文本块，最后解析文件名
+// source 可指定来源（midjourney / dalle），不传时按文件名自动判断
+#[tauri::command]
+pub async fn import_archive(
+    app: tauri::AppHandle,
+    path: String,
+    source: Option<String>,
+) -> Result<ImportResult, String> {
+    crate::remote_backend::ensure_local(&app)?;
+    if crate::safe_mode::is_read_only(&app) {
+        return Err("database is in read-only safe mode".to_string());
+    }
+    let port = *app.state::<BackendPort>().0.lock().unwrap();
+    if port == 0 {
+        return Err("backend is not running".to_string());
+    }
+
+    let archive_path = PathBuf::from(path.trim());
+    let app_for_task = app.clone();
+    let (candidates, skipped, mut failed) = tauri::async_runtime::spawn_blocking(move || {
+        let mut skipped = 0;
+        let mut failed = Vec::new();
+        extract_archive(
+            &app_for_task,
+            &archive_path,
+            source.as_deref(),
+            &mut skipped,
+            &mut failed,
+        )
+        .map(|c| (c, skipped, failed))
+    })
+    .await
+    .map_err(|e| format!("import task failed: {}", e))??;
+
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(30))
+        .build()
+        .map_err(|e| format!("build import client failed: {}", e))?;
+    let total = candidates.len();
+    let mut imported = 0;
+    for (done, candidate) in candidates.iter().enumerate() {
+        match register_image(&client, port, candidate).await {
+            Ok(()) => imported += 1,
+            Err(reason) => {
+                // 登记失败就删掉已复制的文件，避免存储目录里出现孤儿图片
+                let _ = fs::remove_file(&candidate.dest);
+                failed.push(ImportFailure {
+                    name: candidate.name.clone(),
+                    reason,
+                });
+            }
+        }
+        let _ = app.emit(
+            "import-progress",
+            ImportProgress {
+                done: done + 1,
+                total,
+            },
+        );
+    }
+
+    app.state::<LogState>().log_app(
+        "INFO",
+        &format!(
+            "Archive import finished imported={} skipped={} failed={}",
+            imported,
+            skipped,
+            failed.len()
+        ),
+    );
+    if imported > 0 {
+        crate::search::notify();
+    }
+    Ok(ImportResult {
+        imported,
+        skipped,
+        failed,
+    })
+}
@@ -0,0 +1,86 @@
+use tauri::Manager;
+
+// 先把主窗口拉到前台，系统面板才会把字符插入到 webview 当前聚焦的输入框
+fn focus_main_window(app: &tauri::AppHandle) -> Result<(), String> {
+    let window = app
+        .get_webview_window("main")
+        .ok_or_else(|| "main window not found".to_string())?;
+    let _ = window.show();
+    window
+        .set_focus()
+        .map_err(|e| format!("focus window failed: {}", e))
+}
+
+#[cfg(target_os = "macos")]
+fn open_character_palette(app: &tauri::AppHandle) -> Result<(), String> {
+    use std::sync::mpsc;
+
+    // AppKit 调用必须在主线程
+    let (tx, rx) = mpsc::channel::<Result<(), String>>();
+    app.run_on_main_thread(move || {
+        let result = match objc2::MainThreadMarker::new() {
+            Some(mtm) => {
+                objc2_app_kit::NSApplication::sharedApplication(mtm)
+                    .orderFrontCharacterPalette(None);
+                Ok(())
+            }
+            None => Err("not on main thread".to_string()),
+        };
+        let _ = tx.send(result);
+    })
+    .map_err(|e| format!("run_on_main_thread failed: {}", e))?;
+
+    rx.recv()
+        .map_err(|_| "emoji picker task aborted".to_string())?
+}
+
+#[cfg(target_os = "windows")]
+fn open_character_palette(_app: &tauri::AppHandle) -> Result<(), String> {
+    use windows_sys::Win32::UI::Input::KeyboardAndMouse::{
+        SendInput, INPUT, INPUT_0, INPUT_KEYBOARD, KEYBDINPUT, KEYEVENTF_KEYUP, VK_LWIN,
+        VK_OEM_PERIOD,
+    };
+
+    // Windows 没有公开的拉起表情面板 API，只能模拟 Win + . 组合键
+    let key = |vk: u16, flags: u32| INPUT {
+        r#type: INPUT_KEYBOARD,
+        Anonymous: INPUT_0 {
+            ki: KEYBDINPUT {
+                wVk: vk,
+                wScan: 0,
+                dwFlags: flags,
+                time: 0,
+                dwExtraInfo: 0,
+            },
+        },
+    };
+    let inputs = [
+        key(VK_LWIN, 0),
+        key(VK_OEM_PERIOD, 0),
+        key(VK_OEM_PERIOD, KEYEVENTF_KEYUP),
+        key(VK_LWIN, KEYEVENTF_KEYUP),
+    ];
+    let sent = unsafe {
+        SendInput(
+            inputs.len() as u32,
+            inputs.as_ptr(),
+            std::mem::size_of::<INPUT>() as i32,
+        )
+    };
+    if sent as usize != inputs.len() {
+        return Err("send emoji shortcut failed".to_string());
+    }
+    Ok(())
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn open_character_palette(_app: &tauri::AppHandle) -> Result<(), String> {
+    Err("emoji picker is not supported on this platform".to_string())
+}
+
+// 拉起系统表情与符号面板（macOS 字符检视器 / Windows Win+.），打包版 webview 不一定暴露该入口
+#[tauri::command]
+pub fn show_emoji_picker(app: tauri::AppHandle) -> Result<(), String> {
+    focus_main_window(&app)?;
+    open_character_palette(&app)
+}
@@ -0,0 +1,272 @@
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use crate::proxy::ProxyRequest;
+
+// 壳层本机接口挂在代理端口下，需要 instance.json 里的 token 才能调用
+pub const IPC_PREFIX: &str = "/__shell/";
+const TOKEN_HEADER: &str = "x-shell-token";
+const RERUN_ARG: &str = "--rerun-prompt=";
+// 与 tauri.conf.json 的 identifier 一致
+const IDENTIFIER: &str = "com.dztool.banana";
+
+static TOKEN: OnceLock<String> = OnceLock::new();
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct InstanceInfo {
+    pid: u32,
+    port: u16,
+    token: String,
+}
+
+// 第二个进程在 Builder 之前就要读到，拿不到 AppHandle，按平台规则拼出当前用户私有的目录；
+// 不能放共享的 /tmp：token 能调用重跑提示词（消耗额度）、读取日志等接口
+fn instance_dir() -> Result<PathBuf, String> {
+    let env_dir = |key: &str| {
+        std::env::var_os(key)
+            .map(PathBuf::from)
+            .filter(|p| p.is_absolute())
+    };
+    let base = if cfg!(windows) {
+        env_dir("LOCALAPPDATA")
+    } else if cfg!(target_os = "macos") {
+        env_dir("HOME").map(|home| home.join("Library").join("Application Support"))
+    } else {
+        env_dir("XDG_RUNTIME_DIR")
+            .or_else(|| env_dir("XDG_DATA_HOME"))
+            .or_else(|| env_dir("HOME").map(|home| home.join(".local").join("share")))
+    };
+    base.map(|base| base.join(IDENTIFIER))
+        .ok_or_else(|| "no per-user directory for instance info".to_string())
+}
+
+// 目录只允许当前用户访问；已存在但属于其他用户（或是符号链接）时拒绝使用，避免被人预先创建后劫持
+#[cfg(unix)]
+fn ensure_private_dir(dir: &Path) -> Result<(), String> {
+    use std::os::unix::fs::{DirBuilderExt, MetadataExt, PermissionsExt};
+
+    std::fs::DirBuilder::new()
+        .recursive(true)
+        .mode(0o700)
+        .create(dir)
+        .map_err(|e| format!("create instance dir failed: {}", e))?;
+    let meta =
+        std::fs::symlink_metadata(dir).map_err(|e| format!("read instance dir failed: {}", e))?;
+    if !meta.is_dir() || meta.uid() != unsafe { libc::getuid() } {
+        return Err(format!(
+            "instance dir is not owned by current user: {}",
+            dir.display()
+        ));
+    }
+    if meta.mode() & 0o077 != 0 {
+        std::fs::set_permissions(dir, std::fs::Permissions::from_mode(0o700))
+            .map_err(|e| format!("restrict instance dir failed: {}", e))?;
+    }
+    Ok(())
+}
+
+// Windows 的 LOCALAPPDATA 默认只有当前用户可访问
+#[cfg(not(unix))]
+fn ensure_private_dir(dir: &Path) -> Result<(), String> {
+    std::fs::create_dir_all(dir).map_err(|e| format!("create instance dir failed: {}", e))
+}
+
+fn instance_path() -> Result<PathBuf, String> {
+    let dir = instance_dir()?;
+    ensure_private_dir(&dir)?;
+    Ok(dir.join("instance.json"))
+}
+
+// 先写 0600 的临时文件再改名，文件从创建起就只有当前用户能读
+fn write_private(path: &Path, info: &InstanceInfo) -> Result<(), String> {
+    let bytes =
+        serde_json::to_vec_pretty(info).map_err(|e| format!("serialize json failed: {}", e))?;
+    let tmp = path.with_extension("json.tmp");
+    let _ = std::fs::remove_file(&tmp);
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options
+        .open(&tmp)
+        .map_err(|e| format!("write json failed: {}", e))?;
+    file.write_all(&bytes)
+        .map_err(|e| format!("write json failed: {}", e))?;
+    drop(file);
+    std::fs::rename(&tmp, path).map_err(|e| format!("finalize json failed: {}", e))
+}
+
+// 代理启动后调用，记录本实例的本机接口地址
+pub fn publish(port: u16) -> Result<(), String> {
+    let token = match TOKEN.get() {
+        Some(token) => token.clone(),
+        None => {
+            let token = random_token()?;
+            TOKEN.get_or_init(|| token).clone()
+        }
+    };
+    write_private(
+        &instance_path()?,
+        &InstanceInfo {
+            pid: std::process::id(),
+            port,
+            token,
+        },
+    )
+}
+
+// 退出时只删除自己写的文件，避免误删后启动实例的记录
+pub fn unpublish() {
+    let Ok(path) = instance_path() else {
+        return;
+    };
+    let info: Option<InstanceInfo> = std::fs::read(&path)
+        .ok()
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok());
+    if info.is_some_and(|i| i.pid == std::process::id()) {
+        let _ = std::fs::remove_file(path);
+    }
+}
+
+fn query_param<'a>(query: &'a str, key: &str) -> Option<&'a str> {
+    query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(k, _)| *k == key)
+        .map(|(_, v)| v)
+}
+
+pub fn handle(app: &tauri::AppHandle, request: &ProxyRequest) -> (u16, serde_json::Value) {
+    let authorized = TOKEN.get().is_some_and(|token| {
+        request
+            .header(TOKEN_HEADER)
+            .is_some_and(|given| constant_time_eq(given.as_bytes(), token.as_bytes()))
+    });
+    if !authorized {
+        return (403, serde_json::json!({ "error": "forbidden" }));
+    }
+    let route = &request.path[IPC_PREFIX.len()..];
+    match (request.method.as_str(), route) {
+        ("GET", "status") => (200, crate::cli::status_json(app)),
+        ("GET", "logs") => {
+            let source = query_param(&request.query, "source").unwrap_or("app");
+            let offset = query_param(&request.query, "offset").and_then(|o| o.parse().ok());
+            match crate::cli::log_chunk(app, source, offset) {
+                Ok(chunk) => (200, chunk),
+                Err(err) => (400, serde_json::json!({ "error": err })),
+            }
+        }
+        ("POST", "rerun-prompt") => {
+            let Some(id) = query_param(&request.query, "id").filter(|id| valid_id(id)) else {
+                return (400, serde_json::json!({ "error": "invalid id" }));
+            };
+            crate::history::spawn_rerun(app, id.to_string());
+            (200, serde_json::json!({ "ok": true }))
+        }
+        _ => (404, serde_json::json!({ "error": "not found" })),
+    }
+}
+
+// 本机接口、浏览器会话、分享链接共用：取不到系统随机数时直接报错，不能退回固定值
+pub fn random_bytes<const N: usize>() -> Result<[u8; N], String> {
+    let mut bytes = [0u8; N];
+    getrandom::fill(&mut bytes).map_err(|e| format!("generate token failed: {}", e))?;
+    Ok(bytes)
+}
+
+pub fn random_token() -> Result<String, String> {
+    Ok(random_bytes::<16>()?
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect())
+}
+
+// 比较耗时与第一个不同字节的位置无关
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+fn valid_id(id: &str) -> bool {
+    !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric())
+}
+
+fn read_info() -> Option<InstanceInfo> {
+    std::fs::read(instance_path().ok()?)
+        .ok()
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+}
+
+fn request(info: &InstanceInfo, method: &str, target: &str) -> Result<(u16, String), String> {
+    let mut stream =
+        TcpStream::connect_timeout(&([127, 0, 0, 1], info.port).into(), Duration::from_secs(2))
+            .map_err(|e| format!("connect running instance failed: {}", e))?;
+    let _ = stream.set_read_timeout(Some(Duration::from_secs(5)));
+    let request = format!(
+        "{} {}{} HTTP/1.1\r\nHost: 127.0.0.1\r\n{}: {}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+        method, IPC_PREFIX, target, TOKEN_HEADER, info.token
+    );
+    stream
+        .write_all(request.as_bytes())
+        .map_err(|e| format!("write running instance failed: {}", e))?;
+    let mut response = String::new();
+    let _ = stream.read_to_string(&mut response);
+    let status = response
+        .split_whitespace()
+        .nth(1)
+        .and_then(|code| code.parse().ok())
+        .ok_or_else(|| "invalid response from running instance".to_string())?;
+    let body = response
+        .split_once("\r\n\r\n")
+        .map(|(_, body)| body.to_string())
+        .unwrap_or_default();
+    Ok((status, body))
+}
+
+// 已运行实例的本机端口（代理端口），批处理据此复用它的后端
+pub fn running_port() -> Option<u16> {
+    read_info().map(|info| info.port)
+}
+
+// 命令行子命令（见 cli.rs）调用已运行实例的本机接口，非 2xx 时返回接口给出的错误信息
+pub fn call(method: &str, target: &str) -> Result<serde_json::Value, String> {
+    let info = read_info().ok_or_else(|| "no running instance found".to_string())?;
+    let (status, body) = request(&info, method, target)?;
+    let value: serde_json::Value = serde_json::from_str(&body)
+        .map_err(|e| format!("parse running instance response failed: {}", e))?;
+    if !(200..300).contains(&status) {
+        let message = value["error"].as_str().unwrap_or("request failed");
+        return Err(format!("running instance returned {}: {}", status, message));
+    }
+    Ok(value)
+}
+
+// 处理跳转列表等带参数的启动：已有实例在运行时转发过去并返回 true，当前进程应直接退出；
+// 否则记下待办，正常启动后再执行
+pub fn forward_launch_args() -> bool {
+    let Some(id) = std::env::args().find_map(|arg| arg.strip_prefix(RERUN_ARG).map(String::from))
+    else {
+        return false;
+    };
+    if !valid_id(&id) {
+        return false;
+    }
+    if let Some(info) = read_info() {
+        let forwarded = request(&info, "POST", &format!("rerun-prompt?id={}", id));
+        if forwarded.is_ok_and(|(status, _)| status == 200) {
+            return true;
+        }
+    }
+    crate::history::set_pending_rerun(id);
+    false
+}
+
+#[cfg_attr(not(windows), allow(dead_code))]
+pub fn rerun_arg(id: &str) -> String {
+    format!("{}{}", RERUN_ARG, id)
+}
@@ -0,0 +1,203 @@
+use std::collections::{BTreeSet, HashMap};
+use std::path::Path;
+use std::sync::{Mutex, OnceLock};
+
+use rusqlite::OptionalExtension;
+use tauri::{Emitter, Manager};
+
+use crate::proxy::ProxyRequest;
+use crate::LogState;
+
+// 后端给 /storage 设置了一年的强缓存，文件被替换后 webview 会一直显示旧图；
+// 代理改写为 no-cache + ETag，ETag 带上本进程的起点和每个文件的失效版本
+const STORAGE_PREFIX: &str = "/storage/";
+
+static EPOCH: OnceLock<u128> = OnceLock::new();
+static VERSIONS: Mutex<Option<HashMap<String, u64>>> = Mutex::new(None);
+
+// 代理在转发修改类请求之前查出受影响的图片，请求成功后再失效
+pub struct StaleImages {
+    task_ids: Vec<String>,
+    urls: Vec<String>,
+}
+
+#[derive(Clone, serde::Serialize)]
+struct InvalidatedPayload {
+    task_ids: Vec<String>,
+    urls: Vec<String>,
+    // delete / relocate / redownload / reorganize
+    reason: String,
+}
+
+// /storage/ 下的请求才有 ETag；去掉查询串，前端加的时间戳参数不影响版本
+pub fn etag(path: &str) -> Option<String> {
+    if !path.starts_with(STORAGE_PREFIX) {
+        return None;
+    }
+    let epoch = *EPOCH.get_or_init(crate::now_ms);
+    let version = VERSIONS
+        .lock()
+        .unwrap()
+        .as_ref()
+        .and_then(|m| m.get(path).copied())
+        .unwrap_or(0);
+    Some(format!("W/\"{}-{}\"", epoch, version))
+}
+
+// 替换响应头里的缓存策略；首包里没有完整响应头时原样返回
+pub fn rewrite_head(head: &[u8], etag: &str) -> Vec<u8> {
+    let Some(end) = head.windows(4).position(|w| w == b"\r\n\r\n") else {
+        return head.to_vec();
+    };
+    let text = String::from_utf8_lossy(&head[..end]);
+    let mut out = String::new();
+    for line in text.split("\r\n") {
+        let name = line.split(':').next().unwrap_or("").trim();
+        if name.eq_ignore_ascii_case("cache-control") || name.eq_ignore_ascii_case("etag") {
+            continue;
+        }
+        out.push_str(line);
+        out.push_str("\r\n");
+    }
+    out.push_str(&format!(
+        "Cache-Control: no-cache\r\nETag: {}\r\n\r\n",
+        etag
+    ));
+    let mut bytes = out.into_bytes();
+    bytes.extend_from_slice(&head[end + 4..]);
+    bytes
+}
+
+// 存储目录内的文件对应的访问路径 /storage/<相对路径>
+pub fn url_for_file(app: &tauri::AppHandle, path: &Path) -> Option<String> {
+    let storage = crate::paths::storage_dir(app);
+    let storage = storage.canonicalize().unwrap_or(storage);
+    let path = path
+        .parent()
+        .and_then(|p| p.canonicalize().ok())
+        .zip(path.file_name())
+        .map(|(p, n)| p.join(n))
+        .unwrap_or_else(|| path.to_path_buf());
+    let relative = path.strip_prefix(&storage).ok()?;
+    let parts: Vec<String> = relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().to_string())
+        .collect();
+    Some(format!("{}{}", STORAGE_PREFIX, parts.join("/")))
+}
+
+// 数据库里的 image_url 可能是完整地址，只取 /storage/ 之后的部分
+fn url_from_field(raw: &str) -> Option<String> {
+    let start = raw.find(STORAGE_PREFIX)?;
+    let url = &raw[start..];
+    Some(url.split(['?', '#']).next().unwrap_or(url).to_string())
+}
+
+fn urls_for_tasks(app: &tauri::AppHandle, task_ids: &[String]) -> Vec<String> {
+    let mut urls = BTreeSet::new();
+    let Ok(conn) = crate::db::open_readonly(app) else {
+        return Vec::new();
+    };
+    let base = crate::app_data_base(app);
+    for id in task_ids {
+        let row = conn
+            .query_row(
+                "SELECT local_path, image_url, thumbnail_path, thumbnail_url \
+                 FROM tasks WHERE task_id = ?1",
+                [id],
+                |row| {
+                    Ok([
+                        row.get::<_, Option<String>>(0)?.unwrap_or_default(),
+                        row.get::<_, Option<String>>(1)?.unwrap_or_default(),
+                        row.get::<_, Option<String>>(2)?.unwrap_or_default(),
+                        row.get::<_, Option<String>>(3)?.unwrap_or_default(),
+                    ])
+                },
+            )
+            .optional()
+            .ok()
+            .flatten();
+        let Some([local, image_url, thumb, thumb_url]) = row else {
+            continue;
+        };
+        for path in [local, thumb] {
+            if let Some(url) =
+                crate::storage::resolve_db_path(&base, &path).and_then(|p| url_for_file(app, &p))
+            {
+                urls.insert(url);
+            }
+        }
+        urls.extend(
+            [image_url, thumb_url]
+                .iter()
+                .filter_map(|u| url_from_field(u)),
+        );
+    }
+    urls.into_iter().collect()
+}
+
+// 删除和重定位会让旧地址失效；其余请求返回 None，不查数据库
+pub fn lookup(app: &tauri::AppHandle, request: &ProxyRequest) -> Option<StaleImages> {
+    let task_ids: Vec<String> = match request.method.as_str() {
+        "DELETE" => vec![request
+            .path
+            .strip_prefix("/api/v1/images/")
+            .filter(|id| !id.is_empty() && !id.contains('/'))?
+            .to_string()],
+        "POST" if request.path == "/api/v1/images/relocate" => {
+            let json: serde_json::Value = serde_json::from_slice(&request.body).ok()?;
+            json.get("items")?
+                .as_array()?
+                .iter()
+                .filter_map(|item| item.get("task_id")?.as_str().map(|s| s.to_string()))
+                .collect()
+        }
+        _ => return None,
+    };
+    if task_ids.is_empty() {
+        return None;
+    }
+    let urls = urls_for_tasks(app, &task_ids);
+    Some(StaleImages { task_ids, urls })
+}
+
+pub fn invalidate(app: &tauri::AppHandle, task_ids: Vec<String>, urls: Vec<String>, reason: &str) {
+    if task_ids.is_empty() && urls.is_empty() {
+        return;
+    }
+    {
+        let mut versions = VERSIONS.lock().unwrap();
+        let versions = versions.get_or_insert_with(HashMap::new);
+        for url in &urls {
+            *versions.entry(url.clone()).or_insert(0) += 1;
+        }
+    }
+    app.state::<LogState>().log_app(
+        "INFO",
+        &format!(
+            "Image cache invalidated reason={} tasks={} urls={}",
+            reason,
+            task_ids.len(),
+            urls.len()
+        ),
+    );
+    // 前端据此清理缩略图缓存并重新加载仍在显示的图片
+    let _ = app.emit(
+        "images-invalidated",
+        InvalidatedPayload {
+            task_ids,
+            urls,
+            reason: reason.to_string(),
+        },
+    );
+}
+
+// 代理在修改类请求成功后调用
+pub fn apply(app: &tauri::AppHandle, request: &ProxyRequest, stale: StaleImages) {
+    let reason = if request.method == "DELETE" {
+        "delete"
+    } else {
+        "relocate"
+    };
+    invalidate(app, stale.task_ids, stale.urls, reason);
+}
@@ -0,0 +1,73 @@
+#[cfg(windows)]
+use tauri::Manager;
+
+#[cfg(windows)]
+use crate::LogState;
+
+// Windows 任务栏跳转列表：每条最近提示词对应一个带 --rerun-prompt 参数的快捷方式，
+// 点击后由新进程转发给正在运行的实例（见 instance.rs）
+#[cfg(windows)]
+pub fn update(app: &tauri::AppHandle) {
+    let prompts = crate::history::list(app);
+    let app = app.clone();
+    // COM 调用放到独立线程，避免影响主线程的套间模型
+    std::thread::spawn(move || {
+        if let Err(err) = unsafe { rebuild(&prompts) } {
+            app.state::<LogState>()
+                .log_app("WARN", &format!("update jump list failed: {}", err));
+        }
+    });
+}
+
+#[cfg(not(windows))]
+pub fn update(_app: &tauri::AppHandle) {}
+
+#[cfg(windows)]
+unsafe fn rebuild(prompts: &[crate::history::RecentPrompt]) -> windows::core::Result<()> {
+    use windows::core::{Interface, HSTRING};
+    use windows::Win32::Foundation::E_FAIL;
+    use windows::Win32::Storage::EnhancedStorage::PKEY_Title;
+    use windows::Win32::System::Com::StructuredStorage::PROPVARIANT;
+    use windows::Win32::System::Com::{
+        CoCreateInstance, CoInitializeEx, CoUninitialize, CLSCTX_INPROC_SERVER,
+        COINIT_APARTMENTTHREADED,
+    };
+    use windows::Win32::UI::Shell::Common::{IObjectArray, IObjectCollection};
+    use windows::Win32::UI::Shell::PropertiesSystem::IPropertyStore;
+    use windows::Win32::UI::Shell::{
+        DestinationList, EnumerableObjectCollection, ICustomDestinationList, IShellLinkW, ShellLink,
+    };
+
+    let exe = std::env::current_exe().map_err(|_| windows::core::Error::from(E_FAIL))?;
+    let _ = CoInitializeEx(None, COINIT_APARTMENTTHREADED);
+    let result = (|| {
+        let list: ICustomDestinationList =
+            CoCreateInstance(&DestinationList, None, CLSCTX_INPROC_SERVER)?;
+        let mut slots = 0u32;
+        let _removed: IObjectArray = list.BeginList(&mut slots)?;
+        if !prompts.is_empty() {
+            let collection: IObjectCollection =
+                CoCreateInstance(&EnumerableObjectCollection, None, CLSCTX_INPROC_SERVER)?;
+            for prompt in prompts.iter().take(slots as usize) {
+                let link: IShellLinkW = CoCreateInstance(&ShellLink, None, CLSCTX_INPROC_SERVER)?;
+                link.SetPath(&HSTRING::from(exe.as_path()))?;
+                link.SetArguments(&HSTRING::from(crate::instance::rerun_arg(&prompt.id)))?;
+                link.SetIconLocation(&HSTRING::from(exe.as_path()), 0)?;
+                // 提示文字上限 INFOTIPSIZE（1024），标题截短一些才不会被任务栏折叠
+                link.SetDescription(&HSTRING::from(
+                    prompt.prompt.chars().take(500).collect::<String>(),
+                ))?;
+                let title: String = prompt.prompt.chars().take(40).collect();
+                let store: IPropertyStore = link.cast()?;
+                store.SetValue(&PKEY_Title, &PROPVARIANT::from(title.as_str()))?;
+                store.Commit()?;
+                collection.AddObject(&link)?;
+            }
+            let array: IObjectArray = collection.cast()?;
+            list.AppendCategory(&HSTRING::from("最近提示词"), &array)?;
+        }
+        list.CommitList()
+    })();
+    CoUninitialize();
+    result
+}
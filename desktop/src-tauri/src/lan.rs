@@ -0,0 +1,166 @@
+use std::net::{IpAddr, SocketAddr, TcpStream, UdpSocket};
+use std::time::Duration;
+
+use tauri::Manager;
+
+use crate::BackendPort;
+
+#[derive(Clone, Copy, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FirewallStatus {
+    // 非 Windows 平台不需要预先放行
+    #[cfg_attr(windows, allow(dead_code))]
+    NotRequired,
+    #[cfg_attr(not(windows), allow(dead_code))]
+    Authorized,
+    // 用户拒绝了 UAC 提示或添加规则失败
+    #[cfg_attr(not(windows), allow(dead_code))]
+    Denied,
+}
+
+#[derive(serde::Serialize)]
+pub struct LanAccessStatus {
+    lan_ip: Option<String>,
+    port: u16,
+    firewall: FirewallStatus,
+    // 通过局域网地址能否连上；只能说明端口已对外监听，同机连接不经过 Windows 防火墙
+    reachable: bool,
+    error: Option<String>,
+}
+
+// 借助 UDP connect 选出默认路由对应的网卡地址，不会真正发包
+pub fn lan_ip() -> Option<IpAddr> {
+    let socket = UdpSocket::bind("0.0.0.0:0").ok()?;
+    socket.connect("8.8.8.8:80").ok()?;
+    let ip = socket.local_addr().ok()?.ip();
+    (!ip.is_loopback() && !ip.is_unspecified()).then_some(ip)
+}
+
+fn reachable(ip: IpAddr, port: u16) -> bool {
+    TcpStream::connect_timeout(&SocketAddr::new(ip, port), Duration::from_secs(2)).is_ok()
+}
+
+#[cfg(windows)]
+mod firewall {
+    use std::path::{Path, PathBuf};
+    use std::process::Command;
+
+    use crate::shutdown::hide_console;
+
+    // 按程序放行而不是按端口：sidecar 端口每次启动都可能变化
+    pub fn programs() -> Vec<(&'static str, PathBuf)> {
+        let Ok(exe) = std::env::current_exe() else {
+            return Vec::new();
+        };
+        let mut programs = vec![("Banana Pro AI", exe.clone())];
+        if let Some(dir) = exe.parent() {
+            // externalBin 打包后去掉了 target triple 后缀，和主程序放在同一目录
+            let server = dir.join("server.exe");
+            if server.exists() {
+                programs.push(("Banana Pro AI Server", server));
+            }
+        }
+        programs
+    }
+
+    pub fn rule_exists(name: &str) -> bool {
+        hide_console(
+            Command::new("netsh")
+                .args(["advfirewall", "firewall", "show", "rule"])
+                .arg(format!("name={}", name)),
+        )
+        .output()
+        .map(|o| o.status.success())
+        .unwrap_or(false)
+    }
+
+    // 添加入站规则需要管理员权限，通过 PowerShell Start-Process -Verb RunAs 触发 UAC 提示
+    pub fn add_rule_elevated(name: &str, program: &Path) -> Result<(), String> {
+        let args = format!(
+            "advfirewall firewall add rule name=\"{}\" dir=in action=allow enable=yes protocol=TCP profile=private,domain program=\"{}\"",
+            name,
+            program.display()
+        );
+        let script = format!(
+            "$p = Start-Process -FilePath netsh -ArgumentList '{}' -Verb RunAs -WindowStyle Hidden -Wait -PassThru; exit $p.ExitCode",
+            args.replace('\'', "''")
+        );
+        let status = hide_console(Command::new("powershell").args([
+            "-NoProfile",
+            "-NonInteractive",
+            "-Command",
+            &script,
+        ]))
+        .status()
+        .map_err(|e| format!("run powershell failed: {}", e))?;
+        if !status.success() {
+            return Err(format!("add firewall rule failed: {}", status));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(windows)]
+fn ensure_firewall(app: &tauri::AppHandle) -> FirewallStatus {
+    use crate::LogState;
+
+    for (name, program) in firewall::programs() {
+        if firewall::rule_exists(name) {
+            continue;
+        }
+        if let Err(err) = firewall::add_rule_elevated(name, &program) {
+            app.state::<LogState>().log_app(
+                "WARN",
+                &format!("Firewall rule not added name={}: {}", name, err),
+            );
+            return FirewallStatus::Denied;
+        }
+        app.state::<LogState>().log_app(
+            "INFO",
+            &format!(
+                "Firewall rule added name={} program={}",
+                name,
+                program.display()
+            ),
+        );
+    }
+    FirewallStatus::Authorized
+}
+
+#[cfg(not(windows))]
+fn ensure_firewall(_app: &tauri::AppHandle) -> FirewallStatus {
+    FirewallStatus::NotRequired
+}
+
+// 开启局域网访问前调用：Windows 上先申请防火墙放行（会弹 UAC），再检查局域网地址是否可连通
+// port 缺省时检查 sidecar 当前端口
+#[tauri::command]
+pub async fn prepare_lan_access(
+    app: tauri::AppHandle,
+    port: Option<u16>,
+) -> Result<LanAccessStatus, String> {
+    let port = port.unwrap_or_else(|| *app.state::<BackendPort>().0.lock().unwrap());
+    if port == 0 {
+        return Err("backend is not running".to_string());
+    }
+    tauri::async_runtime::spawn_blocking(move || {
+        let firewall = ensure_firewall(&app);
+        let ip = lan_ip();
+        let reachable = ip.map(|ip| reachable(ip, port)).unwrap_or(false);
+        let error = match (ip, firewall, reachable) {
+            (None, _, _) => Some("no LAN address found".to_string()),
+            (_, FirewallStatus::Denied, _) => Some("firewall rule was not authorized".to_string()),
+            (_, _, false) => Some(format!("port {} is not listening on the LAN address", port)),
+            _ => None,
+        };
+        LanAccessStatus {
+            lan_ip: ip.map(|ip| ip.to_string()),
+            port,
+            firewall,
+            reachable,
+            error,
+        }
+    })
+    .await
+    .map_err(|e| format!("prepare lan access failed: {}", e))
+}
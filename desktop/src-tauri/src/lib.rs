@@ -1,15 +1,97 @@
+mod accessibility;
+mod alt_text;
+mod annotate;
+mod arch;
+mod backend_logs;
+mod backend_resources;
+mod backend_version;
+mod bandwidth;
+mod batch;
+mod browser;
+mod checkpoint;
+mod cli;
+mod clipboard;
+mod clipboard_watch;
+mod clock;
+mod colorblind;
+mod compare;
+mod continuity;
+mod crash_loop;
+mod db;
+mod deepzoom;
+mod disk;
+mod display_profile;
+mod export;
+mod fault;
+mod feedback;
+mod feeds;
+mod filters;
+mod fonts;
+mod gpu;
+mod health;
+mod histogram;
+mod history;
+mod history_export;
+mod hooks;
+mod importer;
+mod input;
+mod instance;
+mod invalidation;
+mod jumplist;
+mod lan;
+mod markdown;
+mod memory;
+mod ocr;
+mod optimize;
+mod paths;
+mod permissions;
+mod pins;
+mod plugins;
+mod presets;
+mod project;
+mod proxy;
+mod queue;
+mod quick_capture;
+mod readiness;
+mod recovery;
+mod references;
+mod remote_backend;
+mod remote_export;
+mod rename;
+mod reorganize;
+mod safe_mode;
+mod search;
+mod server_log;
+mod session_key;
+mod settings;
+mod sftp;
+mod sidecar;
+mod sidecar_config;
+mod share;
+mod shutdown;
+mod sound;
+mod startup;
+mod storage;
+mod timelapse;
+mod timings;
+mod toast;
+mod tray;
+mod usage;
+mod watchdog;
+mod webview;
+
 use std::fs::{self, OpenOptions};
 use std::io::Write;
 use std::path::{Path, PathBuf};
 use std::process::Command;
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tauri::webview::PageLoadEvent;
 use tauri::{Emitter, Manager, State};
 #[cfg(target_os = "macos")]
 use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
 use tauri_plugin_opener::OpenerExt;
 use tauri_plugin_shell::process::{CommandChild, CommandEvent};
-use tauri_plugin_shell::ShellExt;
 
 #[derive(Clone, serde::Serialize)]
 struct PortPayload {
@@ -21,7 +103,53 @@ struct SidecarStatusPayload {
     running: bool,
 }
 
+#[derive(Clone, serde::Serialize)]
+struct BackendRestartedPayload {
+    port: u16,
+    attempt: u32,
+}
+
+// sidecar 崩溃后自动重启：首次等待 1s，之后翻倍，最长 30s；连续失败超过上限后放弃
+const SIDECAR_RESTART_BASE_DELAY: Duration = Duration::from_secs(1);
+const SIDECAR_RESTART_MAX_DELAY: Duration = Duration::from_secs(30);
+const SIDECAR_RESTART_MAX_ATTEMPTS: u32 = 8;
+// 运行超过这个时长再崩溃视为新的故障，退避从头计算
+const SIDECAR_STABLE_UPTIME: Duration = Duration::from_secs(60);
+
+// 后端生命周期，随 CommandEvent 流转，供前端区分启动中 / 运行中 / 崩溃 / 自动重启中
+#[derive(Clone, Copy, Default, PartialEq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+enum BackendLifecycle {
+    #[default]
+    Stopped,
+    Starting,
+    Running,
+    Crashed,
+    Restarting,
+}
+
+#[derive(Default)]
+struct BackendStatus {
+    state: BackendLifecycle,
+    pid: Option<u32>,
+    // 检测到端口的时刻，用于计算运行时长
+    running_since: Option<std::time::Instant>,
+    last_error: Option<String>,
+}
+
+#[derive(Clone, serde::Serialize)]
+struct BackendStatusPayload {
+    state: BackendLifecycle,
+    port: u16,
+    pid: Option<u32>,
+    uptime_secs: u64,
+    last_error: Option<String>,
+    // 外部后端模式下的远程地址，本地 sidecar 时为 None
+    endpoint: Option<String>,
+}
+
 struct BackendPort(Arc<Mutex<u16>>);
+struct BackendStatusState(Arc<Mutex<BackendStatus>>);
 struct SidecarState(Arc<Mutex<Option<CommandChild>>>);
 struct SidecarGeneration(Arc<Mutex<u64>>);
 struct GenerationState(Arc<Mutex<bool>>);
@@ -34,16 +162,49 @@ struct QuitGuard {
 
 struct QuitGuardState(Arc<Mutex<QuitGuard>>);
 
+// 日志轮转策略：超过大小或跨天时把当前文件改名归档，并按数量和天数清理旧归档
+#[derive(Clone, Copy)]
+struct RotationPolicy {
+    max_bytes: u64,
+    keep: usize,
+    daily: bool,
+    // 0 表示不按天数清理
+    max_age_days: u32,
+}
+
+impl Default for RotationPolicy {
+    fn default() -> Self {
+        Self {
+            max_bytes: 5 * 1024 * 1024,
+            keep: 5,
+            daily: false,
+            max_age_days: 0,
+        }
+    }
+}
+
+struct OpenLog {
+    file: std::fs::File,
+    bytes: u64,
+    day: String,
+}
+
 #[derive(Clone)]
 struct LogWriter {
     path: PathBuf,
-    file: Arc<Mutex<Option<std::fs::File>>>,
+    file: Arc<Mutex<Option<OpenLog>>>,
+    policy: Arc<Mutex<RotationPolicy>>,
 }
 
 impl LogWriter {
     fn new(path: PathBuf) -> Self {
         let file = Arc::new(Mutex::new(None));
-        Self { path, file }
+        let policy = Arc::new(Mutex::new(RotationPolicy::default()));
+        Self { path, file, policy }
+    }
+
+    fn set_policy(&self, policy: RotationPolicy) {
+        *self.policy.lock().unwrap() = policy;
     }
 
     fn open(&self) {
@@ -51,17 +212,37 @@ impl LogWriter {
         if guard.is_some() {
             return;
         }
+        self.open_locked(&mut guard);
+    }
+
+    fn open_locked(&self, guard: &mut Option<OpenLog>) {
         if let Some(parent) = self.path.parent() {
             let _ = fs::create_dir_all(parent);
         }
-        let _ = rotate_if_too_large(&self.path, 5 * 1024 * 1024, 5);
+        let policy = *self.policy.lock().unwrap();
+        let _ = rotate_if_too_large(&self.path, policy.max_bytes, policy.keep, policy.max_age_days);
+        // 跨天轮转时以文件最后修改日期判断，重启应用后也能正确归档前一天的日志
+        if policy.daily {
+            let stale = fs::metadata(&self.path)
+                .and_then(|m| m.modified())
+                .map(crate::clock::local_day)
+                .is_ok_and(|day| day != crate::clock::today());
+            if stale {
+                let _ = rotate_if_too_large(&self.path, 0, policy.keep, policy.max_age_days);
+            }
+        }
         match OpenOptions::new()
             .create(true)
             .append(true)
             .open(&self.path)
         {
             Ok(f) => {
-                *guard = Some(f);
+                let bytes = f.metadata().map(|m| m.len()).unwrap_or(0);
+                *guard = Some(OpenLog {
+                    file: f,
+                    bytes,
+                    day: crate::clock::today(),
+                });
             }
             Err(_) => {
                 *guard = None;
@@ -70,19 +251,31 @@ impl LogWriter {
     }
 
     fn write_line(&self, line: &str) {
-        // lazy open
-        if self.file.lock().unwrap().is_none() {
-            self.open();
-        }
-
-        let mut guard = self.file.lock().unwrap();
-        let Some(f) = guard.as_mut() else { return };
         let sanitized = line.replace('\r', "").trim_end_matches('\n').to_string();
         if sanitized.is_empty() {
             return;
         }
-        let _ = writeln!(f, "{}", sanitized);
-        let _ = f.flush();
+        let mut guard = self.file.lock().unwrap();
+        // lazy open
+        if guard.is_none() {
+            self.open_locked(&mut guard);
+        }
+        // 运行期间也要轮转：sidecar 长时间运行时日志会一直增长
+        let policy = *self.policy.lock().unwrap();
+        let rotate = guard.as_ref().is_some_and(|open| {
+            open.bytes >= policy.max_bytes || (policy.daily && open.day != crate::clock::today())
+        });
+        if rotate {
+            *guard = None;
+            let _ = rotate_if_too_large(&self.path, 0, policy.keep, policy.max_age_days);
+            self.open_locked(&mut guard);
+        }
+
+        let Some(open) = guard.as_mut() else { return };
+        if writeln!(open.file, "{}", sanitized).is_ok() {
+            open.bytes += sanitized.len() as u64 + 1;
+        }
+        let _ = open.file.flush();
     }
 }
 
@@ -95,10 +288,7 @@ struct LogState {
 
 impl LogState {
     fn init(app: &tauri::AppHandle) -> Self {
-        let base = app
-            .path()
-            .app_data_dir()
-            .unwrap_or_else(|_| std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")));
+        let base = app_data_base(app);
         let dir = base.join("logs");
         let app_log = LogWriter::new(dir.join("app.log"));
         let server_log = LogWriter::new(dir.join("server.log"));
@@ -141,6 +331,11 @@ struct FrontendLogEntry {
     context: Option<String>,
 }
 
+// 壳层数据与 sidecar 放在同一目录，数据库、storage/ 和壳层自己的文件才能互相找到
+fn app_data_base(app: &tauri::AppHandle) -> PathBuf {
+    paths::backend_data_dir(app)
+}
+
 fn now_ms() -> u128 {
     SystemTime::now()
         .duration_since(UNIX_EPOCH)
@@ -148,7 +343,28 @@ fn now_ms() -> u128 {
         .unwrap_or(0)
 }
 
-fn rotate_if_too_large(path: &Path, max_bytes: u64, keep: usize) -> std::io::Result<()> {
+// 递归统计目录大小（符号链接不跟随，避免循环）
+fn dir_size(path: &Path) -> u64 {
+    let Ok(meta) = fs::symlink_metadata(path) else {
+        return 0;
+    };
+    if meta.is_file() {
+        return meta.len();
+    }
+    if !meta.is_dir() {
+        return 0;
+    }
+    fs::read_dir(path)
+        .map(|entries| entries.flatten().map(|e| dir_size(&e.path())).sum())
+        .unwrap_or(0)
+}
+
+fn rotate_if_too_large(
+    path: &Path,
+    max_bytes: u64,
+    keep: usize,
+    max_age_days: u32,
+) -> std::io::Result<()> {
     let Ok(meta) = fs::metadata(path) else {
         return Ok(());
     };
@@ -182,6 +398,16 @@ fn rotate_if_too_large(path: &Path, max_bytes: u64, keep: usize) -> std::io::Res
         }
     }
     rotated_files.sort_by_key(|(t, _)| *t);
+    if max_age_days > 0 {
+        let max_age = Duration::from_secs(max_age_days as u64 * 24 * 60 * 60);
+        rotated_files.retain(|(modified, p)| {
+            let expired = modified.elapsed().is_ok_and(|age| age > max_age);
+            if expired {
+                let _ = fs::remove_file(p);
+            }
+            !expired
+        });
+    }
     if rotated_files.len() > keep {
         let extra = rotated_files.len() - keep;
         for (_, p) in rotated_files.into_iter().take(extra) {
@@ -194,9 +420,9 @@ fn rotate_if_too_large(path: &Path, max_bytes: u64, keep: usize) -> std::io::Res
 
 // 获取后端实际运行端口的命令
 #[tauri::command]
-fn get_backend_port(state: State<'_, BackendPort>) -> u16 {
-    let port = state.0.lock().unwrap();
-    *port
+fn get_backend_port(app: tauri::AppHandle, state: State<'_, BackendPort>) -> u16 {
+    let port = *state.0.lock().unwrap();
+    proxy::frontend_port(&app, port)
 }
 
 #[tauri::command]
@@ -205,19 +431,24 @@ fn is_sidecar_running(state: State<'_, SidecarState>) -> bool {
 }
 
 #[tauri::command]
-fn set_generation_active(state: State<'_, GenerationState>, active: bool) {
-    if let Ok(mut flag) = state.0.lock() {
-        *flag = active;
+fn set_generation_active(
+    app: tauri::AppHandle,
+    state: State<'_, GenerationState>,
+    active: bool,
+) {
+    let changed = match state.0.lock() {
+        Ok(mut flag) => std::mem::replace(&mut *flag, active) != active,
+        Err(_) => false,
+    };
+    if changed {
+        tray::refresh_icon(&app);
     }
 }
 
 // 获取应用数据目录的命令，用于前端拼接本地图片路径
 #[tauri::command]
 fn get_app_data_dir(app: tauri::AppHandle) -> String {
-    app.path()
-        .app_data_dir()
-        .map(|p| p.to_string_lossy().to_string())
-        .unwrap_or_default()
+    app_data_base(&app).to_string_lossy().to_string()
 }
 
 // 获取日志目录，便于用户导出/提交诊断日志
@@ -305,13 +536,8 @@ fn write_frontend_logs(
     Ok(())
 }
 
-// 将本地图片写入系统剪贴板（用于 macOS 打包环境下 Web Clipboard API 不可用/不稳定的兜底）
-#[tauri::command]
-fn copy_image_to_clipboard(app: tauri::AppHandle, path: String) -> Result<(), String> {
-    use std::borrow::Cow;
-    use std::path::PathBuf;
-    use std::sync::mpsc;
-
+// 把前端传入的图片路径解析为本地绝对路径
+fn resolve_local_path(app: &tauri::AppHandle, path: &str) -> Result<PathBuf, String> {
     let trimmed = path.trim();
     if trimmed.is_empty() {
         return Err("path is empty".to_string());
@@ -329,59 +555,12 @@ fn copy_image_to_clipboard(app: tauri::AppHandle, path: String) -> Result<(), St
     let input_path = PathBuf::from(normalized);
 
     // 兼容：后端历史可能存的是相对路径（如 storage/xxx.jpg），打包/开发环境工作目录也可能不同
-    let mut candidates: Vec<PathBuf> = Vec::new();
-    if input_path.is_absolute() {
-        candidates.push(input_path);
-    } else {
-        if let Ok(app_data) = app.path().app_data_dir() {
-            candidates.push(app_data.join(&input_path));
-        }
-        if let Ok(current_dir) = std::env::current_dir() {
-            candidates.push(current_dir.join(&input_path));
-        }
-        if let Ok(resource_dir) = app.path().resource_dir() {
-            candidates.push(resource_dir.join(&input_path));
-        }
-        // 最后再尝试“原样相对路径”（少数场景下当前目录就是预期目录）
-        candidates.push(input_path);
-    }
-
-    let file_path = candidates
+    let candidates = paths::local_path_candidates(app, &input_path);
+    Ok(candidates
         .iter()
         .find(|p| p.exists())
         .cloned()
-        .unwrap_or_else(|| candidates.first().cloned().unwrap());
-
-    let bytes = std::fs::read(&file_path)
-        .map_err(|e| format!("read file failed: {} ({})", e, file_path.display()))?;
-
-    let img = image::load_from_memory(&bytes).map_err(|e| format!("decode image failed: {}", e))?;
-    let rgba = img.to_rgba8();
-    let (width, height) = rgba.dimensions();
-    let raw = rgba.into_raw();
-
-    // macOS 上部分剪贴板实现要求在主线程调用，这里强制切到主线程执行，避免偶发失败
-    let (tx, rx) = mpsc::channel::<Result<(), String>>();
-    app.run_on_main_thread(move || {
-        let result = (|| {
-            let mut clipboard =
-                arboard::Clipboard::new().map_err(|e| format!("clipboard init failed: {}", e))?;
-            clipboard
-                .set_image(arboard::ImageData {
-                    width: width as usize,
-                    height: height as usize,
-                    bytes: Cow::Owned(raw),
-                })
-                .map_err(|e| format!("clipboard set image failed: {}", e))?;
-            Ok(())
-        })();
-
-        let _ = tx.send(result);
-    })
-    .map_err(|e| format!("run_on_main_thread failed: {}", e))?;
-
-    rx.recv()
-        .map_err(|_| "clipboard task aborted".to_string())?
+        .unwrap_or_else(|| candidates.first().cloned().unwrap()))
 }
 
 // 复制文本到系统剪贴板（用于日志路径等）
@@ -412,9 +591,8 @@ fn copy_text_to_clipboard(app: tauri::AppHandle, text: String) -> Result<(), Str
     rx.recv()
         .map_err(|_| "clipboard task aborted".to_string())?
 }
-// 从系统剪贴板读取图片并写入 AppData 临时文件（用于打包环境下 Web ClipboardData 不可用/不稳定的兜底）
-#[tauri::command]
-fn read_image_from_clipboard(app: tauri::AppHandle) -> Result<Option<String>, String> {
+// 读取剪贴板中的图片，没有图片时返回 None；参考图库也复用这里
+fn clipboard_image(app: &tauri::AppHandle) -> Result<Option<image::RgbaImage>, String> {
     use std::sync::mpsc;
 
     // macOS 上部分剪贴板实现要求在主线程调用：统一切主线程读剪贴板
@@ -442,18 +620,26 @@ fn read_image_from_clipboard(app: tauri::AppHandle) -> Result<Option<String>, St
         return Ok(None);
     };
 
-    let base = app
-        .path()
-        .app_data_dir()
-        .unwrap_or_else(|_| std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")));
+    image::RgbaImage::from_raw(width as u32, height as u32, bytes)
+        .map(Some)
+        .ok_or_else(|| "invalid clipboard image data".to_string())
+}
+
+// 从系统剪贴板读取图片并写入 AppData 临时文件（用于打包环境下 Web ClipboardData 不可用/不稳定的兜底）
+#[tauri::command]
+fn read_image_from_clipboard(app: tauri::AppHandle) -> Result<Option<String>, String> {
+    let Some(buffer) = clipboard_image(&app)? else {
+        return Ok(None);
+    };
+
+    let base = app_data_base(&app);
     let dir = base.join("clipboard");
     fs::create_dir_all(&dir).map_err(|e| format!("create clipboard dir failed: {}", e))?;
 
-    let out_path = dir.join(format!("clipboard-{}.png", now_ms()));
-    let w = width as u32;
-    let h = height as u32;
-    let buffer = image::ImageBuffer::<image::Rgba<u8>, Vec<u8>>::from_raw(w, h, bytes)
-        .ok_or_else(|| "invalid clipboard image data".to_string())?;
+    let out_path = dir.join(format!(
+        "clipboard-{}.png",
+        crate::clock::filename_stamp(&app)
+    ));
     buffer
         .save(&out_path)
         .map_err(|e| format!("save clipboard image failed: {}", e))?;
@@ -468,10 +654,6 @@ fn persist_ref_image(
     path: String,
     dest_name: String,
 ) -> Result<String, String> {
-    let trimmed = path.trim();
-    if trimmed.is_empty() {
-        return Err("path is empty".to_string());
-    }
     let dest = dest_name.trim();
     if dest.is_empty() {
         return Err("dest_name is empty".to_string());
@@ -480,46 +662,16 @@ fn persist_ref_image(
         return Err("dest_name invalid".to_string());
     }
 
-    let normalized = if let Some(p) = trimmed.strip_prefix("file://localhost") {
-        p.to_string()
-    } else if let Some(p) = trimmed.strip_prefix("file://") {
-        p.to_string()
-    } else {
-        trimmed.to_string()
-    };
-
-    let input_path = PathBuf::from(normalized);
-    let mut candidates: Vec<PathBuf> = Vec::new();
-    if input_path.is_absolute() {
-        candidates.push(input_path);
-    } else {
-        if let Ok(app_data) = app.path().app_data_dir() {
-            candidates.push(app_data.join(&input_path));
-        }
-        if let Ok(current_dir) = std::env::current_dir() {
-            candidates.push(current_dir.join(&input_path));
-        }
-        if let Ok(resource_dir) = app.path().resource_dir() {
-            candidates.push(resource_dir.join(&input_path));
-        }
-        candidates.push(input_path);
-    }
-
-    let file_path = candidates
-        .iter()
-        .find(|p| p.exists())
-        .cloned()
-        .unwrap_or_else(|| candidates.first().cloned().unwrap());
+    let file_path = resolve_local_path(&app, &path)?;
 
-    let base = app
-        .path()
-        .app_data_dir()
-        .unwrap_or_else(|_| std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")));
+    let base = app_data_base(&app);
     let dir = base.join("ref_images");
     fs::create_dir_all(&dir).map_err(|e| format!("create ref_images failed: {}", e))?;
 
     let dest_path = dir.join(dest);
     if !dest_path.exists() {
+        let size = fs::metadata(&file_path).map(|m| m.len()).unwrap_or(0);
+        disk::ensure_free_space(&dir, size)?;
         fs::copy(&file_path, &dest_path)
             .map_err(|e| format!("copy file failed: {} ({})", e, file_path.display()))?;
     }
@@ -567,6 +719,7 @@ fn replace_file_safely(src: &Path, dst: &Path) -> Result<(), String> {
 
 #[tauri::command]
 async fn download_file_to_path(
+    app: tauri::AppHandle,
     state: State<'_, LogState>,
     url: String,
     dest_path: String,
@@ -637,6 +790,7 @@ async fn download_file_to_path(
     if !response.status().is_success() {
         return Err(format!("download request failed: {}", response.status()));
     }
+    disk::ensure_free_space(&final_path, response.content_length().unwrap_or(0))?;
 
     let mut file =
         std::fs::File::create(&temp_path).map_err(|e| format!("create temp file failed: {}", e))?;
@@ -656,6 +810,8 @@ async fn download_file_to_path(
         .map_err(|e| format!("flush temp file failed: {}", e))?;
     drop(file);
 
+    bandwidth::record(&app, "download", 0, total_bytes);
+
     if let Err(err) = replace_file_safely(&temp_path, &final_path) {
         let _ = fs::remove_file(&temp_path);
         return Err(err);
@@ -679,38 +835,264 @@ fn greet(name: &str) -> String {
     format!("Hello, {}! You've been greeted from Rust!", name)
 }
 
+fn backend_status(app_handle: &tauri::AppHandle) -> BackendStatusPayload {
+    let port = app_handle
+        .state::<BackendPort>()
+        .0
+        .lock()
+        .map(|p| *p)
+        .unwrap_or(0);
+    let status = app_handle.state::<BackendStatusState>();
+    let status = status.0.lock().unwrap();
+    BackendStatusPayload {
+        state: status.state,
+        port: if port == 0 {
+            0
+        } else {
+            proxy::frontend_port(app_handle, port)
+        },
+        pid: status.pid,
+        uptime_secs: status
+            .running_since
+            .map(|t| t.elapsed().as_secs())
+            .unwrap_or(0),
+        last_error: status.last_error.clone(),
+        endpoint: remote_backend::endpoint(app_handle),
+    }
+}
+
+// 修改生命周期状态后广播 backend-status，前端无需轮询
+fn update_backend_status(app_handle: &tauri::AppHandle, f: impl FnOnce(&mut BackendStatus)) {
+    {
+        let status = app_handle.state::<BackendStatusState>();
+        let mut guard = status.0.lock().unwrap();
+        f(&mut guard);
+        if guard.state != BackendLifecycle::Running {
+            guard.running_since = None;
+        }
+    }
+    let _ = app_handle.emit("backend-status", backend_status(app_handle));
+    tray::refresh_icon(app_handle);
+}
+
+fn mark_backend_stopped(app_handle: &tauri::AppHandle) {
+    update_backend_status(app_handle, |s| {
+        s.state = BackendLifecycle::Stopped;
+        s.pid = None;
+    });
+}
+
+#[tauri::command]
+fn get_backend_status(app: tauri::AppHandle) -> BackendStatusPayload {
+    backend_status(&app)
+}
+
 fn kill_sidecar(app_handle: &tauri::AppHandle) {
     let sidecar_state = app_handle.state::<SidecarState>();
     let log_state = app_handle.state::<LogState>();
     let mut guard = sidecar_state.0.lock().unwrap();
+    // 外部后端模式下没有本地 sidecar，不能把远程后端的就绪状态和端口一起清掉
+    if guard.is_none() && remote_backend::endpoint(app_handle).is_some() {
+        return;
+    }
+    readiness::reset();
     if let Some(child) = guard.take() {
         log_state.log_app("INFO", "Killing sidecar process on app exit.");
         if let Err(err) = child.kill() {
             log_state.log_app("ERROR", &format!("Failed to kill sidecar: {}", err));
         }
+        shutdown::clear_pid(app_handle);
     }
+    drop(guard);
+    mark_backend_stopped(app_handle);
+}
+
+// 应用退出和重启后端时使用：先发终止信号让后端关闭数据库连接，宽限期后仍未退出再强制结束，避免残留孤儿进程
+fn stop_sidecar_gracefully(app_handle: &tauri::AppHandle) {
+    let child = app_handle.state::<SidecarState>().0.lock().unwrap().take();
+    if child.is_none() && remote_backend::endpoint(app_handle).is_some() {
+        return;
+    }
+    readiness::reset();
+    if let Some(child) = child {
+        let log_state = app_handle.state::<LogState>();
+        log_state.log_app("INFO", "Stopping sidecar process gracefully.");
+        shutdown::terminate(child, shutdown::grace_period(app_handle), &log_state);
+        shutdown::clear_pid(app_handle);
+    }
+    mark_backend_stopped(app_handle);
+}
+
+pub enum SidecarRestart {
+    // 用户发起的维护操作：平滑停止，让后端关闭连接、刷完 WAL，并清零崩溃循环计数
+    Maintenance,
+    // 后端已经不响应：直接结束进程，计入崩溃循环统计
+    Unresponsive,
+}
+
+// 停掉 sidecar → 执行 op → 重新拉起：数据库维护、检查点恢复、安全模式接管、健康检查重启共用，
+// 外部后端模式和生成中一律拒绝；op 失败也会重新拉起 sidecar，保证应用仍可用。会阻塞，需在后台线程调用
+fn with_sidecar_stopped<T>(
+    app_handle: &tauri::AppHandle,
+    restart: SidecarRestart,
+    op: impl FnOnce() -> Result<T, String>,
+) -> Result<T, String> {
+    remote_backend::ensure_local(app_handle)?;
+    if *app_handle.state::<GenerationState>().0.lock().unwrap() {
+        return Err("generation in progress".to_string());
+    }
+    match restart {
+        SidecarRestart::Maintenance => {
+            crash_loop::reset();
+            stop_sidecar_gracefully(app_handle);
+        }
+        SidecarRestart::Unresponsive => kill_sidecar(app_handle),
+    }
+    let port_state = app_handle.state::<BackendPort>().0.clone();
+    if let Ok(mut p) = port_state.lock() {
+        *p = 0;
+    }
+    let _ = app_handle.emit("sidecar-status", SidecarStatusPayload { running: false });
+    let result = op();
+    let spawn_result = spawn_sidecar(app_handle, port_state);
+    let value = result?;
+    spawn_result?;
+    Ok(value)
+}
+
+// 拿到可用的后端端口后统一处理：本地 sidecar 打印端口、或连上远程后端的本地转发端口
+fn on_backend_port(app_handle: &tauri::AppHandle, port: u16) {
+    if let Ok(mut p) = app_handle.state::<BackendPort>().0.lock() {
+        *p = port;
+    }
+    startup::mark("backend_port");
+    readiness::probe(app_handle, port);
+    queue::restore_backend_state(app_handle);
+    history::run_pending(app_handle);
+    recovery::offer_pending(app_handle);
+    let _ = app_handle.emit(
+        "backend-port",
+        PortPayload {
+            port: proxy::frontend_port(app_handle, port),
+        },
+    );
+    let _ = app_handle.emit("sidecar-status", SidecarStatusPayload { running: true });
+    update_backend_status(app_handle, |s| {
+        s.state = BackendLifecycle::Running;
+        s.running_since = Some(std::time::Instant::now());
+    });
+}
+
+// 端口握手：子进程在 stdout 打印 <marker><port> 表示已开始监听，后端与插件共用
+fn parse_port_line(line: &str, marker: &str) -> Option<u16> {
+    let (_, rest) = line.split_once(marker)?;
+    rest.split_whitespace().next()?.parse().ok()
 }
 
 fn spawn_sidecar(
     app_handle: &tauri::AppHandle,
     port_state: Arc<Mutex<u16>>,
 ) -> Result<(), String> {
+    spawn_sidecar_attempt(app_handle, port_state, 0)
+}
+
+fn restart_delay(attempt: u32) -> Duration {
+    SIDECAR_RESTART_BASE_DELAY
+        .saturating_mul(1 << attempt.saturating_sub(1).min(16))
+        .min(SIDECAR_RESTART_MAX_DELAY)
+}
+
+// 崩溃后按退避时间重新拉起；等待期间若已有人手动重启或退出应用则放弃
+fn schedule_sidecar_restart(
+    app_handle: tauri::AppHandle,
+    port_state: Arc<Mutex<u16>>,
+    generation: u64,
+    attempt: u32,
+) {
     let log_state = app_handle.state::<LogState>().inner().clone();
-    let shell = app_handle.shell();
-    let sidecar_command = shell
-        .sidecar("server")
-        .map_err(|err| format!("create sidecar command failed: {}", err))?
-        .env("TAURI_PLATFORM", "macos")
-        .env("TAURI_FAMILY", "unix")
-        .env("GODEBUG", "http2debug=2")
-        .env("GIN_MODE", "release");
+    if attempt > SIDECAR_RESTART_MAX_ATTEMPTS {
+        log_state.log_app(
+            "ERROR",
+            &format!(
+                "Sidecar crashed {} times in a row, giving up auto restart",
+                attempt - 1
+            ),
+        );
+        return;
+    }
+    if remote_backend::endpoint(&app_handle).is_some() {
+        return;
+    }
+    // 不管单次运行了多久，一分钟内重启太多次就停下来交给用户，见 crash_loop.rs
+    if !crash_loop::allow_restart(&app_handle) {
+        return;
+    }
+    update_backend_status(&app_handle, |s| s.state = BackendLifecycle::Restarting);
+    let delay = restart_delay(attempt);
+    log_state.log_app(
+        "WARN",
+        &format!(
+            "Restarting sidecar in {}ms attempt={}",
+            delay.as_millis(),
+            attempt
+        ),
+    );
+    std::thread::spawn(move || {
+        std::thread::sleep(delay);
+        let unchanged = app_handle
+            .state::<SidecarGeneration>()
+            .0
+            .lock()
+            .map(|g| *g == generation)
+            .unwrap_or(false);
+        let idle = app_handle
+            .state::<SidecarState>()
+            .0
+            .lock()
+            .map(|c| c.is_none())
+            .unwrap_or(false);
+        if !unchanged || !idle {
+            return;
+        }
+        if let Err(err) = spawn_sidecar_attempt(&app_handle, port_state.clone(), attempt) {
+            log_state.log_app("ERROR", &format!("Sidecar restart failed: {}", err));
+            // 拉起本身失败（如可执行文件被占用）同样计入退避
+            schedule_sidecar_restart(app_handle, port_state, generation, attempt + 1);
+        }
+    });
+}
+
+// attempt 为 0 表示正常启动，大于 0 表示崩溃后的第几次自动重启
+fn spawn_sidecar_attempt(
+    app_handle: &tauri::AppHandle,
+    port_state: Arc<Mutex<u16>>,
+    attempt: u32,
+) -> Result<(), String> {
+    // 所有拉起路径（数据库维护、安全模式接管、崩溃循环后重启等）统一在这里拦住，
+    // 否则外部后端模式下会多出一个本地 sidecar 并覆盖 BackendPort
+    if remote_backend::endpoint(app_handle).is_some() {
+        return Err(remote_backend::SIDECAR_DISABLED.to_string());
+    }
+    let log_state = app_handle.state::<LogState>().inner().clone();
+    // 按平台设置环境变量和工作目录，见 sidecar.rs
+    let sidecar_command = sidecar::command(app_handle)?;
 
     log_state.log_app("INFO", "Attempting to spawn sidecar...");
-    let (mut rx, child) = sidecar_command
-        .spawn()
-        .map_err(|err| format!("spawn sidecar failed: {}", err))?;
+    let (mut rx, child) = match sidecar_command.spawn() {
+        Ok(spawned) => spawned,
+        Err(err) => {
+            let err = format!("spawn sidecar failed: {}", err);
+            update_backend_status(app_handle, |s| {
+                s.state = BackendLifecycle::Crashed;
+                s.pid = None;
+                s.last_error = Some(err.clone());
+            });
+            return Err(err);
+        }
+    };
 
     log_state.log_app("INFO", &format!("Sidecar spawned with PID: {:?}", child.pid()));
+    startup::mark("sidecar_spawned");
 
     let generation = {
         let generation_state = app_handle.state::<SidecarGeneration>();
@@ -719,40 +1101,62 @@ fn spawn_sidecar(
         *guard
     };
 
+    let pid = child.pid();
     {
         let sidecar_state = app_handle.state::<SidecarState>();
         let mut guard = sidecar_state.0.lock().unwrap();
         *guard = Some(child);
     }
+    shutdown::record_pid(app_handle, pid);
+    startup::watch_port(app_handle, generation);
+    sidecar::watch_reserved_port(app_handle, generation);
+    update_backend_status(app_handle, |s| {
+        s.state = if attempt > 0 {
+            BackendLifecycle::Restarting
+        } else {
+            BackendLifecycle::Starting
+        };
+        s.pid = Some(pid);
+    });
 
     let app_handle_clone = app_handle.clone();
     let port_state_inner = port_state.clone();
     let log_state_for_task = log_state.clone();
+    let spawned_at = std::time::Instant::now();
 
     tauri::async_runtime::spawn(async move {
+        // 崩溃时把最后一行 stderr 记进 last_error，通常就是 panic / fatal 信息
+        let mut last_stderr: Option<String> = None;
         while let Some(event) = rx.recv().await {
             match event {
                 CommandEvent::Stdout(line) => {
                     let out = String::from_utf8_lossy(&line);
                     println!("Sidecar STDOUT: {}", out);
                     log_state_for_task.log_server("STDOUT", out.trim_end());
-
-                    if out.contains("SERVER_PORT=") {
-                        if let Some(port_str) = out.split('=').last() {
-                            if let Ok(port) = port_str.trim().parse::<u16>() {
-                                log_state_for_task.log_app(
-                                    "INFO",
-                                    &format!("Detected backend port: {}", port),
-                                );
-                                if let Ok(mut p) = port_state_inner.lock() {
-                                    *p = port;
-                                }
-                                let _ = app_handle_clone.emit("backend-port", PortPayload { port });
-                                let _ = app_handle_clone.emit(
-                                    "sidecar-status",
-                                    SidecarStatusPayload { running: true },
-                                );
-                            }
+                    backend_logs::push(&app_handle_clone, "stdout", &out);
+
+                    if let Some(port) = parse_port_line(&out, "SERVER_PORT=") {
+                        log_state_for_task.log_app(
+                            "INFO",
+                            &format!("Detected backend port: {}", port),
+                        );
+                        // 预分配的端口可能已经探测到，避免重复触发就绪流程
+                        let known = port_state_inner.lock().map(|p| *p == port).unwrap_or(false);
+                        if !known {
+                            on_backend_port(&app_handle_clone, port);
+                        }
+                        if attempt > 0 {
+                            log_state_for_task.log_app(
+                                "INFO",
+                                &format!("Sidecar recovered attempt={}", attempt),
+                            );
+                            let _ = app_handle_clone.emit(
+                                "backend-restarted",
+                                BackendRestartedPayload {
+                                    port: proxy::frontend_port(&app_handle_clone, port),
+                                    attempt,
+                                },
+                            );
                         }
                     }
                 }
@@ -760,33 +1164,76 @@ fn spawn_sidecar(
                     let err = String::from_utf8_lossy(&line);
                     eprintln!("Sidecar STDERR: {}", err);
                     log_state_for_task.log_server("STDERR", err.trim_end());
+                    backend_logs::push(&app_handle_clone, "stderr", &err);
+                    if !err.trim().is_empty() {
+                        last_stderr = Some(err.trim().to_string());
+                    }
                 }
                 CommandEvent::Error(err) => {
                     eprintln!("Sidecar Error: {}", err);
                     log_state_for_task.log_app("ERROR", &format!("Sidecar Error: {}", err));
+                    update_backend_status(&app_handle_clone, |s| s.last_error = Some(err));
                 }
                 CommandEvent::Terminated(status) => {
                     log_state_for_task.log_app(
                         "WARN",
                         &format!("Sidecar Terminated with status: {:?}", status),
                     );
-                    if let Ok(mut p) = port_state_inner.lock() {
-                        *p = 0;
-                    }
                     let current_generation = app_handle_clone
                         .state::<SidecarGeneration>()
                         .0
                         .lock()
                         .map(|g| *g)
                         .unwrap_or(0);
+                    // kill_sidecar 会先取走 child，这里仍持有说明不是主动停止
+                    let mut crashed = false;
+                    // 被替换掉的旧进程可能在新进程已发布预留端口之后才报告退出，只处理当前这一代
                     if generation == current_generation {
+                        if let Ok(mut p) = port_state_inner.lock() {
+                            *p = 0;
+                        }
+                        readiness::reset();
                         if let Ok(mut c) = app_handle_clone.state::<SidecarState>().0.lock() {
-                            *c = None;
+                            crashed = c.take().is_some();
                         }
+                        let _ = app_handle_clone.emit("sidecar-status", SidecarStatusPayload {
+                            running: false,
+                        });
+                    }
+                    if crashed {
+                        let exit = match (status.code, status.signal) {
+                            (Some(code), _) => format!("exited with code {}", code),
+                            (None, Some(signal)) => format!("killed by signal {}", signal),
+                            _ => "exited".to_string(),
+                        };
+                        let last_error = match last_stderr.take() {
+                            Some(line) => format!("{}: {}", exit, line),
+                            None => exit,
+                        };
+                        let failed = status.code != Some(0);
+                        update_backend_status(&app_handle_clone, |s| {
+                            s.pid = None;
+                            if failed {
+                                s.state = BackendLifecycle::Crashed;
+                                s.last_error = Some(last_error);
+                            } else {
+                                s.state = BackendLifecycle::Stopped;
+                            }
+                        });
+                    }
+                    if crashed && status.code != Some(0) {
+                        let next_attempt = if spawned_at.elapsed() >= SIDECAR_STABLE_UPTIME {
+                            1
+                        } else {
+                            attempt + 1
+                        };
+                        schedule_sidecar_restart(
+                            app_handle_clone.clone(),
+                            port_state_inner.clone(),
+                            generation,
+                            next_attempt,
+                        );
                     }
-                    let _ = app_handle_clone.emit("sidecar-status", SidecarStatusPayload {
-                        running: false,
-                    });
                 }
                 _ => {}
             }
@@ -798,6 +1245,10 @@ fn spawn_sidecar(
 
 #[tauri::command]
 fn restart_sidecar(app: tauri::AppHandle, state: State<'_, BackendPort>) -> Result<(), String> {
+    if remote_backend::endpoint(&app).is_some() {
+        return Err(remote_backend::SIDECAR_DISABLED.to_string());
+    }
+    crash_loop::reset();
     kill_sidecar(&app);
     if let Ok(mut p) = state.0.lock() {
         *p = 0;
@@ -809,6 +1260,35 @@ fn restart_sidecar(app: tauri::AppHandle, state: State<'_, BackendPort>) -> Resu
     spawn_sidecar(&app, state.inner().0.clone())
 }
 
+// 后端卡死（如每次生成都 502）时由设置页调用：平滑停止 sidecar 后重新拉起，
+// 等新端口通过健康检查后返回；backend-port 事件照常发出
+#[tauri::command]
+async fn restart_backend(
+    app: tauri::AppHandle,
+    timeout_ms: Option<u64>,
+) -> Result<PortPayload, String> {
+    if *app.state::<GenerationState>().0.lock().unwrap() {
+        return Err("generation in progress".to_string());
+    }
+    if remote_backend::endpoint(&app).is_some() {
+        return Err(remote_backend::SIDECAR_DISABLED.to_string());
+    }
+    app.state::<LogState>()
+        .log_app("INFO", "Restarting backend on user request");
+    let timeout = Duration::from_millis(timeout_ms.unwrap_or(30_000));
+    let app_for_task = app.clone();
+    let port = tauri::async_runtime::spawn_blocking(move || {
+        with_sidecar_stopped(&app_for_task, SidecarRestart::Maintenance, || Ok(()))?;
+        readiness::wait_ready(timeout)
+            .ok_or_else(|| format!("backend not ready within {}ms", timeout.as_millis()))
+    })
+    .await
+    .map_err(|e| format!("restart backend failed: {}", e))??;
+    Ok(PortPayload {
+        port: proxy::frontend_port(&app, port),
+    })
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     let port_state = Arc::new(Mutex::new(0u16)); // 初始为 0
@@ -817,6 +1297,16 @@ pub fn run() {
     let sidecar_generation = Arc::new(Mutex::new(0u64));
     let generation_state = Arc::new(Mutex::new(false));
     let quit_guard_state = Arc::new(Mutex::new(QuitGuard::default()));
+    startup::begin();
+    paths::prepare_environment();
+    // 命令行子命令（status / logs / batch）不启动界面，执行完直接退出
+    if let Some(code) = cli::run() {
+        std::process::exit(code);
+    }
+    // 跳转列表启动的进程：已有实例时转发后直接退出
+    if instance::forward_launch_args() {
+        return;
+    }
 
     tauri::Builder::default()
         .plugin(tauri_plugin_shell::init())
@@ -827,18 +1317,87 @@ pub fn run() {
         .plugin(tauri_plugin_process::init())
         .plugin(tauri_plugin_os::init())
         .plugin(tauri_plugin_updater::Builder::new().build())
+        .plugin(webview::external_links_plugin())
         .manage(BackendPort(port_state_for_state))
+        .manage(BackendStatusState(Default::default()))
+        .manage(backend_logs::BackendLogBuffer::default())
         .manage(SidecarGeneration(sidecar_generation))
         .manage(GenerationState(generation_state))
         .manage(QuitGuardState(quit_guard_state))
+        .manage(gpu::GpuState(Arc::new(Mutex::new(None))))
+        .manage(webview::DebugFlag::from_args())
+        .manage(fonts::FontState(Arc::new(Mutex::new(None))))
+        .manage(disk::DiskState(Arc::new(Mutex::new(Default::default()))))
+        .manage(disk::DiskGuardState(Default::default()))
+        .manage(safe_mode::SafeModeState(Default::default()))
+        .manage(browser::BrowserUiState(Default::default()))
+        .manage(watchdog::WatchdogState(Default::default()))
+        .manage(proxy::ProxyPort(Default::default()))
+        .manage(queue::QueueState(Default::default()))
+        .on_page_load(|webview, payload| {
+            if webview.label() == "main" && payload.event() == PageLoadEvent::Finished {
+                startup::mark("page_loaded");
+            }
+        })
         .setup(move |app| {
+            startup::mark("setup_start");
             let log_state = LogState::init(&app.handle());
             app.manage(log_state.clone());
+            if paths::is_confined() {
+                log_state.log_app(
+                    "INFO",
+                    &format!(
+                        "Confined environment detected, app data dir={}",
+                        app_data_base(app.handle()).display()
+                    ),
+                );
+            }
+            let settings_state = settings::SettingsState::load(app.handle());
+            server_log::apply(&log_state, &settings_state.get().server_log);
+            app.manage(settings_state);
+            startup::mark("settings_loaded");
+            recovery::start_session(app.handle());
+            paths::restore_folder_access(app.handle());
+            match proxy::start(app.handle()) {
+                Ok(port) => {
+                    if let Err(err) = instance::publish(port) {
+                        log_state.log_app("WARN", &format!("Instance info not written: {}", err));
+                    }
+                }
+                Err(err) => {
+                    log_state.log_app("ERROR", &format!("Backend proxy not started: {}", err));
+                }
+            }
+            startup::mark("proxy_started");
+            tray::init(app.handle());
+            jumplist::update(app.handle());
+            gpu::detect_in_background(app.handle());
+            feeds::start(app.handle());
+            disk::start_monitor(app.handle());
+            memory::start_monitor(app.handle());
+            watchdog::start(app.handle());
+            health::start(app.handle());
+            clipboard_watch::start(app.handle());
+            timings::start_monitor(app.handle());
+            search::start(app.handle());
+            arch::warn_if_translated(app.handle());
+            db::snapshot_on_version_change(app.handle());
+            startup::mark("setup_services");
 
             let sidecar_state = Arc::new(Mutex::new(None));
             app.manage(SidecarState(sidecar_state.clone()));
-            spawn_sidecar(&app.handle(), port_state_for_setup.clone())
-                .map_err(|err| -> Box<dyn std::error::Error> { err.into() })?;
+            // 外部后端模式：不拉起 sidecar，直接连接用户配置的远程后端
+            if let Some(url) = remote_backend::endpoint(app.handle()) {
+                remote_backend::start(app.handle(), url);
+            } else {
+                shutdown::kill_orphan(app.handle());
+                if !safe_mode::handle_locked_database(app.handle()) {
+                    spawn_sidecar(&app.handle(), port_state_for_setup.clone())
+                        .map_err(|err| -> Box<dyn std::error::Error> { err.into() })?;
+                }
+            }
+            app.manage(plugins::PluginState::default());
+            plugins::start_all(app.handle());
 
             Ok(())
         })
@@ -850,18 +1409,188 @@ pub fn run() {
             get_log_dir,
             open_log_dir,
             write_frontend_logs,
-            copy_image_to_clipboard,
+            clipboard::copy_image_to_clipboard,
+            clipboard::cancel_clipboard_copy,
             copy_text_to_clipboard,
+            clipboard::copy_image_as_html,
+            clipboard::copy_image_as_base64,
+            clipboard::paste_image_from_clipboard,
+            clipboard::copy_images_to_clipboard,
             read_image_from_clipboard,
             persist_ref_image,
             download_file_to_path,
             set_generation_active,
-            restart_sidecar
+            restart_sidecar,
+            restart_backend,
+            get_backend_status,
+            remote_backend::get_backend_endpoint,
+            remote_backend::set_backend_endpoint,
+            sidecar_config::get_sidecar_config,
+            sidecar_config::set_sidecar_config,
+            backend_logs::get_backend_logs,
+            server_log::get_server_log_settings,
+            server_log::set_server_log_settings,
+            server_log::get_backend_log_path,
+            gpu::get_gpu_capabilities,
+            webview::get_external_link_settings,
+            webview::set_external_link_settings,
+            webview::get_webview_storage_size,
+            webview::clear_webview_cache,
+            webview::toggle_devtools,
+            webview::set_devtools_enabled,
+            export::export_image_scaled,
+            annotate::export_annotated_image,
+            compare::export_comparison_image,
+            markdown::export_session_markdown,
+            history_export::export_history,
+            search::search_prompts,
+            search::rebuild_prompt_index,
+            ocr::ocr_image,
+            alt_text::suggest_alt_text,
+            alt_text::get_cached_alt_text,
+            rename::rename_files,
+            project::export_project,
+            project::import_project,
+            timelapse::get_timelapse_settings,
+            timelapse::set_timelapse_settings,
+            timelapse::list_timelapse_frames,
+            timelapse::assemble_timelapse,
+            colorblind::simulate_color_vision,
+            filters::apply_filters,
+            shutdown::get_shutdown_settings,
+            shutdown::set_shutdown_settings,
+            deepzoom::build_deep_zoom,
+            readiness::wait_for_backend,
+            histogram::get_image_histogram,
+            continuity::import_from_iphone,
+            display_profile::get_display_profile,
+            fonts::list_system_fonts,
+            input::show_emoji_picker,
+            accessibility::announce_task_event,
+            accessibility::get_accessibility_prefs,
+            sound::play_notification_sound,
+            sound::preview_notification_sound,
+            sound::get_sound_settings,
+            sound::set_sound_settings,
+            feedback::trigger_haptic,
+            feedback::trigger_feedback,
+            feedback::get_feedback_settings,
+            feedback::set_feedback_settings,
+            hooks::get_hook_settings,
+            hooks::set_hook_settings,
+            hooks::test_hook,
+            plugins::list_plugins,
+            plugins::start_plugin,
+            plugins::stop_plugin,
+            plugins::invoke_plugin,
+            remote_export::get_remote_targets,
+            remote_export::set_remote_targets,
+            remote_export::set_remote_target_secret,
+            remote_export::has_remote_target_secret,
+            remote_export::export_to_remote,
+            disk::get_disk_space,
+            disk::get_disk_guard_status,
+            disk::check_generation_allowed,
+            disk::get_disk_guard_settings,
+            disk::set_disk_guard_settings,
+            memory::get_memory_pressure,
+            storage::scan_storage_integrity,
+            storage::quarantine_storage_files,
+            storage::redownload_storage_file,
+            db::snapshot_database,
+            db::list_db_snapshots,
+            db::restore_db_snapshot,
+            db::optimize_database,
+            checkpoint::create_checkpoint,
+            checkpoint::restore_checkpoint,
+            safe_mode::get_safe_mode_status,
+            safe_mode::take_over_database,
+            importer::import_archive,
+            reorganize::reorganize_storage,
+            browser::open_in_browser,
+            watchdog::webview_heartbeat,
+            lan::prepare_lan_access,
+            share::create_share_link,
+            share::revoke_share_links,
+            paths::grant_folder_access,
+            paths::revoke_folder_access,
+            paths::list_granted_folders,
+            queue::pause_queue,
+            queue::resume_queue,
+            queue::get_queue_status,
+            queue::get_concurrency_settings,
+            queue::set_concurrency_settings,
+            usage::get_usage_stats,
+            usage::get_usage_settings,
+            usage::set_usage_settings,
+            timings::get_generation_stats,
+            bandwidth::get_bandwidth_usage,
+            bandwidth::get_bandwidth_settings,
+            bandwidth::set_bandwidth_settings,
+            presets::list_presets,
+            presets::save_preset,
+            presets::delete_preset,
+            presets::apply_preset,
+            presets::export_presets,
+            presets::import_presets,
+            history::list_recent_prompts,
+            history::rerun_recent_prompt,
+            history::remove_recent_prompt,
+            history::clear_recent_prompts,
+            references::add_references,
+            references::add_reference_from_url,
+            references::add_reference_from_clipboard,
+            references::search_references,
+            references::list_reference_tags,
+            references::set_reference_tags,
+            references::remove_reference,
+            feeds::list_reference_feeds,
+            feeds::add_reference_feed,
+            feeds::update_reference_feed,
+            feeds::remove_reference_feed,
+            feeds::check_reference_feed,
+            recovery::get_recovery_journal,
+            recovery::set_recovery_ui_state,
+            recovery::replay_recovery_requests,
+            recovery::discard_recovery_journal,
+            startup::get_startup_timings,
+            quick_capture::show_quick_capture,
+            quick_capture::hide_quick_capture,
+            quick_capture::submit_quick_capture,
+            sidecar::get_sidecar_environment,
+            backend_resources::get_backend_resources,
+            backend_resources::set_backend_resources_interval,
+            toast::send_action_notification,
+            pins::list_pinned_images,
+            pins::pin_image,
+            pins::unpin_image,
+            backend_version::get_backend_version,
+            health::get_health_settings,
+            health::set_health_settings,
+            crash_loop::get_restart_stats,
+            clock::get_time_settings,
+            clock::set_time_settings,
+            clock::preview_time_format,
+            clipboard_watch::get_clipboard_watch_settings,
+            clipboard_watch::set_clipboard_watch_settings,
+            permissions::get_permissions,
+            permissions::request_permission,
+            permissions::open_permission_settings,
+            fault::set_fault_injection,
+            fault::get_fault_injection,
+            session_key::set_session_api_key,
+            session_key::clear_session_api_key,
+            session_key::get_session_api_key,
+            export::save_image_as
         ])
         .build(tauri::generate_context!())
         .expect("error while running tauri application")
         .run(|app_handle, event| match event {
             tauri::RunEvent::WindowEvent { label, event, .. } => {
+                accessibility::on_window_event(app_handle, &label, &event);
+                display_profile::on_window_event(app_handle, &label, &event);
+                quick_capture::on_window_event(app_handle, &label, &event);
+                tray::on_window_event(app_handle, &label, &event);
                 #[cfg(target_os = "macos")]
                 {
                     if label == "main" {
@@ -882,6 +1611,13 @@ pub fn run() {
                         }
                     }
                 }
+                // 其他平台关闭主窗口即退出，窗口销毁时就停掉 sidecar，不等事件循环收尾
+                #[cfg(not(target_os = "macos"))]
+                {
+                    if label == "main" && matches!(event, tauri::WindowEvent::Destroyed) {
+                        stop_sidecar_gracefully(app_handle);
+                    }
+                }
             }
             tauri::RunEvent::ExitRequested { code, api, .. } => {
                 #[cfg(target_os = "macos")]
@@ -951,8 +1687,13 @@ pub fn run() {
                     }
                 }
             }
+            tauri::RunEvent::Ready => startup::mark("event_loop_ready"),
             tauri::RunEvent::Exit => {
-                kill_sidecar(app_handle);
+                instance::unpublish();
+                recovery::end_session(app_handle);
+                bandwidth::flush(app_handle);
+                stop_sidecar_gracefully(app_handle);
+                plugins::stop_all(app_handle);
             }
             _ => {}
         });
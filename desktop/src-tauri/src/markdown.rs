@@ -0,0 +1,266 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use rusqlite::OptionalExtension;
+use tauri::Manager;
+
+use crate::LogState;
+
+const IMAGES_DIR: &str = "images";
+const DOCUMENT_NAME: &str = "README.md";
+
+struct SessionTask {
+    task_id: String,
+    prompt: String,
+    prompt_original: String,
+    provider: String,
+    model_id: String,
+    width: i64,
+    height: i64,
+    local_path: String,
+    config_snapshot: String,
+    created_at: String,
+}
+
+#[derive(serde::Serialize)]
+pub struct MarkdownExportResult {
+    document: String,
+    images: usize,
+    // 本地文件已丢失的记录仍写进文档，只是没有图片链接
+    missing: Vec<String>,
+}
+
+fn text(row: &rusqlite::Row<'_>, index: usize) -> rusqlite::Result<String> {
+    Ok(row.get::<_, Option<String>>(index)?.unwrap_or_default())
+}
+
+// 会话即后端的文件夹：同一组提示词实验通常放在同一个文件夹里
+fn load_session(
+    app: &tauri::AppHandle,
+    session_id: &str,
+) -> Result<(String, Vec<SessionTask>), String> {
+    let conn = crate::db::open_readonly(app)?;
+    let name: String = conn
+        .query_row(
+            "SELECT name FROM folders WHERE id = CAST(?1 AS INTEGER) AND deleted_at IS NULL",
+            [session_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| format!("query folder failed: {}", e))?
+        .ok_or_else(|| format!("session not found: {}", session_id))?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT task_id, prompt, prompt_original, provider_name, model_id, width, height, \
+             local_path, config_snapshot, created_at FROM tasks \
+             WHERE folder_id = ?1 AND deleted_at IS NULL AND status = 'completed' \
+             ORDER BY created_at",
+        )
+        .map_err(|e| format!("query tasks failed: {}", e))?;
+    let rows = stmt
+        .query_map([session_id], |row| {
+            Ok(SessionTask {
+                task_id: row.get(0)?,
+                prompt: text(row, 1)?,
+                prompt_original: text(row, 2)?,
+                provider: text(row, 3)?,
+                model_id: text(row, 4)?,
+                width: row.get::<_, Option<i64>>(5)?.unwrap_or(0),
+                height: row.get::<_, Option<i64>>(6)?.unwrap_or(0),
+                local_path: text(row, 7)?,
+                config_snapshot: text(row, 8)?,
+                created_at: text(row, 9)?,
+            })
+        })
+        .map_err(|e| format!("query tasks failed: {}", e))?;
+    Ok((name, rows.flatten().collect()))
+}
+
+// 表格单元格里的 | 和换行会破坏表格结构
+fn table_cell(value: &str) -> String {
+    value.replace('|', "\\|").replace(['\r', '\n'], " ")
+}
+
+// 代码块围栏要比内容里最长的反引号串更长
+fn fence(text: &str) -> String {
+    let mut longest = 0;
+    let mut run = 0;
+    for c in text.chars() {
+        run = if c == '`' { run + 1 } else { 0 };
+        longest = longest.max(run);
+    }
+    "`".repeat(longest.max(2) + 1)
+}
+
+// 配置快照里的简单字段（比例、尺寸、数量等）作为参数列出，嵌套结构略过
+fn snapshot_params(snapshot: &str) -> Vec<(String, String)> {
+    let Ok(serde_json::Value::Object(map)) = serde_json::from_str(snapshot) else {
+        return Vec::new();
+    };
+    map.into_iter()
+        .filter_map(|(key, value)| {
+            let value = match value {
+                serde_json::Value::String(s) if !s.trim().is_empty() => s,
+                serde_json::Value::Number(n) => n.to_string(),
+                serde_json::Value::Bool(b) => b.to_string(),
+                _ => return None,
+            };
+            Some((key, value))
+        })
+        .filter(|(key, _)| !key.to_ascii_lowercase().contains("key"))
+        .collect()
+}
+
+fn render_task(
+    out: &mut String,
+    index: usize,
+    task: &SessionTask,
+    image: Option<&str>,
+    alt: Option<&str>,
+) {
+    out.push_str(&format!("## {}. {}\n\n", index, task.created_at));
+    if let Some(image) = image {
+        // 有缓存的无障碍替代文本时用它，否则取提示词开头
+        let alt: String = match alt {
+            Some(alt) => alt.to_string(),
+            None => task.prompt.chars().take(80).collect(),
+        };
+        out.push_str(&format!(
+            "![{}]({})\n\n",
+            alt.replace(['[', ']', '\n', '\r'], " "),
+            image
+        ));
+    } else {
+        out.push_str("> 图片文件缺失\n\n");
+    }
+    let fence = fence(&format!("{}{}", task.prompt, task.prompt_original));
+    out.push_str(&format!(
+        "**提示词**\n\n{}text\n{}\n{}\n\n",
+        fence, task.prompt, fence
+    ));
+    if !task.prompt_original.is_empty() && task.prompt_original != task.prompt {
+        out.push_str(&format!(
+            "**原始输入**\n\n{}text\n{}\n{}\n\n",
+            fence, task.prompt_original, fence
+        ));
+    }
+    let mut params = vec![
+        ("task_id".to_string(), task.task_id.clone()),
+        ("provider".to_string(), task.provider.clone()),
+        ("model_id".to_string(), task.model_id.clone()),
+    ];
+    if task.width > 0 && task.height > 0 {
+        params.push((
+            "size".to_string(),
+            format!("{}x{}", task.width, task.height),
+        ));
+    }
+    params.extend(snapshot_params(&task.config_snapshot));
+    out.push_str("| 参数 | 值 |\n| --- | --- |\n");
+    for (key, value) in params.iter().filter(|(_, v)| !v.is_empty()) {
+        out.push_str(&format!(
+            "| {} | {} |\n",
+            table_cell(key),
+            table_cell(value)
+        ));
+    }
+    out.push('\n');
+}
+
+fn export(
+    app: &tauri::AppHandle,
+    session_id: &str,
+    dest: &Path,
+) -> Result<MarkdownExportResult, String> {
+    let (name, tasks) = load_session(app, session_id)?;
+    let base = crate::app_data_base(app);
+    let sources: Vec<Option<PathBuf>> = tasks
+        .iter()
+        .map(|t| crate::storage::resolve_db_path(&base, &t.local_path).filter(|p| p.is_file()))
+        .collect();
+    let needed: u64 = sources
+        .iter()
+        .flatten()
+        .filter_map(|p| fs::metadata(p).ok())
+        .map(|m| m.len())
+        .sum();
+    let images_dir = dest.join(IMAGES_DIR);
+    fs::create_dir_all(&images_dir).map_err(|e| format!("create destination dir failed: {}", e))?;
+    crate::disk::ensure_free_space(dest, needed)?;
+
+    let mut doc = format!(
+        "# {}\n\n> 导出时间 {}，共 {} 条记录\n\n",
+        name.trim(),
+        crate::clock::display(app),
+        tasks.len()
+    );
+    let mut images = 0;
+    let mut missing = Vec::new();
+    for (i, (task, source)) in tasks.iter().zip(&sources).enumerate() {
+        // 图片按 task_id 命名，文档里用相对路径，整个目录可以直接提交到仓库
+        let alt = source
+            .as_deref()
+            .and_then(|p| crate::alt_text::cached_for_file(app, p));
+        let link = match source {
+            Some(source) => {
+                let ext = source
+                    .extension()
+                    .and_then(|e| e.to_str())
+                    .unwrap_or("png")
+                    .to_ascii_lowercase();
+                let file_name = format!("{}.{}", task.task_id, ext);
+                fs::copy(source, images_dir.join(&file_name))
+                    .map_err(|e| format!("copy image failed: {} ({})", e, source.display()))?;
+                images += 1;
+                Some(format!("{}/{}", IMAGES_DIR, file_name))
+            }
+            None => {
+                missing.push(task.task_id.clone());
+                None
+            }
+        };
+        render_task(&mut doc, i + 1, task, link.as_deref(), alt.as_deref());
+    }
+    let document = dest.join(DOCUMENT_NAME);
+    let temp = dest.join(format!("{}.part", DOCUMENT_NAME));
+    fs::write(&temp, doc)
+        .and_then(|_| fs::rename(&temp, &document))
+        .map_err(|e| {
+            let _ = fs::remove_file(&temp);
+            format!("write markdown failed: {}", e)
+        })?;
+    Ok(MarkdownExportResult {
+        document: document.to_string_lossy().to_string(),
+        images,
+        missing,
+    })
+}
+
+// 把一个会话（文件夹）导出成 Markdown 文档 + images/ 目录，path 为目标文件夹
+#[tauri::command]
+pub async fn export_session_markdown(
+    app: tauri::AppHandle,
+    session_id: String,
+    path: String,
+) -> Result<MarkdownExportResult, String> {
+    let dest = PathBuf::from(path.trim());
+    if dest.as_os_str().is_empty() {
+        return Err("path is empty".to_string());
+    }
+    let session_id = session_id.trim().to_string();
+    let app_for_task = app.clone();
+    let result =
+        tauri::async_runtime::spawn_blocking(move || export(&app_for_task, &session_id, &dest))
+            .await
+            .map_err(|e| format!("export markdown failed: {}", e))??;
+    app.state::<LogState>().log_app(
+        "INFO",
+        &format!(
+            "Session exported to markdown images={} missing={} dest={}",
+            result.images,
+            result.missing.len(),
+            result.document
+        ),
+    );
+    Ok(result)
+}
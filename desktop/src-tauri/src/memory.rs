@@ -0,0 +1,243 @@
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::OnceLock;
+#[cfg(not(target_os = "macos"))]
+use std::time::Duration;
+
+use tauri::{Emitter, Manager};
+
+use crate::fonts::FontState;
+use crate::LogState;
+
+// 轮询式检测（Windows / Linux）的间隔；macOS 由系统主动推送
+#[cfg(not(target_os = "macos"))]
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+static LEVEL: AtomicU8 = AtomicU8::new(0);
+// 系统回调里拿不到 AppHandle，启动时存一份
+static APP: OnceLock<tauri::AppHandle> = OnceLock::new();
+
+#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MemoryLevel {
+    Normal,
+    Warning,
+    Critical,
+}
+
+impl MemoryLevel {
+    fn from_u8(value: u8) -> Self {
+        match value {
+            1 => Self::Warning,
+            2 => Self::Critical,
+            _ => Self::Normal,
+        }
+    }
+
+    fn as_u8(self) -> u8 {
+        match self {
+            Self::Normal => 0,
+            Self::Warning => 1,
+            Self::Critical => 2,
+        }
+    }
+}
+
+#[derive(Clone, serde::Serialize)]
+struct MemoryPressurePayload {
+    level: MemoryLevel,
+}
+
+pub fn current() -> MemoryLevel {
+    MemoryLevel::from_u8(LEVEL.load(Ordering::SeqCst))
+}
+
+// 后台预取类任务（订阅源轮询等）在内存紧张时跳过本轮
+pub fn under_pressure() -> bool {
+    current() != MemoryLevel::Normal
+}
+
+// 壳层自己持有的可重建缓存：字体库扫描结果，下次用到时重新扫描
+fn release_caches(app: &tauri::AppHandle) {
+    app.state::<FontState>().0.lock().unwrap().take();
+}
+
+fn apply(app: &tauri::AppHandle, level: MemoryLevel) {
+    let previous = MemoryLevel::from_u8(LEVEL.swap(level.as_u8(), Ordering::SeqCst));
+    if previous == level {
+        return;
+    }
+    app.state::<LogState>().log_app(
+        if level == MemoryLevel::Normal {
+            "INFO"
+        } else {
+            "WARN"
+        },
+        &format!("Memory pressure changed {:?} -> {:?}", previous, level),
+    );
+    if level != MemoryLevel::Normal {
+        release_caches(app);
+    }
+    // 前端据此释放屏幕外的大图预览、暂停缩略图预加载
+    let _ = app.emit("memory-pressure", MemoryPressurePayload { level });
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use std::ffi::c_void;
+
+    use super::{apply, MemoryLevel, APP};
+
+    const DISPATCH_MEMORYPRESSURE_NORMAL: usize = 0x01;
+    const DISPATCH_MEMORYPRESSURE_WARN: usize = 0x02;
+    const DISPATCH_MEMORYPRESSURE_CRITICAL: usize = 0x04;
+
+    extern "C" {
+        static _dispatch_source_type_memorypressure: u8;
+        fn dispatch_get_global_queue(identifier: isize, flags: usize) -> *mut c_void;
+        fn dispatch_source_create(
+            source_type: *const c_void,
+            handle: usize,
+            mask: usize,
+            queue: *mut c_void,
+        ) -> *mut c_void;
+        fn dispatch_source_get_data(source: *mut c_void) -> usize;
+        fn dispatch_set_context(object: *mut c_void, context: *mut c_void);
+        fn dispatch_source_set_event_handler_f(
+            source: *mut c_void,
+            handler: extern "C" fn(*mut c_void),
+        );
+        fn dispatch_resume(object: *mut c_void);
+    }
+
+    extern "C" fn on_event(source: *mut c_void) {
+        let data = unsafe { dispatch_source_get_data(source) };
+        let level = if data & DISPATCH_MEMORYPRESSURE_CRITICAL != 0 {
+            MemoryLevel::Critical
+        } else if data & DISPATCH_MEMORYPRESSURE_WARN != 0 {
+            MemoryLevel::Warning
+        } else {
+            MemoryLevel::Normal
+        };
+        if let Some(app) = APP.get() {
+            apply(app, level);
+        }
+    }
+
+    // 系统内存压力变化时由 libdispatch 推送；source 随进程存活，不需要释放
+    pub fn start() {
+        unsafe {
+            let source = dispatch_source_create(
+                &_dispatch_source_type_memorypressure as *const u8 as *const c_void,
+                0,
+                DISPATCH_MEMORYPRESSURE_NORMAL
+                    | DISPATCH_MEMORYPRESSURE_WARN
+                    | DISPATCH_MEMORYPRESSURE_CRITICAL,
+                dispatch_get_global_queue(0, 0),
+            );
+            if source.is_null() {
+                return;
+            }
+            // context 直接放 source 本身，回调里用来读取压力等级
+            dispatch_set_context(source, source);
+            dispatch_source_set_event_handler_f(source, on_event);
+            dispatch_resume(source);
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use windows_sys::Win32::System::Memory::{
+        CreateMemoryResourceNotification, LowMemoryResourceNotification,
+        QueryMemoryResourceNotification,
+    };
+    use windows_sys::Win32::System::SystemInformation::{GlobalMemoryStatusEx, MEMORYSTATUSEX};
+
+    use super::{apply, MemoryLevel, APP, POLL_INTERVAL};
+
+    // 物理内存占用超过该比例时提前进入 warning，系统低内存通知来得比较晚
+    const WARNING_LOAD_PERCENT: u32 = 90;
+
+    fn memory_load() -> Option<u32> {
+        unsafe {
+            let mut status: MEMORYSTATUSEX = std::mem::zeroed();
+            status.dwLength = std::mem::size_of::<MEMORYSTATUSEX>() as u32;
+            (GlobalMemoryStatusEx(&mut status) != 0).then_some(status.dwMemoryLoad)
+        }
+    }
+
+    pub fn start() {
+        std::thread::spawn(|| {
+            let low = unsafe { CreateMemoryResourceNotification(LowMemoryResourceNotification) };
+            loop {
+                std::thread::sleep(POLL_INTERVAL);
+                let mut is_low = 0;
+                let low_signaled = !low.is_null()
+                    && unsafe { QueryMemoryResourceNotification(low, &mut is_low) } != 0
+                    && is_low != 0;
+                let level = if low_signaled {
+                    MemoryLevel::Critical
+                } else if memory_load().is_some_and(|load| load >= WARNING_LOAD_PERCENT) {
+                    MemoryLevel::Warning
+                } else {
+                    MemoryLevel::Normal
+                };
+                if let Some(app) = APP.get() {
+                    apply(app, level);
+                }
+            }
+        });
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+mod platform {
+    use super::{apply, MemoryLevel, APP, POLL_INTERVAL};
+
+    // 按 MemAvailable 占总内存的比例判断，没有 /proc/meminfo 时不检测
+    fn available_ratio() -> Option<f64> {
+        let text = std::fs::read_to_string("/proc/meminfo").ok()?;
+        let field = |name: &str| -> Option<f64> {
+            text.lines()
+                .find(|l| l.starts_with(name))?
+                .split_whitespace()
+                .nth(1)?
+                .parse()
+                .ok()
+        };
+        let total = field("MemTotal:")?;
+        let available = field("MemAvailable:")?;
+        (total > 0.0).then(|| available / total)
+    }
+
+    pub fn start() {
+        std::thread::spawn(|| loop {
+            std::thread::sleep(POLL_INTERVAL);
+            let Some(ratio) = available_ratio() else {
+                return;
+            };
+            let level = if ratio < 0.05 {
+                MemoryLevel::Critical
+            } else if ratio < 0.10 {
+                MemoryLevel::Warning
+            } else {
+                MemoryLevel::Normal
+            };
+            if let Some(app) = APP.get() {
+                apply(app, level);
+            }
+        });
+    }
+}
+
+pub fn start_monitor(app: &tauri::AppHandle) {
+    if APP.set(app.clone()).is_err() {
+        return;
+    }
+    platform::start();
+}
+
+#[tauri::command]
+pub fn get_memory_pressure() -> MemoryLevel {
+    current()
+}
@@ -0,0 +1,232 @@
+use std::path::Path;
+
+use tauri::Manager;
+
+use crate::LogState;
+
+#[derive(serde::Serialize)]
+pub struct OcrLine {
+    text: String,
+    // Windows OCR 不提供置信度
+    confidence: Option<f32>,
+}
+
+#[derive(serde::Serialize)]
+pub struct OcrResult {
+    text: String,
+    lines: Vec<OcrLine>,
+    // vision / windows / tesseract
+    engine: &'static str,
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use std::path::Path;
+
+    use objc2::rc::{Allocated, Retained};
+    use objc2::runtime::AnyObject;
+    use objc2::{class, msg_send};
+    use objc2_foundation::{NSArray, NSDictionary, NSError, NSString, NSURL};
+
+    use super::OcrLine;
+
+    pub const ENGINE: &str = "vision";
+    // 生成图里的文字以中英文为主
+    const DEFAULT_LANGUAGES: &[&str] = &["zh-Hans", "zh-Hant", "en-US"];
+    // VNRequestTextRecognitionLevelAccurate
+    const RECOGNITION_LEVEL_ACCURATE: isize = 0;
+
+    #[link(name = "Vision", kind = "framework")]
+    extern "C" {}
+
+    // Vision 的请求是同步执行的，调用方已经在后台线程
+    pub fn recognize(path: &Path, languages: &[String]) -> Result<Vec<OcrLine>, String> {
+        unsafe {
+            let url = NSURL::fileURLWithPath(&NSString::from_str(&path.to_string_lossy()));
+            let request: Retained<AnyObject> = msg_send![class!(VNRecognizeTextRequest), new];
+            let _: () = msg_send![&request, setRecognitionLevel: RECOGNITION_LEVEL_ACCURATE];
+            let _: () = msg_send![&request, setUsesLanguageCorrection: true];
+            let languages: Vec<Retained<NSString>> = if languages.is_empty() {
+                DEFAULT_LANGUAGES
+                    .iter()
+                    .map(|l| NSString::from_str(l))
+                    .collect()
+            } else {
+                languages.iter().map(|l| NSString::from_str(l)).collect()
+            };
+            let languages = NSArray::from_retained_slice(&languages);
+            let _: () = msg_send![&request, setRecognitionLanguages: &*languages];
+
+            let options = NSDictionary::<NSString, AnyObject>::new();
+            let handler: Allocated<AnyObject> = msg_send![class!(VNImageRequestHandler), alloc];
+            let handler: Retained<AnyObject> =
+                msg_send![handler, initWithURL: &*url, options: &*options];
+            let requests = NSArray::from_retained_slice(&[request.clone()]);
+            let performed: Result<(), Retained<NSError>> =
+                msg_send![&handler, performRequests: &*requests, error: _];
+            performed.map_err(|e| format!("vision ocr failed: {}", e.localizedDescription()))?;
+
+            let results: Option<Retained<NSArray<AnyObject>>> = msg_send![&request, results];
+            let mut lines = Vec::new();
+            for observation in results.iter().flat_map(|r| r.iter()) {
+                let candidates: Retained<NSArray<AnyObject>> =
+                    msg_send![&observation, topCandidates: 1usize];
+                let Some(candidate) = candidates.firstObject() else {
+                    continue;
+                };
+                let text: Retained<NSString> = msg_send![&candidate, string];
+                let confidence: f32 = msg_send![&candidate, confidence];
+                lines.push(OcrLine {
+                    text: text.to_string(),
+                    confidence: Some(confidence),
+                });
+            }
+            Ok(lines)
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use std::path::Path;
+
+    use windows::core::HSTRING;
+    use windows::Globalization::Language;
+    use windows::Graphics::Imaging::BitmapDecoder;
+    use windows::Media::Ocr::OcrEngine;
+    use windows::Storage::{FileAccessMode, StorageFile};
+    use windows::Win32::System::Com::{CoInitializeEx, CoUninitialize, COINIT_MULTITHREADED};
+
+    use super::OcrLine;
+
+    pub const ENGINE: &str = "windows";
+
+    fn run(path: &Path, languages: &[String]) -> windows::core::Result<Option<Vec<OcrLine>>> {
+        let file = StorageFile::GetFileFromPathAsync(&HSTRING::from(path))?.get()?;
+        let stream = file.OpenAsync(FileAccessMode::Read)?.get()?;
+        let bitmap = BitmapDecoder::CreateAsync(&stream)?
+            .get()?
+            .GetSoftwareBitmapAsync()?
+            .get()?;
+        // 指定语言未安装 OCR 语言包时退回用户配置的语言
+        let engine = languages
+            .iter()
+            .filter_map(|tag| Language::CreateLanguage(&HSTRING::from(tag.as_str())).ok())
+            .find_map(|lang| OcrEngine::TryCreateFromLanguage(&lang).ok());
+        let engine = match engine {
+            Some(engine) => engine,
+            None => match OcrEngine::TryCreateFromUserProfileLanguages() {
+                Ok(engine) => engine,
+                Err(_) => return Ok(None),
+            },
+        };
+        let result = engine.RecognizeAsync(&bitmap)?.get()?;
+        let mut lines = Vec::new();
+        for line in result.Lines()? {
+            lines.push(OcrLine {
+                text: line.Text()?.to_string(),
+                confidence: None,
+            });
+        }
+        Ok(Some(lines))
+    }
+
+    pub fn recognize(path: &Path, languages: &[String]) -> Result<Vec<OcrLine>, String> {
+        let max = OcrEngine::MaxImageDimension().unwrap_or(u32::MAX);
+        let (width, height) =
+            image::image_dimensions(path).map_err(|e| format!("read image failed: {}", e))?;
+        if width.max(height) > max {
+            return Err(format!("image too large for windows ocr: max {}px", max));
+        }
+        unsafe {
+            let _ = CoInitializeEx(None, COINIT_MULTITHREADED);
+        }
+        let result = run(path, languages);
+        unsafe { CoUninitialize() };
+        result
+            .map_err(|e| format!("windows ocr failed: {}", e))?
+            .ok_or_else(|| "no OCR language pack installed".to_string())
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+mod platform {
+    use std::path::Path;
+    use std::process::Command;
+
+    use super::OcrLine;
+
+    pub const ENGINE: &str = "tesseract";
+    const DEFAULT_LANGUAGES: &str = "chi_sim+eng";
+
+    // 没有系统 OCR，依赖 PATH 里的 tesseract；languages 使用 tesseract 的语言代码
+    pub fn recognize(path: &Path, languages: &[String]) -> Result<Vec<OcrLine>, String> {
+        let languages = if languages.is_empty() {
+            DEFAULT_LANGUAGES.to_string()
+        } else {
+            languages.join("+")
+        };
+        let output = Command::new("tesseract")
+            .arg(path)
+            .arg("stdout")
+            .args(["-l", &languages])
+            .output()
+            .map_err(|e| format!("run tesseract failed (is it installed?): {}", e))?;
+        if !output.status.success() {
+            return Err(format!(
+                "tesseract failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(|l| l.trim())
+            .filter(|l| !l.is_empty())
+            .map(|l| OcrLine {
+                text: l.to_string(),
+                confidence: None,
+            })
+            .collect())
+    }
+}
+
+fn recognize(path: &Path, languages: &[String]) -> Result<OcrResult, String> {
+    let lines = platform::recognize(path, languages)?;
+    Ok(OcrResult {
+        text: lines
+            .iter()
+            .map(|l| l.text.as_str())
+            .collect::<Vec<_>>()
+            .join("\n"),
+        lines,
+        engine: platform::ENGINE,
+    })
+}
+
+// 识别图片中的文字（生成图或参考图），languages 缺省时按中英文识别
+#[tauri::command]
+pub async fn ocr_image(
+    app: tauri::AppHandle,
+    path: String,
+    languages: Option<Vec<String>>,
+) -> Result<OcrResult, String> {
+    let file_path = crate::resolve_local_path(&app, &path)?;
+    let languages: Vec<String> = languages
+        .unwrap_or_default()
+        .into_iter()
+        .map(|l| l.trim().to_string())
+        .filter(|l| !l.is_empty())
+        .collect();
+    let result = tauri::async_runtime::spawn_blocking(move || recognize(&file_path, &languages))
+        .await
+        .map_err(|e| format!("ocr task failed: {}", e))??;
+    app.state::<LogState>().log_app(
+        "INFO",
+        &format!(
+            "OCR finished engine={} lines={}",
+            result.engine,
+            result.lines.len()
+        ),
+    );
+    Ok(result)
+}
@@ -0,0 +1,145 @@
+use image::DynamicImage;
+
+use crate::export::OutputFormat;
+
+// oxipng 预设：2 为默认档，压缩率和耗时比较均衡
+const OXIPNG_PRESET: u8 = 2;
+// NeuQuant 采样系数，1 最精细，10 是速度和质量的常用折中
+const QUANT_SAMPLE_FACTOR: i32 = 10;
+
+#[derive(Clone, serde::Serialize)]
+pub struct WebOptimization {
+    // 普通编码（与非 web 导出一致）的体积
+    baseline_bytes: u64,
+    optimized_bytes: u64,
+    pub saved_bytes: u64,
+    saved_percent: f64,
+    // 是否采用了有损的调色板量化（仅 PNG）
+    quantized: bool,
+}
+
+fn encode_baseline(
+    img: &DynamicImage,
+    format: OutputFormat,
+    quality: u8,
+) -> Result<Vec<u8>, String> {
+    let mut bytes = Vec::new();
+    match format {
+        OutputFormat::Png => crate::export::write_png(img, &mut bytes, None)?,
+        OutputFormat::Jpeg => crate::export::write_jpeg(img, &mut bytes, quality, None)?,
+        OutputFormat::Webp => crate::export::write_webp(img, &mut bytes)?,
+    }
+    Ok(bytes)
+}
+
+fn oxipng_pass(png: &[u8]) -> Result<Vec<u8>, String> {
+    let mut options = oxipng::Options::from_preset(OXIPNG_PRESET);
+    // 网页用图不需要 pHYs、文本块等元数据
+    options.strip = oxipng::StripChunks::Safe;
+    oxipng::optimize_from_memory(png, &options).map_err(|e| format!("optimize png failed: {}", e))
+}
+
+// 量化到 256 色调色板（pngquant 的思路），带透明通道时写 tRNS
+fn quantize_png(img: &DynamicImage) -> Result<Vec<u8>, String> {
+    let rgba = img.to_rgba8();
+    let quant = color_quant::NeuQuant::new(QUANT_SAMPLE_FACTOR, 256, rgba.as_raw());
+    let indices: Vec<u8> = rgba
+        .as_raw()
+        .chunks_exact(4)
+        .map(|px| quant.index_of(px) as u8)
+        .collect();
+    let map = quant.color_map_rgba();
+    let palette: Vec<u8> = map
+        .chunks_exact(4)
+        .flat_map(|c| [c[0], c[1], c[2]])
+        .collect();
+    let alpha: Vec<u8> = map.chunks_exact(4).map(|c| c[3]).collect();
+
+    let mut bytes = Vec::new();
+    let mut encoder = png::Encoder::new(&mut bytes, img.width(), img.height());
+    encoder.set_color(png::ColorType::Indexed);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.set_palette(palette);
+    if alpha.iter().any(|a| *a != 255) {
+        encoder.set_trns(alpha);
+    }
+    let mut writer = encoder
+        .write_header()
+        .map_err(|e| format!("encode png failed: {}", e))?;
+    writer
+        .write_image_data(&indices)
+        .map_err(|e| format!("encode png failed: {}", e))?;
+    writer
+        .finish()
+        .map_err(|e| format!("encode png failed: {}", e))?;
+    Ok(bytes)
+}
+
+// 渐进式 + 优化 Huffman 表 + 4:2:0 采样，接近 mozjpeg 的默认输出
+fn optimized_jpeg(img: &DynamicImage, quality: u8) -> Result<Vec<u8>, String> {
+    use jpeg_encoder::{ColorType, Encoder, SamplingFactor};
+
+    let rgb = img.to_rgb8();
+    let (Ok(width), Ok(height)) = (u16::try_from(rgb.width()), u16::try_from(rgb.height())) else {
+        return Err("image too large for jpeg".to_string());
+    };
+    let mut bytes = Vec::new();
+    let mut encoder = Encoder::new(&mut bytes, quality.clamp(1, 100));
+    encoder.set_progressive(true);
+    encoder.set_optimized_huffman_tables(true);
+    encoder.set_sampling_factor(SamplingFactor::R_4_2_0);
+    encoder
+        .encode(rgb.as_raw(), width, height, ColorType::Rgb)
+        .map_err(|e| format!("encode jpeg failed: {}", e))?;
+    Ok(bytes)
+}
+
+// 网页导出：在普通编码的基础上跑优化，取体积最小的结果；lossy=false 时 PNG 只做无损优化
+pub fn encode_web(
+    img: &DynamicImage,
+    format: OutputFormat,
+    quality: u8,
+    lossy: bool,
+) -> Result<(Vec<u8>, WebOptimization), String> {
+    let baseline = encode_baseline(img, format, quality)?;
+    let mut best = baseline.clone();
+    let mut quantized = false;
+    match format {
+        OutputFormat::Png => {
+            let lossless = oxipng_pass(&baseline)?;
+            if lossless.len() < best.len() {
+                best = lossless;
+            }
+            if lossy {
+                let palette = oxipng_pass(&quantize_png(img)?)?;
+                if palette.len() < best.len() {
+                    best = palette;
+                    quantized = true;
+                }
+            }
+        }
+        OutputFormat::Jpeg => {
+            let optimized = optimized_jpeg(img, quality)?;
+            if optimized.len() < best.len() {
+                best = optimized;
+            }
+        }
+        // WebP 编码本身就是无损压缩，没有额外的优化步骤
+        OutputFormat::Webp => {}
+    }
+    let baseline_bytes = baseline.len() as u64;
+    let optimized_bytes = best.len() as u64;
+    let saved_bytes = baseline_bytes.saturating_sub(optimized_bytes);
+    let report = WebOptimization {
+        baseline_bytes,
+        optimized_bytes,
+        saved_bytes,
+        saved_percent: if baseline_bytes == 0 {
+            0.0
+        } else {
+            (saved_bytes as f64 * 1000.0 / baseline_bytes as f64).round() / 10.0
+        },
+        quantized,
+    };
+    Ok((best, report))
+}
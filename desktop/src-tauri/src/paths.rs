@@ -0,0 +1,285 @@
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use tauri::Manager;
+
+#[cfg(target_os = "macos")]
+use crate::LogState;
+
+// 壳层所有目录解析集中在这里：App Sandbox 下工作目录、临时目录和可访问范围都与普通分发不同，
+// 以后上架 Mac App Store 时只需要调整这一处
+
+#[derive(Clone, Default, serde::Serialize, serde::Deserialize)]
+struct FolderBookmark {
+    path: String,
+    // security-scoped bookmark 原始数据（十六进制）
+    #[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+    data: String,
+}
+
+#[derive(Default, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+struct BookmarkStore {
+    folders: Vec<FolderBookmark>,
+}
+
+// 串行化 folder_bookmarks.json 的读写
+static BOOKMARK_LOCK: Mutex<()> = Mutex::new(());
+
+// 沙盒进程会带上容器 ID 环境变量
+pub fn is_sandboxed() -> bool {
+    cfg!(target_os = "macos") && std::env::var_os("APP_SANDBOX_CONTAINER_ID").is_some()
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Confinement {
+    None,
+    Flatpak,
+    Snap,
+}
+
+// Linux 下的 Flatpak / Snap 打包：文件系统只暴露部分目录，文件对话框需要走 XDG Desktop Portal
+pub fn linux_confinement() -> Confinement {
+    if !cfg!(target_os = "linux") {
+        return Confinement::None;
+    }
+    if std::env::var_os("FLATPAK_ID").is_some() || Path::new("/.flatpak-info").exists() {
+        Confinement::Flatpak
+    } else if std::env::var_os("SNAP").is_some() {
+        Confinement::Snap
+    } else {
+        Confinement::None
+    }
+}
+
+pub fn is_confined() -> bool {
+    is_sandboxed() || linux_confinement() != Confinement::None
+}
+
+// 必须在创建窗口（GTK 初始化）之前调用
+pub fn prepare_environment() {
+    // 受限环境里 GTK 自带的文件选择器看不到宿主目录，改走 portal，由宿主弹窗并按用户选择授予访问
+    if linux_confinement() != Confinement::None && std::env::var_os("GTK_USE_PORTAL").is_none() {
+        std::env::set_var("GTK_USE_PORTAL", "1");
+    }
+}
+
+// AppData 不可用时回退到当前目录（极少见，多为开发环境权限问题）
+// 沙盒下 app_data_dir 会自动落在 ~/Library/Containers/<id>/Data 内；
+// Flatpak 的 XDG_DATA_HOME 已指向 ~/.var/app/<id>/data，可以直接用
+pub fn app_data_dir(app: &tauri::AppHandle) -> PathBuf {
+    // Snap 的 HOME 按 revision 区分，升级时才整体复制；大量图片放在跨版本共享的 SNAP_USER_COMMON
+    if linux_confinement() == Confinement::Snap {
+        if let Some(common) = std::env::var_os("SNAP_USER_COMMON") {
+            return PathBuf::from(common).join(&app.config().identifier);
+        }
+    }
+    app.path()
+        .app_data_dir()
+        .unwrap_or_else(|_| std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")))
+}
+
+// sidecar 的工作目录（data.db、storage/ 都在这里），与后端 getWorkDir 的选择保持一致：
+// 受限环境下由壳层通过 APP_DATA_DIR 指定，否则后端用 Go 的 UserConfigDir/<identifier>，
+// 对应 tauri 的 app_config_dir（Linux 下是 ~/.config，不同于 app_data_dir 的 ~/.local/share）
+pub fn backend_data_dir(app: &tauri::AppHandle) -> PathBuf {
+    if is_confined() {
+        return app_data_dir(app);
+    }
+    app.path()
+        .app_config_dir()
+        .unwrap_or_else(|_| app_data_dir(app))
+}
+
+// 后端把图片写在工作目录下的 storage/，数据库里记录的也是相对这里的路径
+pub fn storage_dir(app: &tauri::AppHandle) -> PathBuf {
+    backend_data_dir(app).join("storage")
+}
+
+// 沙盒下只能写容器内的临时目录，tauri 解析出的 temp_dir 已经考虑了这一点
+pub fn temp_dir(app: &tauri::AppHandle) -> PathBuf {
+    app.path()
+        .temp_dir()
+        .unwrap_or_else(|_| std::env::temp_dir())
+}
+
+// 前端传入的相对路径可能相对 AppData、当前目录或资源目录（历史数据 + 开发/打包环境差异）
+pub fn local_path_candidates(app: &tauri::AppHandle, input: &Path) -> Vec<PathBuf> {
+    if input.is_absolute() {
+        return vec![input.to_path_buf()];
+    }
+    let mut candidates = vec![backend_data_dir(app).join(input)];
+    let legacy = app_data_dir(app).join(input);
+    if !candidates.contains(&legacy) {
+        candidates.push(legacy);
+    }
+    // 沙盒进程的当前目录是 /，拼出来的路径既不存在也无权访问
+    if !is_sandboxed() {
+        if let Ok(current_dir) = std::env::current_dir() {
+            candidates.push(current_dir.join(input));
+        }
+    }
+    if let Ok(resource_dir) = app.path().resource_dir() {
+        candidates.push(resource_dir.join(input));
+    }
+    // 最后再尝试“原样相对路径”（少数场景下当前目录就是预期目录）
+    candidates.push(input.to_path_buf());
+    candidates
+}
+
+fn bookmarks_path(app: &tauri::AppHandle) -> PathBuf {
+    app_data_dir(app).join("folder_bookmarks.json")
+}
+
+#[cfg(target_os = "macos")]
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(target_os = "macos")]
+fn from_hex(text: &str) -> Option<Vec<u8>> {
+    if !text.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..text.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(text.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+#[cfg(target_os = "macos")]
+mod bookmark {
+    use objc2::runtime::Bool;
+    use objc2_foundation::{
+        NSData, NSString, NSURLBookmarkCreationOptions, NSURLBookmarkResolutionOptions, NSURL,
+    };
+
+    // 用户通过对话框选中的目录只在本次运行内可访问，持久化需要 security-scoped bookmark
+    pub fn create(path: &str) -> Result<Vec<u8>, String> {
+        let url = NSURL::fileURLWithPath(&NSString::from_str(path));
+        url.bookmarkDataWithOptions_includingResourceValuesForKeys_relativeToURL_error(
+            NSURLBookmarkCreationOptions::WithSecurityScope,
+            None,
+            None,
+        )
+        .map(|data| data.to_vec())
+        .map_err(|e| format!("create bookmark failed: {}", e.localizedDescription()))
+    }
+
+    // 返回解析后的路径以及 bookmark 是否过期（目录被移动/重命名后需要重新生成）
+    pub fn resolve_and_access(data: &[u8]) -> Result<(String, bool), String> {
+        let data = NSData::with_bytes(data);
+        let mut stale = Bool::NO;
+        let url = unsafe {
+            NSURL::URLByResolvingBookmarkData_options_relativeToURL_bookmarkDataIsStale_error(
+                &data,
+                NSURLBookmarkResolutionOptions::WithSecurityScope,
+                None,
+                &mut stale,
+            )
+        }
+        .map_err(|e| format!("resolve bookmark failed: {}", e.localizedDescription()))?;
+        // 访问权限保持到进程退出，不调用 stopAccessing
+        if !unsafe { url.startAccessingSecurityScopedResource() } {
+            return Err("start accessing security scoped resource failed".to_string());
+        }
+        let path = url
+            .path()
+            .map(|p| p.to_string())
+            .ok_or_else(|| "resolved bookmark has no path".to_string())?;
+        Ok((path, stale.as_bool()))
+    }
+}
+
+// 启动时恢复用户授权过的目录访问权限；失效的 bookmark 直接丢弃
+pub fn restore_folder_access(app: &tauri::AppHandle) {
+    #[cfg(target_os = "macos")]
+    {
+        if !is_sandboxed() {
+            return;
+        }
+        let _guard = BOOKMARK_LOCK.lock().unwrap();
+        let path = bookmarks_path(app);
+        let store: BookmarkStore = crate::settings::read_json_or_default(&path);
+        let mut kept = Vec::new();
+        for folder in store.folders {
+            let resolved = from_hex(&folder.data)
+                .ok_or_else(|| "invalid bookmark data".to_string())
+                .and_then(|data| bookmark::resolve_and_access(&data));
+            match resolved {
+                Ok((resolved_path, stale)) => {
+                    let data = if stale {
+                        bookmark::create(&resolved_path)
+                            .map(|d| to_hex(&d))
+                            .unwrap_or(folder.data)
+                    } else {
+                        folder.data
+                    };
+                    kept.push(FolderBookmark {
+                        path: resolved_path,
+                        data,
+                    });
+                }
+                Err(err) => {
+                    app.state::<LogState>().log_app(
+                        "WARN",
+                        &format!("Drop folder bookmark path={}: {}", folder.path, err),
+                    );
+                }
+            }
+        }
+        let _ = crate::settings::write_json_atomic(&path, &BookmarkStore { folders: kept });
+    }
+    #[cfg(not(target_os = "macos"))]
+    let _ = app;
+}
+
+// 用户通过系统对话框选中目录后调用，沙盒下为该目录保存长期访问权限；返回 false 表示当前环境无需授权
+#[tauri::command]
+pub fn grant_folder_access(app: tauri::AppHandle, path: String) -> Result<bool, String> {
+    let path = path.trim().to_string();
+    if path.is_empty() {
+        return Err("path is empty".to_string());
+    }
+    #[cfg(target_os = "macos")]
+    {
+        if is_sandboxed() {
+            let data = to_hex(&bookmark::create(&path)?);
+            let _guard = BOOKMARK_LOCK.lock().unwrap();
+            let store_path = bookmarks_path(&app);
+            let mut store: BookmarkStore = crate::settings::read_json_or_default(&store_path);
+            store.folders.retain(|f| f.path != path);
+            store.folders.push(FolderBookmark {
+                path: path.clone(),
+                data,
+            });
+            crate::settings::write_json_atomic(&store_path, &store)?;
+            app.state::<LogState>()
+                .log_app("INFO", &format!("Folder access granted path={}", path));
+            return Ok(true);
+        }
+    }
+    #[cfg(not(target_os = "macos"))]
+    let _ = app;
+    Ok(false)
+}
+
+// 撤销后下次启动不再恢复访问权限（本次运行内已授予的权限保持到退出）
+#[tauri::command]
+pub fn revoke_folder_access(app: tauri::AppHandle, path: String) -> Result<(), String> {
+    let _guard = BOOKMARK_LOCK.lock().unwrap();
+    let store_path = bookmarks_path(&app);
+    let mut store: BookmarkStore = crate::settings::read_json_or_default(&store_path);
+    let before = store.folders.len();
+    store.folders.retain(|f| f.path != path.trim());
+    if store.folders.len() != before {
+        crate::settings::write_json_atomic(&store_path, &store)?;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn list_granted_folders(app: tauri::AppHandle) -> Vec<String> {
+    let store: BookmarkStore = crate::settings::read_json_or_default(&bookmarks_path(&app));
+    store.folders.into_iter().map(|f| f.path).collect()
+}
@@ -0,0 +1,253 @@
+use std::sync::Mutex;
+
+use tauri::{Emitter, Manager};
+use tauri_plugin_opener::OpenerExt;
+
+use crate::LogState;
+
+#[derive(Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Permission {
+    // 截屏取参考图
+    ScreenRecording,
+    // 导出到“照片”
+    Photos,
+    // 全局快捷键（快速输入窗口）
+    Accessibility,
+}
+
+const ALL: [Permission; 3] = [
+    Permission::ScreenRecording,
+    Permission::Photos,
+    Permission::Accessibility,
+];
+
+// 本次运行里已经请求过的权限：屏幕录制 / 辅助功能被拒绝后系统不会再弹框，再次请求时改为打开系统设置
+static ASKED: Mutex<Vec<Permission>> = Mutex::new(Vec::new());
+
+#[derive(Clone, Copy, PartialEq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PermissionState {
+    Granted,
+    Denied,
+    // 还没弹过系统授权框，request_permission 会触发
+    NotDetermined,
+    // 家长控制 / MDM 限制，用户自己改不了
+    Restricted,
+    // 当前平台不需要授权
+    NotRequired,
+}
+
+#[derive(Clone, serde::Serialize)]
+pub struct PermissionStatus {
+    permission: Permission,
+    state: PermissionState,
+    // 被拒绝后引导用户去的系统设置面板
+    settings_url: Option<&'static str>,
+}
+
+impl Permission {
+    fn name(self) -> &'static str {
+        match self {
+            Self::ScreenRecording => "screen_recording",
+            Self::Photos => "photos",
+            Self::Accessibility => "accessibility",
+        }
+    }
+
+    fn settings_url(self) -> Option<&'static str> {
+        if !cfg!(target_os = "macos") {
+            return None;
+        }
+        Some(match self {
+            Self::ScreenRecording => {
+                "x-apple.systempreferences:com.apple.preference.security?Privacy_ScreenCapture"
+            }
+            Self::Photos => {
+                "x-apple.systempreferences:com.apple.preference.security?Privacy_Photos"
+            }
+            Self::Accessibility => {
+                "x-apple.systempreferences:com.apple.preference.security?Privacy_Accessibility"
+            }
+        })
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use objc2::rc::Retained;
+    use objc2::runtime::{AnyObject, Bool};
+    use objc2::{class, msg_send};
+    use objc2_foundation::NSString;
+
+    use super::{Permission, PermissionState};
+
+    // PHAccessLevelAddOnly：导出只需要写入权限，不读取用户的照片
+    const PH_ACCESS_LEVEL_ADD_ONLY: isize = 1;
+
+    #[link(name = "CoreGraphics", kind = "framework")]
+    extern "C" {
+        fn CGPreflightScreenCaptureAccess() -> bool;
+        fn CGRequestScreenCaptureAccess() -> bool;
+    }
+
+    #[link(name = "ApplicationServices", kind = "framework")]
+    extern "C" {
+        fn AXIsProcessTrusted() -> bool;
+        fn AXIsProcessTrustedWithOptions(options: *const AnyObject) -> bool;
+    }
+
+    #[link(name = "Photos", kind = "framework")]
+    extern "C" {}
+
+    fn photos_state(status: isize) -> PermissionState {
+        // PHAuthorizationStatus
+        match status {
+            0 => PermissionState::NotDetermined,
+            1 => PermissionState::Restricted,
+            2 => PermissionState::Denied,
+            // 3 authorized、4 limited 都可以写入
+            _ => PermissionState::Granted,
+        }
+    }
+
+    // 屏幕录制和辅助功能没有“未询问”的查询接口，未授权统一报 denied，request 时仍会尝试弹框
+    pub fn check(permission: Permission) -> PermissionState {
+        let granted = match permission {
+            Permission::ScreenRecording => unsafe { CGPreflightScreenCaptureAccess() },
+            Permission::Accessibility => unsafe { AXIsProcessTrusted() },
+            Permission::Photos => {
+                let status: isize = unsafe {
+                    msg_send![class!(PHPhotoLibrary), authorizationStatusForAccessLevel: PH_ACCESS_LEVEL_ADD_ONLY]
+                };
+                return photos_state(status);
+            }
+        };
+        if granted {
+            PermissionState::Granted
+        } else {
+            PermissionState::Denied
+        }
+    }
+
+    // 系统只在第一次请求时弹框；用户选择的结果异步回调 on_result
+    pub fn request(permission: Permission, on_result: impl Fn(PermissionState) + 'static) {
+        match permission {
+            Permission::ScreenRecording => unsafe {
+                CGRequestScreenCaptureAccess();
+            },
+            Permission::Accessibility => unsafe {
+                let key = NSString::from_str("AXTrustedCheckOptionPrompt");
+                let value: Retained<AnyObject> =
+                    msg_send![class!(NSNumber), numberWithBool: Bool::YES];
+                let options: Retained<AnyObject> =
+                    msg_send![class!(NSDictionary), dictionaryWithObject: &*value, forKey: &*key];
+                AXIsProcessTrustedWithOptions(&*options);
+            },
+            Permission::Photos => {
+                let handler = block2::RcBlock::new(move |status: isize| {
+                    on_result(photos_state(status));
+                });
+                unsafe {
+                    let _: () = msg_send![
+                        class!(PHPhotoLibrary),
+                        requestAuthorizationForAccessLevel: PH_ACCESS_LEVEL_ADD_ONLY,
+                        handler: &*handler
+                    ];
+                }
+            }
+        }
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+mod platform {
+    use super::{Permission, PermissionState};
+
+    pub fn check(_permission: Permission) -> PermissionState {
+        PermissionState::NotRequired
+    }
+
+    pub fn request(_permission: Permission, _on_result: impl Fn(PermissionState) + 'static) {}
+}
+
+fn status(permission: Permission) -> PermissionStatus {
+    PermissionStatus {
+        permission,
+        state: platform::check(permission),
+        settings_url: permission.settings_url(),
+    }
+}
+
+fn open_settings(app: &tauri::AppHandle, permission: Permission) -> Result<(), String> {
+    let Some(url) = permission.settings_url() else {
+        return Ok(());
+    };
+    app.opener()
+        .open_url(url, None::<String>)
+        .map_err(|e| format!("open system settings failed: {}", e))
+}
+
+// 各项权限的当前状态，供引导页展示
+#[tauri::command]
+pub fn get_permissions() -> Vec<PermissionStatus> {
+    ALL.into_iter().map(status).collect()
+}
+
+// 未询问过时弹出系统授权框；已被拒绝时系统不会再弹，直接打开对应的系统设置面板。
+// 授权框是异步的，结果通过 permission-changed 事件通知
+#[tauri::command]
+pub fn request_permission(
+    app: tauri::AppHandle,
+    permission: Permission,
+) -> Result<PermissionStatus, String> {
+    let current = status(permission);
+    match current.state {
+        PermissionState::Granted | PermissionState::NotRequired => return Ok(current),
+        PermissionState::Restricted => return Err("permission restricted by system".to_string()),
+        PermissionState::Denied => {
+            let asked = {
+                let mut asked = ASKED.lock().unwrap();
+                let seen = asked.contains(&permission);
+                if !seen {
+                    asked.push(permission);
+                }
+                seen
+            };
+            // 照片的状态能区分“已拒绝”，拒绝后系统不会再弹框
+            if asked || permission == Permission::Photos {
+                open_settings(&app, permission)?;
+                return Ok(current);
+            }
+        }
+        PermissionState::NotDetermined => {}
+    }
+    app.state::<LogState>().log_app(
+        "INFO",
+        &format!("Requesting permission {}", permission.name()),
+    );
+    let app_for_result = app.clone();
+    // 系统授权框需要在主线程弹出
+    app.run_on_main_thread(move || {
+        platform::request(permission, move |state| {
+            let _ = app_for_result.emit(
+                "permission-changed",
+                PermissionStatus {
+                    permission,
+                    state,
+                    settings_url: permission.settings_url(),
+                },
+            );
+        });
+    })
+    .map_err(|e| format!("run_on_main_thread failed: {}", e))?;
+    Ok(current)
+}
+
+#[tauri::command]
+pub fn open_permission_settings(
+    app: tauri::AppHandle,
+    permission: Permission,
+) -> Result<(), String> {
+    open_settings(&app, permission)
+}
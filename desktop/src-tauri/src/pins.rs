@@ -0,0 +1,192 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use tauri::image::Image;
+use tauri::{Emitter, Manager};
+use tauri_plugin_opener::OpenerExt;
+
+use crate::LogState;
+
+// 托盘子菜单里放不下太多
+const MAX_PINS: usize = 12;
+const THUMBNAIL_SIZE: u32 = 32;
+
+// 串行化 pins.json 的读写
+static PINS_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Clone, Default, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct PinnedImage {
+    pub id: String,
+    pub path: String,
+    pub name: String,
+    pub pinned_at: u128,
+}
+
+#[derive(Default, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+struct PinStore {
+    pins: Vec<PinnedImage>,
+}
+
+fn pins_dir(app: &tauri::AppHandle) -> PathBuf {
+    crate::app_data_base(app).join("pins")
+}
+
+fn store_path(app: &tauri::AppHandle) -> PathBuf {
+    pins_dir(app).join("pins.json")
+}
+
+fn thumbnail_path(app: &tauri::AppHandle, id: &str) -> PathBuf {
+    pins_dir(app).join(format!("{}.png", id))
+}
+
+fn pin_id(path: &Path) -> String {
+    let mut hasher = DefaultHasher::new();
+    path.hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
+
+pub fn list(app: &tauri::AppHandle) -> Vec<PinnedImage> {
+    crate::settings::read_json_or_default::<PinStore>(&store_path(app)).pins
+}
+
+fn changed(app: &tauri::AppHandle) {
+    crate::tray::refresh(app);
+    let _ = app.emit("pins-changed", list(app));
+}
+
+// 固定时生成好小图，托盘重建菜单时只读这个文件，不用每次解码原图
+fn write_thumbnail(source: &Path, dest: &Path) -> Result<(), String> {
+    let image = image::open(source).map_err(|e| format!("decode image failed: {}", e))?;
+    image
+        .thumbnail(THUMBNAIL_SIZE, THUMBNAIL_SIZE)
+        .save_with_format(dest, image::ImageFormat::Png)
+        .map_err(|e| format!("write thumbnail failed: {}", e))
+}
+
+pub fn thumbnail(app: &tauri::AppHandle, id: &str) -> Option<Image<'static>> {
+    let rgba = image::open(thumbnail_path(app, id)).ok()?.to_rgba8();
+    let (width, height) = rgba.dimensions();
+    Some(Image::new_owned(rgba.into_raw(), width, height))
+}
+
+fn find(app: &tauri::AppHandle, id: &str) -> Result<PinnedImage, String> {
+    list(app)
+        .into_iter()
+        .find(|p| p.id == id)
+        .ok_or_else(|| format!("pinned image not found: {}", id))
+}
+
+// 托盘菜单回调在主线程，复制剪贴板时会切回主线程等待，放到后台线程执行避免卡死
+pub fn spawn_copy(app: &tauri::AppHandle, id: &str) {
+    let app = app.clone();
+    let id = id.to_string();
+    std::thread::spawn(move || {
+        let result = find(&app, &id)
+            .and_then(|pin| crate::resolve_local_path(&app, &pin.path))
+            .and_then(|path| {
+                crate::clipboard::copy_image(
+                    &app,
+                    &path,
+                    crate::clipboard::ClipboardImageMode::Bitmap,
+                    None,
+                )
+            });
+        if let Err(err) = result {
+            app.state::<LogState>()
+                .log_app("WARN", &format!("copy pinned image failed: {}", err));
+        }
+    });
+}
+
+pub fn reveal(app: &tauri::AppHandle, id: &str) {
+    let result = find(app, id).and_then(|pin| {
+        app.opener()
+            .reveal_item_in_dir(&pin.path)
+            .map_err(|e| format!("reveal pinned image failed: {}", e))
+    });
+    if let Err(err) = result {
+        app.state::<LogState>().log_app("WARN", &err);
+    }
+}
+
+fn remove(app: &tauri::AppHandle, id: &str) -> Result<Vec<PinnedImage>, String> {
+    let pins = {
+        let _guard = PINS_LOCK.lock().unwrap();
+        let mut store: PinStore = crate::settings::read_json_or_default(&store_path(app));
+        store.pins.retain(|p| p.id != id);
+        crate::settings::write_json_atomic(&store_path(app), &store)?;
+        store.pins
+    };
+    let _ = std::fs::remove_file(thumbnail_path(app, id));
+    changed(app);
+    Ok(pins)
+}
+
+pub fn unpin(app: &tauri::AppHandle, id: &str) {
+    if let Err(err) = remove(app, id) {
+        app.state::<LogState>()
+            .log_app("WARN", &format!("unpin image failed: {}", err));
+    }
+}
+
+#[tauri::command]
+pub fn list_pinned_images(app: tauri::AppHandle) -> Vec<PinnedImage> {
+    list(&app)
+}
+
+// 同一张图重复固定时移到最前；超出上限时挤掉最早固定的
+#[tauri::command]
+pub async fn pin_image(
+    app: tauri::AppHandle,
+    path: String,
+    name: Option<String>,
+) -> Result<Vec<PinnedImage>, String> {
+    let file = crate::resolve_local_path(&app, &path)?;
+    let id = pin_id(&file);
+    let dir = pins_dir(&app);
+    let thumbnail = thumbnail_path(&app, &id);
+    let source = file.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        std::fs::create_dir_all(&dir).map_err(|e| format!("create pins dir failed: {}", e))?;
+        write_thumbnail(&source, &thumbnail)
+    })
+    .await
+    .map_err(|e| format!("pin image failed: {}", e))??;
+
+    let name = name
+        .map(|n| n.trim().to_string())
+        .filter(|n| !n.is_empty())
+        .or_else(|| file.file_name().map(|n| n.to_string_lossy().to_string()))
+        .unwrap_or_else(|| id.clone());
+    let (pins, dropped) = {
+        let _guard = PINS_LOCK.lock().unwrap();
+        let mut store: PinStore = crate::settings::read_json_or_default(&store_path(&app));
+        store.pins.retain(|p| p.id != id);
+        store.pins.insert(
+            0,
+            PinnedImage {
+                id,
+                path: file.to_string_lossy().to_string(),
+                name,
+                pinned_at: crate::now_ms(),
+            },
+        );
+        let dropped = store.pins.split_off(store.pins.len().min(MAX_PINS));
+        crate::settings::write_json_atomic(&store_path(&app), &store)?;
+        (store.pins, dropped)
+    };
+    for pin in dropped {
+        let _ = std::fs::remove_file(thumbnail_path(&app, &pin.id));
+    }
+    changed(&app);
+    Ok(pins)
+}
+
+#[tauri::command]
+pub fn unpin_image(app: tauri::AppHandle, id: String) -> Result<Vec<PinnedImage>, String> {
+    remove(&app, &id)
+}
@@ -0,0 +1,583 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use tauri::{Emitter, Manager, State};
+use tauri_plugin_shell::process::{CommandChild, CommandEvent};
+use tauri_plugin_shell::ShellExt;
+
+use crate::{BackendLifecycle, LogState, LogWriter};
+
+const MANIFEST_NAME: &str = "plugin.json";
+const DEFAULT_HANDSHAKE: &str = "SERVER_PORT=";
+const INVOKE_TIMEOUT: Duration = Duration::from_secs(120);
+
+// 插件声明文件：app_data/plugins/<id>/plugin.json
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct PluginManifest {
+    pub id: String,
+    pub name: String,
+    pub version: String,
+    // 相对插件目录的可执行文件路径，不允许指向目录之外
+    pub program: String,
+    pub args: Vec<String>,
+    pub env: HashMap<String, String>,
+    // 随应用启动自动拉起
+    pub autostart: bool,
+    // 崩溃后按与后端相同的退避策略自动重启
+    pub restart: bool,
+    // 进程打印该前缀加端口号即视为就绪，与后端的 SERVER_PORT= 约定一致
+    pub handshake: String,
+    // 前端可通过 invoke_plugin 调用的命令，对应 POST /commands/<name>
+    pub commands: Vec<String>,
+}
+
+impl Default for PluginManifest {
+    fn default() -> Self {
+        Self {
+            id: String::new(),
+            name: String::new(),
+            version: String::new(),
+            program: String::new(),
+            args: Vec::new(),
+            env: HashMap::new(),
+            autostart: true,
+            restart: true,
+            handshake: DEFAULT_HANDSHAKE.to_string(),
+            commands: Vec::new(),
+        }
+    }
+}
+
+struct PluginRuntime {
+    manifest: PluginManifest,
+    dir: PathBuf,
+    log: LogWriter,
+    child: Option<CommandChild>,
+    // 每次拉起 / 停止都递增，旧进程的事件和过期的重启任务据此忽略
+    generation: u64,
+    state: BackendLifecycle,
+    pid: Option<u32>,
+    port: u16,
+    running_since: Option<Instant>,
+    last_error: Option<String>,
+}
+
+#[derive(Default)]
+pub struct PluginState(Mutex<HashMap<String, PluginRuntime>>);
+
+#[derive(Clone, serde::Serialize)]
+pub struct PluginInfo {
+    id: String,
+    name: String,
+    version: String,
+    commands: Vec<String>,
+    state: BackendLifecycle,
+    pid: Option<u32>,
+    port: u16,
+    uptime_secs: u64,
+    last_error: Option<String>,
+}
+
+fn info(runtime: &PluginRuntime) -> PluginInfo {
+    PluginInfo {
+        id: runtime.manifest.id.clone(),
+        name: runtime.manifest.name.clone(),
+        version: runtime.manifest.version.clone(),
+        commands: runtime.manifest.commands.clone(),
+        state: runtime.state,
+        pid: runtime.pid,
+        port: runtime.port,
+        uptime_secs: runtime
+            .running_since
+            .map(|t| t.elapsed().as_secs())
+            .unwrap_or(0),
+        last_error: runtime.last_error.clone(),
+    }
+}
+
+fn plugins_dir(app: &tauri::AppHandle) -> PathBuf {
+    crate::app_data_base(app).join("plugins")
+}
+
+fn valid_id(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= 64
+        && id
+            .bytes()
+            .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'-' || b == b'_')
+}
+
+fn read_manifest(dir: &Path) -> Result<PluginManifest, String> {
+    let raw = fs::read_to_string(dir.join(MANIFEST_NAME))
+        .map_err(|e| format!("read plugin manifest failed: {}", e))?;
+    let mut manifest: PluginManifest =
+        serde_json::from_str(&raw).map_err(|e| format!("parse plugin manifest failed: {}", e))?;
+    manifest.id = manifest.id.trim().to_string();
+    if !valid_id(&manifest.id) {
+        return Err(format!("invalid plugin id: {}", manifest.id));
+    }
+    if manifest.program.trim().is_empty() {
+        return Err(format!("plugin {} has no program", manifest.id));
+    }
+    if manifest.handshake.trim().is_empty() {
+        manifest.handshake = DEFAULT_HANDSHAKE.to_string();
+    }
+    if manifest.name.trim().is_empty() {
+        manifest.name = manifest.id.clone();
+    }
+    Ok(manifest)
+}
+
+// 可执行文件必须位于插件目录内，防止清单借相对路径启动任意程序
+fn program_path(dir: &Path, program: &str) -> Result<PathBuf, String> {
+    let root = dir
+        .canonicalize()
+        .map_err(|e| format!("resolve plugin dir failed: {}", e))?;
+    let path = root
+        .join(program.trim())
+        .canonicalize()
+        .map_err(|e| format!("plugin program not found: {}", e))?;
+    if !path.starts_with(&root) || !path.is_file() {
+        return Err(format!("plugin program outside plugin dir: {}", program));
+    }
+    Ok(path)
+}
+
+fn emit_status(app: &tauri::AppHandle, id: &str) {
+    let payload = app
+        .state::<PluginState>()
+        .0
+        .lock()
+        .unwrap()
+        .get(id)
+        .map(info);
+    if let Some(payload) = payload {
+        let _ = app.emit("plugin-status", payload);
+    }
+}
+
+fn update(app: &tauri::AppHandle, id: &str, f: impl FnOnce(&mut PluginRuntime)) {
+    {
+        let state = app.state::<PluginState>();
+        let mut plugins = state.0.lock().unwrap();
+        let Some(runtime) = plugins.get_mut(id) else {
+            return;
+        };
+        f(runtime);
+        if runtime.state != BackendLifecycle::Running {
+            runtime.running_since = None;
+            runtime.port = 0;
+        }
+    }
+    emit_status(app, id);
+}
+
+// 扫描插件目录，新增的插件加入列表；已加载的插件只在未运行时刷新清单
+pub fn scan(app: &tauri::AppHandle) {
+    let Ok(entries) = fs::read_dir(plugins_dir(app)) else {
+        return;
+    };
+    let log_state = app.state::<LogState>();
+    let policy = app
+        .state::<crate::settings::SettingsState>()
+        .get()
+        .server_log
+        .policy();
+    let state = app.state::<PluginState>();
+    let mut plugins = state.0.lock().unwrap();
+    for entry in entries.flatten() {
+        let dir = entry.path();
+        if !dir.join(MANIFEST_NAME).is_file() {
+            continue;
+        }
+        let manifest = match read_manifest(&dir) {
+            Ok(manifest) => manifest,
+            Err(err) => {
+                log_state.log_app(
+                    "WARN",
+                    &format!("Skipping plugin at {}: {}", dir.display(), err),
+                );
+                continue;
+            }
+        };
+        match plugins.get_mut(&manifest.id) {
+            Some(runtime) if runtime.child.is_none() => {
+                runtime.manifest = manifest;
+                runtime.dir = dir;
+            }
+            Some(_) => {}
+            None => {
+                let log = LogWriter::new(
+                    log_state
+                        .dir
+                        .join("plugins")
+                        .join(format!("{}.log", manifest.id)),
+                );
+                log.set_policy(policy);
+                log_state.log_app(
+                    "INFO",
+                    &format!(
+                        "Plugin loaded id={} version={}",
+                        manifest.id, manifest.version
+                    ),
+                );
+                plugins.insert(
+                    manifest.id.clone(),
+                    PluginRuntime {
+                        manifest,
+                        dir,
+                        log,
+                        child: None,
+                        generation: 0,
+                        state: BackendLifecycle::Stopped,
+                        pid: None,
+                        port: 0,
+                        running_since: None,
+                        last_error: None,
+                    },
+                );
+            }
+        }
+    }
+}
+
+// 启动时调用：加载清单并拉起标记为 autostart 的插件
+pub fn start_all(app: &tauri::AppHandle) {
+    scan(app);
+    let ids: Vec<String> = app
+        .state::<PluginState>()
+        .0
+        .lock()
+        .unwrap()
+        .values()
+        .filter(|p| p.manifest.autostart)
+        .map(|p| p.manifest.id.clone())
+        .collect();
+    for id in ids {
+        if let Err(err) = spawn(app, &id, 0) {
+            app.state::<LogState>()
+                .log_app("ERROR", &format!("Plugin {} not started: {}", id, err));
+        }
+    }
+}
+
+// 退出应用时与后端一起平滑停止
+pub fn stop_all(app: &tauri::AppHandle) {
+    let Some(state) = app.try_state::<PluginState>() else {
+        return;
+    };
+    let ids: Vec<String> = state.0.lock().unwrap().keys().cloned().collect();
+    for id in ids {
+        stop(app, &id);
+    }
+}
+
+fn stop(app: &tauri::AppHandle, id: &str) {
+    let child = {
+        let state = app.state::<PluginState>();
+        let mut plugins = state.0.lock().unwrap();
+        let Some(runtime) = plugins.get_mut(id) else {
+            return;
+        };
+        runtime.generation += 1;
+        runtime.child.take()
+    };
+    if let Some(child) = child {
+        let log_state = app.state::<LogState>();
+        log_state.log_app("INFO", &format!("Stopping plugin {}", id));
+        crate::shutdown::terminate(child, crate::shutdown::grace_period(app), &log_state);
+    }
+    update(app, id, |p| {
+        p.state = BackendLifecycle::Stopped;
+        p.pid = None;
+    });
+}
+
+// 与 schedule_sidecar_restart 相同的退避；等待期间被手动启停则放弃
+fn schedule_restart(app: tauri::AppHandle, id: String, generation: u64, attempt: u32) {
+    let log_state = app.state::<LogState>().inner().clone();
+    if attempt > crate::SIDECAR_RESTART_MAX_ATTEMPTS {
+        log_state.log_app(
+            "ERROR",
+            &format!(
+                "Plugin {} crashed {} times in a row, giving up auto restart",
+                id,
+                attempt - 1
+            ),
+        );
+        return;
+    }
+    update(&app, &id, |p| p.state = BackendLifecycle::Restarting);
+    let delay = crate::restart_delay(attempt);
+    log_state.log_app(
+        "WARN",
+        &format!(
+            "Restarting plugin {} in {}ms attempt={}",
+            id,
+            delay.as_millis(),
+            attempt
+        ),
+    );
+    std::thread::spawn(move || {
+        std::thread::sleep(delay);
+        let current = app
+            .state::<PluginState>()
+            .0
+            .lock()
+            .unwrap()
+            .get(&id)
+            .is_some_and(|p| p.generation == generation && p.child.is_none());
+        if !current {
+            return;
+        }
+        if let Err(err) = spawn(&app, &id, attempt) {
+            log_state.log_app("ERROR", &format!("Plugin {} restart failed: {}", id, err));
+            schedule_restart(app, id, generation + 1, attempt + 1);
+        }
+    });
+}
+
+// attempt 含义同 spawn_sidecar_attempt：0 为正常启动，大于 0 为崩溃后的第几次重启
+fn spawn(app: &tauri::AppHandle, id: &str, attempt: u32) -> Result<(), String> {
+    let (manifest, dir, log) = {
+        let state = app.state::<PluginState>();
+        let plugins = state.0.lock().unwrap();
+        let runtime = plugins
+            .get(id)
+            .ok_or_else(|| format!("plugin not found: {}", id))?;
+        if runtime.child.is_some() {
+            return Ok(());
+        }
+        (
+            runtime.manifest.clone(),
+            runtime.dir.clone(),
+            runtime.log.clone(),
+        )
+    };
+    let fail = |err: String| {
+        update(app, id, |p| {
+            p.state = BackendLifecycle::Crashed;
+            p.pid = None;
+            p.last_error = Some(err.clone());
+            // 拉起失败也要让等待中的重启任务失效
+            p.generation += 1;
+        });
+        err
+    };
+    let program = program_path(&dir, &manifest.program).map_err(&fail)?;
+    let data_dir = crate::app_data_base(app).join("plugin_data").join(id);
+    let _ = fs::create_dir_all(&data_dir);
+    let command = app
+        .shell()
+        .command(program)
+        .args(&manifest.args)
+        .envs(manifest.env.clone())
+        .env("PLUGIN_ID", id)
+        .env("PLUGIN_DATA_DIR", &data_dir)
+        .current_dir(&dir);
+    let (mut rx, child) = command
+        .spawn()
+        .map_err(|e| fail(format!("spawn plugin failed: {}", e)))?;
+
+    let pid = child.pid();
+    let generation = {
+        let state = app.state::<PluginState>();
+        let mut plugins = state.0.lock().unwrap();
+        let Some(runtime) = plugins.get_mut(id) else {
+            let _ = child.kill();
+            return Err(format!("plugin not found: {}", id));
+        };
+        runtime.generation += 1;
+        runtime.child = Some(child);
+        runtime.generation
+    };
+    update(app, id, |p| {
+        p.state = if attempt > 0 {
+            BackendLifecycle::Restarting
+        } else {
+            BackendLifecycle::Starting
+        };
+        p.pid = Some(pid);
+    });
+    app.state::<LogState>()
+        .log_app("INFO", &format!("Plugin {} spawned with PID: {}", id, pid));
+
+    let app = app.clone();
+    let id = id.to_string();
+    let spawned_at = Instant::now();
+    tauri::async_runtime::spawn(async move {
+        let mut last_stderr: Option<String> = None;
+        while let Some(event) = rx.recv().await {
+            match event {
+                CommandEvent::Stdout(line) => {
+                    let out = String::from_utf8_lossy(&line);
+                    log.write_line(&format!(
+                        "[{}] [STDOUT] {}",
+                        crate::now_ms(),
+                        out.trim_end()
+                    ));
+                    let Some(port) = crate::parse_port_line(&out, &manifest.handshake) else {
+                        continue;
+                    };
+                    app.state::<LogState>()
+                        .log_app("INFO", &format!("Detected plugin {} port: {}", id, port));
+                    update(&app, &id, |p| {
+                        if p.generation == generation {
+                            p.state = BackendLifecycle::Running;
+                            p.port = port;
+                            p.running_since = Some(Instant::now());
+                        }
+                    });
+                }
+                CommandEvent::Stderr(line) => {
+                    let err = String::from_utf8_lossy(&line);
+                    log.write_line(&format!(
+                        "[{}] [STDERR] {}",
+                        crate::now_ms(),
+                        err.trim_end()
+                    ));
+                    if !err.trim().is_empty() {
+                        last_stderr = Some(err.trim().to_string());
+                    }
+                }
+                CommandEvent::Error(err) => {
+                    app.state::<LogState>()
+                        .log_app("ERROR", &format!("Plugin {} error: {}", id, err));
+                    update(&app, &id, |p| p.last_error = Some(err));
+                }
+                CommandEvent::Terminated(status) => {
+                    app.state::<LogState>().log_app(
+                        "WARN",
+                        &format!("Plugin {} terminated with status: {:?}", id, status),
+                    );
+                    // stop 会先取走 child，这里仍持有说明是进程自己退出的
+                    let crashed = app
+                        .state::<PluginState>()
+                        .0
+                        .lock()
+                        .unwrap()
+                        .get_mut(&id)
+                        .filter(|p| p.generation == generation)
+                        .is_some_and(|p| p.child.take().is_some());
+                    if !crashed {
+                        continue;
+                    }
+                    let failed = status.code != Some(0);
+                    let exit = match (status.code, status.signal) {
+                        (Some(code), _) => format!("exited with code {}", code),
+                        (None, Some(signal)) => format!("killed by signal {}", signal),
+                        _ => "exited".to_string(),
+                    };
+                    let last_error = match last_stderr.take() {
+                        Some(line) => format!("{}: {}", exit, line),
+                        None => exit,
+                    };
+                    update(&app, &id, |p| {
+                        p.pid = None;
+                        if failed {
+                            p.state = BackendLifecycle::Crashed;
+                            p.last_error = Some(last_error);
+                        } else {
+                            p.state = BackendLifecycle::Stopped;
+                        }
+                    });
+                    if failed && manifest.restart {
+                        let next_attempt = if spawned_at.elapsed() >= crate::SIDECAR_STABLE_UPTIME {
+                            1
+                        } else {
+                            attempt + 1
+                        };
+                        schedule_restart(app.clone(), id.clone(), generation, next_attempt);
+                    }
+                }
+                _ => {}
+            }
+        }
+    });
+    Ok(())
+}
+
+// 重新扫描插件目录后返回全部插件及运行状态
+#[tauri::command]
+pub fn list_plugins(app: tauri::AppHandle) -> Vec<PluginInfo> {
+    scan(&app);
+    let mut list: Vec<PluginInfo> = app
+        .state::<PluginState>()
+        .0
+        .lock()
+        .unwrap()
+        .values()
+        .map(info)
+        .collect();
+    list.sort_by(|a, b| a.id.cmp(&b.id));
+    list
+}
+
+#[tauri::command]
+pub fn start_plugin(app: tauri::AppHandle, id: String) -> Result<(), String> {
+    scan(&app);
+    spawn(&app, id.trim(), 0)
+}
+
+#[tauri::command]
+pub async fn stop_plugin(app: tauri::AppHandle, id: String) -> Result<(), String> {
+    tauri::async_runtime::spawn_blocking(move || stop(&app, id.trim()))
+        .await
+        .map_err(|e| format!("stop plugin failed: {}", e))
+}
+
+// 以 plugin_id + 命令名的命名空间调用插件：POST http://127.0.0.1:<port>/commands/<command>，
+// 请求体和返回值都是 JSON；命令必须在清单的 commands 中声明
+#[tauri::command]
+pub async fn invoke_plugin(
+    state: State<'_, PluginState>,
+    id: String,
+    command: String,
+    payload: Option<serde_json::Value>,
+) -> Result<serde_json::Value, String> {
+    let port = {
+        let plugins = state.0.lock().unwrap();
+        let runtime = plugins
+            .get(id.trim())
+            .ok_or_else(|| format!("plugin not found: {}", id))?;
+        if !runtime.manifest.commands.iter().any(|c| c == &command) {
+            return Err(format!("plugin {} has no command {}", id, command));
+        }
+        if runtime.state != BackendLifecycle::Running || runtime.port == 0 {
+            return Err(format!("plugin {} is not running", id));
+        }
+        runtime.port
+    };
+    let client = reqwest::Client::builder()
+        .timeout(INVOKE_TIMEOUT)
+        .build()
+        .map_err(|e| format!("create http client failed: {}", e))?;
+    let response = client
+        .post(format!("http://127.0.0.1:{}/commands/{}", port, command))
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .body(payload.unwrap_or(serde_json::Value::Null).to_string())
+        .send()
+        .await
+        .map_err(|e| format!("invoke plugin failed: {}", e))?;
+    let status = response.status();
+    let body = response
+        .text()
+        .await
+        .map_err(|e| format!("read plugin response failed: {}", e))?;
+    if !status.is_success() {
+        return Err(format!(
+            "plugin {} returned {}: {}",
+            id,
+            status,
+            body.trim()
+        ));
+    }
+    if body.trim().is_empty() {
+        return Ok(serde_json::Value::Null);
+    }
+    serde_json::from_str(&body).map_err(|e| format!("parse plugin response failed: {}", e))
+}
@@ -0,0 +1,333 @@
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use tauri::{Emitter, Manager};
+
+use crate::LogState;
+
+// 串行化 presets.json 的读写
+static PRESETS_LOCK: Mutex<()> = Mutex::new(());
+
+const MANIFEST_NAME: &str = "presets.json";
+
+// 一组可以一键套用的生成参数；前端负责把它填回表单
+#[derive(Clone, Default, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct Preset {
+    pub id: String,
+    pub name: String,
+    pub prompt: String,
+    pub aspect_ratio: String,
+    pub image_size: String,
+    pub provider: String,
+    pub model_id: String,
+    // 其余模型参数原样保存，壳层不解释
+    pub params: serde_json::Value,
+    // 参考图复制到预设目录后的绝对路径，原图删除或移动后仍可用
+    pub reference_images: Vec<String>,
+    pub created_at: u128,
+    pub updated_at: u128,
+}
+
+#[derive(Default, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+struct PresetStore {
+    presets: Vec<Preset>,
+}
+
+#[derive(serde::Serialize)]
+pub struct PresetImportResult {
+    imported: usize,
+    skipped: usize,
+}
+
+fn presets_dir(app: &tauri::AppHandle) -> PathBuf {
+    crate::app_data_base(app).join("presets")
+}
+
+fn store_path(app: &tauri::AppHandle) -> PathBuf {
+    presets_dir(app).join(MANIFEST_NAME)
+}
+
+fn load(app: &tauri::AppHandle) -> PresetStore {
+    crate::settings::read_json_or_default(&store_path(app))
+}
+
+fn save(app: &tauri::AppHandle, store: &PresetStore) -> Result<(), String> {
+    crate::settings::write_json_atomic(&store_path(app), store)
+}
+
+fn new_id() -> String {
+    let mut bytes = [0u8; 4];
+    let _ = getrandom::fill(&mut bytes);
+    format!(
+        "{:x}{}",
+        crate::now_ms(),
+        bytes
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect::<String>()
+    )
+}
+
+// 预设 id 会拼进目录名，只允许导出/生成时用到的字符
+fn valid_id(id: &str) -> bool {
+    !id.is_empty()
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+// 把参考图收进预设自己的目录；已经在目录内的（再次保存同一预设）原样保留
+fn collect_references(
+    app: &tauri::AppHandle,
+    preset_id: &str,
+    paths: &[String],
+) -> Result<Vec<String>, String> {
+    let dir = presets_dir(app).join(preset_id);
+    fs::create_dir_all(&dir).map_err(|e| format!("create preset dir failed: {}", e))?;
+    let mut kept = Vec::new();
+    for (index, raw) in paths.iter().enumerate() {
+        let source = crate::resolve_local_path(app, raw)?;
+        if source.starts_with(&dir) {
+            kept.push(source.to_string_lossy().to_string());
+            continue;
+        }
+        let ext = source
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or("png")
+            .to_ascii_lowercase();
+        let dest = dir.join(format!("ref_{}_{}.{}", crate::now_ms(), index, ext));
+        fs::copy(&source, &dest)
+            .map_err(|e| format!("copy reference image {} failed: {}", source.display(), e))?;
+        kept.push(dest.to_string_lossy().to_string());
+    }
+    // 清掉不再引用的旧参考图
+    if let Ok(entries) = fs::read_dir(&dir) {
+        for entry in entries.flatten() {
+            let path = entry.path().to_string_lossy().to_string();
+            if !kept.contains(&path) {
+                let _ = fs::remove_file(entry.path());
+            }
+        }
+    }
+    Ok(kept)
+}
+
+pub fn list(app: &tauri::AppHandle) -> Vec<Preset> {
+    load(app).presets
+}
+
+// 托盘菜单、跳转列表和 deep link 等入口统一走这里：打开主窗口并把预设交给前端套用
+pub fn apply(app: &tauri::AppHandle, id: &str) -> Result<Preset, String> {
+    let preset = list(app)
+        .into_iter()
+        .find(|p| p.id == id)
+        .ok_or_else(|| format!("preset not found: {}", id))?;
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+    app.emit("apply-preset", preset.clone())
+        .map_err(|e| format!("emit apply-preset failed: {}", e))?;
+    Ok(preset)
+}
+
+#[tauri::command]
+pub fn list_presets(app: tauri::AppHandle) -> Vec<Preset> {
+    list(&app)
+}
+
+// id 为空时新建，否则覆盖同 id 的预设
+#[tauri::command]
+pub async fn save_preset(app: tauri::AppHandle, preset: Preset) -> Result<Preset, String> {
+    if preset.name.trim().is_empty() {
+        return Err("preset name is empty".to_string());
+    }
+    if !preset.id.is_empty() && !valid_id(&preset.id) {
+        return Err(format!("invalid preset id: {}", preset.id));
+    }
+    let saved = tauri::async_runtime::spawn_blocking(move || {
+        let _guard = PRESETS_LOCK.lock().unwrap();
+        let mut store = load(&app);
+        let now = crate::now_ms();
+        let mut preset = preset;
+        preset.name = preset.name.trim().to_string();
+        match store.presets.iter().find(|p| p.id == preset.id) {
+            Some(existing) => preset.created_at = existing.created_at,
+            None => {
+                if preset.id.is_empty() {
+                    preset.id = new_id();
+                }
+                preset.created_at = now;
+            }
+        }
+        preset.updated_at = now;
+        preset.reference_images = collect_references(&app, &preset.id, &preset.reference_images)?;
+        store.presets.retain(|p| p.id != preset.id);
+        store.presets.push(preset.clone());
+        save(&app, &store)?;
+        crate::tray::refresh(&app);
+        Ok::<_, String>(preset)
+    })
+    .await
+    .map_err(|e| format!("save preset failed: {}", e))??;
+    Ok(saved)
+}
+
+#[tauri::command]
+pub fn delete_preset(app: tauri::AppHandle, id: String) -> Result<(), String> {
+    if !valid_id(&id) {
+        return Err(format!("invalid preset id: {}", id));
+    }
+    let _guard = PRESETS_LOCK.lock().unwrap();
+    let mut store = load(&app);
+    store.presets.retain(|p| p.id != id);
+    save(&app, &store)?;
+    let _ = fs::remove_dir_all(presets_dir(&app).join(&id));
+    crate::tray::refresh(&app);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn apply_preset(app: tauri::AppHandle, id: String) -> Result<Preset, String> {
+    apply(&app, &id)
+}
+
+fn relative_ref_name(preset_id: &str, path: &Path) -> Option<String> {
+    Some(format!(
+        "{}/{}",
+        preset_id,
+        path.file_name()?.to_string_lossy()
+    ))
+}
+
+// 导出为 zip：presets.json 里的参考图路径改写成包内相对路径，换机器导入后可以还原
+#[tauri::command]
+pub async fn export_presets(
+    app: tauri::AppHandle,
+    ids: Vec<String>,
+    path: String,
+) -> Result<usize, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut presets: Vec<Preset> = list(&app)
+            .into_iter()
+            .filter(|p| ids.is_empty() || ids.contains(&p.id))
+            .collect();
+        let file =
+            fs::File::create(&path).map_err(|e| format!("create export file failed: {}", e))?;
+        let mut zip = zip::ZipWriter::new(file);
+        let options = zip::write::SimpleFileOptions::default();
+        for preset in presets.iter_mut() {
+            let mut refs = Vec::new();
+            for reference in &preset.reference_images {
+                let source = PathBuf::from(reference);
+                let Some(name) = relative_ref_name(&preset.id, &source) else {
+                    continue;
+                };
+                let Ok(bytes) = fs::read(&source) else {
+                    continue;
+                };
+                zip.start_file(format!("refs/{}", name), options)
+                    .and_then(|_| zip.write_all(&bytes).map_err(Into::into))
+                    .map_err(|e| format!("write export file failed: {}", e))?;
+                refs.push(name);
+            }
+            preset.reference_images = refs;
+        }
+        let manifest = serde_json::to_vec_pretty(&PresetStore {
+            presets: presets.clone(),
+        })
+        .map_err(|e| format!("serialize presets failed: {}", e))?;
+        zip.start_file(MANIFEST_NAME, options)
+            .and_then(|_| zip.write_all(&manifest).map_err(Into::into))
+            .map_err(|e| format!("write export file failed: {}", e))?;
+        zip.finish()
+            .map_err(|e| format!("finalize export file failed: {}", e))?;
+        app.state::<LogState>().log_app(
+            "INFO",
+            &format!("Exported {} presets to {}", presets.len(), path),
+        );
+        Ok(presets.len())
+    })
+    .await
+    .map_err(|e| format!("export presets failed: {}", e))?
+}
+
+// 导入时总是分配新 id，同名预设也作为新条目保留，不覆盖本机已有的
+#[tauri::command]
+pub async fn import_presets(
+    app: tauri::AppHandle,
+    path: String,
+) -> Result<PresetImportResult, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let file = fs::File::open(&path).map_err(|e| format!("open preset file failed: {}", e))?;
+        let mut archive =
+            zip::ZipArchive::new(file).map_err(|e| format!("read preset file failed: {}", e))?;
+        let mut manifest = Vec::new();
+        archive
+            .by_name(MANIFEST_NAME)
+            .map_err(|e| format!("preset manifest missing: {}", e))?
+            .read_to_end(&mut manifest)
+            .map_err(|e| format!("read preset manifest failed: {}", e))?;
+        let incoming: PresetStore = serde_json::from_slice(&manifest)
+            .map_err(|e| format!("parse preset manifest failed: {}", e))?;
+
+        let _guard = PRESETS_LOCK.lock().unwrap();
+        let mut store = load(&app);
+        let mut result = PresetImportResult {
+            imported: 0,
+            skipped: 0,
+        };
+        let now = crate::now_ms();
+        for mut preset in incoming.presets {
+            if preset.name.trim().is_empty() {
+                result.skipped += 1;
+                continue;
+            }
+            let id = new_id();
+            let dir = presets_dir(&app).join(&id);
+            fs::create_dir_all(&dir).map_err(|e| format!("create preset dir failed: {}", e))?;
+            let mut refs = Vec::new();
+            for name in &preset.reference_images {
+                // 只认包内 refs/<id>/<file> 形式的条目，防止路径穿越
+                let Some(file_name) = Path::new(name).file_name() else {
+                    continue;
+                };
+                let Ok(mut entry) = archive.by_name(&format!("refs/{}", name)) else {
+                    continue;
+                };
+                let mut bytes = Vec::new();
+                if entry.read_to_end(&mut bytes).is_err() {
+                    continue;
+                }
+                let dest = dir.join(file_name);
+                if fs::write(&dest, bytes).is_ok() {
+                    refs.push(dest.to_string_lossy().to_string());
+                }
+            }
+            preset.id = id;
+            preset.reference_images = refs;
+            preset.created_at = now;
+            preset.updated_at = now;
+            store.presets.push(preset);
+            result.imported += 1;
+        }
+        save(&app, &store)?;
+        crate::tray::refresh(&app);
+        app.state::<LogState>().log_app(
+            "INFO",
+            &format!(
+                "Imported presets imported={} skipped={}",
+                result.imported, result.skipped
+            ),
+        );
+        Ok(result)
+    })
+    .await
+    .map_err(|e| format!("import presets failed: {}", e))?
+}
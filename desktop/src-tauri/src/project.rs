@@ -0,0 +1,657 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::time::Duration;
+
+use rusqlite::OptionalExtension;
+use sha2::{Digest, Sha256};
+use tauri::{Emitter, Manager};
+
+use crate::{BackendPort, LogState};
+
+const MANIFEST_NAME: &str = "manifest.json";
+const MANIFEST_FORMAT: &str = "nano-banana-project";
+const MANIFEST_VERSION: u32 = 1;
+const GENERATIONS_DIR: &str = "generations";
+const REFERENCES_DIR: &str = "references";
+
+// 清单里的文件路径都相对项目目录；整个目录换机器、换位置后仍能导入，
+// 文件被挪动或改名时按 sha256 在项目目录里重新找回
+#[derive(Default, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+struct Manifest {
+    format: String,
+    version: u32,
+    name: String,
+    exported_at: String,
+    folders: Vec<ManifestFolder>,
+    generations: Vec<ManifestGeneration>,
+    references: Vec<ManifestReference>,
+}
+
+#[derive(Default, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+struct ManifestFolder {
+    id: String,
+    name: String,
+}
+
+#[derive(Default, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+struct ManifestGeneration {
+    task_id: String,
+    file: String,
+    sha256: String,
+    prompt: String,
+    prompt_original: String,
+    provider_name: String,
+    model_id: String,
+    width: i64,
+    height: i64,
+    config_snapshot: String,
+    created_at: String,
+    // 只记录手动文件夹，月份文件夹导入时按 created_at 自动归档
+    folder_id: String,
+    // 生成这张图时用到的参考图（清单内的 reference id）
+    reference_ids: Vec<String>,
+}
+
+#[derive(Default, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+struct ManifestReference {
+    id: String,
+    file: String,
+    sha256: String,
+    original_name: String,
+    source: String,
+    source_url: String,
+    tags: Vec<String>,
+}
+
+#[derive(serde::Serialize)]
+pub struct ProjectExportResult {
+    manifest: String,
+    generations: usize,
+    references: usize,
+    // 本地文件已丢失，未写入清单
+    missing: Vec<String>,
+}
+
+#[derive(Clone, serde::Serialize)]
+pub struct ProjectImportFailure {
+    id: String,
+    reason: String,
+}
+
+#[derive(serde::Serialize)]
+pub struct ProjectImportResult {
+    // 旧 id -> 新 id，前端据此恢复自己保存的关联
+    generations: HashMap<String, String>,
+    references: HashMap<String, String>,
+    folders: HashMap<String, String>,
+    // 新 task_id -> 新 reference id 列表
+    links: HashMap<String, Vec<String>>,
+    failed: Vec<ProjectImportFailure>,
+}
+
+#[derive(Clone, serde::Serialize)]
+struct ProjectImportProgress {
+    done: usize,
+    total: usize,
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+fn text(row: &rusqlite::Row<'_>, index: usize) -> rusqlite::Result<String> {
+    Ok(row.get::<_, Option<String>>(index)?.unwrap_or_default())
+}
+
+fn load_generations(
+    app: &tauri::AppHandle,
+    task_ids: &[String],
+) -> Result<(Vec<(ManifestGeneration, String)>, Vec<ManifestFolder>), String> {
+    let conn = crate::db::open_readonly(app)?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT t.task_id, t.prompt, t.prompt_original, t.provider_name, t.model_id, \
+             t.width, t.height, t.config_snapshot, t.created_at, t.local_path, f.id, f.name \
+             FROM tasks t LEFT JOIN folders f \
+             ON f.id = CAST(t.folder_id AS INTEGER) AND f.deleted_at IS NULL AND f.type = 'manual' \
+             WHERE t.task_id = ?1 AND t.deleted_at IS NULL AND t.status = 'completed'",
+        )
+        .map_err(|e| format!("query tasks failed: {}", e))?;
+    let mut generations = Vec::new();
+    let mut folders: BTreeMap<String, String> = BTreeMap::new();
+    for task_id in task_ids {
+        let row = stmt
+            .query_row([task_id], |row| {
+                let folder: Option<(i64, String)> = match row.get::<_, Option<i64>>(10)? {
+                    Some(id) => Some((id, text(row, 11)?)),
+                    None => None,
+                };
+                Ok((
+                    ManifestGeneration {
+                        task_id: row.get(0)?,
+                        prompt: text(row, 1)?,
+                        prompt_original: text(row, 2)?,
+                        provider_name: text(row, 3)?,
+                        model_id: text(row, 4)?,
+                        width: row.get::<_, Option<i64>>(5)?.unwrap_or(0),
+                        height: row.get::<_, Option<i64>>(6)?.unwrap_or(0),
+                        config_snapshot: text(row, 7)?,
+                        created_at: text(row, 8)?,
+                        ..Default::default()
+                    },
+                    text(row, 9)?,
+                    folder,
+                ))
+            })
+            .optional()
+            .map_err(|e| format!("query tasks failed: {}", e))?
+            .ok_or_else(|| format!("task not found: {}", task_id))?;
+        let (mut generation, local_path, folder) = row;
+        if let Some((id, name)) = folder {
+            generation.folder_id = id.to_string();
+            folders.insert(id.to_string(), name);
+        }
+        generations.push((generation, local_path));
+    }
+    let folders = folders
+        .into_iter()
+        .map(|(id, name)| ManifestFolder { id, name })
+        .collect();
+    Ok((generations, folders))
+}
+
+fn copy_hashed(source: &Path, dest: &Path) -> Result<String, String> {
+    let bytes =
+        fs::read(source).map_err(|e| format!("read file failed: {} ({})", e, source.display()))?;
+    fs::write(dest, &bytes)
+        .map_err(|e| format!("write file failed: {} ({})", e, dest.display()))?;
+    Ok(sha256_hex(&bytes))
+}
+
+fn file_ext(path: &Path) -> String {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("png")
+        .to_ascii_lowercase()
+}
+
+fn export(
+    app: &tauri::AppHandle,
+    dest: &Path,
+    name: &str,
+    task_ids: &[String],
+    links: &HashMap<String, Vec<String>>,
+    reference_ids: &[String],
+) -> Result<ProjectExportResult, String> {
+    let (generations, folders) = load_generations(app, task_ids)?;
+    // 单独选的参考图 + 各生成结果关联的参考图，去重后按出现顺序导出
+    let mut wanted: Vec<String> = Vec::new();
+    for id in reference_ids.iter().chain(links.values().flatten()) {
+        if !wanted.contains(id) {
+            wanted.push(id.clone());
+        }
+    }
+    let references: Vec<crate::references::Reference> = wanted
+        .iter()
+        .map(|id| {
+            crate::references::find(app, id).ok_or_else(|| format!("reference not found: {}", id))
+        })
+        .collect::<Result<_, _>>()?;
+
+    let base = crate::app_data_base(app);
+    let sources: Vec<Option<PathBuf>> = generations
+        .iter()
+        .map(|(_, local)| crate::storage::resolve_db_path(&base, local).filter(|p| p.is_file()))
+        .collect();
+    let needed: u64 = sources
+        .iter()
+        .flatten()
+        .map(|p| p.as_path())
+        .chain(references.iter().map(|r| Path::new(&r.path)))
+        .filter_map(|p| fs::metadata(p).ok())
+        .map(|m| m.len())
+        .sum();
+    let generations_dir = dest.join(GENERATIONS_DIR);
+    let references_dir = dest.join(REFERENCES_DIR);
+    fs::create_dir_all(&generations_dir)
+        .and_then(|_| fs::create_dir_all(&references_dir))
+        .map_err(|e| format!("create destination dir failed: {}", e))?;
+    crate::disk::ensure_free_space(dest, needed)?;
+
+    let mut manifest = Manifest {
+        format: MANIFEST_FORMAT.to_string(),
+        version: MANIFEST_VERSION,
+        name: name.to_string(),
+        exported_at: crate::clock::rfc3339(app),
+        folders,
+        ..Default::default()
+    };
+    let mut missing = Vec::new();
+    for ((mut generation, _), source) in generations.into_iter().zip(sources) {
+        let Some(source) = source else {
+            missing.push(generation.task_id);
+            continue;
+        };
+        let file = format!(
+            "{}/{}.{}",
+            GENERATIONS_DIR,
+            generation.task_id,
+            file_ext(&source)
+        );
+        generation.sha256 = copy_hashed(&source, &dest.join(&file))?;
+        generation.file = file;
+        generation.reference_ids = links.get(&generation.task_id).cloned().unwrap_or_default();
+        manifest.generations.push(generation);
+    }
+    for reference in references {
+        let source = PathBuf::from(&reference.path);
+        if !source.is_file() {
+            missing.push(reference.id);
+            continue;
+        }
+        let file = format!("{}/{}.{}", REFERENCES_DIR, reference.id, file_ext(&source));
+        let sha256 = copy_hashed(&source, &dest.join(&file))?;
+        manifest.references.push(ManifestReference {
+            id: reference.id,
+            file,
+            sha256,
+            original_name: reference.original_name,
+            source: reference.source,
+            source_url: reference.source_url,
+            tags: reference.tags,
+        });
+    }
+
+    let manifest_path = dest.join(MANIFEST_NAME);
+    let body = serde_json::to_vec_pretty(&manifest)
+        .map_err(|e| format!("serialize manifest failed: {}", e))?;
+    let temp = dest.join(format!("{}.part", MANIFEST_NAME));
+    fs::write(&temp, body)
+        .and_then(|_| fs::rename(&temp, &manifest_path))
+        .map_err(|e| {
+            let _ = fs::remove_file(&temp);
+            format!("write manifest failed: {}", e)
+        })?;
+    Ok(ProjectExportResult {
+        manifest: manifest_path.to_string_lossy().to_string(),
+        generations: manifest.generations.len(),
+        references: manifest.references.len(),
+        missing,
+    })
+}
+
+// 把选中的生成结果、关联的参考图和清单导出到 path 目录；links 为 task_id -> 参考图 id 列表
+#[tauri::command]
+pub async fn export_project(
+    app: tauri::AppHandle,
+    path: String,
+    name: Option<String>,
+    task_ids: Vec<String>,
+    reference_ids: Option<Vec<String>>,
+    links: Option<HashMap<String, Vec<String>>>,
+) -> Result<ProjectExportResult, String> {
+    let dest = PathBuf::from(path.trim());
+    if dest.as_os_str().is_empty() {
+        return Err("path is empty".to_string());
+    }
+    if task_ids.is_empty() && reference_ids.as_deref().unwrap_or_default().is_empty() {
+        return Err("nothing to export".to_string());
+    }
+    let name = name
+        .map(|n| n.trim().to_string())
+        .filter(|n| !n.is_empty())
+        .or_else(|| dest.file_name().map(|n| n.to_string_lossy().to_string()))
+        .unwrap_or_default();
+    let app_for_task = app.clone();
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        export(
+            &app_for_task,
+            &dest,
+            &name,
+            &task_ids,
+            &links.unwrap_or_default(),
+            &reference_ids.unwrap_or_default(),
+        )
+    })
+    .await
+    .map_err(|e| format!("export project failed: {}", e))??;
+    app.state::<LogState>().log_app(
+        "INFO",
+        &format!(
+            "Project exported generations={} references={} missing={} dest={}",
+            result.generations,
+            result.references,
+            result.missing.len(),
+            result.manifest
+        ),
+    );
+    Ok(result)
+}
+
+// 清单路径失效时（项目目录被整理过）按哈希在目录里找回文件
+struct Relinker {
+    root: PathBuf,
+    by_hash: Option<HashMap<String, PathBuf>>,
+}
+
+impl Relinker {
+    // 清单路径只能是项目目录内的相对路径；解析符号链接后也必须仍在目录内
+    fn resolve(&self, file: &str) -> Result<Option<PathBuf>, String> {
+        let rel = Path::new(file);
+        if file.is_empty() || !rel.components().all(|c| matches!(c, Component::Normal(_))) {
+            return Err(format!("invalid file path in manifest: {}", file));
+        }
+        let Ok(path) = self.root.join(rel).canonicalize() else {
+            return Ok(None);
+        };
+        if !path.starts_with(&self.root) {
+            return Err(format!("file path escapes project folder: {}", file));
+        }
+        Ok(Some(path))
+    }
+
+    fn read(&mut self, file: &str, sha256: &str) -> Result<Vec<u8>, String> {
+        if let Some(direct) = self.resolve(file)? {
+            if let Ok(bytes) = fs::read(&direct) {
+                if sha256.is_empty() || sha256_hex(&bytes) == sha256 {
+                    return Ok(bytes);
+                }
+            }
+        }
+        if sha256.is_empty() {
+            return Err(format!("file not found: {}", file));
+        }
+        let root = self.root.clone();
+        let index = self.by_hash.get_or_insert_with(|| {
+            let mut index = HashMap::new();
+            let mut stack = vec![root];
+            while let Some(dir) = stack.pop() {
+                let Ok(entries) = fs::read_dir(&dir) else {
+                    continue;
+                };
+                for entry in entries.flatten() {
+                    // 不跟随符号链接，按哈希找回时同样不离开项目目录
+                    let Ok(file_type) = entry.file_type() else {
+                        continue;
+                    };
+                    let path = entry.path();
+                    if file_type.is_dir() {
+                        stack.push(path);
+                    } else if !file_type.is_file() {
+                        continue;
+                    } else if let Ok(bytes) = fs::read(&path) {
+                        index.insert(sha256_hex(&bytes), path);
+                    }
+                }
+            }
+            index
+        });
+        let path = index
+            .get(sha256)
+            .ok_or_else(|| format!("file not found: {}", file))?;
+        fs::read(path).map_err(|e| format!("read file failed: {} ({})", e, path.display()))
+    }
+}
+
+fn read_manifest(root: &Path) -> Result<Manifest, String> {
+    let text = fs::read_to_string(root.join(MANIFEST_NAME))
+        .map_err(|e| format!("read manifest failed: {}", e))?;
+    let manifest: Manifest =
+        serde_json::from_str(&text).map_err(|e| format!("parse manifest failed: {}", e))?;
+    if manifest.format != MANIFEST_FORMAT {
+        return Err("not a project manifest".to_string());
+    }
+    if manifest.version > MANIFEST_VERSION {
+        return Err(format!(
+            "unsupported manifest version: {}",
+            manifest.version
+        ));
+    }
+    Ok(manifest)
+}
+
+async fn post_json(
+    client: &reqwest::Client,
+    port: u16,
+    path: &str,
+    body: serde_json::Value,
+) -> Result<serde_json::Value, String> {
+    let response = client
+        .post(format!("http://127.0.0.1:{}{}", port, path))
+        .header(reqwest::header::ORIGIN, "tauri://localhost")
+        .json(&body)
+        .send()
+        .await
+        .map_err(|e| format!("{} request failed: {}", path, e))?;
+    let status = response.status();
+    let value: serde_json::Value = response.json().await.unwrap_or_default();
+    if !status.is_success() {
+        let message = value.get("message").and_then(|m| m.as_str()).unwrap_or("");
+        return Err(format!("{} request failed: {} {}", path, status, message));
+    }
+    Ok(value.get("data").cloned().unwrap_or_default())
+}
+
+// 同名手动文件夹已存在时直接复用
+async fn ensure_folder(
+    app: &tauri::AppHandle,
+    client: &reqwest::Client,
+    port: u16,
+    name: &str,
+) -> Result<String, String> {
+    let existing: Option<i64> = crate::db::open_readonly(app)?
+        .query_row(
+            "SELECT id FROM folders WHERE name = ?1 AND type = 'manual' AND deleted_at IS NULL",
+            [name],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| format!("query folder failed: {}", e))?;
+    if let Some(id) = existing {
+        return Ok(id.to_string());
+    }
+    let data = post_json(
+        client,
+        port,
+        "/api/v1/folders",
+        serde_json::json!({ "name": name }),
+    )
+    .await?;
+    data.get("id")
+        .and_then(|v| v.as_u64())
+        .map(|id| id.to_string())
+        .ok_or_else(|| "create folder returned no id".to_string())
+}
+
+// 从 export_project 导出的目录导入：参考图进参考图库，生成结果复制进存储目录并登记，
+// 再恢复手动文件夹归属；返回新旧 id 对照
+#[tauri::command]
+pub async fn import_project(
+    app: tauri::AppHandle,
+    path: String,
+) -> Result<ProjectImportResult, String> {
+    if crate::safe_mode::is_read_only(&app) {
+        return Err("database is in read-only safe mode".to_string());
+    }
+    let port = *app.state::<BackendPort>().0.lock().unwrap();
+    if port == 0 {
+        return Err("backend is not running".to_string());
+    }
+    let root = PathBuf::from(path.trim())
+        .canonicalize()
+        .map_err(|e| format!("resolve project folder failed: {}", e))?;
+    let manifest = read_manifest(&root)?;
+    let mut relinker = Relinker {
+        root,
+        by_hash: None,
+    };
+    let mut result = ProjectImportResult {
+        generations: HashMap::new(),
+        references: HashMap::new(),
+        folders: HashMap::new(),
+        links: HashMap::new(),
+        failed: Vec::new(),
+    };
+    let total = manifest.generations.len() + manifest.references.len();
+    let mut done = 0;
+
+    // 参考图库按内容去重，同一张图已在库里时只合并标签
+    for reference in &manifest.references {
+        let added = relinker
+            .read(&reference.file, &reference.sha256)
+            .and_then(|bytes| {
+                crate::references::add_bytes(
+                    &app,
+                    &bytes,
+                    &reference.original_name,
+                    &reference.source,
+                    &reference.source_url,
+                    &reference.tags,
+                )
+            });
+        match added {
+            Ok(added) => {
+                result.references.insert(reference.id.clone(), added.id);
+            }
+            Err(reason) => result.failed.push(ProjectImportFailure {
+                id: reference.id.clone(),
+                reason,
+            }),
+        }
+        done += 1;
+        let _ = app.emit(
+            "project-import-progress",
+            ProjectImportProgress { done, total },
+        );
+    }
+
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(30))
+        .build()
+        .map_err(|e| format!("build import client failed: {}", e))?;
+    let storage_dir = crate::paths::storage_dir(&app);
+    fs::create_dir_all(&storage_dir).map_err(|e| format!("create storage dir failed: {}", e))?;
+    let batch = crate::now_ms();
+    let folder_names: HashMap<&str, &str> = manifest
+        .folders
+        .iter()
+        .map(|f| (f.id.as_str(), f.name.as_str()))
+        .collect();
+
+    for (seq, generation) in manifest.generations.iter().enumerate() {
+        let imported = async {
+            let bytes = relinker.read(&generation.file, &generation.sha256)?;
+            let file_name = format!(
+                "project_{}_{}.{}",
+                batch,
+                seq,
+                file_ext(Path::new(&generation.file))
+            );
+            let dest = storage_dir.join(&file_name);
+            crate::disk::ensure_free_space(&dest, bytes.len() as u64)?;
+            fs::write(&dest, &bytes).map_err(|e| format!("write image failed: {}", e))?;
+            let registered = post_json(
+                &client,
+                port,
+                "/api/v1/images/import",
+                serde_json::json!({
+                    "local_path": format!("storage/{}", file_name),
+                    "prompt": generation.prompt,
+                    "provider_name": generation.provider_name,
+                    "model_id": generation.model_id,
+                    "created_at": generation.created_at,
+                }),
+            )
+            .await;
+            // 登记失败就删掉已复制的文件，避免存储目录里出现孤儿图片
+            let data = registered.inspect_err(|_| {
+                let _ = fs::remove_file(&dest);
+            })?;
+            data.get("task_id")
+                .and_then(|v| v.as_str())
+                .map(str::to_string)
+                .ok_or_else(|| "import returned no task_id".to_string())
+        }
+        .await;
+        done += 1;
+        let _ = app.emit(
+            "project-import-progress",
+            ProjectImportProgress { done, total },
+        );
+        let task_id = match imported {
+            Ok(task_id) => task_id,
+            Err(reason) => {
+                result.failed.push(ProjectImportFailure {
+                    id: generation.task_id.clone(),
+                    reason,
+                });
+                continue;
+            }
+        };
+        result
+            .generations
+            .insert(generation.task_id.clone(), task_id.clone());
+        let links: Vec<String> = generation
+            .reference_ids
+            .iter()
+            .filter_map(|id| result.references.get(id).cloned())
+            .collect();
+        if !links.is_empty() {
+            result.links.insert(task_id.clone(), links);
+        }
+
+        let Some(folder_name) = folder_names.get(generation.folder_id.as_str()) else {
+            continue;
+        };
+        let folder_id = match result.folders.get(&generation.folder_id) {
+            Some(id) => Ok(id.clone()),
+            None => ensure_folder(&app, &client, port, folder_name).await,
+        };
+        let moved = match folder_id {
+            Ok(folder_id) => {
+                result
+                    .folders
+                    .insert(generation.folder_id.clone(), folder_id.clone());
+                post_json(
+                    &client,
+                    port,
+                    "/api/v1/folders/move-image",
+                    serde_json::json!({ "task_id": task_id, "folder_id": folder_id }),
+                )
+                .await
+                .map(|_| ())
+            }
+            Err(err) => Err(err),
+        };
+        // 图片已导入，只是文件夹没归上，记一条失败但不回滚
+        if let Err(reason) = moved {
+            result.failed.push(ProjectImportFailure {
+                id: generation.task_id.clone(),
+                reason,
+            });
+        }
+    }
+
+    app.state::<LogState>().log_app(
+        "INFO",
+        &format!(
+            "Project imported generations={} references={} folders={} failed={}",
+            result.generations.len(),
+            result.references.len(),
+            result.folders.len(),
+            result.failed.len()
+        ),
+    );
+    if !result.generations.is_empty() {
+        crate::search::notify();
+    }
+    Ok(result)
+}
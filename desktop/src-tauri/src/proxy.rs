@@ -0,0 +1,513 @@
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tauri::Manager;
+
+use crate::{BackendPort, LogState};
+
+const MAX_HEADER_LINES: usize = 128;
+const MAX_HEADER_LINE_BYTES: u64 = 16 * 1024;
+const MAX_RESPONSE_HEAD_BYTES: usize = 64 * 1024;
+// 图生图会上传多张参考图，给足余量
+const MAX_BODY_BYTES: u64 = 512 * 1024 * 1024;
+// 需要壳层解析请求体的接口（计费、本机 IPC、重定位）才读进内存，其余请求体直接流式转给后端
+const MAX_BUFFERED_BODY_BYTES: u64 = 64 * 1024 * 1024;
+const MAX_CONNECTIONS: usize = 64;
+
+static ACTIVE_CONNECTIONS: AtomicUsize = AtomicUsize::new(0);
+
+// 会消耗 API 额度的接口，暂停队列等管控只针对这些请求
+const BILLABLE_PATHS: &[&str] = &[
+    "/api/v1/tasks/generate",
+    "/api/v1/tasks/generate-with-images",
+    "/api/v1/prompts/optimize",
+    "/api/v1/prompts/image-to-prompt",
+];
+
+// 前端访问后端统一经过壳层代理：端口在本次运行内固定，sidecar 重启后无需重新握手
+pub struct ProxyPort(pub Arc<Mutex<u16>>);
+
+pub struct ProxyRequest {
+    pub method: String,
+    pub path: String,
+    pub query: String,
+    headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+    // 没有读进 body、转发时再从连接里流式读取的字节数
+    streamed: u64,
+}
+
+impl ProxyRequest {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+
+    // multipart 表单里取普通文本字段；参考图是二进制，只按字节查找
+    pub fn form_field(&self, name: &str) -> Option<String> {
+        let marker = format!("name=\"{}\"", name);
+        let body = &self.body;
+        let start = find(body, marker.as_bytes())? + marker.len();
+        let value_start = start + find(&body[start..], b"\r\n\r\n")? + 4;
+        let value_end = value_start + find(&body[value_start..], b"\r\n--")?;
+        Some(String::from_utf8_lossy(&body[value_start..value_end]).to_string())
+    }
+
+    pub fn is_billable(&self) -> bool {
+        is_billable(&self.method, &self.path)
+    }
+}
+
+fn is_billable(method: &str, path: &str) -> bool {
+    method == "POST" && BILLABLE_PATHS.contains(&path)
+}
+
+fn buffers_body(method: &str, path: &str) -> bool {
+    is_billable(method, path)
+        || path.starts_with(crate::instance::IPC_PREFIX)
+        || (method == "POST" && path == "/api/v1/images/relocate")
+}
+
+// 连接数上限：超出时直接拒绝，避免每个连接一个线程把壳层拖垮
+pub struct ConnectionSlot(&'static AtomicUsize);
+
+impl ConnectionSlot {
+    pub fn try_acquire(counter: &'static AtomicUsize, max: usize) -> Option<Self> {
+        counter
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| {
+                (n < max).then_some(n + 1)
+            })
+            .ok()
+            .map(|_| Self(counter))
+    }
+}
+
+impl Drop for ConnectionSlot {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+// 读取一行请求头，单行超过 max 字节时报错，不会无限制地读入内存
+pub fn read_line_capped<R: BufRead>(
+    reader: &mut R,
+    line: &mut String,
+    max: u64,
+) -> Result<usize, String> {
+    let n = reader
+        .take(max)
+        .read_line(line)
+        .map_err(|e| format!("read request failed: {}", e))?;
+    if n as u64 >= max && !line.ends_with('\n') {
+        return Err("request header too long".to_string());
+    }
+    Ok(n)
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|w| w == needle)
+}
+
+// 给前端使用的端口：代理可用时返回代理端口，后端未就绪时返回 0
+pub fn frontend_port(app: &tauri::AppHandle, backend_port: u16) -> u16 {
+    if backend_port == 0 {
+        return 0;
+    }
+    let proxy_port = *app.state::<ProxyPort>().0.lock().unwrap();
+    if proxy_port == 0 {
+        backend_port
+    } else {
+        proxy_port
+    }
+}
+
+fn read_request(reader: &mut BufReader<TcpStream>) -> Result<Option<ProxyRequest>, String> {
+    let mut request_line = String::new();
+    if read_line_capped(reader, &mut request_line, MAX_HEADER_LINE_BYTES)? == 0 {
+        return Ok(None);
+    }
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or("").to_string();
+    let target = parts.next().unwrap_or("/");
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let (path, query) = (path.to_string(), query.to_string());
+
+    let mut headers = Vec::new();
+    for _ in 0..MAX_HEADER_LINES {
+        let mut line = String::new();
+        if read_line_capped(reader, &mut line, MAX_HEADER_LINE_BYTES)? == 0
+            || line.trim().is_empty()
+        {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.push((name.trim().to_string(), value.trim().to_string()));
+        }
+    }
+    let mut request = ProxyRequest {
+        method,
+        path,
+        query,
+        headers,
+        body: Vec::new(),
+        streamed: 0,
+    };
+
+    if request
+        .header("transfer-encoding")
+        .is_some_and(|v| v.to_ascii_lowercase().contains("chunked"))
+    {
+        return Err("chunked request body is not supported".to_string());
+    }
+    let length = request
+        .header("content-length")
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(0);
+    if !buffers_body(&request.method, &request.path) {
+        if length > MAX_BODY_BYTES {
+            return Err(format!("request body too large: {}", length));
+        }
+        request.streamed = length;
+        return Ok(Some(request));
+    }
+    if length > MAX_BUFFERED_BODY_BYTES {
+        return Err(format!("request body too large: {}", length));
+    }
+    // 按实际读到的字节增长，不按客户端声明的长度预先分配
+    reader
+        .take(length)
+        .read_to_end(&mut request.body)
+        .map_err(|e| format!("read request body failed: {}", e))?;
+    if (request.body.len() as u64) < length {
+        return Err("request body is incomplete".to_string());
+    }
+    Ok(Some(request))
+}
+
+fn reason_phrase(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        304 => "Not Modified",
+        400 => "Bad Request",
+        403 => "Forbidden",
+        404 => "Not Found",
+        429 => "Too Many Requests",
+        500 => "Internal Server Error",
+        502 => "Bad Gateway",
+        503 => "Service Unavailable",
+        504 => "Gateway Timeout",
+        507 => "Insufficient Storage",
+        _ => "Unknown",
+    }
+}
+
+// 壳层自己生成的响应沿用后端的 {code,message,data} 结构，前端错误处理无需区分来源
+// 前端来自 tauri:// 源，错误响应也要带上 CORS 头，否则前端只能看到网络错误
+fn write_json(stream: &mut TcpStream, origin: Option<&str>, status: u16, body: &serde_json::Value) {
+    let body = body.to_string();
+    let mut head = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n",
+        status,
+        reason_phrase(status),
+        body.len()
+    );
+    if let Some(origin) = origin {
+        head.push_str(&format!(
+            "Access-Control-Allow-Origin: {}\r\nAccess-Control-Allow-Credentials: true\r\nVary: Origin\r\n",
+            origin
+        ));
+    }
+    head.push_str("\r\n");
+    let _ = stream.write_all(head.as_bytes());
+    let _ = stream.write_all(body.as_bytes());
+    let _ = stream.flush();
+}
+
+// 存储文件未失效时直接由代理回应，不再打到后端
+fn write_not_modified(stream: &mut TcpStream, etag: &str) {
+    let head = format!(
+        "HTTP/1.1 304 Not Modified\r\nCache-Control: no-cache\r\nETag: {}\r\nConnection: close\r\n\r\n",
+        etag
+    );
+    let _ = stream.write_all(head.as_bytes());
+    let _ = stream.flush();
+}
+
+fn write_error(stream: &mut TcpStream, origin: Option<&str>, status: u16, message: &str) {
+    write_json(
+        stream,
+        origin,
+        status,
+        &serde_json::json!({ "code": status, "message": message, "data": null }),
+    );
+}
+
+// 请求到达后端之前的管控点；返回 Some 时直接拒绝
+fn gate(app: &tauri::AppHandle, request: &ProxyRequest) -> Option<(u16, String)> {
+    if request.is_billable() && crate::queue::is_paused(app) {
+        return Some((503, "generation queue is paused".to_string()));
+    }
+    // 磁盘保护触发后直接拒绝，避免后端把结果写到一半
+    if request.is_billable() && crate::disk::generation_paused(app) {
+        return Some((
+            507,
+            "generation paused: disk space is below the configured floor".to_string(),
+        ));
+    }
+    None
+}
+
+struct Relay {
+    status: u16,
+    sent: u64,
+    bytes: u64,
+    // 响应首包（含响应头），生成接口的 JSON 响应通常整个都在里面
+    head: Vec<u8>,
+}
+
+// 读到完整响应头为止（可能顺带读到一部分响应体）；返回已读字节和响应头结束位置
+fn read_response_head(upstream: &mut TcpStream) -> Result<(Vec<u8>, Option<usize>), String> {
+    let mut buf = Vec::with_capacity(8192);
+    let mut chunk = [0u8; 8192];
+    loop {
+        if let Some(end) = find(&buf, b"\r\n\r\n") {
+            return Ok((buf, Some(end + 4)));
+        }
+        if buf.len() >= MAX_RESPONSE_HEAD_BYTES {
+            return Ok((buf, None));
+        }
+        let n = upstream
+            .read(&mut chunk)
+            .map_err(|e| format!("read backend response failed: {}", e))?;
+        if n == 0 {
+            return Ok((buf, None));
+        }
+        buf.extend_from_slice(&chunk[..n]);
+    }
+}
+
+// 只解析状态行，响应体可能是图片等二进制数据
+fn parse_status(head: &[u8]) -> u16 {
+    let line = &head[..find(head, b"\r\n").unwrap_or(head.len())];
+    line.split(|b| *b == b' ')
+        .nth(1)
+        .and_then(|code| std::str::from_utf8(code).ok())
+        .and_then(|code| code.parse().ok())
+        .unwrap_or(0)
+}
+
+fn forward(
+    backend_port: u16,
+    request: &ProxyRequest,
+    body: &mut impl Read,
+    etag: Option<&str>,
+    client: &mut TcpStream,
+) -> Result<Relay, String> {
+    let mut upstream = TcpStream::connect(("127.0.0.1", backend_port))
+        .map_err(|e| format!("connect backend failed: {}", e))?;
+    let target = if request.query.is_empty() {
+        request.path.clone()
+    } else {
+        format!("{}?{}", request.path, request.query)
+    };
+    let mut head = format!("{} {} HTTP/1.1\r\n", request.method, target);
+    for (name, value) in &request.headers {
+        // 每个连接只转发一个请求，和后端之间不复用连接
+        if ["connection", "keep-alive", "proxy-connection"]
+            .iter()
+            .any(|h| name.eq_ignore_ascii_case(h))
+        {
+            continue;
+        }
+        // ETag 由代理管理，后端只按 If-Modified-Since 判断文件是否变化
+        if etag.is_some() && name.eq_ignore_ascii_case("if-none-match") {
+            continue;
+        }
+        // 会话临时 Key 只能由壳层注入
+        if crate::session_key::is_session_header(name) {
+            continue;
+        }
+        head.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    for (name, value) in crate::session_key::headers() {
+        head.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    head.push_str("Connection: close\r\n\r\n");
+    upstream
+        .write_all(head.as_bytes())
+        .and_then(|_| upstream.write_all(&request.body))
+        .map_err(|e| format!("write backend request failed: {}", e))?;
+    if request.streamed > 0 {
+        let copied = std::io::copy(&mut body.take(request.streamed), &mut upstream)
+            .map_err(|e| format!("write backend request failed: {}", e))?;
+        if copied < request.streamed {
+            return Err("write backend request failed: request body is incomplete".to_string());
+        }
+    }
+    // 先读完响应头拿到状态码，其余响应原样回传（含 SSE 流），后端收到 Connection: close 后会在响应结束时断开
+    let (first, head_end) = read_response_head(&mut upstream)?;
+    let status = parse_status(&first);
+    let relayed = match etag.zip(head_end) {
+        Some((etag, _)) => crate::invalidation::rewrite_head(&first, etag),
+        None => first.clone(),
+    };
+    client
+        .write_all(&relayed)
+        .map_err(|e| format!("relay response failed: {}", e))?;
+    let rest = std::io::copy(&mut upstream, client)
+        .map_err(|e| format!("relay response failed: {}", e))?;
+    Ok(Relay {
+        status,
+        sent: head.len() as u64 + request.body.len() as u64 + request.streamed,
+        bytes: first.len() as u64 + rest,
+        head: first,
+    })
+}
+
+fn handle_connection(app: &tauri::AppHandle, mut client: TcpStream) {
+    let _ = client.set_read_timeout(Some(Duration::from_secs(60)));
+    let Ok(clone) = client.try_clone() else {
+        return;
+    };
+    let mut reader = BufReader::new(clone);
+    let request = match read_request(&mut reader) {
+        Ok(Some(request)) => request,
+        Ok(None) => return,
+        Err(err) => {
+            write_error(&mut client, None, 400, &err);
+            return;
+        }
+    };
+    let origin = request.header("origin");
+    // 壳层自己的本机接口（跳转列表等转发给已运行实例），不经过后端
+    if request.path.starts_with(crate::instance::IPC_PREFIX) {
+        let (status, body) = crate::instance::handle(app, &request);
+        write_json(&mut client, None, status, &body);
+        return;
+    }
+    // QA 用的故障注入（默认关闭）：延迟已在 inject 里完成，这里处理断连和合成错误
+    match crate::fault::inject(&request) {
+        Some(crate::fault::Fault::Drop) => {
+            let _ = client.shutdown(std::net::Shutdown::Both);
+            return;
+        }
+        Some(crate::fault::Fault::Error(status)) => {
+            write_error(&mut client, origin, status, "injected failure");
+            return;
+        }
+        None => {}
+    }
+    let etag = crate::invalidation::etag(&request.path);
+    if let Some(etag) = etag.as_deref().filter(|_| request.method == "GET") {
+        if request.header("if-none-match") == Some(etag) {
+            write_not_modified(&mut client, etag);
+            return;
+        }
+    }
+    if let Some((status, message)) = gate(app, &request) {
+        write_error(&mut client, origin, status, &message);
+        return;
+    }
+    // 本地并发上限：名额满时在这里排队，拿到名额后才真正提交
+    let _slot = if request.is_billable() {
+        match crate::queue::acquire_slot(app) {
+            Ok(slot) => Some(slot),
+            Err(err) => {
+                write_error(&mut client, origin, 429, &err);
+                return;
+            }
+        }
+    } else {
+        None
+    };
+    // 生成请求先落盘，渲染进程或壳层中途崩溃后可以重放
+    let journal_id = if request.is_billable() {
+        crate::recovery::begin(app, &request)
+    } else {
+        None
+    };
+    let backend_port = *app.state::<BackendPort>().0.lock().unwrap();
+    if backend_port == 0 {
+        if let Some(id) = &journal_id {
+            crate::recovery::finish(app, id, Some("backend is not running".to_string()));
+        }
+        write_error(&mut client, origin, 502, "backend is not running");
+        return;
+    }
+    // 删除、重定位之前先查出旧地址，成功后对应的缓存失效
+    let stale = crate::invalidation::lookup(app, &request);
+    let result = forward(
+        backend_port,
+        &request,
+        &mut reader,
+        etag.as_deref(),
+        &mut client,
+    );
+    // 后端给出了响应就算送达，前端能看到结果；只保留没送到后端的请求
+    if let Some(id) = &journal_id {
+        let error = match &result {
+            Err(err) if !err.starts_with("relay") => Some(err.clone()),
+            _ => None,
+        };
+        crate::recovery::finish(app, id, error);
+    }
+    match result {
+        Ok(relay) => {
+            crate::bandwidth::record(app, "proxy", relay.sent, relay.bytes);
+            if let Some(stale) = stale.filter(|_| (200..300).contains(&relay.status)) {
+                crate::invalidation::apply(app, &request, stale);
+            }
+            if request.method == "DELETE" && (200..300).contains(&relay.status) {
+                crate::search::notify();
+            }
+            if request.is_billable() && (200..300).contains(&relay.status) {
+                crate::usage::record(app, &request);
+                crate::history::record(app, &request);
+                crate::search::notify();
+                crate::timings::track(app, &request, &relay.head);
+                crate::timelapse::track(app, &request, &relay.head);
+            }
+        }
+        // 已经开始回传响应时无法再改写状态码，只在收到后端响应之前的失败返回 502
+        Err(err) => {
+            if !err.starts_with("relay") {
+                write_error(&mut client, origin, 502, &err);
+            }
+        }
+    }
+}
+
+pub fn start(app: &tauri::AppHandle) -> Result<u16, String> {
+    let listener =
+        TcpListener::bind("127.0.0.1:0").map_err(|e| format!("bind proxy failed: {}", e))?;
+    let port = listener
+        .local_addr()
+        .map_err(|e| format!("bind proxy failed: {}", e))?
+        .port();
+    *app.state::<ProxyPort>().0.lock().unwrap() = port;
+
+    let app_handle = app.clone();
+    std::thread::spawn(move || {
+        for mut stream in listener.incoming().flatten() {
+            let Some(slot) = ConnectionSlot::try_acquire(&ACTIVE_CONNECTIONS, MAX_CONNECTIONS)
+            else {
+                let _ = stream.set_write_timeout(Some(Duration::from_secs(1)));
+                write_error(&mut stream, None, 503, "too many connections");
+                continue;
+            };
+            let app_handle = app_handle.clone();
+            std::thread::spawn(move || {
+                let _slot = slot;
+                handle_connection(&app_handle, stream)
+            });
+        }
+    });
+    app.state::<LogState>()
+        .log_app("INFO", &format!("Backend proxy listening port={}", port));
+    Ok(port)
+}
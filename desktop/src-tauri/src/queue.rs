@@ -0,0 +1,271 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+use tauri::{Emitter, Manager, State};
+
+use crate::settings::SettingsState;
+use crate::{BackendPort, LogState};
+
+// 排队等待名额的请求最长等多久，超时后返回错误交给前端重试
+const SLOT_WAIT_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+// 壳层记录的暂停开关：即使界面卡死，托盘菜单也能立即拦住新的生成请求
+pub struct QueueState(pub Arc<Mutex<bool>>);
+
+// 本地并发名额：转发中的计费请求 + 已提交未结束的生成任务
+static SLOTS: Mutex<Slots> = Mutex::new(Slots {
+    in_flight: 0,
+    waiting: VecDeque::new(),
+    next_ticket: 0,
+});
+static SLOTS_CHANGED: Condvar = Condvar::new();
+
+struct Slots {
+    in_flight: usize,
+    waiting: VecDeque<u64>,
+    next_ticket: u64,
+}
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct ConcurrencySettings {
+    // 同时进行的生成任务上限，0 表示不限制（仍交给后端 Worker 池）
+    pub max_concurrent: u32,
+    // 本地排队的请求数上限，超出直接拒绝，防止前端死循环堆积
+    pub max_waiting: u32,
+}
+
+impl Default for ConcurrencySettings {
+    fn default() -> Self {
+        Self {
+            max_concurrent: 4,
+            max_waiting: 50,
+        }
+    }
+}
+
+#[derive(Clone, serde::Serialize)]
+pub struct QueueStatus {
+    paused: bool,
+    // 后端同步失败时仍会在代理层拦截，这里只用于提示
+    backend_synced: bool,
+    active: usize,
+    waiting: usize,
+    max_concurrent: u32,
+}
+
+#[derive(Clone, serde::Serialize)]
+struct SlotPosition {
+    ticket: u64,
+    // 1 表示排在最前；0 表示已拿到名额开始提交
+    position: usize,
+    waiting: usize,
+    active: usize,
+    max_concurrent: u32,
+}
+
+// 持有期间占用一个名额，请求转发结束时释放
+pub struct SlotGuard;
+
+impl Drop for SlotGuard {
+    fn drop(&mut self) {
+        let mut slots = SLOTS.lock().unwrap();
+        slots.in_flight = slots.in_flight.saturating_sub(1);
+        SLOTS_CHANGED.notify_all();
+    }
+}
+
+pub fn is_paused(app: &tauri::AppHandle) -> bool {
+    *app.state::<QueueState>().0.lock().unwrap()
+}
+
+fn status(app: &tauri::AppHandle, paused: bool, backend_synced: bool) -> QueueStatus {
+    let slots = SLOTS.lock().unwrap();
+    QueueStatus {
+        paused,
+        backend_synced,
+        active: slots.in_flight + crate::timings::active_count(),
+        waiting: slots.waiting.len(),
+        max_concurrent: app
+            .state::<SettingsState>()
+            .get()
+            .concurrency
+            .max_concurrent,
+    }
+}
+
+// 任务结束或设置变化时唤醒排队的请求
+pub fn notify_slots(_app: &tauri::AppHandle) {
+    SLOTS_CHANGED.notify_all();
+}
+
+// 代理转发计费请求前调用：名额已满时在这里阻塞排队（每个连接一个线程），按先来后到放行
+pub fn acquire_slot(app: &tauri::AppHandle) -> Result<SlotGuard, String> {
+    let mut slots = SLOTS.lock().unwrap();
+    let settings = app.state::<SettingsState>().get().concurrency;
+    if settings.max_concurrent > 0 && slots.waiting.len() >= settings.max_waiting as usize {
+        return Err(format!(
+            "too many generation requests waiting (max {})",
+            settings.max_waiting
+        ));
+    }
+    let ticket = slots.next_ticket;
+    slots.next_ticket += 1;
+    slots.waiting.push_back(ticket);
+    let deadline = Instant::now() + SLOT_WAIT_TIMEOUT;
+    let mut last_position = 0;
+    loop {
+        // 每轮重新读取设置，调大上限后排队的请求立即放行
+        let limit = app
+            .state::<SettingsState>()
+            .get()
+            .concurrency
+            .max_concurrent;
+        let position = slots.waiting.iter().position(|t| *t == ticket).unwrap_or(0) + 1;
+        let active = slots.in_flight + crate::timings::active_count();
+        let payload = |position: usize, waiting: usize| SlotPosition {
+            ticket,
+            position,
+            waiting,
+            active,
+            max_concurrent: limit,
+        };
+        if position == 1 && (limit == 0 || active < limit as usize) {
+            slots.waiting.pop_front();
+            slots.in_flight += 1;
+            let waiting = slots.waiting.len();
+            drop(slots);
+            SLOTS_CHANGED.notify_all();
+            if last_position != 0 {
+                let _ = app.emit("generation-slot-queue", payload(0, waiting));
+            }
+            return Ok(SlotGuard);
+        }
+        let error = if is_paused(app) {
+            Some("generation queue is paused".to_string())
+        } else if Instant::now() >= deadline {
+            Some("timed out waiting for a generation slot".to_string())
+        } else {
+            None
+        };
+        if let Some(error) = error {
+            slots.waiting.retain(|t| *t != ticket);
+            drop(slots);
+            SLOTS_CHANGED.notify_all();
+            return Err(error);
+        }
+        if position != last_position {
+            last_position = position;
+            let _ = app.emit(
+                "generation-slot-queue",
+                payload(position, slots.waiting.len()),
+            );
+        }
+        // 任务结束由计时轮询发现，这里定时醒来兜底
+        slots = SLOTS_CHANGED
+            .wait_timeout(slots, Duration::from_secs(1))
+            .unwrap()
+            .0;
+    }
+}
+
+// 同时暂停后端 Worker 池，已经排进后端队列的任务也不会再开始
+async fn sync_backend(app: &tauri::AppHandle, paused: bool) -> Result<(), String> {
+    let port = *app.state::<BackendPort>().0.lock().unwrap();
+    if port == 0 {
+        return Err("backend is not running".to_string());
+    }
+    let action = if paused { "pause" } else { "resume" };
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(5))
+        .build()
+        .map_err(|e| format!("build queue client failed: {}", e))?;
+    let response = client
+        .post(format!("http://127.0.0.1:{}/api/v1/queue/{}", port, action))
+        .header(reqwest::header::ORIGIN, "tauri://localhost")
+        .send()
+        .await
+        .map_err(|e| format!("{} queue request failed: {}", action, e))?;
+    if !response.status().is_success() {
+        return Err(format!(
+            "{} queue request failed: {}",
+            action,
+            response.status()
+        ));
+    }
+    Ok(())
+}
+
+pub async fn set_paused(app: &tauri::AppHandle, paused: bool) -> QueueStatus {
+    *app.state::<QueueState>().0.lock().unwrap() = paused;
+    let backend_synced = match sync_backend(app, paused).await {
+        Ok(()) => true,
+        Err(err) => {
+            app.state::<LogState>().log_app(
+                "WARN",
+                &format!("Queue state not synced to backend: {}", err),
+            );
+            false
+        }
+    };
+    app.state::<LogState>().log_app(
+        "INFO",
+        &format!(
+            "Generation queue {}",
+            if paused { "paused" } else { "resumed" }
+        ),
+    );
+    crate::tray::refresh(app);
+    notify_slots(app);
+    let status = status(app, paused, backend_synced);
+    let _ = app.emit("queue-status", status.clone());
+    status
+}
+
+// sidecar 重启后 Worker 池是未暂停状态，端口就绪时补发一次
+pub fn restore_backend_state(app: &tauri::AppHandle) {
+    if !is_paused(app) {
+        return;
+    }
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(err) = sync_backend(&app, true).await {
+            app.state::<LogState>().log_app(
+                "WARN",
+                &format!("Queue state not synced to backend: {}", err),
+            );
+        }
+    });
+}
+
+#[tauri::command]
+pub async fn pause_queue(app: tauri::AppHandle) -> QueueStatus {
+    set_paused(&app, true).await
+}
+
+#[tauri::command]
+pub async fn resume_queue(app: tauri::AppHandle) -> QueueStatus {
+    set_paused(&app, false).await
+}
+
+#[tauri::command]
+pub fn get_queue_status(app: tauri::AppHandle) -> QueueStatus {
+    status(&app, is_paused(&app), true)
+}
+
+#[tauri::command]
+pub fn get_concurrency_settings(state: State<'_, SettingsState>) -> ConcurrencySettings {
+    state.get().concurrency
+}
+
+#[tauri::command]
+pub fn set_concurrency_settings(
+    app: tauri::AppHandle,
+    state: State<'_, SettingsState>,
+    settings: ConcurrencySettings,
+) -> Result<ConcurrencySettings, String> {
+    let concurrency = state.update(|s| s.concurrency = settings)?.concurrency;
+    notify_slots(&app);
+    Ok(concurrency)
+}
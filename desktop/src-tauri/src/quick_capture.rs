@@ -0,0 +1,226 @@
+use std::sync::Mutex;
+use std::time::Duration;
+
+use tauri::Manager;
+use tauri_plugin_notification::NotificationExt;
+
+use crate::{BackendPort, LogState};
+
+pub const LABEL: &str = "quick_capture";
+const IMAGE_TO_IMAGE_PATH: &str = "/api/v1/tasks/generate-with-images";
+static LAST_HIDDEN_AT: Mutex<u128> = Mutex::new(0);
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use tauri::{Manager, PhysicalPosition, WebviewUrl, WebviewWindow, WebviewWindowBuilder};
+
+    use crate::LogState;
+
+    const ROUTE: &str = "index.html#/quick-capture";
+    const WIDTH: f64 = 380.0;
+    const HEIGHT: f64 = 260.0;
+    // 点击托盘图标时小窗先失焦收起，紧接着的点击事件不应再把它弹出来
+    const RECENTLY_HIDDEN_MS: u128 = 300;
+
+    // 菜单栏小窗只创建一次，之后显示 / 隐藏复用同一个 webview
+    fn window(app: &tauri::AppHandle) -> Result<WebviewWindow, String> {
+        if let Some(window) = app.get_webview_window(super::LABEL) {
+            return Ok(window);
+        }
+        WebviewWindowBuilder::new(app, super::LABEL, WebviewUrl::App(ROUTE.into()))
+            .title("快速生成")
+            .inner_size(WIDTH, HEIGHT)
+            .resizable(false)
+            .decorations(false)
+            .always_on_top(true)
+            .skip_taskbar(true)
+            .visible(false)
+            .build()
+            .map_err(|e| format!("create quick capture window failed: {}", e))
+    }
+
+    // 有托盘图标位置时贴在图标正下方，否则放在屏幕上方居中
+    fn place(window: &WebviewWindow, anchor: Option<tauri::Rect>) {
+        let Some(anchor) = anchor else {
+            let _ = window.center();
+            return;
+        };
+        let scale = window.scale_factor().unwrap_or(1.0);
+        let position = anchor.position.to_physical::<f64>(scale);
+        let size = anchor.size.to_physical::<f64>(scale);
+        let x = position.x + size.width / 2.0 - WIDTH * scale / 2.0;
+        let y = position.y + size.height;
+        let _ = window.set_position(PhysicalPosition::new(x.max(0.0), y));
+    }
+
+    pub fn show(app: &tauri::AppHandle, anchor: Option<tauri::Rect>) -> Result<(), String> {
+        let window = window(app)?;
+        place(&window, anchor);
+        window
+            .show()
+            .map_err(|e| format!("show quick capture window failed: {}", e))?;
+        let _ = window.set_focus();
+        Ok(())
+    }
+
+    // 托盘左键点击：已显示则收起，否则在图标下方弹出
+    pub fn toggle(app: &tauri::AppHandle, anchor: tauri::Rect) {
+        let visible = app
+            .get_webview_window(super::LABEL)
+            .and_then(|w| w.is_visible().ok())
+            .unwrap_or(false);
+        if visible {
+            super::hide(app);
+            return;
+        }
+        let hidden_at = *super::LAST_HIDDEN_AT.lock().unwrap();
+        if crate::now_ms().saturating_sub(hidden_at) < RECENTLY_HIDDEN_MS {
+            return;
+        }
+        if let Err(err) = show(app, Some(anchor)) {
+            app.state::<LogState>().log_app("WARN", &err);
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+pub use platform::toggle;
+
+fn hide(app: &tauri::AppHandle) {
+    if let Some(window) = app.get_webview_window(LABEL) {
+        let _ = window.hide();
+        *LAST_HIDDEN_AT.lock().unwrap() = crate::now_ms();
+    }
+}
+
+// 和菜单栏弹出面板一样，失去焦点即收起
+pub fn on_window_event(app: &tauri::AppHandle, label: &str, event: &tauri::WindowEvent) {
+    if label == LABEL && matches!(event, tauri::WindowEvent::Focused(false)) {
+        hide(app);
+    }
+}
+
+// 带参考图时走图生图接口：沿用模板里的 params，参考图按本地路径传给后端
+async fn submit_with_references(
+    app: &tauri::AppHandle,
+    template: &serde_json::Value,
+    prompt: &str,
+    paths: Vec<String>,
+) -> Result<(), String> {
+    let backend_port = *app.state::<BackendPort>().0.lock().unwrap();
+    let port = crate::proxy::frontend_port(app, backend_port);
+    if port == 0 {
+        return Err("backend is not running".to_string());
+    }
+    let mut form = reqwest::multipart::Form::new();
+    for key in ["provider", "model_id"] {
+        if let Some(value) = template.get(key).and_then(|v| v.as_str()) {
+            form = form.text(key, value.to_string());
+        }
+    }
+    if let Some(params) = template.get("params").and_then(|p| p.as_object()) {
+        for (key, value) in params {
+            let value = match value {
+                serde_json::Value::String(s) => s.clone(),
+                serde_json::Value::Number(n) => n.to_string(),
+                serde_json::Value::Bool(b) => b.to_string(),
+                _ => continue,
+            };
+            if key != "prompt" {
+                form = form.text(key.clone(), value);
+            }
+        }
+    }
+    form = form.text("prompt", prompt.to_string());
+    for path in paths {
+        form = form.text("refPaths", path);
+    }
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(30))
+        .build()
+        .map_err(|e| format!("build quick capture client failed: {}", e))?;
+    let response = client
+        .post(format!("http://127.0.0.1:{}{}", port, IMAGE_TO_IMAGE_PATH))
+        .header(reqwest::header::ORIGIN, "tauri://localhost")
+        .multipart(form)
+        .send()
+        .await
+        .map_err(|e| format!("quick capture request failed: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!(
+            "quick capture request failed: {}",
+            response.status()
+        ));
+    }
+    Ok(())
+}
+
+// 服务商、模型和尺寸沿用最近一次文生图请求，只替换提示词；Windows 通知里的快速回复也走这里
+pub async fn submit_prompt(
+    app: &tauri::AppHandle,
+    prompt: &str,
+    reference_paths: Vec<String>,
+) -> Result<(), String> {
+    let mut template = crate::history::latest_request(app).ok_or_else(|| {
+        "no previous text-to-image request to reuse, generate once from the main window".to_string()
+    })?;
+    if reference_paths.is_empty() {
+        if let Some(slot) = template.pointer_mut("/params/prompt") {
+            *slot = serde_json::Value::String(prompt.to_string());
+        }
+        crate::history::submit(app, &template).await
+    } else {
+        submit_with_references(app, &template, prompt, reference_paths).await
+    }
+}
+
+#[tauri::command]
+pub fn show_quick_capture(app: tauri::AppHandle) -> Result<(), String> {
+    #[cfg(target_os = "macos")]
+    {
+        platform::show(&app, None)
+    }
+    #[cfg(not(target_os = "macos"))]
+    {
+        let _ = app;
+        Err("quick capture is only available on macOS".to_string())
+    }
+}
+
+#[tauri::command]
+pub fn hide_quick_capture(app: tauri::AppHandle) {
+    hide(&app);
+}
+
+// reference_ids 是通过 add_reference_from_clipboard 等粘贴进参考图库的图片
+#[tauri::command]
+pub async fn submit_quick_capture(
+    app: tauri::AppHandle,
+    prompt: String,
+    reference_ids: Option<Vec<String>>,
+) -> Result<(), String> {
+    let prompt = prompt.trim().to_string();
+    if prompt.is_empty() {
+        return Err("prompt is empty".to_string());
+    }
+    let paths = reference_ids
+        .unwrap_or_default()
+        .iter()
+        .map(|id| {
+            crate::references::find(&app, id)
+                .map(|r| r.path)
+                .ok_or_else(|| format!("reference not found: {}", id))
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    submit_prompt(&app, &prompt, paths).await?;
+    hide(&app);
+    app.state::<LogState>()
+        .log_app("INFO", "Quick capture generation submitted");
+    let _ = app
+        .notification()
+        .builder()
+        .title("已提交生成")
+        .body(prompt.chars().take(80).collect::<String>())
+        .show();
+    Ok(())
+}
@@ -0,0 +1,132 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use tauri::{Emitter, Manager};
+
+use crate::LogState;
+
+const HEALTH_TIMEOUT: Duration = Duration::from_secs(60);
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+const WAIT_INTERVAL: Duration = Duration::from_millis(50);
+const DEFAULT_WAIT_MS: u64 = 30_000;
+
+// 健康检查通过的后端端口，0 表示尚未就绪（或 sidecar 已退出）
+static READY_PORT: Mutex<u16> = Mutex::new(0);
+// 每次 sidecar 拉起/退出都递增，旧端口上的探测线程据此自行结束
+static EPOCH: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Clone, serde::Serialize)]
+pub struct BackendReady {
+    // 与 backend-port 事件一致，是前端应连接的端口（启用代理时为代理端口）
+    port: u16,
+}
+
+fn ready_payload(app: &tauri::AppHandle, port: u16) -> BackendReady {
+    BackendReady {
+        port: crate::proxy::frontend_port(app, port),
+    }
+}
+
+pub fn ready_port() -> Option<u16> {
+    Some(*READY_PORT.lock().unwrap()).filter(|p| *p != 0)
+}
+
+// sidecar 退出或被停掉时调用
+pub fn reset() {
+    EPOCH.fetch_add(1, Ordering::SeqCst);
+    *READY_PORT.lock().unwrap() = 0;
+}
+
+// 检测到 SERVER_PORT= 只说明进程打印了端口，轮询健康检查接口确认 Gin 真正开始处理请求后再发 backend-ready
+pub fn probe(app: &tauri::AppHandle, port: u16) {
+    reset();
+    let epoch = EPOCH.load(Ordering::SeqCst);
+    let app = app.clone();
+    // 专用线程里阻塞轮询，不占用异步运行时
+    std::thread::spawn(move || {
+        let Ok(client) = reqwest::Client::builder()
+            .timeout(Duration::from_secs(2))
+            .build()
+        else {
+            return;
+        };
+        let url = format!("http://127.0.0.1:{}/api/v1/health", port);
+        let started = Instant::now();
+        while started.elapsed() < HEALTH_TIMEOUT {
+            if EPOCH.load(Ordering::SeqCst) != epoch {
+                return;
+            }
+            let healthy = tauri::async_runtime::block_on(async {
+                client
+                    .get(&url)
+                    .header(reqwest::header::ORIGIN, "tauri://localhost")
+                    .send()
+                    .await
+                    .is_ok_and(|r| r.status().is_success())
+            });
+            if healthy {
+                {
+                    let mut ready = READY_PORT.lock().unwrap();
+                    if EPOCH.load(Ordering::SeqCst) != epoch {
+                        return;
+                    }
+                    *ready = port;
+                }
+                crate::startup::mark_and_finish(&app, "backend_healthy");
+                app.state::<LogState>().log_app(
+                    "INFO",
+                    &format!(
+                        "Backend ready port={} elapsed_ms={}",
+                        port,
+                        started.elapsed().as_millis()
+                    ),
+                );
+                let _ = app.emit("backend-ready", ready_payload(&app, port));
+                crate::backend_version::check(&app, port);
+                return;
+            }
+            std::thread::sleep(POLL_INTERVAL);
+        }
+        app.state::<LogState>().log_app(
+            "WARN",
+            &format!(
+                "Backend health check not passing after {:?}",
+                HEALTH_TIMEOUT
+            ),
+        );
+    });
+}
+
+// 阻塞等待后端就绪，超时返回 None；调用方需在后台线程
+pub fn wait_ready(timeout: Duration) -> Option<u16> {
+    let started = Instant::now();
+    loop {
+        if let Some(port) = ready_port() {
+            return Some(port);
+        }
+        if started.elapsed() >= timeout {
+            return None;
+        }
+        std::thread::sleep(WAIT_INTERVAL);
+    }
+}
+
+// 前端启动时等待后端就绪；已就绪立即返回，超时返回错误
+#[tauri::command]
+pub async fn wait_for_backend(
+    app: tauri::AppHandle,
+    timeout_ms: Option<u64>,
+) -> Result<BackendReady, String> {
+    let timeout = Duration::from_millis(timeout_ms.unwrap_or(DEFAULT_WAIT_MS));
+    let port = tauri::async_runtime::spawn_blocking(move || wait_ready(timeout))
+        .await
+        .map_err(|e| format!("wait for backend failed: {}", e))?;
+    match port {
+        Some(port) => Ok(ready_payload(&app, port)),
+        None => Err(format!(
+            "backend not ready within {}ms",
+            timeout.as_millis()
+        )),
+    }
+}
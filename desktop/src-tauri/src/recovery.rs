@@ -0,0 +1,392 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use tauri::{Emitter, Manager};
+use tauri_plugin_dialog::{
+    DialogExt, MessageDialogButtons, MessageDialogKind, MessageDialogResult,
+};
+
+use crate::proxy::ProxyRequest;
+use crate::{BackendPort, LogState};
+
+// 串行化 recovery/journal.json 的读写
+static JOURNAL_LOCK: Mutex<()> = Mutex::new(());
+// 上次会话没有正常退出且留下了未完成的工作，等后端就绪后提示一次
+static PENDING_OFFER: AtomicBool = AtomicBool::new(false);
+
+const JOURNAL_NAME: &str = "journal.json";
+const SESSION_NAME: &str = "session.json";
+// 重放时带上原记录 id，代理据此覆盖原记录而不是再记一条
+pub const REPLAY_HEADER: &str = "x-recovery-id";
+
+#[derive(Clone, Default, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct JournalEntry {
+    pub id: String,
+    pub path: String,
+    pub query: String,
+    pub content_type: String,
+    pub prompt: String,
+    pub body_size: u64,
+    pub created_at: u128,
+    // 最近一次提交失败的原因；为空表示提交途中进程就退出了
+    pub last_error: String,
+}
+
+#[derive(Default, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct RecoveryJournal {
+    // 代理已收到、但还没有被后端确认接收的生成请求
+    pub requests: Vec<JournalEntry>,
+    // 前端自行登记的进行中状态（待上传的参考图等），壳层不解释，原样交还
+    pub ui_state: BTreeMap<String, serde_json::Value>,
+}
+
+#[derive(Default, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+struct SessionMarker {
+    running: bool,
+    pid: u32,
+    started_at: u128,
+}
+
+#[derive(serde::Serialize)]
+pub struct ReplayResult {
+    replayed: Vec<String>,
+    failed: Vec<String>,
+}
+
+fn recovery_dir(app: &tauri::AppHandle) -> PathBuf {
+    crate::app_data_base(app).join("recovery")
+}
+
+fn journal_path(app: &tauri::AppHandle) -> PathBuf {
+    recovery_dir(app).join(JOURNAL_NAME)
+}
+
+fn body_path(app: &tauri::AppHandle, id: &str) -> PathBuf {
+    recovery_dir(app).join(format!("{}.body", id))
+}
+
+fn load(app: &tauri::AppHandle) -> RecoveryJournal {
+    crate::settings::read_json_or_default(&journal_path(app))
+}
+
+fn save(app: &tauri::AppHandle, journal: &RecoveryJournal) -> Result<(), String> {
+    crate::settings::write_json_atomic(&journal_path(app), journal)
+}
+
+fn is_empty(journal: &RecoveryJournal) -> bool {
+    journal.requests.is_empty() && journal.ui_state.is_empty()
+}
+
+fn new_id() -> String {
+    let mut bytes = [0u8; 4];
+    let _ = getrandom::fill(&mut bytes);
+    let suffix: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+    format!("{}-{}", crate::now_ms(), suffix)
+}
+
+fn prompt_of(request: &ProxyRequest) -> String {
+    serde_json::from_slice::<serde_json::Value>(&request.body)
+        .ok()
+        .and_then(|j| {
+            j.pointer("/params/prompt")
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string())
+        })
+        .or_else(|| request.form_field("prompt"))
+        .unwrap_or_default()
+        .trim()
+        .chars()
+        .take(200)
+        .collect()
+}
+
+// 代理转发生成请求之前调用，返回记录 id；写盘失败只记日志，不影响本次请求
+pub fn begin(app: &tauri::AppHandle, request: &ProxyRequest) -> Option<String> {
+    if !request.path.starts_with("/api/v1/tasks/generate") {
+        return None;
+    }
+    let id = request
+        .header(REPLAY_HEADER)
+        .filter(|id| !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-'))
+        .map(|id| id.to_string())
+        .unwrap_or_else(new_id);
+    let entry = JournalEntry {
+        id: id.clone(),
+        path: request.path.clone(),
+        query: request.query.clone(),
+        content_type: request
+            .header("content-type")
+            .unwrap_or_default()
+            .to_string(),
+        prompt: prompt_of(request),
+        body_size: request.body.len() as u64,
+        created_at: crate::now_ms(),
+        last_error: String::new(),
+    };
+    let result = (|| {
+        fs::create_dir_all(recovery_dir(app))
+            .map_err(|e| format!("create recovery dir failed: {}", e))?;
+        fs::write(body_path(app, &id), &request.body)
+            .map_err(|e| format!("write recovery body failed: {}", e))?;
+        let _guard = JOURNAL_LOCK.lock().unwrap();
+        let mut journal = load(app);
+        journal.requests.retain(|r| r.id != id);
+        journal.requests.push(entry);
+        save(app, &journal)
+    })();
+    match result {
+        Ok(()) => Some(id),
+        Err(err) => {
+            app.state::<LogState>().log_app(
+                "WARN",
+                &format!("journal generation request failed: {}", err),
+            );
+            None
+        }
+    }
+}
+
+// 后端确认接收后删除记录；失败时保留并写上原因，之后可以重放
+pub fn finish(app: &tauri::AppHandle, id: &str, error: Option<String>) {
+    let _guard = JOURNAL_LOCK.lock().unwrap();
+    let mut journal = load(app);
+    match error {
+        None => {
+            journal.requests.retain(|r| r.id != id);
+            let _ = fs::remove_file(body_path(app, id));
+        }
+        Some(err) => {
+            if let Some(entry) = journal.requests.iter_mut().find(|r| r.id == id) {
+                entry.last_error = err;
+            }
+        }
+    }
+    if let Err(err) = save(app, &journal) {
+        app.state::<LogState>()
+            .log_app("WARN", &format!("update recovery journal failed: {}", err));
+    }
+}
+
+// 启动时调用：上次的会话标记仍是 running 说明进程异常退出
+pub fn start_session(app: &tauri::AppHandle) {
+    let path = recovery_dir(app).join(SESSION_NAME);
+    let previous: SessionMarker = crate::settings::read_json_or_default(&path);
+    let journal = load(app);
+    if previous.running && !is_empty(&journal) {
+        app.state::<LogState>().log_app(
+            "WARN",
+            &format!(
+                "Previous session (pid={}) did not exit cleanly, {} requests and {} ui entries journaled",
+                previous.pid,
+                journal.requests.len(),
+                journal.ui_state.len()
+            ),
+        );
+        PENDING_OFFER.store(true, Ordering::SeqCst);
+    } else if !previous.running && !is_empty(&journal) {
+        // 正常退出前未清理干净的残留，不再提示
+        let _ = clear(app);
+    }
+    let marker = SessionMarker {
+        running: true,
+        pid: std::process::id(),
+        started_at: crate::now_ms(),
+    };
+    if let Err(err) = crate::settings::write_json_atomic(&path, &marker) {
+        app.state::<LogState>()
+            .log_app("WARN", &format!("write session marker failed: {}", err));
+    }
+}
+
+// 正常退出：用户已确认中断进行中的任务，日志不再需要
+pub fn end_session(app: &tauri::AppHandle) {
+    if PENDING_OFFER.load(Ordering::SeqCst) {
+        // 本次启动还没来得及处理上次留下的记录，保留给下次
+        return;
+    }
+    let _ = clear(app);
+    let _ = crate::settings::write_json_atomic(
+        &recovery_dir(app).join(SESSION_NAME),
+        &SessionMarker::default(),
+    );
+}
+
+fn clear(app: &tauri::AppHandle) -> Result<(), String> {
+    let _guard = JOURNAL_LOCK.lock().unwrap();
+    for entry in load(app).requests {
+        let _ = fs::remove_file(body_path(app, &entry.id));
+    }
+    save(app, &RecoveryJournal::default())
+}
+
+// 经过代理重新提交，暂停开关、费用记账照常生效
+async fn replay_entry(app: &tauri::AppHandle, entry: &JournalEntry) -> Result<(), String> {
+    let backend_port = *app.state::<BackendPort>().0.lock().unwrap();
+    let port = crate::proxy::frontend_port(app, backend_port);
+    if port == 0 {
+        return Err("backend is not running".to_string());
+    }
+    let body = fs::read(body_path(app, &entry.id))
+        .map_err(|e| format!("read recovery body failed: {}", e))?;
+    let mut url = format!("http://127.0.0.1:{}{}", port, entry.path);
+    if !entry.query.is_empty() {
+        url.push('?');
+        url.push_str(&entry.query);
+    }
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(120))
+        .build()
+        .map_err(|e| format!("build replay client failed: {}", e))?;
+    let mut request = client
+        .post(url)
+        .header(reqwest::header::ORIGIN, "tauri://localhost")
+        .header(REPLAY_HEADER, &entry.id)
+        .body(body);
+    if !entry.content_type.is_empty() {
+        request = request.header(reqwest::header::CONTENT_TYPE, &entry.content_type);
+    }
+    let response = request
+        .send()
+        .await
+        .map_err(|e| format!("replay request failed: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("replay request failed: {}", response.status()));
+    }
+    Ok(())
+}
+
+pub async fn replay(app: &tauri::AppHandle, ids: Option<Vec<String>>) -> ReplayResult {
+    let entries: Vec<JournalEntry> = load(app)
+        .requests
+        .into_iter()
+        .filter(|e| ids.as_ref().is_none_or(|ids| ids.contains(&e.id)))
+        .collect();
+    let mut result = ReplayResult {
+        replayed: Vec::new(),
+        failed: Vec::new(),
+    };
+    // 顺序提交，保持原来的批次顺序
+    for entry in entries {
+        match replay_entry(app, &entry).await {
+            Ok(()) => result.replayed.push(entry.id),
+            Err(err) => {
+                app.state::<LogState>().log_app(
+                    "WARN",
+                    &format!("replay journaled request failed id={}: {}", entry.id, err),
+                );
+                result.failed.push(entry.id);
+            }
+        }
+    }
+    app.state::<LogState>().log_app(
+        "INFO",
+        &format!(
+            "Recovery replay finished replayed={} failed={}",
+            result.replayed.len(),
+            result.failed.len()
+        ),
+    );
+    let _ = app.emit("recovery-journal-changed", ());
+    result
+}
+
+// 后端端口就绪时调用；只在异常退出后的首次就绪提示
+pub fn offer_pending(app: &tauri::AppHandle) {
+    if !PENDING_OFFER.swap(false, Ordering::SeqCst) {
+        return;
+    }
+    let journal = load(app);
+    if is_empty(&journal) {
+        return;
+    }
+    // 界面状态交给前端恢复；前端加载时也可以主动调用 get_recovery_journal
+    let _ = app.emit("recovery-available", journal.requests.len());
+    if journal.requests.is_empty() {
+        return;
+    }
+    let app_handle = app.clone();
+    app.dialog()
+        .message(format!(
+            "上次会话异常退出，有 {} 个生成请求没有确认提交成功。\n\n重新提交可能会与已经开始的任务重复。",
+            journal.requests.len()
+        ))
+        .title("恢复未完成的任务")
+        .kind(MessageDialogKind::Warning)
+        .buttons(MessageDialogButtons::YesNoCancelCustom(
+            "重新提交".to_string(),
+            "丢弃".to_string(),
+            "稍后处理".to_string(),
+        ))
+        .show_with_result(move |result| {
+            let choice = match result {
+                MessageDialogResult::Custom(label) => label,
+                _ => String::new(),
+            };
+            match choice.as_str() {
+                "重新提交" => {
+                    let app = app_handle.clone();
+                    tauri::async_runtime::spawn(async move {
+                        replay(&app, None).await;
+                    });
+                }
+                "丢弃" => {
+                    let _ = clear(&app_handle);
+                    let _ = app_handle.emit("recovery-journal-changed", ());
+                }
+                // 稍后处理：记录保留到下次正常退出，期间可在界面里重放
+                _ => {}
+            }
+        });
+}
+
+#[tauri::command]
+pub fn get_recovery_journal(app: tauri::AppHandle) -> RecoveryJournal {
+    load(&app)
+}
+
+// value 为 null 时删除该键
+#[tauri::command]
+pub fn set_recovery_ui_state(
+    app: tauri::AppHandle,
+    key: String,
+    value: Option<serde_json::Value>,
+) -> Result<(), String> {
+    let key = key.trim().to_string();
+    if key.is_empty() {
+        return Err("key is empty".to_string());
+    }
+    let _guard = JOURNAL_LOCK.lock().unwrap();
+    let mut journal = load(&app);
+    match value.filter(|v| !v.is_null()) {
+        Some(value) => {
+            journal.ui_state.insert(key, value);
+        }
+        None => {
+            journal.ui_state.remove(&key);
+        }
+    }
+    save(&app, &journal)
+}
+
+// ids 为空时重放全部记录
+#[tauri::command]
+pub async fn replay_recovery_requests(
+    app: tauri::AppHandle,
+    ids: Option<Vec<String>>,
+) -> ReplayResult {
+    replay(&app, ids).await
+}
+
+#[tauri::command]
+pub fn discard_recovery_journal(app: tauri::AppHandle) -> Result<(), String> {
+    clear(&app)?;
+    let _ = app.emit("recovery-journal-changed", ());
+    Ok(())
+}
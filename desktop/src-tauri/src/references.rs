@@ -0,0 +1,339 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use sha2::{Digest, Sha256};
+use tauri::{Emitter, Manager};
+
+use crate::LogState;
+
+// 串行化 references/index.json 的读写
+static INDEX_LOCK: Mutex<()> = Mutex::new(());
+
+const INDEX_NAME: &str = "index.json";
+// 从链接下载参考图时的上限，避免误贴一个大文件链接把磁盘写满
+const MAX_DOWNLOAD_BYTES: usize = 50 * 1024 * 1024;
+
+// 参考图库条目：文件按内容哈希命名，同一张图重复加入只会合并标签
+#[derive(Clone, Default, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct Reference {
+    pub id: String,
+    pub hash: String,
+    pub path: String,
+    pub original_name: String,
+    // file / drop / clipboard / url / capture / feed
+    pub source: String,
+    pub source_url: String,
+    pub tags: Vec<String>,
+    pub width: u32,
+    pub height: u32,
+    pub size: u64,
+    pub added_at: u128,
+}
+
+#[derive(Default, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+struct ReferenceIndex {
+    references: Vec<Reference>,
+}
+
+#[derive(serde::Serialize)]
+pub struct ReferenceTag {
+    tag: String,
+    count: usize,
+}
+
+// 与 storage（生成结果）分开存放，参考图不会混进结果图库
+fn references_dir(app: &tauri::AppHandle) -> PathBuf {
+    crate::app_data_base(app).join("references")
+}
+
+fn index_path(app: &tauri::AppHandle) -> PathBuf {
+    references_dir(app).join(INDEX_NAME)
+}
+
+fn load(app: &tauri::AppHandle) -> ReferenceIndex {
+    crate::settings::read_json_or_default(&index_path(app))
+}
+
+fn save(app: &tauri::AppHandle, index: &ReferenceIndex) -> Result<(), String> {
+    crate::settings::write_json_atomic(&index_path(app), index)?;
+    let _ = app.emit("references-changed", ());
+    Ok(())
+}
+
+// 去空白、转小写并去重，搜索时按标签精确匹配
+fn normalize_tags(tags: &[String]) -> Vec<String> {
+    let mut out: Vec<String> = Vec::new();
+    for tag in tags {
+        let tag = tag.trim().to_lowercase();
+        if !tag.is_empty() && !out.contains(&tag) {
+            out.push(tag);
+        }
+    }
+    out
+}
+
+pub fn add_bytes(
+    app: &tauri::AppHandle,
+    bytes: &[u8],
+    original_name: &str,
+    source: &str,
+    source_url: &str,
+    tags: &[String],
+) -> Result<Reference, String> {
+    let reader = image::ImageReader::new(Cursor::new(bytes))
+        .with_guessed_format()
+        .map_err(|e| format!("read image failed: {}", e))?;
+    let format = reader
+        .format()
+        .ok_or_else(|| format!("unsupported image: {}", original_name))?;
+    // bmp 等格式没有启用解码器，尺寸记为 0，不影响入库
+    let (width, height) = reader.into_dimensions().unwrap_or((0, 0));
+    let ext = format.extensions_str().first().copied().unwrap_or("img");
+    let hash: String = Sha256::digest(bytes)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    let tags = normalize_tags(tags);
+
+    let _guard = INDEX_LOCK.lock().unwrap();
+    let mut index = load(app);
+    if let Some(existing) = index.references.iter_mut().find(|r| r.hash == hash) {
+        for tag in tags {
+            if !existing.tags.contains(&tag) {
+                existing.tags.push(tag);
+            }
+        }
+        if existing.source_url.is_empty() {
+            existing.source_url = source_url.to_string();
+        }
+        let existing = existing.clone();
+        save(app, &index)?;
+        return Ok(existing);
+    }
+
+    let dir = references_dir(app);
+    fs::create_dir_all(&dir).map_err(|e| format!("create references dir failed: {}", e))?;
+    crate::disk::ensure_free_space(&dir, bytes.len() as u64)?;
+    let id = hash[..16].to_string();
+    let dest = dir.join(format!("{}.{}", id, ext));
+    fs::write(&dest, bytes).map_err(|e| format!("write reference image failed: {}", e))?;
+    let reference = Reference {
+        id,
+        hash,
+        path: dest.to_string_lossy().to_string(),
+        original_name: original_name.to_string(),
+        source: source.to_string(),
+        source_url: source_url.to_string(),
+        tags,
+        width,
+        height,
+        size: bytes.len() as u64,
+        added_at: crate::now_ms(),
+    };
+    index.references.push(reference.clone());
+    save(app, &index)?;
+    Ok(reference)
+}
+
+pub fn find(app: &tauri::AppHandle, id: &str) -> Option<Reference> {
+    load(app).references.into_iter().find(|r| r.id == id)
+}
+
+fn add_file(
+    app: &tauri::AppHandle,
+    path: &Path,
+    source: &str,
+    tags: &[String],
+) -> Result<Reference, String> {
+    let bytes =
+        fs::read(path).map_err(|e| format!("read file failed: {} ({})", e, path.display()))?;
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    add_bytes(app, &bytes, &name, source, "", tags)
+}
+
+// 拖入、文件选择和截图保存后的文件都走这里；source 为空时记为 file
+#[tauri::command]
+pub async fn add_references(
+    app: tauri::AppHandle,
+    paths: Vec<String>,
+    tags: Vec<String>,
+    source: Option<String>,
+) -> Result<Vec<Reference>, String> {
+    let source = source
+        .filter(|s| !s.trim().is_empty())
+        .unwrap_or_else(|| "file".to_string());
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut added = Vec::new();
+        for raw in &paths {
+            let path = crate::resolve_local_path(&app, raw)?;
+            added.push(add_file(&app, &path, &source, &tags)?);
+        }
+        app.state::<LogState>().log_app(
+            "INFO",
+            &format!("Added {} reference images source={}", added.len(), source),
+        );
+        Ok(added)
+    })
+    .await
+    .map_err(|e| format!("add references failed: {}", e))?
+}
+
+// 下载链接内容，返回字节和从链接里取出的文件名；订阅源抓取也复用这里，source 用于流量统计
+pub async fn download(
+    app: &tauri::AppHandle,
+    source: &str,
+    url: &str,
+) -> Result<(Vec<u8>, String), String> {
+    if !url.starts_with("http://") && !url.starts_with("https://") {
+        return Err(format!("unsupported url: {}", url));
+    }
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(60))
+        .build()
+        .map_err(|e| format!("build download client failed: {}", e))?;
+    let response = client
+        .get(url)
+        .send()
+        .await
+        .map_err(|e| format!("download reference failed: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("download reference failed: {}", response.status()));
+    }
+    if response
+        .content_length()
+        .is_some_and(|len| len as usize > MAX_DOWNLOAD_BYTES)
+    {
+        return Err("reference image is too large".to_string());
+    }
+    let bytes = response
+        .bytes()
+        .await
+        .map_err(|e| format!("download reference failed: {}", e))?;
+    crate::bandwidth::record(app, source, 0, bytes.len() as u64);
+    if bytes.len() > MAX_DOWNLOAD_BYTES {
+        return Err("reference image is too large".to_string());
+    }
+    let name = url
+        .split(['?', '#'])
+        .next()
+        .and_then(|u| u.rsplit('/').next())
+        .unwrap_or_default()
+        .to_string();
+    Ok((bytes.to_vec(), name))
+}
+
+#[tauri::command]
+pub async fn add_reference_from_url(
+    app: tauri::AppHandle,
+    url: String,
+    tags: Vec<String>,
+) -> Result<Reference, String> {
+    let url = url.trim().to_string();
+    let (bytes, name) = download(&app, "reference", &url).await?;
+    tauri::async_runtime::spawn_blocking(move || add_bytes(&app, &bytes, &name, "url", &url, &tags))
+        .await
+        .map_err(|e| format!("add reference failed: {}", e))?
+}
+
+// 剪贴板里没有图片时返回 None
+#[tauri::command]
+pub async fn add_reference_from_clipboard(
+    app: tauri::AppHandle,
+    tags: Vec<String>,
+) -> Result<Option<Reference>, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let Some(buffer) = crate::clipboard_image(&app)? else {
+            return Ok(None);
+        };
+        let mut png = Vec::new();
+        buffer
+            .write_to(&mut Cursor::new(&mut png), image::ImageFormat::Png)
+            .map_err(|e| format!("encode clipboard image failed: {}", e))?;
+        add_bytes(&app, &png, "clipboard.png", "clipboard", "", &tags).map(Some)
+    })
+    .await
+    .map_err(|e| format!("add reference failed: {}", e))?
+}
+
+// query 匹配文件名、来源链接和标签；tags 需全部命中。结果按加入时间倒序
+#[tauri::command]
+pub fn search_references(
+    app: tauri::AppHandle,
+    query: Option<String>,
+    tags: Vec<String>,
+) -> Vec<Reference> {
+    let query = query.unwrap_or_default().trim().to_lowercase();
+    let tags = normalize_tags(&tags);
+    let mut found: Vec<Reference> = load(&app)
+        .references
+        .into_iter()
+        .filter(|r| tags.iter().all(|t| r.tags.contains(t)))
+        .filter(|r| {
+            query.is_empty()
+                || r.original_name.to_lowercase().contains(&query)
+                || r.source_url.to_lowercase().contains(&query)
+                || r.tags.iter().any(|t| t.contains(&query))
+        })
+        .collect();
+    found.sort_by_key(|r| std::cmp::Reverse(r.added_at));
+    found
+}
+
+#[tauri::command]
+pub fn list_reference_tags(app: tauri::AppHandle) -> Vec<ReferenceTag> {
+    let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+    for reference in load(&app).references {
+        for tag in reference.tags {
+            *counts.entry(tag).or_default() += 1;
+        }
+    }
+    counts
+        .into_iter()
+        .map(|(tag, count)| ReferenceTag { tag, count })
+        .collect()
+}
+
+#[tauri::command]
+pub fn set_reference_tags(
+    app: tauri::AppHandle,
+    id: String,
+    tags: Vec<String>,
+) -> Result<Reference, String> {
+    let _guard = INDEX_LOCK.lock().unwrap();
+    let mut index = load(&app);
+    let reference = index
+        .references
+        .iter_mut()
+        .find(|r| r.id == id)
+        .ok_or_else(|| format!("reference not found: {}", id))?;
+    reference.tags = normalize_tags(&tags);
+    let reference = reference.clone();
+    save(&app, &index)?;
+    Ok(reference)
+}
+
+#[tauri::command]
+pub fn remove_reference(app: tauri::AppHandle, id: String) -> Result<(), String> {
+    let _guard = INDEX_LOCK.lock().unwrap();
+    let mut index = load(&app);
+    let Some(pos) = index.references.iter().position(|r| r.id == id) else {
+        return Err(format!("reference not found: {}", id));
+    };
+    let removed = index.references.remove(pos);
+    save(&app, &index)?;
+    // 只删库目录内的文件，索引被手动改过也不会误删别处的图
+    let path = PathBuf::from(&removed.path);
+    if path.starts_with(references_dir(&app)) {
+        let _ = fs::remove_file(path);
+    }
+    Ok(())
+}
@@ -0,0 +1,210 @@
+use std::io::Write;
+use std::net::{Shutdown, TcpListener, TcpStream};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use tauri::{Emitter, Manager, State};
+
+use crate::settings::SettingsState;
+use crate::{BackendLifecycle, BackendStatusPayload, LogState};
+
+const HEALTH_TIMEOUT: Duration = Duration::from_secs(5);
+
+// 远程后端的 host:port；None 时转发端口拒绝所有连接
+static TARGET: Mutex<Option<(String, u16)>> = Mutex::new(None);
+// 本地转发端口，进程内只监听一次，切换地址时只改 TARGET
+static RELAY_PORT: Mutex<u16> = Mutex::new(0);
+
+#[derive(Clone, Default, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct BackendEndpointSettings {
+    // 如 http://nas.local:8080；为空表示使用内置 sidecar
+    pub url: String,
+}
+
+// 外部后端模式下的远程地址
+pub fn endpoint(app: &tauri::AppHandle) -> Option<String> {
+    let url = app
+        .try_state::<SettingsState>()?
+        .get()
+        .backend_endpoint
+        .url
+        .trim()
+        .to_string();
+    (!url.is_empty()).then_some(url)
+}
+
+pub const SIDECAR_DISABLED: &str = "using remote backend, sidecar is disabled";
+
+// 数据库 / storage 维护只作用于本机数据，外部后端模式下结果会和远程后端对不上，直接拒绝
+pub fn ensure_local(app: &tauri::AppHandle) -> Result<(), String> {
+    if endpoint(app).is_some() {
+        return Err("using remote backend, local data is not available".to_string());
+    }
+    Ok(())
+}
+
+// 壳层各处都按 127.0.0.1:<端口> 访问后端，这里只接受 http 且不带路径的地址，
+// 通过本地 TCP 转发接入，其余代码无需区分本地 / 远程
+fn parse(url: &str) -> Result<(String, u16), String> {
+    let parsed = reqwest::Url::parse(url.trim())
+        .map_err(|e| format!("invalid backend endpoint {}: {}", url, e))?;
+    if parsed.scheme() != "http" {
+        return Err("backend endpoint must use http://".to_string());
+    }
+    if !matches!(parsed.path(), "" | "/") || parsed.query().is_some() {
+        return Err("backend endpoint must not contain a path".to_string());
+    }
+    let host = parsed
+        .host_str()
+        .ok_or_else(|| format!("backend endpoint has no host: {}", url))?;
+    let port = parsed.port_or_known_default().unwrap_or(80);
+    Ok((host.trim_matches(['[', ']']).to_string(), port))
+}
+
+async fn check_health(url: &str) -> Result<(), String> {
+    let client = reqwest::Client::builder()
+        .timeout(HEALTH_TIMEOUT)
+        .build()
+        .map_err(|e| format!("build health client failed: {}", e))?;
+    let response = client
+        .get(format!(
+            "{}/api/v1/health",
+            url.trim().trim_end_matches('/')
+        ))
+        .send()
+        .await
+        .map_err(|e| format!("remote backend unreachable: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!(
+            "remote backend health check returned {}",
+            response.status()
+        ));
+    }
+    Ok(())
+}
+
+fn relay(mut client: TcpStream) {
+    let Some((host, port)) = TARGET.lock().unwrap().clone() else {
+        return;
+    };
+    let Ok(mut upstream) = TcpStream::connect((host.as_str(), port)) else {
+        return;
+    };
+    let (Ok(mut client_read), Ok(mut upstream_write)) = (client.try_clone(), upstream.try_clone())
+    else {
+        return;
+    };
+    std::thread::spawn(move || {
+        let _ = std::io::copy(&mut client_read, &mut upstream_write);
+        let _ = upstream_write.shutdown(Shutdown::Write);
+    });
+    let _ = std::io::copy(&mut upstream, &mut client);
+    let _ = client.flush();
+    let _ = client.shutdown(Shutdown::Write);
+}
+
+fn start_relay() -> Result<u16, String> {
+    let mut relay_port = RELAY_PORT.lock().unwrap();
+    if *relay_port != 0 {
+        return Ok(*relay_port);
+    }
+    let listener =
+        TcpListener::bind("127.0.0.1:0").map_err(|e| format!("bind relay failed: {}", e))?;
+    *relay_port = listener
+        .local_addr()
+        .map_err(|e| format!("read relay address failed: {}", e))?
+        .port();
+    std::thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            std::thread::spawn(move || relay(stream));
+        }
+    });
+    Ok(*relay_port)
+}
+
+fn connect(app: &tauri::AppHandle, url: &str) -> Result<(), String> {
+    *TARGET.lock().unwrap() = Some(parse(url)?);
+    let port = start_relay()?;
+    app.state::<LogState>().log_app(
+        "INFO",
+        &format!("Using remote backend {} via 127.0.0.1:{}", url, port),
+    );
+    crate::update_backend_status(app, |s| {
+        s.pid = None;
+        s.last_error = None;
+    });
+    crate::on_backend_port(app, port);
+    Ok(())
+}
+
+fn mark_unreachable(app: &tauri::AppHandle, err: String) {
+    app.state::<LogState>()
+        .log_app("ERROR", &format!("Remote backend not connected: {}", err));
+    crate::update_backend_status(app, |s| {
+        s.state = BackendLifecycle::Crashed;
+        s.pid = None;
+        s.last_error = Some(err);
+    });
+}
+
+// 启动时代替拉起 sidecar；远程暂时不可达时标记为 crashed，用户可在设置页重试
+pub fn start(app: &tauri::AppHandle, url: String) {
+    crate::update_backend_status(app, |s| s.state = BackendLifecycle::Starting);
+    let app = app.clone();
+    std::thread::spawn(move || {
+        let result =
+            tauri::async_runtime::block_on(check_health(&url)).and_then(|_| connect(&app, &url));
+        if let Err(err) = result {
+            mark_unreachable(&app, err);
+        }
+    });
+}
+
+#[tauri::command]
+pub fn get_backend_endpoint(state: State<'_, SettingsState>) -> BackendEndpointSettings {
+    state.get().backend_endpoint
+}
+
+// 传入地址时校验远程 /api/v1/health 后停掉本地 sidecar 并切换；传空字符串恢复内置 sidecar
+#[tauri::command]
+pub async fn set_backend_endpoint(
+    app: tauri::AppHandle,
+    url: String,
+) -> Result<BackendStatusPayload, String> {
+    if *app.state::<crate::GenerationState>().0.lock().unwrap() {
+        return Err("generation in progress".to_string());
+    }
+    let url = url.trim().trim_end_matches('/').to_string();
+    if !url.is_empty() {
+        parse(&url)?;
+        check_health(&url).await?;
+    }
+    app.state::<SettingsState>()
+        .update(|s| s.backend_endpoint.url = url.clone())?;
+    let app_for_task = app.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let app = app_for_task;
+        crate::stop_sidecar_gracefully(&app);
+        *TARGET.lock().unwrap() = None;
+        crate::readiness::reset();
+        let port_state = app.state::<crate::BackendPort>().0.clone();
+        if let Ok(mut p) = port_state.lock() {
+            *p = 0;
+        }
+        let _ = app.emit(
+            "sidecar-status",
+            crate::SidecarStatusPayload { running: false },
+        );
+        if url.is_empty() {
+            app.state::<LogState>()
+                .log_app("INFO", "Switching back to bundled sidecar");
+            crate::spawn_sidecar(&app, port_state)
+        } else {
+            connect(&app, &url)
+        }
+    })
+    .await
+    .map_err(|e| format!("switch backend failed: {}", e))??;
+    Ok(crate::backend_status(&app))
+}
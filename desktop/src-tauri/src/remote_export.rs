@@ -0,0 +1,662 @@
+use std::path::Path;
+use std::time::Duration;
+
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+use tauri::{Emitter, Manager, State};
+
+use crate::settings::SettingsState;
+use crate::LogState;
+
+// 钥匙串里的服务名，账号为目标 id；设置文件里不落任何密钥
+const KEYRING_SERVICE: &str = "com.dztool.banana.remote-export";
+// 小于该大小的文件直接 PUT，否则走 S3 分段上传（S3 要求除最后一段外每段至少 5MB）
+const PART_SIZE: usize = 8 * 1024 * 1024;
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(300);
+
+#[derive(Clone, Default, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct RemoteTargetSettings {
+    pub targets: Vec<RemoteTarget>,
+}
+
+#[derive(Clone, Default, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct RemoteTarget {
+    pub id: String,
+    pub name: String,
+    // 交付给哪个客户，导出对话框按客户分组展示
+    pub client: String,
+    // s3 / webdav / sftp
+    pub kind: String,
+    // S3：https://s3.<region>.amazonaws.com 或兼容服务地址；WebDAV：如 Nextcloud 的
+    // https://host/remote.php/dav/files/<user>；SFTP：sftp://host:22
+    pub endpoint: String,
+    // 仅 S3
+    pub bucket: String,
+    pub region: String,
+    // MinIO 等自建服务通常需要路径风格（endpoint/bucket/key）
+    pub path_style: bool,
+    // S3 的 Access Key ID / WebDAV、SFTP 用户名
+    pub username: String,
+    // 仅 SFTP：私钥文件路径，为空时用密码登录
+    pub key_path: String,
+    // 远端目录，如 banana/exports
+    pub prefix: String,
+}
+
+#[derive(Clone, serde::Serialize)]
+struct RemoteExportProgress {
+    target_id: String,
+    file: String,
+    index: usize,
+    total: usize,
+    bytes_sent: u64,
+    bytes_total: u64,
+}
+
+#[derive(serde::Serialize)]
+pub struct RemoteUpload {
+    path: String,
+    url: String,
+    bytes: u64,
+}
+
+#[derive(serde::Serialize)]
+pub struct RemoteFailure {
+    path: String,
+    reason: String,
+}
+
+#[derive(serde::Serialize)]
+pub struct RemoteExportResult {
+    uploaded: Vec<RemoteUpload>,
+    failed: Vec<RemoteFailure>,
+}
+
+fn validate(target: &RemoteTarget) -> Result<(), String> {
+    if target.id.trim().is_empty() {
+        return Err("target id is empty".to_string());
+    }
+    let url = reqwest::Url::parse(target.endpoint.trim())
+        .map_err(|e| format!("invalid endpoint {}: {}", target.endpoint, e))?;
+    let scheme_ok = match target.kind.as_str() {
+        "sftp" => url.scheme() == "sftp",
+        _ => matches!(url.scheme(), "http" | "https"),
+    };
+    if !scheme_ok || url.host_str().is_none() {
+        return Err(format!("invalid endpoint: {}", target.endpoint));
+    }
+    match target.kind.as_str() {
+        "s3" if target.bucket.trim().is_empty() => {
+            Err(format!("target {} has no bucket", target.name))
+        }
+        "s3" | "webdav" | "sftp" => Ok(()),
+        other => Err(format!("unknown target kind: {}", other)),
+    }
+}
+
+fn keyring_entry(target_id: &str) -> Result<keyring::Entry, String> {
+    keyring::Entry::new(KEYRING_SERVICE, target_id)
+        .map_err(|e| format!("open keychain failed: {}", e))
+}
+
+fn load_secret(target_id: &str) -> Result<String, String> {
+    keyring_entry(target_id)?
+        .get_password()
+        .map_err(|e| format!("read credentials for {} failed: {}", target_id, e))
+}
+
+fn find_target(app: &tauri::AppHandle, target_id: &str) -> Result<RemoteTarget, String> {
+    app.state::<SettingsState>()
+        .get()
+        .remote_targets
+        .targets
+        .into_iter()
+        .find(|t| t.id == target_id)
+        .ok_or_else(|| format!("export target not found: {}", target_id))
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    hex(&Sha256::digest(bytes))
+}
+
+fn hmac_sha256(key: &[u8], data: &str) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("hmac accepts any key length");
+    mac.update(data.as_bytes());
+    mac.finalize().into_bytes().to_vec()
+}
+
+// RFC 3986 编码；对象路径保留 /
+fn uri_encode(value: &str, keep_slash: bool) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (b as char).to_string()
+            }
+            b'/' if keep_slash => "/".to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+fn remote_key(target: &RemoteTarget, file_name: &str) -> String {
+    let prefix = target.prefix.trim().trim_matches('/');
+    if prefix.is_empty() {
+        file_name.to_string()
+    } else {
+        format!("{}/{}", prefix, file_name)
+    }
+}
+
+fn content_type(path: &Path) -> &'static str {
+    match path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_ascii_lowercase())
+        .as_deref()
+    {
+        Some("png") => "image/png",
+        Some("jpg" | "jpeg") => "image/jpeg",
+        Some("webp") => "image/webp",
+        Some("gif") => "image/gif",
+        _ => "application/octet-stream",
+    }
+}
+
+// 返回 (完整 URL, Host, 规范化路径)
+fn s3_object_url(target: &RemoteTarget, key: &str) -> Result<(String, String, String), String> {
+    let endpoint = reqwest::Url::parse(target.endpoint.trim())
+        .map_err(|e| format!("invalid endpoint: {}", e))?;
+    let host = endpoint.host_str().unwrap_or_default();
+    let host = match endpoint.port() {
+        Some(port) => format!("{}:{}", host, port),
+        None => host.to_string(),
+    };
+    let bucket = target.bucket.trim();
+    let (host, path) = if target.path_style {
+        (host, format!("/{}/{}", bucket, uri_encode(key, true)))
+    } else {
+        (
+            format!("{}.{}", bucket, host),
+            format!("/{}", uri_encode(key, true)),
+        )
+    };
+    Ok((
+        format!("{}://{}{}", endpoint.scheme(), host, path),
+        host,
+        path,
+    ))
+}
+
+struct S3Client<'a> {
+    client: reqwest::Client,
+    target: &'a RemoteTarget,
+    secret: String,
+}
+
+impl S3Client<'_> {
+    fn region(&self) -> &str {
+        match self.target.region.trim() {
+            "" => "us-east-1",
+            region => region,
+        }
+    }
+
+    // AWS Signature Version 4，签名 host / x-amz-content-sha256 / x-amz-date 三个头
+    async fn send(
+        &self,
+        method: reqwest::Method,
+        key: &str,
+        query: &[(&str, String)],
+        body: Vec<u8>,
+        content_type: Option<&str>,
+    ) -> Result<reqwest::Response, String> {
+        let (url, host, path) = s3_object_url(self.target, key)?;
+        let now = chrono::Utc::now();
+        let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+        let date = now.format("%Y%m%d").to_string();
+        let payload_hash = sha256_hex(&body);
+
+        let mut pairs: Vec<(String, String)> = query
+            .iter()
+            .map(|(k, v)| (uri_encode(k, false), uri_encode(v, false)))
+            .collect();
+        pairs.sort();
+        let canonical_query = pairs
+            .iter()
+            .map(|(k, v)| format!("{}={}", k, v))
+            .collect::<Vec<_>>()
+            .join("&");
+        let signed_headers = "host;x-amz-content-sha256;x-amz-date";
+        let canonical_request = format!(
+            "{}\n{}\n{}\nhost:{}\nx-amz-content-sha256:{}\nx-amz-date:{}\n\n{}\n{}",
+            method,
+            path,
+            canonical_query,
+            host,
+            payload_hash,
+            amz_date,
+            signed_headers,
+            payload_hash
+        );
+        let scope = format!("{}/{}/s3/aws4_request", date, self.region());
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            amz_date,
+            scope,
+            sha256_hex(canonical_request.as_bytes())
+        );
+        let signing_key = ["s3", "aws4_request"].iter().fold(
+            hmac_sha256(
+                &hmac_sha256(format!("AWS4{}", self.secret).as_bytes(), &date),
+                self.region(),
+            ),
+            |key, part| hmac_sha256(&key, part),
+        );
+        let signature = hex(&hmac_sha256(&signing_key, &string_to_sign));
+        let authorization = format!(
+            "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+            self.target.username.trim(),
+            scope,
+            signed_headers,
+            signature
+        );
+
+        let url = if canonical_query.is_empty() {
+            url
+        } else {
+            format!("{}?{}", url, canonical_query)
+        };
+        let mut request = self
+            .client
+            .request(method, url)
+            .header("x-amz-content-sha256", payload_hash)
+            .header("x-amz-date", amz_date)
+            .header(reqwest::header::AUTHORIZATION, authorization)
+            .body(body);
+        if let Some(content_type) = content_type {
+            request = request.header(reqwest::header::CONTENT_TYPE, content_type);
+        }
+        let response = request
+            .send()
+            .await
+            .map_err(|e| format!("s3 request failed: {}", e))?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await.unwrap_or_default();
+            return Err(format!(
+                "s3 returned {}: {}",
+                status,
+                xml_value(&text, "Message").unwrap_or_else(|| text
+                    .trim()
+                    .chars()
+                    .take(200)
+                    .collect())
+            ));
+        }
+        Ok(response)
+    }
+
+    async fn upload(
+        &self,
+        key: &str,
+        bytes: Vec<u8>,
+        content_type: &str,
+        progress: impl Fn(u64),
+    ) -> Result<(), String> {
+        if bytes.len() <= PART_SIZE {
+            let len = bytes.len() as u64;
+            self.send(reqwest::Method::PUT, key, &[], bytes, Some(content_type))
+                .await?;
+            progress(len);
+            return Ok(());
+        }
+        let created = self
+            .send(
+                reqwest::Method::POST,
+                key,
+                &[("uploads", String::new())],
+                Vec::new(),
+                Some(content_type),
+            )
+            .await?
+            .text()
+            .await
+            .map_err(|e| format!("read s3 response failed: {}", e))?;
+        let upload_id = xml_value(&created, "UploadId")
+            .ok_or_else(|| "s3 did not return an upload id".to_string())?;
+        match self.upload_parts(key, &upload_id, &bytes, &progress).await {
+            Ok(()) => Ok(()),
+            Err(err) => {
+                // 中途失败要中止，否则已上传的分段会一直占用存储并计费
+                let _ = self
+                    .send(
+                        reqwest::Method::DELETE,
+                        key,
+                        &[("uploadId", upload_id)],
+                        Vec::new(),
+                        None,
+                    )
+                    .await;
+                Err(err)
+            }
+        }
+    }
+
+    async fn upload_parts(
+        &self,
+        key: &str,
+        upload_id: &str,
+        bytes: &[u8],
+        progress: &impl Fn(u64),
+    ) -> Result<(), String> {
+        let mut etags = Vec::new();
+        let mut sent = 0u64;
+        for (index, chunk) in bytes.chunks(PART_SIZE).enumerate() {
+            let number = index + 1;
+            let response = self
+                .send(
+                    reqwest::Method::PUT,
+                    key,
+                    &[
+                        ("partNumber", number.to_string()),
+                        ("uploadId", upload_id.to_string()),
+                    ],
+                    chunk.to_vec(),
+                    None,
+                )
+                .await?;
+            let etag = response
+                .headers()
+                .get(reqwest::header::ETAG)
+                .and_then(|v| v.to_str().ok())
+                .ok_or_else(|| format!("s3 part {} returned no etag", number))?
+                .to_string();
+            etags.push(format!(
+                "<Part><PartNumber>{}</PartNumber><ETag>{}</ETag></Part>",
+                number, etag
+            ));
+            sent += chunk.len() as u64;
+            progress(sent);
+        }
+        let body = format!(
+            "<CompleteMultipartUpload>{}</CompleteMultipartUpload>",
+            etags.join("")
+        );
+        let response = self
+            .send(
+                reqwest::Method::POST,
+                key,
+                &[("uploadId", upload_id.to_string())],
+                body.into_bytes(),
+                Some("application/xml"),
+            )
+            .await?
+            .text()
+            .await
+            .map_err(|e| format!("read s3 response failed: {}", e))?;
+        // 合并失败时 S3 仍返回 200，错误写在响应体里
+        if response.contains("<Error>") {
+            return Err(format!(
+                "complete multipart upload failed: {}",
+                xml_value(&response, "Message").unwrap_or(response)
+            ));
+        }
+        Ok(())
+    }
+}
+
+fn xml_value(xml: &str, tag: &str) -> Option<String> {
+    let start = xml.find(&format!("<{}>", tag))? + tag.len() + 2;
+    let end = xml[start..].find(&format!("</{}>", tag))? + start;
+    Some(xml[start..end].to_string())
+}
+
+fn webdav_url(target: &RemoteTarget, key: &str) -> String {
+    format!(
+        "{}/{}",
+        target.endpoint.trim().trim_end_matches('/'),
+        uri_encode(key, true)
+    )
+}
+
+// 逐级创建远端目录；已存在时服务器返回 405，忽略即可
+async fn webdav_mkdirs(
+    client: &reqwest::Client,
+    target: &RemoteTarget,
+    secret: &str,
+) -> Result<(), String> {
+    let prefix = target.prefix.trim().trim_matches('/');
+    let mut current = String::new();
+    for segment in prefix.split('/').filter(|s| !s.is_empty()) {
+        if !current.is_empty() {
+            current.push('/');
+        }
+        current.push_str(segment);
+        let response = client
+            .request(
+                reqwest::Method::from_bytes(b"MKCOL").expect("valid method"),
+                format!("{}/", webdav_url(target, &current)),
+            )
+            .basic_auth(target.username.trim(), Some(secret))
+            .send()
+            .await
+            .map_err(|e| format!("webdav mkcol failed: {}", e))?;
+        let status = response.status().as_u16();
+        if !(200..300).contains(&status) && status != 405 {
+            return Err(format!("webdav mkcol {} returned {}", current, status));
+        }
+    }
+    Ok(())
+}
+
+async fn webdav_upload(
+    client: &reqwest::Client,
+    target: &RemoteTarget,
+    secret: &str,
+    key: &str,
+    bytes: Vec<u8>,
+    content_type: &str,
+) -> Result<(), String> {
+    let response = client
+        .put(webdav_url(target, key))
+        .basic_auth(target.username.trim(), Some(secret))
+        .header(reqwest::header::CONTENT_TYPE, content_type)
+        .body(bytes)
+        .send()
+        .await
+        .map_err(|e| format!("webdav upload failed: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("webdav returned {}", response.status()));
+    }
+    Ok(())
+}
+
+fn public_url(target: &RemoteTarget, key: &str) -> String {
+    match target.kind.as_str() {
+        "s3" => s3_object_url(target, key)
+            .map(|(url, _, _)| url)
+            .unwrap_or_default(),
+        "sftp" => format!(
+            "{}{}{}",
+            target.endpoint.trim().trim_end_matches('/'),
+            if target.prefix.trim().starts_with('/') {
+                "/"
+            } else {
+                "/~/"
+            },
+            key
+        ),
+        _ => webdav_url(target, key),
+    }
+}
+
+#[tauri::command]
+pub fn get_remote_targets(state: State<'_, SettingsState>) -> RemoteTargetSettings {
+    state.get().remote_targets
+}
+
+#[tauri::command]
+pub fn set_remote_targets(
+    state: State<'_, SettingsState>,
+    settings: RemoteTargetSettings,
+) -> Result<RemoteTargetSettings, String> {
+    for target in &settings.targets {
+        validate(target)?;
+    }
+    Ok(state
+        .update(|s| s.remote_targets = settings)?
+        .remote_targets)
+}
+
+// 密钥（S3 Secret Access Key / WebDAV 密码或应用专用密码）只写入系统钥匙串
+#[tauri::command]
+pub fn set_remote_target_secret(target_id: String, secret: String) -> Result<(), String> {
+    let entry = keyring_entry(target_id.trim())?;
+    if secret.is_empty() {
+        return match entry.delete_credential() {
+            Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+            Err(e) => Err(format!("delete credentials failed: {}", e)),
+        };
+    }
+    entry
+        .set_password(&secret)
+        .map_err(|e| format!("save credentials failed: {}", e))
+}
+
+#[tauri::command]
+pub fn has_remote_target_secret(target_id: String) -> Result<bool, String> {
+    match keyring_entry(target_id.trim())?.get_password() {
+        Ok(_) => Ok(true),
+        Err(keyring::Error::NoEntry) => Ok(false),
+        Err(e) => Err(format!("read credentials failed: {}", e)),
+    }
+}
+
+// 把选中的图片逐个上传到目标；单个文件失败不影响其他文件，进度通过 remote-export-progress 推送
+#[tauri::command]
+pub async fn export_to_remote(
+    app: tauri::AppHandle,
+    target_id: String,
+    paths: Vec<String>,
+) -> Result<RemoteExportResult, String> {
+    let target = find_target(&app, target_id.trim())?;
+    validate(&target)?;
+    // 使用无口令私钥的 SFTP 目标可以不存密钥
+    let secret = match load_secret(&target.id) {
+        Ok(secret) => secret,
+        Err(_) if target.kind == "sftp" && !target.key_path.trim().is_empty() => String::new(),
+        Err(err) => return Err(err),
+    };
+    let client = reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .map_err(|e| format!("build export client failed: {}", e))?;
+    if target.kind == "webdav" {
+        webdav_mkdirs(&client, &target, &secret).await?;
+    }
+    let s3 = S3Client {
+        client: client.clone(),
+        target: &target,
+        secret: secret.clone(),
+    };
+
+    let total = paths.len();
+    let mut result = RemoteExportResult {
+        uploaded: Vec::new(),
+        failed: Vec::new(),
+    };
+    for (index, raw) in paths.iter().enumerate() {
+        let upload = async {
+            let path = crate::resolve_local_path(&app, raw)?;
+            let file_name = path
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .ok_or_else(|| format!("invalid file path: {}", raw))?;
+            let bytes_total = std::fs::metadata(&path)
+                .map_err(|e| format!("read image failed: {}", e))?
+                .len();
+            let key = remote_key(&target, &file_name);
+            let mime = content_type(&path);
+            let progress = {
+                let app = app.clone();
+                let target_id = target.id.clone();
+                let file_name = file_name.clone();
+                move |bytes_sent: u64| {
+                    let _ = app.emit(
+                        "remote-export-progress",
+                        RemoteExportProgress {
+                            target_id: target_id.clone(),
+                            file: file_name.clone(),
+                            index,
+                            total,
+                            bytes_sent,
+                            bytes_total,
+                        },
+                    );
+                }
+            };
+            progress(0);
+            match target.kind.as_str() {
+                "s3" => {
+                    let bytes = read_file(&path).await?;
+                    s3.upload(&key, bytes, mime, progress).await?;
+                }
+                // SFTP 走阻塞的 libssh2，放到后台线程；断线重连后从 .part 续传
+                "sftp" => {
+                    let (target, secret) = (target.clone(), secret.clone());
+                    let remote = crate::sftp::remote_path(&target, &key);
+                    tauri::async_runtime::spawn_blocking(move || {
+                        crate::sftp::upload(&target, &secret, &path, &remote, progress)
+                    })
+                    .await
+                    .map_err(|e| format!("sftp task failed: {}", e))??;
+                }
+                _ => {
+                    let bytes = read_file(&path).await?;
+                    webdav_upload(&client, &target, &secret, &key, bytes, mime).await?;
+                    progress(bytes_total);
+                }
+            }
+            Ok::<_, String>(RemoteUpload {
+                path: raw.clone(),
+                url: public_url(&target, &key),
+                bytes: bytes_total,
+            })
+        };
+        match upload.await {
+            Ok(uploaded) => result.uploaded.push(uploaded),
+            Err(reason) => result.failed.push(RemoteFailure {
+                path: raw.clone(),
+                reason,
+            }),
+        }
+    }
+
+    app.state::<LogState>().log_app(
+        "INFO",
+        &format!(
+            "Remote export finished target={} kind={} uploaded={} failed={}",
+            target.name,
+            target.kind,
+            result.uploaded.len(),
+            result.failed.len()
+        ),
+    );
+    Ok(result)
+}
+
+// 读文件放到阻塞线程，大图不占用异步运行时
+async fn read_file(path: &Path) -> Result<Vec<u8>, String> {
+    let path = path.to_path_buf();
+    tauri::async_runtime::spawn_blocking(move || std::fs::read(&path))
+        .await
+        .map_err(|e| format!("read task failed: {}", e))?
+        .map_err(|e| format!("read image failed: {}", e))
+}
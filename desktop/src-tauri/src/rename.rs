@@ -0,0 +1,536 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use tauri::Manager;
+
+use crate::{BackendPort, GenerationState, LogState};
+
+const SLUG_MAX_CHARS: usize = 40;
+
+#[derive(serde::Deserialize)]
+pub struct RenameTarget {
+    // 只给 task_id 时重命名存储目录里的原图；同时给 path 时只借用任务信息填充模板
+    task_id: Option<String>,
+    path: Option<String>,
+}
+
+#[derive(Clone, serde::Serialize)]
+pub struct RenamePlanItem {
+    task_id: Option<String>,
+    from: String,
+    to: String,
+    // 目标已被其他文件占用时给出原因，存在冲突时不会执行
+    conflict: Option<String>,
+}
+
+#[derive(serde::Serialize)]
+pub struct RenameResult {
+    dry_run: bool,
+    renamed: usize,
+    items: Vec<RenamePlanItem>,
+}
+
+struct TaskInfo {
+    local_path: String,
+    prompt: String,
+    model_id: String,
+    created_at: String,
+    config_snapshot: String,
+}
+
+enum Token {
+    Text(String),
+    Date,
+    Time,
+    Prompt,
+    Seed,
+    Model,
+    Id,
+    Name,
+    Counter(Option<usize>),
+}
+
+struct Planned {
+    task_id: Option<String>,
+    from: PathBuf,
+    to: PathBuf,
+    // 存储目录里的原图改名后要同步数据库路径
+    tracked: bool,
+    conflict: Option<String>,
+}
+
+// 支持 {date} {time} {prompt} {seed} {model} {id} {name} {counter} {counter:3}，其余原样保留
+fn parse_template(template: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut text = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        text.push_str(&rest[..start]);
+        let end = rest[start..]
+            .find('}')
+            .map(|i| start + i)
+            .ok_or_else(|| "unclosed { in template".to_string())?;
+        let (name, arg) = match rest[start + 1..end].split_once(':') {
+            Some((name, arg)) => (name.trim(), Some(arg.trim())),
+            None => (rest[start + 1..end].trim(), None),
+        };
+        let token = match name {
+            "date" => Token::Date,
+            "time" => Token::Time,
+            "prompt" => Token::Prompt,
+            "seed" => Token::Seed,
+            "model" => Token::Model,
+            "id" => Token::Id,
+            "name" => Token::Name,
+            "counter" => Token::Counter(
+                arg.map(|a| {
+                    a.parse::<usize>()
+                        .ok()
+                        .filter(|w| *w <= 9)
+                        .ok_or_else(|| format!("invalid counter width: {}", a))
+                })
+                .transpose()?,
+            ),
+            other => return Err(format!("unknown template token: {{{}}}", other)),
+        };
+        if !text.is_empty() {
+            tokens.push(Token::Text(std::mem::take(&mut text)));
+        }
+        tokens.push(token);
+        rest = &rest[end + 1..];
+    }
+    text.push_str(rest);
+    if !text.is_empty() {
+        tokens.push(Token::Text(text));
+    }
+    Ok(tokens)
+}
+
+// 提示词转成文件名片段：保留字母数字（含中文），其余折叠成 -
+fn slugify(text: &str) -> String {
+    let mut slug = String::new();
+    for c in text.chars().flat_map(|c| c.to_lowercase()) {
+        if c.is_alphanumeric() {
+            slug.push(c);
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+        if slug.chars().count() >= SLUG_MAX_CHARS {
+            break;
+        }
+    }
+    slug.trim_end_matches('-').to_string()
+}
+
+// 模板里的固定文本也可能带路径分隔符或 Windows 保留字符
+fn sanitize_file_stem(stem: &str) -> String {
+    stem.chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect::<String>()
+        .trim_matches(|c| c == '.' || c == ' ')
+        .to_string()
+}
+
+fn seed_of(snapshot: &str) -> String {
+    let Ok(value) = serde_json::from_str::<serde_json::Value>(snapshot) else {
+        return String::new();
+    };
+    match value.get("seed") {
+        Some(serde_json::Value::Number(n)) => n.to_string(),
+        Some(serde_json::Value::String(s)) => s.trim().to_string(),
+        _ => String::new(),
+    }
+}
+
+// gorm 写入的是 "2025-03-01 12:34:56.789+08:00"；没有任务信息时用文件修改时间
+fn timestamp_parts(task: Option<&TaskInfo>, path: &Path) -> (String, String) {
+    if let Some(task) = task {
+        let digits: String = task
+            .created_at
+            .chars()
+            .filter(|c| c.is_ascii_digit())
+            .take(14)
+            .collect();
+        if digits.len() == 14 {
+            return (digits[..8].to_string(), digits[8..].to_string());
+        }
+    }
+    let modified = fs::metadata(path)
+        .and_then(|m| m.modified())
+        .map(chrono::DateTime::<chrono::Local>::from)
+        .unwrap_or_else(|_| chrono::Local::now());
+    (
+        modified.format("%Y%m%d").to_string(),
+        modified.format("%H%M%S").to_string(),
+    )
+}
+
+fn render(
+    tokens: &[Token],
+    task_id: Option<&str>,
+    task: Option<&TaskInfo>,
+    path: &Path,
+    counter: usize,
+    default_width: usize,
+) -> String {
+    let (date, time) = timestamp_parts(task, path);
+    let mut out = String::new();
+    for token in tokens {
+        match token {
+            Token::Text(text) => out.push_str(text),
+            Token::Date => out.push_str(&date),
+            Token::Time => out.push_str(&time),
+            Token::Prompt => out.push_str(&slugify(task.map_or("", |t| t.prompt.as_str()))),
+            Token::Seed => out.push_str(
+                &task
+                    .map(|t| seed_of(&t.config_snapshot))
+                    .unwrap_or_default(),
+            ),
+            Token::Model => out.push_str(&slugify(task.map_or("", |t| t.model_id.as_str()))),
+            Token::Id => out.push_str(&task_id.unwrap_or("").chars().take(8).collect::<String>()),
+            Token::Name => out.push_str(
+                &path
+                    .file_stem()
+                    .map(|s| s.to_string_lossy().to_string())
+                    .unwrap_or_default(),
+            ),
+            Token::Counter(width) => out.push_str(&format!(
+                "{:0width$}",
+                counter,
+                width = width.unwrap_or(default_width)
+            )),
+        }
+    }
+    // 空字段留下的连续分隔符收一收
+    let mut stem = sanitize_file_stem(&out);
+    for sep in ["--", "__", "  "] {
+        while stem.contains(sep) {
+            stem = stem.replace(sep, &sep[..1]);
+        }
+    }
+    stem.trim_matches(|c| c == '-' || c == '_' || c == ' ')
+        .to_string()
+}
+
+fn load_tasks(
+    app: &tauri::AppHandle,
+    task_ids: &[String],
+) -> Result<HashMap<String, TaskInfo>, String> {
+    let mut tasks = HashMap::new();
+    if task_ids.is_empty() {
+        return Ok(tasks);
+    }
+    let conn = crate::db::open_readonly(app)?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT local_path, prompt, model_id, created_at, config_snapshot FROM tasks \
+             WHERE task_id = ?1 AND deleted_at IS NULL",
+        )
+        .map_err(|e| format!("query tasks failed: {}", e))?;
+    for task_id in task_ids {
+        let row = stmt.query_row([task_id], |row| {
+            Ok(TaskInfo {
+                local_path: row.get::<_, Option<String>>(0)?.unwrap_or_default(),
+                prompt: row.get::<_, Option<String>>(1)?.unwrap_or_default(),
+                model_id: row.get::<_, Option<String>>(2)?.unwrap_or_default(),
+                created_at: row.get::<_, Option<String>>(3)?.unwrap_or_default(),
+                config_snapshot: row.get::<_, Option<String>>(4)?.unwrap_or_default(),
+            })
+        });
+        match row {
+            Ok(info) => {
+                tasks.insert(task_id.clone(), info);
+            }
+            Err(rusqlite::Error::QueryReturnedNoRows) => {
+                return Err(format!("task not found: {}", task_id))
+            }
+            Err(e) => return Err(format!("query tasks failed: {}", e)),
+        }
+    }
+    Ok(tasks)
+}
+
+fn plan(
+    app: &tauri::AppHandle,
+    targets: &[RenameTarget],
+    template: &str,
+    start: usize,
+) -> Result<Vec<Planned>, String> {
+    let tokens = parse_template(template)?;
+    let task_ids: Vec<String> = targets
+        .iter()
+        .filter_map(|t| t.task_id.as_deref())
+        .map(|id| id.trim().to_string())
+        .filter(|id| !id.is_empty())
+        .collect();
+    let tasks = load_tasks(app, &task_ids)?;
+    let base = crate::app_data_base(app);
+    let storage = crate::paths::storage_dir(app);
+    let storage = storage.canonicalize().unwrap_or(storage);
+    let default_width = (start + targets.len()).to_string().len().max(2);
+
+    let mut planned = Vec::new();
+    let mut sources = HashSet::new();
+    for (index, target) in targets.iter().enumerate() {
+        let task_id = target
+            .task_id
+            .as_deref()
+            .map(str::trim)
+            .filter(|id| !id.is_empty());
+        let task = task_id.and_then(|id| tasks.get(id));
+        let raw = match (target.path.as_deref().map(str::trim), task) {
+            (Some(path), _) if !path.is_empty() => crate::resolve_local_path(app, path)?,
+            (_, Some(task)) => crate::storage::resolve_db_path(&base, &task.local_path)
+                .ok_or_else(|| format!("task has no local file: {}", task_id.unwrap_or("")))?,
+            _ => return Err("each target needs a task_id or path".to_string()),
+        };
+        let from = raw
+            .canonicalize()
+            .map_err(|e| format!("{}: {}", raw.display(), e))?;
+        if !from.is_file() {
+            return Err(format!("not a file: {}", from.display()));
+        }
+        if !sources.insert(from.clone()) {
+            return Err(format!("duplicate target: {}", from.display()));
+        }
+        // 数据库里记录的就是这个文件时才需要同步路径
+        let tracked = task.is_some_and(|t| {
+            crate::storage::resolve_db_path(&base, &t.local_path)
+                .and_then(|p| p.canonicalize().ok())
+                .is_some_and(|p| p == from)
+        }) && from.starts_with(&storage);
+
+        let stem = render(&tokens, task_id, task, &from, start + index, default_width);
+        if stem.is_empty() {
+            return Err(format!(
+                "template produced an empty name for {}",
+                from.display()
+            ));
+        }
+        let ext = from
+            .extension()
+            .map(|e| format!(".{}", e.to_string_lossy()))
+            .unwrap_or_default();
+        let dir = from.parent().map(Path::to_path_buf).unwrap_or_default();
+        planned.push(Planned {
+            task_id: task_id.map(str::to_string),
+            to: dir.join(format!("{}{}", stem, ext)),
+            from,
+            tracked,
+            conflict: None,
+        });
+    }
+
+    // 同一批里重名的自动加序号；磁盘上已有且不属于本批的文件记为冲突
+    let mut taken: HashSet<PathBuf> = HashSet::new();
+    for item in planned.iter_mut() {
+        let mut candidate = item.to.clone();
+        let mut n = 2;
+        while taken.contains(&candidate) {
+            let stem = item
+                .to
+                .file_stem()
+                .map(|s| s.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let ext = item
+                .to
+                .extension()
+                .map(|e| format!(".{}", e.to_string_lossy()))
+                .unwrap_or_default();
+            candidate = item.to.with_file_name(format!("{}-{}{}", stem, n, ext));
+            n += 1;
+        }
+        taken.insert(candidate.clone());
+        item.to = candidate;
+        if item.to != item.from && item.to.exists() && !sources.contains(&item.to) {
+            // 大小写不敏感的文件系统上只改大小写时 exists() 指向的是自己
+            let same_file = item.to.canonicalize().is_ok_and(|p| p == item.from);
+            if !same_file {
+                item.conflict = Some("target already exists".to_string());
+            }
+        }
+    }
+    Ok(planned)
+}
+
+// 先全部改成临时名再改成目标名，批内互换名字（a->b、b->a）也不会互相覆盖
+// 任何一步失败都按已完成的步骤逆序改回
+fn execute(items: &[&Planned]) -> Result<(), String> {
+    let mut done: Vec<(PathBuf, PathBuf)> = Vec::new();
+    let rollback = |done: &[(PathBuf, PathBuf)]| {
+        for (from, to) in done.iter().rev() {
+            let _ = fs::rename(to, from);
+        }
+    };
+    let mut staged = Vec::new();
+    for (i, item) in items.iter().enumerate() {
+        let file_name = item
+            .from
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let temp = item
+            .from
+            .with_file_name(format!(".{}.renaming-{}", file_name, i));
+        if let Err(e) = fs::rename(&item.from, &temp) {
+            rollback(&done);
+            return Err(format!("rename failed: {} ({})", e, item.from.display()));
+        }
+        done.push((item.from.clone(), temp.clone()));
+        staged.push(temp);
+    }
+    for (item, temp) in items.iter().zip(&staged) {
+        if item.to.exists() {
+            rollback(&done);
+            return Err(format!("target already exists: {}", item.to.display()));
+        }
+        if let Err(e) = fs::rename(temp, &item.to) {
+            rollback(&done);
+            return Err(format!("rename failed: {} ({})", e, item.to.display()));
+        }
+        done.push((temp.clone(), item.to.clone()));
+    }
+    Ok(())
+}
+
+fn undo(items: &[&Planned]) {
+    for item in items.iter().rev() {
+        let _ = fs::rename(&item.to, &item.from);
+    }
+}
+
+async fn sync_paths(app: &tauri::AppHandle, port: u16, items: &[&Planned]) -> Result<(), String> {
+    let base = crate::app_data_base(app);
+    let base = base.canonicalize().unwrap_or(base);
+    // 只改原图路径，thumbnail_path 为空时后端保持原值
+    let items: Vec<serde_json::Value> = items
+        .iter()
+        .map(|item| {
+            serde_json::json!({
+                "task_id": item.task_id,
+                "local_path": item.to.strip_prefix(&base).unwrap_or(&item.to).to_string_lossy(),
+            })
+        })
+        .collect();
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(120))
+        .build()
+        .map_err(|e| format!("build relocate client failed: {}", e))?;
+    let response = client
+        .post(format!("http://127.0.0.1:{}/api/v1/images/relocate", port))
+        .header(reqwest::header::ORIGIN, "tauri://localhost")
+        .json(&serde_json::json!({ "items": items }))
+        .send()
+        .await
+        .map_err(|e| format!("relocate request failed: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("relocate request failed: {}", response.status()));
+    }
+    Ok(())
+}
+
+fn to_item(planned: &Planned) -> RenamePlanItem {
+    RenamePlanItem {
+        task_id: planned.task_id.clone(),
+        from: planned.from.to_string_lossy().to_string(),
+        to: planned.to.to_string_lossy().to_string(),
+        conflict: planned.conflict.clone(),
+    }
+}
+
+// 按模板批量重命名导出文件或存储目录里的原图，dry_run（默认 true）只返回预览
+// 执行时先改磁盘文件名，涉及存储原图的再通过后端一次事务更新路径；任一步失败全部改回
+#[tauri::command]
+pub async fn rename_files(
+    app: tauri::AppHandle,
+    targets: Vec<RenameTarget>,
+    template: String,
+    start: Option<usize>,
+    dry_run: Option<bool>,
+) -> Result<RenameResult, String> {
+    if targets.is_empty() {
+        return Err("no files to rename".to_string());
+    }
+    let dry_run = dry_run.unwrap_or(true);
+    let start = start.unwrap_or(1);
+    let app_for_task = app.clone();
+    let planned = tauri::async_runtime::spawn_blocking(move || {
+        plan(&app_for_task, &targets, &template, start)
+    })
+    .await
+    .map_err(|e| format!("plan rename failed: {}", e))??;
+    let items: Vec<RenamePlanItem> = planned.iter().map(to_item).collect();
+    if dry_run {
+        return Ok(RenameResult {
+            dry_run,
+            renamed: 0,
+            items,
+        });
+    }
+    if let Some(item) = planned.iter().find(|p| p.conflict.is_some()) {
+        return Err(format!("target already exists: {}", item.to.display()));
+    }
+
+    let changed: Vec<&Planned> = planned.iter().filter(|p| p.to != p.from).collect();
+    let tracked: Vec<&Planned> = changed.iter().copied().filter(|p| p.tracked).collect();
+    let mut port = 0;
+    if !tracked.is_empty() {
+        if crate::safe_mode::is_read_only(&app) {
+            return Err("database is in read-only safe mode".to_string());
+        }
+        // 生成中的任务会在改名期间写回路径
+        if *app.state::<GenerationState>().0.lock().unwrap() {
+            return Err("generation in progress".to_string());
+        }
+        port = *app.state::<BackendPort>().0.lock().unwrap();
+        if port == 0 {
+            return Err("backend is not running".to_string());
+        }
+    }
+
+    if let Err(err) = execute(&changed) {
+        app.state::<LogState>()
+            .log_app("ERROR", &format!("Batch rename rolled back: {}", err));
+        return Err(err);
+    }
+    if !tracked.is_empty() {
+        if let Err(err) = sync_paths(&app, port, &tracked).await {
+            undo(&changed);
+            app.state::<LogState>().log_app(
+                "ERROR",
+                &format!(
+                    "Batch rename rolled back renamed={}: {}",
+                    changed.len(),
+                    err
+                ),
+            );
+            return Err(err);
+        }
+        let urls = tracked
+            .iter()
+            .filter_map(|p| crate::invalidation::url_for_file(&app, &p.from))
+            .collect();
+        let task_ids = tracked.iter().filter_map(|p| p.task_id.clone()).collect();
+        crate::invalidation::invalidate(&app, task_ids, urls, "rename");
+    }
+
+    app.state::<LogState>().log_app(
+        "INFO",
+        &format!(
+            "Batch rename finished renamed={} tracked={}",
+            changed.len(),
+            tracked.len()
+        ),
+    );
+    Ok(RenameResult {
+        dry_run,
+        renamed: changed.len(),
+        items,
+    })
+}
@@ -0,0 +1,300 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use tauri::Manager;
+
+use crate::{BackendPort, GenerationState, LogState};
+
+#[derive(serde::Serialize)]
+pub struct ReorganizeResult {
+    moved: usize,
+    // 已在目标位置或文件不存在
+    skipped: usize,
+    failed: Vec<String>,
+}
+
+struct TaskRow {
+    task_id: String,
+    local_path: String,
+    thumbnail_path: String,
+    created_at: String,
+    folder_name: Option<String>,
+}
+
+// 一次移动：失败时按 to -> from 逆序回滚
+struct Move {
+    task_id: String,
+    from: PathBuf,
+    to: PathBuf,
+    thumb: Option<(PathBuf, PathBuf)>,
+}
+
+fn load_tasks(app: &tauri::AppHandle) -> Result<Vec<TaskRow>, String> {
+    let conn = crate::db::open_readonly(app)?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT t.task_id, t.local_path, t.thumbnail_path, t.created_at, f.name \
+             FROM tasks t LEFT JOIN folders f \
+             ON f.id = CAST(t.folder_id AS INTEGER) AND f.deleted_at IS NULL \
+             WHERE t.deleted_at IS NULL AND t.status = 'completed' \
+             AND t.local_path IS NOT NULL AND t.local_path != ''",
+        )
+        .map_err(|e| format!("query tasks failed: {}", e))?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok(TaskRow {
+                task_id: row.get(0)?,
+                local_path: row.get(1)?,
+                thumbnail_path: row.get::<_, Option<String>>(2)?.unwrap_or_default(),
+                // gorm 写入的是 "2025-03-01 12:34:56.789+08:00" 形式的文本
+                created_at: row.get::<_, Option<String>>(3)?.unwrap_or_default(),
+                folder_name: row.get(4)?,
+            })
+        })
+        .map_err(|e| format!("query tasks failed: {}", e))?;
+    Ok(rows.flatten().collect())
+}
+
+// 文件夹名可能包含路径分隔符或 Windows 保留字符
+fn sanitize_dir_name(name: &str) -> String {
+    let cleaned: String = name
+        .trim()
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+    let cleaned = cleaned.trim_matches(|c| c == '.' || c == ' ').to_string();
+    if cleaned.is_empty() {
+        "未分类".to_string()
+    } else {
+        cleaned
+    }
+}
+
+fn target_dir(storage: &Path, layout: &str, task: &TaskRow) -> Option<PathBuf> {
+    match layout {
+        "month" => {
+            let year = task.created_at.get(0..4)?;
+            let month = task.created_at.get(5..7)?;
+            if !year
+                .chars()
+                .chain(month.chars())
+                .all(|c| c.is_ascii_digit())
+            {
+                return None;
+            }
+            Some(storage.join(year).join(month))
+        }
+        "folder" => Some(storage.join(sanitize_dir_name(
+            task.folder_name.as_deref().unwrap_or("未分类"),
+        ))),
+        _ => None,
+    }
+}
+
+fn relative_to_base(base: &Path, path: &Path) -> String {
+    path.strip_prefix(base)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .to_string()
+}
+
+fn rollback(moves: &[Move]) {
+    for m in moves.iter().rev() {
+        if let Some((from, to)) = &m.thumb {
+            let _ = fs::rename(to, from);
+        }
+        let _ = fs::rename(&m.to, &m.from);
+    }
+}
+
+// 移动阶段：只处理存储目录内、且不在目标位置的文件；目标已存在同名文件时跳过
+fn move_files(
+    app: &tauri::AppHandle,
+    layout: &str,
+    skipped: &mut usize,
+    failed: &mut Vec<String>,
+) -> Result<Vec<Move>, String> {
+    let base = crate::app_data_base(app);
+    let storage = crate::paths::storage_dir(app)
+        .canonicalize()
+        .map_err(|e| format!("resolve storage dir failed: {}", e))?;
+
+    let mut moves = Vec::new();
+    for task in load_tasks(app)? {
+        let Some(from) = crate::storage::resolve_db_path(&base, &task.local_path)
+            .and_then(|p| p.canonicalize().ok())
+            .filter(|p| p.starts_with(&storage))
+        else {
+            *skipped += 1;
+            continue;
+        };
+        let Some(dir) = target_dir(&storage, layout, &task) else {
+            *skipped += 1;
+            continue;
+        };
+        if from.parent() == Some(dir.as_path()) {
+            *skipped += 1;
+            continue;
+        }
+        let Some(file_name) = from.file_name() else {
+            *skipped += 1;
+            continue;
+        };
+        let to = dir.join(file_name);
+        if to.exists() {
+            failed.push(format!("{}: target already exists", to.display()));
+            continue;
+        }
+        if let Err(e) = fs::create_dir_all(&dir).and_then(|_| fs::rename(&from, &to)) {
+            failed.push(format!("{}: {}", from.display(), e));
+            continue;
+        }
+
+        // 缩略图跟着原图走；缩略图移动失败不影响原图
+        let thumb = crate::storage::resolve_db_path(&base, &task.thumbnail_path)
+            .and_then(|p| p.canonicalize().ok())
+            .filter(|p| p.starts_with(&storage) && p.parent() != Some(dir.as_path()))
+            .and_then(|thumb_from| {
+                let thumb_to = dir.join(thumb_from.file_name()?);
+                if thumb_to.exists() {
+                    return None;
+                }
+                fs::rename(&thumb_from, &thumb_to).ok()?;
+                Some((thumb_from, thumb_to))
+            });
+        moves.push(Move {
+            task_id: task.task_id,
+            from,
+            to,
+            thumb,
+        });
+    }
+    Ok(moves)
+}
+
+// 整理后旧的年/月或文件夹目录可能已经空了，顺手清掉（非空目录 remove_dir 会失败，直接忽略）
+fn remove_empty_dirs(storage: &Path, moves: &[Move]) {
+    for m in moves {
+        let mut dir = m.from.parent();
+        while let Some(d) = dir {
+            if d == storage || !d.starts_with(storage) || fs::remove_dir(d).is_err() {
+                break;
+            }
+            dir = d.parent();
+        }
+    }
+}
+
+async fn sync_paths(app: &tauri::AppHandle, port: u16, moves: &[Move]) -> Result<(), String> {
+    // 移动阶段用的是 canonicalize 后的路径，这里同样规范化再求相对路径
+    let base = crate::app_data_base(app);
+    let base = base.canonicalize().unwrap_or(base);
+    let items: Vec<serde_json::Value> = moves
+        .iter()
+        .map(|m| {
+            serde_json::json!({
+                "task_id": m.task_id,
+                "local_path": relative_to_base(&base, &m.to),
+                "thumbnail_path": m
+                    .thumb
+                    .as_ref()
+                    .map(|(_, to)| relative_to_base(&base, to))
+                    .unwrap_or_default(),
+            })
+        })
+        .collect();
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(120))
+        .build()
+        .map_err(|e| format!("build relocate client failed: {}", e))?;
+    let response = client
+        .post(format!("http://127.0.0.1:{}/api/v1/images/relocate", port))
+        .header(reqwest::header::ORIGIN, "tauri://localhost")
+        .json(&serde_json::json!({ "items": items }))
+        .send()
+        .await
+        .map_err(|e| format!("relocate request failed: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("relocate request failed: {}", response.status()));
+    }
+    Ok(())
+}
+
+// 把存储目录整理成 年/月（layout=month）或按文件夹（layout=folder）分层
+// 先在磁盘上移动文件，再通过后端一次事务更新路径；同步失败时把文件全部移回原处
+#[tauri::command]
+pub async fn reorganize_storage(
+    app: tauri::AppHandle,
+    layout: String,
+) -> Result<ReorganizeResult, String> {
+    if !matches!(layout.as_str(), "month" | "folder") {
+        return Err(format!("unsupported layout: {}", layout));
+    }
+    crate::remote_backend::ensure_local(&app)?;
+    if crate::safe_mode::is_read_only(&app) {
+        return Err("database is in read-only safe mode".to_string());
+    }
+    // 生成中的任务会在移动期间写回路径，等队列空闲再整理
+    if *app.state::<GenerationState>().0.lock().unwrap() {
+        return Err("generation in progress".to_string());
+    }
+    let port = *app.state::<BackendPort>().0.lock().unwrap();
+    if port == 0 {
+        return Err("backend is not running".to_string());
+    }
+
+    let app_for_task = app.clone();
+    let (moves, skipped, failed) = tauri::async_runtime::spawn_blocking(move || {
+        let mut skipped = 0;
+        let mut failed = Vec::new();
+        move_files(&app_for_task, &layout, &mut skipped, &mut failed).map(|m| (m, skipped, failed))
+    })
+    .await
+    .map_err(|e| format!("reorganize task failed: {}", e))??;
+
+    if !moves.is_empty() {
+        if let Err(err) = sync_paths(&app, port, &moves).await {
+            rollback(&moves);
+            app.state::<LogState>().log_app(
+                "ERROR",
+                &format!(
+                    "Storage reorganize rolled back moved={}: {}",
+                    moves.len(),
+                    err
+                ),
+            );
+            return Err(err);
+        }
+        if let Ok(storage) = crate::paths::storage_dir(&app).canonicalize() {
+            remove_empty_dirs(&storage, &moves);
+        }
+        // 直接调用后端接口，不经过代理，旧地址在这里失效
+        let urls = moves
+            .iter()
+            .flat_map(|m| std::iter::once(&m.from).chain(m.thumb.as_ref().map(|(from, _)| from)))
+            .filter_map(|p| crate::invalidation::url_for_file(&app, p))
+            .collect();
+        let task_ids = moves.iter().map(|m| m.task_id.clone()).collect();
+        crate::invalidation::invalidate(&app, task_ids, urls, "reorganize");
+    }
+
+    app.state::<LogState>().log_app(
+        "INFO",
+        &format!(
+            "Storage reorganized moved={} skipped={} failed={}",
+            moves.len(),
+            skipped,
+            failed.len()
+        ),
+    );
+    Ok(ReorganizeResult {
+        moved: moves.len(),
+        skipped,
+        failed,
+    })
+}
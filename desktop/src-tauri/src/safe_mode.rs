@@ -0,0 +1,295 @@
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use rusqlite::{Connection, ErrorCode};
+use tauri::{Emitter, Manager, State};
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+
+use crate::{BackendPort, LogState, SidecarState};
+
+#[derive(Clone, serde::Serialize)]
+pub struct LockHolder {
+    pid: u32,
+    name: String,
+}
+
+#[derive(Clone, serde::Serialize)]
+pub struct SafeModeStatus {
+    read_only: bool,
+    // 可能为空：锁存在但查不到进程（如网络盘上的其他机器）
+    holders: Vec<LockHolder>,
+}
+
+// Some 表示当前以只读安全模式运行，里面是启动时检测到的占用方
+pub struct SafeModeState(pub Arc<Mutex<Option<Vec<LockHolder>>>>);
+
+pub fn is_read_only(app: &tauri::AppHandle) -> bool {
+    app.try_state::<SafeModeState>()
+        .map(|s| s.0.lock().unwrap().is_some())
+        .unwrap_or(false)
+}
+
+// 尝试拿写锁：BEGIN IMMEDIATE 成功说明没人占用，立即回滚不做任何修改
+fn database_locked(path: &Path) -> bool {
+    if !path.exists() {
+        return false;
+    }
+    let Ok(conn) = Connection::open(path) else {
+        return false;
+    };
+    let _ = conn.busy_timeout(Duration::from_millis(1500));
+    match conn.execute_batch("BEGIN IMMEDIATE; ROLLBACK;") {
+        Ok(()) => false,
+        Err(rusqlite::Error::SqliteFailure(err, _)) => {
+            matches!(
+                err.code,
+                ErrorCode::DatabaseBusy | ErrorCode::DatabaseLocked
+            )
+        }
+        Err(_) => false,
+    }
+}
+
+#[cfg(unix)]
+fn find_holders(path: &Path) -> Vec<LockHolder> {
+    use std::process::Command;
+
+    let mut wal = path.as_os_str().to_owned();
+    wal.push("-wal");
+    let Ok(output) = Command::new("lsof")
+        .arg("-t")
+        .arg("--")
+        .arg(path)
+        .arg(&wal)
+        .output()
+    else {
+        return Vec::new();
+    };
+    let mut pids: Vec<u32> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|l| l.trim().parse().ok())
+        .collect();
+    pids.sort_unstable();
+    pids.dedup();
+
+    pids.into_iter()
+        .map(|pid| {
+            let name = Command::new("ps")
+                .args(["-o", "comm=", "-p", &pid.to_string()])
+                .output()
+                .ok()
+                .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+                .filter(|n| !n.is_empty())
+                .unwrap_or_else(|| "unknown".to_string());
+            LockHolder { pid, name }
+        })
+        .collect()
+}
+
+#[cfg(windows)]
+fn find_holders(path: &Path) -> Vec<LockHolder> {
+    use std::os::windows::ffi::OsStrExt;
+    use windows_sys::Win32::System::RestartManager::{
+        RmEndSession, RmGetList, RmRegisterResources, RmStartSession, CCH_RM_SESSION_KEY,
+        RM_PROCESS_INFO,
+    };
+
+    // Restart Manager 是 Windows 上查询“谁占用了这个文件”的官方接口
+    let wide: Vec<u16> = path.as_os_str().encode_wide().chain(Some(0)).collect();
+    let mut holders = Vec::new();
+    unsafe {
+        let mut session = 0u32;
+        let mut key = [0u16; CCH_RM_SESSION_KEY as usize + 1];
+        if RmStartSession(&mut session, 0, key.as_mut_ptr()) != 0 {
+            return holders;
+        }
+        let files = [wide.as_ptr()];
+        if RmRegisterResources(
+            session,
+            1,
+            files.as_ptr(),
+            0,
+            std::ptr::null(),
+            0,
+            std::ptr::null(),
+        ) == 0
+        {
+            let mut needed = 0u32;
+            let mut count = 16u32;
+            let mut infos: Vec<RM_PROCESS_INFO> = vec![Default::default(); count as usize];
+            let mut reasons = 0u32;
+            if RmGetList(
+                session,
+                &mut needed,
+                &mut count,
+                infos.as_mut_ptr(),
+                &mut reasons,
+            ) == 0
+            {
+                for info in infos.iter().take(count as usize) {
+                    let len = info
+                        .strAppName
+                        .iter()
+                        .position(|c| *c == 0)
+                        .unwrap_or(info.strAppName.len());
+                    holders.push(LockHolder {
+                        pid: info.Process.dwProcessId,
+                        name: String::from_utf16_lossy(&info.strAppName[..len]),
+                    });
+                }
+            }
+        }
+        RmEndSession(session);
+    }
+    holders
+}
+
+// 排除自身和自己拉起的 sidecar（只读模式下它也会打开数据库）
+fn foreign_holders(app: &tauri::AppHandle) -> Vec<LockHolder> {
+    let own_sidecar = app
+        .try_state::<SidecarState>()
+        .and_then(|s| s.0.lock().unwrap().as_ref().map(|c| c.pid()));
+    find_holders(&crate::db::db_path(app))
+        .into_iter()
+        .filter(|h| h.pid != std::process::id() && Some(h.pid) != own_sidecar)
+        .collect()
+}
+
+fn kill_process(pid: u32) -> Result<(), String> {
+    #[cfg(windows)]
+    let status = std::process::Command::new("taskkill")
+        .args(["/PID", &pid.to_string(), "/F"])
+        .status();
+    #[cfg(not(windows))]
+    let status = std::process::Command::new("kill")
+        .args(["-TERM", &pid.to_string()])
+        .status();
+    match status {
+        Ok(s) if s.success() => Ok(()),
+        Ok(s) => Err(format!("kill process {} exited with {}", pid, s)),
+        Err(e) => Err(format!("kill process {} failed: {}", pid, e)),
+    }
+}
+
+fn describe_holders(holders: &[LockHolder]) -> String {
+    if holders.is_empty() {
+        return "未知进程".to_string();
+    }
+    holders
+        .iter()
+        .map(|h| format!("{} (PID {})", h.name, h.pid))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn set_safe_mode(app: &tauri::AppHandle, holders: Option<Vec<LockHolder>>) {
+    let status = SafeModeStatus {
+        read_only: holders.is_some(),
+        holders: holders.clone().unwrap_or_default(),
+    };
+    *app.state::<SafeModeState>().0.lock().unwrap() = holders;
+    let _ = app.emit("safe-mode", status);
+}
+
+// 结束占用进程并等待锁释放，最多等 3 秒
+fn take_over(app: &tauri::AppHandle) -> Result<(), String> {
+    let path = crate::db::db_path(app);
+    for holder in foreign_holders(app) {
+        kill_process(holder.pid)?;
+    }
+    for _ in 0..30 {
+        if !database_locked(&path) {
+            return Ok(());
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    }
+    Err("database is still locked".to_string())
+}
+
+fn start_sidecar(app: &tauri::AppHandle) {
+    let port_state = app.state::<BackendPort>().0.clone();
+    if let Err(err) = crate::spawn_sidecar(app, port_state) {
+        app.state::<LogState>()
+            .log_app("ERROR", &format!("spawn sidecar failed: {}", err));
+    }
+}
+
+// 启动时检测数据库锁：被占用时询问用户接管或以只读模式继续，决定后再拉起 sidecar
+// 返回 false 表示没有锁，调用方按正常流程启动
+pub fn handle_locked_database(app: &tauri::AppHandle) -> bool {
+    let path = crate::db::db_path(app);
+    if !database_locked(&path) {
+        return false;
+    }
+    let holders = foreign_holders(app);
+    app.state::<LogState>().log_app(
+        "WARN",
+        &format!(
+            "Database locked at startup holders=[{}]",
+            describe_holders(&holders).replace('\n', "; ")
+        ),
+    );
+
+    let app_handle = app.clone();
+    app.dialog()
+        .message(format!(
+            "数据库正被以下进程占用：\n{}\n\n可以结束这些进程并接管数据库，或以只读安全模式继续（无法生成和修改图片）。",
+            describe_holders(&holders)
+        ))
+        .title("数据库被占用")
+        .kind(MessageDialogKind::Warning)
+        .buttons(MessageDialogButtons::OkCancelCustom(
+            "接管".to_string(),
+            "只读模式".to_string(),
+        ))
+        .show(move |take| {
+            std::thread::spawn(move || {
+                let result = if take {
+                    take_over(&app_handle)
+                } else {
+                    Err("user chose read-only mode".to_string())
+                };
+                if let Err(reason) = result {
+                    app_handle.state::<LogState>().log_app(
+                        "WARN",
+                        &format!("Entering read-only safe mode: {}", reason),
+                    );
+                    set_safe_mode(&app_handle, Some(holders));
+                }
+                start_sidecar(&app_handle);
+            });
+        });
+    true
+}
+
+#[tauri::command]
+pub fn get_safe_mode_status(state: State<'_, SafeModeState>) -> SafeModeStatus {
+    let holders = state.0.lock().unwrap().clone();
+    SafeModeStatus {
+        read_only: holders.is_some(),
+        holders: holders.unwrap_or_default(),
+    }
+}
+
+// 只读模式下用户确认后接管：结束占用进程，以读写模式重启 sidecar
+#[tauri::command]
+pub async fn take_over_database(app: tauri::AppHandle) -> Result<(), String> {
+    if !is_read_only(&app) {
+        return Ok(());
+    }
+    let app_for_task = app.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let app = app_for_task;
+        // 接管失败时仍按只读模式重新拉起
+        crate::with_sidecar_stopped(&app, crate::SidecarRestart::Maintenance, || {
+            take_over(&app)?;
+            set_safe_mode(&app, None);
+            app.state::<LogState>()
+                .log_app("INFO", "Database taken over, leaving read-only safe mode");
+            Ok(())
+        })
+    })
+    .await
+    .map_err(|e| format!("take over task failed: {}", e))?
+}
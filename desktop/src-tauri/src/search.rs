@@ -0,0 +1,652 @@
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::{Condvar, Mutex};
+use std::time::Duration;
+
+use rusqlite::{params, Connection, OptionalExtension};
+use tauri::Manager;
+
+use crate::LogState;
+
+// 提示词索引放在壳层自己的 SQLite 文件里（FTS5 trigram 分词，中文也能按子串命中），
+// 后端重启或数据库被占用时搜索照常可用。启动时补齐一次，之后由代理在提交、删除任务以及
+// 计时模块在任务结束时调用 notify 触发增量同步
+// 连续的提交、批量删除合并成一次同步
+const SYNC_DEBOUNCE: Duration = Duration::from_millis(500);
+// 同步失败（后端迁移中、数据库被锁）后的重试间隔
+const RETRY_INTERVAL: Duration = Duration::from_secs(15);
+// 每批从后端数据库读取的行数，批与批之间释放索引锁，首次建索引时搜索不会被长时间阻塞
+const SYNC_BATCH: usize = 2000;
+const DEFAULT_LIMIT: usize = 50;
+const MAX_LIMIT: usize = 500;
+// 模糊搜索：先用 trigram OR 召回候选，再按重合比例过滤
+const FUZZY_CANDIDATES: usize = 500;
+const FUZZY_THRESHOLD: f64 = 0.5;
+
+static INDEX: Mutex<Option<Connection>> = Mutex::new(None);
+static DIRTY: Mutex<bool> = Mutex::new(false);
+static CHANGED: Condvar = Condvar::new();
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS meta (key TEXT PRIMARY KEY, value INTEGER NOT NULL);
+CREATE TABLE IF NOT EXISTS prompts (
+    rowid INTEGER PRIMARY KEY,
+    task_id TEXT NOT NULL UNIQUE,
+    prompt TEXT NOT NULL DEFAULT '',
+    prompt_original TEXT NOT NULL DEFAULT '',
+    prompt_optimized TEXT NOT NULL DEFAULT '',
+    provider TEXT NOT NULL DEFAULT '',
+    model_id TEXT NOT NULL DEFAULT '',
+    status TEXT NOT NULL DEFAULT '',
+    created_at TEXT NOT NULL DEFAULT ''
+);
+CREATE VIRTUAL TABLE IF NOT EXISTS prompts_fts USING fts5(
+    prompt, prompt_original, prompt_optimized,
+    content='prompts', content_rowid='rowid', tokenize='trigram'
+);
+CREATE TRIGGER IF NOT EXISTS prompts_ai AFTER INSERT ON prompts BEGIN
+    INSERT INTO prompts_fts(rowid, prompt, prompt_original, prompt_optimized)
+    VALUES (new.rowid, new.prompt, new.prompt_original, new.prompt_optimized);
+END;
+CREATE TRIGGER IF NOT EXISTS prompts_ad AFTER DELETE ON prompts BEGIN
+    INSERT INTO prompts_fts(prompts_fts, rowid, prompt, prompt_original, prompt_optimized)
+    VALUES ('delete', old.rowid, old.prompt, old.prompt_original, old.prompt_optimized);
+END;
+CREATE TRIGGER IF NOT EXISTS prompts_au AFTER UPDATE ON prompts BEGIN
+    INSERT INTO prompts_fts(prompts_fts, rowid, prompt, prompt_original, prompt_optimized)
+    VALUES ('delete', old.rowid, old.prompt, old.prompt_original, old.prompt_optimized);
+    INSERT INTO prompts_fts(rowid, prompt, prompt_original, prompt_optimized)
+    VALUES (new.rowid, new.prompt, new.prompt_original, new.prompt_optimized);
+END;
+";
+
+#[derive(serde::Serialize)]
+pub struct PromptHit {
+    task_id: String,
+    prompt: String,
+    provider: String,
+    model_id: String,
+    status: String,
+    created_at: String,
+    score: f64,
+}
+
+#[derive(serde::Serialize)]
+pub struct PromptSearchResult {
+    hits: Vec<PromptHit>,
+    // exact / fuzzy：精确匹配没有结果时自动退回模糊匹配
+    mode: String,
+    indexed: u64,
+}
+
+struct TaskRow {
+    id: i64,
+    task_id: String,
+    prompt: String,
+    prompt_original: String,
+    prompt_optimized: String,
+    provider: String,
+    model_id: String,
+    status: String,
+    created_at: String,
+}
+
+fn index_path(app: &tauri::AppHandle) -> PathBuf {
+    crate::app_data_base(app).join("prompt_index.db")
+}
+
+fn with_index<T>(
+    app: &tauri::AppHandle,
+    f: impl FnOnce(&mut Connection) -> Result<T, String>,
+) -> Result<T, String> {
+    let mut guard = INDEX.lock().unwrap();
+    if guard.is_none() {
+        let conn = Connection::open(index_path(app))
+            .map_err(|e| format!("open prompt index failed: {}", e))?;
+        conn.execute_batch(SCHEMA)
+            .map_err(|e| format!("init prompt index failed: {}", e))?;
+        *guard = Some(conn);
+    }
+    f(guard.as_mut().unwrap())
+}
+
+fn meta(conn: &Connection, key: &str) -> i64 {
+    conn.query_row("SELECT value FROM meta WHERE key = ?1", [key], |row| {
+        row.get(0)
+    })
+    .optional()
+    .ok()
+    .flatten()
+    .unwrap_or(0)
+}
+
+fn set_meta(conn: &Connection, key: &str, value: i64) -> rusqlite::Result<usize> {
+    conn.execute(
+        "INSERT INTO meta(key, value) VALUES (?1, ?2) \
+         ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+        params![key, value],
+    )
+}
+
+fn text(row: &rusqlite::Row<'_>, index: usize) -> rusqlite::Result<String> {
+    Ok(row.get::<_, Option<String>>(index)?.unwrap_or_default())
+}
+
+fn read_rows(source: &Connection, after: i64) -> Result<Vec<TaskRow>, String> {
+    let mut stmt = source
+        .prepare(
+            "SELECT id, task_id, prompt, prompt_original, prompt_optimized, provider_name, \
+             model_id, status, created_at FROM tasks \
+             WHERE id > ?1 AND deleted_at IS NULL ORDER BY id LIMIT ?2",
+        )
+        .map_err(|e| format!("query tasks failed: {}", e))?;
+    let rows = stmt
+        .query_map(params![after, SYNC_BATCH as i64], |row| {
+            Ok(TaskRow {
+                id: row.get(0)?,
+                task_id: row.get(1)?,
+                prompt: text(row, 2)?,
+                prompt_original: text(row, 3)?,
+                prompt_optimized: text(row, 4)?,
+                provider: text(row, 5)?,
+                model_id: text(row, 6)?,
+                status: text(row, 7)?,
+                created_at: text(row, 8)?,
+            })
+        })
+        .map_err(|e| format!("query tasks failed: {}", e))?;
+    Ok(rows.flatten().collect())
+}
+
+// 写入一批；进度只推进到第一个未结束的任务之前，进行中的任务下一轮会重新写入（优化后的提示词稍后才落库）
+// 返回本批是否全部已结束；之前的批次已有未结束任务时 advance=false，不再推进进度
+fn write_rows(
+    conn: &mut Connection,
+    rows: &[TaskRow],
+    cursor: i64,
+    advance: bool,
+) -> Result<bool, String> {
+    let tx = conn
+        .transaction()
+        .map_err(|e| format!("write prompt index failed: {}", e))?;
+    let mut next = cursor;
+    let mut settled = advance;
+    {
+        let mut stmt = tx
+            .prepare(
+                "INSERT INTO prompts(rowid, task_id, prompt, prompt_original, prompt_optimized, \
+                 provider, model_id, status, created_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9) \
+                 ON CONFLICT(task_id) DO UPDATE SET prompt = excluded.prompt, \
+                 prompt_original = excluded.prompt_original, \
+                 prompt_optimized = excluded.prompt_optimized, provider = excluded.provider, \
+                 model_id = excluded.model_id, status = excluded.status",
+            )
+            .map_err(|e| format!("write prompt index failed: {}", e))?;
+        for row in rows {
+            stmt.execute(params![
+                row.id,
+                row.task_id,
+                row.prompt,
+                row.prompt_original,
+                row.prompt_optimized,
+                row.provider,
+                row.model_id,
+                row.status,
+                row.created_at
+            ])
+            .map_err(|e| format!("write prompt index failed: {}", e))?;
+            settled = settled && row.status != "pending" && row.status != "processing";
+            if settled {
+                next = row.id;
+            }
+        }
+    }
+    if next != cursor {
+        set_meta(&tx, "cursor", next).map_err(|e| format!("write prompt index failed: {}", e))?;
+    }
+    tx.commit()
+        .map_err(|e| format!("write prompt index failed: {}", e))?;
+    Ok(settled)
+}
+
+// 后端是软删除，已删除记录数变化时才同步删除
+fn sync_deleted(app: &tauri::AppHandle, source: &Connection) -> Result<(), String> {
+    let deleted: i64 = source
+        .query_row(
+            "SELECT COUNT(*) FROM tasks WHERE deleted_at IS NOT NULL",
+            [],
+            |row| row.get(0),
+        )
+        .map_err(|e| format!("query tasks failed: {}", e))?;
+    if with_index(app, |conn| Ok(meta(conn, "deleted")))? == deleted {
+        return Ok(());
+    }
+    let mut stmt = source
+        .prepare("SELECT task_id FROM tasks WHERE deleted_at IS NOT NULL")
+        .map_err(|e| format!("query tasks failed: {}", e))?;
+    let ids: Vec<String> = stmt
+        .query_map([], |row| row.get(0))
+        .map_err(|e| format!("query tasks failed: {}", e))?
+        .flatten()
+        .collect();
+    with_index(app, |conn| {
+        let tx = conn
+            .transaction()
+            .map_err(|e| format!("write prompt index failed: {}", e))?;
+        {
+            let mut stmt = tx
+                .prepare("DELETE FROM prompts WHERE task_id = ?1")
+                .map_err(|e| format!("write prompt index failed: {}", e))?;
+            for id in &ids {
+                stmt.execute([id])
+                    .map_err(|e| format!("write prompt index failed: {}", e))?;
+            }
+        }
+        set_meta(&tx, "deleted", deleted)
+            .map_err(|e| format!("write prompt index failed: {}", e))?;
+        tx.commit()
+            .map_err(|e| format!("write prompt index failed: {}", e))
+    })
+}
+
+fn sync(app: &tauri::AppHandle) -> Result<usize, String> {
+    if !crate::db::db_path(app).exists() {
+        return Ok(0);
+    }
+    let source = crate::db::open_readonly(app)?;
+    let mut cursor = with_index(app, |conn| Ok(meta(conn, "cursor")))?;
+    let mut advance = true;
+    let mut written = 0;
+    loop {
+        let rows = read_rows(&source, cursor)?;
+        if rows.is_empty() {
+            break;
+        }
+        advance = with_index(app, |conn| write_rows(conn, &rows, cursor, advance))?;
+        written += rows.len();
+        if rows.len() < SYNC_BATCH {
+            break;
+        }
+        // 读取位置总是往后走，保存的进度停在第一个未结束的任务之前
+        cursor = rows[rows.len() - 1].id;
+    }
+    sync_deleted(app, &source)?;
+    Ok(written)
+}
+
+// 任务提交、结束或删除后调用，唤醒索引线程做一次增量同步
+pub fn notify() {
+    *DIRTY.lock().unwrap() = true;
+    CHANGED.notify_all();
+}
+
+pub fn start(app: &tauri::AppHandle) {
+    let app = app.clone();
+    std::thread::spawn(move || {
+        let mut last_error = None;
+        loop {
+            // 失败时隔一段时间重试，同样的错误只记一次
+            let failed = match sync(&app) {
+                Ok(_) => {
+                    last_error = None;
+                    false
+                }
+                Err(err) => {
+                    if last_error.as_ref() != Some(&err) {
+                        app.state::<LogState>()
+                            .log_app("WARN", &format!("Prompt index sync failed: {}", err));
+                        last_error = Some(err);
+                    }
+                    true
+                }
+            };
+            let dirty = DIRTY.lock().unwrap();
+            if failed {
+                let _ = CHANGED
+                    .wait_timeout_while(dirty, RETRY_INTERVAL, |dirty| !*dirty)
+                    .unwrap();
+            } else {
+                let _ = CHANGED.wait_while(dirty, |dirty| !*dirty).unwrap();
+            }
+            std::thread::sleep(SYNC_DEBOUNCE);
+            *DIRTY.lock().unwrap() = false;
+        }
+    });
+}
+
+// 拆分查询：双引号内为短语，其余按空白切分，全部需要命中
+fn parse_query(query: &str) -> Vec<String> {
+    let mut terms = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    for c in query.chars() {
+        match c {
+            '"' => {
+                if !current.trim().is_empty() {
+                    terms.push(current.trim().to_string());
+                }
+                current.clear();
+                quoted = !quoted;
+            }
+            c if c.is_whitespace() && !quoted => {
+                if !current.is_empty() {
+                    terms.push(current.clone());
+                }
+                current.clear();
+            }
+            c => current.push(c),
+        }
+    }
+    if !current.trim().is_empty() {
+        terms.push(current.trim().to_string());
+    }
+    terms
+}
+
+fn fts_quote(term: &str) -> String {
+    format!("\"{}\"", term.replace('"', "\"\""))
+}
+
+fn like_pattern(term: &str) -> String {
+    let escaped = term
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+    format!("%{}%", escaped)
+}
+
+fn trigrams(text: &str) -> HashSet<String> {
+    let chars: Vec<char> = text.to_lowercase().chars().collect();
+    chars
+        .windows(3)
+        .map(|w| w.iter().collect::<String>())
+        .filter(|t: &String| !t.trim().is_empty())
+        .collect()
+}
+
+fn read_hit(row: &rusqlite::Row<'_>) -> rusqlite::Result<(PromptHit, String)> {
+    Ok((
+        PromptHit {
+            task_id: row.get(0)?,
+            prompt: row.get(1)?,
+            provider: row.get(2)?,
+            model_id: row.get(3)?,
+            status: row.get(4)?,
+            created_at: row.get(5)?,
+            score: row.get(6)?,
+        },
+        // 模糊匹配时用三个字段一起计算重合度
+        format!(
+            "{} {} {}",
+            row.get::<_, String>(7)?,
+            row.get::<_, String>(8)?,
+            row.get::<_, String>(1)?
+        ),
+    ))
+}
+
+const HIT_COLUMNS: &str = "p.task_id, p.prompt, p.provider, p.model_id, p.status, p.created_at";
+
+// trigram 只能匹配 3 个字符以上的片段，更短的词（常见的两字中文词）用 LIKE 过滤
+fn exact(conn: &Connection, terms: &[String], limit: usize) -> rusqlite::Result<Vec<PromptHit>> {
+    let (long, short): (Vec<&String>, Vec<&String>) =
+        terms.iter().partition(|t| t.chars().count() >= 3);
+    let mut values: Vec<String> = Vec::new();
+    let mut conditions = Vec::new();
+    for term in &short {
+        values.push(like_pattern(term));
+        let n = values.len();
+        conditions.push(format!(
+            "(p.prompt LIKE ?{n} ESCAPE '\\' OR p.prompt_original LIKE ?{n} ESCAPE '\\' \
+             OR p.prompt_optimized LIKE ?{n} ESCAPE '\\')"
+        ));
+    }
+    let sql = if long.is_empty() {
+        format!(
+            "SELECT {HIT_COLUMNS}, 0.0, p.prompt_original, p.prompt_optimized FROM prompts p \
+             WHERE {} ORDER BY p.rowid DESC LIMIT {}",
+            conditions.join(" AND "),
+            limit
+        )
+    } else {
+        values.push(
+            long.iter()
+                .map(|t| fts_quote(t))
+                .collect::<Vec<_>>()
+                .join(" AND "),
+        );
+        conditions.push(format!("prompts_fts MATCH ?{}", values.len()));
+        format!(
+            "SELECT {HIT_COLUMNS}, -bm25(prompts_fts), p.prompt_original, p.prompt_optimized \
+             FROM prompts_fts JOIN prompts p ON p.rowid = prompts_fts.rowid \
+             WHERE {} ORDER BY bm25(prompts_fts) LIMIT {}",
+            conditions.join(" AND "),
+            limit
+        )
+    };
+    let mut stmt = conn.prepare(&sql)?;
+    let rows = stmt.query_map(rusqlite::params_from_iter(values), read_hit)?;
+    Ok(rows.flatten().map(|(hit, _)| hit).collect())
+}
+
+// 容错搜索：拼写错一两个字符时大部分 trigram 仍能命中
+fn fuzzy(conn: &Connection, query: &str, limit: usize) -> rusqlite::Result<Vec<PromptHit>> {
+    let wanted = trigrams(query);
+    if wanted.is_empty() {
+        return Ok(Vec::new());
+    }
+    let expr = wanted
+        .iter()
+        .map(|t| fts_quote(t))
+        .collect::<Vec<_>>()
+        .join(" OR ");
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {HIT_COLUMNS}, 0.0, p.prompt_original, p.prompt_optimized \
+         FROM prompts_fts JOIN prompts p ON p.rowid = prompts_fts.rowid \
+         WHERE prompts_fts MATCH ?1 ORDER BY bm25(prompts_fts) LIMIT {}",
+        FUZZY_CANDIDATES
+    ))?;
+    let mut hits: Vec<PromptHit> = stmt
+        .query_map([expr], read_hit)?
+        .flatten()
+        .filter_map(|(mut hit, haystack)| {
+            let found = trigrams(&haystack);
+            hit.score =
+                wanted.iter().filter(|t| found.contains(*t)).count() as f64 / wanted.len() as f64;
+            (hit.score >= FUZZY_THRESHOLD).then_some(hit)
+        })
+        .collect();
+    hits.sort_by(|a, b| b.score.total_cmp(&a.score));
+    hits.truncate(limit);
+    Ok(hits)
+}
+
+// 支持 "短语" 和普通关键词；fuzzy=true 时直接按容错方式匹配
+#[tauri::command]
+pub async fn search_prompts(
+    app: tauri::AppHandle,
+    query: String,
+    limit: Option<usize>,
+    fuzzy: Option<bool>,
+) -> Result<PromptSearchResult, String> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+    tauri::async_runtime::spawn_blocking(move || {
+        with_index(&app, |conn| {
+            let indexed: i64 = conn
+                .query_row("SELECT COUNT(*) FROM prompts", [], |row| row.get(0))
+                .map_err(|e| format!("search prompts failed: {}", e))?;
+            let terms = parse_query(&query);
+            if terms.is_empty() {
+                return Ok(PromptSearchResult {
+                    hits: Vec::new(),
+                    mode: "exact".to_string(),
+                    indexed: indexed as u64,
+                });
+            }
+            let mut mode = "exact";
+            let mut hits = Vec::new();
+            if !fuzzy.unwrap_or(false) {
+                hits = exact(conn, &terms, limit)
+                    .map_err(|e| format!("search prompts failed: {}", e))?;
+            }
+            if hits.is_empty() {
+                mode = "fuzzy";
+                hits = self::fuzzy(conn, &terms.join(" "), limit)
+                    .map_err(|e| format!("search prompts failed: {}", e))?;
+            }
+            Ok(PromptSearchResult {
+                hits,
+                mode: mode.to_string(),
+                indexed: indexed as u64,
+            })
+        })
+    })
+    .await
+    .map_err(|e| format!("search prompts failed: {}", e))?
+}
+
+// 清空后从头重建，用于索引文件损坏或手动排查
+#[tauri::command]
+pub async fn rebuild_prompt_index(app: tauri::AppHandle) -> Result<usize, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        with_index(&app, |conn| {
+            conn.execute_batch("DELETE FROM prompts; DELETE FROM meta;")
+                .map_err(|e| format!("reset prompt index failed: {}", e))
+        })?;
+        let written = sync(&app)?;
+        app.state::<LogState>()
+            .log_app("INFO", &format!("Prompt index rebuilt rows={}", written));
+        Ok(written)
+    })
+    .await
+    .map_err(|e| format!("rebuild prompt index failed: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn index() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(SCHEMA).unwrap();
+        conn
+    }
+
+    fn row(id: i64, prompt: &str, status: &str) -> TaskRow {
+        TaskRow {
+            id,
+            task_id: format!("task-{}", id),
+            prompt: prompt.to_string(),
+            prompt_original: String::new(),
+            prompt_optimized: String::new(),
+            provider: "gemini".to_string(),
+            model_id: String::new(),
+            status: status.to_string(),
+            created_at: String::new(),
+        }
+    }
+
+    fn ids(hits: &[PromptHit]) -> Vec<&str> {
+        hits.iter().map(|h| h.task_id.as_str()).collect()
+    }
+
+    #[test]
+    fn parse_query_splits_words_and_phrases() {
+        assert_eq!(
+            parse_query(r#"red  "sleepy cat" sofa"#),
+            vec!["red", "sleepy cat", "sofa"]
+        );
+        assert_eq!(parse_query(r#""unclosed phrase "#), vec!["unclosed phrase"]);
+        assert!(parse_query(r#"  "" "#).is_empty());
+    }
+
+    #[test]
+    fn exact_matches_phrases_and_short_terms() {
+        let mut conn = index();
+        write_rows(
+            &mut conn,
+            &[
+                row(1, "a red cat on a sofa", "completed"),
+                row(2, "blue dog in the park", "completed"),
+                row(3, "窗边的橘猫", "completed"),
+            ],
+            0,
+            true,
+        )
+        .unwrap();
+        let search = |terms: &[&str]| {
+            let terms: Vec<String> = terms.iter().map(|t| t.to_string()).collect();
+            exact(&conn, &terms, 10).unwrap()
+        };
+        assert_eq!(ids(&search(&["red cat"])), vec!["task-1"]);
+        assert!(search(&["cat red"]).is_empty());
+        assert!(search(&["red", "dog"]).is_empty());
+        assert_eq!(ids(&search(&["橘猫"])), vec!["task-3"]);
+        assert_eq!(ids(&search(&["park", "in"])), vec!["task-2"]);
+    }
+
+    #[test]
+    fn fuzzy_tolerates_typos() {
+        let mut conn = index();
+        write_rows(
+            &mut conn,
+            &[
+                row(1, "a red cat on a sofa", "completed"),
+                row(2, "blue dog in the park", "completed"),
+            ],
+            0,
+            true,
+        )
+        .unwrap();
+        let hits = fuzzy(&conn, "red cat on a sofx", 10).unwrap();
+        assert_eq!(ids(&hits), vec!["task-1"]);
+        assert!(hits[0].score >= FUZZY_THRESHOLD);
+        assert!(fuzzy(&conn, "zzzzzz", 10).unwrap().is_empty());
+    }
+
+    #[test]
+    fn write_rows_stops_cursor_before_unfinished_tasks() {
+        let mut conn = index();
+        let settled = write_rows(
+            &mut conn,
+            &[
+                row(1, "first", "completed"),
+                row(2, "second", "processing"),
+                row(3, "third", "completed"),
+            ],
+            0,
+            true,
+        )
+        .unwrap();
+        assert!(!settled);
+        assert_eq!(meta(&conn, "cursor"), 1);
+
+        // 前一批已有未结束任务，后续批次只写入不推进进度
+        let settled = write_rows(&mut conn, &[row(4, "fourth", "completed")], 3, false).unwrap();
+        assert!(!settled);
+        assert_eq!(meta(&conn, "cursor"), 1);
+
+        // 任务结束后从进度处重新读取，全部结束时推进到最后一条
+        let settled = write_rows(
+            &mut conn,
+            &[
+                row(2, "second, optimized", "completed"),
+                row(3, "third", "completed"),
+                row(4, "fourth", "completed"),
+            ],
+            1,
+            true,
+        )
+        .unwrap();
+        assert!(settled);
+        assert_eq!(meta(&conn, "cursor"), 4);
+        let count: i64 = conn
+            .query_row("SELECT COUNT(*) FROM prompts", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(count, 4);
+        assert_eq!(
+            ids(&exact(&conn, &["optimized".to_string()], 10).unwrap()),
+            vec!["task-2"]
+        );
+    }
+}
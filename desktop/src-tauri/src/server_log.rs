@@ -0,0 +1,66 @@
+use tauri::State;
+
+use crate::settings::SettingsState;
+use crate::{LogState, RotationPolicy};
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct ServerLogSettings {
+    // 单个 server.log 超过该大小即归档
+    pub max_file_mb: u64,
+    // 每天零点后的第一条日志另起新文件，便于按日期附到问题反馈里
+    pub daily: bool,
+    // 最多保留的归档文件数
+    pub keep_files: usize,
+    // 归档超过这么多天后删除，0 表示只按数量清理
+    pub retention_days: u32,
+}
+
+impl Default for ServerLogSettings {
+    fn default() -> Self {
+        Self {
+            max_file_mb: 5,
+            daily: true,
+            keep_files: 10,
+            retention_days: 14,
+        }
+    }
+}
+
+impl ServerLogSettings {
+    pub fn policy(&self) -> RotationPolicy {
+        RotationPolicy {
+            max_bytes: self.max_file_mb.clamp(1, 512) * 1024 * 1024,
+            keep: self.keep_files.clamp(1, 100),
+            daily: self.daily,
+            max_age_days: self.retention_days,
+        }
+    }
+}
+
+// 启动时在设置加载后调用，把保存的轮转策略应用到 sidecar 日志
+pub fn apply(log_state: &LogState, settings: &ServerLogSettings) {
+    log_state.server.set_policy(settings.policy());
+}
+
+#[tauri::command]
+pub fn get_server_log_settings(state: State<'_, SettingsState>) -> ServerLogSettings {
+    state.get().server_log
+}
+
+#[tauri::command]
+pub fn set_server_log_settings(
+    state: State<'_, SettingsState>,
+    log_state: State<'_, LogState>,
+    settings: ServerLogSettings,
+) -> Result<ServerLogSettings, String> {
+    let saved = state.update(|s| s.server_log = settings)?.server_log;
+    apply(&log_state, &saved);
+    Ok(saved)
+}
+
+// 当前正在写入的 sidecar 日志文件，用户反馈问题时直接附上
+#[tauri::command]
+pub fn get_backend_log_path(log_state: State<'_, LogState>) -> String {
+    log_state.server.path.to_string_lossy().to_string()
+}
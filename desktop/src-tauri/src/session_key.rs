@@ -0,0 +1,121 @@
+use std::sync::Mutex;
+
+use tauri::Manager;
+
+use crate::LogState;
+
+// 与后端 internal/api/session_key.go 约定的请求头
+pub const KEY_HEADER: &str = "X-Session-Api-Key";
+pub const PROVIDER_HEADER: &str = "X-Session-Api-Provider";
+
+const MAX_KEY_LEN: usize = 512;
+
+// 只保存在内存里：不写设置、不写日志、不传给前端，退出应用即失效
+static SESSION_KEY: Mutex<Option<SessionKey>> = Mutex::new(None);
+
+struct SessionKey {
+    // 为空时对所有 Provider 生效
+    provider: String,
+    key: String,
+    set_at: u128,
+}
+
+#[derive(serde::Serialize)]
+pub struct SessionKeyStatus {
+    provider: Option<String>,
+    // 只返回首尾几位，方便确认用的是哪个 Key
+    masked: String,
+    set_at: u128,
+}
+
+fn mask(key: &str) -> String {
+    let chars: Vec<char> = key.chars().collect();
+    if chars.len() <= 8 {
+        return "*".repeat(chars.len());
+    }
+    let head: String = chars[..4].iter().collect();
+    let tail: String = chars[chars.len() - 4..].iter().collect();
+    format!("{}…{}", head, tail)
+}
+
+fn status() -> Option<SessionKeyStatus> {
+    SESSION_KEY
+        .lock()
+        .unwrap()
+        .as_ref()
+        .map(|session| SessionKeyStatus {
+            provider: (!session.provider.is_empty()).then(|| session.provider.clone()),
+            masked: mask(&session.key),
+            set_at: session.set_at,
+        })
+}
+
+// 代理转发时注入的请求头；前端自己带的同名请求头在转发时会被去掉
+pub fn headers() -> Vec<(&'static str, String)> {
+    let guard = SESSION_KEY.lock().unwrap();
+    let Some(session) = guard.as_ref() else {
+        return Vec::new();
+    };
+    let mut headers = vec![(KEY_HEADER, session.key.clone())];
+    if !session.provider.is_empty() {
+        headers.push((PROVIDER_HEADER, session.provider.clone()));
+    }
+    headers
+}
+
+pub fn is_session_header(name: &str) -> bool {
+    name.eq_ignore_ascii_case(KEY_HEADER) || name.eq_ignore_ascii_case(PROVIDER_HEADER)
+}
+
+// 本次运行临时使用的 API Key（在别人电脑上演示、用客户的 Key 计费），不覆盖已保存的配置；
+// 经壳层代理注入到发往后端的请求里，provider 为空时对所有 Provider 生效
+#[tauri::command]
+pub fn set_session_api_key(
+    app: tauri::AppHandle,
+    provider: Option<String>,
+    key: String,
+) -> Result<SessionKeyStatus, String> {
+    let key = key.trim().to_string();
+    if key.is_empty() {
+        return Err("api key is empty".to_string());
+    }
+    // 会原样写进请求头，拒绝换行等控制字符
+    if key.len() > MAX_KEY_LEN || key.chars().any(|c| c.is_control() || !c.is_ascii()) {
+        return Err("invalid api key".to_string());
+    }
+    let provider = provider.unwrap_or_default().trim().to_ascii_lowercase();
+    if !provider
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(format!("invalid provider: {}", provider));
+    }
+    app.state::<LogState>().log_app(
+        "INFO",
+        &format!(
+            "Session API key set provider={}",
+            if provider.is_empty() { "*" } else { &provider }
+        ),
+    );
+    *SESSION_KEY.lock().unwrap() = Some(SessionKey {
+        provider,
+        key,
+        set_at: crate::now_ms(),
+    });
+    status().ok_or_else(|| "session api key not set".to_string())
+}
+
+#[tauri::command]
+pub fn clear_session_api_key(app: tauri::AppHandle) -> bool {
+    let cleared = SESSION_KEY.lock().unwrap().take().is_some();
+    if cleared {
+        app.state::<LogState>()
+            .log_app("INFO", "Session API key cleared");
+    }
+    cleared
+}
+
+#[tauri::command]
+pub fn get_session_api_key() -> Option<SessionKeyStatus> {
+    status()
+}
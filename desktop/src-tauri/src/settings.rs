@@ -0,0 +1,104 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use crate::bandwidth::BandwidthSettings;
+use crate::clipboard_watch::ClipboardWatchSettings;
+use crate::clock::TimeSettings;
+use crate::disk::DiskGuardSettings;
+use crate::feedback::FeedbackSettings;
+use crate::health::HealthSettings;
+use crate::hooks::HookSettings;
+use crate::queue::ConcurrencySettings;
+use crate::remote_backend::BackendEndpointSettings;
+use crate::remote_export::RemoteTargetSettings;
+use crate::server_log::ServerLogSettings;
+use crate::shutdown::ShutdownSettings;
+use crate::sidecar_config::SidecarConfig;
+use crate::sound::SoundSettings;
+use crate::timelapse::TimelapseSettings;
+use crate::usage::UsageSettings;
+use crate::webview::ExternalLinkSettings;
+
+// 壳层设置：只保存 Rust 侧在前端加载前就需要读到的开关，前端 UI 偏好仍留在 localStorage
+#[derive(Clone, Default, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct ShellSettings {
+    pub external_links: ExternalLinkSettings,
+    // 隐藏开关：打包版允许打开 WebView 调试器，没有 UI 入口，仅供排查前端问题
+    pub devtools_enabled: bool,
+    pub sounds: SoundSettings,
+    pub feedback: FeedbackSettings,
+    pub disk_guard: DiskGuardSettings,
+    pub usage: UsageSettings,
+    pub bandwidth: BandwidthSettings,
+    pub concurrency: ConcurrencySettings,
+    pub timelapse: TimelapseSettings,
+    pub server_log: ServerLogSettings,
+    pub shutdown: ShutdownSettings,
+    pub backend_endpoint: BackendEndpointSettings,
+    pub sidecar: SidecarConfig,
+    pub hooks: HookSettings,
+    pub health: HealthSettings,
+    pub remote_targets: RemoteTargetSettings,
+    pub time: TimeSettings,
+    pub clipboard_watch: ClipboardWatchSettings,
+    // 上次启动的应用版本，用于识别“刚更新完”的首次启动
+    pub last_app_version: Option<String>,
+}
+
+#[derive(Clone)]
+pub struct SettingsState {
+    path: PathBuf,
+    inner: Arc<Mutex<ShellSettings>>,
+}
+
+impl SettingsState {
+    pub fn load(app: &tauri::AppHandle) -> Self {
+        let path = crate::app_data_base(app).join("shell_settings.json");
+        // 旧版本在 Linux 上把设置写在 app_data_dir（~/.local/share），新位置还没有文件时沿用旧设置
+        let legacy = crate::paths::app_data_dir(app).join("shell_settings.json");
+        let settings = if !path.exists() && legacy.exists() {
+            read_json_or_default(&legacy)
+        } else {
+            read_json_or_default(&path)
+        };
+        Self {
+            path,
+            inner: Arc::new(Mutex::new(settings)),
+        }
+    }
+
+    pub fn get(&self) -> ShellSettings {
+        self.inner.lock().unwrap().clone()
+    }
+
+    pub fn update<F: FnOnce(&mut ShellSettings)>(&self, f: F) -> Result<ShellSettings, String> {
+        let mut guard = self.inner.lock().unwrap();
+        let mut next = guard.clone();
+        f(&mut next);
+        write_json_atomic(&self.path, &next)?;
+        *guard = next.clone();
+        Ok(next)
+    }
+}
+
+// 配置/索引类小文件统一走“读失败即默认值”，文件损坏时不阻塞启动
+pub fn read_json_or_default<T: serde::de::DeserializeOwned + Default>(path: &Path) -> T {
+    fs::read(path)
+        .ok()
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .unwrap_or_default()
+}
+
+// 先写临时文件再 rename，避免写到一半崩溃留下半截 JSON
+pub fn write_json_atomic<T: serde::Serialize>(path: &Path, value: &T) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("create settings dir failed: {}", e))?;
+    }
+    let bytes =
+        serde_json::to_vec_pretty(value).map_err(|e| format!("serialize json failed: {}", e))?;
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, bytes).map_err(|e| format!("write json failed: {}", e))?;
+    fs::rename(&tmp, path).map_err(|e| format!("finalize json failed: {}", e))
+}
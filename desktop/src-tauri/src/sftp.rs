@@ -0,0 +1,165 @@
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use ssh2::{OpenFlags, OpenType, Session, Sftp};
+
+use crate::remote_export::RemoteTarget;
+
+const CHUNK_SIZE: usize = 256 * 1024;
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(15);
+// 单次读写超时，毫秒
+const IO_TIMEOUT_MS: u32 = 60_000;
+// 连接断开后重连续传的次数，间隔 2s / 4s / 8s ...
+const MAX_ATTEMPTS: u32 = 5;
+
+fn connect(target: &RemoteTarget, secret: &str) -> Result<Session, String> {
+    let url = reqwest::Url::parse(target.endpoint.trim())
+        .map_err(|e| format!("invalid endpoint {}: {}", target.endpoint, e))?;
+    let host = url
+        .host_str()
+        .ok_or_else(|| format!("endpoint has no host: {}", target.endpoint))?;
+    let addr = (host, url.port().unwrap_or(22))
+        .to_socket_addrs()
+        .map_err(|e| format!("resolve {} failed: {}", host, e))?
+        .next()
+        .ok_or_else(|| format!("resolve {} failed", host))?;
+    let tcp = TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT)
+        .map_err(|e| format!("connect {} failed: {}", addr, e))?;
+    let mut session = Session::new().map_err(|e| format!("create ssh session failed: {}", e))?;
+    session.set_tcp_stream(tcp);
+    session.set_timeout(IO_TIMEOUT_MS);
+    session
+        .handshake()
+        .map_err(|e| format!("ssh handshake failed: {}", e))?;
+    let user = target.username.trim();
+    let key_path = target.key_path.trim();
+    // 配置了私钥时钥匙串里存的是私钥口令（可为空），否则是登录密码
+    let auth = if key_path.is_empty() {
+        session.userauth_password(user, secret)
+    } else {
+        session.userauth_pubkey_file(
+            user,
+            None,
+            Path::new(key_path),
+            (!secret.is_empty()).then_some(secret),
+        )
+    };
+    auth.map_err(|e| format!("ssh authentication failed: {}", e))?;
+    if !session.authenticated() {
+        return Err("ssh authentication failed".to_string());
+    }
+    Ok(session)
+}
+
+// 前缀以 / 开头时按绝对路径处理，否则相对登录后的主目录
+pub fn remote_path(target: &RemoteTarget, key: &str) -> PathBuf {
+    if target.prefix.trim().starts_with('/') {
+        PathBuf::from(format!("/{}", key))
+    } else {
+        PathBuf::from(key)
+    }
+}
+
+fn mkdirs(sftp: &Sftp, dir: &Path) -> Result<(), String> {
+    let mut current = PathBuf::new();
+    for part in dir.components() {
+        current.push(part);
+        if current.as_os_str().is_empty() || current == Path::new("/") {
+            continue;
+        }
+        if sftp.stat(&current).is_err() {
+            sftp.mkdir(&current, 0o755)
+                .map_err(|e| format!("create remote dir {} failed: {}", current.display(), e))?;
+        }
+    }
+    Ok(())
+}
+
+// 先写 <name>.part，断线后重连时从已有的 .part 大小处续传，完整后再改名
+fn upload_once(
+    session: &Session,
+    local: &Path,
+    remote: &Path,
+    total: u64,
+    progress: &impl Fn(u64),
+) -> Result<(), String> {
+    let sftp = session
+        .sftp()
+        .map_err(|e| format!("open sftp channel failed: {}", e))?;
+    if let Some(parent) = remote.parent() {
+        mkdirs(&sftp, parent)?;
+    }
+    let part = PathBuf::from(format!("{}.part", remote.display()));
+    let mut offset = sftp
+        .stat(&part)
+        .ok()
+        .and_then(|s| s.size)
+        .filter(|size| *size <= total)
+        .unwrap_or(0);
+    let mut remote_file = sftp
+        .open_mode(
+            &part,
+            OpenFlags::WRITE | OpenFlags::CREATE,
+            0o644,
+            OpenType::File,
+        )
+        .map_err(|e| format!("open remote file failed: {}", e))?;
+    remote_file
+        .seek(SeekFrom::Start(offset))
+        .map_err(|e| format!("seek remote file failed: {}", e))?;
+    let mut local_file = File::open(local).map_err(|e| format!("open image failed: {}", e))?;
+    local_file
+        .seek(SeekFrom::Start(offset))
+        .map_err(|e| format!("seek image failed: {}", e))?;
+    progress(offset);
+
+    let mut buf = vec![0u8; CHUNK_SIZE];
+    loop {
+        let read = local_file
+            .read(&mut buf)
+            .map_err(|e| format!("read image failed: {}", e))?;
+        if read == 0 {
+            break;
+        }
+        remote_file
+            .write_all(&buf[..read])
+            .map_err(|e| format!("write remote file failed: {}", e))?;
+        offset += read as u64;
+        progress(offset);
+    }
+    drop(remote_file);
+
+    // 不是所有服务器都支持覆盖式 rename，先删掉旧文件
+    let _ = sftp.unlink(remote);
+    sftp.rename(&part, remote, None)
+        .map_err(|e| format!("finalize remote file failed: {}", e))
+}
+
+// 阻塞执行，调用方需放在后台线程；失败时重连并从断点续传
+pub fn upload(
+    target: &RemoteTarget,
+    secret: &str,
+    local: &Path,
+    remote: &Path,
+    progress: impl Fn(u64),
+) -> Result<(), String> {
+    let total = std::fs::metadata(local)
+        .map_err(|e| format!("read image failed: {}", e))?
+        .len();
+    let mut attempt = 1;
+    loop {
+        let result = connect(target, secret)
+            .and_then(|session| upload_once(&session, local, remote, total, &progress));
+        match result {
+            Ok(()) => return Ok(()),
+            Err(err) if attempt >= MAX_ATTEMPTS => return Err(err),
+            Err(_) => {
+                std::thread::sleep(Duration::from_secs(1 << attempt));
+                attempt += 1;
+            }
+        }
+    }
+}
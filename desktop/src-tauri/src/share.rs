@@ -0,0 +1,303 @@
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{Ipv4Addr, TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use hmac::{Hmac, Mac};
+use rusqlite::OptionalExtension;
+use sha2::Sha256;
+use tauri::Manager;
+
+use crate::LogState;
+
+const SHARE_PREFIX: &str = "/share/";
+const DEFAULT_TTL_SECS: u64 = 60 * 60;
+const MAX_TTL_SECS: u64 = 7 * 24 * 60 * 60;
+const MAX_HEADER_LINES: usize = 64;
+// 请求行加全部请求头的上限，端口对整个局域网开放
+const MAX_REQUEST_BYTES: u64 = 8 * 1024;
+const MAX_CONNECTIONS: usize = 16;
+
+// 签名密钥只在内存里，应用重启或撤销后所有链接失效
+static SECRET: Mutex<Option<[u8; 32]>> = Mutex::new(None);
+// 分享端口单独监听 0.0.0.0，只响应 /share/，后端接口仍然只在本机可见
+static LISTENER_PORT: Mutex<u16> = Mutex::new(0);
+// 撤销时递增，旧的监听线程发现代数变化后退出并释放端口
+static LISTENER_GENERATION: AtomicU64 = AtomicU64::new(0);
+static ACTIVE_CONNECTIONS: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(serde::Serialize)]
+pub struct ShareLink {
+    url: String,
+    expires_at: u128,
+}
+
+// 取不到随机数时不能用全零密钥签名，否则任何人都能伪造链接
+fn current_secret() -> Result<[u8; 32], String> {
+    let mut secret = SECRET.lock().unwrap();
+    if let Some(secret) = *secret {
+        return Ok(secret);
+    }
+    let new = crate::instance::random_bytes::<32>()?;
+    *secret = Some(new);
+    Ok(new)
+}
+
+fn sign(image_id: &str, expires_at: u128) -> Result<String, String> {
+    let secret = current_secret()?;
+    let mut mac = Hmac::<Sha256>::new_from_slice(&secret).expect("hmac accepts any key length");
+    mac.update(format!("{}.{}", image_id, expires_at).as_bytes());
+    Ok(mac.finalize().into_bytes()[..16]
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect())
+}
+
+fn valid_id(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= 128
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+// token 格式：<image_id>.<过期毫秒>.<签名>
+fn verify(token: &str) -> Result<String, (u16, &'static str)> {
+    let mut parts = token.splitn(3, '.');
+    let (Some(id), Some(expires), Some(signature)) = (parts.next(), parts.next(), parts.next())
+    else {
+        return Err((403, "invalid link"));
+    };
+    let expires_at: u128 = expires.parse().map_err(|_| (403, "invalid link"))?;
+    if !valid_id(id) {
+        return Err((403, "invalid link"));
+    }
+    let expected = sign(id, expires_at).map_err(|_| (403, "invalid link"))?;
+    if !crate::instance::constant_time_eq(expected.as_bytes(), signature.as_bytes()) {
+        return Err((403, "invalid link"));
+    }
+    if expires_at <= crate::now_ms() {
+        return Err((410, "link expired"));
+    }
+    Ok(id.to_string())
+}
+
+fn image_path(app: &tauri::AppHandle, image_id: &str) -> Option<PathBuf> {
+    let conn = crate::db::open_readonly(app).ok()?;
+    let raw: Option<String> = conn
+        .query_row(
+            "SELECT local_path FROM tasks \
+             WHERE task_id = ?1 AND deleted_at IS NULL AND status = 'completed'",
+            [image_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .ok()
+        .flatten()?;
+    crate::storage::resolve_db_path(&crate::app_data_base(app), &raw?).filter(|p| p.is_file())
+}
+
+fn content_type(path: &std::path::Path) -> &'static str {
+    match path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_ascii_lowercase())
+        .as_deref()
+    {
+        Some("png") => "image/png",
+        Some("jpg") | Some("jpeg") => "image/jpeg",
+        Some("webp") => "image/webp",
+        Some("gif") => "image/gif",
+        _ => "application/octet-stream",
+    }
+}
+
+fn write_status(stream: &mut TcpStream, status: u16, message: &str) {
+    let reason = match status {
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        410 => "Gone",
+        _ => "Bad Request",
+    };
+    let _ = write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: text/plain; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        reason,
+        message.len(),
+        message
+    );
+    let _ = stream.flush();
+}
+
+fn handle_connection(app: &tauri::AppHandle, mut client: TcpStream) {
+    let _ = client.set_read_timeout(Some(Duration::from_secs(10)));
+    let Ok(clone) = client.try_clone() else {
+        return;
+    };
+    let mut reader = BufReader::new(clone.take(MAX_REQUEST_BYTES));
+    let mut request_line = String::new();
+    if reader.read_line(&mut request_line).unwrap_or(0) == 0 {
+        return;
+    }
+    // 只看请求行，请求头读完丢弃
+    for _ in 0..MAX_HEADER_LINES {
+        let mut line = String::new();
+        if reader.read_line(&mut line).unwrap_or(0) == 0 || line.trim().is_empty() {
+            break;
+        }
+    }
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or("");
+    let target = parts.next().unwrap_or("/");
+    let path = target.split('?').next().unwrap_or(target);
+    let Some(token) = path.strip_prefix(SHARE_PREFIX) else {
+        write_status(&mut client, 404, "not found");
+        return;
+    };
+    if method != "GET" && method != "HEAD" {
+        write_status(&mut client, 405, "method not allowed");
+        return;
+    }
+    let image_id = match verify(token) {
+        Ok(id) => id,
+        Err((status, message)) => {
+            write_status(&mut client, status, message);
+            return;
+        }
+    };
+    let Some(file) = image_path(app, &image_id) else {
+        write_status(&mut client, 404, "image not found");
+        return;
+    };
+    let Some((mut source, length)) = std::fs::File::open(&file)
+        .ok()
+        .and_then(|f| f.metadata().ok().map(|m| (f, m.len())))
+    else {
+        write_status(&mut client, 404, "image not found");
+        return;
+    };
+    let head = format!(
+        "HTTP/1.1 200 OK\r\nContent-Type: {}\r\nContent-Length: {}\r\nCache-Control: private, no-store\r\nConnection: close\r\n\r\n",
+        content_type(&file),
+        length
+    );
+    // 原图直接从文件流式写出，不整张读进内存
+    let sent = client.write_all(head.as_bytes()).and_then(|_| {
+        if method == "HEAD" {
+            Ok(0)
+        } else {
+            std::io::copy(&mut source, &mut client)
+        }
+    });
+    if let Ok(sent) = sent {
+        crate::bandwidth::record(app, "share", sent, 0);
+        app.state::<LogState>()
+            .log_app("INFO", &format!("Shared image served id={}", image_id));
+    }
+}
+
+// 第一次创建链接时才开始监听，不分享就不对局域网暴露端口
+fn ensure_listener(app: &tauri::AppHandle) -> Result<u16, String> {
+    let mut port = LISTENER_PORT.lock().unwrap();
+    if *port != 0 {
+        return Ok(*port);
+    }
+    let listener =
+        TcpListener::bind("0.0.0.0:0").map_err(|e| format!("bind share listener failed: {}", e))?;
+    *port = listener
+        .local_addr()
+        .map_err(|e| format!("bind share listener failed: {}", e))?
+        .port();
+    let generation = LISTENER_GENERATION.load(Ordering::SeqCst);
+    let app_handle = app.clone();
+    std::thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            if LISTENER_GENERATION.load(Ordering::SeqCst) != generation {
+                break;
+            }
+            // 超出并发上限的连接直接关闭
+            let Some(slot) =
+                crate::proxy::ConnectionSlot::try_acquire(&ACTIVE_CONNECTIONS, MAX_CONNECTIONS)
+            else {
+                continue;
+            };
+            let app_handle = app_handle.clone();
+            std::thread::spawn(move || {
+                let _slot = slot;
+                handle_connection(&app_handle, stream)
+            });
+        }
+    });
+    app.state::<LogState>()
+        .log_app("INFO", &format!("Share listener started port={}", *port));
+    Ok(*port)
+}
+
+// 为单张图片生成局域网访问链接，ttl 为秒（默认 1 小时，最长 7 天）
+#[tauri::command]
+pub fn create_share_link(
+    app: tauri::AppHandle,
+    image_id: String,
+    ttl: Option<u64>,
+) -> Result<ShareLink, String> {
+    let image_id = image_id.trim().to_string();
+    if !valid_id(&image_id) {
+        return Err(format!("invalid image id: {}", image_id));
+    }
+    if image_path(&app, &image_id).is_none() {
+        return Err(format!("image not found: {}", image_id));
+    }
+    let ip = crate::lan::lan_ip().ok_or_else(|| "no LAN address found".to_string())?;
+    let port = ensure_listener(&app)?;
+    let ttl = ttl.unwrap_or(DEFAULT_TTL_SECS).clamp(1, MAX_TTL_SECS);
+    let expires_at = crate::now_ms() + ttl as u128 * 1000;
+    let token = format!(
+        "{}.{}.{}",
+        image_id,
+        expires_at,
+        sign(&image_id, expires_at)?
+    );
+    app.state::<LogState>().log_app(
+        "INFO",
+        &format!("Share link created id={} ttl={}s", image_id, ttl),
+    );
+    Ok(ShareLink {
+        url: format!(
+            "http://{}{}{}",
+            std::net::SocketAddr::new(ip, port),
+            SHARE_PREFIX,
+            token
+        ),
+        expires_at,
+    })
+}
+
+// 关闭分享端口：accept 会一直阻塞，先递增代数再自己连一次把监听线程唤醒
+fn stop_listener(app: &tauri::AppHandle) {
+    let mut port = LISTENER_PORT.lock().unwrap();
+    if *port == 0 {
+        return;
+    }
+    LISTENER_GENERATION.fetch_add(1, Ordering::SeqCst);
+    let _ = TcpStream::connect_timeout(
+        &std::net::SocketAddr::new(Ipv4Addr::LOCALHOST.into(), *port),
+        Duration::from_secs(1),
+    );
+    app.state::<LogState>()
+        .log_app("INFO", &format!("Share listener stopped port={}", *port));
+    *port = 0;
+}
+
+// 更换签名密钥并关闭分享端口，已经发出的链接全部失效
+#[tauri::command]
+pub fn revoke_share_links(app: tauri::AppHandle) {
+    // 下次创建链接时重新生成密钥
+    *SECRET.lock().unwrap() = None;
+    stop_listener(&app);
+    app.state::<LogState>()
+        .log_app("INFO", "All share links revoked");
+}
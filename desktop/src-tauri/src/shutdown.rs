@@ -0,0 +1,291 @@
+use std::path::PathBuf;
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+use tauri::{Manager, State};
+use tauri_plugin_shell::process::CommandChild;
+
+use crate::settings::SettingsState;
+use crate::LogState;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+// 退出时最多等这么久，避免用户配置过大导致应用“关不掉”
+const MAX_GRACE_MS: u64 = 30_000;
+// 拉起 sidecar 时写入，正常退出时删除；启动时仍存在说明上次会话异常退出
+const PID_FILE: &str = "sidecar.pid";
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct ShutdownSettings {
+    // 发出终止信号后等待 sidecar 自行退出（落盘、关闭数据库）的时长，超时强制结束
+    pub grace_ms: u64,
+}
+
+impl Default for ShutdownSettings {
+    fn default() -> Self {
+        Self { grace_ms: 3000 }
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct PidRecord {
+    pid: u32,
+    // 拉起它的壳层进程；壳层仍在运行说明是另一个正常实例的后端，不能动
+    parent_pid: u32,
+    started_at: u128,
+}
+
+// 后台执行的辅助命令在 Windows 上不弹出控制台窗口，其他平台不做处理
+pub fn hide_console(command: &mut Command) -> &mut Command {
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+        command.creation_flags(CREATE_NO_WINDOW);
+    }
+    command
+}
+
+#[cfg(unix)]
+pub fn request_stop(pid: u32) -> Result<(), String> {
+    if unsafe { libc::kill(pid as libc::pid_t, libc::SIGTERM) } != 0 {
+        return Err(format!(
+            "send SIGTERM failed: {}",
+            std::io::Error::last_os_error()
+        ));
+    }
+    Ok(())
+}
+
+// Windows 没有 SIGTERM；不带 /F 的 taskkill 发送关闭请求，无窗口的控制台进程会拒绝，随后走强制结束
+#[cfg(windows)]
+pub fn request_stop(pid: u32) -> Result<(), String> {
+    let status = hide_console(Command::new("taskkill").args(["/PID", &pid.to_string()]))
+        .status()
+        .map_err(|e| format!("taskkill failed: {}", e))?;
+    if !status.success() {
+        return Err(format!("taskkill exited with {}", status));
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn is_alive(pid: u32) -> bool {
+    unsafe { libc::kill(pid as libc::pid_t, 0) == 0 }
+}
+
+#[cfg(windows)]
+fn is_alive(pid: u32) -> bool {
+    use windows_sys::Win32::Foundation::CloseHandle;
+    use windows_sys::Win32::System::Threading::{
+        GetExitCodeProcess, OpenProcess, PROCESS_QUERY_LIMITED_INFORMATION,
+    };
+    const STILL_ACTIVE: u32 = 259;
+
+    unsafe {
+        let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, pid);
+        if handle.is_null() {
+            return false;
+        }
+        let mut code = 0u32;
+        let ok = GetExitCodeProcess(handle, &mut code) != 0;
+        CloseHandle(handle);
+        ok && code == STILL_ACTIVE
+    }
+}
+
+#[cfg(unix)]
+fn force_kill(pid: u32) -> Result<(), String> {
+    if unsafe { libc::kill(pid as libc::pid_t, libc::SIGKILL) } != 0 {
+        return Err(format!(
+            "send SIGKILL failed: {}",
+            std::io::Error::last_os_error()
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(windows)]
+fn force_kill(pid: u32) -> Result<(), String> {
+    let status = hide_console(Command::new("taskkill").args(["/F", "/PID", &pid.to_string()]))
+        .status()
+        .map_err(|e| format!("taskkill failed: {}", e))?;
+    if !status.success() {
+        return Err(format!("taskkill exited with {}", status));
+    }
+    Ok(())
+}
+
+// 进程的可执行文件名，用于确认 PID 没有被系统复用给其他程序
+#[cfg(unix)]
+fn process_name(pid: u32) -> Option<String> {
+    let output = Command::new("ps")
+        .args(["-p", &pid.to_string(), "-o", "comm="])
+        .output()
+        .ok()?;
+    let name = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    let name = std::path::Path::new(&name)
+        .file_name()?
+        .to_string_lossy()
+        .to_string();
+    (!name.is_empty()).then_some(name)
+}
+
+#[cfg(windows)]
+fn process_name(pid: u32) -> Option<String> {
+    // 输出形如 "server.exe","1234",...
+    let output = hide_console(Command::new("tasklist").args([
+        "/FI",
+        &format!("PID eq {}", pid),
+        "/FO",
+        "CSV",
+        "/NH",
+    ]))
+    .output()
+    .ok()?;
+    let text = String::from_utf8_lossy(&output.stdout);
+    let name = text.split(',').next()?.trim().trim_matches('"').to_string();
+    (!name.is_empty() && !name.starts_with("INFO:")).then_some(name)
+}
+
+fn pid_path(app: &tauri::AppHandle) -> PathBuf {
+    crate::app_data_base(app).join(PID_FILE)
+}
+
+pub fn record_pid(app: &tauri::AppHandle, pid: u32) {
+    let record = PidRecord {
+        pid,
+        parent_pid: std::process::id(),
+        started_at: crate::now_ms(),
+    };
+    if let Err(err) = crate::settings::write_json_atomic(&pid_path(app), &record) {
+        app.state::<LogState>()
+            .log_app("WARN", &format!("write sidecar pid file failed: {}", err));
+    }
+}
+
+pub fn clear_pid(app: &tauri::AppHandle) {
+    let _ = std::fs::remove_file(pid_path(app));
+}
+
+// 启动时、拉起 sidecar 之前调用：上次崩溃残留的 server 进程仍占着端口和数据目录，
+// 新实例会静默失败。按 PID 文件找到它，确认是 sidecar 且原壳层已退出后结束掉
+pub fn kill_orphan(app: &tauri::AppHandle) {
+    let path = pid_path(app);
+    let Some(record) = std::fs::read(&path)
+        .ok()
+        .and_then(|bytes| serde_json::from_slice::<PidRecord>(&bytes).ok())
+    else {
+        return;
+    };
+    let log = app.state::<LogState>();
+    if record.parent_pid != std::process::id() && is_alive(record.parent_pid) {
+        log.log_app(
+            "INFO",
+            &format!(
+                "Sidecar pid={} belongs to running instance pid={}, leaving it",
+                record.pid, record.parent_pid
+            ),
+        );
+        return;
+    }
+    let _ = std::fs::remove_file(&path);
+    if !is_alive(record.pid) {
+        return;
+    }
+    let Some(name) = process_name(record.pid).filter(|n| n.starts_with("server")) else {
+        log.log_app(
+            "INFO",
+            &format!("Stale sidecar pid={} reused by another process", record.pid),
+        );
+        return;
+    };
+    log.log_app(
+        "WARN",
+        &format!(
+            "Orphaned sidecar found pid={} name={} started_at={}",
+            record.pid, name, record.started_at
+        ),
+    );
+    let grace = grace_period(app);
+    if request_stop(record.pid).is_ok() {
+        let started = Instant::now();
+        while started.elapsed() < grace && is_alive(record.pid) {
+            std::thread::sleep(POLL_INTERVAL);
+        }
+    }
+    if is_alive(record.pid) {
+        if let Err(err) = force_kill(record.pid) {
+            log.log_app(
+                "ERROR",
+                &format!("Kill orphaned sidecar pid={} failed: {}", record.pid, err),
+            );
+            return;
+        }
+    }
+    log.log_app(
+        "INFO",
+        &format!("Orphaned sidecar pid={} terminated", record.pid),
+    );
+}
+
+pub fn grace_period(app: &tauri::AppHandle) -> Duration {
+    let grace_ms = app
+        .try_state::<SettingsState>()
+        .map(|s| s.get().shutdown.grace_ms)
+        .unwrap_or_else(|| ShutdownSettings::default().grace_ms);
+    Duration::from_millis(grace_ms.min(MAX_GRACE_MS))
+}
+
+// 先请求 sidecar 自行退出，宽限期内没有退出再强制结束；返回是否为正常退出
+pub fn terminate(child: CommandChild, grace: Duration, log: &LogState) -> bool {
+    let pid = child.pid();
+    if let Err(err) = request_stop(pid) {
+        log.log_app(
+            "WARN",
+            &format!("Graceful sidecar stop failed pid={}: {}", pid, err),
+        );
+    } else {
+        let started = Instant::now();
+        while started.elapsed() < grace {
+            if !is_alive(pid) {
+                log.log_app(
+                    "INFO",
+                    &format!(
+                        "Sidecar exited gracefully pid={} elapsed_ms={}",
+                        pid,
+                        started.elapsed().as_millis()
+                    ),
+                );
+                return true;
+            }
+            std::thread::sleep(POLL_INTERVAL);
+        }
+        log.log_app(
+            "WARN",
+            &format!(
+                "Sidecar did not exit within {}ms, killing pid={}",
+                grace.as_millis(),
+                pid
+            ),
+        );
+    }
+    if let Err(err) = child.kill() {
+        log.log_app("ERROR", &format!("Failed to kill sidecar: {}", err));
+    }
+    false
+}
+
+#[tauri::command]
+pub fn get_shutdown_settings(state: State<'_, SettingsState>) -> ShutdownSettings {
+    state.get().shutdown
+}
+
+#[tauri::command]
+pub fn set_shutdown_settings(
+    state: State<'_, SettingsState>,
+    mut settings: ShutdownSettings,
+) -> Result<ShutdownSettings, String> {
+    settings.grace_ms = settings.grace_ms.min(MAX_GRACE_MS);
+    Ok(state.update(|s| s.shutdown = settings)?.shutdown)
+}
@@ -0,0 +1,142 @@
+use std::collections::BTreeMap;
+use std::net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use tauri::Manager;
+use tauri_plugin_shell::process::Command;
+
+// 预分配端口的探测间隔和时限；超时后只剩 stdout 里的 SERVER_PORT= 这一条路
+const PORT_POLL_INTERVAL: Duration = Duration::from_millis(100);
+const PORT_POLL_TIMEOUT: Duration = Duration::from_secs(20);
+const PORT_CONNECT_TIMEOUT: Duration = Duration::from_millis(200);
+
+// 最近一次拉起 sidecar 时实际使用的值，供诊断命令展示
+static LAST_SPAWNED: Mutex<Option<SidecarEnvironment>> = Mutex::new(None);
+
+#[derive(Clone, serde::Serialize)]
+pub struct SidecarEnvironment {
+    // macos / windows / linux
+    pub platform: String,
+    // unix / windows
+    pub family: String,
+    pub working_dir: String,
+    // 壳层注入的环境变量，不含设置页里配置的调试开关和额外变量（见 sidecar_config.rs）
+    pub env: BTreeMap<String, String>,
+    // 壳层预先分配并通过 SERVER_PORT 传给后端的端口；分配失败时为 None，由后端自行探测
+    pub reserved_port: Option<u16>,
+}
+
+// 先绑定一个临时端口再立即释放，交给后端监听；
+// 释放到后端绑定之间被别的进程抢走时，后端会顺延到下一个端口并照常打印 SERVER_PORT=
+fn reserve_port() -> Option<u16> {
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).ok()?;
+    listener.local_addr().ok().map(|addr| addr.port())
+}
+
+fn working_dir(app: &tauri::AppHandle) -> PathBuf {
+    crate::paths::backend_data_dir(app)
+}
+
+pub fn environment(app: &tauri::AppHandle) -> SidecarEnvironment {
+    let platform = std::env::consts::OS.to_string();
+    let family = std::env::consts::FAMILY.to_string();
+    let working_dir = working_dir(app);
+    let mut env = BTreeMap::new();
+    // 后端据此判断是否作为桌面端 sidecar 运行
+    env.insert("TAURI_PLATFORM".to_string(), platform.clone());
+    env.insert("TAURI_FAMILY".to_string(), family.clone());
+    // 数据库被其他进程占用时以只读方式启动，见 safe_mode.rs
+    env.insert(
+        "DATABASE_READ_ONLY".to_string(),
+        crate::safe_mode::is_read_only(app).to_string(),
+    );
+    // 磁盘保护阈值同步给后端，保存生成结果前再检查一次剩余空间；修改后重启后端生效
+    let disk_guard = app
+        .state::<crate::settings::SettingsState>()
+        .get()
+        .disk_guard;
+    if disk_guard.enabled {
+        env.insert(
+            "STORAGE_MIN_FREE_MB".to_string(),
+            disk_guard.min_free_mb.to_string(),
+        );
+    }
+    // 受限环境下后端默认的 UserConfigDir 与壳层数据目录不一致（或不持久），显式指定工作目录
+    if crate::paths::is_confined() {
+        env.insert(
+            "APP_DATA_DIR".to_string(),
+            working_dir.to_string_lossy().to_string(),
+        );
+    }
+    SidecarEnvironment {
+        platform,
+        family,
+        working_dir: working_dir.to_string_lossy().to_string(),
+        env,
+        reserved_port: None,
+    }
+}
+
+// 组装 sidecar 启动命令：平台环境变量、工作目录，再叠加设置里的调试开关和额外参数
+pub fn command(app: &tauri::AppHandle) -> Result<Command, String> {
+    let mut environment = environment(app);
+    environment.reserved_port = reserve_port();
+    if let Some(port) = environment.reserved_port {
+        environment
+            .env
+            .insert("SERVER_PORT".to_string(), port.to_string());
+    }
+    let mut command = crate::arch::sidecar_command(app)?.envs(environment.env.clone());
+    let dir = PathBuf::from(&environment.working_dir);
+    // 目录建不出来时沿用壳层的工作目录，后端启动后会自行切换
+    if std::fs::create_dir_all(&dir).is_ok() {
+        command = command.current_dir(dir);
+    }
+    *LAST_SPAWNED.lock().unwrap() = Some(environment);
+    Ok(crate::sidecar_config::apply(app, command))
+}
+
+pub fn reserved_port() -> Option<u16> {
+    LAST_SPAWNED.lock().unwrap().as_ref()?.reserved_port
+}
+
+// 端口在拉起前就已确定：预分配端口开始接受连接即视为握手完成，不依赖 stdout 能否按时读到；
+// 后端因端口被占而顺延时仍以 SERVER_PORT= 为准
+pub fn watch_reserved_port(app: &tauri::AppHandle, generation: u64) {
+    let Some(port) = reserved_port() else {
+        return;
+    };
+    let app = app.clone();
+    let addr = SocketAddr::from((Ipv4Addr::LOCALHOST, port));
+    std::thread::spawn(move || {
+        let started = Instant::now();
+        while started.elapsed() < PORT_POLL_TIMEOUT {
+            std::thread::sleep(PORT_POLL_INTERVAL);
+            let current = *app.state::<crate::SidecarGeneration>().0.lock().unwrap();
+            let known = *app.state::<crate::BackendPort>().0.lock().unwrap();
+            if current != generation || known != 0 {
+                return;
+            }
+            if TcpStream::connect_timeout(&addr, PORT_CONNECT_TIMEOUT).is_ok() {
+                app.state::<crate::LogState>().log_app(
+                    "INFO",
+                    &format!("Reserved backend port {} is accepting connections", port),
+                );
+                crate::on_backend_port(&app, port);
+                return;
+            }
+        }
+    });
+}
+
+// 尚未拉起过 sidecar（如外部后端模式）时返回按当前状态计算的值
+#[tauri::command]
+pub fn get_sidecar_environment(app: tauri::AppHandle) -> SidecarEnvironment {
+    LAST_SPAWNED
+        .lock()
+        .unwrap()
+        .clone()
+        .unwrap_or_else(|| environment(&app))
+}
@@ -0,0 +1,79 @@
+use std::collections::BTreeMap;
+
+use tauri::{Manager, State};
+use tauri_plugin_shell::process::Command;
+
+use crate::settings::SettingsState;
+
+// 壳层自己管理的变量，不允许被用户配置覆盖
+const RESERVED_ENV: [&str; 6] = [
+    "DATABASE_READ_ONLY",
+    "STORAGE_MIN_FREE_MB",
+    "APP_DATA_DIR",
+    "TAURI_PLATFORM",
+    "TAURI_FAMILY",
+    "SERVER_PORT",
+];
+
+#[derive(Clone, Default, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct SidecarConfig {
+    // GODEBUG=http2debug=2，排查 HTTP/2 问题时打开，平时会刷满 stderr
+    pub http2_debug: bool,
+    // GIN_MODE=debug，输出路由注册和每个请求的详细日志
+    pub gin_debug: bool,
+    // 追加到命令行的参数，如 --config /path/to/config.yaml
+    pub extra_args: Vec<String>,
+    // 额外的环境变量，同名时覆盖上面两个开关
+    pub extra_env: BTreeMap<String, String>,
+}
+
+fn validate(config: &SidecarConfig) -> Result<(), String> {
+    for key in config.extra_env.keys() {
+        let key = key.trim();
+        if key.is_empty() || key.contains('=') || key.contains('\0') {
+            return Err(format!("invalid env name: {}", key));
+        }
+        if RESERVED_ENV.contains(&key) {
+            return Err(format!("{} is managed by the app", key));
+        }
+    }
+    Ok(())
+}
+
+// 每次拉起 sidecar 时读取，修改后下一次启动或重启生效
+pub fn apply(app: &tauri::AppHandle, command: Command) -> Command {
+    let config = app
+        .try_state::<SettingsState>()
+        .map(|s| s.get().sidecar)
+        .unwrap_or_default();
+    let mut command = command
+        .env(
+            "GIN_MODE",
+            if config.gin_debug { "debug" } else { "release" },
+        )
+        .args(config.extra_args.iter().filter(|a| !a.trim().is_empty()));
+    if config.http2_debug {
+        command = command.env("GODEBUG", "http2debug=2");
+    }
+    command.envs(
+        config
+            .extra_env
+            .into_iter()
+            .filter(|(k, _)| !RESERVED_ENV.contains(&k.trim())),
+    )
+}
+
+#[tauri::command]
+pub fn get_sidecar_config(state: State<'_, SettingsState>) -> SidecarConfig {
+    state.get().sidecar
+}
+
+#[tauri::command]
+pub fn set_sidecar_config(
+    state: State<'_, SettingsState>,
+    config: SidecarConfig,
+) -> Result<SidecarConfig, String> {
+    validate(&config)?;
+    Ok(state.update(|s| s.sidecar = config)?.sidecar)
+}
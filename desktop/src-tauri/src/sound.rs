@@ -0,0 +1,210 @@
+use std::path::{Path, PathBuf};
+
+use tauri::{Manager, State};
+
+use crate::settings::SettingsState;
+use crate::LogState;
+
+// 内置音效随安装包打包在资源目录的 sounds/<name>.wav，各平台听到的一致
+// pop/tink/funk 是更轻的短音，主要给拖放、导出等交互反馈使用
+const BUILTIN_SOUNDS: [&str; 6] = ["chime", "alert", "notify", "pop", "tink", "funk"];
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct EventSound {
+    pub enabled: bool,
+    // 内置音效名（chime/alert/notify）或 "custom"
+    pub sound: String,
+    pub custom_path: Option<String>,
+}
+
+impl EventSound {
+    fn builtin(sound: &str) -> Self {
+        Self {
+            enabled: true,
+            sound: sound.to_string(),
+            custom_path: None,
+        }
+    }
+}
+
+impl Default for EventSound {
+    fn default() -> Self {
+        Self::builtin("chime")
+    }
+}
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct SoundSettings {
+    pub enabled: bool,
+    pub success: EventSound,
+    pub failure: EventSound,
+    // 批量任务全部结束（队列清空）
+    pub queue_empty: EventSound,
+}
+
+impl Default for SoundSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            success: EventSound::builtin("chime"),
+            failure: EventSound::builtin("alert"),
+            queue_empty: EventSound::builtin("notify"),
+        }
+    }
+}
+
+impl SoundSettings {
+    fn for_kind(&self, kind: &str) -> Result<&EventSound, String> {
+        match kind {
+            "success" => Ok(&self.success),
+            "failure" => Ok(&self.failure),
+            "queue_empty" => Ok(&self.queue_empty),
+            _ => Err(format!("unknown sound kind: {}", kind)),
+        }
+    }
+}
+
+fn builtin_path(app: &tauri::AppHandle, name: &str) -> Result<PathBuf, String> {
+    let path = app
+        .path()
+        .resource_dir()
+        .map_err(|e| format!("resolve resource dir failed: {}", e))?
+        .join("sounds")
+        .join(format!("{}.wav", name));
+    if !path.is_file() {
+        return Err(format!("builtin sound missing: {}", path.display()));
+    }
+    Ok(path)
+}
+
+fn resolve_source(app: &tauri::AppHandle, sound: &EventSound) -> Result<PathBuf, String> {
+    if sound.sound == "custom" {
+        let path = sound
+            .custom_path
+            .as_deref()
+            .map(str::trim)
+            .filter(|p| !p.is_empty())
+            .ok_or_else(|| "custom sound path is empty".to_string())?;
+        let path = PathBuf::from(path);
+        if !path.is_file() {
+            return Err(format!("sound file not found: {}", path.display()));
+        }
+        return Ok(path);
+    }
+    if !BUILTIN_SOUNDS.contains(&sound.sound.as_str()) {
+        return Err(format!("unknown builtin sound: {}", sound.sound));
+    }
+    builtin_path(app, &sound.sound)
+}
+
+#[cfg(target_os = "macos")]
+fn play_blocking(path: &Path) -> Result<(), String> {
+    run_player("afplay", &[path.as_os_str()])
+}
+
+#[cfg(target_os = "windows")]
+fn play_blocking(path: &Path) -> Result<(), String> {
+    use windows_sys::Win32::Media::Audio::{PlaySoundW, SND_FILENAME, SND_NODEFAULT};
+
+    // PlaySound 只支持 WAV，自定义音效需要用户提供 .wav 文件
+    let target = path.to_string_lossy().to_string();
+    let wide: Vec<u16> = target.encode_utf16().chain(Some(0)).collect();
+    let ok = unsafe {
+        PlaySoundW(
+            wide.as_ptr(),
+            std::ptr::null_mut(),
+            SND_FILENAME | SND_NODEFAULT,
+        )
+    };
+    if ok == 0 {
+        return Err(format!("play sound failed: {}", target));
+    }
+    Ok(())
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn play_blocking(path: &Path) -> Result<(), String> {
+    run_player("paplay", &[path.as_os_str()])
+        .or_else(|_| run_player("aplay", &["-q".as_ref(), path.as_os_str()]))
+}
+
+#[cfg(not(target_os = "windows"))]
+fn run_player(program: &str, args: &[&std::ffi::OsStr]) -> Result<(), String> {
+    let status = std::process::Command::new(program)
+        .args(args)
+        .status()
+        .map_err(|e| format!("{} failed: {}", program, e))?;
+    if !status.success() {
+        return Err(format!("{} exited with {}", program, status));
+    }
+    Ok(())
+}
+
+// 在后台线程播放，避免阻塞命令调用方；失败只写日志
+fn play_in_background(app: &tauri::AppHandle, source: PathBuf) {
+    let app = app.clone();
+    std::thread::spawn(move || {
+        if let Err(err) = play_blocking(&source) {
+            app.state::<LogState>()
+                .log_app("WARN", &format!("play notification sound failed: {}", err));
+        }
+    });
+}
+
+// 供 Rust 侧事件（如队列清空）直接复用，按设置决定是否播放
+pub fn play_for_event(app: &tauri::AppHandle, kind: &str) -> Result<bool, String> {
+    let settings = app.state::<SettingsState>().get().sounds;
+    let sound = settings.for_kind(kind)?;
+    if !settings.enabled || !sound.enabled {
+        return Ok(false);
+    }
+    play_in_background(app, resolve_source(app, sound)?);
+    Ok(true)
+}
+
+// 直接播放内置音效，开关由调用方（如 feedback.rs）自行判断
+pub fn play_builtin(app: &tauri::AppHandle, name: &str) -> Result<(), String> {
+    let source = resolve_source(app, &EventSound::builtin(name))?;
+    play_in_background(app, source);
+    Ok(())
+}
+
+// WebView 在窗口隐藏到托盘时会被静音，所以提示音由壳层原生播放
+// 返回 false 表示该事件的音效已被关闭
+#[tauri::command]
+pub fn play_notification_sound(app: tauri::AppHandle, kind: String) -> Result<bool, String> {
+    play_for_event(&app, kind.trim())
+}
+
+// 设置页试听：不检查开关，直接播放指定音效
+#[tauri::command]
+pub fn preview_notification_sound(app: tauri::AppHandle, sound: EventSound) -> Result<(), String> {
+    play_in_background(&app, resolve_source(&app, &sound)?);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_sound_settings(state: State<'_, SettingsState>) -> SoundSettings {
+    state.get().sounds
+}
+
+#[tauri::command]
+pub fn set_sound_settings(
+    state: State<'_, SettingsState>,
+    settings: SoundSettings,
+) -> Result<(), String> {
+    for sound in [&settings.success, &settings.failure, &settings.queue_empty] {
+        if sound.enabled
+            && sound.sound == "custom"
+            && !sound
+                .custom_path
+                .as_deref()
+                .is_some_and(|p| Path::new(p).is_file())
+        {
+            return Err("custom sound file not found".to_string());
+        }
+    }
+    state.update(|s| s.sounds = settings).map(|_| ())
+}
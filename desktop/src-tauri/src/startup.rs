@@ -0,0 +1,207 @@
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use tauri::{Emitter, Manager};
+use tauri_plugin_dialog::{
+    DialogExt, MessageDialogButtons, MessageDialogKind, MessageDialogResult,
+};
+
+use crate::LogState;
+
+// 保留最近几次启动的记录，便于对比“偶尔很慢”的情况
+const MAX_HISTORY: usize = 10;
+// 超过这个时间的启动在日志里标成 WARN
+const SLOW_START: Duration = Duration::from_secs(10);
+// sidecar 拉起后这么久仍没有打印 SERVER_PORT= 就提示用户，否则界面会一直空白
+const PORT_TIMEOUT: Duration = Duration::from_secs(20);
+// 对话框里附上的 stderr 行数
+const DIALOG_STDERR_LINES: usize = 8;
+
+// 进程入口处记下的起点，所有阶段都相对它计时
+static STARTED: OnceLock<Instant> = OnceLock::new();
+static CURRENT: Mutex<StartupReport> = Mutex::new(StartupReport {
+    started_at: 0,
+    total_ms: None,
+    marks: Vec::new(),
+});
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+pub struct StartupMark {
+    name: String,
+    // 距进程启动的毫秒数
+    at_ms: u64,
+    // 距上一个阶段的毫秒数
+    delta_ms: u64,
+}
+
+#[derive(Clone, Default, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct StartupReport {
+    started_at: u128,
+    // 前端开始心跳且后端健康检查通过后才有值
+    total_ms: Option<u64>,
+    marks: Vec<StartupMark>,
+}
+
+#[derive(Default, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+struct StartupHistory {
+    runs: Vec<StartupReport>,
+}
+
+#[derive(serde::Serialize)]
+pub struct StartupTimings {
+    current: StartupReport,
+    history: Vec<StartupReport>,
+}
+
+fn history_path(app: &tauri::AppHandle) -> PathBuf {
+    crate::app_data_base(app).join("startup_timings.json")
+}
+
+// run() 第一行调用
+pub fn begin() {
+    STARTED.get_or_init(Instant::now);
+    CURRENT.lock().unwrap().started_at = crate::now_ms();
+    mark("process_start");
+}
+
+// 同名阶段只记第一次：sidecar 重启等后续事件不算进启动耗时
+pub fn mark(name: &str) {
+    let elapsed = STARTED.get_or_init(Instant::now).elapsed().as_millis() as u64;
+    let mut current = CURRENT.lock().unwrap();
+    if current.total_ms.is_some() || current.marks.iter().any(|m| m.name == name) {
+        return;
+    }
+    let previous = current.marks.last().map(|m| m.at_ms).unwrap_or(0);
+    current.marks.push(StartupMark {
+        name: name.to_string(),
+        at_ms: elapsed,
+        delta_ms: elapsed.saturating_sub(previous),
+    });
+}
+
+// 阶段打点后检查是否已经完整启动：前端首次心跳 + 后端可用
+pub fn mark_and_finish(app: &tauri::AppHandle, name: &str) {
+    mark(name);
+    let report = {
+        let mut current = CURRENT.lock().unwrap();
+        let has = |n: &str| current.marks.iter().any(|m| m.name == n);
+        if current.total_ms.is_some() || !has("frontend_ready") || !has("backend_healthy") {
+            return;
+        }
+        current.total_ms = current.marks.iter().map(|m| m.at_ms).max();
+        current.clone()
+    };
+    let total = report.total_ms.unwrap_or(0);
+    let summary: Vec<String> = report
+        .marks
+        .iter()
+        .map(|m| format!("{}=+{}ms", m.name, m.delta_ms))
+        .collect();
+    app.state::<LogState>().log_app(
+        if Duration::from_millis(total) >= SLOW_START {
+            "WARN"
+        } else {
+            "INFO"
+        },
+        &format!("Startup finished total_ms={} {}", total, summary.join(" ")),
+    );
+
+    let path = history_path(app);
+    let mut history: StartupHistory = crate::settings::read_json_or_default(&path);
+    history.runs.insert(0, report);
+    history.runs.truncate(MAX_HISTORY);
+    if let Err(err) = crate::settings::write_json_atomic(&path, &history) {
+        app.state::<LogState>()
+            .log_app("WARN", &format!("save startup timings failed: {}", err));
+    }
+}
+
+#[derive(Clone, serde::Serialize)]
+struct StartupTimeoutPayload {
+    timeout_secs: u64,
+    stderr: Vec<String>,
+}
+
+// 每次拉起 sidecar 时调用；超时后若仍是同一代进程且没拿到端口（端口被占用、配置缺失、数据库损坏等），
+// 弹出原生对话框提供重试和打开日志目录
+pub fn watch_port(app: &tauri::AppHandle, generation: u64) {
+    let app = app.clone();
+    std::thread::spawn(move || {
+        std::thread::sleep(PORT_TIMEOUT);
+        let current = *app.state::<crate::SidecarGeneration>().0.lock().unwrap();
+        let port = *app.state::<crate::BackendPort>().0.lock().unwrap();
+        if current != generation || port != 0 {
+            return;
+        }
+        let stderr = crate::backend_logs::recent(&app, "stderr", DIALOG_STDERR_LINES);
+        app.state::<LogState>().log_app(
+            "ERROR",
+            &format!(
+                "Sidecar printed no port within {}s, last stderr: {}",
+                PORT_TIMEOUT.as_secs(),
+                stderr.join(" | ")
+            ),
+        );
+        let _ = app.emit(
+            "backend-startup-timeout",
+            StartupTimeoutPayload {
+                timeout_secs: PORT_TIMEOUT.as_secs(),
+                stderr: stderr.clone(),
+            },
+        );
+        let detail = if stderr.is_empty() {
+            "后端没有输出任何错误信息。".to_string()
+        } else {
+            format!("后端最近的错误输出：\n{}", stderr.join("\n"))
+        };
+        let app_handle = app.clone();
+        app.dialog()
+            .message(format!(
+                "后端服务在 {} 秒内没有启动完成，可能是端口被占用、配置缺失或数据库损坏。\n\n{}",
+                PORT_TIMEOUT.as_secs(),
+                detail
+            ))
+            .title("后端启动超时")
+            .kind(MessageDialogKind::Error)
+            .buttons(MessageDialogButtons::YesNoCancelCustom(
+                "重试".to_string(),
+                "打开日志文件夹".to_string(),
+                "关闭".to_string(),
+            ))
+            .show_with_result(move |result| {
+                let choice = match result {
+                    MessageDialogResult::Custom(label) => label,
+                    _ => String::new(),
+                };
+                match choice.as_str() {
+                    "重试" => {
+                        let state = app_handle.state::<crate::BackendPort>();
+                        if let Err(err) = crate::restart_sidecar(app_handle.clone(), state) {
+                            app_handle
+                                .state::<LogState>()
+                                .log_app("ERROR", &format!("Sidecar retry failed: {}", err));
+                        }
+                    }
+                    "打开日志文件夹" => {
+                        let state = app_handle.state::<LogState>();
+                        if let Err(err) = crate::open_log_dir(app_handle.clone(), state) {
+                            app_handle.state::<LogState>().log_app("WARN", &err);
+                        }
+                    }
+                    _ => {}
+                }
+            });
+    });
+}
+
+#[tauri::command]
+pub fn get_startup_timings(app: tauri::AppHandle) -> StartupTimings {
+    let history: StartupHistory = crate::settings::read_json_or_default(&history_path(&app));
+    StartupTimings {
+        current: CURRENT.lock().unwrap().clone(),
+        history: history.runs,
+    }
+}
@@ -0,0 +1,403 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use image::ImageError;
+use tauri::Manager;
+
+use crate::LogState;
+
+const IMAGE_EXTENSIONS: [&str; 5] = ["png", "jpg", "jpeg", "webp", "gif"];
+
+#[derive(Clone, serde::Serialize)]
+pub struct CorruptImage {
+    path: String,
+    reason: String,
+    size_bytes: u64,
+    task_id: Option<String>,
+    source_url: Option<String>,
+    // 任务记录里有远端地址（OSS）时可以重新下载覆盖
+    recoverable: bool,
+}
+
+#[derive(Clone, serde::Serialize)]
+pub struct MissingImage {
+    path: String,
+    task_id: String,
+    source_url: Option<String>,
+    recoverable: bool,
+}
+
+#[derive(serde::Serialize)]
+pub struct StorageIntegrityReport {
+    scanned_files: usize,
+    corrupt: Vec<CorruptImage>,
+    // 数据库里有记录但文件已不存在
+    missing: Vec<MissingImage>,
+}
+
+#[derive(serde::Serialize)]
+pub struct QuarantineResult {
+    quarantine_dir: String,
+    moved: Vec<String>,
+    failed: Vec<String>,
+}
+
+struct TaskFile {
+    task_id: String,
+    source_url: Option<String>,
+}
+
+// 数据库中的路径可能是相对工作目录的 storage/xxx.jpg，也可能是绝对路径
+pub fn resolve_db_path(base: &Path, raw: &str) -> Option<PathBuf> {
+    let raw = raw.trim();
+    if raw.is_empty() {
+        return None;
+    }
+    let path = PathBuf::from(raw);
+    Some(if path.is_absolute() {
+        path
+    } else {
+        base.join(path)
+    })
+}
+
+// 只有远端地址才能用于恢复；/storage/ 这类本地访问地址指向的就是损坏文件本身
+fn remote_url(raw: &str) -> Option<String> {
+    let url = tauri::Url::parse(raw.trim()).ok()?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return None;
+    }
+    match url.host_str() {
+        Some("localhost") | Some("127.0.0.1") | Some("[::1]") | None => None,
+        Some(_) => Some(url.to_string()),
+    }
+}
+
+fn load_task_files(app: &tauri::AppHandle) -> HashMap<PathBuf, TaskFile> {
+    let mut files = HashMap::new();
+    if !crate::db::db_path(app).exists() {
+        return files;
+    }
+    let Ok(conn) = crate::db::open_readonly(app) else {
+        return files;
+    };
+    let base = crate::app_data_base(app);
+    let Ok(mut stmt) = conn.prepare(
+        "SELECT task_id, local_path, image_url, thumbnail_path, thumbnail_url \
+         FROM tasks WHERE deleted_at IS NULL AND status = 'completed'",
+    ) else {
+        return files;
+    };
+    let rows = stmt.query_map([], |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            row.get::<_, Option<String>>(1)?.unwrap_or_default(),
+            row.get::<_, Option<String>>(2)?.unwrap_or_default(),
+            row.get::<_, Option<String>>(3)?.unwrap_or_default(),
+            row.get::<_, Option<String>>(4)?.unwrap_or_default(),
+        ))
+    });
+    let Ok(rows) = rows else {
+        return files;
+    };
+    for (task_id, local, image_url, thumb, thumb_url) in rows.flatten() {
+        for (path, url) in [(local, image_url), (thumb, thumb_url)] {
+            if let Some(path) = resolve_db_path(&base, &path) {
+                files.insert(
+                    path,
+                    TaskFile {
+                        task_id: task_id.clone(),
+                        source_url: remote_url(&url),
+                    },
+                );
+            }
+        }
+    }
+    files
+}
+
+fn collect_image_files(dir: &Path, out: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        if file_type.is_dir() {
+            collect_image_files(&path, out);
+        } else if file_type.is_file() {
+            let ext = path
+                .extension()
+                .and_then(|e| e.to_str())
+                .map(|e| e.to_lowercase())
+                .unwrap_or_default();
+            if IMAGE_EXTENSIONS.contains(&ext.as_str()) {
+                out.push(path);
+            }
+        }
+    }
+}
+
+fn read_tail(file: &mut fs::File, len: u64, count: u64) -> std::io::Result<Vec<u8>> {
+    let count = count.min(len);
+    file.seek(SeekFrom::Start(len - count))?;
+    let mut buf = vec![0u8; count as usize];
+    file.read_exact(&mut buf)?;
+    Ok(buf)
+}
+
+// 只解析文件头并检查结尾标记，不做完整解码，几万张图也能较快扫完
+fn check_image(path: &Path) -> Result<(), String> {
+    let len = fs::metadata(path)
+        .map_err(|e| format!("stat failed: {}", e))?
+        .len();
+    if len == 0 {
+        return Err("empty file".to_string());
+    }
+
+    let reader = image::ImageReader::open(path)
+        .and_then(|r| r.with_guessed_format())
+        .map_err(|e| format!("open failed: {}", e))?;
+    let format = reader.format();
+    match reader.into_dimensions() {
+        Ok(_) => {}
+        // 未启用对应解码器（如 webp/gif）时只做结尾检查
+        Err(ImageError::Unsupported(_)) => {}
+        Err(e) => return Err(format!("invalid header: {}", e)),
+    }
+
+    let mut file = fs::File::open(path).map_err(|e| format!("open failed: {}", e))?;
+    let truncated = match format {
+        Some(image::ImageFormat::Png) => {
+            // 最后一个 chunk 必须是 IEND（4 字节类型 + 4 字节 CRC）
+            let tail = read_tail(&mut file, len, 8).map_err(|e| e.to_string())?;
+            !tail.starts_with(b"IEND")
+        }
+        Some(image::ImageFormat::Jpeg) => {
+            // 部分编码器会在 EOI 后补零，允许结尾有少量填充
+            let tail = read_tail(&mut file, len, 64).map_err(|e| e.to_string())?;
+            !tail.windows(2).any(|w| w == [0xFF, 0xD9])
+        }
+        Some(image::ImageFormat::WebP) => {
+            let mut header = [0u8; 8];
+            file.read_exact(&mut header).map_err(|e| e.to_string())?;
+            let riff_size = u32::from_le_bytes([header[4], header[5], header[6], header[7]]);
+            (riff_size as u64 + 8) > len
+        }
+        Some(image::ImageFormat::Gif) => {
+            let tail = read_tail(&mut file, len, 1).map_err(|e| e.to_string())?;
+            tail != [0x3B]
+        }
+        _ => return Err("unknown image format".to_string()),
+    };
+    if truncated {
+        return Err("truncated file".to_string());
+    }
+    Ok(())
+}
+
+fn scan(app: &tauri::AppHandle) -> StorageIntegrityReport {
+    let dir = crate::paths::storage_dir(app);
+    let mut files = Vec::new();
+    collect_image_files(&dir, &mut files);
+    let task_files = load_task_files(app);
+
+    let mut corrupt = Vec::new();
+    for path in &files {
+        let Err(reason) = check_image(path) else {
+            continue;
+        };
+        let task = task_files.get(path);
+        let source_url = task.and_then(|t| t.source_url.clone());
+        corrupt.push(CorruptImage {
+            path: path.to_string_lossy().to_string(),
+            reason,
+            size_bytes: fs::metadata(path).map(|m| m.len()).unwrap_or(0),
+            task_id: task.map(|t| t.task_id.clone()),
+            recoverable: source_url.is_some(),
+            source_url,
+        });
+    }
+
+    let mut missing: Vec<MissingImage> = task_files
+        .iter()
+        .filter(|(path, _)| !path.exists())
+        .map(|(path, task)| MissingImage {
+            path: path.to_string_lossy().to_string(),
+            task_id: task.task_id.clone(),
+            source_url: task.source_url.clone(),
+            recoverable: task.source_url.is_some(),
+        })
+        .collect();
+    missing.sort_by(|a, b| a.path.cmp(&b.path));
+
+    StorageIntegrityReport {
+        scanned_files: files.len(),
+        corrupt,
+        missing,
+    }
+}
+
+// 维护工具：逐个检查 storage/ 下图片的文件头与结尾标记，找出崩溃时写了一半的文件
+#[tauri::command]
+pub async fn scan_storage_integrity(
+    app: tauri::AppHandle,
+) -> Result<StorageIntegrityReport, String> {
+    let log_state = app.state::<LogState>().inner().clone();
+    let report = tauri::async_runtime::spawn_blocking(move || scan(&app))
+        .await
+        .map_err(|e| format!("scan storage failed: {}", e))?;
+    log_state.log_app(
+        "INFO",
+        &format!(
+            "Storage integrity scan finished scanned={} corrupt={} missing={}",
+            report.scanned_files,
+            report.corrupt.len(),
+            report.missing.len()
+        ),
+    );
+    Ok(report)
+}
+
+// 只允许操作 storage/ 目录内的文件，防止前端传入任意路径
+fn ensure_inside_storage(app: &tauri::AppHandle, path: &str) -> Result<PathBuf, String> {
+    let storage = crate::paths::storage_dir(app)
+        .canonicalize()
+        .map_err(|e| format!("resolve storage dir failed: {}", e))?;
+    let path = PathBuf::from(path.trim());
+    let parent = path
+        .parent()
+        .and_then(|p| p.canonicalize().ok())
+        .ok_or_else(|| format!("invalid path: {}", path.display()))?;
+    if !parent.starts_with(&storage) {
+        return Err(format!("path outside storage: {}", path.display()));
+    }
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| format!("invalid path: {}", path.display()))?;
+    Ok(parent.join(file_name))
+}
+
+// 把损坏文件移到 storage_quarantine/<时间戳>/，不直接删除，便于人工确认
+#[tauri::command]
+pub fn quarantine_storage_files(
+    app: tauri::AppHandle,
+    paths: Vec<String>,
+) -> Result<QuarantineResult, String> {
+    let storage = crate::paths::storage_dir(&app)
+        .canonicalize()
+        .map_err(|e| format!("resolve storage dir failed: {}", e))?;
+    let quarantine_dir = crate::app_data_base(&app)
+        .join("storage_quarantine")
+        .join(crate::now_ms().to_string());
+    fs::create_dir_all(&quarantine_dir)
+        .map_err(|e| format!("create quarantine dir failed: {}", e))?;
+
+    let mut moved = Vec::new();
+    let mut failed = Vec::new();
+    for raw in paths {
+        let result = ensure_inside_storage(&app, &raw).and_then(|path| {
+            // 保留子目录结构，避免不同目录下的同名文件互相覆盖
+            let relative = path.strip_prefix(&storage).unwrap_or(&path).to_path_buf();
+            let target = quarantine_dir.join(relative);
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+            }
+            fs::rename(&path, &target)
+                .or_else(|_| fs::copy(&path, &target).and_then(|_| fs::remove_file(&path)))
+                .map_err(|e| e.to_string())
+        });
+        match result {
+            Ok(()) => moved.push(raw),
+            Err(_) => failed.push(raw),
+        }
+    }
+
+    app.state::<LogState>().log_app(
+        "INFO",
+        &format!(
+            "Quarantined storage files moved={} failed={} dir={}",
+            moved.len(),
+            failed.len(),
+            quarantine_dir.display()
+        ),
+    );
+    Ok(QuarantineResult {
+        quarantine_dir: quarantine_dir.to_string_lossy().to_string(),
+        moved,
+        failed,
+    })
+}
+
+// 从任务记录里的远端地址重新下载，校验通过后再覆盖本地文件
+#[tauri::command]
+pub async fn redownload_storage_file(app: tauri::AppHandle, path: String) -> Result<(), String> {
+    let target = ensure_inside_storage(&app, &path)?;
+    let app_for_lookup = app.clone();
+    let lookup_target = target.clone();
+    let source_url = tauri::async_runtime::spawn_blocking(move || {
+        load_task_files(&app_for_lookup)
+            .into_iter()
+            .find(|(p, _)| {
+                p.parent().and_then(|d| d.canonicalize().ok()).as_deref() == lookup_target.parent()
+                    && p.file_name() == lookup_target.file_name()
+            })
+            .and_then(|(_, task)| task.source_url)
+    })
+    .await
+    .map_err(|e| format!("lookup task failed: {}", e))?
+    .ok_or_else(|| "no remote source for this file".to_string())?;
+
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(300))
+        .build()
+        .map_err(|e| format!("build download client failed: {}", e))?;
+    let response = client
+        .get(&source_url)
+        .send()
+        .await
+        .map_err(|e| format!("download request failed: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("download request failed: {}", response.status()));
+    }
+    let bytes = response
+        .bytes()
+        .await
+        .map_err(|e| format!("read download body failed: {}", e))?;
+    crate::bandwidth::record(&app, "redownload", 0, bytes.len() as u64);
+    crate::disk::ensure_free_space(&target, bytes.len() as u64)?;
+
+    let file_name = target
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("download");
+    let temp_path = target.with_file_name(format!("{}.part", file_name));
+    let result = fs::File::create(&temp_path)
+        .and_then(|mut f| f.write_all(&bytes).and_then(|_| f.flush()))
+        .map_err(|e| format!("write temp file failed: {}", e))
+        .and_then(|_| check_image(&temp_path))
+        .and_then(|_| crate::replace_file_safely(&temp_path, &target));
+    if let Err(err) = result {
+        let _ = fs::remove_file(&temp_path);
+        return Err(err);
+    }
+
+    // 同一路径的文件被替换，webview 里缓存的旧图要失效
+    if let Some(url) = crate::invalidation::url_for_file(&app, &target) {
+        crate::invalidation::invalidate(&app, Vec::new(), vec![url], "redownload");
+    }
+    app.state::<LogState>().log_app(
+        "INFO",
+        &format!(
+            "Redownloaded storage file bytes={} dest={}",
+            bytes.len(),
+            target.display()
+        ),
+    );
+    Ok(())
+}
@@ -0,0 +1,466 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+use base64::Engine;
+use image::imageops::FilterType;
+use image::DynamicImage;
+use rusqlite::OptionalExtension;
+use tauri::{Emitter, Manager, State};
+
+use crate::proxy::ProxyRequest;
+use crate::settings::SettingsState;
+use crate::{BackendPort, LogState};
+
+const FRAMES_NAME: &str = "frames.json";
+// 防御：异常的流不会无限写帧
+const MAX_FRAMES: usize = 300;
+const MAX_CAPTURE_TIME: Duration = Duration::from_secs(2 * 60 * 60);
+
+#[derive(Clone, Default, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct TimelapseSettings {
+    // 打开后跟踪每个生成任务的进度流，把中间预览和最终结果逐帧存下
+    pub capture_frames: bool,
+}
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+pub struct Frame {
+    seq: usize,
+    // 距离提交的毫秒数，合成时可按真实节奏播放
+    elapsed_ms: u64,
+    file: String,
+    final_frame: bool,
+}
+
+#[derive(Default, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+struct FrameIndex {
+    task_id: String,
+    frames: Vec<Frame>,
+}
+
+#[derive(serde::Serialize)]
+pub struct TimelapseResult {
+    path: String,
+    frames: usize,
+    duration_ms: u64,
+}
+
+fn frames_dir(app: &tauri::AppHandle, task_id: &str) -> PathBuf {
+    crate::app_data_base(app).join("timelapse").join(task_id)
+}
+
+fn valid_task_id(task_id: &str) -> bool {
+    !task_id.is_empty()
+        && task_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+// 由代理在生成请求提交成功后调用；未开启时什么都不做
+pub fn track(app: &tauri::AppHandle, request: &ProxyRequest, response_head: &[u8]) {
+    if !request.path.starts_with("/api/v1/tasks/generate") {
+        return;
+    }
+    if !app.state::<SettingsState>().get().timelapse.capture_frames {
+        return;
+    }
+    let Some(task_id) = crate::timings::task_id_from_response(response_head) else {
+        return;
+    };
+    if !valid_task_id(&task_id) {
+        return;
+    }
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(err) = capture(&app, &task_id).await {
+            app.state::<LogState>().log_app(
+                "WARN",
+                &format!("Timelapse capture stopped task={}: {}", task_id, err),
+            );
+        }
+    });
+}
+
+// 进度流里的中间预览：后端若在任务事件里附带 preview_image（base64 / data URI）
+// 或 preview_url，就逐帧保存；目前后端只推送状态，最终结果总会作为最后一帧保存
+async fn preview_bytes(
+    client: &reqwest::Client,
+    port: u16,
+    event: &serde_json::Value,
+) -> Option<Vec<u8>> {
+    if let Some(data) = event.get("preview_image").and_then(|v| v.as_str()) {
+        let data = data.split_once("base64,").map_or(data, |(_, b)| b);
+        return base64::engine::general_purpose::STANDARD
+            .decode(data.trim())
+            .ok();
+    }
+    let url = event.get("preview_url").and_then(|v| v.as_str())?;
+    let url = if url.starts_with('/') {
+        format!("http://127.0.0.1:{}{}", port, url)
+    } else {
+        url.to_string()
+    };
+    let response = client
+        .get(url)
+        .header(reqwest::header::ORIGIN, "tauri://localhost")
+        .send()
+        .await
+        .ok()?;
+    if !response.status().is_success() {
+        return None;
+    }
+    response.bytes().await.ok().map(|b| b.to_vec())
+}
+
+fn final_image(app: &tauri::AppHandle, task_id: &str) -> Option<Vec<u8>> {
+    let local_path: String = crate::db::open_readonly(app)
+        .ok()?
+        .query_row(
+            "SELECT local_path FROM tasks WHERE task_id = ?1",
+            [task_id],
+            |row| row.get::<_, Option<String>>(0),
+        )
+        .optional()
+        .ok()??
+        .unwrap_or_default();
+    let path = crate::storage::resolve_db_path(&crate::app_data_base(app), &local_path)?;
+    fs::read(path).ok()
+}
+
+struct Recorder {
+    dir: PathBuf,
+    index: FrameIndex,
+    started: Instant,
+    last_hash: Option<u64>,
+}
+
+impl Recorder {
+    // 统一转成 PNG 存盘；和上一帧完全相同的预览跳过
+    fn push(&mut self, bytes: &[u8], final_frame: bool) -> Result<(), String> {
+        use std::hash::{Hash, Hasher};
+
+        if self.index.frames.len() >= MAX_FRAMES {
+            return Ok(());
+        }
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        bytes.hash(&mut hasher);
+        let hash = hasher.finish();
+        if self.last_hash == Some(hash) && !final_frame {
+            return Ok(());
+        }
+        self.last_hash = Some(hash);
+        let img =
+            image::load_from_memory(bytes).map_err(|e| format!("decode frame failed: {}", e))?;
+        fs::create_dir_all(&self.dir).map_err(|e| format!("create frames dir failed: {}", e))?;
+        let seq = self.index.frames.len() + 1;
+        let file = format!("{:04}.png", seq);
+        crate::export::write_image(
+            &img,
+            &self.dir.join(&file),
+            crate::export::OutputFormat::Png,
+            100,
+            None,
+        )?;
+        self.index.frames.push(Frame {
+            seq,
+            elapsed_ms: self.started.elapsed().as_millis() as u64,
+            file,
+            final_frame,
+        });
+        crate::settings::write_json_atomic(&self.dir.join(FRAMES_NAME), &self.index)
+    }
+}
+
+async fn capture(app: &tauri::AppHandle, task_id: &str) -> Result<(), String> {
+    let port = *app.state::<BackendPort>().0.lock().unwrap();
+    if port == 0 {
+        return Err("backend is not running".to_string());
+    }
+    let client = reqwest::Client::builder()
+        .timeout(MAX_CAPTURE_TIME)
+        .build()
+        .map_err(|e| format!("build capture client failed: {}", e))?;
+    let mut response = client
+        .get(format!(
+            "http://127.0.0.1:{}/api/v1/tasks/{}/stream",
+            port, task_id
+        ))
+        .header(reqwest::header::ORIGIN, "tauri://localhost")
+        .send()
+        .await
+        .map_err(|e| format!("stream request failed: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("stream request failed: {}", response.status()));
+    }
+
+    let mut recorder = Recorder {
+        dir: frames_dir(app, task_id),
+        index: FrameIndex {
+            task_id: task_id.to_string(),
+            frames: Vec::new(),
+        },
+        started: Instant::now(),
+        last_hash: None,
+    };
+    // 分块可能截断多字节字符，按字节缓冲到完整事件再解码
+    let mut buffer: Vec<u8> = Vec::new();
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|e| format!("read stream failed: {}", e))?
+    {
+        buffer.extend_from_slice(&chunk);
+        // SSE 事件以空行分隔，只处理 data: 行，ping 事件的 data 为 {}
+        while let Some(end) = buffer.windows(2).position(|w| w == b"\n\n") {
+            let block: Vec<u8> = buffer.drain(..end + 2).collect();
+            let block = String::from_utf8_lossy(&block);
+            let Some(data) = block
+                .lines()
+                .find_map(|l| l.strip_prefix("data:"))
+                .map(str::trim)
+            else {
+                continue;
+            };
+            let Ok(event) = serde_json::from_str::<serde_json::Value>(data) else {
+                continue;
+            };
+            if let Some(bytes) = preview_bytes(&client, port, &event).await {
+                if let Err(err) = recorder.push(&bytes, false) {
+                    app.state::<LogState>()
+                        .log_app("WARN", &format!("Timelapse frame skipped: {}", err));
+                }
+            }
+            match event.get("status").and_then(|v| v.as_str()) {
+                Some("completed") => {
+                    if let Some(bytes) = final_image(app, task_id) {
+                        recorder.push(&bytes, true)?;
+                    }
+                    let _ = app.emit(
+                        "timelapse-captured",
+                        serde_json::json!({
+                            "task_id": task_id,
+                            "frames": recorder.index.frames.len(),
+                        }),
+                    );
+                    return Ok(());
+                }
+                // 失败的任务保留已收到的预览帧，没有帧就不留目录
+                Some("failed") => return Ok(()),
+                _ => {}
+            }
+        }
+    }
+    Ok(())
+}
+
+fn load_frames(app: &tauri::AppHandle, task_id: &str) -> Result<(PathBuf, Vec<Frame>), String> {
+    if !valid_task_id(task_id) {
+        return Err(format!("invalid task id: {}", task_id));
+    }
+    let dir = frames_dir(app, task_id);
+    let index: FrameIndex = crate::settings::read_json_or_default(&dir.join(FRAMES_NAME));
+    if index.frames.is_empty() {
+        return Err(format!("no captured frames for task: {}", task_id));
+    }
+    Ok((dir, index.frames))
+}
+
+// 每帧的显示时长：frame_ms 为 0 时按采集时的真实间隔（限制在 100ms~3s），最后一帧额外停留
+fn frame_delays(frames: &[Frame], frame_ms: u64, hold_last_ms: u64) -> Vec<u64> {
+    let mut delays: Vec<u64> = frames
+        .windows(2)
+        .map(|w| {
+            if frame_ms > 0 {
+                frame_ms
+            } else {
+                w[1].elapsed_ms
+                    .saturating_sub(w[0].elapsed_ms)
+                    .clamp(100, 3000)
+            }
+        })
+        .collect();
+    delays.push(frame_ms.max(100) + hold_last_ms);
+    delays
+}
+
+fn load_frame(dir: &Path, frame: &Frame, max_edge: u32) -> Result<DynamicImage, String> {
+    let img = crate::export::load_image(&dir.join(&frame.file))?;
+    Ok(if img.width().max(img.height()) > max_edge {
+        img.resize(max_edge, max_edge, FilterType::Lanczos3)
+    } else {
+        img
+    })
+}
+
+fn write_gif(
+    dir: &Path,
+    frames: &[Frame],
+    delays: &[u64],
+    dest: &Path,
+    max_edge: u32,
+) -> Result<(), String> {
+    use image::codecs::gif::{GifEncoder, Repeat};
+
+    let file = fs::File::create(dest).map_err(|e| format!("create gif failed: {}", e))?;
+    let mut encoder = GifEncoder::new_with_speed(std::io::BufWriter::new(file), 10);
+    encoder
+        .set_repeat(Repeat::Infinite)
+        .map_err(|e| format!("encode gif failed: {}", e))?;
+    // 中间预览和最终图尺寸可能不同，统一缩放到第一帧的尺寸
+    let mut size = None;
+    for (frame, delay) in frames.iter().zip(delays) {
+        let mut img = load_frame(dir, frame, max_edge)?;
+        let (w, h) = *size.get_or_insert((img.width(), img.height()));
+        if (img.width(), img.height()) != (w, h) {
+            img = img.resize_exact(w, h, FilterType::Lanczos3);
+        }
+        encoder
+            .encode_frame(image::Frame::from_parts(
+                img.to_rgba8(),
+                0,
+                0,
+                image::Delay::from_numer_denom_ms(*delay as u32, 1),
+            ))
+            .map_err(|e| format!("encode gif failed: {}", e))?;
+    }
+    Ok(())
+}
+
+// MP4 依赖 PATH 里的 ffmpeg，用 concat 清单给每帧指定时长
+fn write_mp4(
+    dir: &Path,
+    frames: &[Frame],
+    delays: &[u64],
+    dest: &Path,
+    max_edge: u32,
+) -> Result<(), String> {
+    let mut list = String::new();
+    for (frame, delay) in frames.iter().zip(delays) {
+        let path = dir.join(&frame.file);
+        list.push_str(&format!(
+            "file '{}'\nduration {:.3}\n",
+            path.to_string_lossy().replace('\'', "'\\''"),
+            *delay as f64 / 1000.0
+        ));
+    }
+    // concat 的最后一帧时长会被忽略，需要再列一次
+    if let Some(last) = frames.last() {
+        list.push_str(&format!(
+            "file '{}'\n",
+            dir.join(&last.file)
+                .to_string_lossy()
+                .replace('\'', "'\\''")
+        ));
+    }
+    let list_path = dir.join("concat.txt");
+    fs::write(&list_path, list).map_err(|e| format!("write frame list failed: {}", e))?;
+    let filter = format!(
+        "scale='min({0},iw)':'min({0},ih)':force_original_aspect_ratio=decrease,\
+         scale=trunc(iw/2)*2:trunc(ih/2)*2,format=yuv420p",
+        max_edge
+    );
+    let output = Command::new("ffmpeg")
+        .args([
+            "-y",
+            "-loglevel",
+            "error",
+            "-f",
+            "concat",
+            "-safe",
+            "0",
+            "-i",
+        ])
+        .arg(&list_path)
+        .args(["-vf", &filter, "-c:v", "libx264", "-movflags", "+faststart"])
+        .arg(dest)
+        .output();
+    let _ = fs::remove_file(&list_path);
+    let output = output.map_err(|e| format!("run ffmpeg failed (is it installed?): {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "ffmpeg failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_timelapse_settings(state: State<'_, SettingsState>) -> TimelapseSettings {
+    state.get().timelapse
+}
+
+#[tauri::command]
+pub fn set_timelapse_settings(
+    state: State<'_, SettingsState>,
+    settings: TimelapseSettings,
+) -> Result<TimelapseSettings, String> {
+    Ok(state.update(|s| s.timelapse = settings)?.timelapse)
+}
+
+#[tauri::command]
+pub fn list_timelapse_frames(app: tauri::AppHandle, task_id: String) -> Vec<Frame> {
+    load_frames(&app, task_id.trim())
+        .map(|(_, frames)| frames)
+        .unwrap_or_default()
+}
+
+// 把采集到的帧合成为 GIF 或 MP4（按目标扩展名），frame_ms 缺省 400，0 表示按真实节奏
+#[tauri::command]
+pub async fn assemble_timelapse(
+    app: tauri::AppHandle,
+    task_id: String,
+    path: String,
+    frame_ms: Option<u64>,
+    hold_last_ms: Option<u64>,
+    max_edge: Option<u32>,
+) -> Result<TimelapseResult, String> {
+    let dest = PathBuf::from(path.trim());
+    let ext = dest
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_ascii_lowercase())
+        .unwrap_or_default();
+    if !matches!(ext.as_str(), "gif" | "mp4") {
+        return Err(format!("unsupported timelapse format: {}", ext));
+    }
+    let (dir, frames) = load_frames(&app, task_id.trim())?;
+    let delays = frame_delays(
+        &frames,
+        frame_ms.unwrap_or(400),
+        hold_last_ms.unwrap_or(1500),
+    );
+    let max_edge = max_edge.unwrap_or(720).clamp(64, 4096);
+    let duration_ms = delays.iter().sum();
+    let frame_count = frames.len();
+    let dest_for_task = dest.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        if let Some(parent) = dest_for_task.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("create destination dir failed: {}", e))?;
+        }
+        if ext == "gif" {
+            write_gif(&dir, &frames, &delays, &dest_for_task, max_edge)
+        } else {
+            write_mp4(&dir, &frames, &delays, &dest_for_task, max_edge)
+        }
+    })
+    .await
+    .map_err(|e| format!("assemble timelapse failed: {}", e))??;
+    app.state::<LogState>().log_app(
+        "INFO",
+        &format!(
+            "Timelapse assembled frames={} dest={}",
+            frame_count,
+            dest.display()
+        ),
+    );
+    Ok(TimelapseResult {
+        path: dest.to_string_lossy().to_string(),
+        frames: frame_count,
+        duration_ms,
+    })
+}
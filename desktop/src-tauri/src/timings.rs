@@ -0,0 +1,336 @@
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use chrono::Local;
+use rusqlite::OptionalExtension;
+use tauri::{Emitter, Manager};
+
+use crate::proxy::ProxyRequest;
+use crate::LogState;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+// 超过这个时间仍未结束的任务不再跟踪（后端重启后任务可能永远停在 processing）
+const MAX_TRACK_TIME: Duration = Duration::from_secs(2 * 60 * 60);
+// 只保留最近的样本，百分位统计不需要太久远的数据
+const MAX_SAMPLES: usize = 5000;
+
+// 代理提交成功、等待结束的任务；重启后丢失无妨，后端历史里仍有完整记录
+static PENDING: Mutex<Vec<PendingTimer>> = Mutex::new(Vec::new());
+// 串行化 generation_timings.jsonl 的读写
+static SAMPLES_LOCK: Mutex<()> = Mutex::new(());
+
+struct PendingTimer {
+    task_id: String,
+    provider: String,
+    model_id: String,
+    image_size: String,
+    started: Instant,
+}
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+struct TimingSample {
+    ts: i64,
+    task_id: String,
+    provider: String,
+    model_id: String,
+    image_size: String,
+    status: String,
+    // 提交到结束；queue_ms 是在本地 Worker 池里排队的时间，run_ms 是实际调用上游的时间
+    total_ms: u64,
+    queue_ms: Option<u64>,
+    run_ms: Option<u64>,
+}
+
+#[derive(Clone, Copy, serde::Serialize)]
+pub struct Percentiles {
+    median: u64,
+    p90: u64,
+    p95: u64,
+    max: u64,
+}
+
+#[derive(serde::Serialize)]
+pub struct GroupStats {
+    provider: String,
+    model_id: String,
+    image_size: String,
+    count: usize,
+    failed: usize,
+    total: Option<Percentiles>,
+    queue: Option<Percentiles>,
+    run: Option<Percentiles>,
+}
+
+#[derive(serde::Serialize)]
+pub struct GenerationStats {
+    period: String,
+    since: i64,
+    count: usize,
+    groups: Vec<GroupStats>,
+}
+
+#[derive(Clone, serde::Serialize)]
+struct TimerPayload {
+    task_id: String,
+    status: String,
+    total_ms: u64,
+}
+
+fn samples_path(app: &tauri::AppHandle) -> PathBuf {
+    crate::app_data_base(app).join("generation_timings.jsonl")
+}
+
+fn read_samples(app: &tauri::AppHandle) -> Vec<TimingSample> {
+    let Ok(file) = fs::File::open(samples_path(app)) else {
+        return Vec::new();
+    };
+    BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .filter_map(|line| serde_json::from_str(&line).ok())
+        .collect()
+}
+
+fn append_sample(app: &tauri::AppHandle, sample: &TimingSample) -> Result<(), String> {
+    let _guard = SAMPLES_LOCK.lock().unwrap();
+    let path = samples_path(app);
+    let line = serde_json::to_string(sample).map_err(|e| format!("serialize failed: {}", e))?;
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .and_then(|mut f| writeln!(f, "{}", line))
+        .map_err(|e| format!("write timings failed: {}", e))?;
+    // 超出上限时整体重写一次，只留最近的一半余量
+    let samples = read_samples(app);
+    if samples.len() > MAX_SAMPLES {
+        let keep = &samples[samples.len() - MAX_SAMPLES / 2..];
+        let body: String = keep
+            .iter()
+            .filter_map(|s| serde_json::to_string(s).ok())
+            .map(|l| l + "\n")
+            .collect();
+        let tmp = path.with_extension("jsonl.tmp");
+        fs::write(&tmp, body)
+            .and_then(|_| fs::rename(&tmp, &path))
+            .map_err(|e| format!("compact timings failed: {}", e))?;
+    }
+    Ok(())
+}
+
+// 后端响应是 {"code":200,"data":{"task_id":"..."}}，只看首包即可
+pub fn task_id_from_response(head: &[u8]) -> Option<String> {
+    let text = String::from_utf8_lossy(head);
+    let start = text.find("\"task_id\":\"")? + "\"task_id\":\"".len();
+    let end = start + text[start..].find('"')?;
+    Some(text[start..end].to_string())
+}
+
+fn request_param(request: &ProxyRequest, json: &serde_json::Value, name: &str) -> String {
+    json.get(name)
+        .or_else(|| json.pointer(&format!("/params/{}", name)))
+        .and_then(|v| v.as_str())
+        .map(|s| s.to_string())
+        .or_else(|| request.form_field(name))
+        .unwrap_or_default()
+        .trim()
+        .to_string()
+}
+
+// 由代理在生成请求提交成功后调用，开始计时
+pub fn track(app: &tauri::AppHandle, request: &ProxyRequest, response_head: &[u8]) {
+    if !request.path.starts_with("/api/v1/tasks/generate") {
+        return;
+    }
+    let Some(task_id) = task_id_from_response(response_head) else {
+        return;
+    };
+    let json: serde_json::Value = serde_json::from_slice(&request.body).unwrap_or_default();
+    let timer = PendingTimer {
+        provider: request_param(request, &json, "provider"),
+        model_id: request_param(request, &json, "model_id"),
+        image_size: request_param(request, &json, "image_size"),
+        started: Instant::now(),
+        task_id,
+    };
+    let _ = app.emit(
+        "task-timer-started",
+        TimerPayload {
+            task_id: timer.task_id.clone(),
+            status: "pending".to_string(),
+            total_ms: 0,
+        },
+    );
+    PENDING.lock().unwrap().push(timer);
+}
+
+type TaskRow = (String, Option<f64>, Option<f64>, Option<f64>);
+
+// 时长用数据库里的时间戳计算，轮询间隔不影响精度
+fn query_task(conn: &rusqlite::Connection, task_id: &str) -> Option<TaskRow> {
+    conn.query_row(
+        "SELECT status, \
+         (julianday(completed_at) - julianday(created_at)) * 86400000.0, \
+         (julianday(processing_started_at) - julianday(created_at)) * 86400000.0, \
+         (julianday(completed_at) - julianday(processing_started_at)) * 86400000.0 \
+         FROM tasks WHERE task_id = ?1",
+        [task_id],
+        |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+    )
+    .optional()
+    .ok()
+    .flatten()
+}
+
+// 已提交、尚未结束的生成任务数，本地并发上限按它计算
+pub fn active_count() -> usize {
+    PENDING.lock().unwrap().len()
+}
+
+fn poll(app: &tauri::AppHandle) {
+    let ids: Vec<String> = PENDING
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|t| t.task_id.clone())
+        .collect();
+    if ids.is_empty() {
+        return;
+    }
+    let Ok(conn) = crate::db::open_readonly(app) else {
+        return;
+    };
+    // 查询期间任务仍留在列表里，并发计数不会短暂归零
+    let mut finished_rows = Vec::new();
+    for id in ids {
+        if let Some(row) = query_task(&conn, &id)
+            .filter(|(status, ..)| status != "pending" && status != "processing")
+        {
+            finished_rows.push((id, row));
+        }
+    }
+    let mut finished = Vec::new();
+    let expired = {
+        let mut pending = PENDING.lock().unwrap();
+        let before = pending.len();
+        pending.retain(|t| t.started.elapsed() < MAX_TRACK_TIME);
+        for (id, row) in finished_rows {
+            if let Some(pos) = pending.iter().position(|t| t.task_id == id) {
+                finished.push((pending.remove(pos), row));
+            }
+        }
+        pending.len() + finished.len() < before
+    };
+    if expired || !finished.is_empty() {
+        crate::queue::notify_slots(app);
+    }
+    if !finished.is_empty() {
+        crate::search::notify();
+    }
+    for (timer, (status, total, queue, run)) in finished {
+        let to_ms = |v: Option<f64>| v.filter(|v| *v >= 0.0).map(|v| v.round() as u64);
+        let sample = TimingSample {
+            ts: Local::now().timestamp_millis(),
+            task_id: timer.task_id,
+            provider: timer.provider,
+            model_id: timer.model_id,
+            image_size: timer.image_size,
+            // 失败的任务可能没有 completed_at，退回壳层自己的计时
+            total_ms: to_ms(total).unwrap_or_else(|| timer.started.elapsed().as_millis() as u64),
+            queue_ms: to_ms(queue),
+            run_ms: to_ms(run),
+            status,
+        };
+        if let Err(err) = append_sample(app, &sample) {
+            app.state::<LogState>()
+                .log_app("WARN", &format!("record generation timing failed: {}", err));
+        }
+        if sample.status == "completed" {
+            crate::hooks::fire_generation_completed(app, &sample.task_id, sample.total_ms);
+        }
+        let _ = app.emit(
+            "task-timer-finished",
+            TimerPayload {
+                task_id: sample.task_id,
+                status: sample.status,
+                total_ms: sample.total_ms,
+            },
+        );
+    }
+}
+
+pub fn start_monitor(app: &tauri::AppHandle) {
+    let app = app.clone();
+    std::thread::spawn(move || loop {
+        std::thread::sleep(POLL_INTERVAL);
+        poll(&app);
+    });
+}
+
+// 最近邻取法，样本少时也不会插值出不存在的时长
+fn percentiles(mut values: Vec<u64>) -> Option<Percentiles> {
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_unstable();
+    let at =
+        |q: f64| values[((values.len() as f64 * q).ceil() as usize).clamp(1, values.len()) - 1];
+    Some(Percentiles {
+        median: at(0.5),
+        p90: at(0.9),
+        p95: at(0.95),
+        max: values[values.len() - 1],
+    })
+}
+
+// 按模型和尺寸分组统计耗时，period 与 get_usage_stats 相同
+#[tauri::command]
+pub async fn get_generation_stats(
+    app: tauri::AppHandle,
+    period: String,
+) -> Result<GenerationStats, String> {
+    let since = crate::usage::period_start(&period)?;
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut groups: BTreeMap<(String, String, String), Vec<TimingSample>> = BTreeMap::new();
+        for sample in read_samples(&app).into_iter().filter(|s| s.ts >= since) {
+            groups
+                .entry((
+                    sample.provider.clone(),
+                    sample.model_id.clone(),
+                    sample.image_size.clone(),
+                ))
+                .or_default()
+                .push(sample);
+        }
+        let groups: Vec<GroupStats> = groups
+            .into_iter()
+            .map(|((provider, model_id, image_size), samples)| {
+                // 失败往往很快返回，会拉低耗时，百分位只统计成功的任务
+                let ok: Vec<&TimingSample> =
+                    samples.iter().filter(|s| s.status == "completed").collect();
+                GroupStats {
+                    provider,
+                    model_id,
+                    image_size,
+                    count: samples.len(),
+                    failed: samples.len() - ok.len(),
+                    total: percentiles(ok.iter().map(|s| s.total_ms).collect()),
+                    queue: percentiles(ok.iter().filter_map(|s| s.queue_ms).collect()),
+                    run: percentiles(ok.iter().filter_map(|s| s.run_ms).collect()),
+                }
+            })
+            .collect();
+        GenerationStats {
+            period,
+            since,
+            count: groups.iter().map(|g| g.count).sum(),
+            groups,
+        }
+    })
+    .await
+    .map_err(|e| format!("read generation stats failed: {}", e))
+}
@@ -0,0 +1,234 @@
+use tauri::{Emitter, Manager};
+
+use crate::LogState;
+
+#[derive(Clone, Default, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct ToastAction {
+    // open / reply
+    pub action: String,
+    pub task_id: Option<String>,
+    // 通知里输入框的内容，只有 reply 才有
+    pub input: Option<String>,
+}
+
+fn show_main_window(app: &tauri::AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+}
+
+// 通知被点击或在输入框里回复后调用：回复内容按上次的参数直接在后台生成，其余情况打开主窗口
+#[cfg_attr(not(any(windows, target_os = "linux")), allow(dead_code))]
+fn on_activated(app: &tauri::AppHandle, mut action: ToastAction) {
+    action.input = action
+        .input
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty());
+    match (action.action.as_str(), action.input.clone()) {
+        ("reply", Some(prompt)) => {
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                match crate::quick_capture::submit_prompt(&app, &prompt, Vec::new()).await {
+                    Ok(()) => app
+                        .state::<LogState>()
+                        .log_app("INFO", "Follow-up prompt from notification submitted"),
+                    Err(err) => {
+                        app.state::<LogState>().log_app(
+                            "WARN",
+                            &format!("submit follow-up from notification failed: {}", err),
+                        );
+                        show_main_window(&app);
+                    }
+                }
+            });
+        }
+        _ => show_main_window(app),
+    }
+    let _ = app.emit("toast-action", action);
+}
+
+#[cfg(windows)]
+mod platform {
+    use tauri::Manager;
+    use windows::core::{Interface, HSTRING};
+    use windows::Data::Xml::Dom::XmlDocument;
+    use windows::Foundation::TypedEventHandler;
+    use windows::UI::Notifications::{
+        ToastActivatedEventArgs, ToastNotification, ToastNotificationManager,
+    };
+
+    use super::ToastAction;
+
+    const INPUT_ID: &str = "followUp";
+
+    fn escape(text: &str) -> String {
+        text.replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+            .replace('"', "&quot;")
+            .replace('\'', "&apos;")
+    }
+
+    // 激活参数用 JSON，回调里原样解析回 ToastAction
+    fn arguments(action: &str, task_id: Option<&str>) -> String {
+        let action = ToastAction {
+            action: action.to_string(),
+            task_id: task_id.map(|s| s.to_string()),
+            input: None,
+        };
+        escape(&serde_json::to_string(&action).unwrap_or_default())
+    }
+
+    // 应用运行期间由 Activated 回调接收；应用退出后点击通知只会打开应用，不带参数
+    pub fn show(
+        app: &tauri::AppHandle,
+        title: &str,
+        body: &str,
+        task_id: Option<&str>,
+    ) -> windows::core::Result<()> {
+        let open = arguments("open", task_id);
+        let reply = arguments("reply", task_id);
+        let xml = format!(
+            r#"<toast launch="{open}" activationType="foreground"><visual><binding template="ToastGeneric"><text>{title}</text><text>{body}</text></binding></visual><actions><input id="{INPUT_ID}" type="text" placeHolderContent="输入提示词，沿用上次的模型和尺寸继续生成"/><action content="生成" arguments="{reply}" hint-inputId="{INPUT_ID}" activationType="foreground"/><action content="打开" arguments="{open}" activationType="foreground"/></actions></toast>"#,
+            title = escape(title),
+            body = escape(body),
+        );
+        let doc = XmlDocument::new()?;
+        doc.LoadXml(&HSTRING::from(xml))?;
+        let toast = ToastNotification::CreateToastNotification(&doc)?;
+        let app_for_event = app.clone();
+        toast.Activated(&TypedEventHandler::new(move |_, args| {
+            let args: ToastActivatedEventArgs = args.ok()?.cast()?;
+            let mut action: ToastAction =
+                serde_json::from_str(&args.Arguments()?.to_string()).unwrap_or_default();
+            if let Ok(value) = args
+                .UserInput()
+                .and_then(|inputs| inputs.Lookup(&HSTRING::from(INPUT_ID)))
+            {
+                action.input = HSTRING::try_from(&value).ok().map(|s| s.to_string());
+            }
+            super::on_activated(&app_for_event, action);
+            Ok(())
+        }))?;
+        ToastNotificationManager::CreateToastNotifierWithId(&HSTRING::from(
+            app.config().identifier.as_str(),
+        ))?
+        .Show(&toast)
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use std::collections::BTreeMap;
+    use std::sync::Mutex;
+
+    use notify_rust::{Hint, Notification};
+
+    use super::ToastAction;
+
+    // task_id -> 通知 id：同一任务的进度更新和最终结果替换原通知，而不是一条条叠加
+    static IDS: Mutex<BTreeMap<String, u32>> = Mutex::new(BTreeMap::new());
+
+    fn supports_actions() -> bool {
+        notify_rust::get_capabilities()
+            .map(|caps| caps.iter().any(|c| c == "actions"))
+            .unwrap_or(false)
+    }
+
+    // 直接走 org.freedesktop.Notifications；进度用 value 提示（KDE 显示为进度条，GNOME 忽略）
+    pub fn show(
+        app: &tauri::AppHandle,
+        title: &str,
+        body: &str,
+        task_id: Option<&str>,
+        progress: Option<u8>,
+    ) -> Result<(), String> {
+        let mut notification = Notification::new();
+        notification
+            .appname(&app.package_info().name)
+            .summary(title)
+            .body(body);
+        let previous_id = task_id.and_then(|id| IDS.lock().unwrap().get(id).copied());
+        if let Some(id) = previous_id {
+            notification.id(id);
+        }
+        // 进度通知不带按钮，避免替换时旧通知的监听线程重复触发
+        let with_actions = progress.is_none() && supports_actions();
+        if let Some(percent) = progress {
+            notification.hint(Hint::CustomInt(
+                "value".to_string(),
+                percent.min(100) as i32,
+            ));
+        } else if with_actions {
+            notification
+                .action("default", "打开")
+                .action("open", "查看");
+        }
+        let handle = notification
+            .show()
+            .map_err(|e| format!("show notification failed: {}", e))?;
+        if let Some(task_id) = task_id {
+            let mut ids = IDS.lock().unwrap();
+            if progress.is_some() {
+                ids.insert(task_id.to_string(), handle.id());
+            } else {
+                ids.remove(task_id);
+            }
+        }
+        if !with_actions {
+            return Ok(());
+        }
+        let app = app.clone();
+        let task_id = task_id.map(|s| s.to_string());
+        // wait_for_action 会一直阻塞到通知被点击或关闭
+        std::thread::spawn(move || {
+            handle.wait_for_action(|action| {
+                if action == "__closed" {
+                    return;
+                }
+                super::on_activated(
+                    &app,
+                    ToastAction {
+                        action: "open".to_string(),
+                        task_id,
+                        input: None,
+                    },
+                );
+            });
+        });
+        Ok(())
+    }
+}
+
+// Windows 上发出带输入框和“生成 / 打开”按钮的通知，Linux 上经 D-Bus 发出带按钮和进度提示的通知，
+// 返回 true，操作结果通过 toast-action 事件告知前端；其他平台返回 false，由前端继续走普通通知。
+// progress（0-100）目前只有 Linux 使用，同一 task_id 的通知会原地更新
+#[tauri::command]
+pub fn send_action_notification(
+    app: tauri::AppHandle,
+    title: String,
+    body: String,
+    task_id: Option<String>,
+    progress: Option<u8>,
+) -> Result<bool, String> {
+    #[cfg(windows)]
+    {
+        let _ = progress;
+        platform::show(&app, &title, &body, task_id.as_deref())
+            .map_err(|e| format!("show toast failed: {}", e))?;
+        Ok(true)
+    }
+    #[cfg(target_os = "linux")]
+    {
+        platform::show(&app, &title, &body, task_id.as_deref(), progress)?;
+        Ok(true)
+    }
+    #[cfg(not(any(windows, target_os = "linux")))]
+    {
+        let _ = (app, title, body, task_id, progress);
+        Ok(false)
+    }
+}
@@ -0,0 +1,392 @@
+use std::sync::Mutex;
+
+use tauri::image::Image;
+use tauri::menu::{IconMenuItem, IsMenuItem, Menu, MenuItem, PredefinedMenuItem, Submenu};
+use tauri::tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent};
+use tauri::Manager;
+
+use crate::{BackendLifecycle, GenerationState, LogState};
+
+const TRAY_ID: &str = "main";
+const TOOLTIP: &str = "大香蕉 AI";
+// Windows 任务栏叠加图标的尺寸
+#[cfg(windows)]
+const OVERLAY_SIZE: u32 = 16;
+
+// 上一次绘制的状态和主题，没变化时不重复生成图标
+static LAST_ICON: Mutex<Option<(TrayStatus, bool)>> = Mutex::new(None);
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum TrayStatus {
+    Idle,
+    Generating,
+    Error,
+    Paused,
+}
+
+// 暂停优先于其他状态：暂停期间队列不会推进，后端异常也要等恢复后才有影响
+fn status(app: &tauri::AppHandle) -> TrayStatus {
+    if crate::queue::is_paused(app) {
+        return TrayStatus::Paused;
+    }
+    if crate::backend_status(app).state == BackendLifecycle::Crashed {
+        return TrayStatus::Error;
+    }
+    let generating = app
+        .try_state::<GenerationState>()
+        .and_then(|s| s.0.lock().ok().map(|g| *g))
+        .unwrap_or(false);
+    if generating {
+        TrayStatus::Generating
+    } else {
+        TrayStatus::Idle
+    }
+}
+
+fn show_main_window(app: &tauri::AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+}
+
+// 暂停时整体变灰，不加角标
+fn badge_color(status: TrayStatus) -> Option<[u8; 3]> {
+    match status {
+        TrayStatus::Generating => Some([0x3b, 0x82, 0xf6]),
+        TrayStatus::Error => Some([0xef, 0x44, 0x44]),
+        TrayStatus::Idle | TrayStatus::Paused => None,
+    }
+}
+
+// 跟随系统主题：托盘和任务栏没有自己的主题，取主窗口的
+fn dark_theme(app: &tauri::AppHandle) -> bool {
+    app.get_webview_window("main")
+        .and_then(|w| w.theme().ok())
+        .is_some_and(|t| t == tauri::Theme::Dark)
+}
+
+// 角标外圈用菜单栏底色描边，深浅色菜单栏上都能和图标本身区分开
+fn draw_badge(
+    rgba: &mut [u8],
+    width: u32,
+    center: (f32, f32),
+    radius: f32,
+    color: [u8; 3],
+    dark: bool,
+) {
+    let ring = if dark {
+        [0x1f, 0x1f, 0x1f]
+    } else {
+        [0xff, 0xff, 0xff]
+    };
+    let ring_width = (radius * 0.3).max(1.0);
+    for (i, px) in rgba.chunks_exact_mut(4).enumerate() {
+        let x = (i as u32 % width) as f32 + 0.5;
+        let y = (i as u32 / width) as f32 + 0.5;
+        let distance = ((x - center.0).powi(2) + (y - center.1).powi(2)).sqrt();
+        let fill = if distance <= radius {
+            color
+        } else if distance <= radius + ring_width {
+            ring
+        } else {
+            continue;
+        };
+        px[..3].copy_from_slice(&fill);
+        px[3] = 0xff;
+    }
+}
+
+// 暂停时托盘图标变灰，生成中 / 后端异常时右下角加彩色角标，一眼就能看出当前状态
+fn icon(app: &tauri::AppHandle, status: TrayStatus) -> Option<Image<'static>> {
+    let base = app.default_window_icon()?;
+    let mut rgba = base.rgba().to_vec();
+    if status == TrayStatus::Paused {
+        for px in rgba.chunks_exact_mut(4) {
+            let gray = (px[0] as u32 * 30 + px[1] as u32 * 59 + px[2] as u32 * 11) / 100;
+            let gray = gray as u8;
+            px[0] = gray;
+            px[1] = gray;
+            px[2] = gray;
+            px[3] = (px[3] as u32 * 3 / 5) as u8;
+        }
+    }
+    if let Some(color) = badge_color(status) {
+        let (width, height) = (base.width(), base.height());
+        let radius = width.min(height) as f32 * 0.2;
+        let ring = (radius * 0.3).max(1.0);
+        let center = (width as f32 - radius - ring, height as f32 - radius - ring);
+        draw_badge(&mut rgba, width, center, radius, color, dark_theme(app));
+    }
+    Some(Image::new_owned(rgba, base.width(), base.height()))
+}
+
+fn tooltip(status: TrayStatus) -> String {
+    match status {
+        TrayStatus::Idle => TOOLTIP.to_string(),
+        TrayStatus::Generating => format!("{}（生成中）", TOOLTIP),
+        TrayStatus::Error => format!("{}（后端异常）", TOOLTIP),
+        TrayStatus::Paused => format!("{}（生成队列已暂停）", TOOLTIP),
+    }
+}
+
+// Dock（macOS）显示角标文字，任务栏（Windows）显示叠加图标
+fn update_window_badge(app: &tauri::AppHandle, status: TrayStatus, dark: bool) {
+    let Some(window) = app.get_webview_window("main") else {
+        return;
+    };
+    #[cfg(target_os = "macos")]
+    {
+        let _ = dark;
+        let label = match status {
+            TrayStatus::Error => Some("!".to_string()),
+            _ => None,
+        };
+        let _ = window.set_badge_label(label);
+    }
+    #[cfg(windows)]
+    {
+        let overlay = badge_color(status).map(|color| {
+            let mut rgba = vec![0u8; (OVERLAY_SIZE * OVERLAY_SIZE * 4) as usize];
+            let half = OVERLAY_SIZE as f32 / 2.0;
+            draw_badge(
+                &mut rgba,
+                OVERLAY_SIZE,
+                (half, half),
+                half * 0.7,
+                color,
+                dark,
+            );
+            Image::new_owned(rgba, OVERLAY_SIZE, OVERLAY_SIZE)
+        });
+        let _ = window.set_overlay_icon(overlay);
+    }
+    #[cfg(not(any(target_os = "macos", windows)))]
+    {
+        let _ = (window, status, dark);
+    }
+}
+
+// 生成状态、后端状态、队列暂停或系统主题变化时调用；只更新图标和提示文字，不重建菜单
+pub fn refresh_icon(app: &tauri::AppHandle) {
+    let Some(tray) = app.tray_by_id(TRAY_ID) else {
+        return;
+    };
+    let status = status(app);
+    let dark = dark_theme(app);
+    {
+        let mut last = LAST_ICON.lock().unwrap();
+        if *last == Some((status, dark)) {
+            return;
+        }
+        *last = Some((status, dark));
+    }
+    let _ = tray.set_icon(icon(app, status));
+    let _ = tray.set_tooltip(Some(tooltip(status)));
+    update_window_badge(app, status, dark);
+}
+
+pub fn on_window_event(app: &tauri::AppHandle, label: &str, event: &tauri::WindowEvent) {
+    if label == "main" && matches!(event, tauri::WindowEvent::ThemeChanged(_)) {
+        refresh_icon(app);
+    }
+}
+
+// 最多列出的预设数量，太长的菜单在托盘里不好用
+const MAX_PRESET_ITEMS: usize = 15;
+
+fn presets_submenu(app: &tauri::AppHandle) -> tauri::Result<Submenu<tauri::Wry>> {
+    let presets = crate::presets::list(app);
+    let items = presets
+        .iter()
+        .rev()
+        .take(MAX_PRESET_ITEMS)
+        .map(|p| MenuItem::with_id(app, format!("preset:{}", p.id), &p.name, true, None::<&str>))
+        .collect::<tauri::Result<Vec<_>>>()?;
+    let refs: Vec<&dyn IsMenuItem<tauri::Wry>> = items
+        .iter()
+        .map(|i| i as &dyn IsMenuItem<tauri::Wry>)
+        .collect();
+    Submenu::with_items(app, "套用预设", !items.is_empty(), &refs)
+}
+
+// 托盘里的提示词截短显示，完整内容在跳转列表提示和主窗口里
+const PROMPT_LABEL_CHARS: usize = 30;
+
+fn prompts_submenu(app: &tauri::AppHandle) -> tauri::Result<Submenu<tauri::Wry>> {
+    let prompts = crate::history::list(app);
+    let items = prompts
+        .iter()
+        .map(|p| {
+            let mut label: String = p.prompt.chars().take(PROMPT_LABEL_CHARS).collect();
+            if p.prompt.chars().count() > PROMPT_LABEL_CHARS {
+                label.push('…');
+            }
+            MenuItem::with_id(app, format!("prompt:{}", p.id), label, true, None::<&str>)
+        })
+        .collect::<tauri::Result<Vec<_>>>()?;
+    let refs: Vec<&dyn IsMenuItem<tauri::Wry>> = items
+        .iter()
+        .map(|i| i as &dyn IsMenuItem<tauri::Wry>)
+        .collect();
+    Submenu::with_items(app, "最近提示词", !items.is_empty(), &refs)
+}
+
+// 每张固定的图一个子菜单，缩略图放在“复制图片”项上（子菜单本身不支持图标）
+fn pins_submenu(app: &tauri::AppHandle) -> tauri::Result<Submenu<tauri::Wry>> {
+    let pins = crate::pins::list(app);
+    let items = pins
+        .iter()
+        .map(|p| {
+            let mut label: String = p.name.chars().take(PROMPT_LABEL_CHARS).collect();
+            if p.name.chars().count() > PROMPT_LABEL_CHARS {
+                label.push('…');
+            }
+            Submenu::with_items(
+                app,
+                label,
+                true,
+                &[
+                    &IconMenuItem::with_id(
+                        app,
+                        format!("pin-copy:{}", p.id),
+                        "复制图片",
+                        true,
+                        crate::pins::thumbnail(app, &p.id),
+                        None::<&str>,
+                    )?,
+                    &MenuItem::with_id(
+                        app,
+                        format!("pin-reveal:{}", p.id),
+                        "在文件夹中显示",
+                        true,
+                        None::<&str>,
+                    )?,
+                    &MenuItem::with_id(
+                        app,
+                        format!("pin-remove:{}", p.id),
+                        "取消固定",
+                        true,
+                        None::<&str>,
+                    )?,
+                ],
+            )
+        })
+        .collect::<tauri::Result<Vec<_>>>()?;
+    let refs: Vec<&dyn IsMenuItem<tauri::Wry>> = items
+        .iter()
+        .map(|i| i as &dyn IsMenuItem<tauri::Wry>)
+        .collect();
+    Submenu::with_items(app, "固定的图片", !items.is_empty(), &refs)
+}
+
+fn build_menu(app: &tauri::AppHandle, paused: bool) -> tauri::Result<Menu<tauri::Wry>> {
+    let queue_label = if paused {
+        "继续生成队列"
+    } else {
+        "暂停生成队列"
+    };
+    Menu::with_items(
+        app,
+        &[
+            &MenuItem::with_id(app, "show", "显示主窗口", true, None::<&str>)?,
+            &PredefinedMenuItem::separator(app)?,
+            &presets_submenu(app)?,
+            &prompts_submenu(app)?,
+            &pins_submenu(app)?,
+            &MenuItem::with_id(app, "toggle_queue", queue_label, true, None::<&str>)?,
+            &PredefinedMenuItem::separator(app)?,
+            &MenuItem::with_id(app, "quit", "退出", true, None::<&str>)?,
+        ],
+    )
+}
+
+fn on_menu_event(app: &tauri::AppHandle, id: &str) {
+    match id {
+        "show" => show_main_window(app),
+        "toggle_queue" => {
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                let paused = crate::queue::is_paused(&app);
+                crate::queue::set_paused(&app, !paused).await;
+            });
+        }
+        "quit" => app.exit(0),
+        _ => {
+            if let Some(preset_id) = id.strip_prefix("preset:") {
+                if let Err(err) = crate::presets::apply(app, preset_id) {
+                    app.state::<LogState>()
+                        .log_app("WARN", &format!("apply preset from tray failed: {}", err));
+                }
+            } else if let Some(prompt_id) = id.strip_prefix("prompt:") {
+                crate::history::spawn_rerun(app, prompt_id.to_string());
+            } else if let Some(pin_id) = id.strip_prefix("pin-copy:") {
+                crate::pins::spawn_copy(app, pin_id);
+            } else if let Some(pin_id) = id.strip_prefix("pin-reveal:") {
+                crate::pins::reveal(app, pin_id);
+            } else if let Some(pin_id) = id.strip_prefix("pin-remove:") {
+                crate::pins::unpin(app, pin_id);
+            }
+        }
+    }
+}
+
+pub fn init(app: &tauri::AppHandle) {
+    let paused = crate::queue::is_paused(app);
+    let status = status(app);
+    let dark = dark_theme(app);
+    let menu = match build_menu(app, paused) {
+        Ok(menu) => menu,
+        Err(err) => {
+            app.state::<LogState>()
+                .log_app("ERROR", &format!("build tray menu failed: {}", err));
+            return;
+        }
+    };
+    let mut builder = TrayIconBuilder::with_id(TRAY_ID)
+        .tooltip(tooltip(status))
+        .menu(&menu)
+        .show_menu_on_left_click(false)
+        .on_menu_event(|app, event| on_menu_event(app, event.id().as_ref()))
+        .on_tray_icon_event(|tray, event| {
+            if let TrayIconEvent::Click {
+                button: MouseButton::Left,
+                button_state: MouseButtonState::Up,
+                rect,
+                ..
+            } = event
+            {
+                // macOS 菜单栏图标左键弹出快速生成小窗，主窗口从菜单里打开
+                #[cfg(target_os = "macos")]
+                crate::quick_capture::toggle(tray.app_handle(), rect);
+                #[cfg(not(target_os = "macos"))]
+                {
+                    let _ = rect;
+                    show_main_window(tray.app_handle());
+                }
+            }
+        });
+    if let Some(icon) = icon(app, status) {
+        builder = builder.icon(icon);
+    }
+    if let Err(err) = builder.build(app) {
+        app.state::<LogState>()
+            .log_app("ERROR", &format!("create tray icon failed: {}", err));
+        return;
+    }
+    *LAST_ICON.lock().unwrap() = Some((status, dark));
+    update_window_badge(app, status, dark);
+}
+
+// 状态变化后重建菜单并更新图标/提示文字
+pub fn refresh(app: &tauri::AppHandle) {
+    let Some(tray) = app.tray_by_id(TRAY_ID) else {
+        return;
+    };
+    let paused = crate::queue::is_paused(app);
+    if let Ok(menu) = build_menu(app, paused) {
+        let _ = tray.set_menu(Some(menu));
+    }
+    refresh_icon(app);
+}
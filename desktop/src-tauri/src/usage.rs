@@ -0,0 +1,276 @@
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use chrono::{Datelike, Local, NaiveDate};
+use tauri::{Manager, State};
+use tauri_plugin_notification::NotificationExt;
+
+use crate::proxy::ProxyRequest;
+use crate::settings::SettingsState;
+use crate::LogState;
+
+// 串行化账本追加写入，代理每个连接一个线程
+static LEDGER_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct UsageSettings {
+    // 每张图的估算单价，按 model_id 覆盖；没有配置的模型用 default_image_price
+    pub default_image_price: f64,
+    pub model_prices: BTreeMap<String, f64>,
+    // 提示词优化 / 图片反推每次调用的估算费用
+    pub prompt_request_price: f64,
+    // 月度预算，0 表示不提醒；达到 warn_ratio 和 100% 时各通知一次
+    pub monthly_budget: f64,
+    pub warn_ratio: f64,
+    pub currency: String,
+}
+
+impl Default for UsageSettings {
+    fn default() -> Self {
+        Self {
+            default_image_price: 0.04,
+            model_prices: BTreeMap::new(),
+            prompt_request_price: 0.0,
+            monthly_budget: 0.0,
+            warn_ratio: 0.8,
+            currency: "USD".to_string(),
+        }
+    }
+}
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+struct LedgerEntry {
+    ts: i64,
+    // generate / prompt
+    kind: String,
+    provider: String,
+    model_id: String,
+    images: u32,
+    cost: f64,
+}
+
+#[derive(Default, serde::Serialize)]
+pub struct ModelUsage {
+    provider: String,
+    model_id: String,
+    requests: u32,
+    images: u32,
+    cost: f64,
+}
+
+#[derive(serde::Serialize)]
+pub struct UsageStats {
+    period: String,
+    // 统计起点（毫秒时间戳），all 时为 0
+    since: i64,
+    requests: u32,
+    images: u32,
+    cost: f64,
+    currency: String,
+    by_model: Vec<ModelUsage>,
+    monthly_budget: f64,
+    month_cost: f64,
+}
+
+fn ledger_path(app: &tauri::AppHandle) -> PathBuf {
+    crate::app_data_base(app).join("usage_ledger.jsonl")
+}
+
+fn read_ledger(app: &tauri::AppHandle) -> Vec<LedgerEntry> {
+    let Ok(file) = fs::File::open(ledger_path(app)) else {
+        return Vec::new();
+    };
+    // 写到一半的行直接跳过
+    BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .filter_map(|line| serde_json::from_str(&line).ok())
+        .collect()
+}
+
+// 起点按本地时间的自然日/月/年计算；0 点因夏令时不存在时由 clock::start_of_day 顺延
+pub fn period_start(period: &str) -> Result<i64, String> {
+    let today = Local::now().date_naive();
+    let start = match period {
+        "day" => Some(today),
+        "month" => today.with_day(1),
+        "year" => NaiveDate::from_ymd_opt(today.year(), 1, 1),
+        "all" => return Ok(0),
+        _ => return Err(format!("unsupported period: {}", period)),
+    };
+    Ok(start.map(crate::clock::start_of_day).unwrap_or(0))
+}
+
+fn request_field(request: &ProxyRequest, json: &serde_json::Value, name: &str) -> String {
+    json.get(name)
+        .and_then(|v| v.as_str())
+        .map(|s| s.to_string())
+        .or_else(|| request.form_field(name))
+        .unwrap_or_default()
+        .trim()
+        .to_string()
+}
+
+fn entry_for(request: &ProxyRequest, settings: &UsageSettings) -> LedgerEntry {
+    let json: serde_json::Value = serde_json::from_slice(&request.body).unwrap_or_default();
+    let provider = request_field(request, &json, "provider");
+    let mut model_id = request_field(request, &json, "model_id");
+    if model_id.is_empty() {
+        model_id = request_field(request, &json, "model");
+    }
+    let ts = Local::now().timestamp_millis();
+    if !request.path.starts_with("/api/v1/tasks/") {
+        return LedgerEntry {
+            ts,
+            kind: "prompt".to_string(),
+            provider,
+            model_id,
+            images: 0,
+            cost: settings.prompt_request_price,
+        };
+    }
+    // 文生图的 count 在 params 里，图生图是表单字段
+    let images = json
+        .pointer("/params/count")
+        .and_then(|v| v.as_f64())
+        .map(|v| v as u32)
+        .or_else(|| request.form_field("count").and_then(|v| v.trim().parse().ok()))
+        .unwrap_or(1)
+        .max(1);
+    let price = settings
+        .model_prices
+        .get(&model_id)
+        .copied()
+        .unwrap_or(settings.default_image_price);
+    LedgerEntry {
+        ts,
+        kind: "generate".to_string(),
+        provider,
+        model_id,
+        images,
+        cost: price * images as f64,
+    }
+}
+
+fn month_cost(entries: &[LedgerEntry]) -> f64 {
+    let since = period_start("month").unwrap_or(0);
+    entries
+        .iter()
+        .filter(|e| e.ts >= since)
+        .map(|e| e.cost)
+        .sum()
+}
+
+// 只在本次记账跨过阈值时通知，重启或重复统计都不会重复提醒
+fn check_budget(app: &tauri::AppHandle, settings: &UsageSettings, before: f64, after: f64) {
+    if settings.monthly_budget <= 0.0 {
+        return;
+    }
+    let budget = settings.monthly_budget;
+    let warn = budget * settings.warn_ratio.clamp(0.0, 1.0);
+    let body = if before < budget && after >= budget {
+        format!(
+            "本月估算费用 {:.2} {} 已超出预算 {:.2} {}",
+            after, settings.currency, budget, settings.currency
+        )
+    } else if warn > 0.0 && before < warn && after >= warn {
+        format!(
+            "本月估算费用 {:.2} {} 已达到预算的 {:.0}%",
+            after,
+            settings.currency,
+            settings.warn_ratio * 100.0
+        )
+    } else {
+        return;
+    };
+    app.state::<LogState>()
+        .log_app("WARN", &format!("Usage budget alert: {}", body));
+    if let Err(err) = app
+        .notification()
+        .builder()
+        .title("生成费用提醒")
+        .body(body)
+        .show()
+    {
+        app.state::<LogState>()
+            .log_app("WARN", &format!("show budget notification failed: {}", err));
+    }
+}
+
+// 由代理在计费接口成功返回后调用；任务是异步生成的，按提交时请求的张数估算
+pub fn record(app: &tauri::AppHandle, request: &ProxyRequest) {
+    let settings = app.state::<SettingsState>().get().usage;
+    let entry = entry_for(request, &settings);
+    let _guard = LEDGER_LOCK.lock().unwrap();
+    let before = month_cost(&read_ledger(app));
+    let path = ledger_path(app);
+    let line = match serde_json::to_string(&entry) {
+        Ok(line) => line,
+        Err(_) => return,
+    };
+    let written = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .and_then(|mut f| writeln!(f, "{}", line));
+    if let Err(err) = written {
+        app.state::<LogState>()
+            .log_app("WARN", &format!("write usage ledger failed: {}", err));
+        return;
+    }
+    check_budget(app, &settings, before, before + entry.cost);
+}
+
+// period: day / month / year / all
+#[tauri::command]
+pub async fn get_usage_stats(app: tauri::AppHandle, period: String) -> Result<UsageStats, String> {
+    let since = period_start(&period)?;
+    tauri::async_runtime::spawn_blocking(move || {
+        let settings = app.state::<SettingsState>().get().usage;
+        let entries = read_ledger(&app);
+        let mut by_model: BTreeMap<(String, String), ModelUsage> = BTreeMap::new();
+        for entry in entries.iter().filter(|e| e.ts >= since) {
+            let usage = by_model
+                .entry((entry.provider.clone(), entry.model_id.clone()))
+                .or_insert_with(|| ModelUsage {
+                    provider: entry.provider.clone(),
+                    model_id: entry.model_id.clone(),
+                    ..Default::default()
+                });
+            usage.requests += 1;
+            usage.images += entry.images;
+            usage.cost += entry.cost;
+        }
+        let by_model: Vec<ModelUsage> = by_model.into_values().collect();
+        UsageStats {
+            period,
+            since,
+            requests: by_model.iter().map(|m| m.requests).sum(),
+            images: by_model.iter().map(|m| m.images).sum(),
+            cost: by_model.iter().map(|m| m.cost).sum(),
+            currency: settings.currency,
+            by_model,
+            monthly_budget: settings.monthly_budget,
+            month_cost: month_cost(&entries),
+        }
+    })
+    .await
+    .map_err(|e| format!("read usage stats failed: {}", e))
+}
+
+#[tauri::command]
+pub fn get_usage_settings(state: State<'_, SettingsState>) -> UsageSettings {
+    state.get().usage
+}
+
+#[tauri::command]
+pub fn set_usage_settings(
+    state: State<'_, SettingsState>,
+    settings: UsageSettings,
+) -> Result<UsageSettings, String> {
+    Ok(state.update(|s| s.usage = settings)?.usage)
+}
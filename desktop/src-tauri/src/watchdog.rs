@@ -0,0 +1,127 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use tauri::{Manager, State};
+use tauri_plugin_dialog::{
+    DialogExt, MessageDialogButtons, MessageDialogKind, MessageDialogResult,
+};
+
+use crate::LogState;
+
+// 前端每 2 秒发一次心跳，超过这个时间没收到视为渲染进程卡死
+const FREEZE_THRESHOLD: Duration = Duration::from_secs(15);
+const CHECK_INTERVAL: Duration = Duration::from_secs(2);
+
+#[derive(Default)]
+pub struct Watchdog {
+    // 前端加载完成后才开始计时，启动阶段不算卡死
+    last_beat: Option<Instant>,
+    prompting: bool,
+}
+
+pub struct WatchdogState(pub Arc<Mutex<Watchdog>>);
+
+#[tauri::command]
+pub fn webview_heartbeat(app: tauri::AppHandle, state: State<'_, WatchdogState>) {
+    state.0.lock().unwrap().last_beat = Some(Instant::now());
+    // 首次心跳说明前端已经渲染完成，作为启动耗时的终点
+    crate::startup::mark_and_finish(&app, "frontend_ready");
+}
+
+fn reload_ui(app: &tauri::AppHandle) {
+    let Some(window) = app.get_webview_window("main") else {
+        return;
+    };
+    // 只重载 webview，sidecar 和排队中的任务不受影响；重载后重新计时，页面起不来会再次提示
+    app.state::<WatchdogState>().0.lock().unwrap().last_beat = Some(Instant::now());
+    if let Err(err) = window.reload() {
+        app.state::<LogState>()
+            .log_app("ERROR", &format!("reload webview failed: {}", err));
+    }
+}
+
+fn prompt_frozen(app: &tauri::AppHandle, stalled: Duration) {
+    app.state::<LogState>().log_app(
+        "WARN",
+        &format!("Webview unresponsive for {}s", stalled.as_secs()),
+    );
+    let app_handle = app.clone();
+    app.dialog()
+        .message(format!(
+            "界面已经 {} 秒没有响应。\n\n重新加载界面不会中断后台服务和排队中的任务；重启应用会结束当前正在进行的生成。",
+            stalled.as_secs()
+        ))
+        .title("界面无响应")
+        .kind(MessageDialogKind::Warning)
+        .buttons(MessageDialogButtons::YesNoCancelCustom(
+            "重新加载界面".to_string(),
+            "重启应用".to_string(),
+            "继续等待".to_string(),
+        ))
+        .show_with_result(move |result| {
+            let choice = match result {
+                MessageDialogResult::Custom(label) => label,
+                _ => String::new(),
+            };
+            match choice.as_str() {
+                "重新加载界面" => reload_ui(&app_handle),
+                "重启应用" => {
+                    app_handle
+                        .state::<LogState>()
+                        .log_app("WARN", "Restarting app from freeze watchdog");
+                    crate::kill_sidecar(&app_handle);
+                    app_handle.restart();
+                }
+                _ => {
+                    // 继续等待：重新计时，避免立刻再弹一次
+                    app_handle
+                        .state::<WatchdogState>()
+                        .0
+                        .lock()
+                        .unwrap()
+                        .last_beat = Some(Instant::now());
+                }
+            }
+            app_handle.state::<WatchdogState>().0.lock().unwrap().prompting = false;
+        });
+}
+
+// 窗口在前台时才检查：后台/最小化时浏览器内核会节流定时器，心跳本来就会变慢
+fn window_active(app: &tauri::AppHandle) -> bool {
+    app.get_webview_window("main")
+        .map(|w| {
+            w.is_visible().unwrap_or(false)
+                && !w.is_minimized().unwrap_or(false)
+                && w.is_focused().unwrap_or(false)
+        })
+        .unwrap_or(false)
+}
+
+pub fn start(app: &tauri::AppHandle) {
+    let app = app.clone();
+    std::thread::spawn(move || {
+        let mut was_active = false;
+        loop {
+            std::thread::sleep(CHECK_INTERVAL);
+            let active = window_active(&app);
+            let state = app.state::<WatchdogState>();
+            let mut watchdog = state.0.lock().unwrap();
+            // 刚切回前台时从现在开始计时，不把后台节流期间的间隔算进去
+            if active && !was_active && watchdog.last_beat.is_some() {
+                watchdog.last_beat = Some(Instant::now());
+            }
+            was_active = active;
+            if !active || watchdog.prompting {
+                continue;
+            }
+            let Some(stalled) = watchdog.last_beat.map(|t| t.elapsed()) else {
+                continue;
+            };
+            if stalled >= FREEZE_THRESHOLD {
+                watchdog.prompting = true;
+                drop(watchdog);
+                prompt_frozen(&app, stalled);
+            }
+        }
+    });
+}
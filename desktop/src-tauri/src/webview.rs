@@ -0,0 +1,361 @@
+use tauri::plugin::TauriPlugin;
+use tauri::{Manager, Runtime, State, Url};
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+use tauri_plugin_opener::OpenerExt;
+
+use crate::settings::SettingsState;
+use crate::LogState;
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct ExternalLinkSettings {
+    // 未知域名打开前是否弹确认框
+    pub confirm_unknown: bool,
+    pub trusted_domains: Vec<String>,
+}
+
+impl Default for ExternalLinkSettings {
+    fn default() -> Self {
+        Self {
+            confirm_unknown: true,
+            trusted_domains: vec![
+                "github.com".to_string(),
+                "githubusercontent.com".to_string(),
+            ],
+        }
+    }
+}
+
+// target=_blank 和 window.open 不会触发 on_navigation，这里统一改写成当前页导航，交给 Rust 侧拦截；
+// 同时注入 no-referrer，避免外链和远程图片请求带出本地地址与路由
+const LINK_INTERCEPT_SCRIPT: &str = r#"
+(function () {
+  var addReferrerPolicy = function () {
+    if (document.querySelector('meta[name="referrer"]')) return;
+    var meta = document.createElement('meta');
+    meta.name = 'referrer';
+    meta.content = 'no-referrer';
+    (document.head || document.documentElement).appendChild(meta);
+  };
+  if (document.documentElement) {
+    addReferrerPolicy();
+  } else {
+    document.addEventListener('DOMContentLoaded', addReferrerPolicy, { once: true });
+  }
+  var isExternal = function (href) {
+    try {
+      var u = new URL(href, window.location.href);
+      return (u.protocol === 'http:' || u.protocol === 'https:') && u.origin !== window.location.origin;
+    } catch (e) {
+      return false;
+    }
+  };
+  document.addEventListener('click', function (e) {
+    var a = e.target && e.target.closest ? e.target.closest('a[href]') : null;
+    if (!a || !isExternal(a.href)) return;
+    e.preventDefault();
+    window.location.assign(a.href);
+  }, true);
+  var originalOpen = window.open;
+  window.open = function (url) {
+    if (url && isExternal(String(url))) {
+      window.location.assign(String(url));
+      return null;
+    }
+    return originalOpen.apply(window, arguments);
+  };
+})();
+"#;
+
+fn is_local_url(url: &Url) -> bool {
+    match url.scheme() {
+        "tauri" | "asset" | "ipc" | "about" | "data" | "blob" => true,
+        "http" | "https" => matches!(
+            url.host_str(),
+            Some("localhost")
+                | Some("127.0.0.1")
+                | Some("[::1]")
+                | Some("tauri.localhost")
+                | Some("asset.localhost")
+                | Some("ipc.localhost")
+        ),
+        _ => false,
+    }
+}
+
+fn is_trusted_domain(host: &str, trusted: &[String]) -> bool {
+    let host = host.to_lowercase();
+    trusted.iter().any(|domain| {
+        let domain = domain.trim().trim_start_matches('.').to_lowercase();
+        !domain.is_empty() && (host == domain || host.ends_with(&format!(".{}", domain)))
+    })
+}
+
+fn open_externally<R: Runtime>(app: &tauri::AppHandle<R>, url: &Url) {
+    if let Err(err) = app.opener().open_url(url.as_str(), None::<String>) {
+        if let Some(log_state) = app.try_state::<LogState>() {
+            log_state.log_app("ERROR", &format!("open external link failed: {}", err));
+        }
+    }
+}
+
+fn handle_external_navigation<R: Runtime>(app: &tauri::AppHandle<R>, url: &Url) {
+    let settings = app
+        .try_state::<SettingsState>()
+        .map(|s| s.get().external_links)
+        .unwrap_or_default();
+    let host = url.host_str().unwrap_or("");
+
+    if !settings.confirm_unknown || is_trusted_domain(host, &settings.trusted_domains) {
+        open_externally(app, url);
+        return;
+    }
+
+    let app_handle = app.clone();
+    let target = url.clone();
+    app.dialog()
+        .message(format!("即将在浏览器中打开外部链接：\n{}", url))
+        .title("打开外部链接")
+        .kind(MessageDialogKind::Info)
+        .buttons(MessageDialogButtons::OkCancelCustom(
+            "打开".to_string(),
+            "取消".to_string(),
+        ))
+        .show(move |confirmed| {
+            if confirmed {
+                open_externally(&app_handle, &target);
+            }
+        });
+}
+
+// 外链拦截：阻止 webview 跳离应用页面，外部地址统一交给系统浏览器打开
+pub fn external_links_plugin<R: Runtime>() -> TauriPlugin<R> {
+    tauri::plugin::Builder::new("external-links")
+        .js_init_script(LINK_INTERCEPT_SCRIPT)
+        .on_navigation(|webview, url| {
+            if is_local_url(url) {
+                return true;
+            }
+            if matches!(url.scheme(), "http" | "https" | "mailto") {
+                handle_external_navigation(webview.app_handle(), url);
+            }
+            false
+        })
+        .build()
+}
+
+#[tauri::command]
+pub fn get_external_link_settings(state: State<'_, SettingsState>) -> ExternalLinkSettings {
+    state.get().external_links
+}
+
+#[tauri::command]
+pub fn set_external_link_settings(
+    state: State<'_, SettingsState>,
+    settings: ExternalLinkSettings,
+) -> Result<(), String> {
+    state.update(|s| s.external_links = settings).map(|_| ())
+}
+
+#[derive(serde::Serialize)]
+pub struct WebviewStorageSize {
+    cache_bytes: u64,
+    // localStorage/IndexedDB 等站点数据（前端配置存在这里，默认不清理）
+    site_data_bytes: u64,
+    cache_paths: Vec<String>,
+}
+
+#[derive(serde::Serialize)]
+pub struct ClearWebviewCacheResult {
+    freed_bytes: u64,
+    failed_paths: Vec<String>,
+}
+
+// 各平台 WebView 缓存目录不同，这里只列出可安全删除的 HTTP/代码缓存子目录
+fn webview_cache_dirs(app: &tauri::AppHandle) -> Vec<std::path::PathBuf> {
+    let mut dirs = Vec::new();
+    #[cfg(target_os = "macos")]
+    if let Ok(cache) = app.path().app_cache_dir() {
+        dirs.push(cache.join("WebKit"));
+        dirs.push(cache.join("fsCachedData"));
+    }
+    #[cfg(target_os = "windows")]
+    if let Ok(local) = app.path().app_local_data_dir() {
+        let profile = local.join("EBWebView").join("Default");
+        for name in ["Cache", "Code Cache", "GPUCache"] {
+            dirs.push(profile.join(name));
+        }
+        dirs.push(profile.join("Service Worker").join("CacheStorage"));
+    }
+    #[cfg(target_os = "linux")]
+    if let Ok(cache) = app.path().app_cache_dir() {
+        dirs.push(cache.join("WebKitCache"));
+        dirs.push(cache.join("CacheStorage"));
+    }
+    #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+    let _ = app;
+    dirs
+}
+
+fn webview_site_data_dirs(app: &tauri::AppHandle) -> Vec<std::path::PathBuf> {
+    let mut dirs = Vec::new();
+    #[cfg(target_os = "macos")]
+    if let Ok(home) = app.path().home_dir() {
+        dirs.push(
+            home.join("Library")
+                .join("WebKit")
+                .join(&app.config().identifier),
+        );
+    }
+    #[cfg(target_os = "windows")]
+    if let Ok(local) = app.path().app_local_data_dir() {
+        let profile = local.join("EBWebView").join("Default");
+        for name in ["Local Storage", "Session Storage", "IndexedDB"] {
+            dirs.push(profile.join(name));
+        }
+    }
+    #[cfg(target_os = "linux")]
+    if let Ok(data) = app.path().app_local_data_dir() {
+        // Linux 下 AppData 同时存放 storage/ 与日志，只统计 WebKitGTK 自己的子目录
+        for name in ["localstorage", "databases", "indexeddb"] {
+            dirs.push(data.join(name));
+        }
+    }
+    #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+    let _ = app;
+    dirs
+}
+
+// 获取 WebView 缓存/站点数据占用
+#[tauri::command]
+pub async fn get_webview_storage_size(app: tauri::AppHandle) -> Result<WebviewStorageSize, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let cache_dirs = webview_cache_dirs(&app);
+        WebviewStorageSize {
+            cache_bytes: cache_dirs.iter().map(|d| crate::dir_size(d)).sum(),
+            site_data_bytes: webview_site_data_dirs(&app)
+                .iter()
+                .map(|d| crate::dir_size(d))
+                .sum(),
+            cache_paths: cache_dirs
+                .iter()
+                .filter(|d| d.exists())
+                .map(|d| d.to_string_lossy().to_string())
+                .collect(),
+        }
+    })
+    .await
+    .map_err(|e| format!("measure webview storage failed: {}", e))
+}
+
+// 清理 WebView 缓存，修复升级后旧前端资源被缓存导致的白屏/样式错乱
+// include_site_data=true 时连 localStorage 一起清空（会丢失前端配置，需前端二次确认）
+#[tauri::command]
+pub async fn clear_webview_cache(
+    app: tauri::AppHandle,
+    include_site_data: Option<bool>,
+) -> Result<ClearWebviewCacheResult, String> {
+    let log_state = app.state::<LogState>().inner().clone();
+    let window = app.get_webview_window("main");
+
+    if include_site_data.unwrap_or(false) {
+        if let Some(window) = window.as_ref() {
+            window
+                .clear_all_browsing_data()
+                .map_err(|e| format!("clear browsing data failed: {}", e))?;
+        }
+    }
+
+    let app_for_task = app.clone();
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        let mut freed_bytes = 0;
+        let mut failed_paths = Vec::new();
+        for dir in webview_cache_dirs(&app_for_task) {
+            if !dir.exists() {
+                continue;
+            }
+            let before = crate::dir_size(&dir);
+            // Windows 上 WebView2 运行中会锁住部分缓存文件，删不掉的留到下次启动
+            if std::fs::remove_dir_all(&dir).is_err() {
+                failed_paths.push(dir.to_string_lossy().to_string());
+            }
+            freed_bytes += before.saturating_sub(crate::dir_size(&dir));
+        }
+        ClearWebviewCacheResult {
+            freed_bytes,
+            failed_paths,
+        }
+    })
+    .await
+    .map_err(|e| format!("clear webview cache failed: {}", e))?;
+
+    log_state.log_app(
+        "INFO",
+        &format!(
+            "Webview cache cleared freed_bytes={} failed={}",
+            result.freed_bytes,
+            result.failed_paths.len()
+        ),
+    );
+
+    if let Some(window) = window {
+        let _ = window.reload();
+    }
+    Ok(result)
+}
+
+// 启动参数带 --debug 时放开调试器，便于用户按支持人员指引复现问题
+pub struct DebugFlag(pub bool);
+
+impl DebugFlag {
+    pub fn from_args() -> Self {
+        Self(std::env::args().any(|arg| arg == "--debug"))
+    }
+}
+
+// 未编入 devtools feature 的发布包（Mac App Store）始终不可用
+pub fn devtools_allowed(app: &tauri::AppHandle) -> bool {
+    if cfg!(debug_assertions) {
+        return true;
+    }
+    cfg!(feature = "devtools")
+        && (app.state::<DebugFlag>().0 || app.state::<SettingsState>().get().devtools_enabled)
+}
+
+// 打开/关闭 WebView 调试器，返回切换后的状态
+#[cfg(any(debug_assertions, feature = "devtools"))]
+#[tauri::command]
+pub fn toggle_devtools(app: tauri::AppHandle) -> Result<bool, String> {
+    if !devtools_allowed(&app) {
+        return Err("devtools disabled".to_string());
+    }
+    let window = app
+        .get_webview_window("main")
+        .ok_or_else(|| "main window not found".to_string())?;
+    if window.is_devtools_open() {
+        window.close_devtools();
+        Ok(false)
+    } else {
+        window.open_devtools();
+        Ok(true)
+    }
+}
+
+#[cfg(not(any(debug_assertions, feature = "devtools")))]
+#[tauri::command]
+pub fn toggle_devtools(_app: tauri::AppHandle) -> Result<bool, String> {
+    Err("devtools not available in this build".to_string())
+}
+
+// 隐藏开关会放开调试器和代理故障注入，页面脚本不能自行打开：只有以 --debug 启动时才允许开启，关闭随时可以
+#[tauri::command]
+pub fn set_devtools_enabled(
+    app: tauri::AppHandle,
+    state: State<'_, SettingsState>,
+    enabled: bool,
+) -> Result<(), String> {
+    if enabled && !app.state::<DebugFlag>().0 {
+        return Err("devtools can only be enabled when started with --debug".to_string());
+    }
+    state.update(|s| s.devtools_enabled = enabled).map(|_| ())
+}
@@ -4,7 +4,9 @@ import { ToastContainer } from './components/common/Toast';
 import { UpdaterModal } from './components/common/UpdaterModal';
 import { OnboardingTour } from './components/Onboarding/OnboardingTour';
 import i18n, { changeAppLanguage } from './i18n';
+import { useDiskGuard } from './hooks/useDiskGuard';
 import { useGenerationNotifications } from './hooks/useGenerationNotifications';
+import { useWebviewHeartbeat } from './hooks/useWebviewHeartbeat';
 import { useConfigStore } from './store/configStore';
 import { useGenerateStore } from './store/generateStore';
 
@@ -14,6 +16,8 @@ function App() {
   const generateStatus = useGenerateStore((s) => s.status);
   const isSubmitting = useGenerateStore((s) => s.isSubmitting);
   useGenerationNotifications();
+  useWebviewHeartbeat();
+  useDiskGuard();
 
   useEffect(() => {
     if (!language) return;
@@ -102,8 +102,9 @@ export function useReferenceImagePaste({
 
     try {
       const { invoke } = await import('@tauri-apps/api/core');
-      const path = await invoke<string | null>('read_image_from_clipboard');
-      const imagePath = (path || '').trim();
+      // 按内容命名：同一张截图重复粘贴得到同一路径，下面的去重就能生效
+      const pasted = await invoke<{ path: string } | null>('paste_image_from_clipboard');
+      const imagePath = (pasted?.path || '').trim();
       if (!imagePath) return; // 剪贴板里没有图片：静默忽略
 
       const md5Key = buildPathMd5(imagePath);
@@ -121,7 +122,7 @@ export function useReferenceImagePaste({
       toast.success(t('refImage.toast.addedOne'));
     } catch (err) {
       // 原生读取失败：静默忽略，避免影响正常文本粘贴体验
-      console.warn('[ReferenceImageUpload] read_image_from_clipboard failed:', err);
+      console.warn('[ReferenceImageUpload] paste_image_from_clipboard failed:', err);
     }
   }, [addRefFiles, buildPathMd5, fileMd5SetRef, isExpanded, refFilesLength, setIsExpanded, t]);
 
@@ -9,6 +9,7 @@ import { useHistoryStore } from '../../store/historyStore';
 import { toast } from '../../store/toastStore';
 import { useTranslation } from 'react-i18next';
 import { localizeErrorSummary } from '../../utils/errorI18n';
+import { copyImageToClipboard } from '../../utils/nativeClipboard';
 
 interface ImagePreviewProps {
     image: (GeneratedImage & { model?: string }) | null;
@@ -360,10 +361,9 @@ export const ImagePreview = React.memo(function ImagePreview({
             // Tauri 打包环境下，Web Clipboard API 可能不可用/不稳定：优先走原生剪贴板写入
             if (isTauri) {
                 try {
-                    const { invoke } = await import('@tauri-apps/api/core');
                     const localPath = await resolveLocalImagePath([image.filePath, image.thumbnailPath]);
                     if (localPath) {
-                        await invoke('copy_image_to_clipboard', { path: localPath });
+                        await copyImageToClipboard(localPath);
                         toast.success(t('toast.copyImageSuccess'));
                         return;
                     }
@@ -662,10 +662,22 @@ export const ImagePreview = React.memo(function ImagePreview({
 
         try {
             if (window.__TAURI_INTERNALS__) {
+                const { invoke } = await import('@tauri-apps/api/core');
+
+                // 本地有原图时走原生“另存为”，可在对话框里选择 PNG / JPEG / WebP
+                const localPath = await resolveLocalImagePath([image.filePath]);
+                if (localPath) {
+                    const saved = await invoke<{ dest_path: string } | null>('save_image_as', {
+                        path: localPath,
+                        options: { file_name: `image-${image.id}` },
+                    });
+                    if (saved) toast.success(t('toast.downloadSuccess'));
+                    return;
+                }
+
                 await ensureBackendReady();
 
                 const { save } = await import('@tauri-apps/plugin-dialog');
-                const { invoke } = await import('@tauri-apps/api/core');
 
                 const inferExtension = () => {
                     const candidates = [
@@ -709,7 +721,7 @@ export const ImagePreview = React.memo(function ImagePreview({
             console.error('Download failed:', error);
             toast.error(t('toast.downloadFailed'));
         }
-    }, [image, t]);
+    }, [image, resolveLocalImagePath, t]);
 
     if (!image) return null;
 
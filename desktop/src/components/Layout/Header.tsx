@@ -1,13 +1,14 @@
-import React, { useState } from 'react';
+import React, { useEffect, useState } from 'react';
 import { LayoutGrid, History, Settings } from 'lucide-react';
 import { cn } from '../common/Button';
-import { SettingsModal } from '../Settings/SettingsModal';
+import { SettingsModal, type SettingsTab } from '../Settings/SettingsModal';
 import { useGenerateStore } from '../../store/generateStore';
 import { useTranslation } from 'react-i18next';
 
 export function Header() {
   const { t } = useTranslation();
   const [isSettingsOpen, setIsSettingsOpen] = useState(false);
+  const [settingsTab, setSettingsTab] = useState<SettingsTab | undefined>(undefined);
   const currentTab = useGenerateStore((s) => s.currentTab);
   const setTab = useGenerateStore((s) => s.setTab);
 
@@ -15,6 +16,17 @@ export function Header() {
       setTab(tab);
   };
 
+  // 其他模块通过 settings:open 事件打开设置并定位到指定页签
+  useEffect(() => {
+    const handler = (event: Event) => {
+      const tab = (event as CustomEvent<{ tab?: SettingsTab }>).detail?.tab;
+      setSettingsTab(tab);
+      setIsSettingsOpen(true);
+    };
+    window.addEventListener('settings:open', handler);
+    return () => window.removeEventListener('settings:open', handler);
+  }, []);
+
   return (
     <header 
       className="h-16 flex items-center justify-between px-4 sm:px-8 z-50 sticky top-0 bg-[#f8fafc]/50 backdrop-blur-md pt-2 relative"
@@ -67,7 +79,10 @@ export function Header() {
       <div className="w-[40px] sm:w-[140px] flex justify-end relative z-10">
           <button
             data-onboarding="settings-button"
-            onClick={() => setIsSettingsOpen(true)}
+            onClick={() => {
+              setSettingsTab(undefined);
+              setIsSettingsOpen(true);
+            }}
             className="p-2.5 text-slate-500 hover:text-blue-600 hover:bg-white rounded-xl transition-all duration-300 shadow-none hover:shadow-sm"
             title={t('nav.settings')}
             style={{ WebkitAppRegion: 'no-drag' } as any}
@@ -78,6 +93,7 @@ export function Header() {
 
       <SettingsModal
         isOpen={isSettingsOpen}
+        initialTab={settingsTab}
         onClose={() => setIsSettingsOpen(false)}
       />
     </header>
@@ -0,0 +1,139 @@
+import React, { useEffect, useRef, useState } from 'react';
+import { ClipboardPaste, Loader2, Send, X } from 'lucide-react';
+import { invoke, convertFileSrc } from '@tauri-apps/api/core';
+import { useTranslation } from 'react-i18next';
+
+type QuickReference = {
+  id: string;
+  path: string;
+};
+
+// 菜单栏快速生成小窗：只有提示词和粘贴参考图，服务商/模型沿用主窗口最近一次生成
+export function QuickCaptureWindow() {
+  const { t } = useTranslation();
+  const [prompt, setPrompt] = useState('');
+  const [references, setReferences] = useState<QuickReference[]>([]);
+  const [submitting, setSubmitting] = useState(false);
+  const [error, setError] = useState('');
+  const inputRef = useRef<HTMLTextAreaElement>(null);
+
+  useEffect(() => {
+    const onFocus = () => inputRef.current?.focus();
+    window.addEventListener('focus', onFocus);
+    onFocus();
+    return () => window.removeEventListener('focus', onFocus);
+  }, []);
+
+  const hide = () => {
+    void invoke('hide_quick_capture');
+  };
+
+  // 剪贴板图片由壳层读取并存入参考图库，这里只保留 id 和预览路径
+  const pasteImage = async () => {
+    try {
+      const reference = await invoke<QuickReference | null>('add_reference_from_clipboard', { tags: [] });
+      if (!reference) {
+        setError(t('quickCapture.noImage'));
+        return;
+      }
+      setError('');
+      setReferences((prev) => (prev.some((r) => r.id === reference.id) ? prev : [...prev, reference]));
+    } catch (err) {
+      setError(String(err));
+    }
+  };
+
+  const submit = async () => {
+    if (submitting) return;
+    if (!prompt.trim()) {
+      setError(t('prompt.toast.empty'));
+      return;
+    }
+    setSubmitting(true);
+    setError('');
+    try {
+      await invoke('submit_quick_capture', {
+        prompt,
+        referenceIds: references.map((r) => r.id),
+      });
+      setPrompt('');
+      setReferences([]);
+    } catch (err) {
+      setError(String(err));
+    } finally {
+      setSubmitting(false);
+    }
+  };
+
+  const handlePaste = (e: React.ClipboardEvent<HTMLTextAreaElement>) => {
+    const hasImage = Array.from(e.clipboardData.items).some((item) => item.type.startsWith('image/'));
+    if (!hasImage) return;
+    e.preventDefault();
+    void pasteImage();
+  };
+
+  const handleKeyDown = (e: React.KeyboardEvent<HTMLTextAreaElement>) => {
+    if (e.key === 'Escape') {
+      hide();
+    } else if (e.key === 'Enter' && (e.metaKey || e.ctrlKey)) {
+      e.preventDefault();
+      void submit();
+    }
+  };
+
+  return (
+    <div className="flex h-screen flex-col gap-2 rounded-xl bg-white/95 p-3 text-sm text-gray-800 shadow-xl">
+      <div className="flex items-center justify-between" data-tauri-drag-region>
+        <span className="font-medium">{t('quickCapture.title')}</span>
+        <button type="button" onClick={hide} className="rounded p-1 text-gray-400 hover:bg-gray-100 hover:text-gray-600">
+          <X className="h-4 w-4" />
+        </button>
+      </div>
+      <textarea
+        ref={inputRef}
+        value={prompt}
+        onChange={(e) => setPrompt(e.target.value)}
+        onPaste={handlePaste}
+        onKeyDown={handleKeyDown}
+        placeholder={t('prompt.placeholder')}
+        className="min-h-0 flex-1 resize-none rounded-lg border border-gray-200 p-2 outline-none focus:border-yellow-400"
+      />
+      {references.length > 0 && (
+        <div className="flex gap-1.5 overflow-x-auto">
+          {references.map((r) => (
+            <div key={r.id} className="relative h-12 w-12 shrink-0">
+              <img src={convertFileSrc(r.path)} className="h-full w-full rounded object-cover" />
+              <button
+                type="button"
+                onClick={() => setReferences((prev) => prev.filter((x) => x.id !== r.id))}
+                className="absolute -right-1 -top-1 rounded-full bg-gray-700 p-0.5 text-white"
+              >
+                <X className="h-2.5 w-2.5" />
+              </button>
+            </div>
+          ))}
+        </div>
+      )}
+      {error && <div className="truncate text-xs text-red-500" title={error}>{error}</div>}
+      <div className="flex items-center justify-between">
+        <button
+          type="button"
+          onClick={() => void pasteImage()}
+          className="flex items-center gap-1 rounded-lg px-2 py-1 text-gray-500 hover:bg-gray-100"
+        >
+          <ClipboardPaste className="h-4 w-4" />
+          {t('quickCapture.paste')}
+        </button>
+        <button
+          type="button"
+          onClick={() => void submit()}
+          disabled={submitting}
+          className="flex items-center gap-1 rounded-lg bg-yellow-400 px-3 py-1 font-medium text-gray-900 hover:bg-yellow-500 disabled:opacity-60"
+        >
+          {submitting ? <Loader2 className="h-4 w-4 animate-spin" /> : <Send className="h-4 w-4" />}
+          {t('quickCapture.submit')}
+        </button>
+      </div>
+    </div>
+  );
+}
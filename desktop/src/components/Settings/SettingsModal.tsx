@@ -1,5 +1,5 @@
 import React, { useState, useEffect, useMemo } from 'react';
-import { Eye, EyeOff, Key, Globe, Box, Save, Loader2, FileText, FolderOpen, Copy, RefreshCw, Languages, MessageSquare, Github, ScanEye, HelpCircle, Image as ImageIcon, Bell } from 'lucide-react';
+import { Eye, EyeOff, Key, Globe, Box, Save, Loader2, FileText, FolderOpen, Copy, RefreshCw, Languages, MessageSquare, Github, ScanEye, HelpCircle, Image as ImageIcon, Bell, HardDrive } from 'lucide-react';
 import { useTranslation } from 'react-i18next';
 import { useShallow } from 'zustand/react/shallow';
 import { useConfigStore } from '../../store/configStore';
@@ -19,6 +19,7 @@ import { getDefaultImageModelForProvider, getImageModelOptions, VISION_MODEL_OPT
 import { getPromptOptimizeConfigIssue } from '../../utils/promptOptimizeConfig';
 import { ensureNotificationPermission, sendTestSystemNotification } from '../../hooks/useGenerationNotifications';
 import { ProviderConnectionFields } from './ProviderConnectionFields';
+import { StorageStatsPanel } from './StorageStatsPanel';
 
 const CHAT_PROVIDER_OPTIONS = [
   { value: 'gemini-chat', label: 'Gemini(/v1beta)', defaultBase: 'https://generativelanguage.googleapis.com' },
@@ -26,11 +27,13 @@ const CHAT_PROVIDER_OPTIONS = [
 ];
 const DEFAULT_CHAT_PROVIDER = 'openai-chat';
 
-type SettingsTab = 'language' | 'image' | 'vision' | 'chat' | 'notifications' | 'update' | 'logs';
+export type SettingsTab = 'language' | 'image' | 'vision' | 'chat' | 'notifications' | 'storage' | 'update' | 'logs';
 
 interface SettingsModalProps {
   isOpen: boolean;
   onClose: () => void;
+  // 打开时直接定位到的页签，例如磁盘保护弹窗跳转到「存储空间」
+  initialTab?: SettingsTab;
 }
 
 const getDefaultModelId = (models?: string): string => {
@@ -148,7 +151,7 @@ const BaseUrlWarning = ({ yunwuWarn }: { yunwuWarn: ReturnType<typeof hasYunwuUr
   return <p className="text-xs text-amber-600 px-1">{message}</p>;
 };
 
-export function SettingsModal({ isOpen, onClose }: SettingsModalProps) {
+export function SettingsModal({ isOpen, onClose, initialTab }: SettingsModalProps) {
   const { t } = useTranslation();
   const {
     imageProvider, setImageProvider,
@@ -244,6 +247,9 @@ export function SettingsModal({ isOpen, onClose }: SettingsModalProps) {
   );
 
   const [activeTab, setActiveTab] = useState<SettingsTab>('image');
+  useEffect(() => {
+    if (isOpen && initialTab) setActiveTab(initialTab);
+  }, [isOpen, initialTab]);
   const [showImageKey, setShowImageKey] = useState(false);
   const [showVisionKey, setShowVisionKey] = useState(false);
   const [showChatKey, setShowChatKey] = useState(false);
@@ -908,6 +914,7 @@ export function SettingsModal({ isOpen, onClose }: SettingsModalProps) {
     { id: 'vision' as const, label: t('settings.tabs.vision'), icon: ScanEye },
     { id: 'chat' as const, label: t('settings.tabs.chat'), icon: MessageSquare },
     { id: 'notifications' as const, label: t('settings.tabs.notifications'), icon: Bell },
+    { id: 'storage' as const, label: t('settings.tabs.storage'), icon: HardDrive },
     { id: 'update' as const, label: t('settings.update.title'), icon: RefreshCw },
     { id: 'logs' as const, label: t('settings.logs.title'), icon: FileText }
   ];
@@ -1548,6 +1555,8 @@ export function SettingsModal({ isOpen, onClose }: SettingsModalProps) {
                 </>
               )}
 
+              {activeTab === 'storage' && <StorageStatsPanel />}
+
               {activeTab === 'update' && (
                 <div className="space-y-3">
                   <div className="flex items-center gap-3 rounded-2xl bg-white/70 border border-slate-200/60 p-3">
@@ -0,0 +1,139 @@
+import React, { useCallback, useEffect, useState } from 'react';
+import { HardDrive, FolderOpen, RefreshCw, Loader2, AlertTriangle } from 'lucide-react';
+import { useTranslation } from 'react-i18next';
+import { Button } from '../common/Button';
+import { toast } from '../../store/toastStore';
+import type { DiskGuardStatus, DiskStatus } from '../../hooks/useDiskGuard';
+
+type DiskGuardSettings = {
+  enabled: boolean;
+  min_free_mb: number;
+};
+
+const isTauriRuntime = () => typeof window !== 'undefined' && Boolean((window as any).__TAURI_INTERNALS__);
+
+const formatBytes = (bytes: number) => {
+  const gb = bytes / 1024 / 1024 / 1024;
+  if (gb >= 1) return `${gb.toFixed(1)} GB`;
+  return `${Math.floor(bytes / 1024 / 1024)} MB`;
+};
+
+const LEVEL_STYLES: Record<DiskStatus['level'], { bar: string; text: string }> = {
+  ok: { bar: 'bg-blue-500', text: 'text-emerald-600' },
+  warning: { bar: 'bg-amber-500', text: 'text-amber-600' },
+  critical: { bar: 'bg-red-500', text: 'text-red-600' }
+};
+
+// 设置页「存储空间」：各卷剩余空间与磁盘保护状态，磁盘保护弹窗的“释放空间”会打开这里
+export function StorageStatsPanel() {
+  const { t } = useTranslation();
+  const [volumes, setVolumes] = useState<DiskStatus[]>([]);
+  const [guard, setGuard] = useState<DiskGuardStatus | null>(null);
+  const [guardSettings, setGuardSettings] = useState<DiskGuardSettings | null>(null);
+  const [loading, setLoading] = useState(false);
+
+  const load = useCallback(async () => {
+    if (!isTauriRuntime()) return;
+    setLoading(true);
+    try {
+      const { invoke } = await import('@tauri-apps/api/core');
+      const [nextVolumes, nextGuard, nextSettings] = await Promise.all([
+        invoke<DiskStatus[]>('get_disk_space'),
+        invoke<DiskGuardStatus>('get_disk_guard_status'),
+        invoke<DiskGuardSettings>('get_disk_guard_settings')
+      ]);
+      setVolumes(nextVolumes);
+      setGuard(nextGuard);
+      setGuardSettings(nextSettings);
+    } catch (error) {
+      console.warn('[storage] failed to query disk space:', error);
+      toast.error(t('settings.storage.loadFailed'));
+    } finally {
+      setLoading(false);
+    }
+  }, [t]);
+
+  useEffect(() => {
+    void load();
+  }, [load]);
+
+  const handleOpenDir = async (path: string) => {
+    try {
+      // opener:default 已放行 reveal，不需要额外的路径 scope
+      const { revealItemInDir } = await import('@tauri-apps/plugin-opener');
+      await revealItemInDir(path);
+    } catch (error) {
+      console.warn('[storage] failed to open folder:', error);
+      toast.error(t('settings.storage.openDirFailed'));
+    }
+  };
+
+  if (!isTauriRuntime()) {
+    return <p className="text-xs text-slate-500 leading-relaxed px-1">{t('settings.storage.desktopOnly')}</p>;
+  }
+
+  return (
+    <div className="space-y-3">
+      <div className="flex items-center justify-between gap-2 px-1">
+        <label className="text-[13px] font-bold text-slate-700 uppercase tracking-wide flex items-center gap-2">
+          <HardDrive className="w-4 h-4 text-blue-600" />
+          {t('settings.storage.title')}
+        </label>
+        <Button type="button" variant="ghost" size="sm" onClick={() => void load()} disabled={loading} className="rounded-2xl">
+          {loading ? <Loader2 className="w-4 h-4 mr-1.5 animate-spin" /> : <RefreshCw className="w-4 h-4 mr-1.5" />}
+          {t('settings.storage.refresh')}
+        </Button>
+      </div>
+
+      {guard?.paused && (
+        <div className="flex items-start gap-2 rounded-2xl bg-red-50 border border-red-200/70 p-3 text-xs text-red-700 leading-relaxed">
+          <AlertTriangle className="w-4 h-4 shrink-0 mt-0.5" />
+          <span>{t('settings.storage.guardPaused', { floor: formatBytes(guard.floor_bytes) })}</span>
+        </div>
+      )}
+
+      {volumes.map((volume) => {
+        const used = volume.total_bytes > 0 ? 1 - volume.free_bytes / volume.total_bytes : 0;
+        const style = LEVEL_STYLES[volume.level] || LEVEL_STYLES.ok;
+        return (
+          <div key={volume.kind} className="rounded-2xl bg-white/70 border border-slate-200/60 p-3 space-y-2">
+            <div className="flex items-center justify-between gap-2">
+              <span className="text-sm font-bold text-slate-900">
+                {volume.kind === 'storage' ? t('settings.storage.volumeStorage') : t('settings.storage.volumeTemp')}
+              </span>
+              <span className={`text-xs font-semibold ${style.text}`}>{t(`settings.storage.level.${volume.level}`)}</span>
+            </div>
+            <div className="h-2 rounded-full bg-slate-100 overflow-hidden">
+              <div className={`h-full ${style.bar}`} style={{ width: `${Math.min(100, Math.max(0, used * 100)).toFixed(1)}%` }} />
+            </div>
+            <div className="flex items-center justify-between gap-2 text-xs text-slate-500">
+              <span>
+                {t('settings.storage.usage', { free: formatBytes(volume.free_bytes), total: formatBytes(volume.total_bytes) })}
+              </span>
+              {volume.kind === 'storage' && (
+                <button
+                  type="button"
+                  onClick={() => void handleOpenDir(volume.path)}
+                  className="inline-flex items-center gap-1 text-blue-600 hover:text-blue-700 hover:underline underline-offset-2"
+                >
+                  <FolderOpen className="w-3.5 h-3.5" />
+                  {t('settings.storage.openDir')}
+                </button>
+              )}
+            </div>
+            <p className="text-[11px] text-slate-400 font-mono truncate" title={volume.path}>{volume.path}</p>
+          </div>
+        );
+      })}
+
+      {guardSettings && (
+        <p className="text-xs text-slate-500 leading-relaxed px-1">
+          {guardSettings.enabled
+            ? t('settings.storage.guardEnabled', { floor: formatBytes(guardSettings.min_free_mb * 1024 * 1024) })
+            : t('settings.storage.guardDisabled')}
+        </p>
+      )}
+      <p className="text-xs text-slate-500 leading-relaxed px-1">{t('settings.storage.help')}</p>
+    </div>
+  );
+}
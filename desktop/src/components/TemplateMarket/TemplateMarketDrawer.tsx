@@ -34,6 +34,7 @@ import { toast } from '../../store/toastStore';
 import { getTemplateImageProxyUrl, getTemplates } from '../../services/templateApi';
 import { TemplateItem, TemplateListResponse, TemplateMeta, TemplateSource } from '../../types';
 import { cacheImageResponse, getCachedImageUrl } from '../../utils/imageCache';
+import { copyImageToClipboard } from '../../utils/nativeClipboard';
 import { useShallow } from 'zustand/react/shallow';
 import {
   templateChannels,
@@ -407,7 +408,6 @@ const TemplatePreviewModal = ({
       const isTauri = typeof window !== 'undefined' && Boolean(window.__TAURI_INTERNALS__);
       if (isTauri) {
         try {
-          const { invoke } = await import('@tauri-apps/api/core');
           const { writeFile, mkdir, BaseDirectory } = await import('@tauri-apps/plugin-fs');
           const dir = 'template_clipboard';
           await mkdir(dir, { recursive: true, baseDir: BaseDirectory.AppData });
@@ -416,7 +416,7 @@ const TemplatePreviewModal = ({
           const relativePath = `${dir}/${hash}.${ext}`;
           const bytes = new Uint8Array(await blob.arrayBuffer());
           await writeFile(relativePath, bytes, { baseDir: BaseDirectory.AppData });
-          await invoke('copy_image_to_clipboard', { path: relativePath });
+          await copyImageToClipboard(relativePath);
           toast.success(t('toast.copyImageSuccess'));
           return;
         } catch (err) {
@@ -0,0 +1,68 @@
+import { useEffect } from 'react';
+import { listen } from '@tauri-apps/api/event';
+import { toast } from '../store/toastStore';
+import i18n from '../i18n';
+
+export type DiskGuardStatus = {
+  paused: boolean;
+  free_bytes: number | null;
+  floor_bytes: number;
+};
+
+export type DiskStatus = {
+  kind: string;
+  path: string;
+  free_bytes: number;
+  total_bytes: number;
+  level: 'ok' | 'warning' | 'critical';
+};
+
+const toMb = (bytes: number | null | undefined) => Math.floor((bytes || 0) / 1024 / 1024);
+
+const isTauriRuntime = () => typeof window !== 'undefined' && Boolean((window as any).__TAURI_INTERNALS__);
+
+// 壳层磁盘保护：空间不足时暂停生成并提示，“释放空间”按钮会发出 open-storage-stats，
+// 这里转成 settings:open 事件，由 Header 打开设置页的「存储空间」
+export function useDiskGuard() {
+  useEffect(() => {
+    if (!isTauriRuntime()) return;
+
+    const unlisteners: Array<Promise<() => void>> = [
+      listen<DiskGuardStatus>('generation-paused', (event) => {
+        toast.warning(i18n.t('generate.toast.diskPaused', { free: toMb(event.payload?.free_bytes) }));
+      }),
+      listen<DiskGuardStatus>('generation-resumed', () => {
+        toast.info(i18n.t('generate.toast.diskResumed'));
+      }),
+      listen('open-storage-stats', () => {
+        window.dispatchEvent(new CustomEvent('settings:open', { detail: { tab: 'storage' } }));
+      }),
+    ];
+
+    return () => {
+      unlisteners.forEach((p) => {
+        p.then((fn) => fn()).catch(() => {});
+      });
+    };
+  }, []);
+}
+
+// 提交生成前检查：磁盘保护已触发时返回 false，并提示用户
+export async function checkGenerationAllowed(): Promise<boolean> {
+  if (!isTauriRuntime()) return true;
+  try {
+    const { invoke } = await import('@tauri-apps/api/core');
+    await invoke('check_generation_allowed');
+    return true;
+  } catch (error) {
+    console.warn('[disk guard] generation blocked:', error);
+    try {
+      const { invoke } = await import('@tauri-apps/api/core');
+      const status = await invoke<DiskGuardStatus>('get_disk_guard_status');
+      toast.error(i18n.t('generate.toast.diskPaused', { free: toMb(status.free_bytes) }));
+    } catch {
+      toast.error(i18n.t('generate.toast.diskPaused', { free: 0 }));
+    }
+    return false;
+  }
+}
@@ -10,6 +10,7 @@ import { useHistoryStore } from '../store/historyStore';
 import i18n from '../i18n';
 import { getDiagnosticVerbose } from '../utils/diagnosticLogger';
 import { getPromptOptimizeConfigIssue } from '../utils/promptOptimizeConfig';
+import { checkGenerationAllowed } from './useDiskGuard';
 import { isQualityControlSupported, isReferenceImageSupported, isUsingNativeImageSize } from '../store/configStore';
 
 // 流式连接建立超时时间（毫秒）- 超过此时间未建立连接则启动轮询
@@ -387,6 +388,9 @@ export function useGenerate() {
       toast.error(i18n.t('settings.toast.openaiGeminiUnsupported'));
       return;
     }
+    if (!(await checkGenerationAllowed())) {
+      return;
+    }
 
     resetPromptHistory(config.prompt);
 
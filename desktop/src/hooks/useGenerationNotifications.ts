@@ -82,6 +82,18 @@ const getTaskNotificationPayload = (
   };
 };
 
+// Windows 上由壳层发出带输入框的通知，可直接输入提示词继续生成；其他平台返回 false，走普通通知
+const sendTaskNotification = async (title: string, body: string, taskId?: string) => {
+  const { invoke } = await import('@tauri-apps/api/core');
+  const sent = await invoke<boolean>('send_action_notification', { title, body, taskId }).catch((error) => {
+    console.warn('[notification] action notification failed', error);
+    return false;
+  });
+  if (sent) return;
+  const { sendNotification } = await import('@tauri-apps/plugin-notification');
+  await sendNotification({ title, body });
+};
+
 export async function ensureNotificationPermission(
   t: (key: string, params?: Record<string, unknown>) => string,
   options?: { showDeniedToast?: boolean; source?: 'startup' | 'settings' | 'test' | 'task' }
@@ -235,7 +247,6 @@ export function useGenerationNotifications() {
           }
         }
 
-        const { sendNotification } = await import('@tauri-apps/plugin-notification');
         const permissionGranted = await ensureNotificationPermission(t, {
           showDeniedToast: false,
           source: 'task'
@@ -281,10 +292,7 @@ export function useGenerationNotifications() {
           if (!tasks?.length) continue;
 
           const payload = getTaskNotificationPayload(tasks, t);
-          await sendNotification({
-            title: payload.title,
-            body: payload.body
-          });
+          await sendTaskNotification(payload.title, payload.body, tasks.length === 1 ? tasks[0].id : undefined);
           console.info('[notification] task notification sent', {
             taskIds: tasks.map((task) => task.id),
             status,
@@ -0,0 +1,32 @@
+import { useEffect } from 'react';
+
+const HEARTBEAT_INTERVAL_MS = 2000;
+
+const isTauriRuntime = () => typeof window !== 'undefined' && Boolean((window as any).__TAURI_INTERNALS__);
+
+// 定时向壳层发送心跳；渲染进程卡死时心跳中断，壳层会弹出“重新加载界面 / 重启应用”
+export function useWebviewHeartbeat() {
+  useEffect(() => {
+    if (!isTauriRuntime()) return;
+
+    let disposed = false;
+    let invokeFn: ((cmd: string) => Promise<unknown>) | null = null;
+
+    const beat = async () => {
+      try {
+        if (!invokeFn) {
+          const { invoke } = await import('@tauri-apps/api/core');
+          invokeFn = invoke;
+        }
+        if (!disposed) await invokeFn('webview_heartbeat');
+      } catch {}
+    };
+
+    void beat();
+    const timer = setInterval(() => void beat(), HEARTBEAT_INTERVAL_MS);
+    return () => {
+      disposed = true;
+      clearInterval(timer);
+    };
+  }, []);
+}
@@ -1,15 +1,18 @@
 import React from 'react'
 import ReactDOM from 'react-dom/client'
 import App from './App.tsx'
+import { QuickCaptureWindow } from './components/QuickCapture/QuickCaptureWindow'
 import './index.css'
 import { initDiagnosticLogger } from './utils/diagnosticLogger'
 import { initI18n } from './i18n'
 import { useConfigStore } from './store/configStore'
 
 const mountApp = () => {
+  // 菜单栏快速生成小窗与主窗口共用同一份前端，按 hash 区分
+  const isQuickCapture = window.location.hash === '#/quick-capture'
   ReactDOM.createRoot(document.getElementById('root')!).render(
     <React.StrictMode>
-      <App />
+      {isQuickCapture ? <QuickCaptureWindow /> : <App />}
     </React.StrictMode>,
   )
 
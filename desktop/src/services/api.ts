@@ -135,6 +135,11 @@ if (window.__TAURI_INTERNALS__) {
 
   initTauri();
 } else {
+  // 桌面端“在浏览器中打开”时通过 backend_port 传入 sidecar 端口
+  const browserBackendPort = Number(new URLSearchParams(window.location.search).get('backend_port'));
+  if (Number.isInteger(browserBackendPort) && browserBackendPort > 0) {
+    updateBaseUrl(browserBackendPort);
+  }
   // 非 Tauri 环境立即完成
   setTimeout(() => resolveInit?.(), 0);
 }
@@ -219,6 +219,13 @@ export const useUpdaterStore = create<UpdaterState>((set, get) => ({
     inFlightInstall = (async () => {
       set({ status: 'installing', error: null });
       try {
+        // 安装前先给 data.db 留一份快照，失败不阻塞更新
+        try {
+          const { invoke } = await import('@tauri-apps/api/core');
+          await invoke('snapshot_database', { reason: 'pre-update' });
+        } catch (err) {
+          console.warn('[updater] database snapshot failed:', err);
+        }
         await update.install();
         set({ status: 'installed' });
         toast.success(i18n.t('updater.toast.installed'));
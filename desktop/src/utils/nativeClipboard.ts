@@ -0,0 +1,56 @@
+type CopyEvent = {
+  task_id: string;
+  path: string;
+  error?: string | null;
+  cancelled: boolean;
+};
+
+// 原生剪贴板复制在后台解码，命令立即返回任务 id，结果通过事件通知；
+// 先注册监听再调用，避免任务很快完成时错过事件
+export const copyImageToClipboard = async (path: string, signal?: AbortSignal): Promise<void> => {
+  const { invoke } = await import('@tauri-apps/api/core');
+  const { listen } = await import('@tauri-apps/api/event');
+
+  let taskId: string | null = null;
+  const finished = new Map<string, CopyEvent & { ok: boolean }>();
+  let settle: ((event: CopyEvent & { ok: boolean }) => void) | null = null;
+  const onEvent = (ok: boolean) => (event: { payload: CopyEvent }) => {
+    const result = { ...event.payload, ok };
+    if (taskId === null) {
+      finished.set(result.task_id, result);
+    } else if (result.task_id === taskId) {
+      settle?.(result);
+    }
+  };
+
+  const unlistenDone = await listen<CopyEvent>('clipboard-copy-done', onEvent(true));
+  const unlistenFailed = await listen<CopyEvent>('clipboard-copy-failed', onEvent(false));
+  try {
+    const result = await new Promise<CopyEvent & { ok: boolean }>((resolve, reject) => {
+      settle = resolve;
+      invoke<string>('copy_image_to_clipboard', { path })
+        .then((id) => {
+          taskId = id;
+          const early = finished.get(id);
+          if (early) {
+            resolve(early);
+            return;
+          }
+          if (signal?.aborted) {
+            void invoke('cancel_clipboard_copy', { taskId: id });
+            return;
+          }
+          signal?.addEventListener('abort', () => void invoke('cancel_clipboard_copy', { taskId: id }), {
+            once: true,
+          });
+        })
+        .catch(reject);
+    });
+    if (!result.ok) {
+      throw new Error(result.cancelled ? 'cancelled' : result.error || 'copy image failed');
+    }
+  } finally {
+    unlistenDone();
+    unlistenFailed();
+  }
+};